@@ -0,0 +1,266 @@
+// Benchmarks for the per-request hot paths: JSON-RPC dispatch, argument
+// schema validation, fq-name lookups, rate-limit checks and enablement reads.
+//
+// Save a baseline before a performance change and compare against it after:
+//
+//     scripts/bench.sh save main
+//     scripts/bench.sh compare main
+//
+// Nothing here touches the network; tool calls use local tools or arguments
+// that are rejected before any upstream request.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use nova_mcp::mcp::dto::McpRequest;
+use nova_mcp::mcp::handler::handle_request;
+use nova_mcp::plugins::{
+    PluginContextType, PluginEnableRequest, PluginManager, PluginRegistrationRequest,
+    RequestContext,
+};
+use nova_mcp::rate_limits::RateLimitStore;
+use nova_mcp::{schema, NovaConfig, NovaServer};
+use serde_json::{json, Value};
+use std::hint::black_box;
+use std::sync::Arc;
+
+const PLUGIN_COUNTS: [u64; 2] = [100, 1_000];
+
+fn registration(name: String) -> PluginRegistrationRequest {
+    PluginRegistrationRequest {
+        name,
+        description: "bench".to_string(),
+        owner_id: None,
+        input_schema: json!({ "type": "object" }),
+        output_schema: None,
+        endpoint_url: "https://example.com/hook".to_string(),
+        script: None,
+        version: 1,
+        trust_level: Default::default(),
+        client_certificate: None,
+        credentials: None,
+        redact: Vec::new(),
+        request_template: None,
+        listing: None,
+        allowed_contexts: Vec::new(),
+        tags: Vec::new(),
+        coerce_arguments: false,
+    }
+}
+
+fn user(id: u64) -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: id.to_string(),
+        actor_id: None,
+    }
+}
+
+/// `count` plugins owned by users `0..count`, all enabled for user `count`.
+fn seeded_manager(count: u64) -> PluginManager {
+    let manager = PluginManager::in_memory().unwrap();
+    for i in 0..count {
+        let plugin = manager
+            .register_plugin(&user(i), registration("tool".to_string()))
+            .unwrap();
+        manager
+            .set_enablement(PluginEnableRequest {
+                context_type: PluginContextType::User,
+                context_id: count.to_string(),
+                plugin_id: plugin.plugin_id,
+                enable: true,
+                added_by: None,
+            })
+            .unwrap();
+    }
+    manager
+}
+
+fn request(method: &str, params: Value) -> McpRequest {
+    McpRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: method.to_string(),
+        params: Some(params),
+        context_type: Some("user".to_string()),
+        context_id: Some("42".to_string()),
+        actor_id: None,
+    }
+}
+
+fn bench_dispatch(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let server = NovaServer::new(NovaConfig::default(), Arc::new(seeded_manager(0)));
+    let cases = [
+        ("ping", json!({})),
+        ("tools/list", json!({})),
+        (
+            "tools/call",
+            json!({ "name": "get_my_usage", "arguments": {} }),
+        ),
+        // Rejected by address validation before any upstream request
+        (
+            "tools/call",
+            json!({ "name": "get_gecko_pool", "arguments": { "network": "eth", "address": "0x12" } }),
+        ),
+    ];
+    let mut group = c.benchmark_group("dispatch");
+    for (label, (method, params)) in ["ping", "tools_list", "local_tool", "rejected_call"]
+        .into_iter()
+        .zip(cases)
+    {
+        group.bench_function(label, |b| {
+            b.iter(|| {
+                let request = request(method, params.clone());
+                black_box(runtime.block_on(handle_request(&server, request, None)))
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_schema_validation(c: &mut Criterion) {
+    let input_schema = json!({
+        "type": "object",
+        "properties": {
+            "network": { "type": "string", "minLength": 1 },
+            "address": { "type": "string", "pattern": "^0x[0-9a-fA-F]{40}$" },
+            "page": { "type": "integer", "minimum": 1, "maximum": 10 },
+            "filters": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": { "name": { "type": "string" } },
+                    "required": ["name"]
+                }
+            }
+        },
+        "required": ["network", "address"]
+    });
+    let valid = json!({
+        "network": "eth",
+        "address": "0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640",
+        "page": 2,
+        "filters": [{ "name": "volume" }, { "name": "liquidity" }]
+    });
+    let invalid = json!({ "network": "", "page": 0, "filters": [{}] });
+
+    let mut group = c.benchmark_group("schema_validation");
+    // Without the cache: compile, then validate
+    for (label, arguments) in [
+        ("compile_and_validate", &valid),
+        ("compile_and_reject", &invalid),
+    ] {
+        group.bench_function(label, |b| {
+            b.iter(|| {
+                black_box(schema::validate_arguments(
+                    "bench",
+                    &input_schema,
+                    arguments,
+                ))
+            })
+        });
+    }
+    // What a call pays with the schema already in `CompiledSchemas`
+    let schemas = schema::CompiledSchemas::default();
+    schemas.prepare("bench", &input_schema).unwrap();
+    for (label, arguments) in [
+        ("precompiled_validate", &valid),
+        ("precompiled_reject", &invalid),
+    ] {
+        group.bench_function(label, |b| {
+            b.iter(|| black_box(schemas.validate("bench", &input_schema, arguments)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_fq_lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fq_lookup");
+    for count in PLUGIN_COUNTS {
+        let manager = seeded_manager(count);
+        let names: Vec<String> = (0..count).map(|i| format!("user_{}_tool_v1", i)).collect();
+        group.bench_with_input(BenchmarkId::new("hit", count), &names, |b, names| {
+            let mut i = 0;
+            b.iter(|| {
+                i = (i + 1) % names.len();
+                black_box(manager.get_plugin_by_fq_name(&names[i]).ok())
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("miss", count), &count, |b, _| {
+            b.iter(|| black_box(manager.get_plugin_by_fq_name("user_x_missing_v1").ok()))
+        });
+    }
+    group.finish();
+}
+
+fn bench_rate_limits(c: &mut Criterion) {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let stores = [
+        ("memory", RateLimitStore::in_memory()),
+        (
+            "sled",
+            RateLimitStore::persistent(db.open_tree("rate_limits").unwrap()),
+        ),
+    ];
+    let keys: Vec<String> = (0..1_000).map(|i| format!("ctx:user:{}", i)).collect();
+    let mut group = c.benchmark_group("rate_limit");
+    for (label, store) in &stores {
+        group.bench_function(BenchmarkId::new("try_acquire", label), |b| {
+            let mut i = 0;
+            b.iter(|| {
+                i = (i + 1) % keys.len();
+                // Never refused, so every call writes
+                black_box(store.try_acquire(&keys[i], u32::MAX).unwrap())
+            })
+        });
+        group.bench_function(BenchmarkId::new("current", label), |b| {
+            let mut i = 0;
+            b.iter(|| {
+                i = (i + 1) % keys.len();
+                black_box(store.current(&keys[i]).unwrap())
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_enablement_reads(c: &mut Criterion) {
+    let mut group = c.benchmark_group("enablement");
+    group.sample_size(20);
+    for count in PLUGIN_COUNTS {
+        let manager = Arc::new(seeded_manager(count));
+        let member = count.to_string();
+        group.bench_with_input(
+            BenchmarkId::new("is_enabled", count),
+            &count,
+            |b, &count| {
+                let mut id = 0;
+                b.iter(|| {
+                    id = id % count + 1;
+                    black_box(
+                        manager
+                            .is_enabled(id, PluginContextType::User, &member)
+                            .unwrap(),
+                    )
+                })
+            },
+        );
+        // `tools/list` for a context with every plugin enabled
+        let server = NovaServer::new(NovaConfig::default(), Arc::clone(&manager));
+        group.bench_with_input(BenchmarkId::new("get_tools", count), &count, |b, &count| {
+            b.iter(|| black_box(server.get_tools(&user(count)).unwrap().len()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_dispatch,
+    bench_schema_validation,
+    bench_fq_lookup,
+    bench_rate_limits,
+    bench_enablement_reads
+);
+criterion_main!(benches);
@@ -0,0 +1,167 @@
+// Concurrency benchmark for the plugin registry.
+//
+// Compares fq-name lookups against the previous `RwLock<HashMap>` layout while a
+// background writer keeps registering plugins, which is the contention pattern
+// seen under load (many `tools/call` readers, occasional registrations).
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use nova_mcp::plugins::{
+    PluginContextType, PluginManager, PluginRegistrationRequest, RequestContext,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread;
+
+const SEEDED_PLUGINS: u64 = 256;
+const LOOKUPS_PER_THREAD: u64 = 2_000;
+
+fn test_manager() -> PluginManager {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    PluginManager::new(
+        db.open_tree("plugin_metadata").unwrap(),
+        db.open_tree("user_plugins").unwrap(),
+        db.open_tree("group_plugins").unwrap(),
+    )
+    .expect("init plugin manager")
+}
+
+fn registration(name: String) -> PluginRegistrationRequest {
+    PluginRegistrationRequest {
+        name,
+        description: "bench".to_string(),
+        owner_id: None,
+        input_schema: json!({ "type": "object" }),
+        output_schema: None,
+        endpoint_url: "https://example.com/hook".to_string(),
+        script: None,
+        version: 1,
+        trust_level: Default::default(),
+        client_certificate: None,
+        credentials: None,
+        redact: Vec::new(),
+        request_template: None,
+        listing: None,
+        allowed_contexts: Vec::new(),
+        tags: Vec::new(),
+        coerce_arguments: false,
+    }
+}
+
+fn context(id: u64) -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: id.to_string(),
+        actor_id: None,
+    }
+}
+
+/// Runs `readers` lookup threads alongside one writer thread until all readers finish.
+fn run_contended<R, W>(readers: usize, read: R, write: W)
+where
+    R: Fn(u64) + Send + Sync + 'static,
+    W: Fn(u64) + Send + Sync + 'static,
+{
+    let read = Arc::new(read);
+    let done = Arc::new(AtomicBool::new(false));
+    let writer = {
+        let done = Arc::clone(&done);
+        thread::spawn(move || {
+            let mut n = 0;
+            while !done.load(Ordering::Relaxed) {
+                write(n);
+                n += 1;
+            }
+        })
+    };
+    let handles: Vec<_> = (0..readers)
+        .map(|_| {
+            let read = Arc::clone(&read);
+            thread::spawn(move || {
+                for i in 0..LOOKUPS_PER_THREAD {
+                    read(i % SEEDED_PLUGINS);
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    done.store(true, Ordering::Relaxed);
+    writer.join().unwrap();
+}
+
+fn bench_lookups(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fq_lookup_under_writes");
+    group.sample_size(10);
+
+    for readers in [1usize, 4, 8] {
+        // Baseline: the previous single-lock layout.
+        let baseline: Arc<RwLock<HashMap<String, u64>>> = Arc::new(RwLock::new(
+            (0..SEEDED_PLUGINS)
+                .map(|i| (format!("user_{}_tool_v1", i), i))
+                .collect(),
+        ));
+        group.bench_with_input(
+            BenchmarkId::new("rwlock_hashmap", readers),
+            &readers,
+            |b, &readers| {
+                b.iter(|| {
+                    let reader_map = Arc::clone(&baseline);
+                    let writer_map = Arc::clone(&baseline);
+                    let next = AtomicU64::new(SEEDED_PLUGINS);
+                    run_contended(
+                        readers,
+                        move |i| {
+                            let map = reader_map.read().unwrap();
+                            std::hint::black_box(map.get(&format!("user_{}_tool_v1", i)));
+                        },
+                        move |_| {
+                            let id = next.fetch_add(1, Ordering::Relaxed);
+                            let mut map = writer_map.write().unwrap();
+                            map.insert(format!("user_{}_extra_v1", id), id);
+                        },
+                    );
+                })
+            },
+        );
+
+        let manager = Arc::new(test_manager());
+        for i in 0..SEEDED_PLUGINS {
+            manager
+                .register_plugin(&context(i), registration("tool".to_string()))
+                .unwrap();
+        }
+        let next = Arc::new(AtomicU64::new(0));
+        group.bench_with_input(
+            BenchmarkId::new("plugin_manager", readers),
+            &readers,
+            |b, &readers| {
+                b.iter(|| {
+                    let reader = Arc::clone(&manager);
+                    let writer = Arc::clone(&manager);
+                    let next = Arc::clone(&next);
+                    run_contended(
+                        readers,
+                        move |i| {
+                            let fq_name = format!("user_{}_tool_v1", i);
+                            std::hint::black_box(reader.get_plugin_by_fq_name(&fq_name).ok());
+                        },
+                        move |_| {
+                            let id = next.fetch_add(1, Ordering::Relaxed);
+                            let _ = writer.register_plugin(
+                                &context(SEEDED_PLUGINS + id),
+                                registration(format!("extra{}", id)),
+                            );
+                        },
+                    );
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_lookups);
+criterion_main!(benches);
@@ -14,6 +14,7 @@ async fn main() -> Result<()> {
     let context = RequestContext {
         context_type: PluginContextType::User,
         context_id: "0".to_string(),
+        actor_id: None,
     };
     println!("Available tools:");
     for t in server.get_tools(&context)? {
@@ -23,6 +24,9 @@ async fn main() -> Result<()> {
     let networks = ToolCall {
         name: "get_gecko_networks".into(),
         arguments: json!({}),
+        select: None,
+        format: None,
+        priority: None,
     };
     println!(
         "gecko_networks -> {:?}",
@@ -32,6 +36,9 @@ async fn main() -> Result<()> {
     let trending = ToolCall {
         name: "get_trending_pools".into(),
         arguments: json!({"network": "eth", "limit": 5}),
+        select: None,
+        format: None,
+        priority: None,
     };
     println!(
         "trending_pools -> {:?}",
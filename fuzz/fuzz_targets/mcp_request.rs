@@ -0,0 +1,35 @@
+//! One message through `parse_request` and `handle_request`.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nova_mcp::mcp::handler::{handle_request, parse_request};
+use nova_mcp::{NovaConfig, NovaServer, PluginManager};
+use std::sync::{Arc, OnceLock};
+
+fn server() -> &'static NovaServer {
+    static SERVER: OnceLock<NovaServer> = OnceLock::new();
+    SERVER.get_or_init(|| {
+        NovaServer::new(
+            NovaConfig::default(),
+            Arc::new(PluginManager::in_memory().expect("temporary registry")),
+        )
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(message) = std::str::from_utf8(data) else {
+        return;
+    };
+    let response = match parse_request(message) {
+        Ok(request) => {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            runtime.block_on(handle_request(server(), request, None))
+        }
+        Err(response) => *response,
+    };
+    assert_eq!(response.jsonrpc, "2.0");
+    assert!(response.result.is_some() != response.error.is_some());
+});
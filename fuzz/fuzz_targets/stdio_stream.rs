@@ -0,0 +1,29 @@
+//! Raw bytes through the stdio loop, with auto-detected framing.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nova_mcp::stdio::{serve, Framing};
+use nova_mcp::{NovaConfig, NovaServer, PluginManager};
+use std::sync::{Arc, OnceLock};
+
+fn server() -> &'static NovaServer {
+    static SERVER: OnceLock<NovaServer> = OnceLock::new();
+    SERVER.get_or_init(|| {
+        NovaServer::new(
+            NovaConfig::default(),
+            Arc::new(PluginManager::in_memory().expect("temporary registry")),
+        )
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let mut output = Vec::new();
+    runtime
+        .block_on(serve(server(), data, &mut output, Framing::Auto))
+        .expect("malformed input never ends the loop");
+    assert!(std::str::from_utf8(&output).is_ok());
+});
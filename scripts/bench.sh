@@ -0,0 +1,34 @@
+#!/bin/bash
+
+# Nova-MCP Benchmark Script
+#
+#   scripts/bench.sh save <name>      run the benches and store them as baseline <name>
+#   scripts/bench.sh compare <name>   run the benches and report changes against <name> (new benches are just measured)
+#
+# Extra arguments go to Criterion, e.g. a filter: scripts/bench.sh compare main fq_lookup
+# Baselines live in target/criterion/; HTML reports in target/criterion/report/.
+
+set -e
+
+MODE="$1"
+BASELINE="$2"
+shift 2 || true
+
+if [ -z "$MODE" ] || [ -z "$BASELINE" ]; then
+    echo "Usage: $0 save|compare <baseline> [criterion args...]"
+    exit 1
+fi
+
+case "$MODE" in
+    save)
+        cargo bench --bench hot_paths -- --save-baseline "$BASELINE" "$@"
+        ;;
+    compare)
+        # Lenient: benchmarks added since the baseline run without a comparison
+        cargo bench --bench hot_paths -- --baseline-lenient "$BASELINE" "$@"
+        ;;
+    *)
+        echo "Unknown mode: $MODE (expected save or compare)"
+        exit 1
+        ;;
+esac
@@ -0,0 +1,188 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::audit::AuditEntry;
+use crate::auth::LockoutStats;
+use crate::dead_letters::DeliveryKind;
+use crate::http::load::LoadStats;
+use crate::plugins::{
+    PluginContextType, PluginListing, PluginMetadata, PluginReport, RatingSummary, RegistryStats,
+};
+use crate::storage::StorageUsage;
+use crate::tools::gecko_terminal::cross_network::MappedToken;
+use crate::tools::ToolSlots;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminStats {
+    pub registry: RegistryStats,
+    pub rate_limited_contexts: usize,
+    pub active_sessions: usize,
+    pub uptime_seconds: u64,
+    /// Unset when the server was built without a database handle.
+    #[serde(default)]
+    pub storage: Option<StorageUsage>,
+    #[serde(default)]
+    pub backups: BackupArchive,
+    /// Wrong API keys seen and the lockouts they caused.
+    #[serde(default)]
+    pub auth_failures: LockoutStats,
+    /// Requests in flight and queued under `server.max_concurrent_requests`.
+    #[serde(default)]
+    pub load: LoadStats,
+    /// Slots in use for each tool in `limits.tool_concurrency`.
+    #[serde(default)]
+    pub tool_concurrency: BTreeMap<String, ToolSlots>,
+    /// Server events published since startup, by type.
+    #[serde(default)]
+    pub events: BTreeMap<String, u64>,
+}
+
+/// Snapshots written by `POST /admin/backup` that are still in `admin.backup_dir`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupArchive {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyCreateRequest {
+    pub id: String,
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicySettings {
+    pub rate_limit_per_minute: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PolicyUpdateRequest {
+    #[serde(default)]
+    pub rate_limit_per_minute: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupResponse {
+    pub path: String,
+    pub plugins: usize,
+    pub enablements: usize,
+}
+
+/// What `DELETE /contexts/:type/:id` removed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextDeletionReport {
+    pub context_type: PluginContextType,
+    pub context_id: String,
+    pub deleted_at: i64,
+    // Plugins the context owned; their enablements elsewhere go with them
+    pub plugins: Vec<u64>,
+    // The context's own enablement records
+    pub enablements: usize,
+    pub preferences: bool,
+    // OAuth clients issued for the context
+    #[serde(default)]
+    pub oauth_clients: usize,
+    // Quota counters and overrides
+    #[serde(default)]
+    pub quota_records: usize,
+    // Marketplace ratings and reports the context submitted
+    #[serde(default)]
+    pub feedback_records: usize,
+    // Async plugin calls the context started
+    #[serde(default)]
+    pub plugin_jobs: usize,
+    // Undelivered webhooks of those calls
+    #[serde(default)]
+    pub dead_letters: usize,
+    // Pools watched with `watch_whales`; their sightings go with them
+    #[serde(default)]
+    pub whale_watches: usize,
+    // Metering ledger events made by or billed to the context, kept without it
+    #[serde(default)]
+    pub usage_events: usize,
+}
+
+/// `PUT /admin/quotas/:type/:id`; both caps unset removes the override.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct QuotaOverrideRequest {
+    // Plugin fq_name; the context-wide caps when unset
+    #[serde(default)]
+    pub plugin: Option<String>,
+    #[serde(default)]
+    pub daily_calls: Option<u64>,
+    #[serde(default)]
+    pub monthly_calls: Option<u64>,
+}
+
+/// `PUT /admin/token-mappings/:mapping_id`; replaces the whole mapping.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenMappingRequest {
+    pub symbol: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub representations: Vec<MappedToken>,
+}
+
+/// `PUT /admin/marketplace/:plugin_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListingReviewRequest {
+    pub approved: bool,
+}
+
+/// `GET /admin/marketplace/:plugin_id/reports`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListingReports {
+    pub plugin_id: u64,
+    #[serde(default)]
+    pub listing: Option<PluginListing>,
+    pub ratings: RatingSummary,
+    // Oldest first, one per reporting context
+    pub reports: Vec<PluginReport>,
+}
+
+/// `GET /admin/metering/events` page size, next to the `UsageQuery` filters.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MeteringEventsQuery {
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// `GET /admin/dead-letters` query.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DeadLettersQuery {
+    #[serde(default)]
+    pub kind: Option<DeliveryKind>,
+}
+
+/// `GET /admin/plugins/stale` query.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct StalePluginsQuery {
+    #[serde(default)]
+    pub days: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StalePluginsReport {
+    pub days: u32,
+    // Registered before and not called since this unix time
+    pub cutoff: i64,
+    pub plugins: Vec<PluginMetadata>,
+}
+
+/// `GET /admin/audit` query; `since` is unix seconds.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AuditQuery {
+    #[serde(default)]
+    pub since: Option<i64>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditResponse {
+    pub entries: Vec<AuditEntry>,
+    // Whether every stored entry still hashes and links correctly
+    pub chain_valid: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub broken_at: Option<u64>,
+}
@@ -0,0 +1,856 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+
+use crate::audit::AuditEvent;
+use crate::auth::{redact, ApiKeySummary};
+use crate::dead_letters::{DeadLetter, DeliveryKind};
+use crate::http::{ApiJson, ApiPath, ApiQuery, AppState};
+use crate::jobs::JobStatus;
+use crate::metering::{UsageEvent, UsageQuery, UsageReport};
+use crate::oauth::{
+    OAuthClientCreateRequest, OAuthClientCreated, OAuthClientSummary, CLIENT_SCOPES,
+};
+use crate::plugins::helpers::map_error;
+use crate::plugins::{ErrorResponse, PluginContextType, PluginMetadata, RequestContext};
+use crate::quotas::{QuotaOverride, QuotaUsage};
+use crate::reload::ReloadSummary;
+use crate::tools::gecko_terminal::cross_network::TokenMapping;
+use crate::tools::upstream_health::UpstreamStatus;
+
+use super::dto::{
+    AdminStats, ApiKeyCreateRequest, AuditQuery, AuditResponse, BackupArchive, BackupResponse,
+    ContextDeletionReport, DeadLettersQuery, ListingReports, ListingReviewRequest,
+    MeteringEventsQuery, PolicySettings, PolicyUpdateRequest, QuotaOverrideRequest,
+    StalePluginsQuery, StalePluginsReport, TokenMappingRequest,
+};
+use super::helpers::{authorize_admin, error};
+
+type AdminResult<T> = Result<T, (StatusCode, Json<ErrorResponse>)>;
+
+/// `GET /admin/plugins/stale` window when `days` is not given.
+const DEFAULT_STALE_DAYS: u32 = 30;
+
+/// `GET /admin/metering/events` page size when `limit` is not given.
+const DEFAULT_EVENTS_LIMIT: usize = 100;
+
+pub(crate) async fn stats(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AdminResult<Json<AdminStats>> {
+    authorize_admin(&state, &headers)?;
+    let server = state.server();
+    let registry = state.plugin_manager_arc();
+    let registry = tokio::task::spawn_blocking(move || registry.stats())
+        .await
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(AdminStats {
+        registry,
+        rate_limited_contexts: state.rate_entries().await,
+        active_sessions: state.session_count(),
+        uptime_seconds: state.uptime().as_secs(),
+        storage: server.storage_usage().map_err(map_error)?,
+        backups: backup_archive(&state.config().admin.backup_dir).await,
+        auth_failures: state.lockout().stats(),
+        load: state.load().stats(),
+        tool_concurrency: server.tool_concurrency().snapshot(),
+        events: server.event_counters().snapshot(),
+    }))
+}
+
+/// Files `trigger_backup` wrote to `dir`; a missing directory holds none.
+async fn backup_archive(dir: &str) -> BackupArchive {
+    let mut archive = BackupArchive::default();
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return archive;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if !(name.starts_with("nova-backup-") && name.ends_with(".json")) {
+            continue;
+        }
+        if let Ok(metadata) = entry.metadata().await {
+            archive.files += 1;
+            archive.bytes += metadata.len();
+        }
+    }
+    archive
+}
+
+/// Recent success/error/latency figures per upstream API and plugin endpoint.
+pub(crate) async fn list_upstreams(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AdminResult<Json<Vec<UpstreamStatus>>> {
+    authorize_admin(&state, &headers)?;
+    Ok(Json(state.server().upstream_statuses()))
+}
+
+/// Schedule and last run of each background job.
+pub(crate) async fn list_jobs(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AdminResult<Json<Vec<JobStatus>>> {
+    authorize_admin(&state, &headers)?;
+    Ok(Json(state.server().jobs().snapshot()))
+}
+
+pub(crate) async fn list_keys(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AdminResult<Json<Vec<ApiKeySummary>>> {
+    authorize_admin(&state, &headers)?;
+    Ok(Json(state.auth().list_keys()))
+}
+
+pub(crate) async fn create_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiJson(request): ApiJson<ApiKeyCreateRequest>,
+) -> AdminResult<(StatusCode, Json<Vec<ApiKeySummary>>)> {
+    let who = authorize_admin(&state, &headers)?;
+    if request.id.trim().is_empty() || request.key.trim().is_empty() {
+        return Err(error(StatusCode::BAD_REQUEST, "id and key are required"));
+    }
+    if !state.auth().add_key(request.id.trim(), request.key.trim()) {
+        return Err(error(
+            StatusCode::CONFLICT,
+            "A key with this id already exists",
+        ));
+    }
+    tracing::info!("Admin added API key {}", request.id.trim());
+    state.server().audit().record_or_warn(AuditEvent {
+        who,
+        api_key: None,
+        action: "admin.key.create",
+        target: request.id.trim().to_string(),
+        before: None,
+        after: Some(serde_json::json!({ "hint": redact(request.key.trim()) })),
+    });
+    Ok((StatusCode::CREATED, Json(state.auth().list_keys())))
+}
+
+pub(crate) async fn delete_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiPath(key_id): ApiPath<String>,
+) -> AdminResult<StatusCode> {
+    let who = authorize_admin(&state, &headers)?;
+    if !state.auth().remove_key(&key_id) {
+        return Err(error(StatusCode::NOT_FOUND, "Unknown key id"));
+    }
+    tracing::info!("Admin revoked API key {}", key_id);
+    state.server().audit().record_or_warn(AuditEvent {
+        who,
+        api_key: None,
+        action: "admin.key.delete",
+        target: key_id,
+        before: None,
+        after: None,
+    });
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub(crate) async fn get_policies(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AdminResult<Json<PolicySettings>> {
+    authorize_admin(&state, &headers)?;
+    Ok(Json(PolicySettings {
+        rate_limit_per_minute: state.limit_per_minute(),
+    }))
+}
+
+pub(crate) async fn update_policies(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiJson(request): ApiJson<PolicyUpdateRequest>,
+) -> AdminResult<Json<PolicySettings>> {
+    let who = authorize_admin(&state, &headers)?;
+    if let Some(limit) = request.rate_limit_per_minute {
+        if limit == 0 {
+            return Err(error(
+                StatusCode::BAD_REQUEST,
+                "rate_limit_per_minute must be at least 1",
+            ));
+        }
+        let previous = state.limit_per_minute();
+        state.set_limit_per_minute(limit);
+        tracing::info!("Admin set rate_limit_per_minute={}", limit);
+        state.server().audit().record_or_warn(AuditEvent {
+            who,
+            api_key: None,
+            action: "admin.policies.update",
+            target: "rate_limit_per_minute".to_string(),
+            before: Some(previous.into()),
+            after: Some(limit.into()),
+        });
+    }
+    Ok(Json(PolicySettings {
+        rate_limit_per_minute: state.limit_per_minute(),
+    }))
+}
+
+pub(crate) async fn trigger_backup(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AdminResult<(StatusCode, Json<BackupResponse>)> {
+    let who = authorize_admin(&state, &headers)?;
+    let snapshot = state.plugin_manager().snapshot().map_err(map_error)?;
+    let dir = std::path::PathBuf::from(&state.config().admin.backup_dir);
+    let path = dir.join(format!("nova-backup-{}.json", snapshot.taken_at));
+    let encoded = serde_json::to_vec_pretty(&snapshot)
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    tokio::fs::write(&path, encoded)
+        .await
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    tracing::info!("Admin backup written to {}", path.display());
+    state.server().audit().record_or_warn(AuditEvent {
+        who,
+        api_key: None,
+        action: "admin.backup",
+        target: path.display().to_string(),
+        before: None,
+        after: None,
+    });
+    Ok((
+        StatusCode::CREATED,
+        Json(BackupResponse {
+            path: path.display().to_string(),
+            plugins: snapshot.plugins.len(),
+            enablements: snapshot.user_enablements.len()
+                + snapshot.group_enablements.len()
+                + snapshot.channel_enablements.len()
+                + snapshot.organization_enablements.len(),
+        }),
+    ))
+}
+
+/// Data-removal requests: drops everything stored for one context.
+pub(crate) async fn delete_context(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiPath((context_type, context_id)): ApiPath<(String, String)>,
+) -> AdminResult<Json<ContextDeletionReport>> {
+    let who = authorize_admin(&state, &headers)?;
+    let context = path_context(&state, context_type, context_id)?;
+
+    let (plugins, enablements) = state
+        .plugin_manager()
+        .purge_context(&context)
+        .map_err(map_error)?;
+    let preferences = state
+        .server()
+        .preferences()
+        .remove(&context)
+        .map_err(map_error)?;
+    let oauth_clients = state
+        .server()
+        .oauth_clients()
+        .remove_for_context(&context)
+        .map_err(map_error)?;
+    let quota_records = state
+        .server()
+        .quotas()
+        .remove(&context)
+        .map_err(map_error)?;
+    let feedback_records = state
+        .server()
+        .plugin_feedback()
+        .remove_context(&context)
+        .map_err(map_error)?;
+    let plugin_jobs = state
+        .server()
+        .plugin_jobs()
+        .remove_context(&context)
+        .map_err(map_error)?;
+    let dead_letters = state
+        .plugin_manager()
+        .dead_letters()
+        .remove_context(&context.key())
+        .map_err(map_error)?;
+    let whale_watches = state
+        .server()
+        .whale_watches()
+        .remove_context(&context)
+        .map_err(map_error)?;
+    let usage_events = match state
+        .plugin_manager()
+        .metering()
+        .and_then(|metering| metering.ledger())
+    {
+        Some(ledger) => ledger
+            .anonymize_context(&context.key())
+            .map_err(map_error)?,
+        None => 0,
+    };
+
+    tracing::info!(
+        "Admin deleted context {}: {} plugins, {} enablements, preferences {}, {} OAuth clients",
+        context.key(),
+        plugins.len(),
+        enablements,
+        preferences,
+        oauth_clients
+    );
+    let report = ContextDeletionReport {
+        context_type: context.context_type,
+        context_id: context.context_id,
+        deleted_at: state.clock().timestamp(),
+        plugins,
+        enablements,
+        preferences,
+        oauth_clients,
+        quota_records,
+        feedback_records,
+        plugin_jobs,
+        dead_letters,
+        whale_watches,
+        usage_events,
+    };
+    state.server().audit().record_or_warn(AuditEvent {
+        who,
+        api_key: None,
+        action: "context.delete",
+        target: format!("{}:{}", report.context_type, report.context_id),
+        before: None,
+        after: serde_json::to_value(&report).ok(),
+    });
+    Ok(Json(report))
+}
+
+pub(crate) async fn get_quotas(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiPath((context_type, context_id)): ApiPath<(String, String)>,
+) -> AdminResult<Json<QuotaUsage>> {
+    authorize_admin(&state, &headers)?;
+    let context = path_context(&state, context_type, context_id)?;
+    let quotas = &state.config().quotas;
+    let usage = state
+        .server()
+        .quotas()
+        .usage(&context, quotas)
+        .map_err(map_error)?;
+    Ok(Json(usage))
+}
+
+/// Grants a context caps other than `[quotas]`, overall or for one plugin.
+pub(crate) async fn update_quotas(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiPath((context_type, context_id)): ApiPath<(String, String)>,
+    ApiJson(request): ApiJson<QuotaOverrideRequest>,
+) -> AdminResult<Json<QuotaUsage>> {
+    let who = authorize_admin(&state, &headers)?;
+    let context = path_context(&state, context_type, context_id)?;
+    let granted = QuotaOverride {
+        daily_calls: request.daily_calls,
+        monthly_calls: request.monthly_calls,
+    };
+    let server = state.server();
+    let previous = server
+        .quotas()
+        .set_override(&context, request.plugin.as_deref(), granted)
+        .map_err(map_error)?;
+    let target = match &request.plugin {
+        Some(plugin) => format!("{} ({})", context.key(), plugin),
+        None => context.key(),
+    };
+    tracing::info!("Admin set quota override for {}: {:?}", target, granted);
+    server.audit().record_or_warn(AuditEvent {
+        who,
+        api_key: None,
+        action: "admin.quotas.update",
+        target,
+        before: previous.and_then(|limits| serde_json::to_value(limits).ok()),
+        after: (!granted.is_empty())
+            .then(|| serde_json::to_value(granted).ok())
+            .flatten(),
+    });
+    let usage = server
+        .quotas()
+        .usage(&context, &state.config().quotas)
+        .map_err(map_error)?;
+    Ok(Json(usage))
+}
+
+/// Curated cross-network mappings behind `find_token_across_networks`, by id.
+pub(crate) async fn list_token_mappings(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AdminResult<Json<Vec<TokenMapping>>> {
+    authorize_admin(&state, &headers)?;
+    let mappings = state.server().token_mappings().list().map_err(map_error)?;
+    Ok(Json(mappings))
+}
+
+/// Creates or replaces a mapping; 201 when it is new.
+pub(crate) async fn put_token_mapping(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiPath(mapping_id): ApiPath<String>,
+    ApiJson(request): ApiJson<TokenMappingRequest>,
+) -> AdminResult<(StatusCode, Json<TokenMapping>)> {
+    let who = authorize_admin(&state, &headers)?;
+    let mapping = TokenMapping {
+        id: mapping_id,
+        symbol: request.symbol.trim().to_string(),
+        name: request.name,
+        representations: request.representations,
+    };
+    let server = state.server();
+    let previous = server.token_mappings().put(&mapping).map_err(map_error)?;
+    tracing::info!("Admin set token mapping {}", mapping.id);
+    let status = if previous.is_some() {
+        StatusCode::OK
+    } else {
+        StatusCode::CREATED
+    };
+    server.audit().record_or_warn(AuditEvent {
+        who,
+        api_key: None,
+        action: "admin.token_mappings.update",
+        target: mapping.id.clone(),
+        before: previous.and_then(|previous| serde_json::to_value(previous).ok()),
+        after: serde_json::to_value(&mapping).ok(),
+    });
+    Ok((status, Json(mapping)))
+}
+
+pub(crate) async fn delete_token_mapping(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiPath(mapping_id): ApiPath<String>,
+) -> AdminResult<StatusCode> {
+    let who = authorize_admin(&state, &headers)?;
+    let server = state.server();
+    let removed = server
+        .token_mappings()
+        .remove(&mapping_id)
+        .map_err(map_error)?
+        .ok_or_else(|| error(StatusCode::NOT_FOUND, "Unknown token mapping"))?;
+    tracing::info!("Admin deleted token mapping {}", mapping_id);
+    server.audit().record_or_warn(AuditEvent {
+        who,
+        api_key: None,
+        action: "admin.token_mappings.delete",
+        target: mapping_id,
+        before: serde_json::to_value(removed).ok(),
+        after: None,
+    });
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The context named by a `/:context_type/:context_id` path.
+fn path_context(
+    state: &AppState,
+    context_type: String,
+    context_id: String,
+) -> AdminResult<RequestContext> {
+    let context_type = PluginContextType::parse(&context_type)
+        .ok_or_else(|| error(StatusCode::BAD_REQUEST, "Unknown context type"))?;
+    state
+        .config()
+        .context
+        .id_format()
+        .validate(&context_type, &context_id)
+        .map_err(|reason| error(StatusCode::BAD_REQUEST, reason))?;
+    Ok(RequestContext {
+        context_type,
+        context_id,
+        actor_id: None,
+    })
+}
+
+/// Ledger totals per calling context and plugin, for invoicing.
+pub(crate) async fn metering_usage(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiQuery(query): ApiQuery<UsageQuery>,
+) -> AdminResult<Json<UsageReport>> {
+    authorize_admin(&state, &headers)?;
+    let ledger = state
+        .plugin_manager()
+        .metering()
+        .and_then(|metering| metering.ledger())
+        .ok_or_else(|| error(StatusCode::NOT_FOUND, "Metering ledger is not enabled"))?;
+    let lines = ledger.summarize(&query).map_err(map_error)?;
+    Ok(Json(UsageReport {
+        since: query.since,
+        until: query.until,
+        lines,
+    }))
+}
+
+/// Individual plugin calls from the ledger: the newest `limit` matching
+/// events, oldest first, for `nova-cli logs`.
+pub(crate) async fn metering_events(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiQuery(query): ApiQuery<UsageQuery>,
+    ApiQuery(page): ApiQuery<MeteringEventsQuery>,
+) -> AdminResult<Json<Vec<UsageEvent>>> {
+    authorize_admin(&state, &headers)?;
+    let ledger = state
+        .plugin_manager()
+        .metering()
+        .and_then(|metering| metering.ledger())
+        .ok_or_else(|| error(StatusCode::NOT_FOUND, "Metering ledger is not enabled"))?;
+    let mut events = ledger.events(&query).map_err(map_error)?;
+    let limit = page.limit.unwrap_or(DEFAULT_EVENTS_LIMIT);
+    events.drain(..events.len().saturating_sub(limit));
+    Ok(Json(events))
+}
+
+/// Plugins asking to be in the marketplace, with `listing.approved` telling
+/// reviewed ones from pending ones.
+pub(crate) async fn list_listings(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AdminResult<Json<Vec<PluginMetadata>>> {
+    authorize_admin(&state, &headers)?;
+    let mut listed = state.plugin_manager().listed_plugins();
+    state.plugin_manager().annotate_usage(&mut listed);
+    Ok(Json(listed))
+}
+
+/// Plugins no context has called in `days` days, for cleanup.
+pub(crate) async fn stale_plugins(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiQuery(query): ApiQuery<StalePluginsQuery>,
+) -> AdminResult<Json<StalePluginsReport>> {
+    authorize_admin(&state, &headers)?;
+    let days = query.days.unwrap_or(DEFAULT_STALE_DAYS);
+    if days == 0 {
+        return Err(error(StatusCode::BAD_REQUEST, "days must be at least 1"));
+    }
+    let cutoff = state.clock().timestamp() - i64::from(days) * 86_400;
+    let plugins = state
+        .plugin_manager()
+        .stale_plugins(cutoff)
+        .map_err(map_error)?;
+    Ok(Json(StalePluginsReport {
+        days,
+        cutoff,
+        plugins,
+    }))
+}
+
+pub(crate) async fn review_listing(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiPath(plugin_id): ApiPath<u64>,
+    ApiJson(request): ApiJson<ListingReviewRequest>,
+) -> AdminResult<Json<PluginMetadata>> {
+    let who = authorize_admin(&state, &headers)?;
+    let before = state
+        .plugin_manager()
+        .get_plugin(plugin_id)
+        .ok()
+        .and_then(|metadata| metadata.listing);
+    let metadata = state
+        .plugin_manager()
+        .review_listing(plugin_id, request.approved)
+        .map_err(map_error)?;
+    // Reviewed reports no longer count towards the threshold
+    let cleared = state
+        .server()
+        .plugin_feedback()
+        .clear_reports(plugin_id)
+        .map_err(map_error)?;
+    tracing::info!(
+        "Admin {} marketplace listing of plugin {} ({} reports cleared)",
+        if request.approved {
+            "approved"
+        } else {
+            "rejected"
+        },
+        plugin_id,
+        cleared
+    );
+    state.server().audit().record_or_warn(AuditEvent {
+        who,
+        api_key: None,
+        action: "admin.marketplace.review",
+        target: plugin_id.to_string(),
+        before: before.and_then(|listing| serde_json::to_value(listing).ok()),
+        after: serde_json::to_value(&metadata.listing).ok(),
+    });
+    Ok(Json(metadata))
+}
+
+/// A listing with its ratings and the reports waiting for review.
+pub(crate) async fn listing_reports(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiPath(plugin_id): ApiPath<u64>,
+) -> AdminResult<Json<ListingReports>> {
+    authorize_admin(&state, &headers)?;
+    let metadata = state
+        .plugin_manager()
+        .get_plugin(plugin_id)
+        .map_err(map_error)?;
+    let server = state.server();
+    let feedback = server.plugin_feedback();
+    Ok(Json(ListingReports {
+        plugin_id,
+        listing: metadata.listing,
+        ratings: feedback.ratings(plugin_id).map_err(map_error)?,
+        reports: feedback.reports(plugin_id).map_err(map_error)?,
+    }))
+}
+
+pub(crate) async fn dump_config(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AdminResult<Json<serde_json::Value>> {
+    authorize_admin(&state, &headers)?;
+    serde_json::to_value(state.config().redacted())
+        .map(Json)
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+pub(crate) async fn reload_config(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AdminResult<Json<ReloadSummary>> {
+    let who = authorize_admin(&state, &headers)?;
+    let summary = state.reload(who).map_err(map_error)?;
+    tracing::info!("Admin reloaded config: {:?}", summary.changed);
+    Ok(Json(summary))
+}
+
+pub(crate) async fn list_audit(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiQuery(query): ApiQuery<AuditQuery>,
+) -> AdminResult<Json<AuditResponse>> {
+    authorize_admin(&state, &headers)?;
+    let server = state.server();
+    let audit = server.audit();
+    let entries = audit
+        .since(query.since.unwrap_or(0), query.limit.unwrap_or(1000))
+        .map_err(map_error)?;
+    let broken_at = audit.verify().map_err(map_error)?;
+    Ok(Json(AuditResponse {
+        entries,
+        chain_valid: broken_at.is_none(),
+        broken_at,
+    }))
+}
+
+pub(crate) async fn list_oauth_clients(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> AdminResult<Json<Vec<OAuthClientSummary>>> {
+    authorize_admin(&state, &headers)?;
+    let clients = state.server().oauth_clients().list().map_err(map_error)?;
+    Ok(Json(clients.iter().map(OAuthClientSummary::from).collect()))
+}
+
+/// Issues client credentials bound to one context; the secret is only returned here.
+pub(crate) async fn create_oauth_client(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiJson(request): ApiJson<OAuthClientCreateRequest>,
+) -> AdminResult<(StatusCode, Json<OAuthClientCreated>)> {
+    let who = authorize_admin(&state, &headers)?;
+    state
+        .config()
+        .context
+        .id_format()
+        .validate(&request.context_type, &request.context_id)
+        .map_err(|reason| error(StatusCode::BAD_REQUEST, reason))?;
+    let scopes = request.scopes.unwrap_or_else(|| {
+        CLIENT_SCOPES
+            .iter()
+            .map(|scope| scope.to_string())
+            .collect()
+    });
+    if let Some(scope) = scopes
+        .iter()
+        .find(|scope| !CLIENT_SCOPES.contains(&scope.as_str()))
+    {
+        return Err(error(
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unsupported scope {}; allowed: {}",
+                scope,
+                CLIENT_SCOPES.join(", ")
+            ),
+        ));
+    }
+    let context = RequestContext {
+        context_type: request.context_type,
+        context_id: request.context_id,
+        actor_id: None,
+    };
+
+    let (client, client_secret) = state
+        .server()
+        .oauth_clients()
+        .create(&context, scopes)
+        .map_err(map_error)?;
+    let client = OAuthClientSummary::from(&client);
+    tracing::info!(
+        "Admin created OAuth client {} for {}",
+        client.client_id,
+        context.key()
+    );
+    state.server().audit().record_or_warn(AuditEvent {
+        who,
+        api_key: None,
+        action: "admin.oauth.create",
+        target: client.client_id.clone(),
+        before: None,
+        after: serde_json::to_value(&client).ok(),
+    });
+    Ok((
+        StatusCode::CREATED,
+        Json(OAuthClientCreated {
+            client,
+            client_secret,
+        }),
+    ))
+}
+
+pub(crate) async fn delete_oauth_client(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiPath(client_id): ApiPath<String>,
+) -> AdminResult<StatusCode> {
+    let who = authorize_admin(&state, &headers)?;
+    let server = state.server();
+    let before = server
+        .oauth_clients()
+        .get(&client_id)
+        .map_err(map_error)?
+        .ok_or_else(|| error(StatusCode::NOT_FOUND, "Unknown client id"))?;
+    server
+        .oauth_clients()
+        .remove(&client_id)
+        .map_err(map_error)?;
+    tracing::info!("Admin deleted OAuth client {}", client_id);
+    server.audit().record_or_warn(AuditEvent {
+        who,
+        api_key: None,
+        action: "admin.oauth.delete",
+        target: client_id,
+        before: serde_json::to_value(OAuthClientSummary::from(&before)).ok(),
+        after: None,
+    });
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Background deliveries that failed for good, oldest first.
+pub(crate) async fn list_dead_letters(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiQuery(query): ApiQuery<DeadLettersQuery>,
+) -> AdminResult<Json<Vec<DeadLetter>>> {
+    authorize_admin(&state, &headers)?;
+    state
+        .plugin_manager()
+        .dead_letters()
+        .list(query.kind)
+        .map(Json)
+        .map_err(map_error)
+}
+
+/// Sends a dead letter once more; it is removed when that succeeds and
+/// kept with the new error otherwise.
+pub(crate) async fn requeue_dead_letter(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiPath(letter_id): ApiPath<String>,
+) -> AdminResult<Json<DeadLetter>> {
+    let who = authorize_admin(&state, &headers)?;
+    let manager = state.plugin_manager();
+    let letter = manager
+        .dead_letters()
+        .get(&letter_id)
+        .map_err(map_error)?
+        .ok_or_else(|| error(StatusCode::NOT_FOUND, "Unknown dead letter id"))?;
+    let outcome = match letter.delivery.kind {
+        DeliveryKind::JobWebhook => {
+            state
+                .server()
+                .plugin_jobs()
+                .requeue_webhook(manager, &letter_id)
+                .await
+        }
+        DeliveryKind::MeteringWebhook => match manager.metering().and_then(|m| m.webhook()) {
+            Some(webhook) => {
+                manager
+                    .dead_letters()
+                    .requeue(&letter_id, |delivery| webhook.redeliver(delivery))
+                    .await
+            }
+            None => {
+                return Err(error(
+                    StatusCode::CONFLICT,
+                    "The metering webhook sink is not enabled",
+                ))
+            }
+        },
+        DeliveryKind::EventWebhook => match state.server().event_webhook() {
+            Some(webhook) => {
+                manager
+                    .dead_letters()
+                    .requeue(&letter_id, |delivery| webhook.redeliver(delivery))
+                    .await
+            }
+            None => {
+                return Err(error(
+                    StatusCode::CONFLICT,
+                    "The event webhook is not enabled",
+                ))
+            }
+        },
+    };
+    state.server().audit().record_or_warn(AuditEvent {
+        who,
+        api_key: None,
+        action: "admin.dead_letters.requeue",
+        target: letter_id,
+        before: None,
+        after: Some(serde_json::json!({ "delivered": outcome.is_ok() })),
+    });
+    outcome.map(Json).map_err(map_error)
+}
+
+pub(crate) async fn delete_dead_letter(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiPath(letter_id): ApiPath<String>,
+) -> AdminResult<StatusCode> {
+    let who = authorize_admin(&state, &headers)?;
+    let server = state.server();
+    let removed = state
+        .plugin_manager()
+        .dead_letters()
+        .remove(&letter_id)
+        .map_err(map_error)?;
+    if !removed {
+        return Err(error(StatusCode::NOT_FOUND, "Unknown dead letter id"));
+    }
+    server.audit().record_or_warn(AuditEvent {
+        who,
+        api_key: None,
+        action: "admin.dead_letters.delete",
+        target: letter_id,
+        before: None,
+        after: None,
+    });
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,37 @@
+use axum::{
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+
+use crate::auth::redact;
+use crate::http::{error_response, AppState};
+use crate::plugins::ErrorResponse;
+
+/// Checks the admin token and returns who presented it, for the audit log.
+pub(crate) fn authorize_admin(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<String, (StatusCode, Json<ErrorResponse>)> {
+    let admin = state.admin();
+    if !admin.is_enabled() {
+        return Err(error_response(
+            StatusCode::FORBIDDEN,
+            "Admin API is disabled",
+        ));
+    }
+
+    let presented = headers
+        .get(admin.header_name())
+        .and_then(|value| value.to_str().ok());
+    if !admin.validate(presented) {
+        return Err(error_response(StatusCode::UNAUTHORIZED, "Unauthorized"));
+    }
+    Ok(format!("admin:{}", redact(presented.unwrap_or_default())))
+}
+
+pub(crate) fn error(
+    status: StatusCode,
+    message: impl Into<String>,
+) -> (StatusCode, Json<ErrorResponse>) {
+    error_response(status, message)
+}
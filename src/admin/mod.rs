@@ -0,0 +1,18 @@
+pub mod dto;
+pub mod handler;
+mod helpers;
+
+pub use dto::{
+    AdminStats, ApiKeyCreateRequest, AuditQuery, AuditResponse, BackupArchive, BackupResponse,
+    ContextDeletionReport, DeadLettersQuery, ListingReports, ListingReviewRequest,
+    MeteringEventsQuery, PolicySettings, PolicyUpdateRequest, QuotaOverrideRequest,
+    StalePluginsQuery, StalePluginsReport, TokenMappingRequest,
+};
+pub(crate) use handler::{
+    create_key, create_oauth_client, delete_context, delete_dead_letter, delete_key,
+    delete_oauth_client, delete_token_mapping, dump_config, get_policies, get_quotas, list_audit,
+    list_dead_letters, list_jobs, list_keys, list_listings, list_oauth_clients,
+    list_token_mappings, list_upstreams, listing_reports, metering_events, metering_usage,
+    put_token_mapping, reload_config, requeue_dead_letter, review_listing, stale_plugins, stats,
+    trigger_backup, update_policies, update_quotas,
+};
@@ -0,0 +1,202 @@
+//! Append-only record of administrative and registry changes.
+//!
+//! Each entry carries the SHA-256 of its predecessor, so editing or dropping
+//! a stored entry breaks the chain and shows up in [`AuditLog::verify`].
+
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::clock::SharedClock;
+use crate::error::{NovaError, Result};
+use crate::events::{EventKind, EventSubscriber, ServerEvent};
+use crate::storage::{KvStore, MemoryKv};
+
+/// `prev_hash` of the first entry.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub at: i64,
+    // Admin token hint or `<type>:<id>` of the calling context
+    pub who: String,
+    // Id of the API key the caller authenticated with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    // Dotted name, e.g. "plugin.update" or "admin.key.create"
+    pub action: String,
+    pub target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<Value>,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl AuditEntry {
+    fn digest(&self) -> String {
+        let mut body = serde_json::json!({
+            "seq": self.seq,
+            "at": self.at,
+            "who": self.who,
+            "action": self.action,
+            "target": self.target,
+            "before": self.before,
+            "after": self.after,
+        });
+        // Only hashed when set, so entries written before the field existed still verify
+        if let Some(api_key) = &self.api_key {
+            body["api_key"] = Value::from(api_key.as_str());
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(self.prev_hash.as_bytes());
+        hasher.update(body.to_string().as_bytes());
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+/// One change to record; see [`AuditLog::record`].
+pub struct AuditEvent {
+    pub who: String,
+    pub api_key: Option<String>,
+    pub action: &'static str,
+    pub target: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+pub struct AuditLog {
+    store: Box<dyn KvStore>,
+    // Next sequence number and the hash it must link to
+    head: Mutex<(u64, String)>,
+    clock: SharedClock,
+}
+
+impl AuditLog {
+    pub fn in_memory() -> Self {
+        Self {
+            store: Box::new(MemoryKv::default()),
+            head: Mutex::new((0, GENESIS_HASH.to_string())),
+            clock: SharedClock::default(),
+        }
+    }
+
+    /// Entries are JSON keyed by big-endian sequence number; the chain resumes
+    /// from the last stored entry.
+    pub fn persistent(tree: sled::Tree) -> Result<Self> {
+        let head = match KvStore::last(&tree)? {
+            Some((_, bytes)) => {
+                let last: AuditEntry = serde_json::from_slice(&bytes)?;
+                (last.seq + 1, last.hash)
+            }
+            None => (0, GENESIS_HASH.to_string()),
+        };
+        Ok(Self {
+            store: Box::new(tree),
+            head: Mutex::new(head),
+            clock: SharedClock::default(),
+        })
+    }
+
+    /// The clock entries take their `at` from.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn record(&self, event: AuditEvent) -> Result<AuditEntry> {
+        let mut head = self
+            .head
+            .lock()
+            .map_err(|_| NovaError::internal("Audit log lock poisoned"))?;
+        let mut entry = AuditEntry {
+            seq: head.0,
+            at: self.clock.timestamp(),
+            who: event.who,
+            api_key: event.api_key,
+            action: event.action.to_string(),
+            target: event.target,
+            before: event.before,
+            after: event.after,
+            prev_hash: head.1.clone(),
+            hash: String::new(),
+        };
+        entry.hash = entry.digest();
+        self.store.insert_json(&entry.seq.to_be_bytes(), &entry)?;
+        self.store.flush()?;
+        *head = (entry.seq + 1, entry.hash.clone());
+        Ok(entry)
+    }
+
+    /// Like [`record`](Self::record), but only logs a failure; for callers
+    /// whose change has already been applied.
+    pub fn record_or_warn(&self, event: AuditEvent) {
+        let action = event.action;
+        if let Err(err) = self.record(event) {
+            tracing::error!("Failed to audit {}: {}", action, err);
+        }
+    }
+
+    /// Entries at or after `since` (unix seconds), oldest first.
+    pub fn since(&self, since: i64, limit: usize) -> Result<Vec<AuditEntry>> {
+        Ok(self
+            .entries()?
+            .into_iter()
+            .filter(|entry| entry.at >= since)
+            .take(limit)
+            .collect())
+    }
+
+    /// Sequence number of the first entry whose hash or link does not check out.
+    pub fn verify(&self) -> Result<Option<u64>> {
+        let mut prev_hash = GENESIS_HASH.to_string();
+        for entry in self.entries()? {
+            if entry.prev_hash != prev_hash || entry.digest() != entry.hash {
+                return Ok(Some(entry.seq));
+            }
+            prev_hash = entry.hash;
+        }
+        Ok(None)
+    }
+
+    fn entries(&self) -> Result<Vec<AuditEntry>> {
+        self.store.values_json(b"")
+    }
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+/// Keeps fired alerts on the record; every other event is either audited
+/// where it happens, with the caller, or too frequent to chain.
+impl EventSubscriber for AuditLog {
+    fn name(&self) -> &str {
+        "audit"
+    }
+
+    fn handle(&self, event: &ServerEvent) -> Result<()> {
+        let EventKind::AlertFired { alert, message } = &event.kind else {
+            return Ok(());
+        };
+        self.record(AuditEvent {
+            who: "system".to_string(),
+            api_key: None,
+            action: "alert.fired",
+            target: alert.clone(),
+            before: None,
+            after: Some(serde_json::json!({ "message": message })),
+        })
+        .map(drop)
+    }
+}
@@ -0,0 +1,187 @@
+//! Bearer-token verification: HS256 with a shared secret, RS256 against a JWKS.
+
+use super::{AuthMode, Identity};
+use crate::clock::SharedClock;
+use crate::config::AuthConfig;
+use crate::plugins::{ContextIdFormat, PluginContextType, RequestContext};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// Unknown `kid`s trigger a JWKS refetch at most this often.
+const MIN_JWKS_REFRESH: Duration = Duration::from_secs(30);
+
+/// Claims Nova reads from a bearer token; `exp` is required.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwtClaims {
+    pub context_type: String,
+    pub context_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor_id: Option<String>,
+    // Space-separated, as in OAuth2
+    #[serde(default)]
+    pub scope: String,
+    pub exp: i64,
+}
+
+pub struct JwtAuth {
+    secret: Option<DecodingKey>,
+    jwks_url: Option<String>,
+    issuer: Option<String>,
+    audience: Option<String>,
+    jwks_ttl: Duration,
+    http: reqwest::Client,
+    jwks: RwLock<Option<CachedJwks>>,
+    clock: SharedClock,
+}
+
+struct CachedJwks {
+    fetched_at: Instant,
+    keys: JwkSet,
+}
+
+impl JwtAuth {
+    /// `None` unless `auth.mode` is `jwt`; `http` fetches the JWKS.
+    pub fn new(cfg: &AuthConfig, http: reqwest::Client) -> Option<Self> {
+        if cfg.mode() != AuthMode::Jwt {
+            return None;
+        }
+        Some(Self {
+            secret: cfg
+                .jwt_secret
+                .as_deref()
+                .map(|secret| DecodingKey::from_secret(secret.as_bytes())),
+            jwks_url: cfg.jwt_jwks_url.clone(),
+            issuer: cfg.jwt_issuer.clone(),
+            audience: cfg.jwt_audience.clone(),
+            jwks_ttl: Duration::from_secs(cfg.jwt_jwks_cache_secs),
+            http,
+            jwks: RwLock::new(None),
+            clock: SharedClock::default(),
+        })
+    }
+
+    /// Reads `exp` against `clock` instead of the system time.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Checks the signature, `exp` and the configured `iss`/`aud`, then builds
+    /// the context and scopes from the claims.
+    pub async fn verify(
+        &self,
+        token: &str,
+        id_format: ContextIdFormat,
+    ) -> Result<Identity, String> {
+        let header = decode_header(token).map_err(|e| e.to_string())?;
+        let key = match header.alg {
+            Algorithm::HS256 => self
+                .secret
+                .clone()
+                .ok_or_else(|| "HS256 tokens are not accepted".to_string())?,
+            Algorithm::RS256 => self.jwks_key(header.kid.as_deref()).await?,
+            other => return Err(format!("unsupported algorithm {:?}", other)),
+        };
+
+        let mut validation = Validation::new(header.alg);
+        // `exp` is still required, but checked below against the clock
+        validation.validate_exp = false;
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match &self.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+        let claims = decode::<JwtClaims>(token, &key, &validation)
+            .map_err(|e| e.to_string())?
+            .claims;
+        if claims.exp + (validation.leeway as i64) < self.clock.timestamp() {
+            return Err("ExpiredSignature".to_string());
+        }
+
+        let context_type = PluginContextType::parse(&claims.context_type)
+            .ok_or_else(|| format!("unknown context_type {}", claims.context_type))?;
+        id_format.validate(&context_type, &claims.context_id)?;
+        let context = RequestContext {
+            context_type,
+            context_id: claims.context_id,
+            actor_id: None,
+        }
+        .with_actor(claims.actor_id, id_format)?;
+        Ok(Identity {
+            context,
+            scopes: Some(
+                claims
+                    .scope
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .collect(),
+            ),
+        })
+    }
+
+    /// Key for `kid` from the cached JWKS, refetching once the cache is stale
+    /// or the key is unknown (keys may have rotated).
+    async fn jwks_key(&self, kid: Option<&str>) -> Result<DecodingKey, String> {
+        let url = self
+            .jwks_url
+            .as_deref()
+            .ok_or_else(|| "RS256 tokens are not accepted".to_string())?;
+
+        let stale_key = {
+            let cache = self.jwks.read().await;
+            match cache.as_ref() {
+                Some(cached) => {
+                    let age = cached.fetched_at.elapsed();
+                    match find_key(&cached.keys, kid) {
+                        Some(key) if age < self.jwks_ttl => return key,
+                        None if age < MIN_JWKS_REFRESH => {
+                            return Err("unknown signing key".to_string())
+                        }
+                        found => found,
+                    }
+                }
+                None => None,
+            }
+        };
+
+        match self.fetch_jwks(url).await {
+            Ok(keys) => {
+                let found = find_key(&keys, kid);
+                *self.jwks.write().await = Some(CachedJwks {
+                    fetched_at: Instant::now(),
+                    keys,
+                });
+                found.unwrap_or_else(|| Err("unknown signing key".to_string()))
+            }
+            Err(err) => {
+                tracing::warn!("Failed to fetch JWKS from {}: {}", url, err);
+                stale_key.unwrap_or_else(|| Err("signing keys unavailable".to_string()))
+            }
+        }
+    }
+
+    async fn fetch_jwks(&self, url: &str) -> reqwest::Result<JwkSet> {
+        self.http
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+}
+
+/// Without a `kid` only a single-key set is unambiguous.
+fn find_key(keys: &JwkSet, kid: Option<&str>) -> Option<Result<DecodingKey, String>> {
+    let jwk = match kid {
+        Some(kid) => keys.find(kid)?,
+        None if keys.keys.len() == 1 => &keys.keys[0],
+        None => return None,
+    };
+    Some(DecodingKey::from_jwk(jwk).map_err(|e| e.to_string()))
+}
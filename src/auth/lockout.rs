@@ -0,0 +1,188 @@
+//! Lockout for clients guessing API keys.
+//!
+//! Wrong keys are counted per client IP, which is what stops a guesser, and
+//! per presented key (a hash of the whole key, so no valid key shares its
+//! bucket), which backs off a stale key retried from many addresses. Once a
+//! source reaches `auth.lockout_threshold` failures it is locked out for
+//! `auth.lockout_base_secs`, doubling with every further failure up to
+//! `auth.lockout_max_secs`. A request that authenticates clears its sources.
+//!
+//! The address is checked before the key, so a guesser behind a shared NAT
+//! locks out every client behind it until the lockout runs out.
+//!
+//! Sources whose failures have aged out are pruned at most once a minute, and
+//! at most [`MAX_LOCKOUT_SOURCES`] are tracked: past that, new sources go uncounted
+//! until a prune frees room.
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::clock::SharedClock;
+use crate::config::AuthConfig;
+
+/// Most sources tracked at once.
+pub const MAX_LOCKOUT_SOURCES: usize = 100_000;
+
+/// Least time between two prunes of aged-out sources.
+const PRUNE_INTERVAL_SECS: i64 = 60;
+
+#[derive(Debug, Default)]
+pub struct AuthLockout {
+    sources: DashMap<String, Failures>,
+    failures_total: AtomicU64,
+    lockouts_total: AtomicU64,
+    // Clock second of the last prune
+    pruned_at: AtomicI64,
+    clock: SharedClock,
+}
+
+#[derive(Debug)]
+struct Failures {
+    count: u32,
+    last_failure: DateTime<Utc>,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+/// Counters for `GET /admin/stats`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LockoutStats {
+    pub failures_total: u64,
+    pub lockouts_total: u64,
+    pub locked_sources: usize,
+}
+
+impl AuthLockout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// How long the longest lockout among `sources` still runs, if any.
+    pub fn retry_after(&self, sources: &[String]) -> Option<Duration> {
+        let now = self.clock.now();
+        sources
+            .iter()
+            .filter_map(|source| self.sources.get(source)?.locked_until)
+            .filter(|until| *until > now)
+            .filter_map(|until| (until - now).to_std().ok())
+            .max()
+    }
+
+    /// Counts a wrong key against every source and returns the lockout it
+    /// started, if any.
+    pub fn record_failure(&self, sources: &[String], cfg: &AuthConfig) -> Option<Duration> {
+        self.failures_total.fetch_add(1, Ordering::Relaxed);
+        if cfg.lockout_threshold == 0 {
+            return None;
+        }
+        let now = self.clock.now();
+        self.prune(now, cfg);
+
+        let mut started = None;
+        for source in sources {
+            if self.sources.len() >= MAX_LOCKOUT_SOURCES && !self.sources.contains_key(source) {
+                continue;
+            }
+            let mut failures = self.sources.entry(source.clone()).or_insert(Failures {
+                count: 0,
+                last_failure: now,
+                locked_until: None,
+            });
+            failures.count += 1;
+            failures.last_failure = now;
+            if failures.count < cfg.lockout_threshold {
+                continue;
+            }
+            let doublings = (failures.count - cfg.lockout_threshold).min(32);
+            let secs = cfg
+                .lockout_base_secs
+                .saturating_mul(1u64 << doublings)
+                .min(cfg.lockout_max_secs);
+            let lockout = Duration::from_secs(secs);
+            failures.locked_until = Some(plus_secs(now, secs));
+            self.lockouts_total.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!(
+                "Locking out {} for {}s after {} failed API key attempts",
+                source,
+                secs,
+                failures.count
+            );
+            started = started.max(Some(lockout));
+        }
+        started
+    }
+
+    /// Drops sources that are neither locked out nor recent enough to count,
+    /// unless that was done less than a minute ago.
+    fn prune(&self, now: DateTime<Utc>, cfg: &AuthConfig) {
+        let last = self.pruned_at.load(Ordering::Relaxed);
+        let due = now.timestamp() - last >= PRUNE_INTERVAL_SECS;
+        if !due
+            || self
+                .pruned_at
+                .compare_exchange(last, now.timestamp(), Ordering::Relaxed, Ordering::Relaxed)
+                .is_err()
+        {
+            return;
+        }
+        self.sources.retain(|_, failures| {
+            failures.locked_until.is_some_and(|until| until > now)
+                || now < plus_secs(failures.last_failure, cfg.lockout_max_secs)
+        });
+    }
+
+    /// Sources currently tracked, locked out or not.
+    pub fn tracked_sources(&self) -> usize {
+        self.sources.len()
+    }
+
+    pub fn record_success(&self, sources: &[String]) {
+        for source in sources {
+            self.sources.remove(source);
+        }
+    }
+
+    pub fn stats(&self) -> LockoutStats {
+        let now = self.clock.now();
+        LockoutStats {
+            failures_total: self.failures_total.load(Ordering::Relaxed),
+            lockouts_total: self.lockouts_total.load(Ordering::Relaxed),
+            locked_sources: self
+                .sources
+                .iter()
+                .filter(|failures| failures.locked_until.is_some_and(|until| until > now))
+                .count(),
+        }
+    }
+}
+
+/// Lockout source of a client address.
+pub fn ip_source(ip: &str) -> String {
+    format!("ip:{}", ip)
+}
+
+/// Lockout source of a presented key: a truncated SHA-256 of the whole key,
+/// never a prefix another key could share.
+pub fn key_source(key: &str) -> String {
+    let digest: String = Sha256::digest(key.as_bytes())[..12]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("key:{}", digest)
+}
+
+/// `at` plus `secs`, saturating instead of overflowing.
+fn plus_secs(at: DateTime<Utc>, secs: u64) -> DateTime<Utc> {
+    chrono::Duration::from_std(Duration::from_secs(secs))
+        .ok()
+        .and_then(|offset| at.checked_add_signed(offset))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
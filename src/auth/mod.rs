@@ -0,0 +1,287 @@
+mod jwt;
+mod lockout;
+mod telegram;
+
+use crate::config::{AdminConfig, AuthConfig};
+use crate::plugins::RequestContext;
+use serde::Serialize;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+pub use jwt::{JwtAuth, JwtClaims};
+pub use lockout::{ip_source, key_source, AuthLockout, LockoutStats, MAX_LOCKOUT_SOURCES};
+pub use telegram::{TelegramAuth, TELEGRAM_INIT_DATA_HEADER, TELEGRAM_LOGIN_HEADER};
+
+/// How HTTP callers prove which context they act for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AuthMode {
+    /// Context comes from the `x-nova-context-*` headers as sent.
+    #[default]
+    ApiKey,
+    /// Context is derived from signed Telegram data; context headers are ignored.
+    Telegram,
+    /// Context and scopes come from a verified `Authorization: Bearer` JWT.
+    Jwt,
+}
+
+impl AuthMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "api_key" => Some(Self::ApiKey),
+            "telegram" => Some(Self::Telegram),
+            "jwt" => Some(Self::Jwt),
+            _ => None,
+        }
+    }
+}
+
+/// JSON-RPC on `/rpc` and `/mcp`, and `POST /plugins/:id/call`.
+pub const SCOPE_TOOLS: &str = "tools";
+/// `GET /plugins`.
+pub const SCOPE_PLUGINS_READ: &str = "plugins:read";
+/// Registering, updating, removing and enabling plugins.
+pub const SCOPE_PLUGINS_WRITE: &str = "plugins:write";
+/// The `/preferences` routes.
+pub const SCOPE_PREFERENCES: &str = "preferences";
+
+/// Context proven by a credential rather than taken from request headers.
+#[derive(Debug, Clone)]
+pub struct Identity {
+    pub context: RequestContext,
+    // Granted scopes; `None` (Telegram data) allows everything
+    pub scopes: Option<Vec<String>>,
+}
+
+impl Identity {
+    pub fn allows(&self, scope: &str) -> bool {
+        self.scopes
+            .as_ref()
+            .is_none_or(|scopes| scopes.iter().any(|granted| granted == scope))
+    }
+}
+
+/// Which API key a request authenticated with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyIdentity {
+    /// The configured or admin-added key with this id (`key-1`, a
+    /// `named_keys` name, or the id given to `POST /admin/keys`).
+    Key(String),
+    /// API keys are disabled; every request is let through.
+    Open,
+    /// No API key: a bearer token verified in jwt mode for this context
+    /// (`<type>:<id>`) stood in for one.
+    Bearer(String),
+}
+
+impl KeyIdentity {
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            KeyIdentity::Key(id) => Some(id),
+            KeyIdentity::Open | KeyIdentity::Bearer(_) => None,
+        }
+    }
+
+    /// Rate-limit bucket for `principal` calling with this key, so two
+    /// integrations acting for the same context do not share a budget.
+    pub fn rate_key(&self, principal: &str) -> String {
+        match self {
+            KeyIdentity::Key(id) => format!("key:{}|{}", id, principal),
+            KeyIdentity::Open | KeyIdentity::Bearer(_) => principal.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for KeyIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id().unwrap_or("-"))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ApiKeyAuth {
+    enabled: bool,
+    header_name: String,
+    // For now keep raw secrets; replace with hashed+DB in production.
+    // Shared so admin key management is visible to every clone.
+    allowed: Arc<RwLock<Vec<ApiKey>>>,
+}
+
+#[derive(Clone, Debug)]
+struct ApiKey {
+    id: String,
+    secret: String,
+}
+
+/// Redacted view of a configured API key.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiKeySummary {
+    pub id: String,
+    pub hint: String,
+}
+
+impl ApiKeyAuth {
+    pub fn new(cfg: &AuthConfig) -> Self {
+        Self {
+            enabled: cfg.enabled,
+            header_name: cfg.header_name.clone(),
+            allowed: Arc::new(RwLock::new(configured_keys(cfg))),
+        }
+    }
+
+    pub fn header_name(&self) -> &str {
+        &self.header_name
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn validate(&self, presented: Option<&str>) -> bool {
+        self.authenticate(presented).is_some()
+    }
+
+    /// The key `presented` matches, or `None` when it is missing or unknown.
+    pub fn authenticate(&self, presented: Option<&str>) -> Option<KeyIdentity> {
+        if !self.enabled {
+            return Some(KeyIdentity::Open);
+        }
+        let key = presented.filter(|k| !k.is_empty())?;
+        let allowed = self.allowed.read().ok()?;
+        // Compare against every key so timing does not reveal which one matched
+        let mut matched = None;
+        for allowed in allowed.iter() {
+            if constant_time_eq(allowed.secret.as_bytes(), key.as_bytes()) && matched.is_none() {
+                matched = Some(KeyIdentity::Key(allowed.id.clone()));
+            }
+        }
+        matched
+    }
+
+    pub fn list_keys(&self) -> Vec<ApiKeySummary> {
+        self.allowed
+            .read()
+            .map(|keys| {
+                keys.iter()
+                    .map(|key| ApiKeySummary {
+                        id: key.id.clone(),
+                        hint: redact(&key.secret),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Adds a key under `id`; returns false when the id is already taken.
+    pub fn add_key(&self, id: &str, secret: &str) -> bool {
+        let Ok(mut keys) = self.allowed.write() else {
+            return false;
+        };
+        if keys.iter().any(|key| key.id == id) {
+            return false;
+        }
+        keys.push(ApiKey {
+            id: id.to_string(),
+            secret: secret.to_string(),
+        });
+        true
+    }
+
+    /// Swaps in a freshly loaded key list; keys added via the admin API are dropped.
+    pub fn replace_keys(&self, secrets: &[String]) {
+        self.reload_keys(&AuthConfig {
+            allowed_keys: secrets.to_vec(),
+            ..AuthConfig::default()
+        });
+    }
+
+    /// Like [`Self::replace_keys`], with `named_keys` as well.
+    pub fn reload_keys(&self, cfg: &AuthConfig) {
+        if let Ok(mut keys) = self.allowed.write() {
+            *keys = configured_keys(cfg);
+        }
+    }
+
+    /// Removes the key with `id`; returns false when no such key exists.
+    pub fn remove_key(&self, id: &str) -> bool {
+        let Ok(mut keys) = self.allowed.write() else {
+            return false;
+        };
+        let before = keys.len();
+        keys.retain(|key| key.id != id);
+        keys.len() != before
+    }
+}
+
+/// `allowed_keys` numbered `key-1`, `key-2`, ..., then `named_keys` under their names.
+fn configured_keys(cfg: &AuthConfig) -> Vec<ApiKey> {
+    let numbered = cfg
+        .allowed_keys
+        .iter()
+        .enumerate()
+        .map(|(i, secret)| ApiKey {
+            id: format!("key-{}", i + 1),
+            secret: secret.clone(),
+        });
+    let named = cfg.named_keys.iter().map(|(name, secret)| ApiKey {
+        id: name.trim().to_string(),
+        secret: secret.clone(),
+    });
+    numbered.chain(named).collect()
+}
+
+/// Operator credentials for the `/admin` routes, independent of tenant API keys.
+#[derive(Clone, Debug)]
+pub struct AdminAuth {
+    header_name: String,
+    tokens: Vec<String>,
+}
+
+impl AdminAuth {
+    pub fn new(cfg: &AdminConfig) -> Self {
+        Self {
+            header_name: cfg.header_name.clone(),
+            tokens: cfg.tokens.clone(),
+        }
+    }
+
+    pub fn header_name(&self) -> &str {
+        &self.header_name
+    }
+
+    /// The admin API is off unless at least one token is configured.
+    pub fn is_enabled(&self) -> bool {
+        !self.tokens.is_empty()
+    }
+
+    pub fn validate(&self, presented: Option<&str>) -> bool {
+        match presented {
+            Some(token) if !token.is_empty() => self
+                .tokens
+                .iter()
+                .any(|allowed| constant_time_eq(allowed.as_bytes(), token.as_bytes())),
+            _ => false,
+        }
+    }
+}
+
+/// Keeps a short prefix so operators can tell secrets apart without exposing them.
+pub fn redact(secret: &str) -> String {
+    let prefix: String = secret.chars().take(4).collect();
+    if secret.chars().count() <= 8 {
+        "****".to_string()
+    } else {
+        format!("{}****", prefix)
+    }
+}
+
+// Minimal constant-time equality to avoid timing leaks
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut r: u8 = 0;
+    for i in 0..a.len() {
+        r |= a[i] ^ b[i];
+    }
+    r == 0
+}
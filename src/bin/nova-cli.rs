@@ -0,0 +1,418 @@
+//! Operator client for a running Nova HTTP server.
+//!
+//! ```text
+//! nova-cli [options] <command> [args]    run one command
+//! nova-cli [options]                     read commands from stdin, one per line
+//! ```
+//!
+//! Run `nova-cli help` for the commands and options.
+
+use anyhow::{bail, Context, Result};
+use nova_mcp::client::{parse_context, ClientConfig, NovaClient};
+use nova_mcp::metering::{UsageEvent, UsageQuery};
+use nova_mcp::plugins::manifest::{substitute_env, ManifestFormat};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::io::{IsTerminal, Write};
+use std::time::Duration;
+
+const USAGE: &str = "\
+Usage: nova-cli [options] <command> [args]
+
+Commands:
+  tools                          List the tools available to the context
+  call <tool> [json|@file|-]     Call a tool; arguments default to {}
+  plugins                        List plugins registered by the context
+  register <manifest>            Register a plugin from a plugin.toml or plugin.json;
+                                 ${VAR} placeholders are read from the environment
+  enable <plugin_id>             Enable a plugin for the context
+  disable <plugin_id>            Disable a plugin for the context
+  logs [--follow] [--plugin <fq_name>] [--caller <type:id>] [--limit <n>]
+                                 Show plugin calls from the metering ledger (admin)
+  help                           Show this message
+
+Without a command, commands are read from stdin one per line.
+
+Options (environment variable in brackets):
+  --url <url>              Server base URL [NOVA_MCP_URL] (default http://127.0.0.1:8080)
+  --api-key <key>          API key [NOVA_MCP_API_KEY]
+  --api-key-header <name>  Header for the API key (default x-api-key)
+  --admin-token <token>    Admin token, needed by logs [NOVA_MCP_ADMIN_TOKEN]
+  --admin-header <name>    Header for the admin token (default x-admin-token)
+  --bearer <token>         Bearer token for jwt auth [NOVA_MCP_BEARER_TOKEN]
+  --context <type:id>      Context to act as, e.g. user:42 [NOVA_MCP_CONTEXT]
+  --actor <id>             Actor within a group or channel context [NOVA_MCP_ACTOR_ID]
+  --json                   Print raw JSON responses";
+
+/// How often `logs --follow` polls the ledger.
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    Tools,
+    Call { tool: String, arguments: String },
+    Plugins,
+    Register { manifest: String },
+    Enable { plugin_id: u64, enable: bool },
+    Logs(LogsArgs),
+    Help,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct LogsArgs {
+    follow: bool,
+    plugin: Option<String>,
+    caller: Option<String>,
+    limit: usize,
+}
+
+#[tokio::main]
+async fn main() {
+    let code = match run().await {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            1
+        }
+    };
+    std::process::exit(code);
+}
+
+async fn run() -> Result<i32> {
+    let (client, json, command) = parse_args(std::env::args().skip(1).collect())?;
+    match command {
+        Some(words) => execute(&client, json, parse_command(words)?).await,
+        None => repl(&client, json).await,
+    }
+}
+
+/// Splits global options from the command words.
+fn parse_args(args: Vec<String>) -> Result<(NovaClient, bool, Option<Vec<String>>)> {
+    let env = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+    let mut config = ClientConfig::new(
+        env("NOVA_MCP_URL").unwrap_or_else(|| "http://127.0.0.1:8080".to_string()),
+    );
+    config.api_key = env("NOVA_MCP_API_KEY");
+    config.admin_token = env("NOVA_MCP_ADMIN_TOKEN");
+    config.bearer_token = env("NOVA_MCP_BEARER_TOKEN");
+    let mut context = env("NOVA_MCP_CONTEXT");
+    let mut actor = env("NOVA_MCP_ACTOR_ID");
+    let mut json = false;
+
+    let mut args = args.into_iter();
+    let mut command = None;
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            command = Some(std::iter::once(arg).chain(args).collect());
+            break;
+        }
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg, None),
+        };
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| args.next())
+                .with_context(|| format!("{} requires a value", flag))
+        };
+        match flag.as_str() {
+            "--url" => config.base_url = value()?.trim_end_matches('/').to_string(),
+            "--api-key" => config.api_key = Some(value()?),
+            "--api-key-header" => config.api_key_header = value()?,
+            "--admin-token" => config.admin_token = Some(value()?),
+            "--admin-header" => config.admin_header = value()?,
+            "--bearer" => config.bearer_token = Some(value()?),
+            "--context" => context = Some(value()?),
+            "--actor" => actor = Some(value()?),
+            "--json" => json = true,
+            "--help" => command = Some(vec!["help".to_string()]),
+            _ => bail!("Unknown option: {}", flag),
+        }
+    }
+    if let Some(context) = context {
+        let mut context = parse_context(&context)?;
+        context.actor_id = actor;
+        config.context = Some(context);
+    }
+    Ok((NovaClient::new(config), json, command))
+}
+
+fn parse_command(words: Vec<String>) -> Result<Command> {
+    let mut words = words.into_iter();
+    let name = words.next().unwrap_or_default();
+    let rest: Vec<String> = words.collect();
+    let command = match (name.as_str(), rest.as_slice()) {
+        ("tools", []) => Command::Tools,
+        ("call", [tool]) => Command::Call {
+            tool: tool.clone(),
+            arguments: "{}".to_string(),
+        },
+        ("call", [tool, arguments @ ..]) => Command::Call {
+            tool: tool.clone(),
+            arguments: arguments.join(" "),
+        },
+        ("plugins", []) => Command::Plugins,
+        ("register", [manifest]) => Command::Register {
+            manifest: manifest.clone(),
+        },
+        ("enable" | "disable", [plugin_id]) => Command::Enable {
+            plugin_id: plugin_id
+                .parse()
+                .with_context(|| format!("Invalid plugin id: {}", plugin_id))?,
+            enable: name == "enable",
+        },
+        ("logs", flags) => Command::Logs(parse_logs(flags)?),
+        ("help", []) => Command::Help,
+        _ => bail!("Invalid command: {} (try `help`)", name),
+    };
+    Ok(command)
+}
+
+fn parse_logs(flags: &[String]) -> Result<LogsArgs> {
+    let mut logs = LogsArgs {
+        limit: 20,
+        ..LogsArgs::default()
+    };
+    let mut flags = flags.iter();
+    while let Some(flag) = flags.next() {
+        let mut value = || {
+            flags
+                .next()
+                .cloned()
+                .with_context(|| format!("{} requires a value", flag))
+        };
+        match flag.as_str() {
+            "--follow" | "-f" => logs.follow = true,
+            "--plugin" => logs.plugin = Some(value()?),
+            "--caller" => {
+                let caller = parse_context(&value()?)?;
+                logs.caller = Some(format!("{}:{}", caller.context_type, caller.context_id));
+            }
+            "--limit" => logs.limit = value()?.parse().context("--limit must be a number")?,
+            _ => bail!("Unknown logs option: {}", flag),
+        }
+    }
+    Ok(logs)
+}
+
+/// Runs one command; the exit code is 1 when a tool call returned `isError`.
+async fn execute(client: &NovaClient, json: bool, command: Command) -> Result<i32> {
+    let mut out = std::io::stdout().lock();
+    match command {
+        Command::Tools => {
+            let tools = client.tools_list().await?;
+            if json {
+                print_json(&mut out, &tools)?;
+            }
+            for tool in tools.iter().filter(|_| !json) {
+                writeln!(out, "{:<32} {}", tool.name, tool.description)?;
+            }
+        }
+        Command::Call { tool, arguments } => {
+            let arguments = read_arguments(&arguments)?;
+            let result = client.tools_call(&tool, arguments).await?;
+            if json {
+                print_json(&mut out, &result)?;
+            } else {
+                print_content(&mut out, &result)?;
+            }
+            if result["isError"] == Value::Bool(true) {
+                return Ok(1);
+            }
+        }
+        Command::Plugins => {
+            let plugins = client.list_plugins().await?;
+            if json {
+                print_json(&mut out, &plugins)?;
+            }
+            for plugin in plugins.iter().filter(|_| !json) {
+                writeln!(
+                    out,
+                    "{:>6}  {:<32} v{}  installs {}",
+                    plugin.plugin_id, plugin.fq_name, plugin.version, plugin.installs
+                )?;
+            }
+        }
+        Command::Register { manifest } => {
+            let text = std::fs::read_to_string(&manifest)
+                .with_context(|| format!("Failed to read {}", manifest))?;
+            let text = substitute_env(&text, |name| std::env::var(name).ok())?;
+            let format = ManifestFormat::from_path(std::path::Path::new(&manifest));
+            let plugin = client.register_manifest(&text, format).await?;
+            if json {
+                print_json(&mut out, &plugin)?;
+            } else {
+                writeln!(
+                    out,
+                    "Registered {} (plugin {})",
+                    plugin.fq_name, plugin.plugin_id
+                )?;
+            }
+        }
+        Command::Enable { plugin_id, enable } => {
+            let status = client.enable(plugin_id, enable).await?;
+            if json {
+                print_json(&mut out, &status)?;
+            } else {
+                let state = if status.enabled {
+                    "enabled"
+                } else {
+                    "disabled"
+                };
+                writeln!(
+                    out,
+                    "Plugin {} {} for {}:{}",
+                    plugin_id, state, status.context_type, status.context_id
+                )?;
+            }
+        }
+        Command::Logs(logs) => {
+            drop(out);
+            tail(client, json, logs).await?;
+        }
+        Command::Help => writeln!(out, "{}", USAGE)?,
+    }
+    Ok(0)
+}
+
+/// `logs`: the newest `limit` calls, then with `--follow` new ones as they
+/// land. Events sharing the last seen second are told apart by id.
+async fn tail(client: &NovaClient, json: bool, logs: LogsArgs) -> Result<()> {
+    let mut query = UsageQuery {
+        context: logs.caller,
+        plugin: logs.plugin,
+        ..UsageQuery::default()
+    };
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut limit = logs.limit;
+    loop {
+        let events = client.usage_events(&query, limit).await?;
+        let mut out = std::io::stdout().lock();
+        for event in events.iter().filter(|event| !seen.contains(&event.id)) {
+            print_event(&mut out, json, event)?;
+        }
+        out.flush()?;
+        if !logs.follow {
+            return Ok(());
+        }
+        if let Some(last) = events.last() {
+            if query.since != Some(last.at) {
+                seen.clear();
+            }
+            query.since = Some(last.at);
+            seen.extend(
+                events
+                    .iter()
+                    .filter(|event| event.at == last.at)
+                    .map(|event| event.id.clone()),
+            );
+        }
+        // Everything since the last second seen, however busy
+        limit = usize::MAX;
+        tokio::time::sleep(FOLLOW_INTERVAL).await;
+    }
+}
+
+/// Interactive mode: one command per line until `quit` or end of input.
+async fn repl(client: &NovaClient, json: bool) -> Result<i32> {
+    let interactive = std::io::stdin().is_terminal();
+    let mut failed = false;
+    loop {
+        if interactive {
+            print!("nova> ");
+            std::io::stdout().flush()?;
+        }
+        // Not holding the stdin lock, so `call <tool> -` can still read from it
+        let mut line = String::new();
+        if std::io::stdin().read_line(&mut line)? == 0 {
+            break;
+        }
+        let words = split_line(&line);
+        match words.first().map(String::as_str) {
+            None => continue,
+            Some("quit" | "exit") => break,
+            Some(_) => {}
+        }
+        let result = match parse_command(words) {
+            Ok(command) => execute(client, json, command).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(0) => {}
+            Ok(_) => failed = true,
+            Err(e) => {
+                eprintln!("error: {:#}", e);
+                failed = true;
+            }
+        }
+    }
+    Ok(i32::from(failed))
+}
+
+/// Words of an interactive line. `call` keeps everything after the tool name
+/// as one argument, so JSON needs no quoting.
+fn split_line(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let mut words = line.splitn(3, char::is_whitespace);
+    match (words.next(), words.next(), words.next()) {
+        (Some("call"), Some(tool), rest) => std::iter::once("call".to_string())
+            .chain(std::iter::once(tool.to_string()))
+            .chain(rest.map(|rest| rest.trim().to_string()))
+            .collect(),
+        _ => line.split_whitespace().map(str::to_string).collect(),
+    }
+}
+
+/// Arguments given inline, as `@path`, or as `-` for stdin.
+fn read_arguments(source: &str) -> Result<Value> {
+    let text = match source {
+        "-" => std::io::read_to_string(std::io::stdin()).context("Failed to read stdin")?,
+        path if path.starts_with('@') => std::fs::read_to_string(&path[1..])
+            .with_context(|| format!("Failed to read {}", &path[1..]))?,
+        inline => inline.to_string(),
+    };
+    serde_json::from_str(&text).context("Tool arguments are not valid JSON")
+}
+
+fn print_json(out: &mut impl Write, value: &impl serde::Serialize) -> Result<()> {
+    writeln!(out, "{}", serde_json::to_string_pretty(value)?)?;
+    Ok(())
+}
+
+/// The text of each content item; other items as JSON.
+fn print_content(out: &mut impl Write, result: &Value) -> Result<()> {
+    let items = result["content"].as_array().cloned().unwrap_or_default();
+    for item in &items {
+        match item["text"].as_str() {
+            Some(text) if item["type"] == "text" => writeln!(out, "{}", text)?,
+            _ => print_json(out, item)?,
+        }
+    }
+    if items.is_empty() {
+        print_json(out, result)?;
+    }
+    Ok(())
+}
+
+fn print_event(out: &mut impl Write, json: bool, event: &UsageEvent) -> Result<()> {
+    if json {
+        writeln!(out, "{}", serde_json::to_string(event)?)?;
+        return Ok(());
+    }
+    let at = chrono::DateTime::from_timestamp(event.at, 0)
+        .map(|at| at.to_rfc3339())
+        .unwrap_or_else(|| event.at.to_string());
+    writeln!(
+        out,
+        "{} {:<20} {:<32} {:<6} {}ms {}B/{}B",
+        at,
+        event.context,
+        event.plugin,
+        if event.success { "ok" } else { "failed" },
+        event.duration_ms,
+        event.request_bytes,
+        event.response_bytes
+    )?;
+    Ok(())
+}
@@ -0,0 +1,297 @@
+//! HTTP client for a running server's REST and JSON-RPC routes, used by
+//! `nova-cli` and the [`test_util`](crate::test_util) harness.
+//!
+//! [`NovaClient`] sends the API key, admin token, bearer token and
+//! `x-nova-*` context headers from its [`ClientConfig`] on every request:
+//!
+//! ```no_run
+//! # async fn example() -> nova_mcp::Result<()> {
+//! use nova_mcp::client::{ClientConfig, NovaClient};
+//! use nova_mcp::plugins::{PluginContextType, RequestContext};
+//!
+//! let client = NovaClient::new(ClientConfig {
+//!     api_key: Some("secret".to_string()),
+//!     context: Some(RequestContext {
+//!         context_type: PluginContextType::User,
+//!         context_id: "42".to_string(),
+//!         actor_id: None,
+//!     }),
+//!     ..ClientConfig::new("http://127.0.0.1:8080")
+//! });
+//! let tools = client.tools_list().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use reqwest::{Client, Method, RequestBuilder};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::error::{NovaError, Result};
+use crate::mcp::dto::{McpResponse, Tool};
+use crate::metering::{UsageEvent, UsageQuery};
+use crate::plugins::{
+    ManifestFormat, PluginContextType, PluginDetails, PluginEnableRequest, PluginEnablementStatus,
+    PluginMetadata, PluginRegistrationRequest, PluginUpdateRequest, RequestContext,
+};
+
+/// Where a [`NovaClient`] connects and which credentials it presents.
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    pub base_url: String,
+    pub api_key: Option<String>,
+    /// Server's `auth.header_name`.
+    pub api_key_header: String,
+    pub admin_token: Option<String>,
+    /// Server's `admin.header_name`.
+    pub admin_header: String,
+    /// Sent as `Authorization: Bearer` in jwt mode.
+    pub bearer_token: Option<String>,
+    /// Sent as `x-nova-context-*` and `x-nova-actor-id`.
+    pub context: Option<RequestContext>,
+}
+
+impl ClientConfig {
+    /// No credentials, with the servers' default header names.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            api_key: None,
+            api_key_header: "x-api-key".to_string(),
+            admin_token: None,
+            admin_header: "x-admin-token".to_string(),
+            bearer_token: None,
+            context: None,
+        }
+    }
+}
+
+/// Typed calls against one server. Non-2xx answers and JSON-RPC errors
+/// become [`NovaError::ApiError`]; use [`request`](Self::request) to check
+/// statuses yourself.
+#[derive(Debug, Clone)]
+pub struct NovaClient {
+    config: ClientConfig,
+    http: Client,
+}
+
+impl NovaClient {
+    pub fn new(config: ClientConfig) -> Self {
+        Self {
+            config,
+            http: Client::new(),
+        }
+    }
+
+    pub fn config(&self) -> &ClientConfig {
+        &self.config
+    }
+
+    /// A request to `path` carrying the configured headers.
+    pub fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let config = &self.config;
+        let mut builder = self
+            .http
+            .request(method, format!("{}{}", config.base_url, path));
+        if let Some(api_key) = &config.api_key {
+            builder = builder.header(config.api_key_header.as_str(), api_key);
+        }
+        if let Some(token) = &config.admin_token {
+            builder = builder.header(config.admin_header.as_str(), token);
+        }
+        if let Some(token) = &config.bearer_token {
+            builder = builder.bearer_auth(token);
+        }
+        if let Some(context) = &config.context {
+            builder = builder
+                .header("x-nova-context-type", context.context_type.to_string())
+                .header("x-nova-context-id", &context.context_id);
+            if let Some(actor_id) = &context.actor_id {
+                builder = builder.header("x-nova-actor-id", actor_id);
+            }
+        }
+        builder
+    }
+
+    pub async fn register(&self, request: &PluginRegistrationRequest) -> Result<PluginMetadata> {
+        self.send(Method::POST, "/v1/plugins/register", Some(request))
+            .await
+    }
+
+    /// `POST /plugins/register-manifest` with a `plugin.toml` or
+    /// `plugin.json` body.
+    pub async fn register_manifest(
+        &self,
+        manifest: &str,
+        format: ManifestFormat,
+    ) -> Result<PluginMetadata> {
+        let path = "/v1/plugins/register-manifest";
+        let response = self
+            .request(Method::POST, path)
+            .header(reqwest::header::CONTENT_TYPE, format.content_type())
+            .body(manifest.to_string())
+            .send()
+            .await?;
+        decode(Method::POST, path, response).await
+    }
+
+    /// `PATCH /plugins/:id`; with `request.revision` set, a plugin changed
+    /// since that revision fails with 409 `revision_conflict`.
+    pub async fn update(
+        &self,
+        plugin_id: u64,
+        request: &PluginUpdateRequest,
+    ) -> Result<PluginMetadata> {
+        self.send(
+            Method::PATCH,
+            &format!("/v1/plugins/{}", plugin_id),
+            Some(request),
+        )
+        .await
+    }
+
+    /// Enables or disables the plugin for the configured context, with the
+    /// actor as `added_by`.
+    pub async fn enable(&self, plugin_id: u64, enable: bool) -> Result<PluginEnablementStatus> {
+        let context = self.context()?;
+        let request = PluginEnableRequest {
+            context_type: context.context_type.clone(),
+            context_id: context.context_id.clone(),
+            plugin_id,
+            enable,
+            added_by: context.actor_id.clone(),
+        };
+        self.send(Method::POST, "/v1/plugins/enable", Some(&request))
+            .await
+    }
+
+    pub async fn list_plugins(&self) -> Result<Vec<PluginMetadata>> {
+        self.send::<(), _>(Method::GET, "/v1/plugins", None).await
+    }
+
+    /// A plugin the configured context owns or has enabled, with usage and
+    /// endpoint health.
+    pub async fn get_plugin(&self, plugin_id: u64) -> Result<PluginDetails> {
+        let path = format!("/v1/plugins/{}", plugin_id);
+        self.send::<(), _>(Method::GET, &path, None).await
+    }
+
+    /// The plugin's enablement for `context`, or for the configured context.
+    pub async fn enablement(
+        &self,
+        plugin_id: u64,
+        context: Option<&RequestContext>,
+    ) -> Result<PluginEnablementStatus> {
+        let path = format!("/v1/plugins/{}/enablement", plugin_id);
+        let mut request = self.request(Method::GET, &path);
+        if let Some(context) = context {
+            request = request.query(&[("context", context.key())]);
+        }
+        decode(Method::GET, &path, request.send().await?).await
+    }
+
+    pub async fn tools_list(&self) -> Result<Vec<Tool>> {
+        let response = self.rpc("tools/list", json!({})).await?;
+        let result = rpc_result(response)?;
+        Ok(serde_json::from_value(result["tools"].clone())?)
+    }
+
+    /// The `tools/call` result: `{ content, isError, ... }`.
+    pub async fn tools_call(&self, name: &str, arguments: Value) -> Result<Value> {
+        let response = self
+            .rpc(
+                "tools/call",
+                json!({ "name": name, "arguments": arguments }),
+            )
+            .await?;
+        rpc_result(response)
+    }
+
+    /// One JSON-RPC request on `/v1/rpc`, without a session.
+    pub async fn rpc(&self, method: &str, params: Value) -> Result<McpResponse> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        self.send(Method::POST, "/v1/rpc", Some(&body)).await
+    }
+
+    /// `GET /admin/metering/events`: the newest `limit` plugin calls matching
+    /// `query`, oldest first. Needs the admin token.
+    pub async fn usage_events(&self, query: &UsageQuery, limit: usize) -> Result<Vec<UsageEvent>> {
+        let mut params = vec![("limit", limit.to_string())];
+        params.extend(query.since.map(|since| ("since", since.to_string())));
+        params.extend(query.until.map(|until| ("until", until.to_string())));
+        params.extend(query.context.clone().map(|context| ("context", context)));
+        params.extend(query.plugin.clone().map(|plugin| ("plugin", plugin)));
+        let path = "/v1/admin/metering/events";
+        let response = self
+            .request(Method::GET, path)
+            .query(&params)
+            .send()
+            .await?;
+        decode(Method::GET, path, response).await
+    }
+
+    fn context(&self) -> Result<&RequestContext> {
+        self.config
+            .context
+            .as_ref()
+            .ok_or_else(|| NovaError::config_error("No context configured"))
+    }
+
+    async fn send<B: Serialize, T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<T> {
+        let mut builder = self.request(method.clone(), path);
+        if let Some(body) = body {
+            builder = builder.json(body);
+        }
+        let response = builder.send().await?;
+        decode(method, path, response).await
+    }
+}
+
+async fn decode<T: DeserializeOwned>(
+    method: Method,
+    path: &str,
+    response: reqwest::Response,
+) -> Result<T> {
+    let status = response.status();
+    if !status.is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(NovaError::api_error(format!(
+            "{} {} returned {}: {}",
+            method, path, status, text
+        )));
+    }
+    Ok(response.json().await?)
+}
+
+fn rpc_result(response: McpResponse) -> Result<Value> {
+    match (response.result, response.error) {
+        (_, Some(error)) => Err(NovaError::api_error(format!(
+            "JSON-RPC error {}: {}",
+            error.code, error.message
+        ))),
+        (Some(result), None) => Ok(result),
+        (None, None) => Err(NovaError::api_error("JSON-RPC response without a result")),
+    }
+}
+
+/// Parses `<type>:<id>`, e.g. `user:42` or `group:-100123`.
+pub fn parse_context(value: &str) -> Result<RequestContext> {
+    let (context_type, context_id) = value
+        .split_once(':')
+        .filter(|(_, id)| !id.trim().is_empty())
+        .ok_or_else(|| NovaError::config_error(format!("Expected <type>:<id>, got '{}'", value)))?;
+    let context_type = PluginContextType::parse(context_type).ok_or_else(|| {
+        NovaError::config_error(format!("Unknown context type: {}", context_type))
+    })?;
+    Ok(RequestContext {
+        context_type,
+        context_id: context_id.trim().to_string(),
+        actor_id: None,
+    })
+}
@@ -0,0 +1,118 @@
+//! Wall-clock time, behind a trait so tests can control it.
+//!
+//! Rate-limit windows, quota periods, auth lockouts, negative-cache expiry,
+//! timestamps (plugin `created_at`, `consent_ts` and last use, audit entries,
+//! ratings, reports and OAuth clients) and job run history read the time from a
+//! [`SharedClock`]. It is the system clock unless a [`ManualClock`] is passed
+//! to the components' `with_clock` builders:
+//!
+//! ```
+//! use nova_mcp::clock::{ManualClock, SharedClock};
+//! use nova_mcp::PluginManager;
+//! use std::time::Duration;
+//!
+//! let clock = ManualClock::at(1_700_000_000);
+//! let manager = PluginManager::in_memory()
+//!     .unwrap()
+//!     .with_clock(SharedClock::new(clock.clone()));
+//! clock.advance(Duration::from_secs(60));
+//! assert_eq!(manager.clock().timestamp(), 1_700_000_060);
+//! ```
+//!
+//! Elapsed-time measurements (latencies, timeouts) stay on `Instant` and
+//! Tokio's timer, which tests pause with `tokio::time::pause`.
+
+use chrono::{DateTime, TimeZone, Utc};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The operating system's clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to. Clones share the same time.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Arc<Mutex<DateTime<Utc>>>,
+}
+
+impl ManualClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(start)),
+        }
+    }
+
+    /// A clock at `secs` seconds after the Unix epoch.
+    pub fn at(secs: i64) -> Self {
+        Self::new(Utc.timestamp_opt(secs, 0).single().unwrap_or_default())
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.lock() = now;
+    }
+
+    pub fn advance(&self, by: Duration) {
+        let by = chrono::Duration::from_std(by).unwrap_or(chrono::Duration::MAX);
+        let mut now = self.lock();
+        *now = now
+            .checked_add_signed(by)
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, DateTime<Utc>> {
+        self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.lock()
+    }
+}
+
+/// A cheaply cloned handle to a [`Clock`]; the system clock by default.
+#[derive(Clone)]
+pub struct SharedClock(Arc<dyn Clock>);
+
+impl SharedClock {
+    pub fn new(clock: impl Clock + 'static) -> Self {
+        Self(Arc::new(clock))
+    }
+
+    pub fn system() -> Self {
+        Self::new(SystemClock)
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        self.0.now()
+    }
+
+    /// Seconds since the Unix epoch.
+    pub fn timestamp(&self) -> i64 {
+        self.now().timestamp()
+    }
+}
+
+impl Default for SharedClock {
+    fn default() -> Self {
+        Self::system()
+    }
+}
+
+impl fmt::Debug for SharedClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SharedClock").field(&self.now()).finish()
+    }
+}
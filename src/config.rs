@@ -1,5 +1,14 @@
+use crate::auth::AuthMode;
 use crate::error::{NovaError, Result};
+use crate::pipeline::PipelineDefinition;
+use crate::plugins::{ContextIdFormat, PluginContextType, PluginTrustLevel, RequestContext};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::time::Duration;
+
+/// Transports `main` knows how to start.
+const TRANSPORTS: &[&str] = &["stdio", "http"];
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(default)]
@@ -8,22 +17,112 @@ pub struct NovaConfig {
     pub apis: ApiConfig,
     pub cache: CacheConfig,
     pub auth: AuthConfig,
+    pub limits: LimitsConfig,
+    pub compression: CompressionConfig,
+    pub timeouts: TimeoutConfig,
+    pub admin: AdminConfig,
+    pub tools: ToolsConfig,
+    pub storage: StorageConfig,
+    pub outbound: OutboundConfig,
+    pub preferences: PreferencesConfig,
+    pub context: ContextConfig,
+    pub access: AccessConfig,
+    pub plugins: PluginsConfig,
+    pub readiness: ReadinessConfig,
+    pub quotas: QuotasConfig,
+    pub metering: MeteringConfig,
+    pub events: EventsConfig,
+    pub whales: WhalesConfig,
+    pub dylib_tools: DylibToolsConfig,
+    // `[[pipelines]]`: virtual tools composed of other tool calls
+    pub pipelines: Vec<PipelineDefinition>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ServerConfig {
     pub port: u16,
+    // Address the TCP listener binds: "0.0.0.0" (IPv4), "::" (IPv6 and
+    // IPv4), or a single interface such as "127.0.0.1" or "::1"
+    pub bind_address: String,
     pub log_level: String,
     pub transport: String, // "stdio", "sse", "http"
+    // stdio message delimiting: "auto", "newline" or "content-length"
+    pub stdio_framing: String,
+    // HTTP sessions (Mcp-Session-Id) expire after this long without requests
+    pub session_idle_ttl_secs: u64,
+    // Live HTTP sessions at once; `initialize` past it gets 503. 0 removes the cap
+    pub max_sessions: usize,
+    // Request body cap for HTTP routes without an override
+    pub max_body_bytes: usize,
+    // Route path (as registered, e.g. "/rpc") -> body cap in bytes
+    pub route_body_limits: HashMap<String, usize>,
+    // HTTP requests handled at once; 0 removes the cap
+    pub max_concurrent_requests: usize,
+    // Requests waiting for a slot beyond which new ones are shed with 503
+    pub max_queued_requests: usize,
+    // How long a queued request waits for a slot before it is shed
+    pub queue_timeout_ms: u64,
+    // Serve lookups and calls but reject plugin registry writes with 403,
+    // for call-serving replicas behind a single writer
+    pub read_only: bool,
+    // Serve HTTP on this Unix socket path instead of the TCP port
+    pub unix_socket: Option<String>,
+    // Date (YYYY-MM-DD) announced in the `Sunset` header of the deprecated
+    // unversioned routes; empty leaves the header out
+    pub legacy_sunset: String,
+    // Origins (e.g. "https://app.example.com") browsers may call /mcp from;
+    // requests from any other cross-origin page get 403
+    pub allowed_origins: Vec<String>,
+}
+
+impl ServerConfig {
+    /// `bind_address` parsed; `[::1]` is accepted like `::1`.
+    pub fn bind_ip(&self) -> Option<std::net::IpAddr> {
+        let address = self.bind_address.trim();
+        let address = address
+            .strip_prefix('[')
+            .and_then(|inner| inner.strip_suffix(']'))
+            .unwrap_or(address);
+        address.parse().ok()
+    }
+
+    /// `legacy_sunset` parsed; `None` when empty or malformed.
+    pub fn legacy_sunset_date(&self) -> Option<chrono::NaiveDate> {
+        chrono::NaiveDate::parse_from_str(self.legacy_sunset.trim(), "%Y-%m-%d").ok()
+    }
+
+    pub fn body_limit_for(&self, path: &str) -> usize {
+        self.route_body_limits
+            .get(path)
+            .copied()
+            .unwrap_or(self.max_body_bytes)
+    }
 }
 
 impl Default for ServerConfig {
     fn default() -> Self {
         Self {
             port: 8080,
+            bind_address: "0.0.0.0".to_string(),
             log_level: "info".to_string(),
             transport: "stdio".to_string(),
+            stdio_framing: "auto".to_string(),
+            session_idle_ttl_secs: 3600,
+            max_sessions: 10_000,
+            max_body_bytes: 1024 * 1024,
+            // JSON-RPC envelopes are small; keep the hot endpoint tight
+            route_body_limits: HashMap::from([
+                ("/rpc".to_string(), 256 * 1024),
+                ("/mcp".to_string(), 256 * 1024),
+            ]),
+            max_concurrent_requests: 256,
+            max_queued_requests: 512,
+            queue_timeout_ms: 5000,
+            read_only: false,
+            unix_socket: None,
+            legacy_sunset: "2027-07-01".to_string(),
+            allowed_origins: Vec::new(),
         }
     }
 }
@@ -35,6 +134,27 @@ pub struct ApiConfig {
     pub coingecko_api_key: Option<String>,
     pub dexscreener_api_key: Option<String>,
     pub rate_limit_per_minute: u32,
+    // Outbound budget for GeckoTerminal (public tier allows ~30 req/min)
+    pub gecko_terminal_rate_limit_per_minute: u32,
+    // API root for the GeckoTerminal tools, e.g. a mirror; None uses the public one
+    pub gecko_terminal_url: Option<String>,
+    // How long a tool call may queue for an upstream token; 0 fails fast
+    pub upstream_max_wait_ms: u64,
+    // Requests per client IP per minute that fail auth or are malformed;
+    // past it the IP is refused before auth. 0 disables
+    pub pre_auth_rate_limit_per_minute: u32,
+    // JSON-RPC endpoint for the Solana tools; None uses the public mainnet
+    // endpoint. Treated as a secret, since provider URLs often carry a key
+    pub solana_rpc_url: Option<String>,
+    // Outbound budget for the Solana RPC
+    pub solana_rpc_rate_limit_per_minute: u32,
+    // DefiLlama-compatible API serving `/emission/:slug` for `get_token_unlocks`;
+    // None uses the public one. Secret, since Pro URLs carry the key
+    pub token_unlocks_url: Option<String>,
+    pub token_unlocks_rate_limit_per_minute: u32,
+    // Tool `find_arbitrage_spreads` asks for a CEX price with `{ "symbol": ... }`,
+    // e.g. a plugin's fully qualified name; None compares DEX pools only
+    pub cex_price_tool: Option<String>,
 }
 
 impl Default for ApiConfig {
@@ -44,6 +164,15 @@ impl Default for ApiConfig {
             coingecko_api_key: None,
             dexscreener_api_key: None,
             rate_limit_per_minute: 60,
+            gecko_terminal_rate_limit_per_minute: 30,
+            gecko_terminal_url: None,
+            upstream_max_wait_ms: 5000,
+            pre_auth_rate_limit_per_minute: 30,
+            solana_rpc_url: None,
+            solana_rpc_rate_limit_per_minute: 60,
+            token_unlocks_url: None,
+            token_unlocks_rate_limit_per_minute: 30,
+            cex_price_tool: None,
         }
     }
 }
@@ -53,6 +182,12 @@ impl Default for ApiConfig {
 pub struct CacheConfig {
     pub ttl_seconds: u64,
     pub max_entries: usize,
+    // How long an upstream 404 for a token/pool is remembered; 0 disables
+    pub negative_ttl_seconds: u64,
+    // How often the network list behind `network` aliases is refetched; 0 fetches it once
+    pub networks_ttl_seconds: u64,
+    // How long `find_arbitrage_spreads` reuses fetched prices; 0 disables
+    pub arbitrage_ttl_seconds: u64,
 }
 
 impl Default for CacheConfig {
@@ -60,6 +195,9 @@ impl Default for CacheConfig {
         Self {
             ttl_seconds: 300,
             max_entries: 1000,
+            negative_ttl_seconds: 60,
+            networks_ttl_seconds: 3600,
+            arbitrage_ttl_seconds: 30,
         }
     }
 }
@@ -70,7 +208,40 @@ pub struct AuthConfig {
     pub enabled: bool,
     // Comma-separated API keys via env; for production replace with hashed store
     pub allowed_keys: Vec<String>,
+    // Keys by integration name, e.g. `telegram-bot = "..."`; the name is what
+    // logs, rate limits and the audit log attribute requests to
+    pub named_keys: BTreeMap<String, String>,
     pub header_name: String,
+    // "api_key", "telegram" or "jwt"; read at startup only
+    pub mode: String,
+    // Bot token used to check Telegram initData / login-widget signatures
+    pub telegram_bot_token: Option<String>,
+    // Signed Telegram data older than this is rejected
+    pub telegram_max_age_secs: u64,
+    // HS256 shared secret for bearer tokens
+    pub jwt_secret: Option<String>,
+    // JWKS document with the RS256 keys for bearer tokens
+    pub jwt_jwks_url: Option<String>,
+    // Expected `iss` / `aud` claims; unchecked when unset
+    pub jwt_issuer: Option<String>,
+    pub jwt_audience: Option<String>,
+    // How long a fetched JWKS is reused before it is fetched again
+    pub jwt_jwks_cache_secs: u64,
+    // Lifetime of tokens from POST /oauth/token
+    pub oauth_token_ttl_secs: u64,
+    // Wrong API keys from one client IP or key prefix before it is locked
+    // out; 0 disables the lockout
+    pub lockout_threshold: u32,
+    // First lockout; each further failure doubles it up to lockout_max_secs
+    pub lockout_base_secs: u64,
+    pub lockout_max_secs: u64,
+}
+
+impl AuthConfig {
+    /// Parsed `mode`; invalid values are rejected by `validate`.
+    pub fn mode(&self) -> AuthMode {
+        AuthMode::parse(&self.mode).unwrap_or_default()
+    }
 }
 
 impl Default for AuthConfig {
@@ -78,16 +249,1040 @@ impl Default for AuthConfig {
         Self {
             enabled: false,
             allowed_keys: vec![],
+            named_keys: BTreeMap::new(),
             header_name: "x-api-key".to_string(),
+            mode: "api_key".to_string(),
+            telegram_bot_token: None,
+            telegram_max_age_secs: 86_400,
+            jwt_secret: None,
+            jwt_jwks_url: None,
+            jwt_issuer: None,
+            jwt_audience: None,
+            jwt_jwks_cache_secs: 300,
+            oauth_token_ttl_secs: 900,
+            lockout_threshold: 5,
+            lockout_base_secs: 30,
+            lockout_max_secs: 3600,
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LimitsConfig {
+    // Serialized size of `tools/call` arguments
+    pub max_argument_bytes: usize,
+    // Nesting depth of `tools/call` arguments
+    pub max_json_depth: usize,
+    // Tool results beyond this are truncated, with a note in the result
+    pub max_response_bytes: usize,
+    // Tool name (built-in, pipeline or plugin fq_name) -> concurrent calls;
+    // read at startup
+    pub tool_concurrency: HashMap<String, usize>,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_argument_bytes: 64 * 1024,
+            max_json_depth: 32,
+            max_response_bytes: 256 * 1024,
+            tool_concurrency: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompressionConfig {
+    // HTTP transport only; negotiated via Accept-Encoding
+    pub enabled: bool,
+    pub gzip: bool,
+    pub br: bool,
+    // Smaller bodies are sent as-is
+    pub min_size_bytes: u16,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            gzip: true,
+            br: true,
+            min_size_bytes: 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimeoutConfig {
+    // Whole-request ceiling for the HTTP transport (408 when exceeded)
+    pub request_timeout_secs: u64,
+    // Default budget for a single tools/call
+    pub tool_timeout_secs: u64,
+    // Tool name -> seconds, overriding `tool_timeout_secs`
+    pub tool_overrides: HashMap<String, u64>,
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        Self {
+            request_timeout_secs: 30,
+            tool_timeout_secs: 15,
+            tool_overrides: HashMap::new(),
+        }
+    }
+}
+
+impl TimeoutConfig {
+    pub fn for_tool(&self, name: &str) -> Duration {
+        let secs = self
+            .tool_overrides
+            .get(name)
+            .copied()
+            .unwrap_or(self.tool_timeout_secs);
+        Duration::from_secs(secs)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdminConfig {
+    // Operator tokens for /admin; the admin API is disabled when empty
+    pub tokens: Vec<String>,
+    pub header_name: String,
+    // Where POST /admin/backup writes registry snapshots
+    pub backup_dir: String,
+}
+
+impl Default for AdminConfig {
+    fn default() -> Self {
+        Self {
+            tokens: vec![],
+            header_name: "x-admin-token".to_string(),
+            backup_dir: "backups".to_string(),
+        }
+    }
+}
+
+/// Checks `/readyz` runs beyond storage and the plugin registry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReadinessConfig {
+    // Require a successful GeckoTerminal call within canary_max_age_secs,
+    // probing /networks when there was none
+    pub upstream_canary: bool,
+    pub canary_max_age_secs: u64,
+}
+
+impl Default for ReadinessConfig {
+    fn default() -> Self {
+        Self {
+            upstream_canary: false,
+            canary_max_age_secs: 300,
+        }
+    }
+}
+
+/// Tool-call quotas per context, counted per UTC day and calendar month.
+/// 0 leaves a cap off; `/admin/quotas` can override the caps per context.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct QuotasConfig {
+    // Calls to any tool
+    pub daily_calls: u64,
+    pub monthly_calls: u64,
+    // Plugin fq_name -> caps on each context's calls to that plugin
+    pub plugins: HashMap<String, QuotaLimits>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct QuotaLimits {
+    pub daily_calls: u64,
+    pub monthly_calls: u64,
+}
+
+/// Usage events for plugin calls; read at startup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MeteringConfig {
+    pub enabled: bool,
+    // "ledger" (sled tree `metering_ledger`, summed by GET /admin/metering/usage)
+    // and/or "webhook"
+    pub sinks: Vec<String>,
+    // Receives each event as a JSON POST when the webhook sink is listed
+    pub webhook_url: Option<String>,
+}
+
+impl Default for MeteringConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sinks: vec!["ledger".to_string()],
+            webhook_url: None,
+        }
+    }
+}
+
+/// Server events sent to an outside receiver; read at startup.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct EventsConfig {
+    // Receives each event as a JSON POST; unset sends none
+    pub webhook_url: Option<String>,
+    // Event types to send, e.g. "alert_fired"; empty sends every type
+    pub webhook_events: Vec<String>,
+}
+
+/// The `watch_whales` poller; read at startup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WhalesConfig {
+    // How often watched pools are checked for large trades; 0 stops the poller
+    pub poll_interval_seconds: u64,
+    // Pools checked per run, least recently checked first, so many watches
+    // cannot use up the GeckoTerminal budget
+    pub max_pools_per_poll: usize,
+    // Watches one context may hold
+    pub max_watches_per_context: usize,
+    // Smallest `min_volume_usd` a watch may ask for
+    pub min_volume_usd: f64,
+    // Sightings kept per watch; older ones are dropped
+    pub max_sightings_per_watch: usize,
+}
+
+impl Default for WhalesConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_seconds: 60,
+            max_pools_per_poll: 10,
+            max_watches_per_context: 10,
+            min_volume_usd: 10_000.0,
+            max_sightings_per_watch: 100,
+        }
+    }
+}
+
+/// Native tools loaded from shared libraries; needs the `dylib-tools`
+/// feature and is read at startup.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct DylibToolsConfig {
+    // Directory scanned for tool libraries; unset loads none
+    pub dir: Option<String>,
+}
+
+/// Client IP rules for the HTTP transport; entries are CIDRs or single addresses.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct AccessConfig {
+    // Only these clients are served; empty allows everyone
+    pub allow: Vec<String>,
+    // Always rejected, even when also allowed
+    pub deny: Vec<String>,
+    // Additional allowlist for /admin and /contexts; empty adds no restriction
+    pub admin_allow: Vec<String>,
+    // Peers whose X-Forwarded-For is trusted to name the client
+    pub trusted_proxies: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    // sled directory; ":memory:" opens a temporary database removed on exit
+    pub path: String,
+    pub cache_capacity_bytes: u64,
+    // Background flush interval; 0 leaves flushing to explicit calls
+    pub flush_every_ms: u64,
+    pub compression: bool,
+}
+
+impl StorageConfig {
+    pub const MEMORY_PATH: &'static str = ":memory:";
+
+    /// Default tuning with a temporary database.
+    pub fn in_memory() -> Self {
+        Self {
+            path: Self::MEMORY_PATH.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn is_temporary(&self) -> bool {
+        self.path == Self::MEMORY_PATH
+    }
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            path: "nova_mcp_db".to_string(),
+            // sled's own defaults
+            cache_capacity_bytes: 1024 * 1024 * 1024,
+            flush_every_ms: 500,
+            compression: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct OutboundConfig {
+    // http://, https://, socks5:// or socks5h:// proxy for every upstream call
+    pub proxy: Option<String>,
+    // Comma-separated hosts/CIDRs that bypass the proxy
+    pub no_proxy: Option<String>,
+    // PEM files with extra root CAs trusted in addition to the system roots
+    pub ca_certs: Vec<String>,
+    // Per-upstream overrides keyed by "geckoterminal" or "plugins"
+    pub upstreams: HashMap<String, UpstreamOutboundConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct UpstreamOutboundConfig {
+    pub proxy: Option<String>,
+    // Ignore any proxy (including the global one) for this upstream
+    pub direct: bool,
+    // Replaces the global CA list when set
+    pub ca_certs: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ToolsConfig {
+    // Allowlist of built-in tools; None enables all of them
+    pub enabled: Option<Vec<String>>,
+    // Built-in tool names hidden from tools/list and rejected by tools/call
+    pub disabled: Vec<String>,
+    // Built-in tools whose arguments are coerced to their schema before
+    // validation; "*" for all of them
+    pub coerce_arguments: Vec<String>,
+}
+
+impl ToolsConfig {
+    /// Whether a built-in tool is switched on; `disabled` wins over `enabled`.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        let allowed = self
+            .enabled
+            .as_ref()
+            .is_none_or(|enabled| enabled.iter().any(|n| n == name));
+        allowed && !self.disabled.iter().any(|n| n == name)
+    }
+
+    /// Whether a built-in tool's arguments go through
+    /// [`schema::coerce_arguments`](crate::schema::coerce_arguments).
+    pub fn coerces_arguments(&self, name: &str) -> bool {
+        self.coerce_arguments.iter().any(|n| n == name || n == "*")
+    }
+}
+
 // Default is derivable since all fields implement Default
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct PreferencesConfig {
+    // Currency code -> units per 1 USD; contexts may only pick USD or one of these
+    pub usd_rates: HashMap<String, f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContextConfig {
+    // "numeric", "uuid" or "opaque"; read at startup only
+    pub id_format: String,
+    // "<type>:<id>" used for MCP requests that carry no context, so generic
+    // clients such as the MCP Inspector can list and call tools
+    pub default: Option<String>,
+}
+
+impl ContextConfig {
+    /// Parsed `id_format`; invalid values are rejected by `validate`.
+    pub fn id_format(&self) -> ContextIdFormat {
+        ContextIdFormat::parse(&self.id_format).unwrap_or_default()
+    }
+
+    /// Parsed `default`; invalid values are rejected by `validate`.
+    pub fn default_context(&self) -> Option<RequestContext> {
+        self.parse_default().ok().flatten()
+    }
+
+    fn parse_default(&self) -> std::result::Result<Option<RequestContext>, String> {
+        let Some(value) = self.default.as_deref() else {
+            return Ok(None);
+        };
+        let (context_type, context_id) = value
+            .split_once(':')
+            .ok_or_else(|| "must be <type>:<id>, e.g. user:0".to_string())?;
+        let context_type = PluginContextType::parse(context_type)
+            .ok_or_else(|| format!("unknown context type: {}", context_type))?;
+        let context_id = context_id.trim();
+        self.id_format().validate(&context_type, context_id)?;
+        Ok(Some(RequestContext {
+            context_type,
+            context_id: context_id.to_string(),
+            actor_id: None,
+        }))
+    }
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            id_format: "numeric".to_string(),
+            default: None,
+        }
+    }
+}
+
+/// Where plugin endpoints may point; checked at registration and on every call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginsConfig {
+    // URL schemes endpoints may use
+    pub allowed_schemes: Vec<String>,
+    // Let endpoints reach loopback, private, link-local and other internal ranges
+    pub allow_private_networks: bool,
+    // Trust level -> hosts ("api.example.com" or "*.example.com") its plugins
+    // may call; levels without an entry may call any public host
+    pub allowed_domains: HashMap<String, Vec<String>>,
+    // Same-host redirects followed per call; 0 disables redirects
+    pub max_redirects: usize,
+    // Reject registrations and updates whose endpoint host does not resolve
+    pub resolve_endpoints: bool,
+    // Hosts ("schemas.example.com" or "*.example.com") that schema `$ref`s
+    // may be fetched from at registration; empty rejects remote refs
+    pub schema_ref_hosts: Vec<String>,
+    // Base64 32-byte key that encrypts plugin credentials at rest; required
+    // before plugins can register headers or auth
+    pub secrets_key: Option<String>,
+    // Redaction rules applied to every plugin response, on top of each
+    // plugin's own: "$.path.to.field" or field-name patterns like "*_token"
+    pub redact: Vec<String>,
+    // Serve GET /marketplace and POST /marketplace/:id/install
+    pub marketplace: bool,
+    // Reports from distinct contexts that withdraw a listing for admin
+    // review; 0 never withdraws
+    pub report_threshold: usize,
+    // Base URL plugins reach this server at, e.g. "https://nova.example.com";
+    // async calls carry a callback URL under it, and are only polled without
+    pub public_url: Option<String>,
+    // Wait between polls of a running async job, unless the plugin asks for another
+    pub job_poll_interval_ms: u64,
+    // Async jobs still unfinished this long after submission fail
+    pub job_timeout_secs: u64,
+    // HMAC-SHA256 key signing job results sent to callers' `webhook_url`;
+    // webhooks are refused without one
+    pub webhook_secret: Option<String>,
+    // Tries per webhook delivery before it is dead-lettered
+    pub webhook_attempts: u32,
+    // Wait before the first retry, doubled for each one after
+    pub webhook_retry_base_ms: u64,
+    // Scripted plugins: Rhai operations per call, roughly its CPU budget
+    pub script_max_operations: u64,
+    // Scripted plugins: wall-clock budget per call, HTTP calls included
+    pub script_timeout_ms: u64,
+    // Scripted plugins: longest string, and most array or map entries, a
+    // script may build; together they bound its memory
+    pub script_max_string_bytes: usize,
+    pub script_max_collection_items: usize,
+    // Scripted plugins: `http_get`/`http_post` calls per call; 0 disables them
+    pub script_max_http_calls: usize,
+}
+
+impl Default for PluginsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_schemes: vec!["https".to_string()],
+            allow_private_networks: false,
+            allowed_domains: HashMap::new(),
+            max_redirects: 0,
+            resolve_endpoints: false,
+            schema_ref_hosts: Vec::new(),
+            secrets_key: None,
+            redact: Vec::new(),
+            marketplace: true,
+            report_threshold: 5,
+            public_url: None,
+            job_poll_interval_ms: 5_000,
+            job_timeout_secs: 3_600,
+            webhook_secret: None,
+            webhook_attempts: 5,
+            webhook_retry_base_ms: 1_000,
+            script_max_operations: 1_000_000,
+            script_timeout_ms: 5_000,
+            script_max_string_bytes: 1024 * 1024,
+            script_max_collection_items: 10_000,
+            script_max_http_calls: 5,
+        }
+    }
+}
+
+/// Command-line overrides; these take precedence over env, file and defaults.
+#[derive(Debug, Clone, Default)]
+pub struct CliArgs {
+    pub config_path: Option<String>,
+    pub port: Option<u16>,
+    pub transport: Option<String>,
+    pub log_level: Option<String>,
+    /// Keep all state in a temporary database that is removed on exit.
+    pub ephemeral: bool,
+    /// Run the `doctor` self-test instead of serving.
+    pub doctor: bool,
+}
+
+impl CliArgs {
+    /// Parses `--config`, `--port`, `--transport` and `--log-level` (`--flag value` or `--flag=value`),
+    /// the bare `--ephemeral` and the `doctor` command.
+    pub fn parse<I>(args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut cli = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| NovaError::config_error(format!("{} requires a value", flag)))
+            };
+            match flag.as_str() {
+                "--config" => cli.config_path = Some(value()?),
+                "--port" => {
+                    cli.port = Some(
+                        value()?
+                            .parse()
+                            .map_err(|_| NovaError::config_error("Invalid --port"))?,
+                    )
+                }
+                "--transport" => cli.transport = Some(value()?),
+                "--log-level" => cli.log_level = Some(value()?),
+                "--ephemeral" => {
+                    if inline.is_some() {
+                        return Err(NovaError::config_error("--ephemeral takes no value"));
+                    }
+                    cli.ephemeral = true
+                }
+                "doctor" if inline.is_none() => cli.doctor = true,
+                _ => {
+                    return Err(NovaError::config_error(format!(
+                        "Unknown argument: {}",
+                        flag
+                    )))
+                }
+            }
+        }
+        Ok(cli)
+    }
+
+    fn apply(&self, config: &mut NovaConfig) {
+        if let Some(port) = self.port {
+            config.server.port = port;
+        }
+        if let Some(transport) = &self.transport {
+            config.server.transport = transport.clone();
+        }
+        if let Some(log_level) = &self.log_level {
+            config.server.log_level = log_level.clone();
+        }
+        if self.ephemeral {
+            config.storage.path = StorageConfig::MEMORY_PATH.to_string();
+        }
+    }
+}
+
+/// One problem found by [`NovaConfig::validate`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigIssue {
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
 impl NovaConfig {
+    /// Loads config without CLI overrides; see [`NovaConfig::load_with`].
+    pub fn load() -> Result<Self> {
+        Self::load_with(&CliArgs::default())
+    }
+
+    /// Resolves config with precedence CLI > env > file > defaults, then validates it.
+    ///
+    /// The file is `--config` if given, else `NOVA_MCP_CONFIG`; without either
+    /// only defaults and env apply.
+    pub fn load_with(cli: &CliArgs) -> Result<Self> {
+        let path = cli
+            .config_path
+            .clone()
+            .or_else(|| std::env::var("NOVA_MCP_CONFIG").ok())
+            .filter(|path| !path.trim().is_empty());
+        let base = match path {
+            Some(path) => Self::from_file(path.trim())?,
+            None => Self::default(),
+        };
+        let mut config = base.with_env_overrides()?;
+        cli.apply(&mut config);
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks the whole config and reports every problem at once.
+    pub fn validate(&self) -> Result<()> {
+        let mut issues = Vec::new();
+        let mut check = |ok: bool, field: &str, message: &str| {
+            if !ok {
+                issues.push(ConfigIssue {
+                    field: field.to_string(),
+                    message: message.to_string(),
+                });
+            }
+        };
+
+        check(
+            self.server.port != 0,
+            "server.port",
+            "must be between 1 and 65535",
+        );
+        check(
+            TRANSPORTS.contains(&self.server.transport.to_lowercase().as_str()),
+            "server.transport",
+            "must be one of: stdio, http",
+        );
+        check(
+            crate::stdio::Framing::parse(&self.server.stdio_framing).is_some(),
+            "server.stdio_framing",
+            "must be one of: auto, newline, content-length",
+        );
+        check(
+            self.server.bind_ip().is_some(),
+            "server.bind_address",
+            "must be an IP address such as 0.0.0.0, ::, 127.0.0.1 or ::1",
+        );
+        check(
+            self.server
+                .unix_socket
+                .as_deref()
+                .is_none_or(|path| cfg!(unix) && !path.is_empty()),
+            "server.unix_socket",
+            "must be a non-empty path, on Unix platforms",
+        );
+        check(
+            self.server.allowed_origins.iter().all(|origin| {
+                reqwest::Url::parse(origin).is_ok_and(|url| {
+                    matches!(url.scheme(), "http" | "https")
+                        && url.origin().ascii_serialization() == origin.trim_end_matches('/')
+                })
+            }),
+            "server.allowed_origins",
+            "must be origins such as https://app.example.com, without a path",
+        );
+        check(
+            self.server.legacy_sunset.trim().is_empty()
+                || self.server.legacy_sunset_date().is_some(),
+            "server.legacy_sunset",
+            "must be a date such as 2027-07-01, or empty",
+        );
+        check(
+            self.server.max_concurrent_requests == 0 || self.server.queue_timeout_ms > 0,
+            "server.queue_timeout_ms",
+            "must be greater than 0",
+        );
+        check(
+            self.server.max_body_bytes > 0,
+            "server.max_body_bytes",
+            "must be greater than 0",
+        );
+        check(
+            !self.auth.enabled
+                || !self.auth.allowed_keys.is_empty()
+                || !self.auth.named_keys.is_empty(),
+            "auth.allowed_keys",
+            "must not be empty when auth is enabled",
+        );
+        check(
+            self.auth
+                .named_keys
+                .iter()
+                .all(|(name, key)| !name.trim().is_empty() && !key.is_empty()),
+            "auth.named_keys",
+            "names and keys must not be empty",
+        );
+        check(
+            !self.auth.header_name.trim().is_empty(),
+            "auth.header_name",
+            "must not be empty",
+        );
+        check(
+            AuthMode::parse(&self.auth.mode).is_some(),
+            "auth.mode",
+            "must be one of: api_key, telegram, jwt",
+        );
+        let set = |value: &Option<String>| value.as_deref().is_some_and(|v| !v.trim().is_empty());
+        check(
+            self.auth.mode() != AuthMode::Telegram || set(&self.auth.telegram_bot_token),
+            "auth.telegram_bot_token",
+            "must be set when auth.mode is telegram",
+        );
+        check(
+            self.auth.mode() != AuthMode::Jwt
+                || set(&self.auth.jwt_secret)
+                || set(&self.auth.jwt_jwks_url),
+            "auth.jwt_secret",
+            "auth.jwt_secret or auth.jwt_jwks_url must be set when auth.mode is jwt",
+        );
+        check(
+            self.auth.telegram_max_age_secs > 0,
+            "auth.telegram_max_age_secs",
+            "must be greater than 0",
+        );
+        check(
+            self.auth.oauth_token_ttl_secs > 0,
+            "auth.oauth_token_ttl_secs",
+            "must be greater than 0",
+        );
+        check(
+            self.auth.lockout_threshold == 0 || self.auth.lockout_base_secs > 0,
+            "auth.lockout_base_secs",
+            "must be greater than 0",
+        );
+        check(
+            self.auth.lockout_threshold == 0
+                || self.auth.lockout_max_secs >= self.auth.lockout_base_secs,
+            "auth.lockout_max_secs",
+            "must be at least auth.lockout_base_secs",
+        );
+        check(
+            self.apis.rate_limit_per_minute > 0,
+            "apis.rate_limit_per_minute",
+            "must be greater than 0",
+        );
+        if let Some(url) = self.apis.gecko_terminal_url.as_deref() {
+            check(
+                reqwest::Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https")),
+                "apis.gecko_terminal_url",
+                "must be an http(s):// URL",
+            );
+        }
+        if let Some(url) = self.apis.solana_rpc_url.as_deref() {
+            check(
+                reqwest::Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https")),
+                "apis.solana_rpc_url",
+                "must be an http(s):// URL",
+            );
+        }
+        if let Some(url) = self.apis.token_unlocks_url.as_deref() {
+            check(
+                reqwest::Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https")),
+                "apis.token_unlocks_url",
+                "must be an http(s):// URL",
+            );
+        }
+        if let Some(tool) = self.apis.cex_price_tool.as_deref() {
+            check(
+                !tool.trim().is_empty() && tool != "find_arbitrage_spreads",
+                "apis.cex_price_tool",
+                "must name another tool",
+            );
+        }
+        check(
+            self.timeouts.request_timeout_secs > 0,
+            "timeouts.request_timeout_secs",
+            "must be greater than 0",
+        );
+        check(
+            self.timeouts.tool_timeout_secs > 0,
+            "timeouts.tool_timeout_secs",
+            "must be greater than 0",
+        );
+        check(
+            !self.storage.path.trim().is_empty(),
+            "storage.path",
+            "must not be empty (use \":memory:\" for a temporary database)",
+        );
+        check(
+            self.limits.max_response_bytes > 0,
+            "limits.max_response_bytes",
+            "must be greater than 0",
+        );
+        check(
+            self.limits.tool_concurrency.values().all(|cap| *cap > 0),
+            "limits.tool_concurrency",
+            "caps must be greater than 0",
+        );
+        check(
+            self.readiness.canary_max_age_secs > 0,
+            "readiness.canary_max_age_secs",
+            "must be greater than 0",
+        );
+
+        let unknown_tools = self
+            .tools
+            .enabled
+            .iter()
+            .flatten()
+            .chain(&self.tools.disabled)
+            .chain(
+                self.tools
+                    .coerce_arguments
+                    .iter()
+                    .filter(|name| *name != "*"),
+            )
+            .filter(|name| !crate::server::BUILTIN_TOOLS.contains(&name.as_str()))
+            .cloned()
+            .collect::<Vec<_>>();
+        check(
+            unknown_tools.is_empty(),
+            "tools",
+            &format!("unknown built-in tools: {}", unknown_tools.join(", ")),
+        );
+
+        for (field, entries) in [
+            ("access.allow", &self.access.allow),
+            ("access.deny", &self.access.deny),
+            ("access.admin_allow", &self.access.admin_allow),
+            ("access.trusted_proxies", &self.access.trusted_proxies),
+        ] {
+            let invalid = entries
+                .iter()
+                .filter(|entry| crate::http::access::parse_net(entry).is_none())
+                .cloned()
+                .collect::<Vec<_>>();
+            check(
+                invalid.is_empty(),
+                field,
+                &format!("invalid CIDR or address: {}", invalid.join(", ")),
+            );
+        }
+
+        check(
+            !self.plugins.allowed_schemes.is_empty(),
+            "plugins.allowed_schemes",
+            "must list at least one scheme",
+        );
+        check(
+            self.plugins
+                .secrets_key
+                .as_deref()
+                .is_none_or(|key| crate::plugins::SecretBox::from_base64(key).is_some()),
+            "plugins.secrets_key",
+            "must be 32 bytes, base64-encoded",
+        );
+        if let Err(err) = crate::plugins::RedactionRules::parse(&self.plugins.redact) {
+            check(false, "plugins.redact", &err);
+        }
+        check(
+            self.plugins.public_url.as_deref().is_none_or(|url| {
+                reqwest::Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+            }),
+            "plugins.public_url",
+            "must be an absolute http or https URL",
+        );
+        check(
+            self.plugins.job_poll_interval_ms > 0,
+            "plugins.job_poll_interval_ms",
+            "must be greater than 0",
+        );
+        check(
+            self.plugins.job_timeout_secs > 0,
+            "plugins.job_timeout_secs",
+            "must be greater than 0",
+        );
+        check(
+            self.plugins.webhook_attempts > 0,
+            "plugins.webhook_attempts",
+            "must be greater than 0",
+        );
+        for (value, field) in [
+            (
+                self.plugins.script_max_operations,
+                "plugins.script_max_operations",
+            ),
+            (self.plugins.script_timeout_ms, "plugins.script_timeout_ms"),
+            (
+                self.plugins.script_max_string_bytes as u64,
+                "plugins.script_max_string_bytes",
+            ),
+            (
+                self.plugins.script_max_collection_items as u64,
+                "plugins.script_max_collection_items",
+            ),
+        ] {
+            check(value > 0, field, "must be greater than 0");
+        }
+        for level in self.plugins.allowed_domains.keys() {
+            check(
+                PluginTrustLevel::parse(level).is_some(),
+                &format!("plugins.allowed_domains.{}", level),
+                "must be keyed by a trust level: standard, high",
+            );
+        }
+
+        if let Err(err) = crate::pipeline::PipelineRegistry::new(self.pipelines.clone()) {
+            check(false, "pipelines", &err);
+        }
+
+        check(
+            ContextIdFormat::parse(&self.context.id_format).is_some(),
+            "context.id_format",
+            "must be one of: numeric, uuid, opaque",
+        );
+        if let Err(err) = self.context.parse_default() {
+            check(false, "context.default", &err);
+        }
+
+        let quota_limits = std::iter::once((
+            "quotas".to_string(),
+            QuotaLimits {
+                daily_calls: self.quotas.daily_calls,
+                monthly_calls: self.quotas.monthly_calls,
+            },
+        ))
+        .chain(
+            self.quotas
+                .plugins
+                .iter()
+                .map(|(name, limits)| (format!("quotas.plugins.{}", name), *limits)),
+        );
+        for (field, limits) in quota_limits {
+            check(
+                limits.daily_calls == 0
+                    || limits.monthly_calls == 0
+                    || limits.daily_calls <= limits.monthly_calls,
+                &format!("{}.daily_calls", field),
+                "must not exceed monthly_calls",
+            );
+        }
+
+        if self.metering.enabled {
+            for sink in &self.metering.sinks {
+                check(
+                    matches!(sink.as_str(), "ledger" | "webhook"),
+                    "metering.sinks",
+                    "must list only: ledger, webhook",
+                );
+            }
+            if self.metering.sinks.iter().any(|sink| sink == "webhook") {
+                let url = self.metering.webhook_url.as_deref().unwrap_or_default();
+                check(
+                    reqwest::Url::parse(url)
+                        .is_ok_and(|url| matches!(url.scheme(), "http" | "https")),
+                    "metering.webhook_url",
+                    "must be an http(s):// URL when the webhook sink is listed",
+                );
+            }
+        }
+
+        if let Some(url) = self.events.webhook_url.as_deref() {
+            check(
+                reqwest::Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https")),
+                "events.webhook_url",
+                "must be an http(s):// URL",
+            );
+        }
+        check(
+            self.whales.max_pools_per_poll > 0,
+            "whales.max_pools_per_poll",
+            "must be greater than 0",
+        );
+        check(
+            self.whales.min_volume_usd.is_finite() && self.whales.min_volume_usd >= 0.0,
+            "whales.min_volume_usd",
+            "must be a non-negative number",
+        );
+        check(
+            self.whales.max_sightings_per_watch > 0,
+            "whales.max_sightings_per_watch",
+            "must be greater than 0",
+        );
+        check(
+            self.dylib_tools.dir.is_none() || cfg!(feature = "dylib-tools"),
+            "dylib_tools.dir",
+            "needs a build with the dylib-tools feature",
+        );
+        for kind in &self.events.webhook_events {
+            check(
+                crate::events::EVENT_TYPES.contains(&kind.as_str()),
+                "events.webhook_events",
+                &format!(
+                    "unknown event type {}; expected one of: {}",
+                    kind,
+                    crate::events::EVENT_TYPES.join(", ")
+                ),
+            );
+        }
+
+        for (code, rate) in &self.preferences.usd_rates {
+            check(
+                code.len() == 3 && code.chars().all(|c| c.is_ascii_uppercase()),
+                &format!("preferences.usd_rates.{}", code),
+                "must be keyed by a three-letter uppercase currency code",
+            );
+            check(
+                rate.is_finite() && *rate > 0.0,
+                &format!("preferences.usd_rates.{}", code),
+                "must be a positive number",
+            );
+        }
+
+        let mut proxies: Vec<(String, &String)> = self
+            .outbound
+            .proxy
+            .iter()
+            .map(|url| ("outbound.proxy".to_string(), url))
+            .collect();
+        let mut ca_certs: Vec<(String, &String)> = self
+            .outbound
+            .ca_certs
+            .iter()
+            .map(|path| ("outbound.ca_certs".to_string(), path))
+            .collect();
+        for (name, upstream) in &self.outbound.upstreams {
+            if let Some(url) = &upstream.proxy {
+                proxies.push((format!("outbound.upstreams.{}.proxy", name), url));
+            }
+            for path in upstream.ca_certs.iter().flatten() {
+                ca_certs.push((format!("outbound.upstreams.{}.ca_certs", name), path));
+            }
+        }
+        for (field, url) in proxies {
+            check(
+                reqwest::Proxy::all(url.as_str()).is_ok(),
+                &field,
+                "must be an http(s):// or socks5:// URL",
+            );
+        }
+        for (field, path) in ca_certs {
+            check(
+                crate::outbound::load_certificates(path).is_ok(),
+                &field,
+                "must point to a readable PEM file with at least one certificate",
+            );
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(NovaError::InvalidConfig { issues })
+        }
+    }
+
     pub fn from_env() -> Result<Self> {
-        let mut config = Self::default();
+        Self::default().with_env_overrides()
+    }
+
+    fn with_env_overrides(self) -> Result<Self> {
+        let mut config = self;
 
         // Override with environment variables
         if let Ok(port) = std::env::var("NOVA_MCP_PORT") {
@@ -104,9 +1299,189 @@ impl NovaConfig {
             config.server.transport = transport;
         }
 
-        config.apis.uniswap_api_key = std::env::var("UNISWAP_API_KEY").ok();
-        config.apis.coingecko_api_key = std::env::var("COINGECKO_API_KEY").ok();
-        config.apis.dexscreener_api_key = std::env::var("DEXSCREENER_API_KEY").ok();
+        if let Ok(framing) = std::env::var("NOVA_MCP_STDIO_FRAMING") {
+            config.server.stdio_framing = framing;
+        }
+
+        if let Ok(id_format) = std::env::var("NOVA_MCP_CONTEXT_ID_FORMAT") {
+            config.context.id_format = id_format;
+        }
+
+        if let Ok(context) = std::env::var("NOVA_MCP_DEFAULT_CONTEXT") {
+            config.context.default = Some(context).filter(|value| !value.trim().is_empty());
+        }
+
+        if let Ok(address) = std::env::var("NOVA_MCP_BIND_ADDRESS") {
+            config.server.bind_address = address;
+        }
+        if let Ok(path) = std::env::var("NOVA_MCP_UNIX_SOCKET") {
+            config.server.unix_socket = Some(path).filter(|path| !path.is_empty());
+        }
+        if let Ok(sunset) = std::env::var("NOVA_MCP_LEGACY_SUNSET") {
+            config.server.legacy_sunset = sunset;
+        }
+        if let Ok(origins) = std::env::var("NOVA_MCP_ALLOWED_ORIGINS") {
+            config.server.allowed_origins = origins
+                .split(',')
+                .map(|origin| origin.trim().to_string())
+                .filter(|origin| !origin.is_empty())
+                .collect();
+        }
+        if let Ok(read_only) = std::env::var("NOVA_MCP_READ_ONLY") {
+            config.server.read_only =
+                matches!(read_only.as_str(), "1" | "true" | "TRUE" | "yes" | "on");
+        }
+        if let Ok(secs) = std::env::var("NOVA_MCP_SESSION_IDLE_TTL_SECS") {
+            config.server.session_idle_ttl_secs = secs
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_SESSION_IDLE_TTL_SECS"))?;
+        }
+        if let Ok(max) = std::env::var("NOVA_MCP_MAX_SESSIONS") {
+            config.server.max_sessions = max
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_MAX_SESSIONS"))?;
+        }
+
+        if let Ok(bytes) = std::env::var("NOVA_MCP_MAX_BODY_BYTES") {
+            config.server.max_body_bytes = bytes
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_MAX_BODY_BYTES"))?;
+        }
+        if let Ok(bytes) = std::env::var("NOVA_MCP_RPC_MAX_BODY_BYTES") {
+            let bytes = bytes
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_RPC_MAX_BODY_BYTES"))?;
+            config
+                .server
+                .route_body_limits
+                .insert("/rpc".to_string(), bytes);
+        }
+
+        if let Ok(key) = std::env::var("UNISWAP_API_KEY") {
+            config.apis.uniswap_api_key = Some(key);
+        }
+        if let Ok(key) = std::env::var("COINGECKO_API_KEY") {
+            config.apis.coingecko_api_key = Some(key);
+        }
+        if let Ok(key) = std::env::var("DEXSCREENER_API_KEY") {
+            config.apis.dexscreener_api_key = Some(key);
+        }
+        if let Ok(limit) = std::env::var("NOVA_MCP_RATE_LIMIT_PER_MINUTE") {
+            config.apis.rate_limit_per_minute = limit
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_RATE_LIMIT_PER_MINUTE"))?;
+        }
+        if let Ok(limit) = std::env::var("GECKO_TERMINAL_RATE_LIMIT_PER_MINUTE") {
+            config.apis.gecko_terminal_rate_limit_per_minute = limit.parse().map_err(|_| {
+                NovaError::config_error("Invalid GECKO_TERMINAL_RATE_LIMIT_PER_MINUTE")
+            })?;
+        }
+        if let Ok(wait) = std::env::var("NOVA_MCP_UPSTREAM_MAX_WAIT_MS") {
+            config.apis.upstream_max_wait_ms = wait
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_UPSTREAM_MAX_WAIT_MS"))?;
+        }
+        if let Ok(limit) = std::env::var("NOVA_MCP_PRE_AUTH_RATE_LIMIT_PER_MINUTE") {
+            config.apis.pre_auth_rate_limit_per_minute = limit.parse().map_err(|_| {
+                NovaError::config_error("Invalid NOVA_MCP_PRE_AUTH_RATE_LIMIT_PER_MINUTE")
+            })?;
+        }
+        if let Ok(url) = std::env::var("GECKO_TERMINAL_BASE_URL") {
+            config.apis.gecko_terminal_url = Some(url).filter(|url| !url.is_empty());
+        }
+        if let Ok(url) = std::env::var("SOLANA_RPC_URL") {
+            config.apis.solana_rpc_url = Some(url).filter(|url| !url.is_empty());
+        }
+        if let Ok(limit) = std::env::var("SOLANA_RPC_RATE_LIMIT_PER_MINUTE") {
+            config.apis.solana_rpc_rate_limit_per_minute = limit
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid SOLANA_RPC_RATE_LIMIT_PER_MINUTE"))?;
+        }
+        if let Ok(url) = std::env::var("TOKEN_UNLOCKS_URL") {
+            config.apis.token_unlocks_url = Some(url).filter(|url| !url.is_empty());
+        }
+        if let Ok(limit) = std::env::var("TOKEN_UNLOCKS_RATE_LIMIT_PER_MINUTE") {
+            config.apis.token_unlocks_rate_limit_per_minute = limit.parse().map_err(|_| {
+                NovaError::config_error("Invalid TOKEN_UNLOCKS_RATE_LIMIT_PER_MINUTE")
+            })?;
+        }
+
+        if let Ok(limit) = std::env::var("NOVA_MCP_QUOTA_DAILY_CALLS") {
+            config.quotas.daily_calls = limit
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_QUOTA_DAILY_CALLS"))?;
+        }
+        if let Ok(limit) = std::env::var("NOVA_MCP_QUOTA_MONTHLY_CALLS") {
+            config.quotas.monthly_calls = limit
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_QUOTA_MONTHLY_CALLS"))?;
+        }
+
+        if let Ok(enabled) = std::env::var("NOVA_MCP_METERING_ENABLED") {
+            config.metering.enabled =
+                matches!(enabled.as_str(), "1" | "true" | "TRUE" | "yes" | "on");
+        }
+        if let Ok(url) = std::env::var("NOVA_MCP_METERING_WEBHOOK_URL") {
+            config.metering.webhook_url = Some(url);
+            if !config.metering.sinks.iter().any(|sink| sink == "webhook") {
+                config.metering.sinks.push("webhook".to_string());
+            }
+        }
+
+        if let Ok(url) = std::env::var("NOVA_MCP_EVENTS_WEBHOOK_URL") {
+            config.events.webhook_url = Some(url).filter(|url| !url.is_empty());
+        }
+        if let Ok(dir) = std::env::var("NOVA_MCP_DYLIB_TOOLS_DIR") {
+            config.dylib_tools.dir = Some(dir).filter(|dir| !dir.is_empty());
+        }
+        if let Ok(kinds) = std::env::var("NOVA_MCP_EVENTS_WEBHOOK_EVENTS") {
+            config.events.webhook_events = kinds
+                .split(',')
+                .map(|kind| kind.trim().to_string())
+                .filter(|kind| !kind.is_empty())
+                .collect();
+        }
+
+        if let Ok(ttl) = std::env::var("NOVA_MCP_NEGATIVE_CACHE_TTL_SECONDS") {
+            config.cache.negative_ttl_seconds = ttl.parse().map_err(|_| {
+                NovaError::config_error("Invalid NOVA_MCP_NEGATIVE_CACHE_TTL_SECONDS")
+            })?;
+        }
+
+        // Payload limits
+        if let Ok(value) = std::env::var("NOVA_MCP_MAX_ARGUMENT_BYTES") {
+            config.limits.max_argument_bytes = value
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_MAX_ARGUMENT_BYTES"))?;
+        }
+        if let Ok(value) = std::env::var("NOVA_MCP_MAX_JSON_DEPTH") {
+            config.limits.max_json_depth = value
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_MAX_JSON_DEPTH"))?;
+        }
+        if let Ok(value) = std::env::var("NOVA_MCP_MAX_RESPONSE_BYTES") {
+            config.limits.max_response_bytes = value
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_MAX_RESPONSE_BYTES"))?;
+        }
+
+        // Response compression (HTTP)
+        if let Ok(enabled) = std::env::var("NOVA_MCP_COMPRESSION") {
+            config.compression.enabled =
+                matches!(enabled.as_str(), "1" | "true" | "TRUE" | "yes" | "on");
+        }
+
+        // Timeouts
+        if let Ok(secs) = std::env::var("NOVA_MCP_REQUEST_TIMEOUT_SECS") {
+            config.timeouts.request_timeout_secs = secs
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_REQUEST_TIMEOUT_SECS"))?;
+        }
+        if let Ok(secs) = std::env::var("NOVA_MCP_TOOL_TIMEOUT_SECS") {
+            config.timeouts.tool_timeout_secs = secs
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_TOOL_TIMEOUT_SECS"))?;
+        }
 
         // Auth configuration
         if let Ok(enabled) = std::env::var("NOVA_MCP_AUTH_ENABLED") {
@@ -122,15 +1497,275 @@ impl NovaConfig {
                 config.auth.allowed_keys = list;
             }
         }
+        if let Ok(keys) = std::env::var("NOVA_MCP_NAMED_API_KEYS") {
+            // `name=key` pairs; keys may contain `=`, names may not
+            let named = keys
+                .split(',')
+                .map(str::trim)
+                .filter(|pair| !pair.is_empty())
+                .map(|pair| {
+                    pair.split_once('=')
+                        .map(|(name, key)| (name.trim().to_string(), key.to_string()))
+                        .ok_or_else(|| {
+                            NovaError::config_error("Invalid NOVA_MCP_NAMED_API_KEYS entry")
+                        })
+                })
+                .collect::<Result<BTreeMap<_, _>>>()?;
+            if !named.is_empty() {
+                config.auth.named_keys = named;
+            }
+        }
         if let Ok(header_name) = std::env::var("NOVA_MCP_AUTH_HEADER") {
             if !header_name.trim().is_empty() {
                 config.auth.header_name = header_name;
             }
         }
+        if let Ok(mode) = std::env::var("NOVA_MCP_AUTH_MODE") {
+            config.auth.mode = mode;
+        }
+        if let Ok(token) = std::env::var("NOVA_MCP_TELEGRAM_BOT_TOKEN") {
+            if !token.trim().is_empty() {
+                config.auth.telegram_bot_token = Some(token);
+            }
+        }
+        if let Ok(secs) = std::env::var("NOVA_MCP_TELEGRAM_MAX_AGE_SECS") {
+            config.auth.telegram_max_age_secs = secs
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_TELEGRAM_MAX_AGE_SECS"))?;
+        }
+        if let Ok(secret) = std::env::var("NOVA_MCP_JWT_SECRET") {
+            config.auth.jwt_secret = Some(secret);
+        }
+        if let Ok(url) = std::env::var("NOVA_MCP_JWT_JWKS_URL") {
+            config.auth.jwt_jwks_url = Some(url);
+        }
+        if let Ok(issuer) = std::env::var("NOVA_MCP_JWT_ISSUER") {
+            config.auth.jwt_issuer = Some(issuer);
+        }
+        if let Ok(audience) = std::env::var("NOVA_MCP_JWT_AUDIENCE") {
+            config.auth.jwt_audience = Some(audience);
+        }
+        if let Ok(secs) = std::env::var("NOVA_MCP_OAUTH_TOKEN_TTL_SECS") {
+            config.auth.oauth_token_ttl_secs = secs
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_OAUTH_TOKEN_TTL_SECS"))?;
+        }
+        if let Ok(threshold) = std::env::var("NOVA_MCP_AUTH_LOCKOUT_THRESHOLD") {
+            config.auth.lockout_threshold = threshold
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_AUTH_LOCKOUT_THRESHOLD"))?;
+        }
+
+        for (name, entries) in [
+            ("NOVA_MCP_ALLOW_IPS", &mut config.access.allow),
+            ("NOVA_MCP_DENY_IPS", &mut config.access.deny),
+            ("NOVA_MCP_ADMIN_ALLOW_IPS", &mut config.access.admin_allow),
+            (
+                "NOVA_MCP_TRUSTED_PROXIES",
+                &mut config.access.trusted_proxies,
+            ),
+        ] {
+            if let Ok(list) = std::env::var(name) {
+                *entries = list
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect();
+            }
+        }
+
+        if let Ok(schemes) = std::env::var("NOVA_MCP_PLUGIN_SCHEMES") {
+            config.plugins.allowed_schemes = schemes
+                .split(',')
+                .map(|s| s.trim().to_lowercase())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        if let Ok(allow) = std::env::var("NOVA_MCP_PLUGIN_ALLOW_PRIVATE") {
+            config.plugins.allow_private_networks =
+                matches!(allow.as_str(), "1" | "true" | "TRUE" | "yes" | "on");
+        }
+        if let Ok(resolve) = std::env::var("NOVA_MCP_PLUGIN_RESOLVE_ENDPOINTS") {
+            config.plugins.resolve_endpoints =
+                matches!(resolve.as_str(), "1" | "true" | "TRUE" | "yes" | "on");
+        }
+        if let Ok(hosts) = std::env::var("NOVA_MCP_PLUGIN_SCHEMA_REF_HOSTS") {
+            config.plugins.schema_ref_hosts = hosts
+                .split(',')
+                .map(|s| s.trim().to_lowercase())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        if let Ok(key) = std::env::var("NOVA_MCP_PLUGIN_SECRETS_KEY") {
+            config.plugins.secrets_key = Some(key);
+        }
+        if let Ok(hops) = std::env::var("NOVA_MCP_PLUGIN_MAX_REDIRECTS") {
+            config.plugins.max_redirects = hops
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_PLUGIN_MAX_REDIRECTS"))?;
+        }
+        if let Ok(threshold) = std::env::var("NOVA_MCP_PLUGIN_REPORT_THRESHOLD") {
+            config.plugins.report_threshold = threshold
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_PLUGIN_REPORT_THRESHOLD"))?;
+        }
+        if let Ok(url) = std::env::var("NOVA_MCP_PUBLIC_URL") {
+            config.plugins.public_url = Some(url).filter(|url| !url.trim().is_empty());
+        }
+        if let Ok(interval) = std::env::var("NOVA_MCP_JOB_POLL_INTERVAL_MS") {
+            config.plugins.job_poll_interval_ms = interval
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_JOB_POLL_INTERVAL_MS"))?;
+        }
+        if let Ok(timeout) = std::env::var("NOVA_MCP_JOB_TIMEOUT_SECS") {
+            config.plugins.job_timeout_secs = timeout
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_JOB_TIMEOUT_SECS"))?;
+        }
+        if let Ok(secret) = std::env::var("NOVA_MCP_WEBHOOK_SECRET") {
+            config.plugins.webhook_secret = Some(secret).filter(|secret| !secret.is_empty());
+        }
+        if let Ok(attempts) = std::env::var("NOVA_MCP_WEBHOOK_ATTEMPTS") {
+            config.plugins.webhook_attempts = attempts
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_WEBHOOK_ATTEMPTS"))?;
+        }
+        if let Ok(delay) = std::env::var("NOVA_MCP_WEBHOOK_RETRY_BASE_MS") {
+            config.plugins.webhook_retry_base_ms = delay
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_WEBHOOK_RETRY_BASE_MS"))?;
+        }
+        if let Ok(operations) = std::env::var("NOVA_MCP_SCRIPT_MAX_OPERATIONS") {
+            config.plugins.script_max_operations = operations
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_SCRIPT_MAX_OPERATIONS"))?;
+        }
+        if let Ok(timeout) = std::env::var("NOVA_MCP_SCRIPT_TIMEOUT_MS") {
+            config.plugins.script_timeout_ms = timeout
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_SCRIPT_TIMEOUT_MS"))?;
+        }
+        if let Ok(marketplace) = std::env::var("NOVA_MCP_PLUGIN_MARKETPLACE") {
+            config.plugins.marketplace =
+                matches!(marketplace.as_str(), "1" | "true" | "TRUE" | "yes" | "on");
+        }
+
+        if let Ok(names) = std::env::var("NOVA_MCP_ENABLED_TOOLS") {
+            config.tools.enabled = Some(
+                names
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+            );
+        }
+        if let Ok(names) = std::env::var("NOVA_MCP_DISABLED_TOOLS") {
+            config.tools.disabled = names
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        if let Ok(names) = std::env::var("NOVA_MCP_COERCE_TOOLS") {
+            config.tools.coerce_arguments = names
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+
+        // Outbound HTTP
+        if let Ok(proxy) = std::env::var("NOVA_MCP_PROXY") {
+            if !proxy.trim().is_empty() {
+                config.outbound.proxy = Some(proxy.trim().to_string());
+            }
+        }
+        if let Ok(no_proxy) = std::env::var("NOVA_MCP_NO_PROXY") {
+            config.outbound.no_proxy = Some(no_proxy);
+        }
+        if let Ok(paths) = std::env::var("NOVA_MCP_CA_CERTS") {
+            config.outbound.ca_certs = paths
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+
+        // Storage
+        if let Ok(path) = std::env::var("NOVA_MCP_DB_PATH") {
+            if !path.trim().is_empty() {
+                config.storage.path = path;
+            }
+        }
+        if let Ok(bytes) = std::env::var("NOVA_MCP_DB_CACHE_BYTES") {
+            config.storage.cache_capacity_bytes = bytes
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_DB_CACHE_BYTES"))?;
+        }
+        if let Ok(ms) = std::env::var("NOVA_MCP_DB_FLUSH_EVERY_MS") {
+            config.storage.flush_every_ms = ms
+                .parse()
+                .map_err(|_| NovaError::config_error("Invalid NOVA_MCP_DB_FLUSH_EVERY_MS"))?;
+        }
+        if let Ok(enabled) = std::env::var("NOVA_MCP_DB_COMPRESSION") {
+            config.storage.compression =
+                matches!(enabled.as_str(), "1" | "true" | "TRUE" | "yes" | "on");
+        }
+
+        // Admin API
+        if let Ok(tokens) = std::env::var("NOVA_MCP_ADMIN_TOKENS") {
+            config.admin.tokens = tokens
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        if let Ok(dir) = std::env::var("NOVA_MCP_BACKUP_DIR") {
+            if !dir.trim().is_empty() {
+                config.admin.backup_dir = dir;
+            }
+        }
 
         Ok(config)
     }
 
+    /// Copy safe to show operators: secrets replaced by short hints.
+    pub fn redacted(&self) -> Self {
+        let mut copy = self.clone();
+        let hide = |value: &mut Option<String>| {
+            if let Some(secret) = value.as_mut() {
+                *secret = crate::auth::redact(secret);
+            }
+        };
+        hide(&mut copy.apis.uniswap_api_key);
+        hide(&mut copy.apis.coingecko_api_key);
+        hide(&mut copy.apis.dexscreener_api_key);
+        hide(&mut copy.apis.solana_rpc_url);
+        hide(&mut copy.apis.token_unlocks_url);
+        hide(&mut copy.auth.telegram_bot_token);
+        hide(&mut copy.auth.jwt_secret);
+        hide(&mut copy.plugins.secrets_key);
+        hide(&mut copy.plugins.webhook_secret);
+        hide(&mut copy.metering.webhook_url);
+        hide(&mut copy.events.webhook_url);
+        copy.auth.allowed_keys = copy
+            .auth
+            .allowed_keys
+            .iter()
+            .map(|key| crate::auth::redact(key))
+            .collect();
+        for key in copy.auth.named_keys.values_mut() {
+            *key = crate::auth::redact(key);
+        }
+        copy.admin.tokens = copy
+            .admin
+            .tokens
+            .iter()
+            .map(|token| crate::auth::redact(token))
+            .collect();
+        copy
+    }
+
     pub fn from_file(path: &str) -> Result<Self> {
         let content = std::fs::read_to_string(path)
             .map_err(|e| NovaError::config_error(format!("Failed to read config file: {}", e)))?;
@@ -0,0 +1,170 @@
+//! Background deliveries that failed for good: job webhooks after their last
+//! retry, and metering or server events their webhook could not take.
+//!
+//! Each letter keeps what was being sent and where, so an admin can read it
+//! at `GET /admin/dead-letters` and send it again with
+//! `POST /admin/dead-letters/:id/requeue` instead of it being dropped.
+
+use std::future::Future;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::clock::SharedClock;
+use crate::error::{NovaError, Result};
+use crate::storage::{KvStore, MemoryKv};
+
+/// What kind of delivery failed, which decides how a requeue sends it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryKind {
+    /// A finished async plugin job for the caller's `webhook_url`
+    JobWebhook,
+    /// A usage event for `metering.webhook_url`
+    MeteringWebhook,
+    /// A server event for `events.webhook_url`
+    EventWebhook,
+}
+
+/// One delivery: where it went and the JSON it carried.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delivery {
+    pub kind: DeliveryKind,
+    pub target: String,
+    // The job, usage event or server event id
+    pub reference: String,
+    // `<type>:<id>` of the context the delivery is about, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    pub payload: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub id: String,
+    #[serde(flatten)]
+    pub delivery: Delivery,
+    // Every send so far, requeues included
+    pub attempts: u32,
+    pub requeues: u32,
+    pub last_error: String,
+    pub first_failed_at: i64,
+    pub failed_at: i64,
+}
+
+/// Dead letters keyed by id; they stay until requeued successfully or
+/// deleted.
+pub struct DeadLetters {
+    store: Box<dyn KvStore>,
+    clock: SharedClock,
+}
+
+impl DeadLetters {
+    pub fn in_memory() -> Self {
+        Self::with_store(Box::new(MemoryKv::default()))
+    }
+
+    pub fn persistent(tree: sled::Tree) -> Self {
+        Self::with_store(Box::new(tree))
+    }
+
+    fn with_store(store: Box<dyn KvStore>) -> Self {
+        Self {
+            store,
+            clock: SharedClock::default(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Stores a delivery that failed `attempts` times, the last with `error`.
+    pub fn record(&self, delivery: Delivery, attempts: u32, error: &str) -> Result<DeadLetter> {
+        let now = self.clock.timestamp();
+        tracing::warn!(
+            "Dead-lettered {:?} {} for {} after {} attempts: {}",
+            delivery.kind,
+            delivery.reference,
+            delivery.target,
+            attempts,
+            error
+        );
+        let letter = DeadLetter {
+            id: uuid::Uuid::new_v4().to_string(),
+            delivery,
+            attempts,
+            requeues: 0,
+            last_error: error.to_string(),
+            first_failed_at: now,
+            failed_at: now,
+        };
+        self.put(&letter)?;
+        Ok(letter)
+    }
+
+    /// Sends the letter's delivery once more with `send`: the letter is
+    /// removed when that succeeds, and kept with the new error otherwise.
+    pub async fn requeue<F, Fut>(&self, id: &str, send: F) -> Result<DeadLetter>
+    where
+        F: FnOnce(Delivery) -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        let mut letter = self
+            .get(id)?
+            .ok_or_else(|| NovaError::validation_error(format!("Unknown dead letter {}", id)))?;
+        letter.attempts += 1;
+        letter.requeues += 1;
+        match send(letter.delivery.clone()).await {
+            Ok(()) => {
+                self.remove(id)?;
+                Ok(letter)
+            }
+            Err(e) => {
+                letter.last_error = e.to_string();
+                letter.failed_at = self.clock.timestamp();
+                self.put(&letter)?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Dead letters of one kind or all, oldest first.
+    pub fn list(&self, kind: Option<DeliveryKind>) -> Result<Vec<DeadLetter>> {
+        let mut letters: Vec<DeadLetter> = self.store.values_json(b"")?;
+        letters.retain(|letter| kind.is_none_or(|kind| letter.delivery.kind == kind));
+        letters.sort_by_key(|letter| letter.first_failed_at);
+        Ok(letters)
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<DeadLetter>> {
+        self.store.get_json(id.as_bytes())
+    }
+
+    /// Drops a dead letter; false when there was none.
+    pub fn remove(&self, id: &str) -> Result<bool> {
+        Ok(self.store.remove(id.as_bytes())?.is_some())
+    }
+
+    /// Removes the dead letters about a context, given as `<type>:<id>`.
+    pub fn remove_context(&self, context: &str) -> Result<usize> {
+        let mut removed = 0;
+        for letter in self.list(None)? {
+            if letter.delivery.context.as_deref() == Some(context) && self.remove(&letter.id)? {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn put(&self, letter: &DeadLetter) -> Result<()> {
+        self.store.insert_json(letter.id.as_bytes(), letter)
+    }
+}
+
+impl Default for DeadLetters {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
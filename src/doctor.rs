@@ -0,0 +1,638 @@
+//! `nova-mcp-stdio doctor`: a self-test run before pointing MCP clients at a
+//! deployment.
+//!
+//! The server is started in-process on the configured transport (stdio over
+//! an in-memory pipe, HTTP on a free loopback port) and an embedded client
+//! walks the MCP lifecycle: `initialize`, `notifications/initialized`,
+//! `ping`, `tools/list` and `tools/call`, plus the errors the spec requires
+//! for unknown tools and methods. A last check connects the way the MCP
+//! Inspector does, without Nova's context fields, which only works with
+//! `context.default` set.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT};
+use serde_json::{json, Value};
+use tokio::io::{DuplexStream, ReadHalf, WriteHalf};
+use tokio::task::JoinHandle;
+
+use crate::auth::AuthMode;
+use crate::error::{NovaError, Result};
+use crate::mcp::protocol::ProtocolVersion;
+use crate::plugins::{ContextIdFormat, PluginContextType, RequestContext};
+use crate::stdio::{self, FrameReader, Framing, Incoming};
+use crate::{http, NovaConfig, NovaServer};
+
+/// How long the client waits for each response.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+const PIPE_CAPACITY: usize = 1 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    /// Works for Nova-aware clients, but not for every MCP client.
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Clone)]
+pub struct DoctorCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl DoctorCheck {
+    fn new(name: &'static str, outcome: std::result::Result<String, String>) -> Self {
+        let (status, detail) = match outcome {
+            Ok(detail) => (CheckStatus::Pass, detail),
+            Err(detail) => (CheckStatus::Fail, detail),
+        };
+        Self {
+            name,
+            status,
+            detail,
+        }
+    }
+}
+
+/// What [`run`] found, printable as the command's output.
+#[derive(Debug, Clone)]
+pub struct DoctorReport {
+    pub transport: String,
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    /// True unless a check failed; warnings pass.
+    pub fn passed(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|check| check.status != CheckStatus::Fail)
+    }
+
+    pub fn check(&self, name: &str) -> Option<&DoctorCheck> {
+        self.checks.iter().find(|check| check.name == name)
+    }
+}
+
+impl fmt::Display for DoctorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "MCP conformance over {}:", self.transport)?;
+        for check in &self.checks {
+            let label = match check.status {
+                CheckStatus::Pass => "PASS",
+                CheckStatus::Warn => "WARN",
+                CheckStatus::Fail => "FAIL",
+            };
+            writeln!(f, "  [{}] {:<26} {}", label, check.name, check.detail)?;
+        }
+        let count = |status| {
+            self.checks
+                .iter()
+                .filter(|check| check.status == status)
+                .count()
+        };
+        write!(
+            f,
+            "{} passed, {} warnings, {} failed",
+            count(CheckStatus::Pass),
+            count(CheckStatus::Warn),
+            count(CheckStatus::Fail)
+        )
+    }
+}
+
+/// Serves `server` on `config.server.transport` and runs every check
+/// against it. Errors only when the transport cannot be started.
+pub async fn run(server: NovaServer, config: &NovaConfig) -> Result<DoctorReport> {
+    let context = config
+        .context
+        .default_context()
+        .unwrap_or_else(|| probe_context(config.context.id_format()));
+    let has_default = config.context.default.is_some();
+    if config.server.transport.eq_ignore_ascii_case("http") {
+        let (transport, task) = Transport::start_http(server, config).await?;
+        let checks = run_checks(&transport, &context, has_default).await;
+        task.abort();
+        Ok(DoctorReport {
+            transport: "http (POST /mcp)".to_string(),
+            checks,
+        })
+    } else {
+        let framing = match Framing::parse(&config.server.stdio_framing) {
+            Some(Framing::ContentLength) => Framing::ContentLength,
+            _ => Framing::Newline,
+        };
+        let transport = Transport::Stdio {
+            server: Arc::new(server),
+            framing,
+        };
+        Ok(DoctorReport {
+            transport: "stdio".to_string(),
+            checks: run_checks(&transport, &context, has_default).await,
+        })
+    }
+}
+
+/// A context valid under `id_format`, used when no default is configured.
+fn probe_context(id_format: ContextIdFormat) -> RequestContext {
+    let context_id = match id_format {
+        ContextIdFormat::Uuid => "00000000-0000-0000-0000-000000000000",
+        _ => "0",
+    };
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: context_id.to_string(),
+        actor_id: None,
+    }
+}
+
+async fn run_checks(
+    transport: &Transport,
+    context: &RequestContext,
+    has_default: bool,
+) -> Vec<DoctorCheck> {
+    let mut checks = Vec::new();
+    let mut connection = match transport.connect() {
+        Ok(connection) => connection,
+        Err(e) => {
+            checks.push(DoctorCheck::new("connect", Err(e.to_string())));
+            return checks;
+        }
+    };
+
+    let initialize = connection.initialize(Some(context)).await;
+    let initialized = initialize.is_ok();
+    checks.push(DoctorCheck::new("initialize", initialize));
+    if !initialized {
+        return checks;
+    }
+    let notified = connection
+        .notify("notifications/initialized")
+        .await
+        .map(|()| "accepted without a response".to_string())
+        .map_err(|e| e.to_string());
+    checks.push(DoctorCheck::new("notifications/initialized", notified));
+
+    let ping = connection.request("ping", json!({}), None).await;
+    checks.push(DoctorCheck::new(
+        "ping",
+        result(ping).and_then(|result| match result.as_object() {
+            Some(object) if object.is_empty() => Ok("empty result".to_string()),
+            _ => Err(format!("expected an empty result, got {}", result)),
+        }),
+    ));
+
+    let tools = result(
+        connection
+            .request("tools/list", json!({}), Some(context))
+            .await,
+    );
+    let names = tools
+        .as_ref()
+        .map(tool_names)
+        .unwrap_or_else(|_| Ok(vec![]));
+    checks.push(DoctorCheck::new(
+        "tools/list",
+        tools
+            .and(names.clone())
+            .map(|names| format!("{} tools for {}", names.len(), context.principal())),
+    ));
+
+    let names = names.unwrap_or_default();
+    if names.iter().any(|name| name == "get_my_usage") {
+        let call = connection
+            .request(
+                "tools/call",
+                json!({ "name": "get_my_usage", "arguments": {} }),
+                Some(context),
+            )
+            .await;
+        checks.push(DoctorCheck::new(
+            "tools/call",
+            result(call).and_then(|result| call_result(&result)),
+        ));
+    } else {
+        checks.push(DoctorCheck {
+            name: "tools/call",
+            status: CheckStatus::Warn,
+            detail: "skipped: get_my_usage is not enabled".to_string(),
+        });
+    }
+
+    let unknown_tool = connection
+        .request(
+            "tools/call",
+            json!({ "name": "nova_doctor_no_such_tool", "arguments": {} }),
+            Some(context),
+        )
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|response| match (&response["error"], &response["result"]) {
+            (error, _) if error.is_object() => Ok(format!(
+                "error {}: {}",
+                error["code"],
+                text(&error["message"])
+            )),
+            (_, result) if result["isError"] == true => Ok("isError result".to_string()),
+            _ => Err("an unknown tool was not reported as an error".to_string()),
+        });
+    checks.push(DoctorCheck::new("tools/call (unknown tool)", unknown_tool));
+
+    let unknown_method = connection
+        .request("nova/doctor-no-such-method", json!({}), Some(context))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|response| match response["error"]["code"].as_i64() {
+            Some(-32601) => Ok("error -32601 (method not found)".to_string()),
+            Some(code) => Err(format!("expected error -32601, got {}", code)),
+            None => Err("an unknown method did not fail".to_string()),
+        });
+    checks.push(DoctorCheck::new("unknown method", unknown_method));
+    connection.close().await;
+
+    checks.push(generic_client_check(transport, has_default).await);
+    checks
+}
+
+/// A fresh session that never sends Nova's context fields.
+async fn generic_client_check(transport: &Transport, has_default: bool) -> DoctorCheck {
+    let name = "client without context";
+    let outcome = async {
+        let mut connection = transport.connect().map_err(|e| e.to_string())?;
+        connection.initialize(None).await?;
+        let tools = result(connection.request("tools/list", json!({}), None).await);
+        connection.close().await;
+        tools.and_then(|tools| tool_names(&tools))
+    }
+    .await;
+    match outcome {
+        Ok(names) => DoctorCheck::new(
+            name,
+            Ok(format!("{} tools via context.default", names.len())),
+        ),
+        Err(detail) if !has_default => DoctorCheck {
+            name,
+            status: CheckStatus::Warn,
+            detail: format!(
+                "{}; set context.default for clients such as the MCP Inspector",
+                detail
+            ),
+        },
+        Err(detail) => DoctorCheck::new(name, Err(detail)),
+    }
+}
+
+fn result(response: Result<Value>) -> std::result::Result<Value, String> {
+    let response = response.map_err(|e| e.to_string())?;
+    if let Some(error) = response.get("error") {
+        return Err(format!(
+            "error {}: {}",
+            error["code"],
+            text(&error["message"])
+        ));
+    }
+    response
+        .get("result")
+        .cloned()
+        .ok_or_else(|| "response has neither result nor error".to_string())
+}
+
+fn tool_names(result: &Value) -> std::result::Result<Vec<String>, String> {
+    let tools = result["tools"]
+        .as_array()
+        .ok_or("result has no tools array")?;
+    tools
+        .iter()
+        .map(|tool| {
+            let name = tool["name"].as_str().ok_or("a tool has no name")?;
+            if tool["inputSchema"]["type"] != "object" {
+                return Err(format!("{}: inputSchema must have type \"object\"", name));
+            }
+            Ok(name.to_string())
+        })
+        .collect()
+}
+
+fn call_result(result: &Value) -> std::result::Result<String, String> {
+    let content = result["content"]
+        .as_array()
+        .ok_or("result has no content array")?;
+    match result["isError"].as_bool() {
+        Some(false) => Ok(format!(
+            "get_my_usage returned {} content items",
+            content.len()
+        )),
+        Some(true) => Err(format!(
+            "get_my_usage failed: {}",
+            text(&content[0]["text"])
+        )),
+        None => Err("result has no isError flag".to_string()),
+    }
+}
+
+fn text(value: &Value) -> &str {
+    value.as_str().unwrap_or_default()
+}
+
+enum Transport {
+    Stdio {
+        server: Arc<NovaServer>,
+        framing: Framing,
+    },
+    Http {
+        url: String,
+        headers: HeaderMap,
+        client: reqwest::Client,
+    },
+}
+
+impl Transport {
+    /// Runs the HTTP server on a free loopback port, authenticating with
+    /// the first configured API key.
+    async fn start_http(server: NovaServer, config: &NovaConfig) -> Result<(Self, JoinHandle<()>)> {
+        let mut config = config.clone();
+        // The checks run over loopback TCP even when the server would use a socket
+        config.server.unix_socket = None;
+        config.server.bind_address = "127.0.0.1".to_string();
+        config.server.port = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .map_err(|e| NovaError::internal(format!("Failed to find a free port: {}", e)))?
+            .port();
+        let base_url = format!("http://127.0.0.1:{}", config.server.port);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            ACCEPT,
+            HeaderValue::from_static("application/json, text/event-stream"),
+        );
+        let key = config
+            .auth
+            .allowed_keys
+            .first()
+            .or_else(|| config.auth.named_keys.values().next());
+        if let (true, AuthMode::ApiKey, Some(key)) = (config.auth.enabled, config.auth.mode(), key)
+        {
+            let name = HeaderName::try_from(config.auth.header_name.as_str())
+                .map_err(|e| NovaError::config_error(e.to_string()))?;
+            let value =
+                HeaderValue::try_from(key).map_err(|e| NovaError::config_error(e.to_string()))?;
+            headers.insert(name, value);
+        }
+        let task = tokio::spawn(async move {
+            if let Err(e) = http::run_http_server(server, config).await {
+                tracing::error!("Doctor HTTP server stopped: {}", e);
+            }
+        });
+
+        let client = reqwest::Client::new();
+        let started = tokio::time::Instant::now();
+        while client
+            .get(format!("{}/healthz", base_url))
+            .send()
+            .await
+            .is_err()
+        {
+            if started.elapsed() > STARTUP_TIMEOUT {
+                task.abort();
+                return Err(NovaError::internal("HTTP server did not start"));
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        let transport = Transport::Http {
+            url: format!("{}/mcp", base_url),
+            headers,
+            client,
+        };
+        Ok((transport, task))
+    }
+
+    fn connect(&self) -> Result<Connection> {
+        let link = match self {
+            Transport::Stdio { server, framing } => {
+                let (client_end, server_end) = tokio::io::duplex(PIPE_CAPACITY);
+                let (server_read, server_write) = tokio::io::split(server_end);
+                let server = Arc::clone(server);
+                let serve_framing = *framing;
+                let task = tokio::spawn(async move {
+                    if let Err(e) =
+                        stdio::serve(&server, server_read, server_write, serve_framing).await
+                    {
+                        tracing::error!("Doctor stdio session stopped: {}", e);
+                    }
+                });
+                let (read, write) = tokio::io::split(client_end);
+                Link::Stdio {
+                    frames: FrameReader::new(read, *framing),
+                    writer: write,
+                    framing: *framing,
+                    task,
+                }
+            }
+            Transport::Http {
+                url,
+                headers,
+                client,
+            } => Link::Http {
+                url: url.clone(),
+                headers: headers.clone(),
+                client: client.clone(),
+            },
+        };
+        Ok(Connection { link, next_id: 0 })
+    }
+}
+
+/// One client session.
+struct Connection {
+    link: Link,
+    next_id: u64,
+}
+
+enum Link {
+    Stdio {
+        frames: FrameReader<ReadHalf<DuplexStream>>,
+        writer: WriteHalf<DuplexStream>,
+        framing: Framing,
+        task: JoinHandle<()>,
+    },
+    Http {
+        url: String,
+        /// Grows the session id and protocol version after `initialize`.
+        headers: HeaderMap,
+        client: reqwest::Client,
+    },
+}
+
+impl Connection {
+    async fn initialize(
+        &mut self,
+        context: Option<&RequestContext>,
+    ) -> std::result::Result<String, String> {
+        let params = json!({
+            "protocolVersion": ProtocolVersion::LATEST.as_str(),
+            "capabilities": {},
+            "clientInfo": { "name": "nova-mcp-doctor", "version": env!("CARGO_PKG_VERSION") }
+        });
+        let result = result(self.request("initialize", params, context).await)?;
+        let version = result["protocolVersion"]
+            .as_str()
+            .ok_or("result has no protocolVersion")?;
+        if !result["capabilities"]["tools"].is_object() {
+            return Err("capabilities do not include tools".to_string());
+        }
+        let server = &result["serverInfo"];
+        let server_name = server["name"]
+            .as_str()
+            .ok_or("result has no serverInfo.name")?;
+        if let Link::Http { headers, .. } = &mut self.link {
+            if let Ok(value) = HeaderValue::from_str(version) {
+                headers.insert("mcp-protocol-version", value);
+            }
+        }
+        Ok(format!(
+            "protocol {}, server {} {}",
+            version,
+            server_name,
+            text(&server["version"])
+        ))
+    }
+
+    /// The response to `method`, with the context as root fields when given.
+    async fn request(
+        &mut self,
+        method: &str,
+        params: Value,
+        context: Option<&RequestContext>,
+    ) -> Result<Value> {
+        self.next_id += 1;
+        let id = self.next_id;
+        let mut message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        if let Some(context) = context {
+            message["context_type"] = json!(context.context_type.to_string());
+            message["context_id"] = json!(context.context_id);
+        }
+        let response = tokio::time::timeout(REQUEST_TIMEOUT, self.exchange(&message, Some(id)))
+            .await
+            .map_err(|_| NovaError::api_error(format!("No response to {}", method)))??
+            .ok_or_else(|| NovaError::api_error(format!("No response to {}", method)))?;
+        if response["jsonrpc"] != "2.0" {
+            return Err(NovaError::api_error("Response is not JSON-RPC 2.0"));
+        }
+        // Transport-level errors, such as a rejected API key, carry no id
+        let rejected = response["id"].is_null() && response["error"].is_object();
+        if !rejected && response["id"] != json!(id) {
+            return Err(NovaError::api_error(format!(
+                "Response id {} does not match request id {}",
+                response["id"], id
+            )));
+        }
+        Ok(response)
+    }
+
+    async fn notify(&mut self, method: &str) -> Result<()> {
+        let message = json!({ "jsonrpc": "2.0", "method": method });
+        match self.exchange(&message, None).await? {
+            None => Ok(()),
+            Some(response) => Err(NovaError::api_error(format!(
+                "Notification was answered: {}",
+                response
+            ))),
+        }
+    }
+
+    /// Sends `message` and waits for the response with `id`, skipping
+    /// server notifications; `None` for notifications.
+    async fn exchange(&mut self, message: &Value, id: Option<u64>) -> Result<Option<Value>> {
+        match &mut self.link {
+            Link::Stdio {
+                frames,
+                writer,
+                framing,
+                ..
+            } => {
+                let pipe_error = |e: std::io::Error| NovaError::internal(format!("Pipe: {}", e));
+                stdio::write_message(writer, *framing, &message.to_string())
+                    .await
+                    .map_err(pipe_error)?;
+                if id.is_none() {
+                    return Ok(None);
+                }
+                loop {
+                    match frames.next().await.map_err(pipe_error)? {
+                        Some(Incoming::Message(text)) => {
+                            let response: Value = serde_json::from_str(&text)?;
+                            // Log messages and other server notifications
+                            if response.get("method").is_none() {
+                                return Ok(Some(response));
+                            }
+                        }
+                        Some(Incoming::Malformed(reason)) => {
+                            return Err(NovaError::api_error(format!(
+                                "Malformed frame from server: {}",
+                                reason
+                            )))
+                        }
+                        None => return Err(NovaError::api_error("Server closed the stream")),
+                    }
+                }
+            }
+            Link::Http {
+                url,
+                headers,
+                client,
+            } => {
+                let response = client
+                    .post(url.as_str())
+                    .headers(headers.clone())
+                    .json(message)
+                    .send()
+                    .await?;
+                let status = response.status();
+                if let Some(session) = response.headers().get("mcp-session-id").cloned() {
+                    headers.insert("mcp-session-id", session);
+                }
+                if id.is_none() {
+                    return match status {
+                        reqwest::StatusCode::ACCEPTED => Ok(None),
+                        status => Err(NovaError::api_error(format!(
+                            "Notification returned {}, expected 202",
+                            status
+                        ))),
+                    };
+                }
+                let body = response.text().await?;
+                serde_json::from_str(&body)
+                    .map(Some)
+                    .map_err(|_| NovaError::api_error(format!("HTTP {}: {}", status, body.trim())))
+            }
+        }
+    }
+
+    async fn close(self) {
+        match self.link {
+            Link::Stdio {
+                task,
+                writer,
+                frames,
+                ..
+            } => {
+                // Closing both ends gives the server end of input
+                drop((writer, frames));
+                let _ = tokio::time::timeout(Duration::from_secs(1), task).await;
+            }
+            Link::Http {
+                url,
+                headers,
+                client,
+            } => {
+                if headers.contains_key("mcp-session-id") {
+                    let _ = client.delete(url).headers(headers).send().await;
+                }
+            }
+        }
+    }
+}
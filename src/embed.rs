@@ -0,0 +1,205 @@
+//! Hosting Nova inside another binary instead of running `nova-mcp-stdio`.
+//!
+//! [`RegistryHandles`] is the sled database Nova keeps its registry and
+//! stores in, [`build_server`] wires it into a [`NovaServer`] the way the
+//! binary does, and [`serve`] runs that server on `server.transport` within
+//! the caller's Tokio runtime:
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! let config = nova_mcp::NovaConfig::load()?;
+//! let handles = nova_mcp::RegistryHandles::open(&config.storage)?;
+//! nova_mcp::serve(config, handles).await
+//! # }
+//! ```
+//!
+//! To mount the HTTP routes in an existing axum app instead, pass the server
+//! to [`AppState::new`](crate::http::AppState::new) and merge
+//! [`http::router`](crate::http::router) into the app.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::audit::AuditLog;
+use crate::dead_letters::DeadLetters;
+use crate::error::{NovaError, Result};
+use crate::metering::Metering;
+use crate::oauth::OAuthClientStore;
+use crate::plugins::{
+    EgressPolicy, FeedbackStore, PluginJobs, PluginManager, RedactionRules, SchemaRefs,
+    ScriptLimits, SecretBox,
+};
+use crate::preferences::PreferenceStore;
+use crate::quotas::QuotaStore;
+use crate::rate_limits::RateLimitStore;
+use crate::reload::spawn_sighup_listener;
+use crate::stdio::{self, Framing};
+use crate::storage::{self, migrations};
+use crate::tools::gecko_terminal::{TokenMappingStore, WhaleWatchStore};
+use crate::tools::negative_cache::NegativeCache;
+use crate::{http, outbound, NovaConfig, NovaServer};
+
+/// The sled database behind a hosted Nova, migrated to this build's schema.
+///
+/// Nova opens its own trees in it (`plugin_metadata`, `audit_log`, ...), so
+/// embedders may share one database as long as they keep to other tree names.
+#[derive(Clone)]
+pub struct RegistryHandles {
+    db: sled::Db,
+}
+
+impl RegistryHandles {
+    /// Opens the database described by `storage` and migrates it.
+    pub fn open(storage: &crate::config::StorageConfig) -> Result<Self> {
+        if storage.is_temporary() {
+            tracing::warn!("Using a temporary in-memory database; state is lost on exit");
+        }
+        Self::from_db(storage::open_db(storage)?)
+    }
+
+    /// Uses a database the embedder already opened, migrating it first.
+    pub fn from_db(db: sled::Db) -> Result<Self> {
+        let report = migrations::run(&db)?;
+        if !report.applied.is_empty() {
+            tracing::info!(
+                "Storage schema migrated from version {} to {}",
+                report.from,
+                report.to
+            );
+        }
+        Ok(Self { db })
+    }
+
+    pub fn db(&self) -> &sled::Db {
+        &self.db
+    }
+
+    fn tree(&self, name: &str) -> Result<sled::Tree> {
+        Ok(self.db.open_tree(name)?)
+    }
+}
+
+/// A server with every store persisted in `handles`, configured from
+/// `config` as `nova-mcp-stdio` configures its own.
+pub fn build_server(config: &NovaConfig, handles: &RegistryHandles) -> Result<NovaServer> {
+    let dead_letters = Arc::new(DeadLetters::persistent(handles.tree("dead_letters")?));
+    let egress = EgressPolicy::new(&config.plugins).with_outbound_proxy(&config.outbound);
+    let plugin_client = egress
+        .configure(outbound::client_builder(&config.outbound, "plugins")?)
+        .build()?;
+    let mut plugin_manager = PluginManager::new(
+        handles.tree("plugin_metadata")?,
+        handles.tree("user_plugins")?,
+        handles.tree("group_plugins")?,
+    )?
+    .with_context_trees(
+        handles.tree("channel_plugins")?,
+        handles.tree("organization_plugins")?,
+    )
+    .with_activity_tree(handles.tree("plugin_activity")?)
+    .with_dead_letters(Arc::clone(&dead_letters))
+    .with_context_id_format(config.context.id_format())
+    .with_egress_policy(egress)
+    .with_schema_refs(SchemaRefs::new(&config.plugins))
+    .with_script_limits(ScriptLimits::new(&config.plugins))
+    .with_outbound_config(config.outbound.clone())
+    .with_http_client(plugin_client)
+    .with_read_only(config.server.read_only)
+    .with_redaction(
+        RedactionRules::parse(&config.plugins.redact).map_err(NovaError::config_error)?,
+    );
+    if let Some(secrets) = config
+        .plugins
+        .secrets_key
+        .as_deref()
+        .and_then(SecretBox::from_base64)
+    {
+        plugin_manager = plugin_manager.with_secret_box(secrets);
+    }
+    if config.metering.enabled {
+        let webhook_client = outbound::build_client(&config.outbound, "metering", |b| {
+            b.timeout(Duration::from_secs(10))
+        })?;
+        let metering = Metering::from_config(
+            &config.metering,
+            Some(handles.tree("metering_ledger")?),
+            webhook_client,
+            dead_letters,
+        )?;
+        plugin_manager = plugin_manager.with_metering(Arc::new(metering));
+    }
+
+    let server = NovaServer::try_new(config.clone(), Arc::new(plugin_manager))?
+        .with_negative_cache(NegativeCache::persistent(
+            handles.tree("negative_cache")?,
+            config.cache.negative_ttl_seconds,
+        ))
+        .with_preferences(PreferenceStore::persistent(
+            handles.tree("context_preferences")?,
+        ))
+        .with_oauth_clients(OAuthClientStore::persistent(handles.tree("oauth_clients")?))
+        .with_audit_log(AuditLog::persistent(handles.tree("audit_log")?)?)
+        .with_rate_limits(RateLimitStore::persistent(handles.tree("rate_limits")?))
+        .with_quotas(QuotaStore::persistent(handles.tree("quotas")?))
+        .with_token_mappings(TokenMappingStore::persistent(
+            handles.tree("token_mappings")?,
+        ))
+        .with_whale_watches(WhaleWatchStore::persistent(handles.tree("whale_watches")?))
+        .with_plugin_feedback(FeedbackStore::persistent(handles.tree("plugin_feedback")?))
+        .with_plugin_jobs(
+            PluginJobs::persistent(handles.tree("plugin_jobs")?).with_config(&config.plugins),
+        )
+        .with_readiness_probe(handles.tree("readiness")?)
+        .with_database(handles.db.clone());
+    #[cfg(feature = "dylib-tools")]
+    let server = with_dylib_tools(server, config)?;
+    Ok(server)
+}
+
+/// Registers the tool libraries in `dylib_tools.dir`, if set.
+#[cfg(feature = "dylib-tools")]
+fn with_dylib_tools(mut server: NovaServer, config: &NovaConfig) -> Result<NovaServer> {
+    let Some(dir) = &config.dylib_tools.dir else {
+        return Ok(server);
+    };
+    // SAFETY: setting `dylib_tools.dir` is the operator's opt-in to running
+    // these libraries in-process
+    let tools = unsafe { crate::tools::dylib::DylibTool::load_dir(dir.as_ref())? };
+    for tool in tools {
+        tracing::info!("Loaded dylib tool from {}", tool.path().display());
+        server = server.with_tool(tool)?;
+    }
+    Ok(server)
+}
+
+/// Builds the server over `handles` and [`run`]s it.
+pub async fn serve(config: NovaConfig, handles: RegistryHandles) -> anyhow::Result<()> {
+    let server = build_server(&config, &handles)?;
+    run(server, config).await
+}
+
+/// Starts the server's background jobs and serves `server.transport` until
+/// it stops: HTTP on the configured address, or stdio on the process's own
+/// stdin and stdout. Both reload the config on SIGHUP.
+pub async fn run(server: NovaServer, config: NovaConfig) -> anyhow::Result<()> {
+    server.start_jobs();
+    if config.server.transport.eq_ignore_ascii_case("http") {
+        tracing::info!(
+            "Nova MCP Server running with HTTP transport on port {}",
+            config.server.port
+        );
+        return http::run_http_server(server, config).await;
+    }
+
+    tracing::info!("Nova MCP Server running with stdio transport");
+    let runtime = server.runtime().clone();
+    spawn_sighup_listener(move || match runtime.reload() {
+        Ok(summary) => tracing::info!("Config reloaded on SIGHUP: {:?}", summary.changed),
+        Err(e) => tracing::error!("Config reload failed: {}", e),
+    });
+    let framing = Framing::parse(&config.server.stdio_framing).unwrap_or(Framing::Auto);
+    let stdout = stdio::claim_stdout()?;
+    stdio::serve(&server, tokio::io::stdin(), stdout, framing).await?;
+    tracing::info!("Nova MCP Server shutting down");
+    Ok(())
+}
@@ -1,3 +1,8 @@
+use crate::config::ConfigIssue;
+use crate::schema::{FieldError, SchemaChange};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, NovaError>;
@@ -16,21 +21,49 @@ pub enum NovaError {
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
+    #[error("Invalid configuration: {}", join_issues(issues))]
+    InvalidConfig { issues: Vec<ConfigIssue> },
+
     #[error("Validation error: {message}")]
     ValidationError { message: String },
 
+    /// Every problem found in a request body, keyed by dotted field path.
+    #[error("Validation error: {}", join_fields(fields))]
+    InvalidFields { fields: BTreeMap<String, String> },
+
+    #[error("Invalid arguments for {tool}: {}", join_issues(errors))]
+    InvalidArguments {
+        tool: String,
+        errors: Vec<FieldError>,
+    },
+
     #[error("Pool not found: {address}")]
     PoolNotFound { address: String },
 
     #[error("Token not found: {address}")]
     TokenNotFound { address: String },
 
-    #[error("Invalid address: {address}")]
-    InvalidAddress { address: String },
+    #[error("Invalid address: {address}{}", did_you_mean(suggestion))]
+    InvalidAddress {
+        address: String,
+        suggestion: Option<String>,
+    },
+
+    #[error("Unknown network: {network}")]
+    UnknownNetwork {
+        network: String,
+        suggestions: Vec<String>,
+    },
+
+    #[error("Tool disabled: {name}")]
+    ToolDisabled { name: String },
 
     #[error("Plugin not found: {plugin_id}")]
     PluginNotFound { plugin_id: u64 },
 
+    #[error("Job not found: {job_id}")]
+    JobNotFound { job_id: String },
+
     #[error("Plugin {plugin_id} is not enabled for {context_type} {context_id}")]
     PluginNotEnabled {
         plugin_id: u64,
@@ -38,16 +71,76 @@ pub enum NovaError {
         context_id: String,
     },
 
+    #[error("The plugin registry is read-only on this instance")]
+    ReadOnly,
+
+    #[error("Plugin {plugin_id} is at revision {current}, not {expected}")]
+    RevisionConflict {
+        plugin_id: u64,
+        expected: u64,
+        current: u64,
+    },
+
+    #[error(
+        "Input schema change for plugin {plugin_id} can break callers ({}); resend with breaking=true to publish it",
+        join_issues(changes)
+    )]
+    BreakingSchemaChange {
+        plugin_id: u64,
+        changes: Vec<SchemaChange>,
+    },
+
     #[error("Storage error: {0}")]
     StorageError(#[from] sled::Error),
 
+    #[error("Database schema version {found} is newer than this build supports ({supported})")]
+    SchemaTooNew { found: u32, supported: u32 },
+
     #[error("Rate limit exceeded for API: {api}")]
-    RateLimitExceeded { api: String },
+    RateLimitExceeded {
+        api: String,
+        retry_after_secs: Option<u64>,
+    },
+
+    #[error("Quota of {limit} {period} calls used up for {scope}")]
+    QuotaExceeded {
+        scope: String,
+        period: String,
+        limit: u64,
+        resets_at: String,
+    },
+
+    #[error("Tool {tool} timed out after {timeout_secs}s")]
+    ToolTimeout { tool: String, timeout_secs: u64 },
+
+    #[error("Pipeline {pipeline} step {step} ({tool}) failed: {source}")]
+    PipelineStepFailed {
+        pipeline: String,
+        step: String,
+        tool: String,
+        source: Box<NovaError>,
+    },
 
     #[error("Internal error: {0}")]
     Internal(String),
 }
 
+/// Coarse grouping of [`NovaError`]s for clients that branch on the kind of failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    Validation,
+    NotFound,
+    PermissionDenied,
+    // The resource changed since the caller read it
+    Conflict,
+    RateLimited,
+    Timeout,
+    Upstream,
+    Configuration,
+    Internal,
+}
+
 impl NovaError {
     pub fn api_error(msg: impl Into<String>) -> Self {
         NovaError::ApiError(msg.into())
@@ -67,10 +160,303 @@ impl NovaError {
         }
     }
 
+    pub fn invalid_fields(fields: BTreeMap<String, String>) -> Self {
+        NovaError::InvalidFields { fields }
+    }
+
+    pub fn breaking_schema_change(plugin_id: u64, changes: Vec<SchemaChange>) -> Self {
+        NovaError::BreakingSchemaChange { plugin_id, changes }
+    }
+
+    pub fn invalid_arguments(tool: impl Into<String>, errors: Vec<FieldError>) -> Self {
+        NovaError::InvalidArguments {
+            tool: tool.into(),
+            errors,
+        }
+    }
+
+    pub fn token_not_found(address: impl Into<String>) -> Self {
+        NovaError::TokenNotFound {
+            address: address.into(),
+        }
+    }
+
+    pub fn pool_not_found(address: impl Into<String>) -> Self {
+        NovaError::PoolNotFound {
+            address: address.into(),
+        }
+    }
+
+    pub fn invalid_address(address: impl Into<String>) -> Self {
+        NovaError::InvalidAddress {
+            address: address.into(),
+            suggestion: None,
+        }
+    }
+
+    /// A well-formed EVM address whose EIP-55 checksum does not match.
+    pub fn invalid_checksum(address: impl Into<String>, checksummed: impl Into<String>) -> Self {
+        NovaError::InvalidAddress {
+            address: address.into(),
+            suggestion: Some(checksummed.into()),
+        }
+    }
+
+    pub fn unknown_network(network: impl Into<String>, suggestions: Vec<String>) -> Self {
+        NovaError::UnknownNetwork {
+            network: network.into(),
+            suggestions,
+        }
+    }
+
+    pub fn rate_limit_exceeded(api: impl Into<String>, retry_after_secs: Option<u64>) -> Self {
+        NovaError::RateLimitExceeded {
+            api: api.into(),
+            retry_after_secs,
+        }
+    }
+
+    pub fn quota_exceeded(
+        scope: impl Into<String>,
+        period: impl Into<String>,
+        limit: u64,
+        resets_at: impl Into<String>,
+    ) -> Self {
+        NovaError::QuotaExceeded {
+            scope: scope.into(),
+            period: period.into(),
+            limit,
+            resets_at: resets_at.into(),
+        }
+    }
+
+    /// Seconds the caller should wait before retrying, when known.
+    pub fn retry_after_secs(&self) -> Option<u64> {
+        match self {
+            NovaError::RateLimitExceeded {
+                retry_after_secs, ..
+            } => *retry_after_secs,
+            _ => None,
+        }
+    }
+
+    pub fn tool_timeout(tool: impl Into<String>, timeout_secs: u64) -> Self {
+        NovaError::ToolTimeout {
+            tool: tool.into(),
+            timeout_secs,
+        }
+    }
+
+    /// Stable machine-readable identifier; published values are never renamed.
+    pub fn code(&self) -> &'static str {
+        match self {
+            NovaError::ApiError(_) => "upstream_error",
+            NovaError::NetworkError(_) => "network_error",
+            NovaError::SerializationError(_) => "serialization_error",
+            NovaError::ConfigError(_) => "config_error",
+            NovaError::InvalidConfig { .. } => "invalid_config",
+            // The same failure as `ValidationError`, reported field by field
+            NovaError::ValidationError { .. } | NovaError::InvalidFields { .. } => {
+                "validation_failed"
+            }
+            NovaError::InvalidArguments { .. } => "invalid_arguments",
+            NovaError::PoolNotFound { .. } => "pool_not_found",
+            NovaError::TokenNotFound { .. } => "token_not_found",
+            NovaError::InvalidAddress { .. } => "invalid_address",
+            NovaError::UnknownNetwork { .. } => "unknown_network",
+            NovaError::ToolDisabled { .. } => "tool_disabled",
+            NovaError::PluginNotFound { .. } => "plugin_not_found",
+            NovaError::JobNotFound { .. } => "job_not_found",
+            NovaError::PluginNotEnabled { .. } => "plugin_not_enabled",
+            NovaError::ReadOnly => "read_only",
+            NovaError::RevisionConflict { .. } => "revision_conflict",
+            NovaError::BreakingSchemaChange { .. } => "breaking_schema_change",
+            NovaError::StorageError(_) => "storage_error",
+            NovaError::SchemaTooNew { .. } => "schema_too_new",
+            NovaError::RateLimitExceeded { .. } => "rate_limited",
+            NovaError::QuotaExceeded { .. } => "quota_exceeded",
+            NovaError::ToolTimeout { .. } => "tool_timeout",
+            NovaError::PipelineStepFailed { .. } => "pipeline_step_failed",
+            NovaError::Internal(_) => "internal_error",
+        }
+    }
+
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            // A pipeline fails the way its failing step did
+            NovaError::PipelineStepFailed { source, .. } => source.category(),
+            NovaError::ValidationError { .. }
+            | NovaError::InvalidFields { .. }
+            | NovaError::InvalidArguments { .. }
+            | NovaError::InvalidAddress { .. }
+            | NovaError::UnknownNetwork { .. } => ErrorCategory::Validation,
+            NovaError::PoolNotFound { .. }
+            | NovaError::TokenNotFound { .. }
+            | NovaError::PluginNotFound { .. }
+            | NovaError::JobNotFound { .. } => ErrorCategory::NotFound,
+            NovaError::ToolDisabled { .. }
+            | NovaError::PluginNotEnabled { .. }
+            | NovaError::ReadOnly => ErrorCategory::PermissionDenied,
+            NovaError::RevisionConflict { .. } | NovaError::BreakingSchemaChange { .. } => {
+                ErrorCategory::Conflict
+            }
+            NovaError::RateLimitExceeded { .. } | NovaError::QuotaExceeded { .. } => {
+                ErrorCategory::RateLimited
+            }
+            NovaError::ToolTimeout { .. } => ErrorCategory::Timeout,
+            NovaError::ApiError(_) | NovaError::NetworkError(_) => ErrorCategory::Upstream,
+            NovaError::ConfigError(_)
+            | NovaError::InvalidConfig { .. }
+            | NovaError::SchemaTooNew { .. } => ErrorCategory::Configuration,
+            NovaError::SerializationError(_)
+            | NovaError::StorageError(_)
+            | NovaError::Internal(_) => ErrorCategory::Internal,
+        }
+    }
+
+    /// True when repeating the same call later may succeed.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            NovaError::PipelineStepFailed { source, .. } => source.is_retryable(),
+            _ => matches!(
+                self,
+                NovaError::RateLimitExceeded { .. }
+                    | NovaError::NetworkError(_)
+                    | NovaError::ToolTimeout { .. }
+            ),
+        }
+    }
+
+    /// Variant-specific fields, keyed as in the variant.
+    pub fn details(&self) -> Option<Value> {
+        match self {
+            NovaError::InvalidConfig { issues } => Some(json!({ "issues": issues })),
+            NovaError::InvalidFields { fields } => Some(json!({ "fields": fields })),
+            NovaError::InvalidArguments { tool, errors } => {
+                Some(json!({ "tool": tool, "errors": errors }))
+            }
+            NovaError::PoolNotFound { address } | NovaError::TokenNotFound { address } => {
+                Some(json!({ "address": address }))
+            }
+            NovaError::InvalidAddress {
+                address,
+                suggestion: None,
+            } => Some(json!({ "address": address })),
+            NovaError::InvalidAddress {
+                address,
+                suggestion: Some(suggestion),
+            } => Some(json!({ "address": address, "suggestion": suggestion })),
+            NovaError::UnknownNetwork {
+                network,
+                suggestions,
+            } => Some(json!({ "network": network, "suggestions": suggestions })),
+            NovaError::ToolDisabled { name } => Some(json!({ "tool": name })),
+            NovaError::PluginNotFound { plugin_id } => Some(json!({ "plugin_id": plugin_id })),
+            NovaError::JobNotFound { job_id } => Some(json!({ "job_id": job_id })),
+            NovaError::RevisionConflict {
+                plugin_id,
+                expected,
+                current,
+            } => Some(json!({
+                "plugin_id": plugin_id,
+                "expected": expected,
+                "current": current,
+            })),
+            NovaError::BreakingSchemaChange { plugin_id, changes } => {
+                Some(json!({ "plugin_id": plugin_id, "changes": changes }))
+            }
+            NovaError::PluginNotEnabled {
+                plugin_id,
+                context_type,
+                context_id,
+            } => Some(json!({
+                "plugin_id": plugin_id,
+                "context_type": context_type,
+                "context_id": context_id,
+            })),
+            NovaError::RateLimitExceeded {
+                api,
+                retry_after_secs,
+            } => Some(json!({ "api": api, "retry_after_secs": retry_after_secs })),
+            NovaError::QuotaExceeded {
+                scope,
+                period,
+                limit,
+                resets_at,
+            } => Some(json!({
+                "scope": scope,
+                "period": period,
+                "limit": limit,
+                "resets_at": resets_at,
+            })),
+            NovaError::SchemaTooNew { found, supported } => {
+                Some(json!({ "found": found, "supported": supported }))
+            }
+            NovaError::ToolTimeout { tool, timeout_secs } => {
+                Some(json!({ "tool": tool, "timeout_secs": timeout_secs }))
+            }
+            NovaError::PipelineStepFailed {
+                pipeline,
+                step,
+                tool,
+                source,
+            } => Some(json!({
+                "pipeline": pipeline,
+                "step": step,
+                "tool": tool,
+                "error": source.to_data(),
+            })),
+            _ => None,
+        }
+    }
+
+    /// `{code, category, retryable, details}`, as carried by `McpError.data`
+    /// and `ErrorResponse.details`.
+    pub fn to_data(&self) -> Value {
+        json!({
+            "code": self.code(),
+            "category": self.category(),
+            "retryable": self.is_retryable(),
+            "details": self.details(),
+        })
+    }
+
+    pub fn pipeline_step_failed(
+        pipeline: impl Into<String>,
+        step: impl Into<String>,
+        tool: impl Into<String>,
+        source: NovaError,
+    ) -> Self {
+        NovaError::PipelineStepFailed {
+            pipeline: pipeline.into(),
+            step: step.into(),
+            tool: tool.into(),
+            source: Box::new(source),
+        }
+    }
+
+    pub fn tool_disabled(name: impl Into<String>) -> Self {
+        NovaError::ToolDisabled { name: name.into() }
+    }
+
     pub fn plugin_not_found(plugin_id: u64) -> Self {
         NovaError::PluginNotFound { plugin_id }
     }
 
+    pub fn job_not_found(job_id: impl Into<String>) -> Self {
+        NovaError::JobNotFound {
+            job_id: job_id.into(),
+        }
+    }
+
+    pub fn revision_conflict(plugin_id: u64, expected: u64, current: u64) -> Self {
+        NovaError::RevisionConflict {
+            plugin_id,
+            expected,
+            current,
+        }
+    }
+
     pub fn plugin_not_enabled(
         plugin_id: u64,
         context_type: impl Into<String>,
@@ -83,3 +469,26 @@ impl NovaError {
         }
     }
 }
+
+fn did_you_mean(suggestion: &Option<String>) -> String {
+    suggestion
+        .as_ref()
+        .map(|suggestion| format!(" (checksum mismatch; did you mean {}?)", suggestion))
+        .unwrap_or_default()
+}
+
+fn join_fields(fields: &BTreeMap<String, String>) -> String {
+    fields
+        .iter()
+        .map(|(field, message)| format!("{}: {}", field, message))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn join_issues<T: ToString>(issues: &[T]) -> String {
+    issues
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -0,0 +1,205 @@
+//! Typed server events on one bus, so cross-cutting features subscribe
+//! instead of being called from every place something happens.
+//!
+//! Publishers: MCP `tools/call` ([`EventKind::ToolCalled`]), plugin
+//! registration, the per-key HTTP rate limit, upstream health (errors,
+//! and an alert when an upstream goes down), and the whale watcher. Subscribers run in
+//! [`EventBus::publish`], in attach order; async consumers such as the MCP
+//! notification streams read [`EventBus::subscribe`] instead.
+
+pub mod webhook;
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, RwLock};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use crate::clock::SharedClock;
+use crate::error::Result;
+
+pub use webhook::EventWebhook;
+
+/// Events buffered per async subscriber before it starts lagging.
+const EVENT_BUFFER: usize = 256;
+
+/// Every `type` an event can have, as named in `events.webhook_events`.
+pub const EVENT_TYPES: &[&str] = &[
+    "tool_called",
+    "plugin_registered",
+    "rate_limited",
+    "upstream_error",
+    "alert_fired",
+    "whale_trade",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventKind {
+    /// An MCP `tools/call` finished, successfully or not.
+    ToolCalled {
+        tool: String,
+        // `<type>:<id>` of the calling context
+        context: String,
+        duration_ms: u64,
+        success: bool,
+    },
+    PluginRegistered {
+        plugin_id: u64,
+        fq_name: String,
+        // `<type>:<id>` of the registering context
+        owner: String,
+    },
+    /// A request was turned away by the per-key HTTP rate limit.
+    RateLimited { key: String },
+    /// An upstream API or plugin endpoint failed a call.
+    UpstreamError { upstream: String, error: String },
+    /// Something an operator should look at, e.g. `upstream_down`.
+    AlertFired { alert: String, message: String },
+    /// A trade on a pool watched with `watch_whales` crossed the watch's
+    /// `min_volume_usd`.
+    WhaleTrade {
+        // `<network>_<pool>`
+        watch: String,
+        // `<type>:<id>` of the context that owns the watch
+        context: String,
+        network: String,
+        pool: String,
+        tx_hash: String,
+        // "buy" or "sell" of the pool's base token
+        trade_kind: Option<String>,
+        volume_usd: f64,
+        tx_url: Option<String>,
+    },
+}
+
+impl EventKind {
+    /// The event's `type`, one of [`EVENT_TYPES`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventKind::ToolCalled { .. } => "tool_called",
+            EventKind::PluginRegistered { .. } => "plugin_registered",
+            EventKind::RateLimited { .. } => "rate_limited",
+            EventKind::UpstreamError { .. } => "upstream_error",
+            EventKind::AlertFired { .. } => "alert_fired",
+            EventKind::WhaleTrade { .. } => "whale_trade",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerEvent {
+    // Unique per event, so receivers can drop redeliveries
+    pub id: String,
+    pub at: i64,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+/// Something that reacts to every event, e.g. metrics or the audit log.
+///
+/// `handle` runs on the publisher's path, so subscribers doing I/O should
+/// hand the event off rather than wait on it, as [`EventWebhook`] does.
+pub trait EventSubscriber: Send + Sync {
+    fn name(&self) -> &str;
+    fn handle(&self, event: &ServerEvent) -> Result<()>;
+}
+
+pub struct EventBus {
+    sender: broadcast::Sender<ServerEvent>,
+    subscribers: RwLock<Vec<Arc<dyn EventSubscriber>>>,
+    clock: SharedClock,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self {
+            sender: broadcast::channel(EVENT_BUFFER).0,
+            subscribers: RwLock::new(Vec::new()),
+            clock: SharedClock::default(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Adds a subscriber, replacing one with the same name.
+    pub fn attach(&self, subscriber: Arc<dyn EventSubscriber>) {
+        let mut subscribers = self.subscribers.write().unwrap_or_else(|e| e.into_inner());
+        subscribers.retain(|existing| existing.name() != subscriber.name());
+        subscribers.push(subscriber);
+    }
+
+    /// Events published from now on; a receiver that lags misses the
+    /// oldest ones.
+    pub fn subscribe(&self) -> broadcast::Receiver<ServerEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Stamps the event and hands it to every subscriber. A failing
+    /// subscriber is logged and never fails the publisher.
+    pub fn publish(&self, kind: EventKind) {
+        let event = ServerEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            at: self.clock.timestamp(),
+            kind,
+        };
+        let subscribers = self
+            .subscribers
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        for subscriber in subscribers {
+            if let Err(e) = subscriber.handle(&event) {
+                tracing::warn!(
+                    "Event subscriber {} failed for {} {}: {}",
+                    subscriber.name(),
+                    event.kind.name(),
+                    event.id,
+                    e
+                );
+            }
+        }
+        // No receivers just means no stream is listening.
+        let _ = self.sender.send(event);
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Events published since startup, by type; reported by `GET /admin/stats`.
+#[derive(Default)]
+pub struct EventCounters {
+    counts: Mutex<BTreeMap<String, u64>>,
+}
+
+impl EventCounters {
+    pub fn snapshot(&self) -> BTreeMap<String, u64> {
+        self.counts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+impl EventSubscriber for EventCounters {
+    fn name(&self) -> &str {
+        "metrics"
+    }
+
+    fn handle(&self, event: &ServerEvent) -> Result<()> {
+        *self
+            .counts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(event.kind.name().to_string())
+            .or_default() += 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use reqwest::Client;
+use tokio::sync::mpsc;
+
+use super::{EventSubscriber, ServerEvent};
+use crate::config::EventsConfig;
+use crate::dead_letters::{DeadLetters, Delivery, DeliveryKind};
+use crate::error::{NovaError, Result};
+use crate::metering::sink::post;
+
+/// Events waiting for the webhook before new ones are dead-lettered.
+const WEBHOOK_QUEUE: usize = 1024;
+
+/// POSTs events of the types in `events.webhook_events` as JSON to
+/// `events.webhook_url` from a background task.
+///
+/// Each event is sent once; events whose POST fails, or that arrive while
+/// the queue is full, become dead letters an admin can requeue.
+pub struct EventWebhook {
+    url: String,
+    // Empty sends every type
+    types: Vec<String>,
+    client: Client,
+    queue: mpsc::Sender<ServerEvent>,
+    dead_letters: Arc<DeadLetters>,
+}
+
+impl EventWebhook {
+    /// Starts the delivery task; call from within the Tokio runtime.
+    /// `None` without `events.webhook_url`.
+    pub fn from_config(
+        config: &EventsConfig,
+        client: Client,
+        dead_letters: Arc<DeadLetters>,
+    ) -> Option<Self> {
+        let url = config.webhook_url.clone()?;
+        let (queue, mut events) = mpsc::channel::<ServerEvent>(WEBHOOK_QUEUE);
+        let (target, sender, failures) = (url.clone(), client.clone(), dead_letters.clone());
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                if let Err(e) = post(&sender, &target, &event).await {
+                    dead_letter(&failures, &target, &event, 1, &e.to_string());
+                }
+            }
+        });
+        Some(Self {
+            url,
+            types: config.webhook_events.clone(),
+            client,
+            queue,
+            dead_letters,
+        })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Sends a dead-lettered event again, to the URL it first went to.
+    pub async fn redeliver(&self, delivery: Delivery) -> Result<()> {
+        post(&self.client, &delivery.target, &delivery.payload).await
+    }
+}
+
+impl EventSubscriber for EventWebhook {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    fn handle(&self, event: &ServerEvent) -> Result<()> {
+        let kind = event.kind.name();
+        if !self.types.is_empty() && !self.types.iter().any(|wanted| wanted == kind) {
+            return Ok(());
+        }
+        self.queue.try_send(event.clone()).map_err(|e| {
+            dead_letter(&self.dead_letters, &self.url, event, 0, &e.to_string());
+            NovaError::internal(format!("Event webhook queue: {}", e))
+        })
+    }
+}
+
+fn dead_letter(
+    dead_letters: &DeadLetters,
+    url: &str,
+    event: &ServerEvent,
+    attempts: u32,
+    error: &str,
+) {
+    let delivery = serde_json::to_value(event).map(|payload| Delivery {
+        kind: DeliveryKind::EventWebhook,
+        target: url.to_string(),
+        reference: event.id.clone(),
+        context: None,
+        payload,
+    });
+    let recorded = delivery
+        .map_err(NovaError::from)
+        .and_then(|delivery| dead_letters.record(delivery, attempts, error));
+    if let Err(e) = recorded {
+        tracing::warn!("Event webhook dropped event {}: {}", event.id, e);
+    }
+}
@@ -0,0 +1,118 @@
+//! Client IP allow/deny rules (`[access]`), applied to every HTTP route with a
+//! stricter allowlist for the operator routes.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use ipnet::IpNet;
+
+use crate::config::AccessConfig;
+
+/// A CIDR (`10.0.0.0/8`) or a single address (`192.0.2.7`).
+pub fn parse_net(entry: &str) -> Option<IpNet> {
+    let entry = entry.trim();
+    entry
+        .parse::<IpNet>()
+        .ok()
+        .or_else(|| entry.parse::<IpAddr>().ok().map(IpNet::from))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AccessRules {
+    allow: Vec<IpNet>,
+    deny: Vec<IpNet>,
+    admin_allow: Vec<IpNet>,
+    trusted_proxies: Vec<IpNet>,
+}
+
+impl AccessRules {
+    /// Invalid entries are rejected by config validation and skipped here.
+    pub fn new(cfg: &AccessConfig) -> Self {
+        let parse = |entries: &[String]| entries.iter().filter_map(|e| parse_net(e)).collect();
+        Self {
+            allow: parse(&cfg.allow),
+            deny: parse(&cfg.deny),
+            admin_allow: parse(&cfg.admin_allow),
+            trusted_proxies: parse(&cfg.trusted_proxies),
+        }
+    }
+
+    /// The peer, or when the peer is a trusted proxy the rightmost
+    /// `X-Forwarded-For` hop that is not itself a trusted proxy.
+    pub fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        let peer = peer.to_canonical();
+        if !contains(&self.trusted_proxies, peer) {
+            return peer;
+        }
+        let hops = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|hop| hop.trim().parse::<IpAddr>().ok())
+            .map(|hop| hop.to_canonical())
+            .collect::<Vec<_>>();
+        hops.iter()
+            .rev()
+            .find(|hop| !contains(&self.trusted_proxies, **hop))
+            .or(hops.first())
+            .copied()
+            .unwrap_or(peer)
+    }
+
+    /// Deny wins over allow; `admin` routes must also pass `admin_allow`.
+    pub fn permits(&self, ip: IpAddr, admin: bool) -> bool {
+        let ip = ip.to_canonical();
+        if contains(&self.deny, ip) {
+            return false;
+        }
+        if !self.allow.is_empty() && !contains(&self.allow, ip) {
+            return false;
+        }
+        !admin || self.admin_allow.is_empty() || contains(&self.admin_allow, ip)
+    }
+}
+
+fn contains(nets: &[IpNet], ip: IpAddr) -> bool {
+    nets.iter().any(|net| net.contains(&ip))
+}
+
+/// Routes guarded by the admin token.
+fn is_admin_path(path: &str) -> bool {
+    path == "/admin" || path.starts_with("/admin/") || path.starts_with("/contexts/")
+}
+
+/// The client address of `request` under `rules`' trusted proxies.
+pub(crate) fn request_ip(rules: &AccessRules, request: &Request) -> IpAddr {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        // IPv4 clients of a dual-stack listener arrive as `::ffff:a.b.c.d`
+        .map(|info| info.0.ip().to_canonical())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+    rules.client_ip(peer, request.headers())
+}
+
+pub(crate) async fn enforce_access(
+    State(rules): State<Arc<AccessRules>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let ip = request_ip(&rules, &request);
+    if !rules.permits(ip, is_admin_path(super::unversioned(request.uri().path()))) {
+        tracing::debug!(
+            "Rejected {} {} from {}",
+            request.method(),
+            request.uri(),
+            ip
+        );
+        return super::error_response(StatusCode::FORBIDDEN, "Forbidden").into_response();
+    }
+    next.run(request).await
+}
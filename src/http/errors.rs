@@ -0,0 +1,222 @@
+//! One JSON envelope for every HTTP error: [`ErrorResponse`] whose `details`
+//! has the `{code, category, retryable, details}` shape of
+//! [`NovaError::to_data`](crate::NovaError::to_data). Handlers build it with
+//! [`error_response`], the `Api*` extractors turn axum's rejections into it,
+//! and [`normalize_errors`] wraps whatever still leaves as plain text or an
+//! empty body (unmatched routes, 405, request timeouts).
+
+use axum::{
+    async_trait,
+    body::Body,
+    extract::{
+        rejection::{JsonRejection, PathRejection, QueryRejection},
+        FromRequest, FromRequestParts, Path, Query, Request,
+    },
+    http::{header, request::Parts, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+use crate::error::ErrorCategory;
+use crate::plugins::ErrorResponse;
+
+/// What an error handler returns.
+pub(crate) type ApiError = (StatusCode, Json<ErrorResponse>);
+
+/// Plain-text bodies longer than this are replaced by the status reason.
+const MAX_TEXT_BYTES: usize = 4096;
+
+/// `message` in the envelope, with the code, category and retryability of
+/// `status`.
+pub(crate) fn error_response(status: StatusCode, message: impl Into<String>) -> ApiError {
+    with_code(status, status_code(status), message)
+}
+
+fn with_code(status: StatusCode, code: &str, message: impl Into<String>) -> ApiError {
+    let body = ErrorResponse {
+        error: message.into(),
+        details: Some(json!({
+            "code": code,
+            "category": status_category(status),
+            "retryable": is_retryable(status),
+            "details": null,
+        })),
+    };
+    (status, Json(body))
+}
+
+/// Stable snake_case code for errors that are not a `NovaError`.
+pub(crate) fn status_code(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::BAD_REQUEST => "bad_request",
+        StatusCode::UNAUTHORIZED => "unauthorized",
+        StatusCode::FORBIDDEN => "forbidden",
+        StatusCode::NOT_FOUND => "not_found",
+        StatusCode::METHOD_NOT_ALLOWED => "method_not_allowed",
+        StatusCode::NOT_ACCEPTABLE => "not_acceptable",
+        StatusCode::REQUEST_TIMEOUT => "request_timeout",
+        StatusCode::CONFLICT => "conflict",
+        StatusCode::PAYLOAD_TOO_LARGE => "payload_too_large",
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => "unsupported_media_type",
+        StatusCode::UNPROCESSABLE_ENTITY => "invalid_body",
+        StatusCode::TOO_MANY_REQUESTS => "rate_limited",
+        StatusCode::BAD_GATEWAY => "upstream_error",
+        StatusCode::SERVICE_UNAVAILABLE => "unavailable",
+        StatusCode::GATEWAY_TIMEOUT => "timeout",
+        status if status.is_client_error() => "bad_request",
+        _ => "internal_error",
+    }
+}
+
+fn status_category(status: StatusCode) -> ErrorCategory {
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ErrorCategory::PermissionDenied,
+        StatusCode::NOT_FOUND => ErrorCategory::NotFound,
+        StatusCode::CONFLICT => ErrorCategory::Conflict,
+        StatusCode::REQUEST_TIMEOUT | StatusCode::GATEWAY_TIMEOUT => ErrorCategory::Timeout,
+        StatusCode::TOO_MANY_REQUESTS => ErrorCategory::RateLimited,
+        StatusCode::BAD_GATEWAY => ErrorCategory::Upstream,
+        status if status.is_client_error() => ErrorCategory::Validation,
+        _ => ErrorCategory::Internal,
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::REQUEST_TIMEOUT
+            | StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// `axum::Json` whose rejections are [`ErrorResponse`]s: `invalid_json`
+/// (400) for malformed JSON, `invalid_body` (422) for JSON of the wrong
+/// shape, `unsupported_media_type` (415) without a JSON content type and
+/// `payload_too_large` (413) past the route's body limit.
+pub(crate) struct ApiJson<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for ApiJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        match Json::<T>::from_request(request, state).await {
+            Ok(Json(value)) => Ok(Self(value)),
+            Err(rejection) => {
+                let code = match &rejection {
+                    JsonRejection::JsonSyntaxError(_) => "invalid_json",
+                    other => status_code(other.status()),
+                };
+                Err(with_code(rejection.status(), code, rejection.body_text()))
+            }
+        }
+    }
+}
+
+/// `axum::extract::Path` rejecting unparsable segments with `invalid_path`
+/// (400).
+pub(crate) struct ApiPath<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for ApiPath<T>
+where
+    T: DeserializeOwned + Send,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match Path::<T>::from_request_parts(parts, state).await {
+            Ok(Path(value)) => Ok(Self(value)),
+            Err(rejection) => {
+                let code = match &rejection {
+                    PathRejection::FailedToDeserializePathParams(_) => "invalid_path",
+                    other => status_code(other.status()),
+                };
+                Err(with_code(rejection.status(), code, rejection.body_text()))
+            }
+        }
+    }
+}
+
+/// `axum::extract::Query` rejecting malformed query strings with
+/// `invalid_query` (400).
+pub(crate) struct ApiQuery<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for ApiQuery<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match Query::<T>::from_request_parts(parts, state).await {
+            Ok(Query(value)) => Ok(Self(value)),
+            Err(rejection) => {
+                let code = match &rejection {
+                    QueryRejection::FailedToDeserializeQueryString(_) => "invalid_query",
+                    other => status_code(other.status()),
+                };
+                Err(with_code(rejection.status(), code, rejection.body_text()))
+            }
+        }
+    }
+}
+
+/// Rewrites 4xx and 5xx responses that are not JSON into the envelope,
+/// keeping their status and headers. A short plain-text body becomes the
+/// message; otherwise the status reason is used.
+pub(crate) async fn normalize_errors(request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    let status = response.status();
+    if !(status.is_client_error() || status.is_server_error()) || is_json(&response) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let text = axum::body::to_bytes(body, MAX_TEXT_BYTES)
+        .await
+        .ok()
+        .map(|bytes| String::from_utf8_lossy(&bytes).trim().to_string())
+        .filter(|text| !text.is_empty());
+    let message = text.unwrap_or_else(|| {
+        status
+            .canonical_reason()
+            .unwrap_or("Request failed")
+            .to_string()
+    });
+    let (_, Json(envelope)) = error_response(status, message);
+    let body = match serde_json::to_vec(&envelope) {
+        Ok(body) => body,
+        Err(_) => return status.into_response(),
+    };
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("application/json"),
+    );
+    Response::from_parts(parts, Body::from(body))
+}
+
+fn is_json(response: &Response) -> bool {
+    response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|mime| {
+            let mime = mime.trim();
+            mime == "application/json" || mime.ends_with("+json")
+        })
+}
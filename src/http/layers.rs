@@ -0,0 +1,46 @@
+//! Tower layers embedders wrap around Nova's API routes (`/rpc`, plugins,
+//! tools, marketplace, preferences, admin), added with
+//! [`NovaServer::with_api_layer`](crate::NovaServer::with_api_layer).
+//!
+//! A layer sees a request after the `[access]` rules, the auth lockout and
+//! pre-auth rate limit, load shedding, the request timeout and the body
+//! limit, and before the handler authenticates the caller, resolves its
+//! context and applies the per-key rate limit and quotas. So it can reject a
+//! request or rewrite its headers (e.g. set `x-nova-context-*` from a tenant
+//! header) before Nova's auth runs, and sees Nova's auth and rate-limit
+//! rejections only as responses. Layers run in the order they were added.
+//! `/mcp` and the health checks are not wrapped.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use axum::{extract::Request, response::IntoResponse, routing::Route, Router};
+use tower::{Layer, Service};
+
+use super::AppState;
+
+/// One embedder layer, ready to apply to the API router.
+#[derive(Clone)]
+pub struct ApiLayer(Arc<dyn Fn(Router<AppState>) -> Router<AppState> + Send + Sync>);
+
+impl ApiLayer {
+    pub fn new<L>(layer: L) -> Self
+    where
+        L: Layer<Route> + Clone + Send + Sync + 'static,
+        L::Service: Service<Request> + Clone + Send + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        Self(Arc::new(move |router| router.route_layer(layer.clone())))
+    }
+
+    pub(crate) fn apply(&self, router: Router<AppState>) -> Router<AppState> {
+        (self.0)(router)
+    }
+}
+
+/// Wraps `api` in `layers`, the first one outermost.
+pub(crate) fn wrap(api: Router<AppState>, layers: &[ApiLayer]) -> Router<AppState> {
+    layers.iter().rev().fold(api, |api, layer| layer.apply(api))
+}
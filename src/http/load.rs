@@ -0,0 +1,146 @@
+//! Global cap on requests in flight (`server.max_concurrent_requests`), with
+//! a bounded queue in front of it. Requests beyond the queue, or queued for
+//! longer than `server.queue_timeout_ms`, are shed with 503 so a burst
+//! degrades into quick refusals instead of a pile of tasks that all time out.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
+
+use crate::config::ServerConfig;
+
+#[derive(Debug)]
+pub struct LoadShedder {
+    // `None` when `max_concurrent_requests` is 0
+    permits: Option<Arc<Semaphore>>,
+    max_in_flight: usize,
+    max_queued: usize,
+    queue_timeout: Duration,
+    in_flight: AtomicUsize,
+    queued: AtomicUsize,
+    peak_queued: AtomicUsize,
+    shed_queue_full: AtomicU64,
+    shed_timed_out: AtomicU64,
+}
+
+/// Counters for `GET /admin/stats`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LoadStats {
+    pub max_in_flight: usize,
+    pub in_flight: usize,
+    pub max_queued: usize,
+    pub queued: usize,
+    pub peak_queued: usize,
+    pub shed_queue_full: u64,
+    pub shed_timed_out: u64,
+}
+
+impl LoadShedder {
+    pub fn new(cfg: &ServerConfig) -> Self {
+        Self {
+            permits: (cfg.max_concurrent_requests > 0)
+                .then(|| Arc::new(Semaphore::new(cfg.max_concurrent_requests))),
+            max_in_flight: cfg.max_concurrent_requests,
+            max_queued: cfg.max_queued_requests,
+            queue_timeout: Duration::from_millis(cfg.queue_timeout_ms),
+            in_flight: AtomicUsize::new(0),
+            queued: AtomicUsize::new(0),
+            peak_queued: AtomicUsize::new(0),
+            shed_queue_full: AtomicU64::new(0),
+            shed_timed_out: AtomicU64::new(0),
+        }
+    }
+
+    pub fn stats(&self) -> LoadStats {
+        LoadStats {
+            max_in_flight: self.max_in_flight,
+            in_flight: self.in_flight.load(Ordering::Relaxed),
+            max_queued: self.max_queued,
+            queued: self.queued.load(Ordering::Relaxed),
+            peak_queued: self.peak_queued.load(Ordering::Relaxed),
+            shed_queue_full: self.shed_queue_full.load(Ordering::Relaxed),
+            shed_timed_out: self.shed_timed_out.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Probes must answer even when the server is saturated.
+fn is_exempt(path: &str) -> bool {
+    matches!(path, "/healthz" | "/readyz")
+}
+
+pub(crate) async fn shed_load(
+    State(load): State<Arc<LoadShedder>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(permits) = load.permits.as_ref() else {
+        return next.run(request).await;
+    };
+    if is_exempt(request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    let _permit = match Arc::clone(permits).try_acquire_owned() {
+        Ok(permit) => permit,
+        Err(_) => {
+            let queued = Gauge::enter(&load.queued);
+            if queued.depth > load.max_queued {
+                load.shed_queue_full.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!("Shedding {}: request queue is full", request.uri());
+                return overloaded();
+            }
+            load.peak_queued.fetch_max(queued.depth, Ordering::Relaxed);
+            let waited =
+                tokio::time::timeout(load.queue_timeout, Arc::clone(permits).acquire_owned()).await;
+            match waited {
+                Ok(Ok(permit)) => permit,
+                _ => {
+                    load.shed_timed_out.fetch_add(1, Ordering::Relaxed);
+                    tracing::warn!("Shedding {}: queued past the timeout", request.uri());
+                    return overloaded();
+                }
+            }
+        }
+    };
+
+    let _in_flight = Gauge::enter(&load.in_flight);
+    next.run(request).await
+}
+
+/// Counts a request into a gauge until dropped, including when the client
+/// goes away and the handler future is cancelled.
+struct Gauge<'a> {
+    value: &'a AtomicUsize,
+    depth: usize,
+}
+
+impl<'a> Gauge<'a> {
+    fn enter(value: &'a AtomicUsize) -> Self {
+        let depth = value.fetch_add(1, Ordering::SeqCst) + 1;
+        Self { value, depth }
+    }
+}
+
+impl Drop for Gauge<'_> {
+    fn drop(&mut self) {
+        self.value.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn overloaded() -> Response {
+    (
+        [(header::RETRY_AFTER, "1")],
+        super::error_response(StatusCode::SERVICE_UNAVAILABLE, "Server is overloaded"),
+    )
+        .into_response()
+}
@@ -0,0 +1,1043 @@
+pub mod access;
+mod errors;
+pub mod layers;
+pub mod load;
+mod streamable;
+
+pub(crate) use errors::{error_response, ApiJson, ApiPath, ApiQuery};
+pub use layers::ApiLayer;
+
+use crate::admin;
+use crate::audit::AuditEvent;
+use crate::auth::{
+    ip_source, key_source, AdminAuth, AuthLockout, Identity, JwtAuth, KeyIdentity, TelegramAuth,
+    SCOPE_TOOLS, TELEGRAM_INIT_DATA_HEADER, TELEGRAM_LOGIN_HEADER,
+};
+use crate::clock::SharedClock;
+use crate::config::ServerConfig;
+use crate::events::EventKind;
+use crate::mcp::dto::{McpError, McpRequest, McpResponse};
+use crate::mcp::protocol::ProtocolVersion;
+use crate::mcp::session::{McpSession, SessionStore};
+use crate::oauth;
+use crate::plugins::{self, ContextIdFormat, PluginContextType, PluginManager, RequestContext};
+use crate::preferences;
+use crate::readiness::ReadinessReport;
+use crate::reload::{spawn_sighup_listener, ReloadSummary};
+use crate::{ApiKeyAuth, NovaConfig, NovaServer};
+use anyhow::Result;
+use axum::{
+    body::Body,
+    extract::{DefaultBodyLimit, MatchedPath, Request},
+    http::{header, HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tower_http::compression::{
+    predicate::{DefaultPredicate, Predicate, SizeAbove},
+    CompressionLayer,
+};
+use tower_http::timeout::TimeoutLayer;
+use tracing::Instrument;
+
+/// Everything the HTTP routes share: the server plus the auth, session,
+/// access and load state built from the config. Build one with
+/// [`AppState::new`] and turn it into routes with [`router`].
+#[derive(Clone)]
+pub struct AppState {
+    server: Arc<NovaServer>,
+    plugin_manager: Arc<PluginManager>,
+    auth: ApiKeyAuth,
+    admin: AdminAuth,
+    telegram: Option<TelegramAuth>,
+    jwt: Option<Arc<JwtAuth>>,
+    sessions: Arc<SessionStore>,
+    streams: Arc<streamable::EventHub>,
+    started_at: Instant,
+    access: Arc<access::AccessRules>,
+    lockout: Arc<AuthLockout>,
+    load: Arc<load::LoadShedder>,
+    clock: SharedClock,
+    // The config the state was built from; [`router`] reads its layers from it
+    startup: Arc<NovaConfig>,
+}
+
+impl AppState {
+    /// Like [`try_new`](Self::try_new), for a config that already passed
+    /// [`NovaConfig::validate`].
+    ///
+    /// # Panics
+    ///
+    /// If the JWKS HTTP client cannot be built, e.g. from a bad CA file.
+    pub fn new(server: NovaServer, config: &NovaConfig) -> Self {
+        match Self::try_new(server, config) {
+            Ok(state) => state,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// State for serving `server` with the auth, sessions, access rules and
+    /// load limits in `config`.
+    pub fn try_new(server: NovaServer, config: &NovaConfig) -> crate::error::Result<Self> {
+        let plugin_manager = server.plugin_manager_arc();
+        let clock = server.clock().clone();
+        let streams = Arc::new(streamable::EventHub::default());
+        let expired = Arc::clone(&streams);
+        Ok(Self {
+            server: Arc::new(server),
+            plugin_manager,
+            auth: crate::ApiKeyAuth::new(&config.auth),
+            admin: AdminAuth::new(&config.admin),
+            telegram: TelegramAuth::new(&config.auth),
+            jwt: JwtAuth::new(
+                &config.auth,
+                crate::outbound::build_client(&config.outbound, "jwks", |b| {
+                    b.timeout(Duration::from_secs(10))
+                })?,
+            )
+            .map(|jwt| Arc::new(jwt.with_clock(clock.clone()))),
+            sessions: Arc::new(
+                SessionStore::new(Duration::from_secs(config.server.session_idle_ttl_secs))
+                    .with_max_sessions(config.server.max_sessions)
+                    .with_eviction_hook(move |id| expired.remove(id)),
+            ),
+            streams,
+            started_at: Instant::now(),
+            access: Arc::new(access::AccessRules::new(&config.access)),
+            lockout: Arc::new(AuthLockout::new().with_clock(clock.clone())),
+            load: Arc::new(load::LoadShedder::new(&config.server)),
+            clock,
+            startup: Arc::new(config.clone()),
+        })
+    }
+
+    pub(crate) fn server(&self) -> Arc<NovaServer> {
+        Arc::clone(&self.server)
+    }
+
+    pub(crate) fn plugin_manager(&self) -> &PluginManager {
+        self.plugin_manager.as_ref()
+    }
+
+    pub(crate) fn plugin_manager_arc(&self) -> Arc<PluginManager> {
+        Arc::clone(&self.plugin_manager)
+    }
+
+    pub(crate) fn auth(&self) -> &ApiKeyAuth {
+        &self.auth
+    }
+
+    pub(crate) fn admin(&self) -> &AdminAuth {
+        &self.admin
+    }
+
+    pub(crate) fn clock(&self) -> &SharedClock {
+        &self.clock
+    }
+
+    pub(crate) fn lockout(&self) -> &AuthLockout {
+        &self.lockout
+    }
+
+    pub(crate) fn load(&self) -> &load::LoadShedder {
+        &self.load
+    }
+
+    pub(crate) fn config(&self) -> Arc<NovaConfig> {
+        self.server.runtime().current()
+    }
+
+    pub(crate) fn limit_per_minute(&self) -> u32 {
+        self.config().apis.rate_limit_per_minute
+    }
+
+    pub(crate) fn set_limit_per_minute(&self, limit: u32) {
+        self.server
+            .runtime()
+            .update(|config| config.apis.rate_limit_per_minute = limit);
+    }
+
+    /// Re-reads config and pushes reloadable settings into the live state.
+    /// Reloads the config on behalf of `who` and audits the changed fields.
+    pub(crate) fn reload(&self, who: String) -> crate::error::Result<ReloadSummary> {
+        let before = serde_json::to_value(self.config().redacted())?;
+        let summary = self.server.runtime().reload()?;
+        if summary
+            .changed
+            .iter()
+            .any(|c| c == "auth.allowed_keys" || c == "auth.named_keys")
+        {
+            self.auth.reload_keys(&self.config().auth);
+        }
+        let after = serde_json::to_value(self.config().redacted())?;
+        // Old and new values of just the fields that changed, keyed by dotted path
+        let pick = |config: &serde_json::Value| {
+            summary
+                .changed
+                .iter()
+                .map(|field| {
+                    let pointer = format!("/{}", field.replace('.', "/"));
+                    let value = config.pointer(&pointer).cloned().unwrap_or_default();
+                    (field.clone(), value)
+                })
+                .collect::<serde_json::Map<_, _>>()
+        };
+        self.server.audit().record_or_warn(AuditEvent {
+            who,
+            api_key: None,
+            action: "admin.reload",
+            target: "config".to_string(),
+            before: Some(pick(&before).into()),
+            after: Some(pick(&after).into()),
+        });
+        Ok(summary)
+    }
+
+    pub(crate) fn session_count(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub(crate) async fn rate_entries(&self) -> usize {
+        self.server.rate_limits().active(RATE_PREFIX)
+    }
+
+    pub(crate) fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+}
+
+/// Header carrying the session id issued by `initialize`.
+const SESSION_HEADER: &str = "mcp-session-id";
+
+async fn handle_rpc(
+    axum::extract::State(state): axum::extract::State<AppState>,
+    headers: axum::http::HeaderMap,
+    ApiJson(req): ApiJson<McpRequest>,
+) -> Response {
+    // API key enforcement
+    let presented = headers
+        .get(state.auth().header_name())
+        .and_then(|v| v.to_str().ok());
+    let Some(key) = caller_key(&state, &headers).await else {
+        let res = rpc_error_response(None, StatusCode::UNAUTHORIZED, "Unauthorized");
+        return Json(res).into_response();
+    };
+    let owner = session_owner(&key, presented);
+
+    let is_initialize = req.method == "initialize";
+    let session_id = headers
+        .get(SESSION_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|_| !is_initialize);
+    let mut session = match session_id {
+        Some(id) => match state.sessions.get(id, owner.as_deref()) {
+            Some(session) => session,
+            None => {
+                let res = rpc_error_response(req.id, StatusCode::NOT_FOUND, "Session not found");
+                return (StatusCode::NOT_FOUND, Json(res)).into_response();
+            }
+        },
+        None => McpSession::new(),
+    };
+
+    // Clients on newer revisions announce theirs per request.
+    if let Some(requested) = headers
+        .get("mcp-protocol-version")
+        .and_then(|v| v.to_str().ok())
+        .filter(|_| !is_initialize)
+    {
+        match ProtocolVersion::negotiate(Some(requested)) {
+            Ok(version) => session.set_protocol_version(version),
+            Err(error) => {
+                return Json(McpResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id: req.id,
+                    result: None,
+                    error: Some(error),
+                })
+                .into_response()
+            }
+        }
+    }
+
+    let id_format = state.config().context.id_format();
+    let context = if let Some(identity) = verified_identity(&state, &headers, id_format).await {
+        match identity {
+            Ok(identity) if identity.allows(SCOPE_TOOLS) => identity.context,
+            Ok(_) => {
+                let message = format!("Token lacks the {} scope", SCOPE_TOOLS);
+                let res = rpc_error_response(req.id, StatusCode::FORBIDDEN, message);
+                return Json(res).into_response();
+            }
+            Err(reason) => {
+                let res = rpc_error_response(req.id, StatusCode::UNAUTHORIZED, reason);
+                return Json(res).into_response();
+            }
+        }
+    } else {
+        let context = match session.context() {
+            Some(context) if !has_context_headers(&headers) => context.clone(),
+            _ => match extract_context_from_headers(&headers, req.id.clone(), id_format) {
+                Ok(context) => context,
+                Err(response) => return Json(*response).into_response(),
+            },
+        };
+        match attach_actor(context, &headers, req.id.clone(), id_format) {
+            Ok(context) => context,
+            Err(response) => return Json(*response).into_response(),
+        }
+    };
+
+    if let Some(code) = check_rate_limit(&state, &key.rate_key(&context.principal())).await {
+        let res = rpc_error_response(req.id.clone(), code, "Rate limit exceeded");
+        return Json(res).into_response();
+    }
+
+    let server = state.server();
+    let res = crate::mcp::handler::handle_session_request(
+        server.as_ref(),
+        &mut session,
+        req,
+        Some(context),
+    )
+    .await;
+
+    if is_initialize {
+        if res.error.is_some() {
+            return Json(res).into_response();
+        }
+        let id = session.id().to_string();
+        if !state.sessions.insert(session, owner.as_deref()) {
+            let res =
+                rpc_error_response(res.id, StatusCode::SERVICE_UNAVAILABLE, "Too many sessions");
+            return (StatusCode::SERVICE_UNAVAILABLE, Json(res)).into_response();
+        }
+        return ([(SESSION_HEADER, id)], Json(res)).into_response();
+    }
+    if session_id.is_some() {
+        state.sessions.update(session);
+    }
+    Json(res).into_response()
+}
+
+/// Ends the session named by `Mcp-Session-Id`.
+async fn end_session(
+    axum::extract::State(state): axum::extract::State<AppState>,
+    headers: axum::http::HeaderMap,
+) -> StatusCode {
+    let presented = headers
+        .get(state.auth().header_name())
+        .and_then(|v| v.to_str().ok());
+    let Some(key) = caller_key(&state, &headers).await else {
+        return StatusCode::UNAUTHORIZED;
+    };
+    let owner = session_owner(&key, presented);
+    match headers.get(SESSION_HEADER).and_then(|v| v.to_str().ok()) {
+        Some(id) if state.sessions.remove(id, owner.as_deref()) => {
+            state.streams.remove(id);
+            StatusCode::NO_CONTENT
+        }
+        Some(_) => StatusCode::NOT_FOUND,
+        None => StatusCode::BAD_REQUEST,
+    }
+}
+
+async fn healthz() -> &'static str {
+    "ok"
+}
+
+/// 503 when a component check fails; `upstreams` shows whether failures are a provider's.
+async fn readyz(
+    axum::extract::State(state): axum::extract::State<AppState>,
+) -> (StatusCode, Json<ReadinessReport>) {
+    let report = state.server().readiness().await;
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
+/// Serves [`router`] on `server.unix_socket` or `server.bind_address` and
+/// reloads the config on SIGHUP, until the listener fails.
+pub async fn run_http_server(server: NovaServer, config: NovaConfig) -> Result<()> {
+    let state = AppState::try_new(server, &config)?;
+    let reload_state = state.clone();
+    spawn_sighup_listener(
+        move || match reload_state.reload("signal:SIGHUP".to_string()) {
+            Ok(summary) => tracing::info!("Config reloaded on SIGHUP: {:?}", summary.changed),
+            Err(e) => tracing::error!("Config reload failed: {}", e),
+        },
+    );
+    let app = router(state);
+
+    #[cfg(unix)]
+    if let Some(path) = config.server.unix_socket.as_deref() {
+        tracing::info!("Starting HTTP MCP server on unix socket {}", path);
+        return serve_unix_socket(path, app).await;
+    }
+
+    let ip = config
+        .server
+        .bind_ip()
+        .ok_or_else(|| anyhow::anyhow!("Invalid server.bind_address"))?;
+    let addr = SocketAddr::new(ip, config.server.port);
+    tracing::info!("Starting HTTP MCP server on {}", addr);
+    let listener = bind_tcp(addr)?;
+    let app = app.into_make_service_with_connect_info::<SocketAddr>();
+    if let Err(e) = axum::serve(listener, app).await {
+        tracing::error!("HTTP server error: {}", e);
+    }
+    Ok(())
+}
+
+/// Every Nova route (`/mcp`, `/v1/*` and its root aliases, health checks)
+/// behind Nova's own middleware, for serving directly or merging into an
+/// embedder's axum app.
+///
+/// Spawns the tasks that push registry changes and alerts to MCP streams, so
+/// call it from within the Tokio runtime. `[access]` rules read the client
+/// address from [`ConnectInfo`](axum::extract::ConnectInfo): serve the app
+/// with `into_make_service_with_connect_info::<SocketAddr>()`, or every
+/// client counts as `0.0.0.0`. SIGHUP reloads are left to the embedder,
+/// through `server.runtime()`. Layers from
+/// [`NovaServer::with_api_layer`] wrap the API routes as described in
+/// [`layers`].
+pub fn router(state: AppState) -> Router {
+    let config = Arc::clone(&state.startup);
+    tokio::spawn(streamable::forward_list_changes(state.clone()));
+    tokio::spawn(streamable::forward_alerts(state.clone()));
+
+    // Served under `/v1` and, deprecated, at the root
+    let api = Router::new()
+        .route("/rpc", post(handle_rpc).delete(end_session))
+        .route("/plugins/register", post(plugins::register_plugin))
+        .route(
+            "/plugins/register-manifest",
+            post(plugins::register_manifest),
+        )
+        .route(
+            "/plugins/:plugin_id",
+            get(plugins::get_plugin)
+                .delete(plugins::unregister_plugin)
+                .patch(plugins::update_plugin)
+                .put(plugins::update_plugin),
+        )
+        .route(
+            "/plugins/:plugin_id/enablement",
+            get(plugins::get_plugin_enablement),
+        )
+        .route("/plugins", get(plugins::list_plugins))
+        .route("/plugins/:plugin_id/call", post(plugins::invoke_plugin))
+        .route("/plugins/enable", post(plugins::set_plugin_enablement))
+        .route("/jobs/:job_id", get(plugins::get_job))
+        .route("/jobs/:job_id/callback", post(plugins::job_callback))
+        .route("/tools/register", post(plugins::register_plugin))
+        .route("/tools/register-manifest", post(plugins::register_manifest))
+        .route(
+            "/tools/:plugin_id",
+            get(plugins::get_plugin)
+                .delete(plugins::unregister_plugin)
+                .patch(plugins::update_plugin)
+                .put(plugins::update_plugin),
+        )
+        .route(
+            "/tools/:plugin_id/enablement",
+            get(plugins::get_plugin_enablement),
+        )
+        .route("/tools", get(plugins::list_plugins))
+        .route("/tools/:plugin_id/call", post(plugins::invoke_plugin))
+        .route("/tools/enable", post(plugins::set_plugin_enablement))
+        .route("/marketplace", get(plugins::list_marketplace))
+        .route(
+            "/marketplace/:plugin_id/install",
+            post(plugins::install_listed_plugin),
+        )
+        .route(
+            "/marketplace/:plugin_id/ratings",
+            get(plugins::plugin_ratings),
+        )
+        .route("/marketplace/:plugin_id/rating", put(plugins::rate_plugin))
+        .route(
+            "/marketplace/:plugin_id/reports",
+            post(plugins::report_plugin),
+        )
+        .route(
+            "/preferences",
+            get(preferences::get_preferences)
+                .put(preferences::put_preferences)
+                .delete(preferences::delete_preferences),
+        )
+        .route("/admin/stats", get(admin::stats))
+        .route("/admin/upstreams", get(admin::list_upstreams))
+        .route("/admin/jobs", get(admin::list_jobs))
+        .route("/admin/keys", get(admin::list_keys).post(admin::create_key))
+        .route("/admin/keys/:key_id", delete(admin::delete_key))
+        .route(
+            "/admin/policies",
+            get(admin::get_policies).put(admin::update_policies),
+        )
+        .route("/admin/backup", post(admin::trigger_backup))
+        .route("/admin/config", get(admin::dump_config))
+        .route("/admin/reload", post(admin::reload_config))
+        .route("/admin/audit", get(admin::list_audit))
+        .route("/admin/metering/usage", get(admin::metering_usage))
+        .route("/admin/metering/events", get(admin::metering_events))
+        .route("/admin/plugins/stale", get(admin::stale_plugins))
+        .route("/admin/dead-letters", get(admin::list_dead_letters))
+        .route(
+            "/admin/dead-letters/:letter_id",
+            delete(admin::delete_dead_letter),
+        )
+        .route(
+            "/admin/dead-letters/:letter_id/requeue",
+            post(admin::requeue_dead_letter),
+        )
+        .route("/admin/marketplace", get(admin::list_listings))
+        .route("/admin/marketplace/:plugin_id", put(admin::review_listing))
+        .route(
+            "/admin/marketplace/:plugin_id/reports",
+            get(admin::listing_reports),
+        )
+        .route(
+            "/admin/oauth/clients",
+            get(admin::list_oauth_clients).post(admin::create_oauth_client),
+        )
+        .route(
+            "/admin/oauth/clients/:client_id",
+            delete(admin::delete_oauth_client),
+        )
+        .route(
+            "/admin/quotas/:context_type/:context_id",
+            get(admin::get_quotas).put(admin::update_quotas),
+        )
+        .route("/admin/token-mappings", get(admin::list_token_mappings))
+        .route(
+            "/admin/token-mappings/:mapping_id",
+            put(admin::put_token_mapping).delete(admin::delete_token_mapping),
+        )
+        .route("/oauth/token", post(oauth::issue_token))
+        .route(
+            "/contexts/:context_type/:context_id",
+            delete(admin::delete_context),
+        );
+    let api = layers::wrap(api, state.server.api_layers());
+    let legacy = api.clone().route_layer(middleware::from_fn_with_state(
+        sunset_header(&config.server),
+        mark_deprecated,
+    ));
+
+    // MCP has its own protocol versioning and probes stay put
+    let app = Router::new()
+        .route(
+            "/mcp",
+            post(streamable::post_messages)
+                .get(streamable::open_stream)
+                .delete(streamable::close_session),
+        )
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .nest(API_PREFIX, api)
+        .merge(legacy)
+        .route_layer(middleware::from_fn_with_state(
+            Arc::new(config.server.clone()),
+            enforce_body_limit,
+        ))
+        // Limits are enforced per route by `enforce_body_limit`.
+        .layer(DefaultBodyLimit::disable())
+        // Safety net above the per-tool budgets enforced in the MCP handler.
+        .layer(TimeoutLayer::new(Duration::from_secs(
+            config.timeouts.request_timeout_secs,
+        )))
+        // Queue wait is not part of the request timeout.
+        .layer(middleware::from_fn_with_state(
+            Arc::clone(&state.load),
+            load::shed_load,
+        ))
+        .layer(middleware::from_fn_with_state(state.clone(), guard_api_key))
+        // Outermost, so rejected clients never reach auth or body parsing.
+        .layer(middleware::from_fn_with_state(
+            Arc::clone(&state.access),
+            access::enforce_access,
+        ))
+        // Outside everything that may answer with a plain-text or empty error
+        .layer(middleware::from_fn(errors::normalize_errors))
+        .with_state(state);
+
+    if config.compression.enabled {
+        tracing::info!(
+            "Response compression enabled (gzip={}, br={}, min_size={}B)",
+            config.compression.gzip,
+            config.compression.br,
+            config.compression.min_size_bytes
+        );
+        app.layer(
+            CompressionLayer::new()
+                .gzip(config.compression.gzip)
+                .br(config.compression.br)
+                .compress_when(
+                    DefaultPredicate::new().and(SizeAbove::new(config.compression.min_size_bytes)),
+                ),
+        )
+    } else {
+        app
+    }
+}
+
+/// Binds the TCP listener. The IPv6 wildcard `::` also accepts IPv4 clients,
+/// whatever the platform's `IPV6_V6ONLY` default.
+fn bind_tcp(addr: SocketAddr) -> std::io::Result<tokio::net::TcpListener> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    if addr.is_ipv6() && addr.ip().is_unspecified() {
+        socket.set_only_v6(false)?;
+    }
+    #[cfg(unix)]
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+    tokio::net::TcpListener::from_std(socket.into())
+}
+
+/// Serves `app` on a Unix socket at `path`, replacing a socket left behind by
+/// an earlier run. Socket clients count as `127.0.0.1` for `[access]` rules.
+#[cfg(unix)]
+async fn serve_unix_socket(path: &str, app: Router) -> Result<()> {
+    use hyper_util::rt::TokioIo;
+    use hyper_util::service::TowerToHyperService;
+    use std::os::unix::fs::FileTypeExt;
+
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => std::fs::remove_file(path)?,
+        Ok(_) => anyhow::bail!("server.unix_socket {} exists and is not a socket", path),
+        Err(_) => {}
+    }
+    let listener = tokio::net::UnixListener::bind(path)?;
+    let peer = SocketAddr::from(([127, 0, 0, 1], 0));
+    let app = app.layer(axum::Extension(axum::extract::ConnectInfo(peer)));
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                tracing::warn!("Unix socket accept failed: {}", e);
+                continue;
+            }
+        };
+        let service = TowerToHyperService::new(app.clone());
+        tokio::spawn(async move {
+            let connection = hyper::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service);
+            if let Err(e) = connection.await {
+                tracing::debug!("Unix socket connection error: {}", e);
+            }
+        });
+    }
+}
+
+/// Prefix of the current route tree; the same routes at the root are
+/// deprecated aliases.
+pub const API_PREFIX: &str = "/v1";
+
+/// `path` without the [`API_PREFIX`], so `/v1/admin/stats` and
+/// `/admin/stats` get the same access, auth and body-limit rules.
+pub(crate) fn unversioned(path: &str) -> &str {
+    match path.strip_prefix(API_PREFIX) {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => rest,
+        _ => path,
+    }
+}
+
+/// `Sunset` value (an HTTP-date) for `server.legacy_sunset`.
+fn sunset_header(server: &ServerConfig) -> Option<HeaderValue> {
+    let date = server.legacy_sunset_date()?;
+    let value = date.format("%a, %d %b %Y 00:00:00 GMT").to_string();
+    HeaderValue::try_from(value).ok()
+}
+
+/// Marks responses from the root aliases of `/v1` routes as deprecated
+/// (`Deprecation`, `Sunset` from `server.legacy_sunset`) and links the
+/// versioned route.
+async fn mark_deprecated(
+    axum::extract::State(sunset): axum::extract::State<Option<HeaderValue>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let successor = format!(
+        "<{}{}>; rel=\"successor-version\"",
+        API_PREFIX,
+        request.uri().path()
+    );
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    if let Some(sunset) = sunset {
+        headers.insert("sunset", sunset);
+    }
+    if let Ok(link) = HeaderValue::try_from(successor) {
+        headers.append(header::LINK, link);
+    }
+    response
+}
+
+/// Caps the request body at the limit configured for the matched route
+/// (`server.route_body_limits`), falling back to `server.max_body_bytes`.
+/// Extractors reading past the cap reject with 413.
+async fn enforce_body_limit(
+    axum::extract::State(server): axum::extract::State<Arc<ServerConfig>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let limit = match request.extensions().get::<MatchedPath>() {
+        Some(path) => server.body_limit_for(unversioned(path.as_str())),
+        None => server.max_body_bytes,
+    };
+    let (parts, body) = request.into_parts();
+    let body = Body::new(http_body_util::Limited::new(body, limit));
+    next.run(Request::from_parts(parts, body)).await
+}
+
+/// Runs the request inside a span naming the API key it authenticated with,
+/// so every log line it emits says which key made the call.
+///
+/// Requests that fail auth or are malformed count against a per-IP budget
+/// (`apis.pre_auth_rate_limit_per_minute`); once it is spent the IP is
+/// refused before auth or body parsing until the minute is over. Wrong keys
+/// also count towards a lockout of the client IP and of the presented key.
+/// A locked-out IP is refused with 429 before the key is checked; a locked-out
+/// key only refuses further wrong attempts, since the key is checked first.
+/// In jwt mode a request with no valid key but a verified bearer token has
+/// not failed auth.
+async fn guard_api_key(
+    axum::extract::State(state): axum::extract::State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let ip = access::request_ip(&state.access, &request).to_string();
+    let pre_auth_limit = state.config().apis.pre_auth_rate_limit_per_minute;
+    if pre_auth_limit > 0 && pre_auth_exhausted(&state, &ip, pre_auth_limit).await {
+        let now_sec = state.clock.timestamp().rem_euclid(60) as u64;
+        return too_many_requests("Too many failed or malformed requests", 60 - now_sec);
+    }
+
+    let presented = request
+        .headers()
+        .get(state.auth().header_name())
+        .and_then(|v| v.to_str().ok())
+        .filter(|key| !key.is_empty());
+    let sources = match presented {
+        Some(key) if state.auth().is_enabled() => vec![ip_source(&ip), key_source(key)],
+        _ => Vec::new(),
+    };
+    // Only the address is checked up front: a key lockout never refuses the right key
+    if let Some(wait) = state
+        .lockout
+        .retry_after(sources.get(..1).unwrap_or_default())
+    {
+        return too_many_requests("Too many failed authentication attempts", wait.as_secs());
+    }
+    let mut key = state.auth().authenticate(presented);
+    match &key {
+        Some(_) => state.lockout.record_success(&sources),
+        None if !sources.is_empty() => {
+            if let Some(wait) = state.lockout.retry_after(&sources) {
+                return too_many_requests(
+                    "Too many failed authentication attempts",
+                    wait.as_secs(),
+                );
+            }
+            state.lockout.record_failure(&sources, &state.config().auth);
+        }
+        None => {}
+    }
+    let needs_key = !is_keyless_path(unversioned(request.uri().path()));
+    if key.is_none() && needs_key {
+        key = bearer_key(&state, request.headers()).await;
+    }
+    let api_key = key.as_ref().map_or("-".to_string(), |key| key.to_string());
+
+    let span = tracing::info_span!("request", api_key = %api_key);
+    let response = next.run(request).instrument(span).await;
+    let status = response.status();
+    let failed = (needs_key && key.is_none()) || status == StatusCode::UNAUTHORIZED;
+    if pre_auth_limit > 0 && (failed || is_malformed(status)) {
+        count_pre_auth(&state, &ip).await;
+    }
+    response
+}
+
+fn too_many_requests(message: &str, retry_after_secs: u64) -> Response {
+    (
+        [(
+            axum::http::header::RETRY_AFTER,
+            retry_after_secs.max(1).to_string(),
+        )],
+        error_response(StatusCode::TOO_MANY_REQUESTS, message),
+    )
+        .into_response()
+}
+
+fn has_context_headers(headers: &axum::http::HeaderMap) -> bool {
+    headers.contains_key("x-nova-context-type") || headers.contains_key("x-nova-context-id")
+}
+
+/// The API key the request authenticated with, or in jwt mode a verified
+/// bearer token standing in for one.
+pub(crate) async fn caller_key(
+    state: &AppState,
+    headers: &axum::http::HeaderMap,
+) -> Option<KeyIdentity> {
+    let presented = headers
+        .get(state.auth().header_name())
+        .and_then(|v| v.to_str().ok());
+    match state.auth().authenticate(presented) {
+        Some(key) => Some(key),
+        None => bearer_key(state, headers).await,
+    }
+}
+
+async fn bearer_key(state: &AppState, headers: &axum::http::HeaderMap) -> Option<KeyIdentity> {
+    if state.telegram.is_some() || state.jwt.is_none() {
+        return None;
+    }
+    let id_format = state.config().context.id_format();
+    match verified_identity(state, headers, id_format).await? {
+        Ok(identity) => Some(KeyIdentity::Bearer(identity.context.key())),
+        Err(_) => None,
+    }
+}
+
+/// Who may resume a session: the API key that opened it, or for a bearer
+/// caller the context the token was issued for.
+fn session_owner(key: &KeyIdentity, presented: Option<&str>) -> Option<String> {
+    match key {
+        KeyIdentity::Bearer(context) => Some(format!("bearer:{}", context)),
+        _ => presented.map(str::to_string),
+    }
+}
+
+/// Identity from signed credentials in telegram or jwt mode, where every
+/// request must carry them so a session cannot outlive the credential. `None`
+/// in api_key mode, where the context headers are taken as sent.
+pub(crate) async fn verified_identity(
+    state: &AppState,
+    headers: &axum::http::HeaderMap,
+    id_format: ContextIdFormat,
+) -> Option<std::result::Result<Identity, String>> {
+    if let Some(telegram) = &state.telegram {
+        let identity = telegram_context(telegram, headers, id_format, state.clock.timestamp())
+            .map(|context| Identity {
+                context,
+                scopes: None,
+            })
+            .map_err(|reason| format!("Invalid Telegram auth data: {}", reason));
+        return Some(identity);
+    }
+    let jwt = state.jwt.as_ref()?;
+    let token = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty());
+    let identity = match token {
+        Some(token) => jwt.verify(token, id_format).await,
+        None => Err("missing Authorization: Bearer header".to_string()),
+    };
+    Some(identity.map_err(|reason| format!("Invalid bearer token: {}", reason)))
+}
+
+/// Context proven by `x-telegram-init-data` or `x-telegram-login`.
+fn telegram_context(
+    telegram: &TelegramAuth,
+    headers: &axum::http::HeaderMap,
+    id_format: ContextIdFormat,
+    now: i64,
+) -> std::result::Result<RequestContext, String> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| !value.trim().is_empty())
+    };
+    let context = match (
+        header(TELEGRAM_INIT_DATA_HEADER),
+        header(TELEGRAM_LOGIN_HEADER),
+    ) {
+        (Some(init_data), _) => telegram.verify_init_data(init_data, now)?,
+        (None, Some(login)) => telegram.verify_login(login, now)?,
+        (None, None) => {
+            return Err(format!(
+                "missing {} or {}",
+                TELEGRAM_INIT_DATA_HEADER, TELEGRAM_LOGIN_HEADER
+            ))
+        }
+    };
+    id_format.validate(&context.context_type, &context.context_id)?;
+    Ok(context)
+}
+
+/// Applies `x-nova-actor-id`, which also works on top of a session's context.
+fn attach_actor(
+    context: RequestContext,
+    headers: &axum::http::HeaderMap,
+    id: Option<serde_json::Value>,
+    id_format: ContextIdFormat,
+) -> Result<RequestContext, Box<McpResponse>> {
+    let actor_id = headers
+        .get("x-nova-actor-id")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    context.with_actor(actor_id, id_format).map_err(|reason| {
+        Box::new(rpc_error_response(
+            id,
+            StatusCode::BAD_REQUEST,
+            format!("Invalid x-nova-actor-id: {}", reason),
+        ))
+    })
+}
+
+fn extract_context_from_headers(
+    headers: &axum::http::HeaderMap,
+    id: Option<serde_json::Value>,
+    id_format: ContextIdFormat,
+) -> Result<RequestContext, Box<McpResponse>> {
+    let context_type = headers
+        .get("x-nova-context-type")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_lowercase());
+
+    let context_type = match context_type.as_deref().and_then(PluginContextType::parse) {
+        Some(context_type) => context_type,
+        None => {
+            return Err(Box::new(rpc_error_response(
+                id,
+                StatusCode::BAD_REQUEST,
+                "Invalid or missing x-nova-context-type",
+            )))
+        }
+    };
+
+    let context_id_value = headers
+        .get("x-nova-context-id")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string());
+
+    let context_id = match context_id_value {
+        Some(ref value) if !value.is_empty() => value.clone(),
+        _ => {
+            return Err(Box::new(rpc_error_response(
+                id,
+                StatusCode::BAD_REQUEST,
+                "Invalid or missing x-nova-context-id",
+            )))
+        }
+    };
+
+    if let Err(reason) = id_format.validate(&context_type, &context_id) {
+        return Err(Box::new(rpc_error_response(
+            id,
+            StatusCode::BAD_REQUEST,
+            format!("Invalid x-nova-context-id: {}", reason),
+        )));
+    }
+
+    Ok(RequestContext {
+        context_type,
+        context_id,
+        actor_id: None,
+    })
+}
+
+fn rpc_error_response(
+    id: Option<serde_json::Value>,
+    status: StatusCode,
+    message: impl Into<String>,
+) -> McpResponse {
+    McpResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(McpError {
+            code: status.as_u16() as i32,
+            message: message.into(),
+            data: None,
+        }),
+    }
+}
+
+// Key prefixes in the shared rate-limit store
+const RATE_PREFIX: &str = "ctx:";
+const PRE_AUTH_PREFIX: &str = "ip:";
+
+pub(crate) async fn check_rate_limit(state: &AppState, key: &str) -> Option<StatusCode> {
+    let key = format!("{}{}", RATE_PREFIX, key);
+    match state
+        .server
+        .rate_limits()
+        .try_acquire(&key, state.limit_per_minute())
+    {
+        Ok(true) => None,
+        Ok(false) => {
+            state.server.events().publish(EventKind::RateLimited {
+                key: key[RATE_PREFIX.len()..].to_string(),
+            });
+            Some(StatusCode::TOO_MANY_REQUESTS)
+        }
+        Err(e) => {
+            // A storage fault should not take the API down with it
+            tracing::warn!("Rate limit check failed for {}: {}", key, e);
+            None
+        }
+    }
+}
+
+/// Whether `ip` has used up `limit` failed or malformed requests this minute.
+async fn pre_auth_exhausted(state: &AppState, ip: &str, limit: u32) -> bool {
+    let key = format!("{}{}", PRE_AUTH_PREFIX, ip);
+    match state.server.rate_limits().current(&key) {
+        Ok(count) => count >= limit,
+        Err(e) => {
+            tracing::warn!("Rate limit check failed for {}: {}", key, e);
+            false
+        }
+    }
+}
+
+async fn count_pre_auth(state: &AppState, ip: &str) {
+    let key = format!("{}{}", PRE_AUTH_PREFIX, ip);
+    if let Err(e) = state.server.rate_limits().record(&key) {
+        tracing::warn!("Failed to count request for {}: {}", key, e);
+    }
+}
+
+/// Routes that take no API key, so a missing key there is not a failure.
+fn is_keyless_path(path: &str) -> bool {
+    matches!(
+        path,
+        "/healthz" | "/readyz" | "/oauth/token" | "/marketplace"
+    ) || path == "/admin"
+        || path.starts_with("/admin/")
+        || (path.starts_with("/marketplace/") && path.ends_with("/ratings"))
+        || path.starts_with("/contexts/")
+        || (path.starts_with("/jobs/") && path.ends_with("/callback"))
+}
+
+/// Statuses for requests the server could not parse.
+fn is_malformed(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_REQUEST
+            | StatusCode::PAYLOAD_TOO_LARGE
+            | StatusCode::UNSUPPORTED_MEDIA_TYPE
+            | StatusCode::UNPROCESSABLE_ENTITY
+    )
+}
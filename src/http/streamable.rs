@@ -0,0 +1,564 @@
+//! MCP "Streamable HTTP" transport on `/mcp`: POST carries client messages,
+//! GET opens a server event stream, DELETE ends the session.
+
+use super::{
+    attach_actor, caller_key, check_rate_limit, extract_context_from_headers, has_context_headers,
+    session_owner, verified_identity, ApiJson, AppState, SESSION_HEADER,
+};
+use crate::auth::SCOPE_TOOLS;
+use crate::mcp::dto::{McpError, McpResponse};
+use crate::mcp::handler::{
+    alert_notification, handle_session_request, list_changed_notification, request_from_value,
+};
+use crate::mcp::logging;
+use crate::mcp::progress;
+use crate::mcp::protocol::ProtocolVersion;
+use crate::mcp::session::McpSession;
+use crate::plugins::RequestContext;
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    Json,
+};
+use dashmap::DashMap;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::{BroadcastStream, UnboundedReceiverStream};
+use tokio_stream::StreamExt;
+
+/// Events kept per session for `Last-Event-ID` replay.
+const REPLAY_BUFFER: usize = 256;
+
+/// Per-session outbound messages, numbered so clients can resume.
+pub(crate) struct EventLog {
+    buffer: Mutex<(u64, VecDeque<(u64, String)>)>,
+    live: broadcast::Sender<(u64, String)>,
+}
+
+impl EventLog {
+    fn new() -> Self {
+        let (live, _) = broadcast::channel(REPLAY_BUFFER);
+        Self {
+            buffer: Mutex::new((0, VecDeque::new())),
+            live,
+        }
+    }
+
+    /// Numbers and buffers `data` for replay without sending it to the GET stream.
+    fn record(&self, data: &str) -> u64 {
+        let mut guard = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+        let (next_id, events) = &mut *guard;
+        *next_id += 1;
+        if events.len() == REPLAY_BUFFER {
+            events.pop_front();
+        }
+        events.push_back((*next_id, data.to_string()));
+        *next_id
+    }
+
+    /// Records `data` and delivers it on the session's GET stream.
+    fn publish(&self, data: String) -> u64 {
+        let id = self.record(&data);
+        // No receivers just means nobody is on the GET stream right now.
+        let _ = self.live.send((id, data));
+        id
+    }
+
+    fn since(&self, last_seen: u64) -> Vec<(u64, String)> {
+        let guard = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+        guard
+            .1
+            .iter()
+            .filter(|(id, _)| *id > last_seen)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Event logs keyed by session id.
+#[derive(Default)]
+pub(crate) struct EventHub {
+    logs: DashMap<String, Arc<EventLog>>,
+}
+
+impl EventHub {
+    fn log(&self, session_id: &str) -> Arc<EventLog> {
+        Arc::clone(
+            self.logs
+                .entry(session_id.to_string())
+                .or_insert_with(|| Arc::new(EventLog::new()))
+                .value(),
+        )
+    }
+
+    pub(crate) fn remove(&self, session_id: &str) {
+        self.logs.remove(session_id);
+    }
+}
+
+fn event(id: u64, data: String) -> Result<Event, Infallible> {
+    Ok(Event::default()
+        .id(id.to_string())
+        .event("message")
+        .data(data))
+}
+
+fn accepts(headers: &HeaderMap, mime: &str) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains(mime) || accept.contains("*/*"))
+}
+
+/// Requests without `Origin` come from non-browser clients and pass. Browser
+/// requests must come from the server's own origin or `server.allowed_origins`,
+/// so a web page cannot drive a local server (DNS rebinding).
+fn origin_allowed(state: &AppState, headers: &HeaderMap) -> bool {
+    let Some(origin) = headers.get(header::ORIGIN) else {
+        return true;
+    };
+    let Some(origin) = origin
+        .to_str()
+        .ok()
+        .and_then(|origin| reqwest::Url::parse(origin).ok())
+        .filter(|origin| matches!(origin.scheme(), "http" | "https"))
+    else {
+        return false;
+    };
+    let serialized = origin.origin().ascii_serialization();
+    let listed = state.config().server.allowed_origins.iter().any(|allowed| {
+        allowed
+            .trim_end_matches('/')
+            .eq_ignore_ascii_case(&serialized)
+    });
+    // A rebound name passes the Host comparison too, so only addresses that
+    // cannot be rebound count as the server's own origin
+    let host = origin.host_str().unwrap_or_default();
+    if !listed && !fixed_host(&origin, &state.config().server.bind_address) {
+        return false;
+    }
+    let same_origin = headers
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|authority| {
+            let bare = authority.eq_ignore_ascii_case(host) && origin.port().is_none();
+            let with_port = origin
+                .port_or_known_default()
+                .is_some_and(|port| authority.eq_ignore_ascii_case(&format!("{}:{}", host, port)));
+            bare || with_port
+        });
+    listed || same_origin
+}
+
+/// Whether the origin names a loopback host or the address the server binds.
+fn fixed_host(origin: &reqwest::Url, bind_address: &str) -> bool {
+    let bound = bind_address
+        .parse::<IpAddr>()
+        .ok()
+        .filter(|ip| !ip.is_unspecified());
+    let host = origin.host_str().unwrap_or_default();
+    match host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+    {
+        Ok(ip) => ip.is_loopback() || bound == Some(ip),
+        Err(_) => host.eq_ignore_ascii_case("localhost"),
+    }
+}
+
+fn presented_key<'a>(state: &AppState, headers: &'a HeaderMap) -> Option<&'a str> {
+    headers
+        .get(state.auth().header_name())
+        .and_then(|v| v.to_str().ok())
+}
+
+fn error_body(id: Option<Value>, code: i32, message: &str) -> Json<McpResponse> {
+    Json(McpResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(McpError {
+            code,
+            message: message.to_string(),
+            data: None,
+        }),
+    })
+}
+
+pub(crate) async fn post_messages(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiJson(body): ApiJson<Value>,
+) -> Response {
+    if !origin_allowed(&state, &headers) {
+        return (
+            StatusCode::FORBIDDEN,
+            error_body(None, -32001, "Origin not allowed"),
+        )
+            .into_response();
+    }
+    let presented = presented_key(&state, &headers);
+    let Some(key) = caller_key(&state, &headers).await else {
+        return (
+            StatusCode::UNAUTHORIZED,
+            error_body(None, -32001, "Unauthorized"),
+        )
+            .into_response();
+    };
+    let owner = session_owner(&key, presented);
+
+    let batch = body.is_array();
+    let messages = match body {
+        Value::Array(items) if !items.is_empty() => items,
+        Value::Array(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                error_body(None, -32600, "Empty batch"),
+            )
+                .into_response()
+        }
+        single => vec![single],
+    };
+    let is_initialize = messages
+        .iter()
+        .any(|m| m.get("method").and_then(Value::as_str) == Some("initialize"));
+    if is_initialize && messages.len() > 1 {
+        return (
+            StatusCode::BAD_REQUEST,
+            error_body(None, -32600, "initialize must not be batched"),
+        )
+            .into_response();
+    }
+
+    let mut session = if is_initialize {
+        McpSession::new()
+    } else {
+        let Some(id) = headers.get(SESSION_HEADER).and_then(|v| v.to_str().ok()) else {
+            return (
+                StatusCode::BAD_REQUEST,
+                error_body(None, -32600, "Missing Mcp-Session-Id header"),
+            )
+                .into_response();
+        };
+        match state.sessions.get(id, owner.as_deref()) {
+            Some(session) => session,
+            None => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    error_body(None, -32001, "Session not found"),
+                )
+                    .into_response()
+            }
+        }
+    };
+
+    if let Some(requested) = headers
+        .get("mcp-protocol-version")
+        .and_then(|v| v.to_str().ok())
+        .filter(|_| !is_initialize)
+    {
+        match ProtocolVersion::negotiate(Some(requested)) {
+            Ok(version) if version == session.protocol_version() => {}
+            Ok(_) | Err(_) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    error_body(None, -32600, "MCP-Protocol-Version does not match session"),
+                )
+                    .into_response()
+            }
+        }
+    }
+
+    let id_format = state.config().context.id_format();
+    let context = if let Some(identity) = verified_identity(&state, &headers, id_format).await {
+        match identity {
+            Ok(identity) if identity.allows(SCOPE_TOOLS) => Some(identity.context),
+            Ok(_) => {
+                let message = format!("Token lacks the {} scope", SCOPE_TOOLS);
+                return (StatusCode::FORBIDDEN, error_body(None, -32001, &message)).into_response();
+            }
+            Err(reason) => {
+                return (StatusCode::UNAUTHORIZED, error_body(None, -32001, &reason))
+                    .into_response();
+            }
+        }
+    } else {
+        let context = if has_context_headers(&headers) {
+            match extract_context_from_headers(&headers, None, id_format) {
+                Ok(context) => Some(context),
+                Err(response) => return (StatusCode::BAD_REQUEST, Json(*response)).into_response(),
+            }
+        } else {
+            session.context().cloned()
+        };
+        match context {
+            Some(context) => match attach_actor(context, &headers, None, id_format) {
+                Ok(context) => Some(context),
+                Err(response) => return (StatusCode::BAD_REQUEST, Json(*response)).into_response(),
+            },
+            None => None,
+        }
+    };
+    let rate_key = match &context {
+        Some(context) => context.principal(),
+        None => format!("session:{}", session.id()),
+    };
+    if let Some(code) = check_rate_limit(&state, &key.rate_key(&rate_key)).await {
+        return (code, error_body(None, -32000, "Rate limit exceeded")).into_response();
+    }
+
+    let streaming =
+        accepts(&headers, "text/event-stream") && !accepts(&headers, "application/json");
+    if streaming && !is_initialize && messages.iter().any(expects_reply) {
+        let session_header = [(SESSION_HEADER, session.id().to_string())];
+        let events = stream_replies(state, session, messages, context);
+        return (session_header, Sse::new(events)).into_response();
+    }
+
+    let server = state.server();
+    let (notify_tx, mut notify_rx) = mpsc::unbounded_channel::<Value>();
+    let mut responses = Vec::new();
+    for message in messages {
+        if !expects_reply(&message) {
+            continue;
+        }
+        let response = match request_from_value(message) {
+            Ok(request) => {
+                let logger = session.client_logger(&notify_tx);
+                progress::scope(
+                    notify_tx.clone(),
+                    logging::scope(
+                        logger,
+                        handle_session_request(
+                            server.as_ref(),
+                            &mut session,
+                            request,
+                            context.clone(),
+                        ),
+                    ),
+                )
+                .await
+            }
+            Err(response) => *response,
+        };
+        responses.push(response);
+    }
+
+    let session_id = session.id().to_string();
+    if is_initialize {
+        if responses.iter().any(|r| r.error.is_some()) {
+            return Json(responses.remove(0)).into_response();
+        }
+        if !state.sessions.insert(session, owner.as_deref()) {
+            let id = responses.first().and_then(|response| response.id.clone());
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                error_body(id, -32000, "Too many sessions"),
+            )
+                .into_response();
+        }
+    } else {
+        state.sessions.update(session);
+    }
+
+    drop(notify_tx);
+    let mut notifications = Vec::new();
+    while let Ok(note) = notify_rx.try_recv() {
+        notifications.push(note.to_string());
+    }
+
+    let log = state.streams.log(&session_id);
+    if !streaming || responses.is_empty() {
+        // A JSON reply has no room for notifications; route them to the GET stream.
+        for note in notifications.drain(..) {
+            log.publish(note);
+        }
+    }
+    if responses.is_empty() {
+        return StatusCode::ACCEPTED.into_response();
+    }
+
+    let session_header = [(SESSION_HEADER, session_id.clone())];
+    if streaming {
+        // Recorded so a client that drops mid-stream can pick them up via GET + Last-Event-ID.
+        let mut events = Vec::with_capacity(notifications.len() + responses.len());
+        for data in notifications {
+            events.push(event(log.record(&data), data));
+        }
+        for response in &responses {
+            let data = serde_json::to_string(response).unwrap_or_default();
+            events.push(event(log.record(&data), data));
+        }
+        let stream = tokio_stream::iter(events);
+        return (session_header, Sse::new(stream)).into_response();
+    }
+
+    if batch {
+        (session_header, Json(responses)).into_response()
+    } else {
+        (session_header, Json(responses.remove(0))).into_response()
+    }
+}
+
+/// Notifications and client responses need no reply.
+fn expects_reply(message: &Value) -> bool {
+    message.get("id").is_some() && message.get("method").is_some()
+}
+
+/// Handles `messages` in the background for a client reading the reply as
+/// SSE, so each notification (log messages, progress of a streaming plugin)
+/// goes out the moment it is raised instead of with the response.
+fn stream_replies(
+    state: AppState,
+    mut session: McpSession,
+    messages: Vec<Value>,
+    context: Option<RequestContext>,
+) -> UnboundedReceiverStream<Result<Event, Infallible>> {
+    let (events_tx, events_rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let server = state.server();
+        let log = state.streams.log(session.id());
+        // Recorded so a client that drops mid-stream can pick them up via GET + Last-Event-ID.
+        let send = |data: String| {
+            let _ = events_tx.send(event(log.record(&data), data));
+        };
+        for message in messages {
+            if !expects_reply(&message) {
+                continue;
+            }
+            let response = match request_from_value(message) {
+                Ok(request) => {
+                    let (notify_tx, mut notify_rx) = mpsc::unbounded_channel::<Value>();
+                    let logger = session.client_logger(&notify_tx);
+                    let call = progress::scope(
+                        notify_tx,
+                        logging::scope(
+                            logger,
+                            handle_session_request(
+                                server.as_ref(),
+                                &mut session,
+                                request,
+                                context.clone(),
+                            ),
+                        ),
+                    );
+                    tokio::pin!(call);
+                    let response = loop {
+                        tokio::select! {
+                            response = &mut call => break response,
+                            Some(note) = notify_rx.recv() => send(note.to_string()),
+                        }
+                    };
+                    while let Ok(note) = notify_rx.try_recv() {
+                        send(note.to_string());
+                    }
+                    response
+                }
+                Err(response) => *response,
+            };
+            send(serde_json::to_string(&response).unwrap_or_default());
+        }
+        state.sessions.update(session);
+    });
+    UnboundedReceiverStream::new(events_rx)
+}
+
+/// Puts `notifications/tools/list_changed` on the GET stream of every
+/// session whose tool list a registry change may alter.
+pub(crate) async fn forward_list_changes(state: AppState) {
+    let server = state.server();
+    let mut changes = BroadcastStream::new(state.plugin_manager().subscribe());
+    while let Some(change) = changes.next().await {
+        let change = change.ok();
+        for session in state.sessions.live() {
+            if let Some(note) = list_changed_notification(&server, &session, change.as_ref()) {
+                state.streams.log(session.id()).publish(note.to_string());
+            }
+        }
+    }
+}
+
+/// Pushes fired alerts, and whale trades to their context, to sessions whose
+/// log level lets them through.
+pub(crate) async fn forward_alerts(state: AppState) {
+    let mut events = BroadcastStream::new(state.server().events().subscribe());
+    while let Some(event) = events.next().await {
+        let Ok(event) = event else {
+            continue;
+        };
+        let server = state.server();
+        for session in state.sessions.live() {
+            if let Some(note) = alert_notification(&server, &session, &event) {
+                state.streams.log(session.id()).publish(note.to_string());
+            }
+        }
+    }
+}
+
+pub(crate) async fn open_stream(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if !origin_allowed(&state, &headers) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let presented = presented_key(&state, &headers);
+    let Some(key) = caller_key(&state, &headers).await else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let owner = session_owner(&key, presented);
+    if !accepts(&headers, "text/event-stream") {
+        return StatusCode::NOT_ACCEPTABLE.into_response();
+    }
+    let Some(session_id) = headers.get(SESSION_HEADER).and_then(|v| v.to_str().ok()) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    if state.sessions.get(session_id, owner.as_deref()).is_none() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let last_seen = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(0);
+    let log = state.streams.log(session_id);
+    // Subscribe before reading the backlog so nothing falls in between.
+    let live = BroadcastStream::new(log.live.subscribe());
+    let backlog = log.since(last_seen);
+    let replayed_up_to = backlog.last().map(|(id, _)| *id).unwrap_or(last_seen);
+
+    let replay = tokio_stream::iter(backlog.into_iter().map(|(id, data)| event(id, data)));
+    let live = live.filter_map(move |item| match item {
+        Ok((id, data)) if id > replayed_up_to => Some(event(id, data)),
+        _ => None,
+    });
+    Sse::new(replay.chain(live))
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+pub(crate) async fn close_session(State(state): State<AppState>, headers: HeaderMap) -> StatusCode {
+    if !origin_allowed(&state, &headers) {
+        return StatusCode::FORBIDDEN;
+    }
+    let presented = presented_key(&state, &headers);
+    let Some(key) = caller_key(&state, &headers).await else {
+        return StatusCode::UNAUTHORIZED;
+    };
+    let owner = session_owner(&key, presented);
+    match headers.get(SESSION_HEADER).and_then(|v| v.to_str().ok()) {
+        Some(id) if state.sessions.remove(id, owner.as_deref()) => {
+            state.streams.remove(id);
+            StatusCode::NO_CONTENT
+        }
+        Some(_) => StatusCode::NOT_FOUND,
+        None => StatusCode::BAD_REQUEST,
+    }
+}
@@ -0,0 +1,277 @@
+//! Periodic background work (cache warming, probes, polling) with per-job
+//! run history for `GET /admin/jobs`.
+//!
+//! Each job runs on its own task, never overlapping with itself. A run that
+//! fails or panics is recorded and the job is scheduled again as usual.
+
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+use crate::clock::SharedClock;
+use crate::error::{NovaError, Result};
+
+/// Share of the interval added at random before each run, so jobs with the
+/// same interval do not fire together.
+const DEFAULT_JITTER_DIVISOR: u32 = 10;
+
+type JobFn = Arc<dyn Fn() -> BoxFuture<'static, Result<()>> + Send + Sync>;
+
+#[derive(Default)]
+pub struct JobScheduler {
+    jobs: DashMap<String, Arc<Job>>,
+    started: AtomicBool,
+    handles: Mutex<Vec<JoinHandle<()>>>,
+    // Stamps run history; intervals run on Tokio's timer
+    clock: SharedClock,
+}
+
+struct Job {
+    name: String,
+    interval: Duration,
+    jitter: Duration,
+    run: JobFn,
+    spawned: AtomicBool,
+    record: Mutex<JobRecord>,
+    clock: SharedClock,
+}
+
+#[derive(Default)]
+struct JobRecord {
+    running: bool,
+    runs: u64,
+    failures: u64,
+    last_started_at: Option<i64>,
+    last_finished_at: Option<i64>,
+    last_duration_ms: Option<u64>,
+    last_outcome: Option<JobOutcome>,
+    last_error: Option<String>,
+    next_run_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobOutcome {
+    Succeeded,
+    Failed,
+    Panicked,
+}
+
+/// One job's schedule and its most recent run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobStatus {
+    pub name: String,
+    pub interval_secs: u64,
+    pub jitter_secs: u64,
+    pub running: bool,
+    pub runs: u64,
+    pub failures: u64,
+    pub last_started_at: Option<i64>,
+    pub last_finished_at: Option<i64>,
+    pub last_duration_ms: Option<u64>,
+    pub last_outcome: Option<JobOutcome>,
+    pub last_error: Option<String>,
+    /// Unset until the scheduler is started.
+    pub next_run_at: Option<i64>,
+}
+
+impl JobScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The clock behind `last_started_at`, `last_finished_at` and
+    /// `next_run_at`, for jobs registered afterwards.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Adds a job run every `interval` plus up to a tenth of it in jitter; the
+    /// first run comes one interval after the scheduler starts.
+    pub fn register<F, Fut>(&self, name: &str, interval: Duration, run: F) -> Result<()>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<()>> + Send + 'static,
+    {
+        let jitter = interval / DEFAULT_JITTER_DIVISOR;
+        self.register_with_jitter(name, interval, jitter, run)
+    }
+
+    pub fn register_with_jitter<F, Fut>(
+        &self,
+        name: &str,
+        interval: Duration,
+        jitter: Duration,
+        run: F,
+    ) -> Result<()>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<()>> + Send + 'static,
+    {
+        if interval.is_zero() {
+            return Err(NovaError::config_error(format!(
+                "Job {} needs a non-zero interval",
+                name
+            )));
+        }
+        let job = Arc::new(Job {
+            name: name.to_string(),
+            interval,
+            jitter,
+            run: Arc::new(move || -> BoxFuture<'static, Result<()>> { Box::pin(run()) }),
+            spawned: AtomicBool::new(false),
+            record: Mutex::new(JobRecord::default()),
+            clock: self.clock.clone(),
+        });
+        match self.jobs.entry(name.to_string()) {
+            Entry::Occupied(_) => {
+                return Err(NovaError::config_error(format!(
+                    "Job {} is already registered",
+                    name
+                )))
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(Arc::clone(&job));
+            }
+        }
+        if self.started.load(Ordering::SeqCst) {
+            self.spawn(job);
+        }
+        Ok(())
+    }
+
+    /// Starts every registered job; later registrations start right away.
+    /// Must be called within a Tokio runtime. Calling it again is a no-op.
+    pub fn start(&self) {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let jobs: Vec<Arc<Job>> = self.jobs.iter().map(|job| Arc::clone(&job)).collect();
+        for job in jobs {
+            self.spawn(job);
+        }
+    }
+
+    /// Every registered job, by name.
+    pub fn snapshot(&self) -> Vec<JobStatus> {
+        let mut statuses: Vec<JobStatus> = self.jobs.iter().map(|job| job.status()).collect();
+        statuses.sort_by(|a, b| a.name.cmp(&b.name));
+        statuses
+    }
+
+    fn spawn(&self, job: Arc<Job>) {
+        // `start` and a concurrent `register` may both get here
+        if job.spawned.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let handle = tokio::spawn(async move {
+            loop {
+                let delay = job.interval + random_below(job.jitter);
+                job.lock().next_run_at =
+                    Some(job.clock.timestamp() + delay.as_secs_f64().ceil() as i64);
+                tokio::time::sleep(delay).await;
+                job.run_once().await;
+            }
+        });
+        self.handles
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(handle);
+    }
+}
+
+impl Drop for JobScheduler {
+    fn drop(&mut self) {
+        let handles = self.handles.get_mut().unwrap_or_else(|e| e.into_inner());
+        for handle in handles.drain(..) {
+            handle.abort();
+        }
+    }
+}
+
+impl Job {
+    fn lock(&self) -> std::sync::MutexGuard<'_, JobRecord> {
+        self.record.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    async fn run_once(&self) {
+        {
+            let mut record = self.lock();
+            record.running = true;
+            record.next_run_at = None;
+            record.last_started_at = Some(self.clock.timestamp());
+        }
+        let started = Instant::now();
+        // A separate task keeps a panicking job from taking its loop down
+        let run = Arc::clone(&self.run);
+        let result = tokio::spawn(async move { run().await }).await;
+        let (outcome, error) = match result {
+            Ok(Ok(())) => (JobOutcome::Succeeded, None),
+            Ok(Err(e)) => (JobOutcome::Failed, Some(e.to_string())),
+            Err(e) if e.is_panic() => (JobOutcome::Panicked, Some(panic_message(e.into_panic()))),
+            Err(e) => (JobOutcome::Failed, Some(e.to_string())),
+        };
+        if let Some(error) = &error {
+            tracing::warn!("Background job {} {:?}: {}", self.name, outcome, error);
+        }
+
+        let mut record = self.lock();
+        record.running = false;
+        record.runs += 1;
+        if outcome != JobOutcome::Succeeded {
+            record.failures += 1;
+            record.last_error = error;
+        }
+        record.last_outcome = Some(outcome);
+        record.last_finished_at = Some(self.clock.timestamp());
+        record.last_duration_ms = Some(started.elapsed().as_millis() as u64);
+    }
+
+    fn status(&self) -> JobStatus {
+        let record = self.lock();
+        JobStatus {
+            name: self.name.clone(),
+            interval_secs: self.interval.as_secs(),
+            jitter_secs: self.jitter.as_secs(),
+            running: record.running,
+            runs: record.runs,
+            failures: record.failures,
+            last_started_at: record.last_started_at,
+            last_finished_at: record.last_finished_at,
+            last_duration_ms: record.last_duration_ms,
+            last_outcome: record.last_outcome,
+            last_error: record.last_error.clone(),
+            next_run_at: record.next_run_at,
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => format!("panicked: {}", message),
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(message) => format!("panicked: {}", message),
+            Err(_) => "panicked".to_string(),
+        },
+    }
+}
+
+/// Uniform in `[0, max)`; `RandomState` is seeded per instance, which is
+/// random enough to spread jobs out.
+fn random_below(max: Duration) -> Duration {
+    let nanos = max.as_nanos() as u64;
+    if nanos == 0 {
+        return Duration::ZERO;
+    }
+    let random = RandomState::new().build_hasher().finish();
+    Duration::from_nanos(random % nanos)
+}
@@ -1,14 +1,38 @@
+pub mod admin;
+pub mod audit;
 pub mod auth;
+pub mod client;
+pub mod clock;
 pub mod config;
+pub mod dead_letters;
+pub mod doctor;
+pub mod embed;
 pub mod error;
+pub mod events;
 pub mod http;
+pub mod jobs;
 pub mod mcp;
+pub mod metering;
+pub mod oauth;
+pub mod outbound;
+pub mod pipeline;
 pub mod plugins;
+pub mod preferences;
+pub mod quotas;
+pub mod rate_limits;
+pub mod readiness;
+pub mod reload;
+pub mod schema;
 pub mod server;
+pub mod stdio;
+pub mod storage;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 pub mod tools;
 
-pub use auth::ApiKeyAuth;
+pub use auth::{AdminAuth, ApiKeyAuth};
 pub use config::NovaConfig;
+pub use embed::{serve, RegistryHandles};
 pub use error::{NovaError, Result};
 pub use plugins::PluginManager;
 pub use server::NovaServer;
@@ -1,25 +1,38 @@
 use anyhow::{Context, Result};
-use nova_mcp::http;
-use nova_mcp::mcp::{
-    dto::{McpError, McpRequest, McpResponse},
-    handler,
-};
-use nova_mcp::plugins::{PluginContextType, PluginManager, RequestContext};
-use nova_mcp::{NovaConfig, NovaServer};
+use nova_mcp::config::CliArgs;
+use nova_mcp::plugins::{PluginContextType, RequestContext};
+use nova_mcp::reload::LogLevelHook;
+use nova_mcp::stdio::LogOutput;
+use nova_mcp::{doctor, embed};
+use nova_mcp::{NovaConfig, NovaError, RegistryHandles};
 use std::sync::Arc;
-use tokio::io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging
+    // Initialize logging; the filter sits behind a reload handle so config reloads can change it
+    // Logs go to stderr until the transport is known to leave stdout free
+    let (filter, filter_handle) = reload::Layer::new(
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| "nova_mcp=info".into()),
+    );
+    let log_output = LogOutput::default();
     tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "nova_mcp=info".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(log_output.clone()))
         .init();
+    let log_level_hook: LogLevelHook = Arc::new(move |level: &str| {
+        // Bare levels apply to this crate; anything else is a full filter directive
+        let directive = if level.contains('=') || level.contains(',') {
+            level.to_string()
+        } else {
+            format!("nova_mcp={}", level)
+        };
+        let filter = EnvFilter::try_new(directive)
+            .map_err(|e| NovaError::config_error(format!("Invalid log level: {}", e)))?;
+        filter_handle
+            .reload(filter)
+            .map_err(|e| NovaError::config_error(e.to_string()))
+    });
 
     // Load .env for local dev (if present)
     if dotenvy::dotenv().is_ok() {
@@ -29,31 +42,33 @@ async fn main() -> Result<()> {
     tracing::info!("Starting Nova MCP Server");
 
     // Load configuration
-    let config = NovaConfig::from_env()?;
+    let cli = CliArgs::parse(std::env::args().skip(1))?;
+    let run_doctor = cli.doctor;
+    let config = NovaConfig::load_with(&cli)?;
+    if config.server.transport.eq_ignore_ascii_case("http") {
+        log_output.use_stdout();
+    }
     tracing::info!(
         "Configuration loaded: transport={}, port={}",
         config.server.transport,
         config.server.port
     );
 
-    let sled_db = sled::open("nova_mcp_db").context("failed to open sled database")?;
-    let metadata_tree = sled_db
-        .open_tree("plugin_metadata")
-        .context("failed to open plugin_metadata tree")?;
-    let user_tree = sled_db
-        .open_tree("user_plugins")
-        .context("failed to open user_plugins tree")?;
-    let group_tree = sled_db
-        .open_tree("group_plugins")
-        .context("failed to open group_plugins tree")?;
-    let plugin_manager = Arc::new(PluginManager::new(metadata_tree, user_tree, group_tree)?);
+    let handles = RegistryHandles::open(&config.storage).context("failed to open sled database")?;
+    let server = embed::build_server(&config, &handles)?
+        .with_cli_args(cli)
+        .with_log_level_hook(log_level_hook);
 
-    // Create server instance
-    let server = NovaServer::new(config.clone(), Arc::clone(&plugin_manager));
+    if run_doctor {
+        let report = doctor::run(server, &config).await?;
+        println!("{}", report);
+        std::process::exit(if report.passed() { 0 } else { 1 });
+    }
 
     let bootstrap_context = RequestContext {
         context_type: PluginContextType::User,
         context_id: "0".to_string(),
+        actor_id: None,
     };
     let tools = server.get_tools(&bootstrap_context)?;
     tracing::info!("Available tools: {}", tools.len());
@@ -61,77 +76,9 @@ async fn main() -> Result<()> {
         tracing::info!("  - {}: {}", tool.name, tool.description);
     }
 
-    match config.server.transport.to_lowercase().as_str() {
-        "http" => {
-            tracing::info!(
-                "Nova MCP Server running with HTTP transport on port {}",
-                config.server.port
-            );
-            http::run_http_server(server, config.clone()).await?;
-            Ok(())
-        }
-        _ => {
-            tracing::info!("Nova MCP Server running with stdio transport");
-
-            // Handle stdio MCP protocol
-            let stdin = io::stdin();
-            let mut stdout = io::stdout();
-            let mut reader = BufReader::new(stdin);
-            let mut line = String::new();
-
-            loop {
-                line.clear();
-                match reader.read_line(&mut line).await {
-                    Ok(0) => break, // EOF
-                    Ok(_) => {
-                        let line = line.trim();
-                        if line.is_empty() {
-                            continue;
-                        }
-
-                        tracing::debug!("Received: {}", line);
-
-                        match serde_json::from_str::<McpRequest>(line) {
-                            Ok(request) => {
-                                let response =
-                                    handler::handle_request(&server, request, None).await;
-                                let response_json = serde_json::to_string(&response)?;
-
-                                tracing::debug!("Sending: {}", response_json);
-
-                                stdout.write_all(response_json.as_bytes()).await?;
-                                stdout.write_all(b"\n").await?;
-                                stdout.flush().await?;
-                            }
-                            Err(e) => {
-                                tracing::error!("Failed to parse request: {}", e);
-                                let error_response = McpResponse {
-                                    jsonrpc: "2.0".to_string(),
-                                    id: None,
-                                    result: None,
-                                    error: Some(McpError {
-                                        code: -32700,
-                                        message: "Parse error".to_string(),
-                                        data: Some(serde_json::json!({"details": e.to_string()})),
-                                    }),
-                                };
-
-                                let error_json = serde_json::to_string(&error_response)?;
-                                stdout.write_all(error_json.as_bytes()).await?;
-                                stdout.write_all(b"\n").await?;
-                                stdout.flush().await?;
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        tracing::error!("Error reading from stdin: {}", e);
-                        break;
-                    }
-                }
-            }
-
-            tracing::info!("Nova MCP Server shutting down");
-            Ok(())
-        }
+    if config.server.read_only {
+        tracing::info!("Read-only mode: plugin registry writes are rejected");
     }
+
+    embed::run(server, config).await
 }
@@ -0,0 +1,81 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use super::dto::Tool;
+use crate::server::NovaServer;
+
+/// The spec caps a single `completion/complete` reply at 100 values.
+pub const MAX_COMPLETION_VALUES: usize = 100;
+
+/// Candidate values for one argument of `tool`, before prefix filtering.
+pub type CompletionProvider = fn(&NovaServer, &Tool, &str) -> Vec<String>;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Completion {
+    pub values: Vec<String>,
+    pub total: usize,
+    pub has_more: bool,
+}
+
+/// Per-tool completion hook; tools without a dedicated one fall back to schema enums.
+pub fn provider_for(tool_name: &str) -> CompletionProvider {
+    match tool_name {
+        "get_gecko_token"
+        | "get_gecko_pool"
+        | "get_trending_pools"
+        | "search_pools"
+        | "get_new_pools"
+        | "find_token_across_networks"
+        | "find_arbitrage_spreads"
+        | "watch_whales"
+        | "unwatch_whales" => gecko_arguments,
+        _ => schema_enum,
+    }
+}
+
+/// Completes `argument` of `tool` with the values starting with `prefix` (case-insensitive).
+pub fn complete(server: &NovaServer, tool: &Tool, argument: &str, prefix: &str) -> Completion {
+    let prefix = prefix.to_lowercase();
+    let mut values: Vec<String> = provider_for(&tool.name)(server, tool, argument)
+        .into_iter()
+        .filter(|value| value.to_lowercase().starts_with(&prefix))
+        .collect();
+    let total = values.len();
+    values.truncate(MAX_COMPLETION_VALUES);
+    Completion {
+        values,
+        total,
+        has_more: total > MAX_COMPLETION_VALUES,
+    }
+}
+
+/// `network` comes from the cached `get_gecko_networks` result; other arguments use the schema.
+fn gecko_arguments(server: &NovaServer, tool: &Tool, argument: &str) -> Vec<String> {
+    if argument == "network" {
+        server.gecko_terminal_tools().cached_network_slugs()
+    } else {
+        schema_enum(server, tool, argument)
+    }
+}
+
+fn schema_enum(_server: &NovaServer, tool: &Tool, argument: &str) -> Vec<String> {
+    let property = &tool.input_schema["properties"][argument];
+    // Array arguments complete their item values.
+    let choices = property
+        .get("enum")
+        .or_else(|| property.get("items").and_then(|items| items.get("enum")));
+    choices
+        .and_then(Value::as_array)
+        .map(|choices| {
+            choices
+                .iter()
+                .filter_map(|choice| match choice {
+                    Value::String(text) => Some(text.clone()),
+                    Value::Number(_) | Value::Bool(_) => Some(choice.to_string()),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -1,23 +1,98 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::preferences::ResultFormat;
+use crate::tools::CallPriority;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tool {
     pub name: String,
     pub description: String,
+    #[serde(rename = "inputSchema", alias = "input_schema")]
     pub input_schema: Value,
+    /// Behaviour hints; only sent to clients on protocol 2025-03-26 or later.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<ToolAnnotations>,
+    /// JSON schema of `structuredContent`; only sent on protocol 2025-06-18 or later.
+    #[serde(
+        default,
+        rename = "outputSchema",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub output_schema: Option<Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolAnnotations {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_only_hint: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_world_hint: Option<bool>,
+}
+
+impl ToolAnnotations {
+    /// Lookup against an external service that never mutates state.
+    pub fn read_only_lookup() -> Self {
+        Self {
+            read_only_hint: Some(true),
+            open_world_hint: Some(true),
+        }
+    }
+
+    /// Reads state kept by this server only.
+    pub fn local_read() -> Self {
+        Self {
+            read_only_hint: Some(true),
+            open_world_hint: Some(false),
+        }
+    }
+
+    /// Writes state kept by this server only.
+    pub fn local_update() -> Self {
+        Self {
+            read_only_hint: Some(false),
+            open_world_hint: Some(false),
+        }
+    }
+
+    /// Writes state kept by this server after a lookup against an external
+    /// service.
+    pub fn remote_update() -> Self {
+        Self {
+            read_only_hint: Some(false),
+            open_world_hint: Some(true),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ToolCall {
     pub name: String,
     pub arguments: Value,
+    /// Path applied to the result before serialization, e.g. `data.attributes.name`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub select: Option<String>,
+    /// `summary` asks for a short text rendering, `telegram_markdown` or
+    /// `telegram_html` for one marked up for Telegram; defaults to the
+    /// context's preference.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<ResultFormat>,
+    /// `background` lets interactive calls take freed tool slots first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<CallPriority>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ToolResult {
     pub content: String,
     pub is_error: bool,
+    /// Full serialized size when `content` was cut to the response limit.
+    #[serde(default)]
+    pub truncated_from: Option<usize>,
+    /// The result as JSON, kept when it fit within the response limit.
+    #[serde(default)]
+    pub structured_content: Option<Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -30,13 +105,19 @@ pub struct McpRequest {
     pub context_type: Option<String>,
     #[serde(default)]
     pub context_id: Option<String>,
+    // Acting user inside a shared context, like the `x-nova-actor-id` header
+    #[serde(default)]
+    pub actor_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct McpResponse {
     pub jsonrpc: String,
     pub id: Option<Value>,
+    // JSON-RPC 2.0: exactly one of `result` and `error` is present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub error: Option<McpError>,
 }
 
@@ -44,5 +125,6 @@ pub struct McpResponse {
 pub struct McpError {
     pub code: i32,
     pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub data: Option<Value>,
 }
@@ -1,33 +1,73 @@
-use crate::plugins::{PluginContextType, RequestContext};
+use crate::events::{EventKind, ServerEvent};
+use crate::pipeline;
+use crate::plugins::{
+    unescape_context_id, ContextIdFormat, PluginContextType, RegistryChange, RequestContext,
+};
+use crate::preferences::{Localizer, PreferencesUpdate};
+use crate::schema;
 use crate::server::NovaServer;
 use crate::{
-    error::NovaError,
+    error::{ErrorCategory, NovaError},
     tools::gecko_terminal::{
-        get_networks, get_pool, get_token, GetGeckoNetworksInput, GetGeckoPoolInput,
-        GetGeckoTokenInput,
+        self, find_arbitrage_spreads, find_token_across_networks, get_networks, get_pool,
+        get_token, unwatch_whales, watch_whales, FindArbitrageSpreadsInput,
+        FindTokenAcrossNetworksInput, GetGeckoNetworksInput, GetGeckoPoolInput, GetGeckoTokenInput,
+        UnwatchWhalesInput, WatchWhalesInput,
     },
     tools::new_pools::{get_new_pools, GetNewPoolsInput},
     tools::search_pools::{search_pools, SearchPoolsInput},
+    tools::solana::{get_solana_token_holders, GetSolanaTokenHoldersInput},
+    tools::token_unlocks::{get_token_unlocks, GetTokenUnlocksInput},
     tools::trending_pools::{get_trending_pools, GetTrendingPoolsInput},
+    tools::CallPriority,
 };
 use axum::http::StatusCode;
+use futures::future::BoxFuture;
 use serde_json::json;
+use std::time::Instant;
 
-use super::dto::{McpError, McpRequest, McpResponse, ToolCall, ToolResult};
+use super::completion;
+use super::dto::{McpError, McpRequest, McpResponse, Tool, ToolCall, ToolResult};
+use super::logging::{self, LogLevel};
+use super::progress;
+use super::protocol::ProtocolVersion;
+use super::select::Selector;
+use super::session::McpSession;
 
+/// Handles a request outside any session (each call negotiates from scratch).
 pub async fn handle_request(
     server: &NovaServer,
     request: McpRequest,
     transport_context: Option<RequestContext>,
 ) -> McpResponse {
+    handle_session_request(server, &mut McpSession::new(), request, transport_context).await
+}
+
+pub async fn handle_session_request(
+    server: &NovaServer,
+    session: &mut McpSession,
+    request: McpRequest,
+    transport_context: Option<RequestContext>,
+) -> McpResponse {
+    let version = session.protocol_version();
+    let config = server.runtime().current();
+    let id_format = config.context.id_format();
+    // Requests inside a session may omit context once `initialize` resolved
+    // it; without one, `context.default` stands in for generic MCP clients.
+    let transport_context = transport_context.or_else(|| {
+        (request.context_type.is_none() && request.context_id.is_none())
+            .then(|| session.context().cloned())
+            .flatten()
+            .or_else(|| config.context.default_context())
+    });
     match request.method.as_str() {
-        "tools/list" => match resolve_context(&request, transport_context) {
+        "tools/list" => match resolve_context(&request, transport_context, id_format) {
             Ok(context) => match server.get_tools(&context) {
                 Ok(tools) => McpResponse {
                     jsonrpc: "2.0".to_string(),
                     id: request.id,
                     result: Some(json!({
-                        "tools": tools
+                        "tools": tools_for_version(tools, version)
                     })),
                     error: None,
                 },
@@ -41,29 +81,41 @@ pub async fn handle_request(
         },
         "tools/call" => {
             if let Some(params) = request.params.clone() {
+                let progress_token = params
+                    .get("_meta")
+                    .and_then(|meta| meta.get("progressToken"))
+                    .cloned();
                 if let Ok(tool_call) = serde_json::from_value::<ToolCall>(params) {
-                    match resolve_context(&request, transport_context.clone()) {
-                        Ok(context) => match handle_tool_call(server, tool_call, &context).await {
+                    if let Err(e) = server.limits().check_arguments(&tool_call.arguments) {
+                        return McpResponse {
+                            jsonrpc: "2.0".to_string(),
+                            id: request.id,
+                            result: None,
+                            error: Some(McpError {
+                                code: -32602,
+                                message: e.to_string(),
+                                data: Some(e.to_data()),
+                            }),
+                        };
+                    }
+                    match resolve_context(&request, transport_context.clone(), id_format) {
+                        Ok(context) => match progress::track(
+                            progress_token,
+                            call_with_timeout(server, tool_call, &context),
+                        )
+                        .await
+                        {
                             Ok(result) => McpResponse {
                                 jsonrpc: "2.0".to_string(),
                                 id: request.id,
-                                result: Some(json!({
-                                    "content": [
-                                        { "type": "text", "text": result.content }
-                                    ],
-                                    "isError": result.is_error
-                                })),
+                                result: Some(tool_result_json(result, version)),
                                 error: None,
                             },
-                            Err(e) => McpResponse {
+                            Err(error) => McpResponse {
                                 jsonrpc: "2.0".to_string(),
                                 id: request.id,
                                 result: None,
-                                error: Some(McpError {
-                                    code: -32603,
-                                    message: format!("Tool execution failed: {}", e),
-                                    data: None,
-                                }),
+                                error: Some(error),
                             },
                         },
                         Err(response) => *response,
@@ -93,20 +145,77 @@ pub async fn handle_request(
                 }
             }
         }
-        "initialize" => McpResponse {
-            jsonrpc: "2.0".to_string(),
-            id: request.id,
-            result: Some(json!({
-                "protocolVersion": "2024-11-05",
-                "capabilities": { "tools": {} },
-                "serverInfo": { "name": "nova-mcp", "version": "0.1.0" }
-            })),
-            error: None,
+        "initialize" => {
+            let params = request.params.as_ref();
+            let requested = params
+                .and_then(|params| params.get("protocolVersion"))
+                .and_then(|v| v.as_str());
+            match ProtocolVersion::negotiate(requested) {
+                Ok(version) => {
+                    session.set_protocol_version(version);
+                    session.set_client(
+                        params.and_then(|p| p.get("capabilities")).cloned(),
+                        params.and_then(|p| p.get("clientInfo")).cloned(),
+                    );
+                    if let Ok(context) = resolve_context(&request, transport_context, id_format) {
+                        session.set_context(context);
+                    }
+                    McpResponse {
+                        jsonrpc: "2.0".to_string(),
+                        id: request.id,
+                        result: Some(json!({
+                            "protocolVersion": version.as_str(),
+                            "capabilities": { "tools": { "listChanged": true }, "logging": {}, "completions": {} },
+                            "serverInfo": { "name": "nova-mcp", "version": "0.1.0" }
+                        })),
+                        error: None,
+                    }
+                }
+                Err(error) => McpResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id: request.id,
+                    result: None,
+                    error: Some(error),
+                },
+            }
+        }
+        "completion/complete" => match resolve_context(&request, transport_context, id_format) {
+            Ok(context) => handle_completion(server, &request, &context),
+            Err(response) => *response,
         },
+        "logging/setLevel" => {
+            let level = request
+                .params
+                .as_ref()
+                .and_then(|params| params.get("level"))
+                .cloned()
+                .and_then(|level| serde_json::from_value::<LogLevel>(level).ok());
+            match level {
+                Some(level) => {
+                    session.set_log_level(level);
+                    McpResponse {
+                        jsonrpc: "2.0".to_string(),
+                        id: request.id,
+                        result: Some(json!({})),
+                        error: None,
+                    }
+                }
+                None => McpResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id: request.id,
+                    result: None,
+                    error: Some(McpError {
+                        code: -32602,
+                        message: "Invalid or missing log level".to_string(),
+                        data: None,
+                    }),
+                },
+            }
+        }
         "ping" => McpResponse {
             jsonrpc: "2.0".to_string(),
             id: request.id,
-            result: Some(json!({ "ok": true })),
+            result: Some(json!({})),
             error: None,
         },
         _ => McpResponse {
@@ -122,15 +231,185 @@ pub async fn handle_request(
     }
 }
 
+/// JSON-RPC server error code used when a tool exceeds its time budget.
+const TOOL_TIMEOUT_CODE: i32 = -32000;
+
+async fn call_with_timeout(
+    server: &NovaServer,
+    tool_call: ToolCall,
+    context: &RequestContext,
+) -> Result<ToolResult, McpError> {
+    let budget = server.tool_timeout(&tool_call.name);
+    let name = tool_call.name.clone();
+    let started = Instant::now();
+    let outcome = tokio::time::timeout(budget, handle_tool_call(server, tool_call, context)).await;
+    server.events().publish(EventKind::ToolCalled {
+        tool: name.clone(),
+        context: context.key(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        success: matches!(&outcome, Ok(Ok(result)) if !result.is_error),
+    });
+    let error = match outcome {
+        Ok(Ok(result)) => return Ok(result),
+        Ok(Err(e)) => e,
+        Err(_) => {
+            tracing::warn!("Tool {} timed out after {:?}", name, budget);
+            let error = NovaError::tool_timeout(&name, budget.as_secs());
+            // Keeps the `tool`/`timeoutSeconds` fields clients already read
+            let mut data = error.to_data();
+            data["tool"] = json!(name);
+            data["timeoutSeconds"] = json!(budget.as_secs());
+            return Err(McpError {
+                code: TOOL_TIMEOUT_CODE,
+                message: format!("Tool call timed out after {}s", budget.as_secs()),
+                data: Some(data),
+            });
+        }
+    };
+    let (code, message) = match error.category() {
+        ErrorCategory::Validation => (-32602, error.to_string()),
+        ErrorCategory::Timeout => (TOOL_TIMEOUT_CODE, error.to_string()),
+        _ => (-32603, format!("Tool execution failed: {}", error)),
+    };
+    Err(McpError {
+        code,
+        message,
+        data: Some(error.to_data()),
+    })
+}
+
 pub(crate) async fn handle_tool_call(
     server: &NovaServer,
     tool_call: ToolCall,
     context: &RequestContext,
 ) -> Result<ToolResult, NovaError> {
-    tracing::info!("Handling tool call: {}", tool_call.name);
-    let result = match tool_call.name.as_str() {
+    tracing::info!(
+        "Handling tool call: {} for {}{}",
+        tool_call.name,
+        context.key(),
+        context
+            .actor_id
+            .as_ref()
+            .map(|actor| format!(" (actor {})", actor))
+            .unwrap_or_default()
+    );
+    logging::log(
+        LogLevel::Info,
+        "tools",
+        json!({ "message": "Tool started", "tool": tool_call.name }),
+    );
+    let selector = tool_call
+        .select
+        .as_deref()
+        .map(Selector::parse)
+        .transpose()
+        .map_err(|e| NovaError::validation_error(format!("Invalid select {}", e)))?;
+    // Checking the quota or a job is free; pipeline steps count as part of their pipeline
+    if !matches!(tool_call.name.as_str(), "get_my_usage" | "get_job_status") {
+        let quotas = &server.runtime().current().quotas;
+        server.quotas().consume(context, None, quotas)?;
+    }
+    let priority = tool_call.priority.unwrap_or_default();
+    let mut result = call_tool(
+        server,
+        &tool_call.name,
+        tool_call.arguments,
+        context,
+        priority,
+    )
+    .await?;
+    if let Some(selector) = &selector {
+        result = selector.apply(&result);
+    }
+    let tool_call_name = tool_call.name;
+
+    // Text follows the caller's display preferences; structuredContent stays raw.
+    let preferences = server.preferences().get(context)?;
+    let format = tool_call
+        .format
+        .or(preferences.as_ref().map(|p| p.result_format))
+        .unwrap_or_default();
+    let rates = &server.runtime().current().preferences.usd_rates;
+    let markup = format.markup();
+    let summary = (format.summarizes() && selector.is_none())
+        .then(|| {
+            let localizer = Localizer::new(&preferences.clone().unwrap_or_default(), rates);
+            gecko_terminal::summary::summarize_as(&tool_call_name, &result, &localizer, markup)
+        })
+        .flatten();
+    // JSON is cut before it goes in a code block, so the block stays well formed
+    let (content, truncated_from) = match (summary, preferences) {
+        (Some(summary), _) => server.limits().render_text(summary),
+        (None, Some(preferences)) => {
+            let localized = Localizer::new(&preferences, rates).localize(&result);
+            let (json, truncated_from) = server.limits().render(&localized)?;
+            (markup.json_block(json), truncated_from)
+        }
+        (None, None) => {
+            let (json, truncated_from) = server.limits().render(&result)?;
+            (markup.json_block(json), truncated_from)
+        }
+    };
+    if let Some(total) = truncated_from {
+        tracing::warn!(
+            "Truncated {} result from {} to {} bytes",
+            tool_call_name,
+            total,
+            content.len()
+        );
+    }
+    // structuredContent must be a JSON object matching outputSchema; skip it for
+    // cut, scalar or selected results
+    let structured_content =
+        (truncated_from.is_none() && selector.is_none() && result.is_object()).then_some(result);
+    Ok(ToolResult {
+        content,
+        is_error: false,
+        truncated_from,
+        structured_content,
+    })
+}
+
+/// Normalizes a `network` argument ("Ethereum", "arbitrum one") to its GeckoTerminal slug.
+async fn resolve_network(server: &NovaServer, network: &str) -> Result<String, NovaError> {
+    server.gecko_terminal_tools().resolve_network(network).await
+}
+
+/// Runs one tool (built-in, pipeline or plugin) and returns its raw output.
+// Boxed because pipelines call back into it
+fn call_tool<'a>(
+    server: &'a NovaServer,
+    name: &'a str,
+    arguments: serde_json::Value,
+    context: &'a RequestContext,
+    priority: CallPriority,
+) -> BoxFuture<'a, Result<serde_json::Value, NovaError>> {
+    Box::pin(dispatch_tool(server, name, arguments, context, priority))
+}
+
+async fn dispatch_tool(
+    server: &NovaServer,
+    name: &str,
+    mut arguments: serde_json::Value,
+    context: &RequestContext,
+    priority: CallPriority,
+) -> Result<serde_json::Value, NovaError> {
+    if server.is_tool_disabled(name) {
+        return Err(NovaError::tool_disabled(name));
+    }
+    if let Some(tool) = server.builtin_tool(name) {
+        if server.coerces_arguments(name) {
+            schema::coerce_arguments(&tool.input_schema, &mut arguments);
+        }
+        server
+            .schemas()
+            .validate(&tool.name, &tool.input_schema, &arguments)?;
+    }
+    // Waiting for a slot counts against the caller's time budget
+    let _slot = server.tool_concurrency().acquire(name, priority).await;
+    let result = match name {
         "get_gecko_networks" => {
-            let input: GetGeckoNetworksInput = match serde_json::from_value(tool_call.arguments) {
+            let input: GetGeckoNetworksInput = match serde_json::from_value(arguments) {
                 Ok(v) => v,
                 Err(_) => return Err(NovaError::api_error("Invalid arguments")),
             };
@@ -138,68 +417,143 @@ pub(crate) async fn handle_tool_call(
             serde_json::to_value(output)?
         }
         "get_gecko_token" => {
-            let input: GetGeckoTokenInput = match serde_json::from_value(tool_call.arguments) {
+            let mut input: GetGeckoTokenInput = match serde_json::from_value(arguments) {
                 Ok(v) => v,
                 Err(_) => return Err(NovaError::api_error("Invalid arguments")),
             };
-            if input.network.trim().is_empty() || input.address.trim().is_empty() {
-                return Err(NovaError::api_error("network and address are required"));
-            }
+            input.network = resolve_network(server, &input.network).await?;
             let output = get_token(server.gecko_terminal_tools(), input).await?;
             serde_json::to_value(output)?
         }
         "get_gecko_pool" => {
-            let input: GetGeckoPoolInput = match serde_json::from_value(tool_call.arguments) {
+            let mut input: GetGeckoPoolInput = match serde_json::from_value(arguments) {
                 Ok(v) => v,
                 Err(_) => return Err(NovaError::api_error("Invalid arguments")),
             };
-            if input.network.trim().is_empty() || input.address.trim().is_empty() {
-                return Err(NovaError::api_error("network and address are required"));
-            }
+            input.network = resolve_network(server, &input.network).await?;
             let output = get_pool(server.gecko_terminal_tools(), input).await?;
             serde_json::to_value(output)?
         }
         "get_trending_pools" => {
-            let input: GetTrendingPoolsInput = match serde_json::from_value(tool_call.arguments) {
+            let mut input: GetTrendingPoolsInput = match serde_json::from_value(arguments) {
                 Ok(v) => v,
                 Err(_) => return Err(NovaError::api_error("Invalid arguments")),
             };
-            if input.network.trim().is_empty() {
-                return Err(NovaError::api_error("network is required"));
-            }
+            input.network = resolve_network(server, &input.network).await?;
             let output = get_trending_pools(server.trending_pools_tools(), input).await?;
             serde_json::to_value(output)?
         }
         "search_pools" => {
-            let input: SearchPoolsInput = match serde_json::from_value(tool_call.arguments) {
+            let mut input: SearchPoolsInput = match serde_json::from_value(arguments) {
                 Ok(v) => v,
                 Err(_) => return Err(NovaError::api_error("Invalid arguments")),
             };
-            if input.query.trim().is_empty() {
-                return Err(NovaError::api_error("query is required"));
+            if let Some(network) = &input.network {
+                input.network = Some(resolve_network(server, network).await?);
             }
             let output = search_pools(server.search_pools_tools(), input).await?;
             serde_json::to_value(output)?
         }
         "get_new_pools" => {
-            let input: GetNewPoolsInput = match serde_json::from_value(tool_call.arguments) {
+            let mut input: GetNewPoolsInput = match serde_json::from_value(arguments) {
                 Ok(v) => v,
                 Err(_) => return Err(NovaError::api_error("Invalid arguments")),
             };
-            if input.network.trim().is_empty() {
-                return Err(NovaError::api_error("network is required"));
-            }
+            input.network = resolve_network(server, &input.network).await?;
             let output = get_new_pools(server.new_pools_tools(), input).await?;
             serde_json::to_value(output)?
         }
+        "find_token_across_networks" => {
+            let mut input: FindTokenAcrossNetworksInput = match serde_json::from_value(arguments) {
+                Ok(v) => v,
+                Err(_) => return Err(NovaError::api_error("Invalid arguments")),
+            };
+            if let Some(network) = input.network.as_deref().filter(|n| !n.trim().is_empty()) {
+                input.network = Some(resolve_network(server, network).await?);
+            }
+            let output = find_token_across_networks(server.cross_network_tools(), input).await?;
+            serde_json::to_value(output)?
+        }
+        "find_arbitrage_spreads" => {
+            let mut input: FindArbitrageSpreadsInput = match serde_json::from_value(arguments) {
+                Ok(v) => v,
+                Err(_) => return Err(NovaError::api_error("Invalid arguments")),
+            };
+            input.network = resolve_network(server, &input.network).await?;
+            let output =
+                find_arbitrage_spreads(server.arbitrage_tools(), input, |tool, arguments| {
+                    call_step(server, tool, arguments, context, priority)
+                })
+                .await?;
+            serde_json::to_value(output)?
+        }
+        "get_solana_token_holders" => {
+            let input: GetSolanaTokenHoldersInput = match serde_json::from_value(arguments) {
+                Ok(v) => v,
+                Err(_) => return Err(NovaError::api_error("Invalid arguments")),
+            };
+            let output = get_solana_token_holders(server.solana_tools(), input).await?;
+            serde_json::to_value(output)?
+        }
+        "watch_whales" => {
+            let mut input: WatchWhalesInput = match serde_json::from_value(arguments) {
+                Ok(v) => v,
+                Err(_) => return Err(NovaError::api_error("Invalid arguments")),
+            };
+            input.network = resolve_network(server, &input.network).await?;
+            let output = watch_whales(server.whale_tools(), context, input).await?;
+            serde_json::to_value(output)?
+        }
+        "unwatch_whales" => {
+            let mut input: UnwatchWhalesInput = match serde_json::from_value(arguments) {
+                Ok(v) => v,
+                Err(_) => return Err(NovaError::api_error("Invalid arguments")),
+            };
+            input.network = resolve_network(server, &input.network).await?;
+            let output = unwatch_whales(server.whale_tools(), context, input)?;
+            serde_json::to_value(output)?
+        }
+        "get_token_unlocks" => {
+            let input: GetTokenUnlocksInput = match serde_json::from_value(arguments) {
+                Ok(v) => v,
+                Err(_) => return Err(NovaError::api_error("Invalid arguments")),
+            };
+            let output = get_token_unlocks(server.token_unlock_tools(), input).await?;
+            serde_json::to_value(output)?
+        }
+        "set_my_preferences" => {
+            let update: PreferencesUpdate = serde_json::from_value(arguments)
+                .map_err(|_| NovaError::api_error("Invalid arguments"))?;
+            let store = server.preferences();
+            let mut preferences = store.get_or_default(context)?;
+            preferences.apply(update);
+            preferences.validate(&server.runtime().current().preferences.usd_rates)?;
+            store.put(context, &preferences)?;
+            serde_json::to_value(preferences)?
+        }
+        "get_my_usage" => {
+            let quotas = &server.runtime().current().quotas;
+            serde_json::to_value(server.quotas().usage(context, quotas)?)?
+        }
+        "get_job_status" => {
+            let job_id = arguments["job_id"].as_str().unwrap_or_default();
+            serde_json::to_value(server.plugin_jobs().get(job_id, context)?)?
+        }
+        name if server.pipelines().contains(name) => {
+            run_pipeline(server, name, arguments, context, priority).await?
+        }
+        name if server.native_tools().contains(name) => {
+            let provider = server
+                .native_tools()
+                .get(name)
+                .ok_or_else(|| NovaError::api_error("Invalid tool name"))?;
+            provider.call(arguments, context).await?
+        }
         _ => {
-            let (expected_type, expected_id, _base, _version) =
-                parse_fully_qualified_name(&tool_call.name)
-                    .ok_or_else(|| NovaError::api_error("Invalid tool name"))?;
+            let (expected_type, expected_id, _base, _version) = parse_fully_qualified_name(name)
+                .ok_or_else(|| NovaError::api_error("Invalid tool name"))?;
 
-            let metadata = server
-                .plugin_manager()
-                .get_plugin_by_fq_name(&tool_call.name)?;
+            let metadata = server.plugin_manager().get_plugin_by_fq_name(name)?;
 
             if metadata.context_type != expected_type || metadata.context_id != expected_id {
                 return Err(NovaError::api_error(
@@ -207,26 +561,245 @@ pub(crate) async fn handle_tool_call(
                 ));
             }
 
+            let quotas = &server.runtime().current().quotas;
+            server.quotas().consume(context, Some(name), quotas)?;
             let response = server
                 .plugin_manager()
-                .invoke_plugin(&metadata, context, tool_call.arguments)
+                .invoke_plugin(&metadata, context, arguments)
                 .await?;
             response
         }
     };
+    Ok(result)
+}
 
-    Ok(ToolResult {
-        content: serde_json::to_string_pretty(&result)?,
-        is_error: false,
+/// Each step gets its own tool's time budget; the whole pipeline runs under the pipeline's.
+async fn run_pipeline(
+    server: &NovaServer,
+    name: &str,
+    arguments: serde_json::Value,
+    context: &RequestContext,
+    priority: CallPriority,
+) -> Result<serde_json::Value, NovaError> {
+    let pipeline = server
+        .pipelines()
+        .get(name)
+        .ok_or_else(|| NovaError::api_error("Invalid tool name"))?;
+    server
+        .schemas()
+        .validate(name, &pipeline.definition.input_schema, &arguments)?;
+    pipeline::execute(&pipeline, arguments, |tool, arguments| {
+        call_step(server, tool, arguments, context, priority)
+    })
+    .await
+}
+
+/// Calls a tool on behalf of a composite one under the called tool's own
+/// time budget.
+fn call_step<'a>(
+    server: &'a NovaServer,
+    tool: String,
+    arguments: serde_json::Value,
+    context: &'a RequestContext,
+    priority: CallPriority,
+) -> BoxFuture<'a, Result<serde_json::Value, NovaError>> {
+    Box::pin(async move {
+        let budget = server.tool_timeout(&tool);
+        match tokio::time::timeout(
+            budget,
+            call_tool(server, &tool, arguments, context, priority),
+        )
+        .await
+        {
+            Ok(result) => result,
+            Err(_) => Err(NovaError::tool_timeout(tool, budget.as_secs())),
+        }
     })
 }
 
+/// Drops tool fields the negotiated protocol revision doesn't define.
+fn tools_for_version(mut tools: Vec<Tool>, version: ProtocolVersion) -> Vec<Tool> {
+    for tool in &mut tools {
+        if !version.supports_tool_annotations() {
+            tool.annotations = None;
+        }
+        if !version.supports_structured_output() {
+            tool.output_schema = None;
+        }
+    }
+    tools
+}
+
+fn tool_result_json(result: ToolResult, version: ProtocolVersion) -> serde_json::Value {
+    let mut content = vec![json!({ "type": "text", "text": result.content })];
+    if let Some(total) = result.truncated_from {
+        content.push(json!({
+            "type": "text",
+            "text": format!(
+                "[truncated: showing the first {} of {} bytes]",
+                result.content.len(),
+                total
+            )
+        }));
+        return json!({
+            "content": content,
+            "isError": result.is_error,
+            "_meta": { "truncated": true, "originalBytes": total }
+        });
+    }
+    let mut body = json!({
+        "content": content,
+        "isError": result.is_error
+    });
+    if let Some(structured) = result
+        .structured_content
+        .filter(|_| version.supports_structured_output())
+    {
+        body["structuredContent"] = structured;
+    }
+    body
+}
+
+/// Answers `completion/complete` for `ref/tool` references; prompts and resources don't exist here.
+fn handle_completion(
+    server: &NovaServer,
+    request: &McpRequest,
+    context: &RequestContext,
+) -> McpResponse {
+    let params = request.params.as_ref();
+    let reference = params.and_then(|p| p.get("ref"));
+    let argument = params.and_then(|p| p.get("argument"));
+    let tool_name = reference
+        .filter(|r| r.get("type").and_then(|t| t.as_str()) == Some("ref/tool"))
+        .and_then(|r| r.get("name"))
+        .and_then(|n| n.as_str());
+    let argument_name = argument
+        .and_then(|a| a.get("name"))
+        .and_then(|n| n.as_str());
+    let (Some(tool_name), Some(argument_name)) = (tool_name, argument_name) else {
+        return invalid_params(
+            request.id.clone(),
+            "completion/complete needs a ref/tool reference and an argument name",
+        );
+    };
+    let prefix = argument
+        .and_then(|a| a.get("value"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+
+    let tools = match server.get_tools(context) {
+        Ok(tools) => tools,
+        Err(err) => {
+            return error_response(
+                request.id.clone(),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to load tools: {}", err),
+            )
+        }
+    };
+    let Some(tool) = tools.iter().find(|tool| tool.name == tool_name) else {
+        return invalid_params(request.id.clone(), format!("Unknown tool: {}", tool_name));
+    };
+    let completion = completion::complete(server, tool, argument_name, prefix);
+    McpResponse {
+        jsonrpc: "2.0".to_string(),
+        id: request.id.clone(),
+        result: Some(json!({ "completion": completion })),
+        error: None,
+    }
+}
+
+fn invalid_params(id: Option<serde_json::Value>, message: impl Into<String>) -> McpResponse {
+    McpResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(McpError {
+            code: -32602,
+            message: message.into(),
+            data: None,
+        }),
+    }
+}
+
+/// `notifications/tools/list_changed` for `session` when `change` may alter
+/// its tool list; `None` (a lagged subscriber) counts as any change. Nothing
+/// is sent before `initialize`.
+pub fn list_changed_notification(
+    server: &NovaServer,
+    session: &McpSession,
+    change: Option<&RegistryChange>,
+) -> Option<serde_json::Value> {
+    if !session.is_initialized() {
+        return None;
+    }
+    let context = session
+        .context()
+        .cloned()
+        .or_else(|| server.runtime().current().context.default_context());
+    if let Some(change) = change {
+        if !change.affects(context.as_ref()) {
+            return None;
+        }
+    }
+    Some(json!({ "jsonrpc": "2.0", "method": "notifications/tools/list_changed" }))
+}
+
+/// A fired alert as an `alert`-level `notifications/message` for every
+/// session, and a whale trade as a `notice` for sessions of the context
+/// watching the pool; each only when `logging/setLevel` lets it through.
+pub fn alert_notification(
+    server: &NovaServer,
+    session: &McpSession,
+    event: &ServerEvent,
+) -> Option<serde_json::Value> {
+    if !session.is_initialized() {
+        return None;
+    }
+    let (level, logger) = match &event.kind {
+        EventKind::AlertFired { .. } => (LogLevel::Alert, "events"),
+        EventKind::WhaleTrade { context, .. } => {
+            let owner = session
+                .context()
+                .cloned()
+                .or_else(|| server.runtime().current().context.default_context())?;
+            if owner.key() != *context {
+                return None;
+            }
+            (LogLevel::Notice, "whales")
+        }
+        _ => return None,
+    };
+    let wanted = session.log_level()?;
+    (wanted <= level).then(|| logging::notification(level, logger, json!(event)))
+}
+
 fn resolve_context(
     request: &McpRequest,
     transport_context: Option<RequestContext>,
+    id_format: ContextIdFormat,
 ) -> Result<RequestContext, Box<McpResponse>> {
+    let actor_id = request
+        .actor_id
+        .as_ref()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let invalid_actor = |reason: String| {
+        Box::new(error_response(
+            request.id.clone(),
+            StatusCode::UNAUTHORIZED,
+            format!("Invalid actor_id: {}", reason),
+        ))
+    };
+
     if let Some(context) = transport_context {
-        return Ok(context);
+        // Transports attach their own actor; the message field only fills a gap
+        if context.actor_id.is_some() || actor_id.is_none() {
+            return Ok(context);
+        }
+        return context
+            .with_actor(actor_id, id_format)
+            .map_err(invalid_actor);
     }
 
     let context_type = request
@@ -238,10 +811,9 @@ fn resolve_context(
         .as_ref()
         .map(|value| value.trim().to_string());
 
-    let context_type = match context_type.as_deref() {
-        Some("user") => PluginContextType::User,
-        Some("group") => PluginContextType::Group,
-        _ => {
+    let context_type = match context_type.as_deref().and_then(PluginContextType::parse) {
+        Some(context_type) => context_type,
+        None => {
             return Err(Box::new(error_response(
                 request.id.clone(),
                 StatusCode::UNAUTHORIZED,
@@ -261,38 +833,96 @@ fn resolve_context(
         }
     };
 
-    if context_id.parse::<i64>().is_err() {
+    if let Err(reason) = id_format.validate(&context_type, &context_id) {
         return Err(Box::new(error_response(
             request.id.clone(),
             StatusCode::UNAUTHORIZED,
-            "context_id must be numeric",
+            format!("Invalid context_id: {}", reason),
         )));
     }
 
-    Ok(RequestContext {
+    RequestContext {
         context_type,
         context_id,
-    })
+        actor_id: None,
+    }
+    .with_actor(actor_id, id_format)
+    .map_err(invalid_actor)
 }
 
 fn parse_fully_qualified_name(name: &str) -> Option<(PluginContextType, String, String, u32)> {
-    if let Some(stripped) = name.strip_prefix("user_") {
+    PluginContextType::ALL.into_iter().find_map(|context_type| {
+        let stripped = name
+            .strip_prefix(context_type.as_str())?
+            .strip_prefix('_')?;
         parse_name_parts(stripped)
-            .map(|(context_id, base, version)| (PluginContextType::User, context_id, base, version))
-    } else if let Some(stripped) = name.strip_prefix("group_") {
-        parse_name_parts(stripped).map(|(context_id, base, version)| {
-            (PluginContextType::Group, context_id, base, version)
-        })
-    } else {
-        None
-    }
+            .map(|(context_id, base, version)| (context_type, context_id, base, version))
+    })
 }
 
 fn parse_name_parts(input: &str) -> Option<(String, String, u32)> {
-    let (context_id, remainder) = input.split_once('_')?;
+    let (escaped_id, remainder) = input.split_once('_')?;
     let (base, version_part) = remainder.rsplit_once("_v")?;
     let version = version_part.parse::<u32>().ok()?;
-    Some((context_id.to_string(), base.to_string(), version))
+    Some((unescape_context_id(escaped_id)?, base.to_string(), version))
+}
+
+/// Decodes one JSON-RPC message. Text that is not JSON gets the `-32700`
+/// response; JSON that is not a request object gets [`request_from_value`]'s.
+pub fn parse_request(message: &str) -> Result<McpRequest, Box<McpResponse>> {
+    let value = serde_json::from_str::<serde_json::Value>(message).map_err(|e| {
+        Box::new(McpResponse {
+            jsonrpc: "2.0".to_string(),
+            id: None,
+            result: None,
+            error: Some(McpError {
+                code: -32700,
+                message: "Parse error".to_string(),
+                data: Some(json!({ "details": e.to_string() })),
+            }),
+        })
+    })?;
+    request_from_value(value)
+}
+
+/// Checks the envelope of a decoded message: an object with `"jsonrpc": "2.0"`,
+/// a string `method` and a string, number or null `id`. Anything else gets a
+/// `-32600` response, echoing the id when it is usable.
+pub fn request_from_value(value: serde_json::Value) -> Result<McpRequest, Box<McpResponse>> {
+    use serde_json::Value;
+    let id = value
+        .get("id")
+        .filter(|id| matches!(id, Value::String(_) | Value::Number(_)))
+        .cloned();
+    let problem = if !value.is_object() {
+        Some("request must be a JSON object".to_string())
+    } else if value.get("jsonrpc") != Some(&json!("2.0")) {
+        Some("jsonrpc must be \"2.0\"".to_string())
+    } else if !matches!(
+        value.get("id"),
+        None | Some(Value::Null | Value::String(_) | Value::Number(_))
+    ) {
+        Some("id must be a string, number or null".to_string())
+    } else {
+        None
+    };
+    let problem = match problem {
+        Some(problem) => problem,
+        None => match serde_json::from_value::<McpRequest>(value) {
+            Ok(request) => return Ok(request),
+            Err(e) => e.to_string(),
+        },
+    };
+    Err(Box::new(McpResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(McpError {
+            code: -32600,
+            message: "Invalid Request".to_string(),
+            data: Some(json!({ "details": problem })),
+        }),
+    }))
 }
 
 fn error_response(
@@ -0,0 +1,119 @@
+use crate::config::LimitsConfig;
+use crate::error::{NovaError, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::io;
+
+/// Guards applied to `tools/call` payloads before dispatch and after execution.
+#[derive(Debug, Clone)]
+pub struct PayloadLimits {
+    pub max_argument_bytes: usize,
+    pub max_json_depth: usize,
+    pub max_response_bytes: usize,
+}
+
+impl From<&LimitsConfig> for PayloadLimits {
+    fn from(cfg: &LimitsConfig) -> Self {
+        Self {
+            max_argument_bytes: cfg.max_argument_bytes,
+            max_json_depth: cfg.max_json_depth,
+            max_response_bytes: cfg.max_response_bytes,
+        }
+    }
+}
+
+impl Default for PayloadLimits {
+    fn default() -> Self {
+        Self::from(&LimitsConfig::default())
+    }
+}
+
+impl PayloadLimits {
+    pub fn check_arguments(&self, arguments: &Value) -> Result<()> {
+        let depth = json_depth(arguments);
+        if depth > self.max_json_depth {
+            return Err(NovaError::validation_error(format!(
+                "arguments nesting depth {} exceeds limit of {}",
+                depth, self.max_json_depth
+            )));
+        }
+        let size = serde_json::to_vec(arguments)?.len();
+        if size > self.max_argument_bytes {
+            return Err(NovaError::validation_error(format!(
+                "arguments size {} bytes exceeds limit of {} bytes",
+                size, self.max_argument_bytes
+            )));
+        }
+        Ok(())
+    }
+
+    /// Pretty-prints `value`, keeping at most `max_response_bytes` in memory.
+    /// Returns the (possibly cut) text and the full serialized length when cut.
+    pub fn render<T: Serialize>(&self, value: &T) -> Result<(String, Option<usize>)> {
+        let mut writer = BoundedWriter::new(self.max_response_bytes);
+        serde_json::to_writer_pretty(&mut writer, value)?;
+        Ok(writer.finish())
+    }
+
+    /// Cuts already-rendered text to `max_response_bytes` on a character boundary.
+    pub fn render_text(&self, text: String) -> (String, Option<usize>) {
+        let mut writer = BoundedWriter::new(self.max_response_bytes);
+        let _ = io::Write::write(&mut writer, text.as_bytes());
+        writer.finish()
+    }
+}
+
+/// Maximum nesting depth of arrays/objects; scalars have depth 0.
+pub fn json_depth(value: &Value) -> usize {
+    match value {
+        Value::Array(items) => 1 + items.iter().map(json_depth).max().unwrap_or(0),
+        Value::Object(map) => 1 + map.values().map(json_depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// `io::Write` sink that counts every byte but only buffers up to `limit`,
+/// so oversized results are never fully materialized.
+struct BoundedWriter {
+    buf: Vec<u8>,
+    limit: usize,
+    total: usize,
+}
+
+impl BoundedWriter {
+    fn new(limit: usize) -> Self {
+        Self {
+            buf: Vec::new(),
+            limit,
+            total: 0,
+        }
+    }
+
+    fn finish(self) -> (String, Option<usize>) {
+        let truncated = self.total > self.limit;
+        let text = match String::from_utf8(self.buf) {
+            Ok(text) => text,
+            // The cut may land inside a multi-byte character; drop the partial tail.
+            Err(err) => {
+                let valid = err.utf8_error().valid_up_to();
+                let mut bytes = err.into_bytes();
+                bytes.truncate(valid);
+                String::from_utf8(bytes).unwrap_or_default()
+            }
+        };
+        (text, truncated.then_some(self.total))
+    }
+}
+
+impl io::Write for BoundedWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let room = self.limit.saturating_sub(self.buf.len());
+        self.buf.extend_from_slice(&data[..data.len().min(room)]);
+        self.total += data.len();
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
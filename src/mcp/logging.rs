@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::future::Future;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Syslog-style severities used by `logging/setLevel` and `notifications/message`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+/// Delivers log notifications for one in-flight request to its client.
+#[derive(Clone)]
+pub struct ClientLogger {
+    min_level: LogLevel,
+    sink: UnboundedSender<Value>,
+}
+
+impl ClientLogger {
+    pub fn new(min_level: LogLevel, sink: UnboundedSender<Value>) -> Self {
+        Self { min_level, sink }
+    }
+}
+
+tokio::task_local! {
+    static CLIENT_LOGGER: ClientLogger;
+}
+
+/// Runs `future` with `logger` receiving [`log`] calls made anywhere beneath it.
+pub async fn scope<F: Future>(logger: Option<ClientLogger>, future: F) -> F::Output {
+    match logger {
+        Some(logger) => CLIENT_LOGGER.scope(logger, future).await,
+        None => future.await,
+    }
+}
+
+/// Sends a `notifications/message` to the current client if it asked for `level` or lower.
+/// Outside a logging scope (or with no client listening) this does nothing.
+pub fn log(level: LogLevel, logger: &str, data: Value) {
+    let _ = CLIENT_LOGGER.try_with(|client| {
+        if level < client.min_level {
+            return;
+        }
+        let _ = client.sink.send(notification(level, logger, data));
+    });
+}
+
+/// The `notifications/message` carrying `data`.
+pub fn notification(level: LogLevel, logger: &str, data: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/message",
+        "params": { "level": level, "logger": logger, "data": data }
+    })
+}
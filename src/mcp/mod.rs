@@ -1,2 +1,9 @@
+pub mod completion;
 pub mod dto;
 pub mod handler;
+pub mod limits;
+pub mod logging;
+pub mod progress;
+pub mod protocol;
+pub mod select;
+pub mod session;
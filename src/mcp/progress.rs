@@ -0,0 +1,54 @@
+//! `notifications/progress` for tool calls that asked for it with
+//! `params._meta.progressToken`, e.g. chunks of a streaming plugin response.
+
+use serde_json::{json, Value};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedSender;
+
+tokio::task_local! {
+    // Where notifications for the request being handled go; set by the transport
+    static SINK: UnboundedSender<Value>;
+    // The `progressToken` of the tool call being handled, with the count sent so far
+    static TOKEN: (Value, Arc<AtomicU64>);
+}
+
+/// Runs `future` with [`report`] calls beneath it delivered to `sink`.
+pub async fn scope<F: Future>(sink: UnboundedSender<Value>, future: F) -> F::Output {
+    SINK.scope(sink, future).await
+}
+
+/// Runs `future` with [`report`] calls tagged with `token`, the
+/// `params._meta.progressToken` of a `tools/call`. Without a token reports
+/// are dropped, as the client did not ask for them.
+pub async fn track<F: Future>(token: Option<Value>, future: F) -> F::Output {
+    match token {
+        Some(token) => {
+            TOKEN
+                .scope((token, Arc::new(AtomicU64::new(0))), future)
+                .await
+        }
+        None => future.await,
+    }
+}
+
+/// Sends a `notifications/progress` for the current tool call with `message`
+/// and a progress count that grows by one per report. Outside a transport
+/// scope or without a token this does nothing.
+pub fn report(message: &str) {
+    let _ = TOKEN.try_with(|(token, sent)| {
+        let progress = sent.fetch_add(1, Ordering::SeqCst) + 1;
+        let _ = SINK.try_with(|sink| {
+            let _ = sink.send(json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/progress",
+                "params": {
+                    "progressToken": token,
+                    "progress": progress,
+                    "message": message
+                }
+            }));
+        });
+    });
+}
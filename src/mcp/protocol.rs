@@ -0,0 +1,69 @@
+use serde::Serialize;
+use serde_json::json;
+
+use super::dto::McpError;
+
+/// MCP protocol revisions this server can speak, oldest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum ProtocolVersion {
+    #[serde(rename = "2024-11-05")]
+    V2024_11_05,
+    /// Adds tool annotations.
+    #[serde(rename = "2025-03-26")]
+    V2025_03_26,
+    /// Adds tool output schemas and `structuredContent` results.
+    #[serde(rename = "2025-06-18")]
+    V2025_06_18,
+}
+
+impl ProtocolVersion {
+    pub const SUPPORTED: &'static [ProtocolVersion] = &[
+        ProtocolVersion::V2024_11_05,
+        ProtocolVersion::V2025_03_26,
+        ProtocolVersion::V2025_06_18,
+    ];
+
+    pub const LATEST: ProtocolVersion = ProtocolVersion::V2025_06_18;
+
+    /// Assumed for clients that never send `initialize` (or omit the version).
+    pub const DEFAULT: ProtocolVersion = ProtocolVersion::V2024_11_05;
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProtocolVersion::V2024_11_05 => "2024-11-05",
+            ProtocolVersion::V2025_03_26 => "2025-03-26",
+            ProtocolVersion::V2025_06_18 => "2025-06-18",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::SUPPORTED
+            .iter()
+            .copied()
+            .find(|version| version.as_str() == value.trim())
+    }
+
+    /// Resolves the version requested in `initialize`; unknown versions are rejected
+    /// with the supported list in `error.data`.
+    pub fn negotiate(requested: Option<&str>) -> Result<Self, McpError> {
+        let Some(requested) = requested else {
+            return Ok(Self::DEFAULT);
+        };
+        Self::parse(requested).ok_or_else(|| McpError {
+            code: -32602,
+            message: format!("Unsupported protocol version: {}", requested),
+            data: Some(json!({
+                "requested": requested,
+                "supported": Self::SUPPORTED.iter().map(|v| v.as_str()).collect::<Vec<_>>(),
+            })),
+        })
+    }
+
+    pub fn supports_tool_annotations(self) -> bool {
+        self >= ProtocolVersion::V2025_03_26
+    }
+
+    pub fn supports_structured_output(self) -> bool {
+        self >= ProtocolVersion::V2025_06_18
+    }
+}
@@ -0,0 +1,83 @@
+//! `select` on `tools/call`: trims a tool result to the parts an agent asked for.
+//!
+//! Expressions use the redaction path grammar (`$.data.attributes.name`,
+//! `$.pools[*].address`, `$..price_usd`); the leading `$.` may be omitted.
+
+use serde_json::Value;
+
+use crate::plugins::redaction::{parse_segments, Segment};
+
+#[derive(Debug, Clone)]
+pub struct Selector {
+    segments: Vec<Segment>,
+}
+
+impl Selector {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = expression.trim();
+        let path = if expression.starts_with('$') {
+            expression.to_string()
+        } else if expression.starts_with('[') {
+            format!("${}", expression)
+        } else {
+            format!("$.{}", expression)
+        };
+        let segments = parse_segments(&path).map_err(|e| format!("{}: {}", expression, e))?;
+        Ok(Self { segments })
+    }
+
+    /// A path without wildcards yields its value (or `null`); otherwise an
+    /// array of every match.
+    pub fn apply(&self, value: &Value) -> Value {
+        let mut matches = vec![value];
+        for segment in &self.segments {
+            matches = matches
+                .into_iter()
+                .flat_map(|value| step(value, segment))
+                .collect();
+        }
+        let definite = self
+            .segments
+            .iter()
+            .all(|segment| matches!(segment, Segment::Key(_) | Segment::Index(_)));
+        if definite {
+            matches
+                .first()
+                .map_or(Value::Null, |value| (*value).clone())
+        } else {
+            Value::Array(matches.into_iter().cloned().collect())
+        }
+    }
+}
+
+fn step<'a>(value: &'a Value, segment: &Segment) -> Vec<&'a Value> {
+    match (segment, value) {
+        (Segment::Key(key), Value::Object(map)) => map.get(key).into_iter().collect(),
+        (Segment::Index(index), Value::Array(items)) => items.get(*index).into_iter().collect(),
+        (Segment::Wildcard, Value::Object(map)) => map.values().collect(),
+        (Segment::Wildcard, Value::Array(items)) => items.iter().collect(),
+        (Segment::Descendant(key), value) => {
+            let mut found = Vec::new();
+            descendants(value, key, &mut found);
+            found
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn descendants<'a>(value: &'a Value, key: &str, found: &mut Vec<&'a Value>) {
+    match value {
+        Value::Object(map) => {
+            for (name, child) in map {
+                if name == key {
+                    found.push(child);
+                }
+                descendants(child, key, found);
+            }
+        }
+        Value::Array(items) => items
+            .iter()
+            .for_each(|child| descendants(child, key, found)),
+        _ => {}
+    }
+}
@@ -0,0 +1,281 @@
+use crate::plugins::RequestContext;
+use dashmap::DashMap;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::time::{Duration, Instant};
+
+use super::logging::{ClientLogger, LogLevel};
+use super::protocol::ProtocolVersion;
+
+/// Per-connection MCP state: negotiated protocol, client capabilities and caller context.
+///
+/// Stdio keeps one for the life of the process; HTTP clients opt in by echoing
+/// the `Mcp-Session-Id` returned from `initialize`.
+#[derive(Debug, Clone)]
+pub struct McpSession {
+    id: String,
+    protocol_version: Option<ProtocolVersion>,
+    client_capabilities: Option<Value>,
+    client_info: Option<Value>,
+    context: Option<RequestContext>,
+    log_level: Option<LogLevel>,
+    // Fields set since the session was read from a store
+    changed: Changed,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Changed {
+    protocol_version: bool,
+    client: bool,
+    context: bool,
+    log_level: bool,
+}
+
+impl Default for McpSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl McpSession {
+    pub fn new() -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            protocol_version: None,
+            client_capabilities: None,
+            client_info: None,
+            context: None,
+            log_level: None,
+            changed: Changed::default(),
+        }
+    }
+
+    /// Session already pinned to `version`, e.g. from an HTTP `MCP-Protocol-Version` header.
+    pub fn with_protocol_version(version: ProtocolVersion) -> Self {
+        let mut session = Self::new();
+        session.protocol_version = Some(version);
+        session
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Negotiated version, or [`ProtocolVersion::DEFAULT`] before `initialize`.
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        self.protocol_version.unwrap_or(ProtocolVersion::DEFAULT)
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.protocol_version.is_some()
+    }
+
+    pub fn client_capabilities(&self) -> Option<&Value> {
+        self.client_capabilities.as_ref()
+    }
+
+    pub fn client_info(&self) -> Option<&Value> {
+        self.client_info.as_ref()
+    }
+
+    /// Caller context resolved at `initialize`, reused when later requests omit it.
+    pub fn context(&self) -> Option<&RequestContext> {
+        self.context.as_ref()
+    }
+
+    /// Minimum level set via `logging/setLevel`; no log notifications are sent before that.
+    pub fn log_level(&self) -> Option<LogLevel> {
+        self.log_level
+    }
+
+    /// Logger feeding `sink`, if the client enabled logging.
+    pub fn client_logger(
+        &self,
+        sink: &tokio::sync::mpsc::UnboundedSender<Value>,
+    ) -> Option<ClientLogger> {
+        self.log_level
+            .map(|level| ClientLogger::new(level, sink.clone()))
+    }
+
+    pub(crate) fn set_log_level(&mut self, level: LogLevel) {
+        self.log_level = Some(level);
+        self.changed.log_level = true;
+    }
+
+    pub(crate) fn set_protocol_version(&mut self, version: ProtocolVersion) {
+        self.protocol_version = Some(version);
+        self.changed.protocol_version = true;
+    }
+
+    pub(crate) fn set_client(&mut self, capabilities: Option<Value>, info: Option<Value>) {
+        self.client_capabilities = capabilities;
+        self.client_info = info;
+        self.changed.client = true;
+    }
+
+    /// The actor is per request, so it is not kept for later calls.
+    pub(crate) fn set_context(&mut self, mut context: RequestContext) {
+        context.actor_id = None;
+        self.context = Some(context);
+        self.changed.context = true;
+    }
+
+    /// Copies the fields `other` set since it was read, leaving the rest as
+    /// other requests on the session left them.
+    fn merge(&mut self, other: McpSession) {
+        if other.changed.protocol_version {
+            self.protocol_version = other.protocol_version;
+        }
+        if other.changed.client {
+            self.client_capabilities = other.client_capabilities;
+            self.client_info = other.client_info;
+        }
+        if other.changed.context {
+            self.context = other.context;
+        }
+        if other.changed.log_level {
+            self.log_level = other.log_level;
+        }
+    }
+}
+
+struct StoredSession {
+    session: McpSession,
+    // SHA-256 of the credential that created the session; other keys cannot
+    // resume it, and the store never holds the key itself.
+    owner: Option<String>,
+    last_seen: Instant,
+}
+
+type EvictionHook = Box<dyn Fn(&str) + Send + Sync>;
+
+/// HTTP sessions keyed by `Mcp-Session-Id`, dropped after `idle_ttl` without use.
+pub struct SessionStore {
+    sessions: DashMap<String, StoredSession>,
+    idle_ttl: Duration,
+    // 0 = no cap
+    max_sessions: usize,
+    on_evict: Option<EvictionHook>,
+}
+
+impl SessionStore {
+    pub fn new(idle_ttl: Duration) -> Self {
+        Self {
+            sessions: DashMap::new(),
+            idle_ttl,
+            max_sessions: 0,
+            on_evict: None,
+        }
+    }
+
+    /// Refuses new sessions while `max` live ones exist; 0 removes the cap.
+    pub fn with_max_sessions(mut self, max: usize) -> Self {
+        self.max_sessions = max;
+        self
+    }
+
+    /// Calls `hook` with the id of every session that idles out, so state
+    /// kept beside the store, such as its event stream, goes with it.
+    pub fn with_eviction_hook(mut self, hook: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        self.on_evict = Some(Box::new(hook));
+        self
+    }
+
+    /// Stores a new session; false when the store is full.
+    pub fn insert(&self, mut session: McpSession, owner: Option<&str>) -> bool {
+        self.prune();
+        if self.max_sessions > 0 && self.sessions.len() >= self.max_sessions {
+            return false;
+        }
+        session.changed = Changed::default();
+        self.sessions.insert(
+            session.id.clone(),
+            StoredSession {
+                session,
+                owner: owner.map(owner_digest),
+                last_seen: Instant::now(),
+            },
+        );
+        true
+    }
+
+    /// Live session `id` if it exists, hasn't idled out and belongs to `owner`.
+    pub fn get(&self, id: &str, owner: Option<&str>) -> Option<McpSession> {
+        let mut entry = self.sessions.get_mut(id)?;
+        if entry.last_seen.elapsed() > self.idle_ttl {
+            drop(entry);
+            if self.sessions.remove(id).is_some() {
+                self.evicted(id);
+            }
+            return None;
+        }
+        if entry.owner != owner.map(owner_digest) {
+            return None;
+        }
+        entry.last_seen = Instant::now();
+        Some(entry.session.clone())
+    }
+
+    /// Writes back the fields a request changed. The stored session is
+    /// updated in place, so concurrent requests that change different
+    /// fields all keep their changes.
+    pub fn update(&self, session: McpSession) {
+        if let Some(mut entry) = self.sessions.get_mut(&session.id) {
+            entry.session.merge(session);
+            entry.last_seen = Instant::now();
+        }
+    }
+
+    /// Sessions that have not idled out.
+    pub fn live(&self) -> Vec<McpSession> {
+        self.sessions
+            .iter()
+            .filter(|entry| entry.last_seen.elapsed() <= self.idle_ttl)
+            .map(|entry| entry.session.clone())
+            .collect()
+    }
+
+    pub fn remove(&self, id: &str, owner: Option<&str>) -> bool {
+        let owner = owner.map(owner_digest);
+        self.sessions
+            .remove_if(id, |_, stored| stored.owner == owner)
+            .is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    fn prune(&self) {
+        let ttl = self.idle_ttl;
+        let mut expired = Vec::new();
+        self.sessions.retain(|id, stored| {
+            let live = stored.last_seen.elapsed() <= ttl;
+            if !live {
+                expired.push(id.clone());
+            }
+            live
+        });
+        for id in expired {
+            self.evicted(&id);
+        }
+    }
+
+    fn evicted(&self, id: &str) {
+        if let Some(hook) = &self.on_evict {
+            hook(id);
+        }
+    }
+}
+
+/// Hex SHA-256 of a session owner, so a dump of the store holds no keys.
+fn owner_digest(owner: &str) -> String {
+    Sha256::digest(owner.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+/// One plugin call that reached the plugin's endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageEvent {
+    // Unique per event, so webhook receivers can drop redeliveries
+    pub id: String,
+    // Unix seconds when the call finished
+    pub at: i64,
+    // `<type>:<id>` of the calling context
+    pub context: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor_id: Option<String>,
+    pub plugin_id: u64,
+    pub plugin: String,
+    // `<type>:<id>` of the context that registered the plugin
+    pub owner: String,
+    pub duration_ms: u64,
+    // Body sent to the endpoint and body received back
+    pub request_bytes: u64,
+    pub response_bytes: u64,
+    pub success: bool,
+}
+
+/// `GET /admin/metering/usage` filters; `since` and `until` are unix seconds,
+/// `until` exclusive.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageQuery {
+    #[serde(default)]
+    pub since: Option<i64>,
+    #[serde(default)]
+    pub until: Option<i64>,
+    #[serde(default)]
+    pub context: Option<String>,
+    #[serde(default)]
+    pub plugin: Option<String>,
+}
+
+/// Ledger totals for one calling context and plugin: an invoice line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageSummary {
+    pub context: String,
+    pub plugin: String,
+    pub owner: String,
+    pub calls: u64,
+    pub failed_calls: u64,
+    pub duration_ms: u64,
+    pub request_bytes: u64,
+    pub response_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageReport {
+    #[serde(default)]
+    pub since: Option<i64>,
+    #[serde(default)]
+    pub until: Option<i64>,
+    pub lines: Vec<UsageSummary>,
+}
@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+use std::ops::Bound;
+
+use super::dto::{UsageEvent, UsageQuery, UsageSummary};
+use crate::error::Result;
+use crate::storage::{KvStore, MemoryKv};
+
+/// Stands in for the calling and owning context of events whose context was
+/// deleted.
+pub const DELETED_CONTEXT: &str = "deleted";
+
+/// Append-only record of usage events, the source for invoices.
+///
+/// Keys are the event's `at` (big-endian) followed by its id, so a billing
+/// period is one range scan.
+pub struct UsageLedger {
+    store: Box<dyn KvStore>,
+}
+
+impl UsageLedger {
+    pub fn in_memory() -> Self {
+        Self {
+            store: Box::new(MemoryKv::default()),
+        }
+    }
+
+    pub fn persistent(tree: sled::Tree) -> Self {
+        Self {
+            store: Box::new(tree),
+        }
+    }
+
+    pub fn append(&self, event: &UsageEvent) -> Result<()> {
+        self.store
+            .insert_json(&event_key(event.at, &event.id), event)
+    }
+
+    /// Events matching `query`, oldest first.
+    pub fn events(&self, query: &UsageQuery) -> Result<Vec<UsageEvent>> {
+        let start = query.since.map_or(Bound::Unbounded, |since| {
+            Bound::Included(event_key(since, ""))
+        });
+        let end = query.until.map_or(Bound::Unbounded, |until| {
+            Bound::Excluded(event_key(until, ""))
+        });
+        let mut events = Vec::new();
+        for (_, bytes) in self.store.range(
+            start.as_ref().map(Vec::as_slice),
+            end.as_ref().map(Vec::as_slice),
+        )? {
+            events.push(serde_json::from_slice::<UsageEvent>(&bytes)?);
+        }
+        Ok(events
+            .into_iter()
+            .filter(|event| query.context.as_ref().is_none_or(|c| *c == event.context))
+            .filter(|event| query.plugin.as_ref().is_none_or(|p| *p == event.plugin))
+            .collect())
+    }
+
+    /// Keeps the events made by or billed to a deleted context, given as
+    /// `<type>:<id>`, for the totals, but drops who made them: `context` and
+    /// `owner` become [`DELETED_CONTEXT`], `actor_id` goes, and the fq_name of
+    /// a plugin the context owned, which names it, becomes `deleted_<plugin_id>`.
+    /// Returns how many events changed.
+    pub fn anonymize_context(&self, context: &str) -> Result<usize> {
+        let mut changed = 0;
+        for (key, bytes) in self.store.scan_prefix(b"")? {
+            let mut event: UsageEvent = serde_json::from_slice(&bytes)?;
+            if event.context != context && event.owner != context {
+                continue;
+            }
+            if event.context == context {
+                event.context = DELETED_CONTEXT.to_string();
+                event.actor_id = None;
+            }
+            if event.owner == context {
+                event.owner = DELETED_CONTEXT.to_string();
+                event.plugin = format!("{}_{}", DELETED_CONTEXT, event.plugin_id);
+            }
+            self.store.insert_json(&key, &event)?;
+            changed += 1;
+        }
+        Ok(changed)
+    }
+
+    /// Totals per calling context and plugin, ordered by both.
+    pub fn summarize(&self, query: &UsageQuery) -> Result<Vec<UsageSummary>> {
+        let mut lines: BTreeMap<(String, String), UsageSummary> = BTreeMap::new();
+        for event in self.events(query)? {
+            let line = lines
+                .entry((event.context.clone(), event.plugin.clone()))
+                .or_insert_with(|| UsageSummary {
+                    context: event.context.clone(),
+                    plugin: event.plugin.clone(),
+                    owner: event.owner.clone(),
+                    calls: 0,
+                    failed_calls: 0,
+                    duration_ms: 0,
+                    request_bytes: 0,
+                    response_bytes: 0,
+                });
+            line.calls += 1;
+            line.failed_calls += u64::from(!event.success);
+            line.duration_ms += event.duration_ms;
+            line.request_bytes += event.request_bytes;
+            line.response_bytes += event.response_bytes;
+        }
+        Ok(lines.into_values().collect())
+    }
+}
+
+impl Default for UsageLedger {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+fn event_key(at: i64, id: &str) -> Vec<u8> {
+    let mut key = (at.max(0) as u64).to_be_bytes().to_vec();
+    key.extend_from_slice(id.as_bytes());
+    key
+}
@@ -0,0 +1,122 @@
+//! Usage events for plugin calls, for operators who bill plugin usage.
+//!
+//! Every plugin call that reaches its endpoint produces a [`UsageEvent`],
+//! which goes to the [`UsageLedger`] (behind `GET /admin/metering/usage`) and
+//! to any extra [`MeteringSink`]s. Sink failures are logged and never fail
+//! the call.
+
+pub mod dto;
+pub mod ledger;
+pub mod sink;
+
+use std::sync::Arc;
+
+use reqwest::Client;
+
+use crate::config::MeteringConfig;
+use crate::dead_letters::DeadLetters;
+use crate::error::{NovaError, Result};
+
+pub use dto::{UsageEvent, UsageQuery, UsageReport, UsageSummary};
+pub use ledger::{UsageLedger, DELETED_CONTEXT};
+pub use sink::{MeteringSink, WebhookSink};
+
+#[derive(Default)]
+pub struct Metering {
+    ledger: Option<UsageLedger>,
+    sinks: Vec<Arc<dyn MeteringSink>>,
+    // Also in `sinks`; kept to requeue its dead letters
+    webhook: Option<Arc<WebhookSink>>,
+}
+
+impl Metering {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The sinks listed in `metering.sinks`; the ledger is kept in
+    /// `ledger_tree`, or in memory without one, and events the webhook
+    /// fails to take go to `dead_letters`.
+    pub fn from_config(
+        config: &MeteringConfig,
+        ledger_tree: Option<sled::Tree>,
+        client: Client,
+        dead_letters: Arc<DeadLetters>,
+    ) -> Result<Self> {
+        let mut metering = Self::new();
+        for sink in &config.sinks {
+            match sink.as_str() {
+                "ledger" => {
+                    let ledger = match ledger_tree.clone() {
+                        Some(tree) => UsageLedger::persistent(tree),
+                        None => UsageLedger::in_memory(),
+                    };
+                    metering = metering.with_ledger(ledger);
+                }
+                "webhook" => {
+                    let url = config.webhook_url.clone().ok_or_else(|| {
+                        NovaError::config_error(
+                            "metering.webhook_url is required for the webhook sink",
+                        )
+                    })?;
+                    let webhook = Arc::new(WebhookSink::new(
+                        url,
+                        client.clone(),
+                        Arc::clone(&dead_letters),
+                    ));
+                    metering.webhook = Some(Arc::clone(&webhook));
+                    metering = metering.with_sink(webhook);
+                }
+                other => {
+                    return Err(NovaError::config_error(format!(
+                        "Unknown metering sink: {}",
+                        other
+                    )))
+                }
+            }
+        }
+        Ok(metering)
+    }
+
+    pub fn with_ledger(mut self, ledger: UsageLedger) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+
+    /// Adds a sink, e.g. a Kafka producer supplied by the embedding binary.
+    pub fn with_sink(mut self, sink: Arc<dyn MeteringSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    pub fn ledger(&self) -> Option<&UsageLedger> {
+        self.ledger.as_ref()
+    }
+
+    /// The `webhook` sink, when `metering.sinks` lists it.
+    pub fn webhook(&self) -> Option<&WebhookSink> {
+        self.webhook.as_deref()
+    }
+
+    pub fn record(&self, event: UsageEvent) {
+        if let Some(ledger) = &self.ledger {
+            if let Err(e) = ledger.append(&event) {
+                tracing::warn!(
+                    "Failed to write usage event {} to the ledger: {}",
+                    event.id,
+                    e
+                );
+            }
+        }
+        for sink in &self.sinks {
+            if let Err(e) = sink.record(&event) {
+                tracing::warn!(
+                    "Metering sink {} failed for event {}: {}",
+                    sink.name(),
+                    event.id,
+                    e
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use reqwest::Client;
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+use super::dto::UsageEvent;
+use crate::dead_letters::{DeadLetters, Delivery, DeliveryKind};
+use crate::error::{NovaError, Result};
+
+/// Events waiting for the webhook before new ones are dropped.
+const WEBHOOK_QUEUE: usize = 1024;
+
+/// Somewhere usage events go besides the ledger, e.g. a billing service or a
+/// message queue.
+///
+/// `record` runs on the plugin call path after the response is in, so sinks
+/// doing I/O should hand the event off rather than wait on it, as
+/// [`WebhookSink`] does.
+pub trait MeteringSink: Send + Sync {
+    fn name(&self) -> &str;
+    fn record(&self, event: &UsageEvent) -> Result<()>;
+}
+
+/// POSTs each event as JSON to `metering.webhook_url` from a background task.
+///
+/// Each event is sent once. Events whose POST fails, or that arrive while
+/// the queue is full, become dead letters an admin can requeue. The ledger
+/// is the record to bill from.
+pub struct WebhookSink {
+    url: String,
+    client: Client,
+    queue: mpsc::Sender<UsageEvent>,
+    dead_letters: Arc<DeadLetters>,
+}
+
+impl WebhookSink {
+    /// Starts the delivery task; call from within the Tokio runtime.
+    pub fn new(url: impl Into<String>, client: Client, dead_letters: Arc<DeadLetters>) -> Self {
+        let url = url.into();
+        let (queue, mut events) = mpsc::channel::<UsageEvent>(WEBHOOK_QUEUE);
+        let (target, sender, failures) = (url.clone(), client.clone(), dead_letters.clone());
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                if let Err(e) = post(&sender, &target, &event).await {
+                    dead_letter(&failures, &target, &event, 1, &e.to_string());
+                }
+            }
+        });
+        Self {
+            url,
+            client,
+            queue,
+            dead_letters,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Sends a dead-lettered event again, to the URL it first went to.
+    pub async fn redeliver(&self, delivery: Delivery) -> Result<()> {
+        post(&self.client, &delivery.target, &delivery.payload).await
+    }
+}
+
+impl MeteringSink for WebhookSink {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    fn record(&self, event: &UsageEvent) -> Result<()> {
+        self.queue.try_send(event.clone()).map_err(|e| {
+            dead_letter(&self.dead_letters, &self.url, event, 0, &e.to_string());
+            NovaError::internal(format!("Metering webhook queue: {}", e))
+        })
+    }
+}
+
+pub(crate) async fn post(client: &Client, url: &str, body: &impl Serialize) -> Result<()> {
+    client
+        .post(url)
+        .json(body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map(drop)
+        .map_err(NovaError::from)
+}
+
+fn dead_letter(
+    dead_letters: &DeadLetters,
+    url: &str,
+    event: &UsageEvent,
+    attempts: u32,
+    error: &str,
+) {
+    let delivery = serde_json::to_value(event).map(|payload| Delivery {
+        kind: DeliveryKind::MeteringWebhook,
+        target: url.to_string(),
+        reference: event.id.clone(),
+        // Usage events outlive their context, as the ledger does
+        context: None,
+        payload,
+    });
+    let recorded = delivery
+        .map_err(NovaError::from)
+        .and_then(|delivery| dead_letters.record(delivery, attempts, error));
+    if let Err(e) = recorded {
+        tracing::warn!("Metering webhook dropped event {}: {}", event.id, e);
+    }
+}
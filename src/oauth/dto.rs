@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+
+use crate::auth::{SCOPE_PLUGINS_READ, SCOPE_PLUGINS_WRITE};
+use crate::plugins::PluginContextType;
+
+/// Scopes a plugin-developer client may hold; also the default grant.
+pub const CLIENT_SCOPES: &[&str] = &[SCOPE_PLUGINS_READ, SCOPE_PLUGINS_WRITE];
+
+/// Stored client; tokens it obtains act for its context only.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthClient {
+    pub client_id: String,
+    // Hex SHA-256 of the secret; the secret itself is shown once at creation
+    pub secret_hash: String,
+    pub context_type: PluginContextType,
+    pub context_id: String,
+    pub scopes: Vec<String>,
+    pub created_at: i64,
+}
+
+/// Listing view without the secret hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthClientSummary {
+    pub client_id: String,
+    pub context_type: PluginContextType,
+    pub context_id: String,
+    pub scopes: Vec<String>,
+    pub created_at: i64,
+}
+
+impl From<&OAuthClient> for OAuthClientSummary {
+    fn from(client: &OAuthClient) -> Self {
+        Self {
+            client_id: client.client_id.clone(),
+            context_type: client.context_type.clone(),
+            context_id: client.context_id.clone(),
+            scopes: client.scopes.clone(),
+            created_at: client.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthClientCreateRequest {
+    pub context_type: PluginContextType,
+    pub context_id: String,
+    // Subset of `CLIENT_SCOPES`; all of them when omitted
+    #[serde(default)]
+    pub scopes: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthClientCreated {
+    #[serde(flatten)]
+    pub client: OAuthClientSummary,
+    pub client_secret: String,
+}
+
+/// `application/x-www-form-urlencoded` body of `POST /oauth/token`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TokenRequest {
+    pub grant_type: String,
+    // Either here or via HTTP Basic
+    #[serde(default)]
+    pub client_id: Option<String>,
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    // Space-separated subset of the client's scopes
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: u64,
+    pub scope: String,
+}
+
+/// Error body as defined by RFC 6749 section 5.2.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthErrorResponse {
+    pub error: String,
+    pub error_description: String,
+}
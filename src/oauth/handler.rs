@@ -0,0 +1,168 @@
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Form, Json,
+};
+use base64::Engine;
+use jsonwebtoken::{encode, EncodingKey, Header};
+
+use crate::auth::{AuthMode, JwtClaims};
+use crate::http::AppState;
+
+use super::dto::{OAuthErrorResponse, TokenRequest, TokenResponse};
+
+type OAuthResult<T> = Result<T, (StatusCode, Json<OAuthErrorResponse>)>;
+
+/// `POST /oauth/token`: client-credentials grant, answered with a short-lived
+/// HS256 bearer token bound to the client's context.
+pub(crate) async fn issue_token(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Form(request): Form<TokenRequest>,
+) -> Response {
+    match token(&state, &headers, request) {
+        Ok(response) => ([(header::CACHE_CONTROL, "no-store")], Json(response)).into_response(),
+        Err(rejection) => rejection.into_response(),
+    }
+}
+
+fn token(
+    state: &AppState,
+    headers: &HeaderMap,
+    request: TokenRequest,
+) -> OAuthResult<TokenResponse> {
+    let config = state.config();
+    let secret = match (&config.auth.jwt_secret, config.auth.mode()) {
+        (Some(secret), AuthMode::Jwt) => secret.clone(),
+        _ => {
+            return Err(oauth_error(
+                StatusCode::BAD_REQUEST,
+                "unsupported_grant_type",
+                "token issuance requires auth.mode = \"jwt\" with auth.jwt_secret",
+            ))
+        }
+    };
+    if request.grant_type != "client_credentials" {
+        return Err(oauth_error(
+            StatusCode::BAD_REQUEST,
+            "unsupported_grant_type",
+            "only client_credentials is supported",
+        ));
+    }
+
+    let (client_id, client_secret) = match basic_credentials(headers) {
+        Some(credentials) => credentials,
+        None => match (request.client_id, request.client_secret) {
+            (Some(id), Some(secret)) => (id, secret),
+            _ => return Err(invalid_client()),
+        },
+    };
+    let client = state
+        .server()
+        .oauth_clients()
+        .authenticate(&client_id, &client_secret)
+        .map_err(|err| {
+            oauth_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "server_error",
+                &err.to_string(),
+            )
+        })?
+        .ok_or_else(invalid_client)?;
+
+    let scopes = match request.scope.as_deref().map(str::split_whitespace) {
+        Some(requested) => requested.map(str::to_string).collect::<Vec<_>>(),
+        None => client.scopes.clone(),
+    };
+    if let Some(extra) = scopes.iter().find(|scope| !client.scopes.contains(scope)) {
+        return Err(oauth_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_scope",
+            &format!("client may not request {}", extra),
+        ));
+    }
+
+    let ttl = config.auth.oauth_token_ttl_secs;
+    let scope = scopes.join(" ");
+    let mut claims = serde_json::to_value(JwtClaims {
+        context_type: client.context_type.as_str().to_string(),
+        context_id: client.context_id.clone(),
+        actor_id: None,
+        scope: scope.clone(),
+        exp: state.clock().timestamp() + ttl as i64,
+    })
+    .map_err(|err| {
+        oauth_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "server_error",
+            &err.to_string(),
+        )
+    })?;
+    // Issued tokens must pass the same iss/aud checks as external ones
+    if let Some(issuer) = &config.auth.jwt_issuer {
+        claims["iss"] = issuer.clone().into();
+    }
+    if let Some(audience) = &config.auth.jwt_audience {
+        claims["aud"] = audience.clone().into();
+    }
+    let access_token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(secret.as_bytes()),
+    )
+    .map_err(|err| {
+        oauth_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "server_error",
+            &err.to_string(),
+        )
+    })?;
+
+    tracing::info!(
+        "Issued token for OAuth client {} ({})",
+        client.client_id,
+        scope
+    );
+    Ok(TokenResponse {
+        access_token,
+        token_type: "Bearer".to_string(),
+        expires_in: ttl,
+        scope,
+    })
+}
+
+/// `Authorization: Basic base64(client_id:client_secret)`.
+fn basic_credentials(headers: &HeaderMap) -> Option<(String, String)> {
+    let encoded = headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Basic ")?;
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+    let (id, secret) = decoded.split_once(':')?;
+    Some((id.to_string(), secret.to_string()))
+}
+
+fn invalid_client() -> (StatusCode, Json<OAuthErrorResponse>) {
+    oauth_error(
+        StatusCode::UNAUTHORIZED,
+        "invalid_client",
+        "unknown client or wrong secret",
+    )
+}
+
+fn oauth_error(
+    status: StatusCode,
+    error: &str,
+    description: &str,
+) -> (StatusCode, Json<OAuthErrorResponse>) {
+    let body = OAuthErrorResponse {
+        error: error.to_string(),
+        error_description: description.to_string(),
+    };
+    (status, Json(body))
+}
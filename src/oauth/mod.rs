@@ -0,0 +1,10 @@
+pub mod dto;
+pub(crate) mod handler;
+pub mod store;
+
+pub use dto::{
+    OAuthClient, OAuthClientCreateRequest, OAuthClientCreated, OAuthClientSummary, TokenRequest,
+    TokenResponse, CLIENT_SCOPES,
+};
+pub(crate) use handler::issue_token;
+pub use store::OAuthClientStore;
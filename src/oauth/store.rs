@@ -0,0 +1,112 @@
+use sha2::{Digest, Sha256};
+
+use super::dto::OAuthClient;
+use crate::auth::constant_time_eq;
+use crate::clock::SharedClock;
+use crate::error::Result;
+use crate::plugins::RequestContext;
+use crate::storage::{KvStore, MemoryKv};
+
+/// Client-credentials clients issued to plugin developers.
+pub struct OAuthClientStore {
+    store: Box<dyn KvStore>,
+    clock: SharedClock,
+}
+
+impl OAuthClientStore {
+    pub fn in_memory() -> Self {
+        Self::with_store(Box::new(MemoryKv::default()))
+    }
+
+    /// Stores JSON-encoded clients keyed by client id.
+    pub fn persistent(tree: sled::Tree) -> Self {
+        Self::with_store(Box::new(tree))
+    }
+
+    fn with_store(store: Box<dyn KvStore>) -> Self {
+        Self {
+            store,
+            clock: SharedClock::default(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Creates a client for `context` and returns it with its one-time secret.
+    pub fn create(
+        &self,
+        context: &RequestContext,
+        scopes: Vec<String>,
+    ) -> Result<(OAuthClient, String)> {
+        let secret = format!(
+            "{}{}",
+            uuid::Uuid::new_v4().simple(),
+            uuid::Uuid::new_v4().simple()
+        );
+        let client = OAuthClient {
+            client_id: format!("nova-{}", uuid::Uuid::new_v4().simple()),
+            secret_hash: hash_secret(&secret),
+            context_type: context.context_type.clone(),
+            context_id: context.context_id.clone(),
+            scopes,
+            created_at: self.clock.timestamp(),
+        };
+        self.store
+            .insert_json(client.client_id.as_bytes(), &client)?;
+        Ok((client, secret))
+    }
+
+    pub fn get(&self, client_id: &str) -> Result<Option<OAuthClient>> {
+        self.store.get_json(client_id.as_bytes())
+    }
+
+    pub fn list(&self) -> Result<Vec<OAuthClient>> {
+        let mut clients: Vec<OAuthClient> = self.store.values_json(b"")?;
+        clients.sort_by_key(|client: &OAuthClient| client.created_at);
+        Ok(clients)
+    }
+
+    /// Returns whether the client existed.
+    pub fn remove(&self, client_id: &str) -> Result<bool> {
+        Ok(self.store.remove(client_id.as_bytes())?.is_some())
+    }
+
+    /// Drops every client bound to `context`; returns how many there were.
+    pub fn remove_for_context(&self, context: &RequestContext) -> Result<usize> {
+        let mut removed = 0;
+        for client in self.list()? {
+            if client.context_type == context.context_type
+                && client.context_id == context.context_id
+            {
+                removed += usize::from(self.remove(&client.client_id)?);
+            }
+        }
+        Ok(removed)
+    }
+
+    /// The client, if `secret` matches the stored hash.
+    pub fn authenticate(&self, client_id: &str, secret: &str) -> Result<Option<OAuthClient>> {
+        Ok(self.get(client_id)?.filter(|client| {
+            constant_time_eq(
+                client.secret_hash.as_bytes(),
+                hash_secret(secret).as_bytes(),
+            )
+        }))
+    }
+}
+
+impl Default for OAuthClientStore {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+fn hash_secret(secret: &str) -> String {
+    Sha256::digest(secret.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
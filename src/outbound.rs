@@ -0,0 +1,68 @@
+use crate::config::OutboundConfig;
+use crate::error::{NovaError, Result};
+
+/// Client builder for calls to `upstream` with proxy and extra root CAs applied.
+///
+/// `upstream` selects an entry in `outbound.upstreams` (e.g. `"geckoterminal"`,
+/// `"plugins"`); unset fields fall back to the global settings.
+pub fn client_builder(cfg: &OutboundConfig, upstream: &str) -> Result<reqwest::ClientBuilder> {
+    let overrides = cfg.upstreams.get(upstream);
+    let mut builder = reqwest::Client::builder();
+
+    let direct = overrides.is_some_and(|o| o.direct);
+    let proxy = overrides
+        .and_then(|o| o.proxy.as_ref())
+        .or(cfg.proxy.as_ref());
+    if direct {
+        builder = builder.no_proxy();
+    } else if let Some(url) = proxy {
+        let mut proxy = reqwest::Proxy::all(url)
+            .map_err(|e| NovaError::config_error(format!("Invalid proxy {}: {}", url, e)))?;
+        if let Some(no_proxy) = cfg.no_proxy.as_deref() {
+            proxy = proxy.no_proxy(reqwest::NoProxy::from_string(no_proxy));
+        }
+        builder = builder.proxy(proxy);
+    }
+
+    let ca_certs = overrides
+        .and_then(|o| o.ca_certs.as_ref())
+        .unwrap_or(&cfg.ca_certs);
+    for path in ca_certs {
+        for cert in load_certificates(path)? {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+    Ok(builder)
+}
+
+/// Builds a client for `upstream` with `configure` applied on top.
+pub fn build_client(
+    cfg: &OutboundConfig,
+    upstream: &str,
+    configure: impl FnOnce(reqwest::ClientBuilder) -> reqwest::ClientBuilder,
+) -> Result<reqwest::Client> {
+    let builder = configure(client_builder(cfg, upstream)?);
+    builder.build().map_err(|e| {
+        NovaError::config_error(format!(
+            "Failed to build HTTP client for {}: {}",
+            upstream, e
+        ))
+    })
+}
+
+/// Every certificate in the PEM file at `path`; a file without one is an error.
+pub(crate) fn load_certificates(path: &str) -> Result<Vec<reqwest::Certificate>> {
+    let pem = std::fs::read(path)
+        .map_err(|e| NovaError::config_error(format!("Failed to read CA file {}: {}", path, e)))?;
+    match reqwest::Certificate::from_pem_bundle(&pem) {
+        Ok(certs) if !certs.is_empty() => Ok(certs),
+        Ok(_) => Err(NovaError::config_error(format!(
+            "Invalid CA file {}: no certificates",
+            path
+        ))),
+        Err(e) => Err(NovaError::config_error(format!(
+            "Invalid CA file {}: {}",
+            path, e
+        ))),
+    }
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+fn default_input_schema() -> Value {
+    json!({ "type": "object" })
+}
+
+fn empty_arguments() -> Value {
+    json!({})
+}
+
+/// A virtual tool: a DAG of existing tool calls, listed and called like any other tool.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineDefinition {
+    pub name: String,
+    pub description: String,
+    #[serde(default = "default_input_schema")]
+    pub input_schema: Value,
+    pub steps: Vec<PipelineStep>,
+    /// Result template over `input`, `steps` and `errors`; defaults to every step's output.
+    #[serde(default)]
+    pub output: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineStep {
+    pub id: String,
+    /// Built-in tool name or plugin FQN.
+    pub tool: String,
+    /// Template over `input` (the pipeline arguments) and `steps.<id>` (earlier outputs).
+    #[serde(default = "empty_arguments")]
+    pub arguments: Value,
+    /// Steps to wait for besides those `arguments` reference.
+    #[serde(default)]
+    pub after: Vec<String>,
+    #[serde(default)]
+    pub on_error: StepErrorPolicy,
+    /// Output recorded for a failed `continue` step.
+    #[serde(default)]
+    pub fallback: Value,
+}
+
+/// What a failing step does to the rest of the pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StepErrorPolicy {
+    /// Abort the pipeline with the step's error.
+    #[default]
+    Fail,
+    /// Record the error and use `fallback` as the step's output.
+    Continue,
+    /// Record the error and skip every step that depends on this one.
+    Skip,
+}
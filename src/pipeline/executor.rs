@@ -0,0 +1,116 @@
+use futures::future::{join_all, BoxFuture};
+use serde_json::{json, Map, Value};
+
+use crate::error::{NovaError, Result};
+
+use super::dto::StepErrorPolicy;
+use super::registry::Pipeline;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StepState {
+    Pending,
+    Done,
+    Skipped,
+}
+
+/// Runs `pipeline` wave by wave: every step whose dependencies have finished
+/// is called concurrently through `call(tool, arguments)`.
+pub async fn execute<'a, F>(pipeline: &Pipeline, input: Value, call: F) -> Result<Value>
+where
+    F: Fn(String, Value) -> BoxFuture<'a, Result<Value>>,
+{
+    let mut states = vec![StepState::Pending; pipeline.steps.len()];
+    let mut outputs = Map::new();
+    let mut errors = Map::new();
+    let mut skipped = Vec::new();
+
+    loop {
+        // Skips cascade: a step waiting on a skipped step is skipped as well
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (index, step) in pipeline.steps.iter().enumerate() {
+                if states[index] == StepState::Pending
+                    && step
+                        .depends_on
+                        .iter()
+                        .any(|&dep| states[dep] == StepState::Skipped)
+                {
+                    states[index] = StepState::Skipped;
+                    skipped.push(Value::String(pipeline.step(index).id.clone()));
+                    changed = true;
+                }
+            }
+        }
+
+        let ready: Vec<usize> = (0..pipeline.steps.len())
+            .filter(|&index| {
+                states[index] == StepState::Pending
+                    && pipeline.steps[index]
+                        .depends_on
+                        .iter()
+                        .all(|&dep| states[dep] == StepState::Done)
+            })
+            .collect();
+        if ready.is_empty() {
+            break;
+        }
+
+        let scope = json!({ "input": input, "steps": outputs });
+        let results = join_all(ready.iter().map(|&index| {
+            let arguments = pipeline.steps[index].arguments.render(&scope);
+            call(pipeline.step(index).tool.clone(), arguments)
+        }))
+        .await;
+
+        for (index, result) in ready.into_iter().zip(results) {
+            let step = pipeline.step(index);
+            match result {
+                Ok(output) => {
+                    outputs.insert(step.id.clone(), output);
+                    states[index] = StepState::Done;
+                }
+                Err(err) => {
+                    if step.on_error == StepErrorPolicy::Fail {
+                        return Err(NovaError::pipeline_step_failed(
+                            pipeline.name(),
+                            &step.id,
+                            &step.tool,
+                            err,
+                        ));
+                    }
+                    tracing::warn!(
+                        "Pipeline {} step {} failed ({:?}): {}",
+                        pipeline.name(),
+                        step.id,
+                        step.on_error,
+                        err
+                    );
+                    errors.insert(step.id.clone(), Value::String(err.to_string()));
+                    if step.on_error == StepErrorPolicy::Continue {
+                        outputs.insert(step.id.clone(), step.fallback.clone());
+                        states[index] = StepState::Done;
+                    } else {
+                        states[index] = StepState::Skipped;
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(output) = &pipeline.output {
+        return Ok(output.render(&json!({
+            "input": input,
+            "steps": outputs,
+            "errors": errors,
+        })));
+    }
+    let mut result = json!({ "steps": outputs });
+    if !errors.is_empty() {
+        result["errors"] = Value::Object(errors);
+    }
+    if !skipped.is_empty() {
+        result["skipped"] = Value::Array(skipped);
+    }
+    Ok(result)
+}
@@ -0,0 +1,7 @@
+pub mod dto;
+pub mod executor;
+pub mod registry;
+
+pub use dto::{PipelineDefinition, PipelineStep, StepErrorPolicy};
+pub use executor::execute;
+pub use registry::{Pipeline, PipelineRegistry};
@@ -0,0 +1,213 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::plugins::RequestTemplate;
+use crate::schema;
+use crate::server::BUILTIN_TOOLS;
+
+use super::dto::{PipelineDefinition, PipelineStep};
+
+const ARGUMENT_ROOTS: &[&str] = &["input", "steps"];
+const OUTPUT_ROOTS: &[&str] = &["input", "steps", "errors"];
+
+/// A checked pipeline: templates parsed and dependencies resolved to step indexes.
+#[derive(Debug)]
+pub struct Pipeline {
+    pub definition: PipelineDefinition,
+    pub(crate) steps: Vec<CompiledStep>,
+    pub(crate) output: Option<RequestTemplate>,
+}
+
+#[derive(Debug)]
+pub(crate) struct CompiledStep {
+    pub(crate) arguments: RequestTemplate,
+    pub(crate) depends_on: Vec<usize>,
+}
+
+impl Pipeline {
+    pub fn name(&self) -> &str {
+        &self.definition.name
+    }
+
+    pub(crate) fn step(&self, index: usize) -> &PipelineStep {
+        &self.definition.steps[index]
+    }
+}
+
+/// Pipelines by name, in definition order.
+#[derive(Debug, Clone, Default)]
+pub struct PipelineRegistry {
+    pipelines: Vec<Arc<Pipeline>>,
+    by_name: HashMap<String, usize>,
+}
+
+impl PipelineRegistry {
+    /// Fails with the first invalid pipeline and why.
+    pub fn new(definitions: Vec<PipelineDefinition>) -> Result<Self, String> {
+        let mut names = HashSet::new();
+        for definition in &definitions {
+            let name = definition.name.as_str();
+            if name.trim().is_empty() {
+                return Err("pipeline names cannot be empty".to_string());
+            }
+            if BUILTIN_TOOLS.contains(&name) {
+                return Err(format!(
+                    "pipeline name {} is taken by a built-in tool",
+                    name
+                ));
+            }
+            if !names.insert(name.to_string()) {
+                return Err(format!("duplicate pipeline name {}", name));
+            }
+        }
+        let mut registry = Self::default();
+        for definition in definitions {
+            let name = definition.name.clone();
+            let pipeline =
+                compile(definition, &names).map_err(|e| format!("pipeline {}: {}", name, e))?;
+            registry.by_name.insert(name, registry.pipelines.len());
+            registry.pipelines.push(Arc::new(pipeline));
+        }
+        Ok(registry)
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<Pipeline>> {
+        self.by_name
+            .get(name)
+            .map(|&index| Arc::clone(&self.pipelines[index]))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.by_name.contains_key(name)
+    }
+
+    pub fn definitions(&self) -> impl Iterator<Item = &PipelineDefinition> {
+        self.pipelines.iter().map(|pipeline| &pipeline.definition)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pipelines.is_empty()
+    }
+}
+
+fn compile(
+    definition: PipelineDefinition,
+    pipelines: &HashSet<String>,
+) -> Result<Pipeline, String> {
+    if definition.description.trim().is_empty() {
+        return Err("description cannot be empty".to_string());
+    }
+    if !definition.input_schema.is_object() {
+        return Err("input_schema must be a JSON object".to_string());
+    }
+    schema::compile(&definition.input_schema)
+        .map_err(|e| format!("input_schema is not a valid JSON schema: {}", e))?;
+    if definition.steps.is_empty() {
+        return Err("needs at least one step".to_string());
+    }
+
+    let mut index_of = HashMap::new();
+    for (index, step) in definition.steps.iter().enumerate() {
+        if step.id.is_empty() || step.id.contains('.') {
+            return Err(format!(
+                "step id \"{}\" must be non-empty without dots",
+                step.id
+            ));
+        }
+        if index_of.insert(step.id.as_str(), index).is_some() {
+            return Err(format!("duplicate step id {}", step.id));
+        }
+    }
+
+    let mut steps = Vec::with_capacity(definition.steps.len());
+    for step in &definition.steps {
+        if step.tool.trim().is_empty() {
+            return Err(format!("step {} has no tool", step.id));
+        }
+        if pipelines.contains(&step.tool) {
+            return Err(format!("step {} cannot call another pipeline", step.id));
+        }
+        let arguments = RequestTemplate::parse_with_roots(&step.arguments, ARGUMENT_ROOTS)
+            .map_err(|e| format!("step {}: {}", step.id, e))?;
+        let mut depends_on = Vec::new();
+        let referenced = step_references(&arguments);
+        for id in referenced
+            .iter()
+            .map(String::as_str)
+            .chain(step.after.iter().map(String::as_str))
+        {
+            let &index = index_of
+                .get(id)
+                .ok_or_else(|| format!("step {} depends on unknown step {}", step.id, id))?;
+            if !depends_on.contains(&index) {
+                depends_on.push(index);
+            }
+        }
+        steps.push(CompiledStep {
+            arguments,
+            depends_on,
+        });
+    }
+    check_acyclic(&definition.steps, &steps)?;
+
+    let output = definition
+        .output
+        .as_ref()
+        .map(|output| {
+            let template = RequestTemplate::parse_with_roots(output, OUTPUT_ROOTS)
+                .map_err(|e| format!("output: {}", e))?;
+            match step_references(&template)
+                .into_iter()
+                .find(|id| !index_of.contains_key(id.as_str()))
+            {
+                Some(id) => Err(format!("output references unknown step {}", id)),
+                None => Ok(template),
+            }
+        })
+        .transpose()?;
+
+    Ok(Pipeline {
+        definition,
+        steps,
+        output,
+    })
+}
+
+/// Step ids named by `steps.<id>...` placeholders.
+fn step_references(template: &RequestTemplate) -> Vec<String> {
+    template
+        .references()
+        .into_iter()
+        .filter_map(|path| {
+            let mut segments = path.split('.');
+            (segments.next() == Some("steps"))
+                .then(|| segments.next().map(str::to_string))
+                .flatten()
+        })
+        .collect()
+}
+
+fn check_acyclic(definitions: &[PipelineStep], steps: &[CompiledStep]) -> Result<(), String> {
+    let mut remaining: Vec<usize> = steps.iter().map(|step| step.depends_on.len()).collect();
+    let mut ready: Vec<usize> = (0..steps.len()).filter(|&i| remaining[i] == 0).collect();
+    let mut visited = 0;
+    while let Some(done) = ready.pop() {
+        visited += 1;
+        for (index, step) in steps.iter().enumerate() {
+            if step.depends_on.contains(&done) {
+                remaining[index] -= 1;
+                if remaining[index] == 0 {
+                    ready.push(index);
+                }
+            }
+        }
+    }
+    if visited == steps.len() {
+        return Ok(());
+    }
+    let cycle = (0..steps.len())
+        .filter(|&i| remaining[i] > 0)
+        .map(|i| definitions[i].id.as_str())
+        .collect::<Vec<_>>();
+    Err(format!("steps form a cycle: {}", cycle.join(", ")))
+}
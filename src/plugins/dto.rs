@@ -1,9 +1,19 @@
 use serde::{Deserialize, Serialize};
 
+use crate::schema::{SchemaChange, SchemaDraft};
+use crate::tools::upstream_health::UpstreamStatus;
+use std::collections::BTreeMap;
+use std::fmt;
+
 const fn default_plugin_version() -> u32 {
     1
 }
 
+// Records stored before revisions were tracked start at the first one
+const fn first_revision() -> u64 {
+    1
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginRegistrationRequest {
     pub name: String,
@@ -13,9 +23,42 @@ pub struct PluginRegistrationRequest {
     pub input_schema: serde_json::Value,
     #[serde(default)]
     pub output_schema: Option<serde_json::Value>,
+    // Empty for scripted plugins
+    #[serde(default)]
     pub endpoint_url: String,
+    /// Runs in Nova instead of calling `endpoint_url`; see [`PluginScript`].
+    #[serde(default)]
+    pub script: Option<PluginScript>,
     #[serde(default = "default_plugin_version")]
     pub version: u32,
+    #[serde(default)]
+    pub trust_level: PluginTrustLevel,
+    /// Presented by Nova when calling the endpoint; `high` trust plugins only.
+    #[serde(default)]
+    pub client_certificate: Option<PluginClientCertificate>,
+    /// Headers and auth added to every call; stored encrypted.
+    #[serde(default)]
+    pub credentials: Option<PluginCredentials>,
+    /// Response redaction rules (`$.path` or field-name patterns).
+    #[serde(default)]
+    pub redact: Vec<String>,
+    /// Body sent to the endpoint instead of the default payload, with
+    /// `{{ arguments.* }}` placeholders.
+    #[serde(default)]
+    pub request_template: Option<serde_json::Value>,
+    /// Marketplace entry; setting one submits the plugin for review.
+    #[serde(default)]
+    pub listing: Option<PluginListing>,
+    /// Context types the plugin may be enabled in; empty allows all.
+    #[serde(default)]
+    pub allowed_contexts: Vec<PluginContextType>,
+    /// Lowercase slugs for grouping and search, e.g. "weather".
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Coerce call arguments to `input_schema` (defaults, `"5"` to `5`)
+    /// before validating them.
+    #[serde(default)]
+    pub coerce_arguments: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -30,6 +73,219 @@ pub struct PluginUpdateRequest {
     pub output_schema: Option<Option<serde_json::Value>>,
     #[serde(default)]
     pub endpoint_url: Option<String>,
+    // Scripted plugins only: the next version's script
+    #[serde(default)]
+    pub script: Option<PluginScript>,
+    #[serde(default)]
+    pub trust_level: Option<PluginTrustLevel>,
+    // `null` removes the certificate, an absent field keeps it
+    #[serde(default)]
+    pub client_certificate: Option<Option<PluginClientCertificate>>,
+    // Replaced as a whole; `null` removes them
+    #[serde(default)]
+    pub credentials: Option<Option<PluginCredentials>>,
+    #[serde(default)]
+    pub redact: Option<Vec<String>>,
+    // `null` removes the template, an absent field keeps it
+    #[serde(default)]
+    pub request_template: Option<Option<serde_json::Value>>,
+    // `null` withdraws the plugin from the marketplace
+    #[serde(default)]
+    pub listing: Option<Option<PluginListing>>,
+    // Narrowing hides the plugin from contexts of the dropped types
+    #[serde(default)]
+    pub allowed_contexts: Option<Vec<PluginContextType>>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub coerce_arguments: Option<bool>,
+    // The `revision` the change was based on; a stale one fails with 409.
+    // Set from `If-Match` on HTTP
+    #[serde(default)]
+    pub revision: Option<u64>,
+    // Acknowledges an `input_schema` change that can break existing callers;
+    // without it such a change fails with 409
+    #[serde(default)]
+    pub breaking: bool,
+}
+
+/// How a plugin appears in `GET /marketplace`. Owners opt in per plugin; the
+/// entry is listed once an admin approves it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct PluginListing {
+    // Lowercase slugs such as "defi" or "analytics"
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub icon_url: Option<String>,
+    // Set through `PUT /admin/marketplace/:plugin_id`; owners cannot set it
+    #[serde(default)]
+    pub approved: bool,
+    // Withdrawn after `plugins.report_threshold` reports, until an admin reviews it
+    #[serde(default)]
+    pub flagged: bool,
+}
+
+/// One plugin in the public catalog. Leaves out the endpoint and the owner
+/// context, which the catalog does not disclose.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketplaceEntry {
+    pub plugin_id: u64,
+    pub name: String,
+    pub description: String,
+    // The owner's free-form `owner_id`, if given
+    #[serde(default)]
+    pub publisher: Option<String>,
+    pub version: u32,
+    pub trust_level: PluginTrustLevel,
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub icon_url: Option<String>,
+    // Contexts other than the owner that have the plugin enabled
+    pub installs: usize,
+    #[serde(default)]
+    pub last_used_at: Option<i64>,
+    #[serde(default)]
+    pub ratings: u64,
+    #[serde(default)]
+    pub average_stars: Option<f64>,
+    pub input_schema: serde_json::Value,
+    pub updated_at: i64,
+}
+
+/// `PUT /marketplace/:plugin_id/rating`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginRatingRequest {
+    pub stars: u8,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+/// `POST /marketplace/:plugin_id/reports`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginReportRequest {
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginRating {
+    pub stars: u8,
+    #[serde(default)]
+    pub comment: Option<String>,
+    pub at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginReport {
+    // `<type>:<id>` of the reporting context
+    pub context: String,
+    pub reason: String,
+    pub at: i64,
+}
+
+/// Public aggregate of a plugin's ratings; `stars` counts ratings per star.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RatingSummary {
+    pub plugin_id: u64,
+    pub ratings: u64,
+    #[serde(default)]
+    pub average_stars: Option<f64>,
+    pub stars: BTreeMap<u8, u64>,
+}
+
+/// `GET /marketplace` filters: one category, and `q` matched against names
+/// and descriptions, case-insensitively.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MarketplaceQuery {
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub q: Option<String>,
+}
+
+/// Static headers and an auth scheme `invoke_plugin` adds to endpoint calls.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct PluginCredentials {
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub auth: Option<PluginAuth>,
+}
+
+impl PluginCredentials {
+    /// Lowercased names of the headers these credentials set.
+    pub fn header_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.headers.keys().map(|h| h.to_lowercase()).collect();
+        match &self.auth {
+            Some(PluginAuth::Bearer { .. } | PluginAuth::Basic { .. }) => {
+                names.push("authorization".to_string())
+            }
+            Some(PluginAuth::ApiKey { header, .. }) => names.push(header.to_lowercase()),
+            None => {}
+        }
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+impl fmt::Debug for PluginCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PluginCredentials")
+            .field("headers", &self.header_names())
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PluginAuth {
+    /// `Authorization: Bearer <token>`
+    Bearer { token: String },
+    /// `Authorization: Basic base64(username:password)`
+    Basic { username: String, password: String },
+    /// `<header>: <value>`, e.g. `x-api-key`
+    ApiKey { header: String, value: String },
+}
+
+/// Client certificate and PKCS#8 private key (PEM) Nova uses for mutual TLS
+/// with one plugin endpoint.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PluginClientCertificate {
+    pub cert_pem: String,
+    pub key_pem: String,
+}
+
+impl fmt::Debug for PluginClientCertificate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PluginClientCertificate")
+            .field("cert_pem", &self.cert_pem)
+            .field("key_pem", &"<redacted>")
+            .finish()
+    }
+}
+
+/// A client certificate as stored, its private key sealed with
+/// `plugins.secrets_key`.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StoredClientCertificate {
+    pub cert_pem: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sealed_key_pem: Option<String>,
+    // Plaintext key of records stored before keys were sealed; sealed as soon
+    // as a secrets key is set and never written to snapshots
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_pem: Option<String>,
+}
+
+impl fmt::Debug for StoredClientCertificate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StoredClientCertificate")
+            .field("cert_pem", &self.cert_pem)
+            .field("sealed_key_pem", &self.sealed_key_pem.is_some())
+            .field("key_pem", &self.key_pem.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -37,12 +293,259 @@ pub struct PluginUpdateRequest {
 pub enum PluginContextType {
     User,
     Group,
+    Channel,
+    Organization,
+}
+
+impl PluginContextType {
+    pub const ALL: [PluginContextType; 4] = [
+        PluginContextType::User,
+        PluginContextType::Group,
+        PluginContextType::Channel,
+        PluginContextType::Organization,
+    ];
+
+    /// Wire name, also used as the fq-name prefix and in per-context keys.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluginContextType::User => "user",
+            PluginContextType::Group => "group",
+            PluginContextType::Channel => "channel",
+            PluginContextType::Organization => "organization",
+        }
+    }
+
+    /// Case-insensitive inverse of [`as_str`](Self::as_str).
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().to_lowercase();
+        Self::ALL.into_iter().find(|ty| ty.as_str() == value)
+    }
+}
+
+impl fmt::Display for PluginContextType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How sensitive a plugin is. `high` marks plugins such as treasury
+/// operations, which deployments can hold to stricter egress rules
+/// (`plugins.allowed_domains`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginTrustLevel {
+    #[default]
+    Standard,
+    High,
+}
+
+impl PluginTrustLevel {
+    pub const ALL: [PluginTrustLevel; 2] = [PluginTrustLevel::Standard, PluginTrustLevel::High];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluginTrustLevel::Standard => "standard",
+            PluginTrustLevel::High => "high",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().to_lowercase();
+        Self::ALL.into_iter().find(|level| level.as_str() == value)
+    }
+}
+
+impl fmt::Display for PluginTrustLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Languages a scripted plugin can be written in.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ScriptLanguage {
+    #[default]
+    Rhai,
+}
+
+/// Source of a scripted plugin, stored with each version and run in a
+/// sandbox by [`plugins::script`](super::script).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PluginScript {
+    #[serde(default)]
+    pub language: ScriptLanguage,
+    pub source: String,
+}
+
+/// Checks `context_id` against the id rules of `context_type`:
+/// - user and group: signed 64-bit integers (Telegram-style, groups negative)
+/// - channel: a negative integer starting with `-100` (Telegram channel/supergroup)
+/// - organization: a lowercase slug of letters, digits and `-`, 2 to 63 chars
+pub fn validate_context_pair(
+    context_type: &PluginContextType,
+    context_id: &str,
+) -> std::result::Result<(), String> {
+    let valid = match context_type {
+        PluginContextType::User | PluginContextType::Group => context_id.parse::<i64>().is_ok(),
+        PluginContextType::Channel => {
+            context_id.starts_with("-100")
+                && context_id.len() > 4
+                && context_id.parse::<i64>().is_ok()
+        }
+        PluginContextType::Organization => {
+            (2..=63).contains(&context_id.len())
+                && context_id
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+                && !context_id.starts_with('-')
+                && !context_id.ends_with('-')
+        }
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(match context_type {
+            PluginContextType::User | PluginContextType::Group => {
+                format!("{} context ids must be numeric", context_type)
+            }
+            PluginContextType::Channel => {
+                "channel context ids must be numeric and start with -100".to_string()
+            }
+            PluginContextType::Organization => {
+                "organization context ids must be lowercase slugs (a-z, 0-9, -)".to_string()
+            }
+        })
+    }
+}
+
+/// How user, group and channel ids are checked (`context.id_format`).
+/// Organization ids are always slugs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContextIdFormat {
+    /// Telegram-style integers, per [`validate_context_pair`].
+    #[default]
+    Numeric,
+    /// Hyphenated UUIDs.
+    Uuid,
+    /// Any platform id up to 128 visible characters, e.g. Slack `T024BE7LD`
+    /// or a Discord `guild:user` snowflake pair.
+    Opaque,
+}
+
+impl ContextIdFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "numeric" => Some(ContextIdFormat::Numeric),
+            "uuid" => Some(ContextIdFormat::Uuid),
+            "opaque" => Some(ContextIdFormat::Opaque),
+            _ => None,
+        }
+    }
+
+    pub fn validate(
+        &self,
+        context_type: &PluginContextType,
+        context_id: &str,
+    ) -> std::result::Result<(), String> {
+        if *self == ContextIdFormat::Numeric || *context_type == PluginContextType::Organization {
+            return validate_context_pair(context_type, context_id);
+        }
+        let valid = match self {
+            ContextIdFormat::Uuid => {
+                context_id.len() == 36 && uuid::Uuid::try_parse(context_id).is_ok()
+            }
+            _ => {
+                (1..=128).contains(&context_id.chars().count())
+                    && !context_id
+                        .chars()
+                        .any(|c| c.is_control() || c.is_whitespace())
+            }
+        };
+        if valid {
+            Ok(())
+        } else if *self == ContextIdFormat::Uuid {
+            Err(format!("{} context ids must be UUIDs", context_type))
+        } else {
+            Err(format!(
+                "{} context ids must be 1-128 characters without whitespace",
+                context_type
+            ))
+        }
+    }
+}
+
+/// Makes a context id safe inside a tool name: ASCII letters, digits and `-`
+/// pass through, every other byte becomes `.XX` (uppercase hex). Numeric ids,
+/// slugs and UUIDs are unchanged, so existing fq-names stay valid.
+pub fn escape_context_id(context_id: &str) -> String {
+    let mut escaped = String::with_capacity(context_id.len());
+    for byte in context_id.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'-' {
+            escaped.push(byte as char);
+        } else {
+            escaped.push_str(&format!(".{:02X}", byte));
+        }
+    }
+    escaped
+}
+
+/// Inverse of [`escape_context_id`]; `None` for malformed escapes.
+pub fn unescape_context_id(escaped: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(escaped.len());
+    let mut input = escaped.bytes();
+    while let Some(byte) = input.next() {
+        if byte == b'.' {
+            let hex = [input.next()?, input.next()?];
+            let hex = std::str::from_utf8(&hex).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct RequestContext {
     pub context_type: PluginContextType,
     pub context_id: String,
+    // User acting inside a shared context (`x-nova-actor-id`); per request only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor_id: Option<String>,
+}
+
+impl RequestContext {
+    /// `<type>:<id>`, used for per-context stores.
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.context_type, self.context_id)
+    }
+
+    /// Who is calling: the member when an actor is known, else the context.
+    /// Keys rate-limit buckets and audit entries.
+    pub fn principal(&self) -> String {
+        match &self.actor_id {
+            Some(actor_id) => format!("{}|user:{}", self.key(), actor_id),
+            None => self.key(),
+        }
+    }
+
+    /// Attaches the acting user. Only shared contexts take an actor, and its id
+    /// follows the user rule of `id_format`.
+    pub fn with_actor(
+        mut self,
+        actor_id: Option<String>,
+        id_format: ContextIdFormat,
+    ) -> std::result::Result<Self, String> {
+        if let Some(actor_id) = &actor_id {
+            if self.context_type == PluginContextType::User {
+                return Err("an actor can only be given for shared contexts".to_string());
+            }
+            id_format.validate(&PluginContextType::User, actor_id)?;
+        }
+        self.actor_id = actor_id;
+        Ok(self)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,6 +563,45 @@ pub struct PluginMetadata {
     #[serde(default)]
     pub output_schema: Option<serde_json::Value>,
     pub endpoint_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<PluginScript>,
+    #[serde(default)]
+    pub trust_level: PluginTrustLevel,
+    // Whether calls present a client certificate; the key never leaves the registry
+    #[serde(default)]
+    pub mutual_tls: bool,
+    // Names of the headers injected from stored credentials; values stay sealed
+    #[serde(default)]
+    pub credential_headers: Vec<String>,
+    #[serde(default)]
+    pub redact: Vec<String>,
+    #[serde(default)]
+    pub request_template: Option<serde_json::Value>,
+    #[serde(default)]
+    pub listing: Option<PluginListing>,
+    // Empty when every context type may enable the plugin
+    #[serde(default)]
+    pub allowed_contexts: Vec<PluginContextType>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub coerce_arguments: bool,
+    // Contexts other than the owner that have the plugin enabled; filled in
+    // on REST responses only, 0 on the tool-call path
+    #[serde(default)]
+    pub installs: usize,
+    // Latest call from any context, when tracked
+    #[serde(default)]
+    pub last_used_at: Option<i64>,
+    // Bumped by every change to the plugin; the `ETag` of its REST responses
+    #[serde(default = "first_revision")]
+    pub revision: u64,
+    // What this version broke in `input_schema` relative to the one before
+    #[serde(default)]
+    pub schema_changes: Vec<SchemaChange>,
+    // The draft `input_schema` declares in `$schema`, draft-07 without one
+    #[serde(default)]
+    pub schema_draft: SchemaDraft,
     pub created_at: i64,
     pub updated_at: i64,
 }
@@ -68,13 +610,105 @@ pub struct PluginMetadata {
 pub struct PluginInvocationRequest {
     #[serde(default)]
     pub arguments: serde_json::Value,
+    // Async calls only: where the finished job is POSTed, signed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginInvocationPayload {
     pub context_type: PluginContextType,
     pub context_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor_id: Option<String>,
     pub arguments: serde_json::Value,
+    // Only on asynchronous calls, which the plugin may answer with `202 Accepted`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job: Option<PluginJobTicket>,
+}
+
+/// What a plugin learns about the async job it is answering: where to post
+/// the result instead of being polled, when `plugins.public_url` is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginJobTicket {
+    pub job_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub callback_url: Option<String>,
+    // Sent back as `x-nova-job-token`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub callback_token: Option<String>,
+}
+
+/// `POST /plugins/:id/call?async=true`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InvocationQuery {
+    #[serde(default, rename = "async")]
+    pub run_async: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    // Stored, not yet sent to the plugin
+    Pending,
+    // Accepted by the plugin, which is polled or calls back
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(self, JobStatus::Succeeded | JobStatus::Failed)
+    }
+}
+
+/// An asynchronous plugin call, as `GET /jobs/:id` and `get_job_status`
+/// report it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginJob {
+    pub job_id: String,
+    pub plugin_id: u64,
+    pub plugin: String,
+    pub status: JobStatus,
+    // The plugin's answer, after output schema validation and redaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    // Why the job failed, shaped like the error body of a synchronous call
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorResponse>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    // Fails with `tool_timeout` when still unfinished at this time
+    pub expires_at: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<JobWebhook>,
+}
+
+/// The caller's webhook for a job and how delivering the outcome went.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobWebhook {
+    pub url: String,
+    pub state: WebhookState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookState {
+    // Waiting for the job to finish, or being delivered
+    Pending,
+    Delivered,
+    // Every attempt failed; see `GET /admin/webhooks/dead-letters`
+    DeadLettered,
+}
+
+/// Body of `POST /jobs/:id/callback`: the result, or why there is none.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JobCallback {
+    #[serde(default)]
+    pub result: Option<serde_json::Value>,
+    #[serde(default)]
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -87,6 +721,23 @@ pub struct PluginEnableRequest {
     pub added_by: Option<String>,
 }
 
+/// One plugin as `GET /plugins/:id` returns it: the metadata, with
+/// `installs` and `last_used_at` filled in, and the endpoint's health.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginDetails {
+    #[serde(flatten)]
+    pub metadata: PluginMetadata,
+    pub health: UpstreamStatus,
+}
+
+/// `GET /plugins/:id/enablement`; the caller's own context when `context`
+/// (`<type>:<id>`) is absent.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EnablementQuery {
+    #[serde(default)]
+    pub context: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginEnablementStatus {
     pub context_type: PluginContextType,
@@ -111,6 +762,7 @@ pub struct UserPluginRecord {
     pub consent_ts: i64,
 }
 
+/// Enablement record for group, channel and organization contexts.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GroupPluginRecord {
     pub enabled: bool,
@@ -127,6 +779,14 @@ pub struct PluginVersionRecord {
     #[serde(default)]
     pub output_schema: Option<serde_json::Value>,
     pub endpoint_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<PluginScript>,
+    #[serde(default)]
+    pub request_template: Option<serde_json::Value>,
+    // Breaking input changes from the previous version, acknowledged with
+    // `breaking: true`
+    #[serde(default)]
+    pub schema_changes: Vec<SchemaChange>,
     pub created_at: i64,
 }
 
@@ -139,7 +799,108 @@ pub struct StoredPluginRecord {
     pub owner_id: Option<String>,
     pub context_type: PluginContextType,
     pub context_id: String,
+    #[serde(default)]
+    pub trust_level: PluginTrustLevel,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_certificate: Option<StoredClientCertificate>,
+    // `PluginCredentials` JSON sealed with `plugins.secrets_key`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sealed_credentials: Option<String>,
+    #[serde(default)]
+    pub credential_headers: Vec<String>,
+    #[serde(default)]
+    pub redact: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listing: Option<PluginListing>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_contexts: Vec<PluginContextType>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub coerce_arguments: bool,
+    #[serde(default = "first_revision")]
+    pub revision: u64,
     pub created_at: i64,
     pub updated_at: i64,
     pub versions: Vec<PluginVersionRecord>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RegistryStats {
+    pub plugins: usize,
+    pub versions: usize,
+    pub user_owned: usize,
+    pub group_owned: usize,
+    #[serde(default)]
+    pub channel_owned: usize,
+    #[serde(default)]
+    pub organization_owned: usize,
+    pub user_enablements: usize,
+    pub group_enablements: usize,
+    #[serde(default)]
+    pub channel_enablements: usize,
+    #[serde(default)]
+    pub organization_enablements: usize,
+    /// Plugin counts keyed by owner context type, trust level and status
+    /// (`enabled` for at least one context, else `not_enabled`).
+    #[serde(default)]
+    pub by_context_type: BTreeMap<String, usize>,
+    #[serde(default)]
+    pub by_trust_level: BTreeMap<String, usize>,
+    #[serde(default)]
+    pub by_status: BTreeMap<String, usize>,
+    /// Enablement records across all context types, by their `enabled` flag.
+    #[serde(default)]
+    pub enabled_records: usize,
+    #[serde(default)]
+    pub disabled_records: usize,
+    /// Superseded versions kept for `fq_name` lookups.
+    #[serde(default)]
+    pub archived_versions: usize,
+}
+
+/// Point-in-time copy of the registry and enablement trees, used for backups.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistrySnapshot {
+    pub taken_at: i64,
+    pub plugins: Vec<StoredPluginRecord>,
+    pub user_enablements: std::collections::BTreeMap<String, serde_json::Value>,
+    pub group_enablements: std::collections::BTreeMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub channel_enablements: std::collections::BTreeMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub organization_enablements: std::collections::BTreeMap<String, serde_json::Value>,
+}
+
+/// A registry write that may change some context's `tools/list`, announced
+/// by [`PluginManager::subscribe`](super::PluginManager::subscribe).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryChange {
+    /// A plugin was registered, updated or removed; any context may see it.
+    Plugin(u64),
+    /// A plugin was enabled or disabled for one context.
+    Enablement {
+        context_type: PluginContextType,
+        context_id: String,
+        plugin_id: u64,
+    },
+}
+
+impl RegistryChange {
+    /// Whether a client in `context` may see a different tool list; one
+    /// without a context lists no plugins.
+    pub fn affects(&self, context: Option<&RequestContext>) -> bool {
+        match (self, context) {
+            (_, None) => false,
+            (RegistryChange::Plugin(_), Some(_)) => true,
+            (
+                RegistryChange::Enablement {
+                    context_type,
+                    context_id,
+                    ..
+                },
+                Some(context),
+            ) => *context_type == context.context_type && *context_id == context.context_id,
+        }
+    }
+}
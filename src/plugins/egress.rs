@@ -0,0 +1,302 @@
+//! Where plugin endpoints may point (`[plugins]`): allowed schemes, internal
+//! address ranges, per-trust-level domain allowlists and redirect hops.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use hyper_014::client::connect::dns::Name;
+use ipnet::IpNet;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::{redirect, ClientBuilder, Url};
+
+use crate::config::{OutboundConfig, PluginsConfig};
+use crate::error::{NovaError, Result};
+
+use super::dto::PluginTrustLevel;
+
+/// Loopback, private, link-local (cloud metadata), CGNAT, multicast and
+/// other ranges that never belong to a public plugin host.
+const INTERNAL_NETS: &[&str] = &[
+    "0.0.0.0/8",
+    "10.0.0.0/8",
+    "100.64.0.0/10",
+    "127.0.0.0/8",
+    "169.254.0.0/16",
+    "172.16.0.0/12",
+    "192.0.0.0/24",
+    "192.168.0.0/16",
+    "198.18.0.0/15",
+    "224.0.0.0/4",
+    "240.0.0.0/4",
+    "::/128",
+    "::1/128",
+    "64:ff9b::/96",
+    "fc00::/7",
+    "fe80::/10",
+    "ff00::/8",
+];
+
+#[derive(Debug, Clone)]
+pub struct EgressPolicy {
+    schemes: Vec<String>,
+    blocked: Vec<IpNet>,
+    domains: HashMap<PluginTrustLevel, Vec<String>>,
+    max_redirects: usize,
+    resolve_endpoints: bool,
+    proxy_hosts: Vec<String>,
+}
+
+impl Default for EgressPolicy {
+    fn default() -> Self {
+        Self::new(&PluginsConfig::default())
+    }
+}
+
+impl EgressPolicy {
+    /// Unknown trust levels are rejected by config validation and skipped here.
+    pub fn new(cfg: &PluginsConfig) -> Self {
+        let blocked = if cfg.allow_private_networks {
+            Vec::new()
+        } else {
+            INTERNAL_NETS
+                .iter()
+                .filter_map(|net| net.parse().ok())
+                .collect()
+        };
+        let domains = cfg
+            .allowed_domains
+            .iter()
+            .filter_map(|(level, hosts)| {
+                let hosts = hosts.iter().map(|h| h.trim().to_lowercase()).collect();
+                PluginTrustLevel::parse(level).map(|level| (level, hosts))
+            })
+            .collect();
+        Self {
+            schemes: cfg
+                .allowed_schemes
+                .iter()
+                .map(|s| s.trim().to_lowercase())
+                .collect(),
+            blocked,
+            domains,
+            max_redirects: cfg.max_redirects,
+            resolve_endpoints: cfg.resolve_endpoints,
+            proxy_hosts: Vec::new(),
+        }
+    }
+
+    /// Lets plugin clients reach the `outbound` proxy for `"plugins"` even
+    /// when its name resolves to an internal address; the proxy resolves
+    /// plugin hosts itself.
+    pub fn with_outbound_proxy(mut self, outbound: &OutboundConfig) -> Self {
+        let proxy = match outbound.upstreams.get("plugins") {
+            Some(overrides) if overrides.direct => None,
+            Some(overrides) => overrides.proxy.as_ref().or(outbound.proxy.as_ref()),
+            None => outbound.proxy.as_ref(),
+        };
+        self.proxy_hosts = proxy
+            .and_then(|proxy| Url::parse(proxy).ok())
+            .and_then(|proxy| proxy.host_str().map(str::to_lowercase))
+            .into_iter()
+            .collect();
+        self
+    }
+
+    /// Checks what can be told from the URL alone: scheme, the trust level's
+    /// domain allowlist, and literal IP hosts.
+    pub fn check_url(&self, endpoint: &str, trust_level: PluginTrustLevel) -> Result<Url> {
+        let url = Url::parse(endpoint.trim())
+            .map_err(|e| NovaError::validation_error(format!("Invalid plugin endpoint: {}", e)))?;
+        self.check_parsed(&url, Some(trust_level))?;
+        Ok(url)
+    }
+
+    /// The form an endpoint is stored in: checked as by [`check_url`](Self::check_url),
+    /// without a fragment or trailing slashes on the path. The scheme and
+    /// host are lowercased and a default port dropped while parsing, so
+    /// `HTTPS://Api.Example.com:443/nova/` becomes `https://api.example.com/nova`.
+    pub fn canonical_url(&self, endpoint: &str, trust_level: PluginTrustLevel) -> Result<String> {
+        let mut url = self.check_url(endpoint, trust_level)?;
+        url.set_fragment(None);
+        let path = url.path().trim_end_matches('/').to_string();
+        url.set_path(&path);
+        Ok(url.to_string())
+    }
+
+    /// Resolves the endpoint host and rejects it when any address is internal,
+    /// so public names pointing at private ranges are caught too. Hosts that do
+    /// not resolve are left to the request itself.
+    pub async fn check_resolved(&self, url: &Url) -> Result<()> {
+        self.resolve(url, false).await
+    }
+
+    /// [`check_resolved`](Self::check_resolved) for registrations and updates:
+    /// with `plugins.resolve_endpoints`, a host that does not resolve is
+    /// rejected as well.
+    pub async fn check_registered(&self, url: &Url) -> Result<()> {
+        self.resolve(url, self.resolve_endpoints).await
+    }
+
+    async fn resolve(&self, url: &Url, must_resolve: bool) -> Result<()> {
+        if self.blocked.is_empty() && !must_resolve {
+            return Ok(());
+        }
+        let Some(host) = url.host_str().filter(|host| literal_ip(host).is_none()) else {
+            return Ok(());
+        };
+        let port = url.port_or_known_default().unwrap_or(443);
+        let addrs = match tokio::net::lookup_host((host, port)).await {
+            Ok(addrs) => addrs.collect::<Vec<_>>(),
+            Err(_) => Vec::new(),
+        };
+        if addrs.is_empty() && must_resolve {
+            return Err(NovaError::validation_error(format!(
+                "Plugin endpoint host {} does not resolve",
+                host
+            )));
+        }
+        for addr in addrs {
+            if self.is_blocked(addr.ip()) {
+                return Err(NovaError::validation_error(format!(
+                    "Plugin endpoint host {} resolves to a blocked address",
+                    host
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Scheme and address checks for other fetches made on a plugin's behalf,
+    /// such as remote schema `$ref`s; the domain allowlists do not apply.
+    pub async fn check_fetch(&self, url: &Url) -> Result<()> {
+        self.check_parsed(url, None)?;
+        self.check_resolved(url).await
+    }
+
+    /// Applies the redirect rules to a plugin client: hops must stay on the
+    /// endpoint's host and pass the scheme and address checks. The client
+    /// also resolves hosts through [`FilteringResolver`], so the addresses it
+    /// connects to are the ones checked.
+    pub fn configure(&self, builder: ClientBuilder) -> ClientBuilder {
+        let builder = if self.blocked.is_empty() {
+            builder
+        } else {
+            builder.dns_resolver(Arc::new(FilteringResolver {
+                blocked: self.blocked.clone(),
+                proxy_hosts: self.proxy_hosts.clone(),
+            }))
+        };
+        if self.max_redirects == 0 {
+            return builder.redirect(redirect::Policy::none());
+        }
+        let policy = self.clone();
+        builder.redirect(redirect::Policy::custom(move |attempt| {
+            let same_host = attempt
+                .previous()
+                .first()
+                .is_some_and(|origin| origin.host_str() == attempt.url().host_str());
+            if attempt.previous().len() > policy.max_redirects {
+                attempt.error("too many redirects")
+            } else if !same_host {
+                attempt.error("plugin endpoints may only redirect within their host")
+            } else if let Err(err) = policy.check_parsed(attempt.url(), None) {
+                attempt.error(err.to_string())
+            } else {
+                attempt.follow()
+            }
+        }))
+    }
+
+    fn check_parsed(&self, url: &Url, trust_level: Option<PluginTrustLevel>) -> Result<()> {
+        if !self.schemes.iter().any(|s| s == url.scheme()) {
+            return Err(NovaError::validation_error(format!(
+                "Plugin endpoint must use one of: {}",
+                self.schemes.join(", ")
+            )));
+        }
+        let host = url
+            .host_str()
+            .filter(|host| !host.is_empty())
+            .ok_or_else(|| NovaError::validation_error("Plugin endpoint must include a host"))?
+            .to_lowercase();
+        if let Some(ip) = literal_ip(&host) {
+            if self.is_blocked(ip) {
+                return Err(NovaError::validation_error(
+                    "Plugin endpoint must not point at an internal address",
+                ));
+            }
+        }
+        let Some(level) = trust_level else {
+            return Ok(());
+        };
+        match self.domains.get(&level) {
+            Some(allowed) if !allowed.iter().any(|pattern| host_matches(pattern, &host)) => {
+                Err(NovaError::validation_error(format!(
+                    "Plugin endpoint host {} is not allowed for {} trust plugins",
+                    host, level
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn is_blocked(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.blocked.iter().any(|net| net.contains(&ip))
+    }
+}
+
+/// Resolves a plugin client's hosts and refuses any that has an internal
+/// address. Checking the addresses actually connected to, instead of a
+/// separate lookup beforehand, leaves no room for a DNS answer that changes
+/// in between (DNS rebinding).
+struct FilteringResolver {
+    blocked: Vec<IpNet>,
+    proxy_hosts: Vec<String>,
+}
+
+impl Resolve for FilteringResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_lowercase();
+        let blocked = if self.proxy_hosts.contains(&host) {
+            Vec::new()
+        } else {
+            self.blocked.clone()
+        };
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            let internal = addrs.iter().any(|addr| {
+                let ip = addr.ip().to_canonical();
+                blocked.iter().any(|net| net.contains(&ip))
+            });
+            if internal {
+                return Err(format!(
+                    "Plugin endpoint host {} resolves to a blocked address",
+                    host
+                )
+                .into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// IPv6 hosts keep their brackets in URLs.
+fn literal_ip(host: &str) -> Option<IpAddr> {
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse()
+        .ok()
+}
+
+/// `*.example.com` matches subdomains only; anything else matches exactly.
+pub(crate) fn host_matches(pattern: &str, host: &str) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => host
+            .strip_suffix(suffix)
+            .is_some_and(|rest| rest.ends_with('.') && rest.len() > 1),
+        None => pattern == host,
+    }
+}
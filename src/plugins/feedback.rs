@@ -0,0 +1,188 @@
+use super::dto::{
+    PluginRating, PluginRatingRequest, PluginReport, PluginReportRequest, RatingSummary,
+    RequestContext,
+};
+use crate::clock::SharedClock;
+use crate::error::{NovaError, Result};
+use crate::storage::{KvStore, MemoryKv};
+
+/// Longest comment or report text kept, in characters.
+pub const MAX_FEEDBACK_TEXT: usize = 500;
+
+/// Ratings and abuse reports on marketplace plugins, one of each per context
+/// and plugin; submitting again replaces the earlier one.
+///
+/// Keyed `rating|<plugin_id>|<type>:<id>` and `report|<plugin_id>|<type>:<id>`,
+/// with the plugin id zero-padded so one plugin's entries are one prefix scan.
+pub struct FeedbackStore {
+    store: Box<dyn KvStore>,
+    clock: SharedClock,
+}
+
+impl FeedbackStore {
+    pub fn in_memory() -> Self {
+        Self::with_store(Box::new(MemoryKv::default()))
+    }
+
+    pub fn persistent(tree: sled::Tree) -> Self {
+        Self::with_store(Box::new(tree))
+    }
+
+    fn with_store(store: Box<dyn KvStore>) -> Self {
+        Self {
+            store,
+            clock: SharedClock::default(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn rate(
+        &self,
+        plugin_id: u64,
+        context: &RequestContext,
+        request: PluginRatingRequest,
+    ) -> Result<()> {
+        if !(1..=5).contains(&request.stars) {
+            return Err(NovaError::validation_error("stars must be between 1 and 5"));
+        }
+        check_text("comment", request.comment.as_deref().unwrap_or_default())?;
+        let rating = PluginRating {
+            stars: request.stars,
+            comment: request.comment,
+            at: self.clock.timestamp(),
+        };
+        self.put(
+            &entry_key("rating", plugin_id, context),
+            serde_json::to_vec(&rating)?,
+        )
+    }
+
+    /// Stores the report and returns how many contexts have reported the plugin.
+    pub fn report(
+        &self,
+        plugin_id: u64,
+        context: &RequestContext,
+        request: PluginReportRequest,
+    ) -> Result<usize> {
+        if request.reason.trim().is_empty() {
+            return Err(NovaError::validation_error("reason cannot be empty"));
+        }
+        check_text("reason", &request.reason)?;
+        let report = PluginReport {
+            context: context.key(),
+            reason: request.reason,
+            at: self.clock.timestamp(),
+        };
+        self.put(
+            &entry_key("report", plugin_id, context),
+            serde_json::to_vec(&report)?,
+        )?;
+        Ok(self.scan(&plugin_prefix("report", plugin_id))?.len())
+    }
+
+    pub fn ratings(&self, plugin_id: u64) -> Result<RatingSummary> {
+        let mut summary = RatingSummary {
+            plugin_id,
+            ratings: 0,
+            average_stars: None,
+            stars: (1..=5).map(|stars| (stars, 0)).collect(),
+        };
+        let mut total = 0u64;
+        for (_, bytes) in self.scan(&plugin_prefix("rating", plugin_id))? {
+            let rating: PluginRating = serde_json::from_slice(&bytes)?;
+            summary.ratings += 1;
+            total += u64::from(rating.stars);
+            *summary.stars.entry(rating.stars).or_default() += 1;
+        }
+        if summary.ratings > 0 {
+            let average = total as f64 / summary.ratings as f64;
+            summary.average_stars = Some((average * 100.0).round() / 100.0);
+        }
+        Ok(summary)
+    }
+
+    /// Reports on the plugin, oldest first.
+    pub fn reports(&self, plugin_id: u64) -> Result<Vec<PluginReport>> {
+        let mut reports = self
+            .scan(&plugin_prefix("report", plugin_id))?
+            .into_iter()
+            .map(|(_, bytes)| Ok(serde_json::from_slice::<PluginReport>(&bytes)?))
+            .collect::<Result<Vec<_>>>()?;
+        reports.sort_by_key(|report| report.at);
+        Ok(reports)
+    }
+
+    /// Drops the plugin's reports once an admin has dealt with them.
+    pub fn clear_reports(&self, plugin_id: u64) -> Result<usize> {
+        self.remove_all(&plugin_prefix("report", plugin_id))
+    }
+
+    /// Removes the ratings and reports on an unregistered plugin.
+    pub fn remove_plugin(&self, plugin_id: u64) -> Result<usize> {
+        Ok(
+            self.remove_all(&plugin_prefix("rating", plugin_id))?
+                + self.clear_reports(plugin_id)?,
+        )
+    }
+
+    /// Removes the ratings and reports the context submitted.
+    pub fn remove_context(&self, context: &RequestContext) -> Result<usize> {
+        let owner = context.key();
+        let mut removed = 0;
+        for (key, _) in self.scan("")? {
+            if key.splitn(3, '|').nth(2) == Some(owner.as_str()) {
+                self.delete(&key)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn put(&self, key: &str, value: Vec<u8>) -> Result<()> {
+        self.store.insert(key.as_bytes(), value).map(drop)
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        self.store.remove(key.as_bytes()).map(drop)
+    }
+
+    fn remove_all(&self, prefix: &str) -> Result<usize> {
+        let keys = self.scan(prefix)?;
+        for (key, _) in &keys {
+            self.delete(key)?;
+        }
+        Ok(keys.len())
+    }
+
+    fn scan(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        self.store.scan_str(prefix)
+    }
+}
+
+impl Default for FeedbackStore {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+fn check_text(field: &str, text: &str) -> Result<()> {
+    if text.chars().count() > MAX_FEEDBACK_TEXT {
+        return Err(NovaError::validation_error(format!(
+            "{} must be at most {} characters",
+            field, MAX_FEEDBACK_TEXT
+        )));
+    }
+    Ok(())
+}
+
+fn plugin_prefix(kind: &str, plugin_id: u64) -> String {
+    format!("{}|{:020}|", kind, plugin_id)
+}
+
+fn entry_key(kind: &str, plugin_id: u64, context: &RequestContext) -> String {
+    format!("{}{}", plugin_prefix(kind, plugin_id), context.key())
+}
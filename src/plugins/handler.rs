@@ -1,26 +1,95 @@
 use axum::{
-    extract::{Path, State},
-    http::HeaderMap,
-    http::StatusCode,
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 
-use crate::http::AppState;
+use crate::audit::AuditEvent;
+use crate::auth::{SCOPE_PLUGINS_READ, SCOPE_PLUGINS_WRITE, SCOPE_TOOLS};
+use crate::error::NovaError;
+use crate::http::{error_response, ApiJson, ApiPath, ApiQuery, AppState};
 
 use super::dto::{
-    ErrorResponse, PluginEnableRequest, PluginEnablementStatus, PluginInvocationRequest,
-    PluginMetadata, PluginRegistrationRequest, PluginUpdateRequest, RequestContext,
+    EnablementQuery, ErrorResponse, InvocationQuery, JobCallback, MarketplaceEntry,
+    MarketplaceQuery, PluginDetails, PluginEnableRequest, PluginEnablementStatus,
+    PluginInvocationRequest, PluginJob, PluginMetadata, PluginRatingRequest,
+    PluginRegistrationRequest, PluginReportRequest, PluginUpdateRequest, RatingSummary,
+    RequestContext,
 };
-use super::helpers::{authorize_request, map_error};
+use super::helpers::{
+    authorize_caller, authorize_request, ensure_own_context, has_admin_token, map_error,
+};
+use super::manifest::{ManifestFormat, PluginManifest};
+
+/// Carries the callback token of `POST /jobs/:id/callback`.
+pub const JOB_TOKEN_HEADER: &str = "x-nova-job-token";
 
+/// Takes the body as plain JSON so every problem in it, down to unknown enum
+/// values, is reported field by field.
 pub(crate) async fn register_plugin(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Json(request): Json<PluginRegistrationRequest>,
+    ApiJson(body): ApiJson<serde_json::Value>,
+) -> Result<(StatusCode, Json<PluginMetadata>), (StatusCode, Json<ErrorResponse>)> {
+    let request = state
+        .plugin_manager()
+        .parse_registration(body)
+        .await
+        .map_err(map_error)?;
+    register(&state, &headers, request).await
+}
+
+/// Registers a `plugin.toml` or `plugin.json` body; see [`super::manifest`].
+pub(crate) async fn register_manifest(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<(StatusCode, Json<PluginMetadata>), (StatusCode, Json<ErrorResponse>)> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+    let format = ManifestFormat::detect(content_type, &body);
+    let manifest = PluginManifest::parse(&body, format).map_err(map_error)?;
+    let mut request = manifest.into_registration();
+    state
+        .plugin_manager()
+        .bundle_registration(&mut request)
+        .await
+        .map_err(map_error)?;
+    register(&state, &headers, request).await
+}
+
+async fn register(
+    state: &AppState,
+    headers: &HeaderMap,
+    request: PluginRegistrationRequest,
 ) -> Result<(StatusCode, Json<PluginMetadata>), (StatusCode, Json<ErrorResponse>)> {
-    let context = authorize_request(&state, &headers).await?;
+    let (context, key) = authorize_caller(state, headers, SCOPE_PLUGINS_WRITE).await?;
+    // All field problems at once, before the endpoint's host is resolved
+    state
+        .plugin_manager()
+        .validate_registration(&request)
+        .map_err(map_error)?;
+    if request.script.is_none() {
+        state
+            .plugin_manager()
+            .check_endpoint(&request.endpoint_url, request.trust_level)
+            .await
+            .map_err(map_error)?;
+    }
     match state.plugin_manager().register_plugin(&context, request) {
-        Ok(metadata) => Ok((StatusCode::CREATED, Json(metadata))),
+        Ok(metadata) => {
+            state.server().audit().record_or_warn(AuditEvent {
+                who: context.principal(),
+                api_key: key.id().map(str::to_string),
+                action: "plugin.register",
+                target: metadata.plugin_id.to_string(),
+                before: None,
+                after: serde_json::to_value(&metadata).ok(),
+            });
+            Ok((StatusCode::CREATED, Json(metadata)))
+        }
         Err(err) => Err(map_error(err)),
     }
 }
@@ -28,73 +97,490 @@ pub(crate) async fn register_plugin(
 pub(crate) async fn unregister_plugin(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Path(plugin_id): Path<u64>,
+    ApiPath(plugin_id): ApiPath<u64>,
 ) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
-    let context = authorize_request(&state, &headers).await?;
+    let (context, key) = authorize_caller(&state, &headers, SCOPE_PLUGINS_WRITE).await?;
+    let before = state.plugin_manager().get_plugin(plugin_id).ok();
     match state
         .plugin_manager()
         .unregister_plugin(&context, plugin_id)
     {
-        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Ok(()) => {
+            if let Err(e) = state.server().plugin_feedback().remove_plugin(plugin_id) {
+                tracing::warn!("Failed to remove feedback on plugin {}: {}", plugin_id, e);
+            }
+            state.server().audit().record_or_warn(AuditEvent {
+                who: context.principal(),
+                api_key: key.id().map(str::to_string),
+                action: "plugin.unregister",
+                target: plugin_id.to_string(),
+                before: before.and_then(|metadata| serde_json::to_value(metadata).ok()),
+                after: None,
+            });
+            Ok(StatusCode::NO_CONTENT)
+        }
         Err(err) => Err(map_error(err)),
     }
 }
 
+/// `PATCH` (or `PUT`) `/plugins/:id`: fields left out keep their value. With
+/// `If-Match` or `revision`, the change only applies to that revision.
 pub(crate) async fn update_plugin(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Path(plugin_id): Path<u64>,
-    Json(request): Json<PluginUpdateRequest>,
-) -> Result<Json<PluginMetadata>, (StatusCode, Json<ErrorResponse>)> {
-    let context = authorize_request(&state, &headers).await?;
+    ApiPath(plugin_id): ApiPath<u64>,
+    ApiJson(mut request): ApiJson<PluginUpdateRequest>,
+) -> Result<Tagged<PluginMetadata>, (StatusCode, Json<ErrorResponse>)> {
+    let (context, key) = authorize_caller(&state, &headers, SCOPE_PLUGINS_WRITE).await?;
+    if let Some(revision) = if_match(&headers).map_err(map_error)? {
+        if request.revision.is_some_and(|body| body != revision) {
+            return Err(map_error(NovaError::validation_error(
+                "If-Match and revision name different revisions",
+            )));
+        }
+        request.revision = Some(revision);
+    }
+    let before = state.plugin_manager().get_plugin(plugin_id).ok();
+    if before.is_some() {
+        state
+            .plugin_manager()
+            .bundle_update(&mut request)
+            .await
+            .map_err(map_error)?;
+    }
+    if let (Some(endpoint), Some(current)) = (&request.endpoint_url, &before) {
+        let trust_level = request.trust_level.unwrap_or(current.trust_level);
+        state
+            .plugin_manager()
+            .check_endpoint(endpoint, trust_level)
+            .await
+            .map_err(map_error)?;
+    }
     match state
         .plugin_manager()
         .update_plugin(&context, plugin_id, request)
     {
-        Ok(metadata) => Ok(Json(metadata)),
+        Ok(mut metadata) => {
+            state
+                .plugin_manager()
+                .annotate_usage(std::slice::from_mut(&mut metadata));
+            state.server().audit().record_or_warn(AuditEvent {
+                who: context.principal(),
+                api_key: key.id().map(str::to_string),
+                action: "plugin.update",
+                target: plugin_id.to_string(),
+                before: before.and_then(|metadata| serde_json::to_value(metadata).ok()),
+                after: serde_json::to_value(&metadata).ok(),
+            });
+            Ok(tagged(metadata.revision, metadata))
+        }
+        Err(err @ NovaError::RevisionConflict { .. }) => {
+            // The caller needs the current state to redo its change
+            let (status, Json(mut body)) = map_error(err);
+            if let (Some(details), Ok(current)) = (
+                body.details.as_mut(),
+                state.plugin_manager().get_plugin(plugin_id),
+            ) {
+                details["details"]["plugin"] = serde_json::to_value(current).unwrap_or_default();
+            }
+            Err((status, Json(body)))
+        }
         Err(err) => Err(map_error(err)),
     }
 }
 
+/// A JSON body with the resource's revision as `ETag`.
+type Tagged<T> = ([(header::HeaderName, String); 1], Json<T>);
+
+fn tagged<T>(revision: u64, body: T) -> Tagged<T> {
+    ([(header::ETAG, format!("\"{}\"", revision))], Json(body))
+}
+
+/// The revision named by `If-Match` (`"3"`, `W/"3"` or `3`); `None` without
+/// the header or for `*`.
+fn if_match(headers: &HeaderMap) -> Result<Option<u64>, NovaError> {
+    let Some(value) = headers.get(header::IF_MATCH) else {
+        return Ok(None);
+    };
+    let value = value.to_str().unwrap_or_default().trim();
+    if value == "*" {
+        return Ok(None);
+    }
+    let tag = value.strip_prefix("W/").unwrap_or(value).trim_matches('"');
+    tag.parse().map(Some).map_err(|_| {
+        NovaError::validation_error(format!(
+            "If-Match must name one plugin revision, e.g. \"3\", got {}",
+            value
+        ))
+    })
+}
+
 pub(crate) async fn list_plugins(
     State(state): State<AppState>,
     headers: HeaderMap,
 ) -> Result<Json<Vec<PluginMetadata>>, (StatusCode, Json<ErrorResponse>)> {
-    let context = authorize_request(&state, &headers).await?;
+    let context = authorize_request(&state, &headers, SCOPE_PLUGINS_READ).await?;
     match state.plugin_manager().list_plugins_for_context(&context) {
-        Ok(list) => Ok(Json(list)),
+        Ok(mut list) => {
+            state.plugin_manager().annotate_usage(&mut list);
+            Ok(Json(list))
+        }
         Err(err) => Err(map_error(err)),
     }
 }
 
+/// One plugin the caller owns or has enabled; others are 404.
+pub(crate) async fn get_plugin(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiPath(plugin_id): ApiPath<u64>,
+) -> Result<Tagged<PluginDetails>, (StatusCode, Json<ErrorResponse>)> {
+    let context = authorize_request(&state, &headers, SCOPE_PLUGINS_READ).await?;
+    let manager = state.plugin_manager();
+    let mut metadata = manager
+        .get_plugin_for_context(&context, plugin_id)
+        .map_err(map_error)?;
+    manager.annotate_usage(std::slice::from_mut(&mut metadata));
+    let health = manager.plugin_health(&metadata);
+    Ok(tagged(
+        metadata.revision,
+        PluginDetails { metadata, health },
+    ))
+}
+
+/// The enablement record for the caller's context, or for another one named
+/// by `?context=<type>:<id>` when the request carries the admin token.
+pub(crate) async fn get_plugin_enablement(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiPath(plugin_id): ApiPath<u64>,
+    ApiQuery(query): ApiQuery<EnablementQuery>,
+) -> Result<Json<PluginEnablementStatus>, (StatusCode, Json<ErrorResponse>)> {
+    let context = authorize_request(&state, &headers, SCOPE_PLUGINS_READ).await?;
+    let target = match query.context.as_deref() {
+        Some(value) => crate::client::parse_context(value)
+            .map_err(|e| map_error(NovaError::validation_error(e.to_string())))?,
+        None => context.clone(),
+    };
+    if !has_admin_token(&state, &headers) {
+        ensure_own_context(&context, &target.context_type, &target.context_id)?;
+    }
+    state
+        .plugin_manager()
+        .enablement(plugin_id, target.context_type, &target.context_id)
+        .map(Json)
+        .map_err(map_error)
+}
+
+/// With `?async=true`, answers `202 Accepted` with the job and a `Location`
+/// of `/jobs/:id` instead of waiting for the plugin, and posts the outcome to
+/// `webhook_url` when one is given.
 pub(crate) async fn invoke_plugin(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Path(plugin_id): Path<u64>,
-    Json(request): Json<PluginInvocationRequest>,
-) -> Result<Json<serde_json::Value>, (StatusCode, Json<ErrorResponse>)> {
-    let context = authorize_request(&state, &headers).await?;
+    ApiPath(plugin_id): ApiPath<u64>,
+    ApiQuery(query): ApiQuery<InvocationQuery>,
+    ApiJson(request): ApiJson<PluginInvocationRequest>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)> {
+    let context = authorize_request(&state, &headers, SCOPE_TOOLS).await?;
     let manager = state.plugin_manager_arc();
-    match manager.get_plugin(plugin_id) {
-        Ok(metadata) => match manager
-            .invoke_plugin(&metadata, &context, request.arguments)
-            .await
-        {
-            Ok(value) => Ok(Json(value)),
-            Err(err) => Err(map_error(err)),
-        },
+    let metadata = manager.get_plugin(plugin_id).map_err(map_error)?;
+    if request.webhook_url.is_some() && !query.run_async {
+        return Err(map_error(NovaError::validation_error(
+            "webhook_url needs ?async=true",
+        )));
+    }
+    let quotas = &state.config().quotas;
+    let server = state.server();
+    server
+        .quotas()
+        .consume(&context, None, quotas)
+        .and_then(|_| {
+            server
+                .quotas()
+                .consume(&context, Some(&metadata.fq_name), quotas)
+        })
+        .map_err(map_error)?;
+    if query.run_async {
+        let job = server
+            .plugin_jobs()
+            .submit(
+                &manager,
+                &metadata,
+                &context,
+                request.arguments,
+                request.webhook_url,
+            )
+            .map_err(map_error)?;
+        let location = format!("{}/jobs/{}", crate::http::API_PREFIX, job.job_id);
+        return Ok((
+            StatusCode::ACCEPTED,
+            [(header::LOCATION, location)],
+            Json(job),
+        )
+            .into_response());
+    }
+    match manager
+        .invoke_plugin(&metadata, &context, request.arguments)
+        .await
+    {
+        Ok(value) => Ok(Json(value).into_response()),
         Err(err) => Err(map_error(err)),
     }
 }
 
+/// An async call's status, for the context that started it.
+pub(crate) async fn get_job(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiPath(job_id): ApiPath<String>,
+) -> Result<Json<PluginJob>, (StatusCode, Json<ErrorResponse>)> {
+    let context = authorize_request(&state, &headers, SCOPE_TOOLS).await?;
+    state
+        .server()
+        .plugin_jobs()
+        .get(&job_id, &context)
+        .map(Json)
+        .map_err(map_error)
+}
+
+/// Where a plugin posts an async call's outcome. It needs no API key: the
+/// job's callback token, sent as `x-nova-job-token`, stands in for one.
+pub(crate) async fn job_callback(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiPath(job_id): ApiPath<String>,
+    ApiJson(callback): ApiJson<JobCallback>,
+) -> Result<Json<PluginJob>, (StatusCode, Json<ErrorResponse>)> {
+    let token = headers
+        .get(JOB_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    state
+        .server()
+        .plugin_jobs()
+        .complete(state.plugin_manager(), &job_id, token, callback)
+        .map(Json)
+        .map_err(map_error)
+}
+
 pub(crate) async fn set_plugin_enablement(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Json(request): Json<PluginEnableRequest>,
+    ApiJson(request): ApiJson<PluginEnableRequest>,
+) -> Result<Json<PluginEnablementStatus>, (StatusCode, Json<ErrorResponse>)> {
+    let (context, key) = authorize_caller(&state, &headers, SCOPE_PLUGINS_WRITE).await?;
+    ensure_own_context(&context, &request.context_type, &request.context_id)?;
+    let manager = state.plugin_manager();
+    let was_enabled = manager
+        .is_enabled(
+            request.plugin_id,
+            request.context_type.clone(),
+            &request.context_id,
+        )
+        .unwrap_or(false);
+    match manager.set_enablement(request) {
+        Ok(status) => {
+            state.server().audit().record_or_warn(AuditEvent {
+                who: context.principal(),
+                api_key: key.id().map(str::to_string),
+                action: "plugin.enablement",
+                target: format!(
+                    "{}:{}/{}",
+                    status.context_type, status.context_id, status.plugin_id
+                ),
+                before: Some(serde_json::json!({ "enabled": was_enabled })),
+                after: serde_json::to_value(&status).ok(),
+            });
+            Ok(Json(status))
+        }
+        Err(err) => Err(map_error(err)),
+    }
+}
+
+/// The public catalog; needs no API key.
+pub(crate) async fn list_marketplace(
+    State(state): State<AppState>,
+    ApiQuery(query): ApiQuery<MarketplaceQuery>,
+) -> Result<Json<Vec<MarketplaceEntry>>, (StatusCode, Json<ErrorResponse>)> {
+    ensure_marketplace(&state)?;
+    let mut entries = state
+        .plugin_manager()
+        .marketplace(&query)
+        .map_err(map_error)?;
+    let server = state.server();
+    let feedback = server.plugin_feedback();
+    for entry in &mut entries {
+        let summary = feedback.ratings(entry.plugin_id).map_err(map_error)?;
+        entry.ratings = summary.ratings;
+        entry.average_stars = summary.average_stars;
+    }
+    Ok(Json(entries))
+}
+
+/// Rating totals of an approved listing; needs no API key.
+pub(crate) async fn plugin_ratings(
+    State(state): State<AppState>,
+    ApiPath(plugin_id): ApiPath<u64>,
+) -> Result<Json<RatingSummary>, (StatusCode, Json<ErrorResponse>)> {
+    ensure_marketplace(&state)?;
+    let metadata = state
+        .plugin_manager()
+        .get_plugin(plugin_id)
+        .map_err(map_error)?;
+    if !metadata.listing.is_some_and(|listing| listing.approved) {
+        return Err(map_error(NovaError::plugin_not_found(plugin_id)));
+    }
+    match state.server().plugin_feedback().ratings(plugin_id) {
+        Ok(summary) => Ok(Json(summary)),
+        Err(err) => Err(map_error(err)),
+    }
+}
+
+pub(crate) async fn rate_plugin(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiPath(plugin_id): ApiPath<u64>,
+    ApiJson(request): ApiJson<PluginRatingRequest>,
+) -> Result<Json<RatingSummary>, (StatusCode, Json<ErrorResponse>)> {
+    ensure_marketplace(&state)?;
+    let context = authorize_request(&state, &headers, SCOPE_PLUGINS_WRITE).await?;
+    feedback_target(&state, plugin_id, &context, true)?;
+    let server = state.server();
+    let feedback = server.plugin_feedback();
+    feedback
+        .rate(plugin_id, &context, request)
+        .and_then(|_| feedback.ratings(plugin_id))
+        .map(Json)
+        .map_err(map_error)
+}
+
+/// Records an abuse report. Reaching `plugins.report_threshold` reports
+/// withdraws the listing until an admin reviews it.
+pub(crate) async fn report_plugin(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiPath(plugin_id): ApiPath<u64>,
+    ApiJson(request): ApiJson<PluginReportRequest>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    ensure_marketplace(&state)?;
+    // Enough reports withdraw the listing, which a read-only registry cannot do
+    if state.plugin_manager().is_read_only() {
+        return Err(map_error(NovaError::ReadOnly));
+    }
+    let (context, key) = authorize_caller(&state, &headers, SCOPE_PLUGINS_WRITE).await?;
+    feedback_target(&state, plugin_id, &context, false)?;
+    let reason = request.reason.clone();
+    let server = state.server();
+    let reports = server
+        .plugin_feedback()
+        .report(plugin_id, &context, request)
+        .map_err(map_error)?;
+    let audit = server.audit();
+    audit.record_or_warn(AuditEvent {
+        who: context.principal(),
+        api_key: key.id().map(str::to_string),
+        action: "plugin.report",
+        target: plugin_id.to_string(),
+        before: None,
+        after: Some(serde_json::json!({ "reason": reason })),
+    });
+
+    let threshold = state.config().plugins.report_threshold;
+    if threshold > 0 && reports >= threshold {
+        let flagged = state
+            .plugin_manager()
+            .flag_listing(plugin_id)
+            .map_err(map_error)?;
+        if flagged {
+            tracing::warn!(
+                "Plugin {} withdrawn from the marketplace after {} reports",
+                plugin_id,
+                reports
+            );
+            audit.record_or_warn(AuditEvent {
+                who: "system:reports".to_string(),
+                api_key: None,
+                action: "marketplace.flag",
+                target: plugin_id.to_string(),
+                before: Some(serde_json::json!({ "approved": true })),
+                after: Some(
+                    serde_json::json!({ "approved": false, "flagged": true, "reports": reports }),
+                ),
+            });
+        }
+    }
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// Checks that `context` may give feedback on the plugin: it is listed (or
+/// flagged), not the context's own, and for ratings enabled for the context.
+fn feedback_target(
+    state: &AppState,
+    plugin_id: u64,
+    context: &RequestContext,
+    needs_install: bool,
+) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    let manager = state.plugin_manager();
+    let metadata = manager.get_plugin(plugin_id).map_err(map_error)?;
+    let listed = metadata
+        .listing
+        .as_ref()
+        .is_some_and(|listing| listing.approved || listing.flagged);
+    if !listed {
+        return Err(map_error(NovaError::plugin_not_found(plugin_id)));
+    }
+    if metadata.context_type == context.context_type && metadata.context_id == context.context_id {
+        return Err(map_error(NovaError::validation_error(
+            "Owners cannot rate or report their own plugin",
+        )));
+    }
+    if needs_install
+        && !manager
+            .is_enabled(plugin_id, context.context_type.clone(), &context.context_id)
+            .map_err(map_error)?
+    {
+        return Err(map_error(NovaError::plugin_not_enabled(
+            plugin_id,
+            context.context_type.to_string(),
+            context.context_id.clone(),
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) async fn install_listed_plugin(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiPath(plugin_id): ApiPath<u64>,
 ) -> Result<Json<PluginEnablementStatus>, (StatusCode, Json<ErrorResponse>)> {
-    let _context: RequestContext = authorize_request(&state, &headers).await?;
-    match state.plugin_manager().set_enablement(request) {
-        Ok(status) => Ok(Json(status)),
+    ensure_marketplace(&state)?;
+    let (context, key) = authorize_caller(&state, &headers, SCOPE_PLUGINS_WRITE).await?;
+    match state.plugin_manager().install_listed(plugin_id, &context) {
+        Ok(status) => {
+            state.server().audit().record_or_warn(AuditEvent {
+                who: context.principal(),
+                api_key: key.id().map(str::to_string),
+                action: "plugin.install",
+                target: format!(
+                    "{}:{}/{}",
+                    status.context_type, status.context_id, status.plugin_id
+                ),
+                before: None,
+                after: serde_json::to_value(&status).ok(),
+            });
+            Ok(Json(status))
+        }
         Err(err) => Err(map_error(err)),
     }
 }
+
+fn ensure_marketplace(state: &AppState) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    if state.config().plugins.marketplace {
+        return Ok(());
+    }
+    Err(error_response(
+        StatusCode::NOT_FOUND,
+        "The marketplace is disabled",
+    ))
+}
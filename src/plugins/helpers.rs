@@ -3,29 +3,52 @@ use axum::{
     Json,
 };
 
+use crate::auth::KeyIdentity;
 use crate::error::NovaError;
-use crate::http::{check_rate_limit, AppState};
+use crate::http::{caller_key, check_rate_limit, error_response, verified_identity, AppState};
 
 use super::dto::{ErrorResponse, PluginContextType, RequestContext};
 
 const CONTEXT_TYPE_HEADER: &str = "x-nova-context-type";
 const CONTEXT_ID_HEADER: &str = "x-nova-context-id";
+const ACTOR_ID_HEADER: &str = "x-nova-actor-id";
 
+/// Resolves the caller's context; with JWT auth the token must also grant `scope`.
 pub(crate) async fn authorize_request(
     state: &AppState,
     headers: &HeaderMap,
+    scope: &str,
 ) -> Result<RequestContext, (StatusCode, Json<ErrorResponse>)> {
-    let header_name = state.auth().header_name().to_string();
-    let presented = headers
-        .get(header_name.as_str())
-        .and_then(|value| value.to_str().ok());
+    authorize_caller(state, headers, scope)
+        .await
+        .map(|(context, _)| context)
+}
 
-    if !state.auth().validate(presented) {
-        let body = ErrorResponse {
-            error: "Unauthorized".to_string(),
-            details: None,
+/// Like [`authorize_request`], also returning the API key the caller used.
+pub(crate) async fn authorize_caller(
+    state: &AppState,
+    headers: &HeaderMap,
+    scope: &str,
+) -> Result<(RequestContext, KeyIdentity), (StatusCode, Json<ErrorResponse>)> {
+    let Some(key) = caller_key(state, headers).await else {
+        return Err(error_response(StatusCode::UNAUTHORIZED, "Unauthorized"));
+    };
+
+    let id_format = state.config().context.id_format();
+    if let Some(identity) = verified_identity(state, headers, id_format).await {
+        let identity = match identity {
+            Ok(identity) => identity,
+            Err(reason) => {
+                return Err(error_response(StatusCode::UNAUTHORIZED, reason));
+            }
         };
-        return Err((StatusCode::UNAUTHORIZED, Json(body)));
+        if !identity.allows(scope) {
+            return Err(error_response(
+                StatusCode::FORBIDDEN,
+                format!("Token lacks the {} scope", scope),
+            ));
+        }
+        return rate_limited(state, identity.context, key).await;
     }
 
     let context_type = headers
@@ -33,15 +56,13 @@ pub(crate) async fn authorize_request(
         .and_then(|value| value.to_str().ok())
         .map(|value| value.trim().to_lowercase());
 
-    let context_type = match context_type.as_deref() {
-        Some("user") => PluginContextType::User,
-        Some("group") => PluginContextType::Group,
-        _ => {
-            let body = ErrorResponse {
-                error: "Invalid or missing x-nova-context-type".to_string(),
-                details: None,
-            };
-            return Err((StatusCode::BAD_REQUEST, Json(body)));
+    let context_type = match context_type.as_deref().and_then(PluginContextType::parse) {
+        Some(context_type) => context_type,
+        None => {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                "Invalid or missing x-nova-context-type",
+            ));
         }
     };
 
@@ -53,67 +74,115 @@ pub(crate) async fn authorize_request(
     let context_id = match context_id_value {
         Some(ref id) if !id.is_empty() => id.clone(),
         _ => {
-            let body = ErrorResponse {
-                error: "Invalid or missing x-nova-context-id".to_string(),
-                details: None,
-            };
-            return Err((StatusCode::BAD_REQUEST, Json(body)));
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                "Invalid or missing x-nova-context-id",
+            ));
         }
     };
 
-    if context_id.parse::<i64>().is_err() {
-        let body = ErrorResponse {
-            error: "x-nova-context-id must be a numeric identifier".to_string(),
-            details: None,
-        };
-        return Err((StatusCode::BAD_REQUEST, Json(body)));
+    if let Err(reason) = id_format.validate(&context_type, &context_id) {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            format!("Invalid x-nova-context-id: {}", reason),
+        ));
     }
 
+    let actor_id = headers
+        .get(ACTOR_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+
     let context = RequestContext {
         context_type,
         context_id,
+        actor_id: None,
+    };
+    let context = match context.with_actor(actor_id, id_format) {
+        Ok(context) => context,
+        Err(reason) => {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid x-nova-actor-id: {}", reason),
+            ));
+        }
     };
 
-    let rate_key = format!(
-        "{}:{}",
-        match context.context_type {
-            PluginContextType::User => "user",
-            PluginContextType::Group => "group",
-        },
-        context.context_id
-    );
-
-    if let Some(code) = check_rate_limit(state, &rate_key).await {
-        let body = ErrorResponse {
-            error: "Rate limit exceeded".to_string(),
-            details: None,
-        };
-        return Err((code, Json(body)));
+    rate_limited(state, context, key).await
+}
+
+/// Refuses a context other than the one the caller authenticated as.
+pub(crate) fn ensure_own_context(
+    context: &RequestContext,
+    context_type: &PluginContextType,
+    context_id: &str,
+) -> Result<(), (StatusCode, Json<ErrorResponse>)> {
+    if context.context_type == *context_type && context.context_id == context_id {
+        return Ok(());
     }
+    Err(error_response(
+        StatusCode::FORBIDDEN,
+        "Context differs from the authenticated one",
+    ))
+}
 
-    Ok(context)
+/// Whether the request also carries a valid admin token.
+pub(crate) fn has_admin_token(state: &AppState, headers: &HeaderMap) -> bool {
+    let admin = state.admin();
+    let presented = headers
+        .get(admin.header_name())
+        .and_then(|value| value.to_str().ok());
+    admin.validate(presented)
 }
 
-pub(crate) fn map_error(err: NovaError) -> (StatusCode, Json<ErrorResponse>) {
-    let (status, details) = match &err {
-        NovaError::PluginNotFound { .. } => (StatusCode::NOT_FOUND, None),
-        NovaError::PluginNotEnabled { .. } => (StatusCode::FORBIDDEN, None),
-        NovaError::ValidationError { .. } => (StatusCode::BAD_REQUEST, None),
-        NovaError::RateLimitExceeded { .. } => (StatusCode::TOO_MANY_REQUESTS, None),
-        NovaError::ApiError(_) | NovaError::NetworkError(_) => (StatusCode::BAD_GATEWAY, None),
-        NovaError::StorageError(_) => (StatusCode::SERVICE_UNAVAILABLE, None),
-        NovaError::SerializationError(_) => (StatusCode::INTERNAL_SERVER_ERROR, None),
-        NovaError::ConfigError(_) => (StatusCode::BAD_REQUEST, None),
-        NovaError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, None),
-        NovaError::PoolNotFound { .. }
-        | NovaError::TokenNotFound { .. }
-        | NovaError::InvalidAddress { .. } => (StatusCode::BAD_REQUEST, None),
-    };
+async fn rate_limited(
+    state: &AppState,
+    context: RequestContext,
+    key: KeyIdentity,
+) -> Result<(RequestContext, KeyIdentity), (StatusCode, Json<ErrorResponse>)> {
+    if let Some(code) = check_rate_limit(state, &key.rate_key(&context.principal())).await {
+        return Err(error_response(code, "Rate limit exceeded"));
+    }
+
+    Ok((context, key))
+}
 
+pub(crate) fn map_error(err: NovaError) -> (StatusCode, Json<ErrorResponse>) {
     let body = ErrorResponse {
         error: err.to_string(),
-        details,
+        details: Some(err.to_data()),
     };
+    (status_for(&err), Json(body))
+}
 
-    (status, Json(body))
+fn status_for(err: &NovaError) -> StatusCode {
+    match err {
+        NovaError::PipelineStepFailed { source, .. } => status_for(source),
+        NovaError::PluginNotFound { .. } | NovaError::JobNotFound { .. } => StatusCode::NOT_FOUND,
+        NovaError::PluginNotEnabled { .. }
+        | NovaError::ToolDisabled { .. }
+        | NovaError::ReadOnly => StatusCode::FORBIDDEN,
+        NovaError::RevisionConflict { .. } | NovaError::BreakingSchemaChange { .. } => {
+            StatusCode::CONFLICT
+        }
+        NovaError::ValidationError { .. }
+        | NovaError::InvalidFields { .. }
+        | NovaError::InvalidArguments { .. } => StatusCode::BAD_REQUEST,
+        NovaError::RateLimitExceeded { .. } | NovaError::QuotaExceeded { .. } => {
+            StatusCode::TOO_MANY_REQUESTS
+        }
+        NovaError::ToolTimeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+        NovaError::ApiError(_) | NovaError::NetworkError(_) => StatusCode::BAD_GATEWAY,
+        NovaError::StorageError(_) | NovaError::SchemaTooNew { .. } => {
+            StatusCode::SERVICE_UNAVAILABLE
+        }
+        NovaError::SerializationError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        NovaError::ConfigError(_) | NovaError::InvalidConfig { .. } => StatusCode::BAD_REQUEST,
+        NovaError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        NovaError::PoolNotFound { .. } | NovaError::TokenNotFound { .. } => StatusCode::NOT_FOUND,
+        NovaError::InvalidAddress { .. } | NovaError::UnknownNetwork { .. } => {
+            StatusCode::BAD_REQUEST
+        }
+    }
 }
@@ -0,0 +1,485 @@
+//! Asynchronous plugin calls: `POST /plugins/:id/call?async=true` stores a
+//! job and returns its id straight away, and a background runner sends the
+//! call. A plugin may answer `202 Accepted` with `{ "poll_url",
+//! "retry_after_secs" }`, after which the runner polls it, or waits for the
+//! result at `POST /jobs/:id/callback` when `plugins.public_url` is set.
+//! Clients read the outcome from `GET /jobs/:id` or the `get_job_status` tool,
+//! or have it POSTed to a `webhook_url` (see [`super::webhooks`]).
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Notify;
+
+use super::dto::{
+    ErrorResponse, JobCallback, JobStatus, JobWebhook, PluginJob, PluginJobTicket, PluginMetadata,
+    RequestContext, WebhookState,
+};
+use super::manager::PluginManager;
+use super::webhooks::Webhooks;
+use crate::auth::constant_time_eq;
+use crate::clock::SharedClock;
+use crate::config::PluginsConfig;
+use crate::dead_letters::DeadLetter;
+use crate::error::{NovaError, Result};
+use crate::storage::{KvStore, MemoryKv};
+
+/// What a plugin answered a call with.
+pub(crate) enum PluginAnswer {
+    Ready(Value),
+    // `202 Accepted` to an async call: the result comes later
+    Accepted(JobAcceptance),
+}
+
+/// Body of a plugin's `202 Accepted`; its `Location` header stands in for
+/// a missing `poll_url`.
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct JobAcceptance {
+    #[serde(default)]
+    pub(crate) poll_url: Option<String>,
+    #[serde(default)]
+    pub(crate) retry_after_secs: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredJob {
+    #[serde(flatten)]
+    job: PluginJob,
+    caller: RequestContext,
+    // Until the call is sent; kept so a restart can send it again
+    #[serde(default)]
+    arguments: Value,
+    callback_token: String,
+    // Absolute, once the plugin named one
+    #[serde(default)]
+    poll_url: Option<String>,
+}
+
+/// Async plugin jobs, keyed by job id; finished ones are kept until their
+/// context is deleted.
+pub struct PluginJobs {
+    store: Box<dyn KvStore>,
+    // Runners and callbacks both finish jobs; one read-modify-write at a time
+    writes: Mutex<()>,
+    public_url: Option<String>,
+    poll_interval: Duration,
+    timeout: Duration,
+    clock: SharedClock,
+    // Runners waiting for a callback, woken when it arrives
+    wakers: DashMap<String, Arc<Notify>>,
+    webhooks: Webhooks,
+}
+
+impl PluginJobs {
+    pub fn in_memory() -> Self {
+        Self::with_store(Box::new(MemoryKv::default()))
+    }
+
+    pub fn persistent(tree: sled::Tree) -> Self {
+        Self::with_store(Box::new(tree))
+    }
+
+    fn with_store(store: Box<dyn KvStore>) -> Self {
+        let defaults = PluginsConfig::default();
+        Self {
+            store,
+            writes: Mutex::new(()),
+            public_url: None,
+            poll_interval: Duration::from_millis(defaults.job_poll_interval_ms),
+            timeout: Duration::from_secs(defaults.job_timeout_secs),
+            clock: SharedClock::default(),
+            wakers: DashMap::new(),
+            webhooks: Webhooks::new(),
+        }
+    }
+
+    /// `plugins.public_url`, `plugins.job_poll_interval_ms`,
+    /// `plugins.job_timeout_secs` and the webhook settings.
+    pub fn with_config(mut self, config: &PluginsConfig) -> Self {
+        self.webhooks = std::mem::take(&mut self.webhooks).with_config(config);
+        self.public_url = config
+            .public_url
+            .as_deref()
+            .map(|url| url.trim_end_matches('/').to_string());
+        self.poll_interval = Duration::from_millis(config.job_poll_interval_ms.max(1));
+        self.timeout = Duration::from_secs(config.job_timeout_secs.max(1));
+        self
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.webhooks = std::mem::take(&mut self.webhooks).with_clock(clock.clone());
+        self.clock = clock;
+        self
+    }
+
+    pub fn webhooks(&self) -> &Webhooks {
+        &self.webhooks
+    }
+
+    /// Checks the call as a synchronous one would be, stores it and sends it
+    /// in the background; the outcome goes to `webhook_url` too when given.
+    /// Quotas are the caller's business.
+    pub fn submit(
+        self: &Arc<Self>,
+        manager: &Arc<PluginManager>,
+        metadata: &PluginMetadata,
+        caller: &RequestContext,
+        arguments: Value,
+        webhook_url: Option<String>,
+    ) -> Result<PluginJob> {
+        let arguments = manager.prepare_call(metadata, caller, arguments)?;
+        if let Some(url) = &webhook_url {
+            self.webhooks.check_url(manager, url)?;
+        }
+        let now = self.clock.timestamp();
+        let job = PluginJob {
+            job_id: uuid::Uuid::new_v4().to_string(),
+            plugin_id: metadata.plugin_id,
+            plugin: metadata.fq_name.clone(),
+            status: JobStatus::Pending,
+            result: None,
+            error: None,
+            created_at: now,
+            updated_at: now,
+            expires_at: now + self.timeout.as_secs() as i64,
+            webhook: webhook_url.map(|url| JobWebhook {
+                url,
+                state: WebhookState::Pending,
+            }),
+        };
+        self.save(&StoredJob {
+            job: job.clone(),
+            caller: caller.clone(),
+            arguments,
+            callback_token: uuid::Uuid::new_v4().simple().to_string(),
+            poll_url: None,
+        })?;
+        self.spawn(manager, &job.job_id);
+        Ok(job)
+    }
+
+    /// The job, if `caller`'s context submitted it; other contexts get
+    /// [`NovaError::JobNotFound`] as for an unknown id.
+    pub fn get(&self, job_id: &str, caller: &RequestContext) -> Result<PluginJob> {
+        match self.load(job_id)? {
+            Some(stored) if stored.caller.key() == caller.key() => Ok(stored.job),
+            _ => Err(NovaError::job_not_found(job_id)),
+        }
+    }
+
+    /// Finishes the job with a result the plugin posted, checked like a
+    /// direct answer. A wrong `token` reads as an unknown job; a job that
+    /// already finished is returned unchanged.
+    pub fn complete(
+        &self,
+        manager: &PluginManager,
+        job_id: &str,
+        token: &str,
+        callback: JobCallback,
+    ) -> Result<PluginJob> {
+        let stored = self
+            .load(job_id)?
+            .filter(|stored| constant_time_eq(stored.callback_token.as_bytes(), token.as_bytes()))
+            .ok_or_else(|| NovaError::job_not_found(job_id))?;
+        if stored.job.status.is_finished() {
+            return Ok(stored.job);
+        }
+        let outcome = match callback {
+            JobCallback {
+                error: Some(message),
+                ..
+            } => Err(NovaError::api_error(format!(
+                "Plugin reported failure: {}",
+                message
+            ))),
+            JobCallback {
+                result: Some(result),
+                ..
+            } => manager
+                .get_plugin_by_fq_name(&stored.job.plugin)
+                .and_then(|metadata| manager.accept_result(&metadata, result)),
+            JobCallback { .. } => {
+                return Err(NovaError::validation_error(
+                    "A callback needs either result or error",
+                ))
+            }
+        };
+        let job = self.finish(job_id, outcome)?;
+        if let Some(waker) = self.wakers.get(job_id) {
+            waker.notify_one();
+        }
+        Ok(job)
+    }
+
+    /// Picks unfinished jobs back up after a restart: pending ones are sent
+    /// again, running ones polled or awaited until they expire, and webhooks
+    /// of finished ones still waiting are delivered.
+    pub fn resume(self: &Arc<Self>, manager: &Arc<PluginManager>) -> Result<usize> {
+        let mut resumed = 0;
+        for (_, bytes) in self.scan()? {
+            let stored: StoredJob = serde_json::from_slice(&bytes)?;
+            let undelivered = stored
+                .job
+                .webhook
+                .as_ref()
+                .is_some_and(|webhook| webhook.state == WebhookState::Pending);
+            if !stored.job.status.is_finished() || undelivered {
+                self.spawn(manager, &stored.job.job_id);
+                resumed += 1;
+            }
+        }
+        Ok(resumed)
+    }
+
+    /// Requeues a dead-lettered job webhook and marks it delivered when it
+    /// gets through; see [`Webhooks::requeue`].
+    pub async fn requeue_webhook(&self, manager: &PluginManager, id: &str) -> Result<DeadLetter> {
+        let letter = self.webhooks.requeue(manager, id).await?;
+        let job_id = &letter.delivery.reference;
+        if self.load(job_id)?.is_some() {
+            self.set_webhook_state(job_id, WebhookState::Delivered)?;
+        }
+        Ok(letter)
+    }
+
+    /// Removes the jobs the context submitted.
+    pub fn remove_context(&self, context: &RequestContext) -> Result<usize> {
+        let owner = context.key();
+        let mut removed = 0;
+        for (key, bytes) in self.scan()? {
+            let stored: StoredJob = serde_json::from_slice(&bytes)?;
+            if stored.caller.key() == owner {
+                self.delete(&key)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn spawn(self: &Arc<Self>, manager: &Arc<PluginManager>, job_id: &str) {
+        let jobs = Arc::clone(self);
+        let manager = Arc::clone(manager);
+        let job_id = job_id.to_string();
+        tokio::spawn(async move {
+            if let Err(e) = jobs.run(&manager, &job_id).await {
+                tracing::warn!("Plugin job {} stopped: {}", job_id, e);
+            }
+            jobs.wakers.remove(&job_id);
+            if let Err(e) = jobs.deliver_webhook(&manager, &job_id).await {
+                tracing::warn!("Webhook for plugin job {} not delivered: {}", job_id, e);
+            }
+        });
+    }
+
+    async fn run(&self, manager: &PluginManager, job_id: &str) -> Result<()> {
+        let waker = Arc::clone(self.wakers.entry(job_id.to_string()).or_default().value());
+        let Some(mut stored) = self.load(job_id)? else {
+            return Ok(());
+        };
+        let metadata = match manager.get_plugin_by_fq_name(&stored.job.plugin) {
+            Ok(metadata) => metadata,
+            Err(e) => return self.finish(job_id, Err(e)).map(drop),
+        };
+        let mut delay = None;
+        if stored.job.status == JobStatus::Pending {
+            let ticket = PluginJobTicket {
+                job_id: job_id.to_string(),
+                callback_url: self.public_url.as_ref().map(|base| {
+                    format!(
+                        "{}{}/jobs/{}/callback",
+                        base,
+                        crate::http::API_PREFIX,
+                        job_id
+                    )
+                }),
+                callback_token: self
+                    .public_url
+                    .as_ref()
+                    .map(|_| stored.callback_token.clone()),
+            };
+            let arguments = std::mem::take(&mut stored.arguments);
+            let answer = manager
+                .send_call(&metadata, &stored.caller, arguments, Some(ticket))
+                .await;
+            match answer {
+                Ok(PluginAnswer::Ready(result)) => {
+                    return self.finish(job_id, Ok(result)).map(drop)
+                }
+                Ok(PluginAnswer::Accepted(accepted)) => {
+                    delay = accepted.retry_after_secs.map(Duration::from_secs);
+                    let poll_url = match resolve(&metadata, accepted.poll_url.as_deref()) {
+                        Ok(url) => url,
+                        Err(e) => return self.finish(job_id, Err(e)).map(drop),
+                    };
+                    // A callback may have beaten the plugin's own answer
+                    self.update(job_id, |stored| {
+                        if stored.job.status == JobStatus::Pending {
+                            stored.job.status = JobStatus::Running;
+                            stored.arguments = Value::Null;
+                            stored.poll_url = poll_url;
+                        }
+                    })?;
+                }
+                Err(e) => return self.finish(job_id, Err(e)).map(drop),
+            }
+        }
+
+        loop {
+            let Some(stored) = self.load(job_id)? else {
+                return Ok(());
+            };
+            if stored.job.status.is_finished() {
+                return Ok(());
+            }
+            let now = self.clock.timestamp();
+            if now >= stored.job.expires_at {
+                let timeout = NovaError::tool_timeout(&stored.job.plugin, self.timeout.as_secs());
+                return self.finish(job_id, Err(timeout)).map(drop);
+            }
+            let remaining = Duration::from_secs((stored.job.expires_at - now) as u64);
+            let Some(poll_url) = stored.poll_url else {
+                // Nothing to poll: only a callback or the deadline ends the job
+                tokio::select! {
+                    _ = waker.notified() => {}
+                    _ = tokio::time::sleep(remaining) => {}
+                }
+                continue;
+            };
+            let wait = delay.take().unwrap_or(self.poll_interval).min(remaining);
+            tokio::select! {
+                _ = waker.notified() => continue,
+                _ = tokio::time::sleep(wait) => {}
+            }
+            match manager.poll_job(&metadata, &stored.caller, &poll_url).await {
+                Ok(PluginAnswer::Ready(result)) => {
+                    return self.finish(job_id, Ok(result)).map(drop)
+                }
+                Ok(PluginAnswer::Accepted(accepted)) => {
+                    delay = accepted.retry_after_secs.map(Duration::from_secs);
+                    if accepted.poll_url.is_some() {
+                        let poll_url = match resolve(&metadata, accepted.poll_url.as_deref()) {
+                            Ok(url) => url,
+                            Err(e) => return self.finish(job_id, Err(e)).map(drop),
+                        };
+                        self.update(job_id, |stored| stored.poll_url = poll_url)?;
+                    }
+                }
+                // Network trouble and timeouts are worth another poll
+                Err(e) if e.is_retryable() => {
+                    tracing::debug!("Polling plugin job {} failed: {}", job_id, e);
+                }
+                Err(e) => return self.finish(job_id, Err(e)).map(drop),
+            }
+        }
+    }
+
+    /// Sends a finished job to its webhook, once.
+    async fn deliver_webhook(&self, manager: &PluginManager, job_id: &str) -> Result<()> {
+        let Some(stored) = self.load(job_id)? else {
+            return Ok(());
+        };
+        let Some(webhook) = stored.job.webhook.clone() else {
+            return Ok(());
+        };
+        if !stored.job.status.is_finished() || webhook.state != WebhookState::Pending {
+            return Ok(());
+        }
+        // The receiver knows where it lives
+        let job = PluginJob {
+            webhook: None,
+            ..stored.job
+        };
+        let delivered = self
+            .webhooks
+            .deliver(manager, &webhook.url, &stored.caller, &job)
+            .await;
+        let state = match delivered {
+            Ok(()) => WebhookState::Delivered,
+            Err(_) => WebhookState::DeadLettered,
+        };
+        self.set_webhook_state(job_id, state)?;
+        delivered
+    }
+
+    fn set_webhook_state(&self, job_id: &str, state: WebhookState) -> Result<PluginJob> {
+        let now = self.clock.timestamp();
+        self.update(job_id, |stored| {
+            if let Some(webhook) = &mut stored.job.webhook {
+                webhook.state = state;
+                stored.job.updated_at = now;
+            }
+        })
+    }
+
+    /// Records the outcome unless the job already has one.
+    fn finish(&self, job_id: &str, outcome: Result<Value>) -> Result<PluginJob> {
+        let now = self.clock.timestamp();
+        self.update(job_id, |stored| {
+            if stored.job.status.is_finished() {
+                return;
+            }
+            match &outcome {
+                Ok(result) => {
+                    stored.job.status = JobStatus::Succeeded;
+                    stored.job.result = Some(result.clone());
+                }
+                Err(e) => {
+                    stored.job.status = JobStatus::Failed;
+                    stored.job.error = Some(ErrorResponse {
+                        error: e.to_string(),
+                        details: Some(e.to_data()),
+                    });
+                }
+            }
+            stored.job.updated_at = now;
+            stored.arguments = Value::Null;
+            stored.poll_url = None;
+        })
+    }
+
+    fn update(&self, job_id: &str, change: impl FnOnce(&mut StoredJob)) -> Result<PluginJob> {
+        let _guard = self.writes.lock().unwrap_or_else(|e| e.into_inner());
+        let mut stored = self
+            .load(job_id)?
+            .ok_or_else(|| NovaError::job_not_found(job_id))?;
+        change(&mut stored);
+        self.save(&stored)?;
+        Ok(stored.job)
+    }
+
+    fn load(&self, job_id: &str) -> Result<Option<StoredJob>> {
+        self.store.get_json(job_id.as_bytes())
+    }
+
+    fn save(&self, stored: &StoredJob) -> Result<()> {
+        self.store.insert_json(stored.job.job_id.as_bytes(), stored)
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        self.store.remove(key.as_bytes()).map(drop)
+    }
+
+    fn scan(&self) -> Result<Vec<(String, Vec<u8>)>> {
+        self.store.scan_str("")
+    }
+}
+
+impl Default for PluginJobs {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+/// `poll_url` made absolute against the plugin's endpoint.
+fn resolve(metadata: &PluginMetadata, poll_url: Option<&str>) -> Result<Option<String>> {
+    let Some(poll_url) = poll_url else {
+        return Ok(None);
+    };
+    reqwest::Url::parse(&metadata.endpoint_url)
+        .and_then(|endpoint| endpoint.join(poll_url))
+        .map(|url| Some(url.to_string()))
+        .map_err(|e| NovaError::api_error(format!("Invalid poll_url {}: {}", poll_url, e)))
+}
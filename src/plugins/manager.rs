@@ -1,33 +1,125 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::str;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::RwLock;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use chrono::Utc;
-use jsonschema::{Draft, JSONSchema};
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
 use reqwest::Client;
-use serde_json::Value;
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
 
+use crate::clock::SharedClock;
+use crate::config::OutboundConfig;
+use crate::dead_letters::DeadLetters;
 use crate::error::{NovaError, Result};
+use crate::events::{EventBus, EventKind};
+use crate::mcp::logging::{self, LogLevel};
+use crate::mcp::progress;
+use crate::metering::{Metering, UsageEvent};
+use crate::tools::upstream_health::{UpstreamHealth, UpstreamStatus};
+use crate::{outbound, schema, storage};
 
 use super::dto::{
-    GroupPluginRecord, PluginContextType, PluginEnableRequest, PluginEnablementStatus,
-    PluginInvocationPayload, PluginMetadata, PluginRegistrationRequest, PluginUpdateRequest,
-    PluginVersionRecord, RequestContext, StoredPluginRecord, UserPluginRecord,
+    escape_context_id, ContextIdFormat, GroupPluginRecord, MarketplaceEntry, MarketplaceQuery,
+    PluginAuth, PluginClientCertificate, PluginContextType, PluginCredentials, PluginEnableRequest,
+    PluginEnablementStatus, PluginInvocationPayload, PluginJobTicket, PluginListing,
+    PluginMetadata, PluginRegistrationRequest, PluginScript, PluginTrustLevel, PluginUpdateRequest,
+    PluginVersionRecord, RegistryChange, RegistrySnapshot, RegistryStats, RequestContext,
+    StoredClientCertificate, StoredPluginRecord, UserPluginRecord,
 };
-
-type PluginStore = HashMap<u64, StoredPluginRecord>;
-type PluginIndex = HashMap<String, (u64, u32)>;
-type LoadedPluginState = (PluginStore, PluginIndex, u64);
+use super::egress::EgressPolicy;
+use super::jobs::{JobAcceptance, PluginAnswer};
+use super::redaction::RedactionRules;
+use super::schema_refs::SchemaRefs;
+use super::script::{ScriptLimits, ScriptRun};
+use super::secrets::SecretBox;
+use super::stream::{ChunkDecoder, StreamFormat};
+use super::template::RequestTemplate;
+use super::validation::FieldProblems;
+
+type PluginStore = DashMap<u64, StoredPluginRecord>;
+type PluginIndex = DashMap<String, (u64, u32)>;
+type NameIndex = DashMap<NameKey, u64>;
+type LoadedPluginState = (PluginStore, PluginIndex, NameIndex, u64);
+
+/// Plugin calls slower than this are reported to clients with logging enabled.
+const SLOW_PLUGIN_THRESHOLD: Duration = Duration::from_secs(2);
+
+/// Headers plugin credentials may not set; the client or payload owns them.
+const RESERVED_HEADERS: &[&str] = &[
+    "host",
+    "content-length",
+    "content-type",
+    "transfer-encoding",
+    "connection",
+];
+
+/// Most categories one marketplace listing may name.
+const MAX_LISTING_CATEGORIES: usize = 5;
+
+/// Most tags one plugin may carry.
+const MAX_TAGS: usize = 10;
+
+/// Why a scripted plugin's registration or update was turned away.
+const SCRIPT_ONLY_FIELDS: &str =
+    "Scripted plugins take no credentials, client certificate or request template";
+
+/// Registry changes buffered per subscriber before it starts lagging.
+const CHANGE_BUFFER: usize = 64;
+
+/// Secondary index key: `(context_type, context_id, lowercased name)`.
+type NameKey = (PluginContextType, String, String);
 
 pub struct PluginManager {
     metadata_tree: sled::Tree,
     user_tree: sled::Tree,
     group_tree: sled::Tree,
-    plugins: RwLock<HashMap<u64, StoredPluginRecord>>,
-    fq_index: RwLock<HashMap<String, (u64, u32)>>,
+    // Optional so embedders that only know users and groups keep working
+    channel_tree: Option<sled::Tree>,
+    organization_tree: Option<sled::Tree>,
+    id_format: ContextIdFormat,
+    // Sharded maps so lookups on the hot `tools/list`/`tools/call` path never
+    // serialize behind registrations or updates.
+    plugins: PluginStore,
+    fq_index: PluginIndex,
+    name_index: NameIndex,
     sequence: AtomicU64,
+    egress: EgressPolicy,
+    // Fetches and inlines remote `$ref`s in registered schemas
+    schema_refs: SchemaRefs,
     http_client: Client,
+    // Base settings for the per-plugin clients of mutual-TLS plugins
+    outbound: OutboundConfig,
+    // Built on first call, dropped when the plugin's certificate changes
+    mtls_clients: DashMap<u64, Client>,
+    // Without it, plugins cannot store credentials
+    secrets: Option<SecretBox>,
+    // `plugins.redact`, applied before each plugin's own rules
+    redaction: RedactionRules,
+    // `plugins.script_*` budgets for scripted plugins
+    script_limits: ScriptLimits,
+    // Compiled scripts by fq_name, with the source they were compiled from
+    scripts: DashMap<String, (String, Arc<rhai::AST>)>,
+    // Compiled input schemas by fq_name, compiled when a version is added
+    validators: schema::CompiledSchemas,
+    // Endpoint outcomes, keyed `plugin:<fq_name>`
+    health: Arc<UpstreamHealth>,
+    // Usage events for calls that reach an endpoint; none without it
+    metering: Option<Arc<Metering>>,
+    // Background deliveries that failed for good, e.g. job webhooks
+    dead_letters: Arc<DeadLetters>,
+    // Registrations and endpoint failures are published here
+    events: Arc<EventBus>,
+    // Last call per plugin and context, keyed `<plugin_id:020>|<type>:<id>`;
+    // untracked without it
+    activity_tree: Option<sled::Tree>,
+    // Timestamps: versions, consent, snapshots, last use, usage events
+    clock: SharedClock,
+    changes: broadcast::Sender<RegistryChange>,
+    // `server.read_only`: registry writes fail with `NovaError::ReadOnly`
+    read_only: bool,
 }
 
 impl PluginManager {
@@ -36,48 +128,317 @@ impl PluginManager {
         user_tree: sled::Tree,
         group_tree: sled::Tree,
     ) -> Result<Self> {
-        let (plugins, fq_index, next_id) = Self::load_plugins(&metadata_tree)?;
+        let (plugins, fq_index, name_index, next_id) = Self::load_plugins(&metadata_tree)?;
+        let egress = EgressPolicy::default();
+        let http_client = egress.configure(Client::builder()).build()?;
+        let events = Arc::new(EventBus::new());
+        let health = Arc::new(UpstreamHealth::default());
+        health.publish_to(Arc::clone(&events));
         Ok(Self {
             metadata_tree,
             user_tree,
             group_tree,
-            plugins: RwLock::new(plugins),
-            fq_index: RwLock::new(fq_index),
+            channel_tree: None,
+            organization_tree: None,
+            id_format: ContextIdFormat::default(),
+            plugins,
+            fq_index,
+            name_index,
             sequence: AtomicU64::new(next_id),
-            http_client: Client::new(),
+            egress,
+            schema_refs: SchemaRefs::default(),
+            http_client,
+            outbound: OutboundConfig::default(),
+            mtls_clients: DashMap::new(),
+            secrets: None,
+            redaction: RedactionRules::default(),
+            script_limits: ScriptLimits::default(),
+            scripts: DashMap::new(),
+            validators: schema::CompiledSchemas::default(),
+            health,
+            metering: None,
+            dead_letters: Arc::new(DeadLetters::in_memory()),
+            events,
+            activity_tree: None,
+            clock: SharedClock::default(),
+            changes: broadcast::channel(CHANGE_BUFFER).0,
+            read_only: false,
         })
     }
 
+    /// A registry over a temporary database, with every context type enabled.
+    pub fn in_memory() -> Result<Self> {
+        let db = storage::open_temporary()?;
+        Ok(Self::new(
+            db.open_tree("plugin_metadata")?,
+            db.open_tree("user_plugins")?,
+            db.open_tree("group_plugins")?,
+        )?
+        .with_context_trees(
+            db.open_tree("channel_plugins")?,
+            db.open_tree("organization_plugins")?,
+        )
+        .with_activity_tree(db.open_tree("plugin_activity")?))
+    }
+
+    /// Enablement trees for channel and organization contexts. Without them,
+    /// enabling another context's plugin for those types is rejected.
+    pub fn with_context_trees(
+        mut self,
+        channel_tree: sled::Tree,
+        organization_tree: sled::Tree,
+    ) -> Self {
+        self.channel_tree = Some(channel_tree);
+        self.organization_tree = Some(organization_tree);
+        self
+    }
+
+    /// Emits a usage event for every call that reaches a plugin endpoint.
+    pub fn with_metering(mut self, metering: Arc<Metering>) -> Self {
+        self.metering = Some(metering);
+        self
+    }
+
+    /// Where job webhooks that fail every attempt are kept; share it with
+    /// the metering webhook sink and the admin API.
+    pub fn with_dead_letters(mut self, dead_letters: Arc<DeadLetters>) -> Self {
+        self.dead_letters = dead_letters;
+        self
+    }
+
+    pub fn dead_letters(&self) -> &Arc<DeadLetters> {
+        &self.dead_letters
+    }
+
+    /// The server's event bus too: [`NovaServer::new`](crate::NovaServer::new)
+    /// takes it from here, as it does the clock.
+    pub fn with_events(mut self, events: Arc<EventBus>) -> Self {
+        self.health.publish_to(Arc::clone(&events));
+        self.events = events;
+        self
+    }
+
+    pub fn events(&self) -> &Arc<EventBus> {
+        &self.events
+    }
+
+    /// Tree recording when each context last called each plugin.
+    pub fn with_activity_tree(mut self, tree: sled::Tree) -> Self {
+        self.activity_tree = Some(tree);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// The server's clock too: [`NovaServer::new`](crate::NovaServer::new)
+    /// takes it from here.
+    pub fn clock(&self) -> &SharedClock {
+        &self.clock
+    }
+
+    pub fn metering(&self) -> Option<&Metering> {
+        self.metering.as_deref()
+    }
+
+    /// Records plugin endpoint outcomes in a shared tracker instead of a private one.
+    pub fn with_upstream_health(mut self, health: Arc<UpstreamHealth>) -> Self {
+        health.publish_to(Arc::clone(&self.events));
+        self.health = health;
+        self
+    }
+
+    pub fn upstream_health(&self) -> &UpstreamHealth {
+        &self.health
+    }
+
+    /// Rule for context ids in enablement requests; see `context.id_format`.
+    pub fn with_context_id_format(mut self, id_format: ContextIdFormat) -> Self {
+        self.id_format = id_format;
+        self
+    }
+
+    /// Endpoint rules from `[plugins]`, and a plugin client built with them.
+    /// A client passed to [`with_http_client`](Self::with_http_client)
+    /// afterwards should be built with [`EgressPolicy::configure`] so
+    /// redirects and resolved addresses follow them too.
+    pub fn with_egress_policy(mut self, egress: EgressPolicy) -> Self {
+        match egress.configure(Client::builder()).build() {
+            Ok(client) => self.http_client = client,
+            Err(e) => tracing::warn!("Failed to build plugin HTTP client: {}", e),
+        }
+        self.egress = egress;
+        self
+    }
+
+    /// Endpoint rules; caller-supplied job webhooks are held to them too.
+    pub(crate) fn egress(&self) -> &EgressPolicy {
+        &self.egress
+    }
+
+    pub(crate) fn http_client(&self) -> &Client {
+        &self.http_client
+    }
+
+    /// Hosts schema `$ref`s may be fetched from (`plugins.schema_ref_hosts`).
+    pub fn with_schema_refs(mut self, schema_refs: SchemaRefs) -> Self {
+        self.schema_refs = schema_refs;
+        self
+    }
+
+    /// Proxy and CA settings for the clients that present a plugin's client
+    /// certificate; the shared client comes from
+    /// [`with_http_client`](Self::with_http_client).
+    pub fn with_outbound_config(mut self, outbound: OutboundConfig) -> Self {
+        self.outbound = outbound;
+        self
+    }
+
+    /// Key for plugin credentials and client certificate keys at rest
+    /// (`plugins.secrets_key`). Certificate keys stored in plaintext before
+    /// keys were sealed are sealed now.
+    pub fn with_secret_box(mut self, secrets: SecretBox) -> Self {
+        self.secrets = Some(secrets);
+        if let Err(e) = self.seal_legacy_certificate_keys() {
+            tracing::warn!("Failed to seal stored client certificate keys: {}", e);
+        }
+        self
+    }
+
+    fn seal_legacy_certificate_keys(&self) -> Result<()> {
+        let legacy: Vec<u64> = self
+            .plugins
+            .iter()
+            .filter(|record| {
+                record
+                    .client_certificate
+                    .as_ref()
+                    .is_some_and(|certificate| certificate.key_pem.is_some())
+            })
+            .map(|record| record.plugin_id)
+            .collect();
+        for plugin_id in legacy {
+            let Some(mut record) = self.plugins.get_mut(&plugin_id) else {
+                continue;
+            };
+            let Some(certificate) = record.client_certificate.as_mut() else {
+                continue;
+            };
+            if let Some(key_pem) = certificate.key_pem.take() {
+                certificate.sealed_key_pem = Some(self.seal_key_pem(&key_pem)?);
+            }
+            let stored = record.clone();
+            drop(record);
+            self.persist_plugin(&stored)?;
+        }
+        Ok(())
+    }
+
+    /// Redaction rules for every plugin response (`plugins.redact`).
+    pub fn with_redaction(mut self, redaction: RedactionRules) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// Operation, time, size and HTTP budgets for scripted plugins
+    /// (`plugins.script_*`).
+    pub fn with_script_limits(mut self, limits: ScriptLimits) -> Self {
+        self.script_limits = limits;
+        self
+    }
+
+    /// Client used for plugin endpoint calls, e.g. one routed through a proxy.
+    pub fn with_http_client(mut self, http_client: Client) -> Self {
+        self.http_client = http_client;
+        self
+    }
+
+    /// Rejects registrations, updates, deletions, enablement changes and
+    /// listing reviews; lookups and plugin calls are unaffected.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn ensure_writable(&self) -> Result<()> {
+        if self.read_only {
+            return Err(NovaError::ReadOnly);
+        }
+        Ok(())
+    }
+
+    /// Writes made through this manager that may change a context's tool
+    /// list, e.g. to send MCP clients `notifications/tools/list_changed`.
+    /// Other processes' writes are not seen. A receiver that lags should
+    /// assume any context changed.
+    pub fn subscribe(&self) -> broadcast::Receiver<RegistryChange> {
+        self.changes.subscribe()
+    }
+
+    fn announce(&self, change: RegistryChange) {
+        // No receivers just means no client is listening.
+        let _ = self.changes.send(change);
+    }
+
     pub fn register_plugin(
         &self,
         context: &RequestContext,
-        request: PluginRegistrationRequest,
+        mut request: PluginRegistrationRequest,
     ) -> Result<PluginMetadata> {
+        self.ensure_writable()?;
         self.validate_registration(&request)?;
-        let mut plugins = self
-            .plugins
-            .write()
-            .map_err(|_| NovaError::internal("Plugin registry lock poisoned"))?;
-
-        Self::ensure_unique_name(&plugins, context, &request.name)?;
+        if request.script.is_none() {
+            request.endpoint_url = self
+                .egress
+                .canonical_url(&request.endpoint_url, request.trust_level)?;
+        }
+        let sealed = self.seal_credentials(request.credentials.as_ref())?;
+        let client_certificate = request
+            .client_certificate
+            .as_ref()
+            .map(|certificate| self.seal_client_certificate(certificate))
+            .transpose()?;
 
-        let plugin_id = self.sequence.fetch_add(1, Ordering::SeqCst);
-        let now = Utc::now().timestamp();
         let fq_name = Self::fq_name(
             &context.context_type,
             &context.context_id,
             &request.name,
             request.version,
         );
-
         self.ensure_unique_fq_name(&fq_name)?;
 
+        // Reserving the name slot is the only serialization point between
+        // concurrent registrations for the same context.
+        let name_key = Self::name_key(&context.context_type, &context.context_id, &request.name);
+        let plugin_id = match self.name_index.entry(name_key.clone()) {
+            Entry::Occupied(_) => {
+                return Err(NovaError::validation_error(
+                    "A tool with this name already exists for the context",
+                ))
+            }
+            Entry::Vacant(slot) => {
+                let plugin_id = self.sequence.fetch_add(1, Ordering::SeqCst);
+                slot.insert(plugin_id);
+                plugin_id
+            }
+        };
+        let now = self.clock.timestamp();
+
         let version_record = PluginVersionRecord {
             version: request.version,
             fq_name: fq_name.clone(),
             input_schema: request.input_schema.clone(),
             output_schema: request.output_schema.clone(),
             endpoint_url: request.endpoint_url.clone(),
+            script: request.script.clone(),
+            request_template: request.request_template.clone(),
+            schema_changes: Vec::new(),
             created_at: now,
         };
 
@@ -88,40 +449,77 @@ impl PluginManager {
             owner_id: request.owner_id,
             context_type: context.context_type.clone(),
             context_id: context.context_id.clone(),
+            trust_level: request.trust_level,
+            client_certificate,
+            credential_headers: sealed
+                .as_ref()
+                .map(|(_, names)| names.clone())
+                .unwrap_or_default(),
+            sealed_credentials: sealed.map(|(sealed, _)| sealed),
+            redact: request.redact,
+            listing: request.listing.map(Self::submitted),
+            allowed_contexts: request.allowed_contexts,
+            tags: request.tags,
+            coerce_arguments: request.coerce_arguments,
+            revision: 1,
             created_at: now,
             updated_at: now,
             versions: vec![version_record.clone()],
         };
 
-        plugins.insert(plugin_id, record.clone());
-        drop(plugins);
+        self.plugins.insert(plugin_id, record.clone());
 
-        self.persist_plugin(&record)?;
+        if let Err(err) = self.persist_plugin(&record) {
+            // Frees the name so a retry is not refused as a duplicate
+            self.plugins.remove(&plugin_id);
+            self.name_index
+                .remove_if(&name_key, |_, id| *id == plugin_id);
+            return Err(err);
+        }
         self.insert_fq_mapping(&version_record, plugin_id);
         self.ensure_owner_enablement(&record)?;
+        self.announce(RegistryChange::Plugin(plugin_id));
+        self.events.publish(EventKind::PluginRegistered {
+            plugin_id,
+            fq_name,
+            owner: context.key(),
+        });
 
         Ok(Self::to_metadata(&record, &version_record))
     }
 
+    /// Endpoint checks that need DNS, for callers that can await before
+    /// [`register_plugin`](Self::register_plugin) or
+    /// [`update_plugin`](Self::update_plugin); calls re-check on every invocation.
+    pub async fn check_endpoint(
+        &self,
+        endpoint_url: &str,
+        trust_level: PluginTrustLevel,
+    ) -> Result<()> {
+        let url = self.egress.check_url(endpoint_url, trust_level)?;
+        self.egress.check_registered(&url).await
+    }
+
     pub fn unregister_plugin(&self, context: &RequestContext, plugin_id: u64) -> Result<()> {
-        let mut plugins = self
+        self.ensure_writable()?;
+        let (_, record) = self
             .plugins
-            .write()
-            .map_err(|_| NovaError::internal("Plugin registry lock poisoned"))?;
-
-        let record = plugins
-            .get(&plugin_id)
-            .cloned()
-            .ok_or_else(|| NovaError::plugin_not_found(plugin_id))?;
-
-        if record.context_type != context.context_type || record.context_id != context.context_id {
-            return Err(NovaError::validation_error(
-                "Only the owner context can delete a tool",
-            ));
-        }
-
-        plugins.remove(&plugin_id);
-        drop(plugins);
+            .remove_if(&plugin_id, |_, record| {
+                record.context_type == context.context_type
+                    && record.context_id == context.context_id
+            })
+            .ok_or_else(|| {
+                if self.plugins.contains_key(&plugin_id) {
+                    NovaError::validation_error("Only the owner context can delete a tool")
+                } else {
+                    NovaError::plugin_not_found(plugin_id)
+                }
+            })?;
+        self.name_index.remove(&Self::name_key(
+            &record.context_type,
+            &record.context_id,
+            &record.name,
+        ));
 
         self.metadata_tree
             .remove(plugin_id.to_be_bytes())
@@ -129,7 +527,16 @@ impl PluginManager {
         self.metadata_tree.flush().map_err(NovaError::from)?;
 
         self.remove_fq_mappings(&record);
+        self.mtls_clients.remove(&plugin_id);
         self.clear_plugin_entries(plugin_id)?;
+        if let Some(tree) = &self.activity_tree {
+            let prefix = format!("{:020}|", plugin_id);
+            for item in tree.scan_prefix(prefix.as_bytes()) {
+                let (key, _) = item.map_err(NovaError::from)?;
+                tree.remove(key).map_err(NovaError::from)?;
+            }
+        }
+        self.announce(RegistryChange::Plugin(plugin_id));
         Ok(())
     }
 
@@ -139,21 +546,30 @@ impl PluginManager {
         plugin_id: u64,
         update: PluginUpdateRequest,
     ) -> Result<PluginMetadata> {
+        self.ensure_writable()?;
         self.validate_update(&update)?;
-        let mut plugins = self
+        let mut entry = self
             .plugins
-            .write()
-            .map_err(|_| NovaError::internal("Plugin registry lock poisoned"))?;
-
-        let record = plugins
             .get_mut(&plugin_id)
             .ok_or_else(|| NovaError::plugin_not_found(plugin_id))?;
+        // Changes go to a copy that replaces the record only once every check passed
+        let mut record = entry.clone();
 
         if record.context_type != context.context_type || record.context_id != context.context_id {
             return Err(NovaError::validation_error(
                 "Only the owner context can update a tool",
             ));
         }
+        if let Some(expected) = update
+            .revision
+            .filter(|&expected| expected != record.revision)
+        {
+            return Err(NovaError::revision_conflict(
+                plugin_id,
+                expected,
+                record.revision,
+            ));
+        }
 
         let previous_version = record
             .versions
@@ -162,7 +578,7 @@ impl PluginManager {
             .clone();
 
         let new_version = previous_version.version + 1;
-        let now = Utc::now().timestamp();
+        let now = self.clock.timestamp();
         let fq_name = Self::fq_name(
             &record.context_type,
             &record.context_id,
@@ -179,16 +595,109 @@ impl PluginManager {
             record.owner_id = Some(owner_id);
         }
 
-        let input_schema = update
-            .input_schema
-            .unwrap_or(previous_version.input_schema.clone());
+        let (input_schema, schema_changes) = match update.input_schema {
+            Some(schema) => {
+                let changes = schema::breaking_changes(&previous_version.input_schema, &schema);
+                if !changes.is_empty() && !update.breaking {
+                    return Err(NovaError::breaking_schema_change(plugin_id, changes));
+                }
+                (schema, changes)
+            }
+            None => (previous_version.input_schema.clone(), Vec::new()),
+        };
         let output_schema = match update.output_schema {
             Some(value) => value,
             None => previous_version.output_schema.clone(),
         };
-        let endpoint_url = update
-            .endpoint_url
-            .unwrap_or(previous_version.endpoint_url.clone());
+        let request_template = match update.request_template {
+            Some(value) => value,
+            None => previous_version.request_template.clone(),
+        };
+        let trust_level = update.trust_level.unwrap_or(record.trust_level);
+        let script = match (&previous_version.script, update.script) {
+            (Some(_), Some(script)) => Some(script),
+            (previous, None) => previous.clone(),
+            (None, Some(_)) => {
+                return Err(NovaError::validation_error(
+                    "Only scripted plugins can take a script",
+                ))
+            }
+        };
+        let endpoint_url = match update.endpoint_url {
+            Some(_) if script.is_some() => {
+                return Err(NovaError::validation_error(
+                    "Scripted plugins have no endpoint",
+                ))
+            }
+            Some(endpoint) => self.egress.canonical_url(&endpoint, trust_level)?,
+            None if script.is_some() => String::new(),
+            None => {
+                // A raised trust level may bring a stricter allowlist for the old endpoint
+                self.egress
+                    .check_url(&previous_version.endpoint_url, trust_level)?;
+                previous_version.endpoint_url.clone()
+            }
+        };
+        let client_certificate = match update.client_certificate {
+            Some(Some(certificate)) => {
+                Self::validate_client_certificate(Some(&certificate), trust_level)?;
+                Some(self.seal_client_certificate(&certificate)?)
+            }
+            Some(None) => None,
+            None => {
+                if record.client_certificate.is_some() {
+                    Self::require_high_trust(trust_level)?;
+                }
+                record.client_certificate.clone()
+            }
+        };
+        if script.is_some()
+            && (matches!(update.credentials, Some(Some(_)))
+                || client_certificate.is_some()
+                || request_template.is_some())
+        {
+            return Err(NovaError::validation_error(SCRIPT_ONLY_FIELDS));
+        }
+        if let Some(credentials) = update.credentials {
+            let sealed = self.seal_credentials(credentials.as_ref())?;
+            record.credential_headers = sealed
+                .as_ref()
+                .map(|(_, names)| names.clone())
+                .unwrap_or_default();
+            record.sealed_credentials = sealed.map(|(sealed, _)| sealed);
+        }
+        if let Some(redact) = update.redact {
+            record.redact = redact;
+        }
+        if let Some(allowed_contexts) = update.allowed_contexts {
+            record.allowed_contexts = allowed_contexts;
+        }
+        if let Some(tags) = update.tags {
+            record.tags = tags;
+        }
+        if let Some(coerce_arguments) = update.coerce_arguments {
+            record.coerce_arguments = coerce_arguments;
+        }
+        if let Some(listing) = update.listing {
+            let listing = listing.map(Self::submitted);
+            // An unchanged entry keeps its review, a flag outlives changes
+            if record.listing.clone().map(Self::submitted) != listing {
+                let flagged = record
+                    .listing
+                    .as_ref()
+                    .is_some_and(|listing| listing.flagged);
+                record.listing = listing.map(|listing| PluginListing { flagged, ..listing });
+            }
+        }
+        // Approval covers the endpoint or script that was reviewed
+        if endpoint_url != previous_version.endpoint_url || script != previous_version.script {
+            if let Some(listing) = record.listing.as_mut() {
+                listing.approved = false;
+            }
+        }
+        record.trust_level = trust_level;
+        let certificate_changed = record.client_certificate != client_certificate;
+        record.client_certificate = client_certificate;
 
         let version_record = PluginVersionRecord {
             version: new_version,
@@ -196,17 +705,25 @@ impl PluginManager {
             input_schema,
             output_schema,
             endpoint_url,
+            script,
+            request_template,
+            schema_changes,
             created_at: now,
         };
 
         record.updated_at = now;
+        record.revision += 1;
         record.versions.push(version_record.clone());
 
-        let stored = record.clone();
-        drop(plugins);
-
-        self.persist_plugin(&stored)?;
+        self.persist_plugin(&record)?;
+        *entry = record.clone();
+        drop(entry);
+        if certificate_changed {
+            self.mtls_clients.remove(&plugin_id);
+        }
+        let stored = record;
         self.insert_fq_mapping(&version_record, plugin_id);
+        self.announce(RegistryChange::Plugin(plugin_id));
 
         Ok(Self::to_metadata(&stored, &version_record))
     }
@@ -215,28 +732,30 @@ impl PluginManager {
         &self,
         context: &RequestContext,
     ) -> Result<Vec<PluginMetadata>> {
-        let plugins = self
+        // `is_enabled` reads `plugins` again, so no shard guard may be held
+        // across it: a writer queued on that shard would block the read.
+        let candidates: Vec<(u64, bool)> = self
             .plugins
-            .read()
-            .map_err(|_| NovaError::internal("Plugin registry lock poisoned"))?;
+            .iter()
+            .map(|entry| {
+                let record = entry.value();
+                let owner_match = record.context_type == context.context_type
+                    && record.context_id == context.context_id;
+                (record.plugin_id, owner_match)
+            })
+            .collect();
 
         let mut result = Vec::new();
-        for record in plugins.values() {
-            let owner_match = record.context_type == context.context_type
-                && record.context_id == context.context_id;
-            let enabled = if owner_match {
-                true
-            } else {
-                self.is_enabled(
-                    record.plugin_id,
-                    context.context_type.clone(),
-                    &context.context_id,
-                )?
-            };
-
-            if owner_match || enabled {
+        for (plugin_id, owner_match) in candidates {
+            let enabled = owner_match
+                || self.is_enabled(plugin_id, context.context_type.clone(), &context.context_id)?;
+            if !enabled {
+                continue;
+            }
+            // Skips plugins deleted since the scan
+            if let Some(record) = self.plugins.get(&plugin_id) {
                 if let Some(version) = record.versions.last() {
-                    result.push(Self::to_metadata(record, version));
+                    result.push(Self::to_metadata(&record, version));
                 }
             }
         }
@@ -245,12 +764,9 @@ impl PluginManager {
     }
 
     pub fn list_plugins(&self) -> Result<Vec<PluginMetadata>> {
-        let plugins = self
-            .plugins
-            .read()
-            .map_err(|_| NovaError::internal("Plugin registry lock poisoned"))?;
         let mut result = Vec::new();
-        for record in plugins.values() {
+        for entry in self.plugins.iter() {
+            let record = entry.value();
             if let Some(version) = record.versions.last() {
                 result.push(Self::to_metadata(record, version));
             }
@@ -259,36 +775,50 @@ impl PluginManager {
     }
 
     pub fn get_plugin(&self, plugin_id: u64) -> Result<PluginMetadata> {
-        let plugins = self
+        let record = self
             .plugins
-            .read()
-            .map_err(|_| NovaError::internal("Plugin registry lock poisoned"))?;
-        let record = plugins
             .get(&plugin_id)
             .ok_or_else(|| NovaError::plugin_not_found(plugin_id))?;
         let version = record
             .versions
             .last()
             .ok_or_else(|| NovaError::internal("Plugin record has no versions"))?;
-        Ok(Self::to_metadata(record, version))
+        Ok(Self::to_metadata(&record, version))
+    }
+
+    /// The plugin if `context` owns it or has it enabled; other plugins are
+    /// reported as not found, as if they did not exist.
+    pub fn get_plugin_for_context(
+        &self,
+        context: &RequestContext,
+        plugin_id: u64,
+    ) -> Result<PluginMetadata> {
+        let metadata = self.get_plugin(plugin_id)?;
+        let owner_match = metadata.context_type == context.context_type
+            && metadata.context_id == context.context_id;
+        if owner_match
+            || self.is_enabled(plugin_id, context.context_type.clone(), &context.context_id)?
+        {
+            Ok(metadata)
+        } else {
+            Err(NovaError::plugin_not_found(plugin_id))
+        }
+    }
+
+    /// Health of the plugin's endpoint, as recorded under `plugin:<fq_name>`.
+    pub fn plugin_health(&self, metadata: &PluginMetadata) -> UpstreamStatus {
+        self.health.status(&format!("plugin:{}", metadata.fq_name))
     }
 
     pub fn get_plugin_by_fq_name(&self, fq_name: &str) -> Result<PluginMetadata> {
-        let fq_index = self
+        let (plugin_id, version) = self
             .fq_index
-            .read()
-            .map_err(|_| NovaError::internal("Plugin registry lock poisoned"))?;
-        let (plugin_id, version) = fq_index
             .get(fq_name)
-            .cloned()
+            .map(|entry| *entry.value())
             .ok_or_else(|| NovaError::api_error("Unknown tool"))?;
-        drop(fq_index);
 
-        let plugins = self
+        let record = self
             .plugins
-            .read()
-            .map_err(|_| NovaError::internal("Plugin registry lock poisoned"))?;
-        let record = plugins
             .get(&plugin_id)
             .ok_or_else(|| NovaError::plugin_not_found(plugin_id))?;
         let version = record
@@ -296,136 +826,1338 @@ impl PluginManager {
             .iter()
             .find(|v| v.version == version)
             .ok_or_else(|| NovaError::internal("Version index out of sync"))?;
-        Ok(Self::to_metadata(record, version))
+        Ok(Self::to_metadata(&record, version))
     }
 
-    pub fn set_enablement(&self, request: PluginEnableRequest) -> Result<PluginEnablementStatus> {
-        self.ensure_plugin_exists(request.plugin_id)?;
+    /// Loaded plugin count, after checking the stored registry is still readable.
+    pub fn check_registry(&self) -> Result<usize> {
+        self.metadata_tree.first().map_err(NovaError::from)?;
+        Ok(self.plugins.len())
+    }
 
-        match request.context_type {
-            PluginContextType::User => self.set_user_enablement(&request),
-            PluginContextType::Group => self.set_group_enablement(&request),
+    pub fn stats(&self) -> RegistryStats {
+        let tree_len = |ty: PluginContextType| self.enablement_tree(&ty).map_or(0, sled::Tree::len);
+        let mut stats = RegistryStats {
+            user_enablements: tree_len(PluginContextType::User),
+            group_enablements: tree_len(PluginContextType::Group),
+            channel_enablements: tree_len(PluginContextType::Channel),
+            organization_enablements: tree_len(PluginContextType::Organization),
+            ..RegistryStats::default()
+        };
+        // Plugins enabled for at least one context; unreadable records are skipped
+        let mut enabled = HashSet::new();
+        for context_type in PluginContextType::ALL {
+            let Some(tree) = self.enablement_tree(&context_type) else {
+                continue;
+            };
+            for (key, value) in tree.iter().flatten() {
+                let Ok(record) = serde_json::from_slice::<UserPluginRecord>(&value) else {
+                    continue;
+                };
+                if record.enabled {
+                    stats.enabled_records += 1;
+                    let plugin_id = str::from_utf8(&key)
+                        .ok()
+                        .and_then(|key| key.rsplit_once('|'))
+                        .and_then(|(_, id)| id.parse::<u64>().ok());
+                    enabled.extend(plugin_id);
+                } else {
+                    stats.disabled_records += 1;
+                }
+            }
         }
+        for context_type in PluginContextType::ALL {
+            stats
+                .by_context_type
+                .insert(context_type.as_str().to_string(), 0);
+        }
+        for trust_level in PluginTrustLevel::ALL {
+            stats
+                .by_trust_level
+                .insert(trust_level.as_str().to_string(), 0);
+        }
+        for status in ["enabled", "not_enabled"] {
+            stats.by_status.insert(status.to_string(), 0);
+        }
+        for entry in self.plugins.iter() {
+            let record = entry.value();
+            stats.plugins += 1;
+            stats.versions += record.versions.len();
+            stats.archived_versions += record.versions.len().saturating_sub(1);
+            match record.context_type {
+                PluginContextType::User => stats.user_owned += 1,
+                PluginContextType::Group => stats.group_owned += 1,
+                PluginContextType::Channel => stats.channel_owned += 1,
+                PluginContextType::Organization => stats.organization_owned += 1,
+            }
+            let status = if enabled.contains(&record.plugin_id) {
+                "enabled"
+            } else {
+                "not_enabled"
+            };
+            *stats
+                .by_context_type
+                .entry(record.context_type.as_str().to_string())
+                .or_default() += 1;
+            *stats
+                .by_trust_level
+                .entry(record.trust_level.as_str().to_string())
+                .or_default() += 1;
+            *stats.by_status.entry(status.to_string()).or_default() += 1;
+        }
+        stats
     }
 
-    pub fn is_enabled(
-        &self,
-        plugin_id: u64,
-        context_type: PluginContextType,
-        context_id: &str,
-    ) -> Result<bool> {
-        match context_type {
-            PluginContextType::User => self.read_user_enablement(context_id, plugin_id),
-            PluginContextType::Group => self.read_group_enablement(context_id, plugin_id),
+    pub fn snapshot(&self) -> Result<RegistrySnapshot> {
+        let mut plugins: Vec<StoredPluginRecord> = self
+            .plugins
+            .iter()
+            .map(|entry| {
+                let mut record = entry.value().clone();
+                // Backups never carry a private key in the clear
+                if let Some(certificate) = record.client_certificate.as_mut() {
+                    certificate.key_pem = None;
+                }
+                record
+            })
+            .collect();
+        plugins.sort_by_key(|record| record.plugin_id);
+        Ok(RegistrySnapshot {
+            taken_at: self.clock.timestamp(),
+            plugins,
+            user_enablements: Self::dump_tree(Some(&self.user_tree))?,
+            group_enablements: Self::dump_tree(Some(&self.group_tree))?,
+            channel_enablements: Self::dump_tree(self.channel_tree.as_ref())?,
+            organization_enablements: Self::dump_tree(self.organization_tree.as_ref())?,
+        })
+    }
+
+    fn dump_tree(tree: Option<&sled::Tree>) -> Result<std::collections::BTreeMap<String, Value>> {
+        let mut entries = std::collections::BTreeMap::new();
+        for item in tree.into_iter().flat_map(sled::Tree::iter) {
+            let (key, value) = item.map_err(NovaError::from)?;
+            let key = String::from_utf8_lossy(&key).into_owned();
+            entries.insert(key, serde_json::from_slice(&value)?);
         }
+        Ok(entries)
     }
 
-    pub async fn invoke_plugin(
-        &self,
-        metadata: &PluginMetadata,
-        caller: &RequestContext,
-        arguments: Value,
-    ) -> Result<Value> {
-        if caller.context_type == metadata.context_type && caller.context_id == metadata.context_id
-        {
-            // owner always enabled
-        } else if !self.is_enabled(
-            metadata.plugin_id,
-            caller.context_type.clone(),
-            &caller.context_id,
-        )? {
-            return Err(NovaError::plugin_not_enabled(
-                metadata.plugin_id,
-                Self::context_type_label(&caller.context_type),
-                caller.context_id.clone(),
-            ));
+    /// Removes every plugin the context owns and every enablement record it
+    /// holds. Returns the removed plugin ids and the context's record count.
+    pub fn purge_context(&self, context: &RequestContext) -> Result<(Vec<u64>, usize)> {
+        self.ensure_writable()?;
+        let mut owned: Vec<u64> = self
+            .plugins
+            .iter()
+            .filter(|entry| {
+                entry.context_type == context.context_type && entry.context_id == context.context_id
+            })
+            .map(|entry| *entry.key())
+            .collect();
+        owned.sort_unstable();
+        let mut enablements = 0;
+        if let Some(tree) = self.enablement_tree(&context.context_type) {
+            let prefix = format!("{}|", context.context_id);
+            let mut keys_to_remove = Vec::new();
+            for item in tree.scan_prefix(prefix.as_bytes()) {
+                let (key, _) = item.map_err(NovaError::from)?;
+                // Opaque ids may contain '|', so compare the whole id part
+                let key_str = String::from_utf8_lossy(&key);
+                if let Some((id, plugin_id)) = key_str.rsplit_once('|') {
+                    if id == context.context_id {
+                        let plugin_id = plugin_id.parse::<u64>().ok();
+                        keys_to_remove.push((key.clone(), plugin_id));
+                    }
+                }
+            }
+            enablements = keys_to_remove.len();
+            for (key, plugin_id) in keys_to_remove {
+                tree.remove(key).map_err(NovaError::from)?;
+                if let Some(plugin_id) = plugin_id {
+                    self.announce(RegistryChange::Enablement {
+                        context_type: context.context_type.clone(),
+                        context_id: context.context_id.clone(),
+                        plugin_id,
+                    });
+                }
+            }
+            tree.flush().map_err(NovaError::from)?;
+        }
+        if let Some(tree) = &self.activity_tree {
+            let owner = context.key();
+            for item in tree.iter() {
+                let (key, _) = item.map_err(NovaError::from)?;
+                if String::from_utf8_lossy(&key)
+                    .split_once('|')
+                    .map(|(_, who)| who)
+                    == Some(owner.as_str())
+                {
+                    tree.remove(key).map_err(NovaError::from)?;
+                }
+            }
+        }
+        for plugin_id in &owned {
+            self.unregister_plugin(context, *plugin_id)?;
         }
+        Ok((owned, enablements))
+    }
 
-        self.validate_instance(&metadata.input_schema, &arguments, "arguments")?;
+    pub fn set_enablement(&self, request: PluginEnableRequest) -> Result<PluginEnablementStatus> {
+        self.ensure_writable()?;
+        self.ensure_plugin_exists(request.plugin_id)?;
+        self.id_format
+            .validate(&request.context_type, &request.context_id)
+            .map_err(NovaError::validation_error)?;
+        if request.enable && !self.allows_context(request.plugin_id, &request.context_type) {
+            return Err(NovaError::validation_error(format!(
+                "This plugin cannot be enabled for a {}",
+                request.context_type
+            )));
+        }
 
-        let payload = PluginInvocationPayload {
-            context_type: caller.context_type.clone(),
-            context_id: caller.context_id.clone(),
-            arguments,
+        let tree = self.enablement_tree(&request.context_type).ok_or_else(|| {
+            NovaError::validation_error(format!(
+                "{} contexts are not configured",
+                request.context_type
+            ))
+        })?;
+        let key = Self::context_key(&request.context_id, request.plugin_id);
+        let now = self.clock.timestamp();
+        let mut record = match tree.get(&key).map_err(NovaError::from)? {
+            Some(value) => serde_json::from_slice::<GroupPluginRecord>(&value)?,
+            None => GroupPluginRecord {
+                enabled: false,
+                added_by: None,
+                consent_ts: now,
+            },
         };
 
-        let response = self
-            .http_client
-            .post(&metadata.endpoint_url)
-            .json(&payload)
-            .send()
-            .await
-            .map_err(NovaError::from)?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(NovaError::api_error(format!(
-                "Plugin endpoint returned {}: {}",
-                status, body
-            )));
+        if request.enable {
+            // Shared contexts record who consented on the context's behalf
+            if request.context_type != PluginContextType::User {
+                let added_by = request.added_by.clone().ok_or_else(|| {
+                    NovaError::validation_error(format!(
+                        "added_by is required when enabling a plugin for a {}",
+                        request.context_type
+                    ))
+                })?;
+                record.added_by = Some(added_by);
+            }
+            record.enabled = true;
+            record.consent_ts = now;
+        } else {
+            record.enabled = false;
         }
 
-        let json = response.json().await.map_err(NovaError::from)?;
-        if let Some(schema) = &metadata.output_schema {
-            self.validate_instance(schema, &json, "response")?;
-        }
-        Ok(json)
+        Self::write_enablement(tree, &request.context_type, key, &record)?;
+        self.announce(RegistryChange::Enablement {
+            context_type: request.context_type.clone(),
+            context_id: request.context_id.clone(),
+            plugin_id: request.plugin_id,
+        });
+
+        Ok(PluginEnablementStatus {
+            context_type: request.context_type,
+            context_id: request.context_id,
+            plugin_id: request.plugin_id,
+            enabled: record.enabled,
+            consent_ts: record.consent_ts,
+            added_by: record.added_by,
+        })
     }
 
-    fn validate_registration(&self, request: &PluginRegistrationRequest) -> Result<()> {
+    /// The stored enablement of the plugin for one context. A context that
+    /// never enabled it, or whose type the plugin does not allow, reads as
+    /// disabled; `consent_ts` is 0 when nothing was stored.
+    pub fn enablement(
+        &self,
+        plugin_id: u64,
+        context_type: PluginContextType,
+        context_id: &str,
+    ) -> Result<PluginEnablementStatus> {
+        self.ensure_plugin_exists(plugin_id)?;
+        self.id_format
+            .validate(&context_type, context_id)
+            .map_err(NovaError::validation_error)?;
+        let record = match self.enablement_tree(&context_type) {
+            Some(tree) => match tree
+                .get(Self::context_key(context_id, plugin_id))
+                .map_err(NovaError::from)?
+            {
+                Some(bytes) => Some(serde_json::from_slice::<GroupPluginRecord>(&bytes)?),
+                None => None,
+            },
+            None => None,
+        };
+        let allowed = self.allows_context(plugin_id, &context_type);
+        Ok(PluginEnablementStatus {
+            context_type,
+            context_id: context_id.to_string(),
+            plugin_id,
+            enabled: allowed && record.as_ref().is_some_and(|record| record.enabled),
+            consent_ts: record.as_ref().map_or(0, |record| record.consent_ts),
+            added_by: record.and_then(|record| record.added_by),
+        })
+    }
+
+    pub fn is_enabled(
+        &self,
+        plugin_id: u64,
+        context_type: PluginContextType,
+        context_id: &str,
+    ) -> Result<bool> {
+        let Some(tree) = self.enablement_tree(&context_type) else {
+            return Ok(false);
+        };
+        if !self.allows_context(plugin_id, &context_type) {
+            return Ok(false);
+        }
+        let key = Self::context_key(context_id, plugin_id);
+        match tree.get(&key).map_err(NovaError::from)? {
+            // User records lack `added_by`, which the group shape treats as absent
+            Some(bytes) => Ok(serde_json::from_slice::<GroupPluginRecord>(&bytes)?.enabled),
+            None => Ok(false),
+        }
+    }
+
+    /// Whether the plugin's `allowed_contexts` admit `context_type`. Unknown
+    /// plugins pass; callers report them separately.
+    fn allows_context(&self, plugin_id: u64, context_type: &PluginContextType) -> bool {
+        self.plugins.get(&plugin_id).is_none_or(|record| {
+            record.allowed_contexts.is_empty() || record.allowed_contexts.contains(context_type)
+        })
+    }
+
+    /// Fills in `installs` and `last_used_at`, which take a pass over the
+    /// enablement and activity trees and so are left out of `get_plugin`.
+    pub fn annotate_usage(&self, plugins: &mut [PluginMetadata]) {
+        let installs = self.install_counts();
+        let last_used = self.last_used_times();
+        for metadata in plugins {
+            metadata.installs = installs.get(&metadata.plugin_id).copied().unwrap_or(0);
+            metadata.last_used_at = last_used.get(&metadata.plugin_id).copied();
+        }
+    }
+
+    /// Plugins registered before `cutoff` and not called since, never-called
+    /// ones first, then oldest last use.
+    pub fn stale_plugins(&self, cutoff: i64) -> Result<Vec<PluginMetadata>> {
+        let mut plugins = self.list_plugins()?;
+        self.annotate_usage(&mut plugins);
+        plugins.retain(|metadata| {
+            metadata.created_at < cutoff && metadata.last_used_at.is_none_or(|at| at < cutoff)
+        });
+        plugins.sort_by_key(|metadata| (metadata.last_used_at, metadata.plugin_id));
+        Ok(plugins)
+    }
+
+    /// Approved listings matching `query`, most installed first.
+    pub fn marketplace(&self, query: &MarketplaceQuery) -> Result<Vec<MarketplaceEntry>> {
+        let installs = self.install_counts();
+        let last_used = self.last_used_times();
+        let needle = query.q.as_deref().map(str::to_lowercase);
+        let mut entries = Vec::new();
+        for entry in self.plugins.iter() {
+            let record = entry.value();
+            let Some(listing) = record.listing.as_ref().filter(|listing| listing.approved) else {
+                continue;
+            };
+            let Some(version) = record.versions.last() else {
+                continue;
+            };
+            if let Some(category) = &query.category {
+                if !listing.categories.contains(category) {
+                    continue;
+                }
+            }
+            if let Some(needle) = &needle {
+                let matches = record.name.to_lowercase().contains(needle)
+                    || record.description.to_lowercase().contains(needle);
+                if !matches {
+                    continue;
+                }
+            }
+            entries.push(MarketplaceEntry {
+                plugin_id: record.plugin_id,
+                name: record.name.clone(),
+                description: record.description.clone(),
+                publisher: record.owner_id.clone(),
+                version: version.version,
+                trust_level: record.trust_level,
+                categories: listing.categories.clone(),
+                icon_url: listing.icon_url.clone(),
+                installs: installs.get(&record.plugin_id).copied().unwrap_or(0),
+                last_used_at: last_used.get(&record.plugin_id).copied(),
+                ratings: 0,
+                average_stars: None,
+                input_schema: version.input_schema.clone(),
+                updated_at: record.updated_at,
+            });
+        }
+        entries.sort_by(|a, b| {
+            b.installs
+                .cmp(&a.installs)
+                .then(a.plugin_id.cmp(&b.plugin_id))
+        });
+        Ok(entries)
+    }
+
+    /// Plugins whose owners asked to be listed, reviewed or not, for admins.
+    pub fn listed_plugins(&self) -> Vec<PluginMetadata> {
+        let mut listed: Vec<PluginMetadata> = self
+            .plugins
+            .iter()
+            .filter(|entry| entry.value().listing.is_some())
+            .filter_map(|entry| {
+                let record = entry.value();
+                record
+                    .versions
+                    .last()
+                    .map(|version| Self::to_metadata(record, version))
+            })
+            .collect();
+        listed.sort_by_key(|metadata| metadata.plugin_id);
+        listed
+    }
+
+    /// Approves or rejects a plugin's marketplace listing; either way clears
+    /// a flag from reports.
+    pub fn review_listing(&self, plugin_id: u64, approved: bool) -> Result<PluginMetadata> {
+        self.ensure_writable()?;
+        let mut record = self
+            .plugins
+            .get_mut(&plugin_id)
+            .ok_or_else(|| NovaError::plugin_not_found(plugin_id))?;
+        let listing = record
+            .listing
+            .as_mut()
+            .ok_or_else(|| NovaError::validation_error("The plugin has not asked to be listed"))?;
+        listing.approved = approved;
+        listing.flagged = false;
+        record.revision += 1;
+        let stored = record.clone();
+        drop(record);
+        self.persist_plugin(&stored)?;
+        let version = stored
+            .versions
+            .last()
+            .ok_or_else(|| NovaError::internal("Plugin record has no versions"))?;
+        Ok(Self::to_metadata(&stored, version))
+    }
+
+    /// Withdraws an approved listing pending admin review. Returns whether the
+    /// listing was approved before.
+    pub fn flag_listing(&self, plugin_id: u64) -> Result<bool> {
+        self.ensure_writable()?;
+        let mut record = self
+            .plugins
+            .get_mut(&plugin_id)
+            .ok_or_else(|| NovaError::plugin_not_found(plugin_id))?;
+        let Some(listing) = record.listing.as_mut().filter(|listing| listing.approved) else {
+            return Ok(false);
+        };
+        listing.approved = false;
+        listing.flagged = true;
+        record.revision += 1;
+        let stored = record.clone();
+        drop(record);
+        self.persist_plugin(&stored)?;
+        Ok(true)
+    }
+
+    /// Enables an approved marketplace plugin for `context`, with the acting
+    /// user as `added_by`. Unlisted plugins are reported as not found.
+    pub fn install_listed(
+        &self,
+        plugin_id: u64,
+        context: &RequestContext,
+    ) -> Result<PluginEnablementStatus> {
+        let approved = self.plugins.get(&plugin_id).is_some_and(|record| {
+            record
+                .listing
+                .as_ref()
+                .is_some_and(|listing| listing.approved)
+        });
+        if !approved {
+            return Err(NovaError::plugin_not_found(plugin_id));
+        }
+        self.set_enablement(PluginEnableRequest {
+            context_type: context.context_type.clone(),
+            context_id: context.context_id.clone(),
+            plugin_id,
+            enable: true,
+            added_by: context.actor_id.clone(),
+        })
+    }
+
+    /// Enabled records per plugin, leaving out each owner's own record.
+    fn install_counts(&self) -> HashMap<u64, usize> {
+        let mut counts = HashMap::new();
+        for context_type in PluginContextType::ALL {
+            let Some(tree) = self.enablement_tree(&context_type) else {
+                continue;
+            };
+            for (key, value) in tree.iter().flatten() {
+                let Ok(record) = serde_json::from_slice::<UserPluginRecord>(&value) else {
+                    continue;
+                };
+                let Some((context_id, plugin_id)) = str::from_utf8(&key)
+                    .ok()
+                    .and_then(|key| key.rsplit_once('|'))
+                    .and_then(|(context_id, id)| Some((context_id, id.parse::<u64>().ok()?)))
+                else {
+                    continue;
+                };
+                let owned = self.plugins.get(&plugin_id).is_some_and(|plugin| {
+                    plugin.context_type == context_type && plugin.context_id == context_id
+                });
+                if record.enabled && !owned {
+                    *counts.entry(plugin_id).or_default() += 1;
+                }
+            }
+        }
+        counts
+    }
+
+    /// Latest call per plugin from any context.
+    fn last_used_times(&self) -> HashMap<u64, i64> {
+        let mut times: HashMap<u64, i64> = HashMap::new();
+        let Some(tree) = &self.activity_tree else {
+            return times;
+        };
+        for (key, value) in tree.iter().flatten() {
+            let Some(plugin_id) = str::from_utf8(&key)
+                .ok()
+                .and_then(|key| key.split_once('|'))
+                .and_then(|(id, _)| id.parse::<u64>().ok())
+            else {
+                continue;
+            };
+            let Ok(at) = <[u8; 8]>::try_from(value.as_ref()).map(i64::from_be_bytes) else {
+                continue;
+            };
+            let latest = times.entry(plugin_id).or_insert(at);
+            *latest = (*latest).max(at);
+        }
+        times
+    }
+
+    /// Records a call that reached the plugin's endpoint. Not flushed: losing
+    /// the last moments before a crash only makes a plugin look older.
+    fn record_use(&self, plugin_id: u64, caller: &RequestContext) {
+        let Some(tree) = &self.activity_tree else {
+            return;
+        };
+        let key = format!("{:020}|{}", plugin_id, caller.key());
+        let now = self.clock.timestamp().to_be_bytes();
+        if let Err(e) = tree.insert(key.as_bytes(), &now) {
+            tracing::warn!("Failed to record use of plugin {}: {}", plugin_id, e);
+        }
+    }
+
+    pub async fn invoke_plugin(
+        &self,
+        metadata: &PluginMetadata,
+        caller: &RequestContext,
+        arguments: Value,
+    ) -> Result<Value> {
+        let arguments = self.prepare_call(metadata, caller, arguments)?;
+        match self.send_call(metadata, caller, arguments, None).await? {
+            PluginAnswer::Ready(result) => Ok(result),
+            // Only calls sent with a job read `202 Accepted` as deferred
+            PluginAnswer::Accepted(_) => {
+                Err(NovaError::internal("Plugin deferred a synchronous call"))
+            }
+        }
+    }
+
+    /// Checks the caller may use the plugin and returns `arguments`, coerced
+    /// when the plugin opted in, once they fit its input schema.
+    pub(crate) fn prepare_call(
+        &self,
+        metadata: &PluginMetadata,
+        caller: &RequestContext,
+        mut arguments: Value,
+    ) -> Result<Value> {
+        if caller.context_type == metadata.context_type && caller.context_id == metadata.context_id
+        {
+            // owner always enabled
+        } else if !self.is_enabled(
+            metadata.plugin_id,
+            caller.context_type.clone(),
+            &caller.context_id,
+        )? {
+            return Err(NovaError::plugin_not_enabled(
+                metadata.plugin_id,
+                caller.context_type.to_string(),
+                caller.context_id.clone(),
+            ));
+        }
+
+        if metadata.coerce_arguments {
+            schema::coerce_arguments(&metadata.input_schema, &mut arguments);
+        }
+        self.validators
+            .validate(&metadata.fq_name, &metadata.input_schema, &arguments)?;
+        Ok(arguments)
+    }
+
+    /// Sends a call [`prepare_call`](Self::prepare_call) accepted. With a
+    /// `job`, the plugin may answer `202 Accepted` and deliver the result later.
+    pub(crate) async fn send_call(
+        &self,
+        metadata: &PluginMetadata,
+        caller: &RequestContext,
+        arguments: Value,
+        job: Option<PluginJobTicket>,
+    ) -> Result<PluginAnswer> {
+        if let Some(script) = &metadata.script {
+            return self
+                .run_script(metadata, script, caller, arguments)
+                .await
+                .map(PluginAnswer::Ready);
+        }
+        let deferrable = job.is_some();
+        let payload = PluginInvocationPayload {
+            context_type: caller.context_type.clone(),
+            context_id: caller.context_id.clone(),
+            actor_id: caller.actor_id.clone(),
+            arguments,
+            job,
+        };
+        let body = match &metadata.request_template {
+            Some(template) => RequestTemplate::parse(template)
+                .map_err(|e| {
+                    NovaError::internal(format!("Invalid stored request template: {}", e))
+                })?
+                .render(&serde_json::to_value(&payload)?),
+            None => serde_json::to_value(&payload)?,
+        };
+
+        // Re-checked per call: the rules or the host's DNS may have changed
+        let url = self
+            .egress
+            .check_url(&metadata.endpoint_url, metadata.trust_level)?;
+        self.egress.check_resolved(&url).await?;
+
+        let client = if metadata.mutual_tls {
+            self.mtls_client(metadata.plugin_id)?
+        } else {
+            self.http_client.clone()
+        };
+
+        let mut request = client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(credentials) = self.credentials(metadata.plugin_id)? {
+            request = Self::apply_credentials(request, credentials);
+        }
+        let body = serde_json::to_vec(&body)?;
+        let request_bytes = body.len() as u64;
+        let started = Instant::now();
+        let mut response_bytes = 0;
+        let result = self
+            .exchange(
+                request.body(body),
+                metadata,
+                caller,
+                started,
+                &mut response_bytes,
+                deferrable,
+            )
+            .await;
+        self.record_use(metadata.plugin_id, caller);
+        self.meter(
+            metadata,
+            caller,
+            started,
+            request_bytes,
+            response_bytes as u64,
+            result.is_ok(),
+        );
+        result
+    }
+
+    /// Runs a scripted plugin, then checks and redacts its result as an
+    /// endpoint's answer would be. Metered like an endpoint call, with the
+    /// arguments and the result as the request and response.
+    async fn run_script(
+        &self,
+        metadata: &PluginMetadata,
+        script: &PluginScript,
+        caller: &RequestContext,
+        arguments: Value,
+    ) -> Result<Value> {
+        let ast = self.compiled_script(&metadata.fq_name, script)?;
+        let request_bytes = serde_json::to_vec(&arguments)?.len() as u64;
+        let started = Instant::now();
+        let run = ScriptRun {
+            limits: self.script_limits.clone(),
+            egress: self.egress.clone(),
+            client: self.http_client.clone(),
+            trust_level: metadata.trust_level,
+        };
+        let result = run
+            .run(ast, arguments, caller)
+            .await
+            .and_then(|result| self.accept_result(metadata, result));
+        self.health.record(
+            &format!("plugin:{}", metadata.fq_name),
+            started.elapsed(),
+            result.as_ref().err().map(ToString::to_string),
+        );
+        self.record_use(metadata.plugin_id, caller);
+        let response_bytes = match &result {
+            Ok(value) => serde_json::to_vec(value)?.len() as u64,
+            Err(_) => 0,
+        };
+        self.meter(
+            metadata,
+            caller,
+            started,
+            request_bytes,
+            response_bytes,
+            result.is_ok(),
+        );
+        result
+    }
+
+    /// The compiled form of a version's script, compiled on first use.
+    fn compiled_script(&self, fq_name: &str, script: &PluginScript) -> Result<Arc<rhai::AST>> {
+        if let Some(entry) = self.scripts.get(fq_name) {
+            // A deleted and re-registered plugin may reuse the fq_name
+            if entry.0 == script.source {
+                return Ok(Arc::clone(&entry.1));
+            }
+        }
+        let ast = Arc::new(self.script_limits.compile(script)?);
+        self.scripts.insert(
+            fq_name.to_string(),
+            (script.source.clone(), Arc::clone(&ast)),
+        );
+        Ok(ast)
+    }
+
+    fn meter(
+        &self,
+        metadata: &PluginMetadata,
+        caller: &RequestContext,
+        started: Instant,
+        request_bytes: u64,
+        response_bytes: u64,
+        success: bool,
+    ) {
+        if let Some(metering) = &self.metering {
+            metering.record(UsageEvent {
+                id: uuid::Uuid::new_v4().to_string(),
+                at: self.clock.timestamp(),
+                context: caller.key(),
+                actor_id: caller.actor_id.clone(),
+                plugin_id: metadata.plugin_id,
+                plugin: metadata.fq_name.clone(),
+                owner: format!("{}:{}", metadata.context_type, metadata.context_id),
+                duration_ms: started.elapsed().as_millis() as u64,
+                request_bytes,
+                response_bytes,
+                success,
+            });
+        }
+    }
+
+    /// Asks the plugin behind an async job for its result at `poll_url`.
+    /// Polls are not metered; the call that started the job was.
+    pub(crate) async fn poll_job(
+        &self,
+        metadata: &PluginMetadata,
+        caller: &RequestContext,
+        poll_url: &str,
+    ) -> Result<PluginAnswer> {
+        let url = self.egress.check_url(poll_url, metadata.trust_level)?;
+        self.egress.check_resolved(&url).await?;
+        let client = if metadata.mutual_tls {
+            self.mtls_client(metadata.plugin_id)?
+        } else {
+            self.http_client.clone()
+        };
+        let mut request = client.get(url);
+        if let Some(credentials) = self.credentials(metadata.plugin_id)? {
+            request = Self::apply_credentials(request, credentials);
+        }
+        self.exchange(request, metadata, caller, Instant::now(), &mut 0, true)
+            .await
+    }
+
+    /// Checks a result posted to an async job's callback as a direct answer
+    /// would be: against the output schema, then redacted.
+    pub(crate) fn accept_result(
+        &self,
+        metadata: &PluginMetadata,
+        mut result: Value,
+    ) -> Result<Value> {
+        if let Some(schema) = &metadata.output_schema {
+            self.validate_instance(schema, &result, "response")?;
+        }
+        self.plugin_redaction(metadata)?.apply(&mut result);
+        Ok(result)
+    }
+
+    /// Sends the call and decodes the answer; `response_bytes` is the size of
+    /// whatever body came back. Unless `deferrable`, `202 Accepted` is read
+    /// like any other success.
+    async fn exchange(
+        &self,
+        request: reqwest::RequestBuilder,
+        metadata: &PluginMetadata,
+        caller: &RequestContext,
+        started: Instant,
+        response_bytes: &mut usize,
+        deferrable: bool,
+    ) -> Result<PluginAnswer> {
+        let upstream = format!("plugin:{}", metadata.fq_name);
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                self.health
+                    .record(&upstream, started.elapsed(), Some(e.to_string()));
+                return Err(NovaError::from(e));
+            }
+        };
+        let elapsed = started.elapsed();
+        let status = response.status();
+        let failure = (status.as_u16() == 429 || status.is_server_error())
+            .then(|| format!("HTTP {}", status.as_u16()));
+        self.health.record(&upstream, elapsed, failure);
+        if elapsed >= SLOW_PLUGIN_THRESHOLD {
+            tracing::warn!("Plugin {} took {:?}", metadata.fq_name, elapsed);
+            logging::log(
+                LogLevel::Warning,
+                "plugins",
+                json!({
+                    "message": "Plugin responded slowly",
+                    "plugin": metadata.fq_name,
+                    "actor": caller.actor_id,
+                    "elapsedMs": elapsed.as_millis() as u64
+                }),
+            );
+        }
+
+        let redaction = self.plugin_redaction(metadata)?;
+        if !status.is_success() {
+            let bytes = response.bytes().await.unwrap_or_default();
+            *response_bytes = bytes.len();
+            let mut body = String::from_utf8_lossy(&bytes).into_owned();
+            if let Ok(mut json) = serde_json::from_str::<Value>(&body) {
+                if redaction.apply(&mut json) > 0 {
+                    body = json.to_string();
+                }
+            }
+            return Err(NovaError::api_error(format!(
+                "Plugin endpoint returned {}: {}",
+                status, body
+            )));
+        }
+
+        if deferrable && status == reqwest::StatusCode::ACCEPTED {
+            let location = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            let bytes = response.bytes().await.map_err(NovaError::from)?;
+            *response_bytes = bytes.len();
+            let mut accepted: JobAcceptance = serde_json::from_slice(&bytes).unwrap_or_default();
+            accepted.poll_url = accepted.poll_url.or(location);
+            return Ok(PluginAnswer::Accepted(accepted));
+        }
+
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok());
+        let mut json = match StreamFormat::of(content_type) {
+            Some(format) => Self::read_stream(response, format, &redaction, response_bytes).await?,
+            None => {
+                let bytes = response.bytes().await.map_err(NovaError::from)?;
+                *response_bytes = bytes.len();
+                serde_json::from_slice(&bytes)?
+            }
+        };
+        if let Some(schema) = &metadata.output_schema {
+            self.validate_instance(schema, &json, "response")?;
+        }
+        // After schema validation, which sees the real values
+        redaction.apply(&mut json);
+        Ok(PluginAnswer::Ready(json))
+    }
+
+    /// Reads a streamed answer chunk by chunk, passing each on (redacted) as
+    /// a progress notification, and returns the last one unredacted.
+    async fn read_stream(
+        mut response: reqwest::Response,
+        format: StreamFormat,
+        redaction: &RedactionRules,
+        response_bytes: &mut usize,
+    ) -> Result<Value> {
+        let mut decoder = ChunkDecoder::new(format);
+        let mut last = None;
+        let mut forward = |chunk: Value| {
+            let mut shown = chunk.clone();
+            redaction.apply(&mut shown);
+            match &shown {
+                Value::String(text) => progress::report(text),
+                other => progress::report(&other.to_string()),
+            }
+            last = Some(chunk);
+        };
+        while let Some(bytes) = response.chunk().await.map_err(NovaError::from)? {
+            *response_bytes += bytes.len();
+            decoder.push(&bytes).into_iter().for_each(&mut forward);
+        }
+        decoder.finish().into_iter().for_each(&mut forward);
+        last.ok_or_else(|| NovaError::api_error("Plugin stream ended without any chunk"))
+    }
+
+    /// Parses a registration body, reporting missing fields, unknown trust
+    /// levels or context types and every failed check together as
+    /// [`NovaError::InvalidFields`].
+    pub async fn parse_registration(&self, mut body: Value) -> Result<PluginRegistrationRequest> {
+        let mut problems = FieldProblems::default();
+        problems.require(&mut body, "name", json!(""));
+        problems.require(&mut body, "description", json!(""));
+        problems.require(&mut body, "input_schema", json!({}));
+        if body.get("script").is_none_or(Value::is_null) {
+            problems.require(&mut body, "endpoint_url", json!(""));
+        }
+        problems.known::<PluginTrustLevel>(&mut body, "trust_level", "standard or high");
+        problems.known_items::<PluginContextType>(
+            &mut body,
+            "allowed_contexts",
+            "user, group, channel or organization",
+        );
+        match problems.deserialize::<PluginRegistrationRequest>(body) {
+            Some(mut request) => {
+                self.bundle_into(&mut request, &mut problems).await;
+                self.check_registration(&request, &mut problems);
+                problems.finish().map(|()| request)
+            }
+            None => Err(problems.into_error()),
+        }
+    }
+
+    /// Inlines the remote `$ref`s of a registration's schemas, which
+    /// [`register_plugin`](Self::register_plugin) would otherwise reject; see
+    /// [`SchemaRefs`].
+    pub async fn bundle_registration(&self, request: &mut PluginRegistrationRequest) -> Result<()> {
+        let mut problems = FieldProblems::default();
+        self.bundle_into(request, &mut problems).await;
+        problems.finish()
+    }
+
+    /// [`bundle_registration`](Self::bundle_registration) for an update's
+    /// schemas.
+    pub async fn bundle_update(&self, update: &mut PluginUpdateRequest) -> Result<()> {
+        let mut problems = FieldProblems::default();
+        if let Some(schema) = update.input_schema.as_mut() {
+            self.bundle_schema(schema, "input_schema", &mut problems)
+                .await;
+        }
+        if let Some(Some(schema)) = update.output_schema.as_mut() {
+            self.bundle_schema(schema, "output_schema", &mut problems)
+                .await;
+        }
+        problems.finish()
+    }
+
+    async fn bundle_into(
+        &self,
+        request: &mut PluginRegistrationRequest,
+        problems: &mut FieldProblems,
+    ) {
+        self.bundle_schema(&mut request.input_schema, "input_schema", problems)
+            .await;
+        if let Some(schema) = request.output_schema.as_mut() {
+            self.bundle_schema(schema, "output_schema", problems).await;
+        }
+    }
+
+    async fn bundle_schema(&self, schema: &mut Value, field: &str, problems: &mut FieldProblems) {
+        match self
+            .schema_refs
+            .bundle(schema, &self.egress, &self.http_client)
+            .await
+        {
+            Ok(bundled) => *schema = bundled,
+            Err(err) => problems.check(field, Err(err)),
+        }
+    }
+
+    pub fn validate_registration(&self, request: &PluginRegistrationRequest) -> Result<()> {
+        let mut problems = FieldProblems::default();
+        self.check_registration(request, &mut problems);
+        problems.finish()
+    }
+
+    fn check_registration(
+        &self,
+        request: &PluginRegistrationRequest,
+        problems: &mut FieldProblems,
+    ) {
         if request.name.trim().is_empty() {
-            return Err(NovaError::validation_error("Plugin name cannot be empty"));
+            problems.add("name", "Plugin name cannot be empty");
         }
         if request.description.trim().is_empty() {
-            return Err(NovaError::validation_error(
-                "Plugin description cannot be empty",
-            ));
+            problems.add("description", "Plugin description cannot be empty");
         }
-        if request.endpoint_url.trim().is_empty() {
-            return Err(NovaError::validation_error(
-                "Plugin endpoint cannot be empty",
-            ));
+        if let Some(script) = &request.script {
+            if !request.endpoint_url.trim().is_empty() {
+                problems.add("endpoint_url", "Scripted plugins have no endpoint");
+            }
+            if request.credentials.is_some()
+                || request.client_certificate.is_some()
+                || request.request_template.is_some()
+            {
+                problems.add("script", SCRIPT_ONLY_FIELDS);
+            }
+            problems.check("script", self.script_limits.compile(script).map(|_| ()));
+        } else if request.endpoint_url.trim().is_empty() {
+            problems.add("endpoint_url", "Plugin endpoint cannot be empty");
+        } else {
+            problems.check(
+                "endpoint_url",
+                self.egress
+                    .check_url(&request.endpoint_url, request.trust_level)
+                    .map(|_| ()),
+            );
         }
-        if !request.endpoint_url.starts_with("https://") {
-            return Err(NovaError::validation_error(
-                "Plugin endpoint must use HTTPS",
-            ));
+        problems.check(
+            "client_certificate",
+            Self::validate_client_certificate(
+                request.client_certificate.as_ref(),
+                request.trust_level,
+            ),
+        );
+        problems.check("redact", Self::validate_redaction(&request.redact));
+        if let Some(template) = &request.request_template {
+            problems.check(
+                "request_template",
+                Self::validate_request_template(template),
+            );
         }
         if request.version == 0 {
-            return Err(NovaError::validation_error(
-                "Version must be greater than or equal to 1",
-            ));
+            problems.add("version", "Version must be greater than or equal to 1");
         }
-        self.validate_schema(&request.input_schema, "input_schema")?;
+        problems.check(
+            "input_schema",
+            self.validate_schema(&request.input_schema, "input_schema"),
+        );
         if let Some(schema) = &request.output_schema {
-            self.validate_schema(schema, "output_schema")?;
+            problems.check(
+                "output_schema",
+                self.validate_schema(schema, "output_schema"),
+            );
         }
-        Ok(())
+        if let Some(listing) = &request.listing {
+            Self::check_listing(listing, problems);
+        }
+        problems.check("tags", Self::validate_tags(&request.tags));
     }
 
     fn validate_update(&self, update: &PluginUpdateRequest) -> Result<()> {
+        let mut problems = FieldProblems::default();
+        if let Some(redact) = &update.redact {
+            problems.check("redact", Self::validate_redaction(redact));
+        }
+        if let Some(Some(template)) = &update.request_template {
+            problems.check(
+                "request_template",
+                Self::validate_request_template(template),
+            );
+        }
         if let Some(schema) = &update.input_schema {
-            self.validate_schema(schema, "input_schema")?;
+            problems.check("input_schema", self.validate_schema(schema, "input_schema"));
         }
         if let Some(Some(schema)) = &update.output_schema {
-            self.validate_schema(schema, "output_schema")?;
+            problems.check(
+                "output_schema",
+                self.validate_schema(schema, "output_schema"),
+            );
+        }
+        if let Some(Some(listing)) = &update.listing {
+            Self::check_listing(listing, &mut problems);
+        }
+        if let Some(tags) = &update.tags {
+            problems.check("tags", Self::validate_tags(tags));
         }
         if let Some(endpoint) = &update.endpoint_url {
             if endpoint.trim().is_empty() {
-                return Err(NovaError::validation_error(
-                    "Plugin endpoint cannot be empty",
-                ));
+                problems.add("endpoint_url", "Plugin endpoint cannot be empty");
             }
-            if !endpoint.starts_with("https://") {
-                return Err(NovaError::validation_error(
-                    "Plugin endpoint must use HTTPS",
-                ));
+        }
+        if let Some(script) = &update.script {
+            problems.check("script", self.script_limits.compile(script).map(|_| ()));
+        }
+        problems.finish()
+    }
+
+    fn check_listing(listing: &PluginListing, problems: &mut FieldProblems) {
+        if listing.categories.len() > MAX_LISTING_CATEGORIES {
+            problems.add(
+                "listing.categories",
+                format!(
+                    "A listing may name at most {} categories",
+                    MAX_LISTING_CATEGORIES
+                ),
+            );
+        }
+        if let Some(category) = listing
+            .categories
+            .iter()
+            .find(|category| !is_slug(category))
+        {
+            problems.add(
+                "listing.categories",
+                format!(
+                    "Invalid listing category '{}': use a lowercase slug (a-z, 0-9, -) of up to 32 chars",
+                    category
+                ),
+            );
+        }
+        if let Some(icon_url) = &listing.icon_url {
+            let https = reqwest::Url::parse(icon_url).is_ok_and(|url| url.scheme() == "https");
+            if !https {
+                problems.add("listing.icon_url", "Listing icon_url must be an https URL");
             }
         }
+    }
+
+    fn validate_tags(tags: &[String]) -> Result<()> {
+        if tags.len() > MAX_TAGS {
+            return Err(NovaError::validation_error(format!(
+                "A plugin may have at most {} tags",
+                MAX_TAGS
+            )));
+        }
+        match tags.iter().find(|tag| !is_slug(tag)) {
+            Some(tag) => Err(NovaError::validation_error(format!(
+                "Invalid tag '{}': use a lowercase slug (a-z, 0-9, -) of up to 32 chars",
+                tag
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// A listing as its owner submitted it: not yet reviewed or flagged.
+    fn submitted(listing: PluginListing) -> PluginListing {
+        PluginListing {
+            approved: false,
+            flagged: false,
+            ..listing
+        }
+    }
+
+    fn validate_client_certificate(
+        certificate: Option<&PluginClientCertificate>,
+        trust_level: PluginTrustLevel,
+    ) -> Result<()> {
+        let Some(certificate) = certificate else {
+            return Ok(());
+        };
+        Self::require_high_trust(trust_level)?;
+        Self::client_identity(certificate).map(|_| ())
+    }
+
+    fn require_high_trust(trust_level: PluginTrustLevel) -> Result<()> {
+        if trust_level != PluginTrustLevel::High {
+            return Err(NovaError::validation_error(
+                "Client certificates are only available to high trust plugins",
+            ));
+        }
         Ok(())
     }
 
+    /// Seals the certificate's private key; like credentials, certificates
+    /// need `plugins.secrets_key`.
+    fn seal_client_certificate(
+        &self,
+        certificate: &PluginClientCertificate,
+    ) -> Result<StoredClientCertificate> {
+        Ok(StoredClientCertificate {
+            cert_pem: certificate.cert_pem.clone(),
+            sealed_key_pem: Some(self.seal_key_pem(&certificate.key_pem)?),
+            key_pem: None,
+        })
+    }
+
+    fn seal_key_pem(&self, key_pem: &str) -> Result<String> {
+        let secrets = self.secrets.as_ref().ok_or_else(|| {
+            NovaError::validation_error("Client certificates require plugins.secrets_key")
+        })?;
+        secrets.seal(key_pem.as_bytes())
+    }
+
+    fn open_client_certificate(
+        &self,
+        certificate: &StoredClientCertificate,
+    ) -> Result<PluginClientCertificate> {
+        let key_pem = match (&certificate.sealed_key_pem, &certificate.key_pem) {
+            (Some(sealed), _) => {
+                let secrets = self.secrets.as_ref().ok_or_else(|| {
+                    NovaError::internal(
+                        "Client certificate keys are stored but plugins.secrets_key is unset",
+                    )
+                })?;
+                String::from_utf8(secrets.open(sealed)?)
+                    .map_err(|_| NovaError::internal("Malformed client certificate key"))?
+            }
+            (None, Some(key_pem)) => key_pem.clone(),
+            (None, None) => {
+                return Err(NovaError::internal("Client certificate has no key"));
+            }
+        };
+        Ok(PluginClientCertificate {
+            cert_pem: certificate.cert_pem.clone(),
+            key_pem,
+        })
+    }
+
+    fn client_identity(certificate: &PluginClientCertificate) -> Result<reqwest::Identity> {
+        reqwest::Identity::from_pkcs8_pem(
+            certificate.cert_pem.as_bytes(),
+            certificate.key_pem.as_bytes(),
+        )
+        .map_err(|e| NovaError::validation_error(format!("Invalid client certificate: {}", e)))
+    }
+
+    /// The plugin's own client, presenting its certificate. Built under the
+    /// record's read guard so a concurrent certificate change cannot leave a
+    /// stale client cached.
+    fn mtls_client(&self, plugin_id: u64) -> Result<Client> {
+        let record = self
+            .plugins
+            .get(&plugin_id)
+            .ok_or_else(|| NovaError::plugin_not_found(plugin_id))?;
+        if let Some(client) = self.mtls_clients.get(&plugin_id) {
+            return Ok(client.clone());
+        }
+        let certificate = record
+            .client_certificate
+            .as_ref()
+            .ok_or_else(|| NovaError::internal("Plugin has no client certificate"))?;
+        let builder = outbound::client_builder(&self.outbound, "plugins")?;
+        let client = self
+            .egress
+            .configure(builder)
+            .identity(Self::client_identity(
+                &self.open_client_certificate(certificate)?,
+            )?)
+            .build()?;
+        self.mtls_clients.insert(plugin_id, client.clone());
+        Ok(client)
+    }
+
+    /// Validates and encrypts credentials, returning the sealed value and the
+    /// header names they set.
+    fn seal_credentials(
+        &self,
+        credentials: Option<&PluginCredentials>,
+    ) -> Result<Option<(String, Vec<String>)>> {
+        let Some(credentials) = credentials else {
+            return Ok(None);
+        };
+        let secrets = self.secrets.as_ref().ok_or_else(|| {
+            NovaError::validation_error("Plugin credentials require plugins.secrets_key")
+        })?;
+        let mut headers: Vec<(&str, &str)> = credentials
+            .headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        match &credentials.auth {
+            Some(PluginAuth::Bearer { token }) => headers.push(("authorization", token)),
+            Some(PluginAuth::Basic { username, .. }) if username.contains(':') => {
+                return Err(NovaError::validation_error(
+                    "Basic auth usernames cannot contain ':'",
+                ))
+            }
+            Some(PluginAuth::ApiKey { header, value }) => headers.push((header, value)),
+            _ => {}
+        }
+        for (name, value) in headers {
+            let valid = reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_ok()
+                && reqwest::header::HeaderValue::from_str(value).is_ok();
+            if !valid {
+                return Err(NovaError::validation_error(format!(
+                    "Invalid plugin header {}",
+                    name
+                )));
+            }
+            if RESERVED_HEADERS.contains(&name.to_lowercase().as_str()) {
+                return Err(NovaError::validation_error(format!(
+                    "Plugin header {} is set by Nova",
+                    name
+                )));
+            }
+        }
+        let plaintext = serde_json::to_vec(credentials).map_err(NovaError::from)?;
+        Ok(Some((
+            secrets.seal(&plaintext)?,
+            credentials.header_names(),
+        )))
+    }
+
+    fn credentials(&self, plugin_id: u64) -> Result<Option<PluginCredentials>> {
+        let sealed = match self.plugins.get(&plugin_id) {
+            Some(record) => record.sealed_credentials.clone(),
+            None => return Err(NovaError::plugin_not_found(plugin_id)),
+        };
+        let Some(sealed) = sealed else {
+            return Ok(None);
+        };
+        let secrets = self.secrets.as_ref().ok_or_else(|| {
+            NovaError::internal("Plugin credentials are stored but plugins.secrets_key is unset")
+        })?;
+        let plaintext = secrets.open(&sealed)?;
+        Ok(Some(serde_json::from_slice(&plaintext)?))
+    }
+
+    /// Header values were checked when the credentials were sealed.
+    fn apply_credentials(
+        mut request: reqwest::RequestBuilder,
+        credentials: PluginCredentials,
+    ) -> reqwest::RequestBuilder {
+        for (name, value) in &credentials.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        match credentials.auth {
+            Some(PluginAuth::Bearer { token }) => request.bearer_auth(token),
+            Some(PluginAuth::Basic { username, password }) => {
+                request.basic_auth(username, Some(password))
+            }
+            Some(PluginAuth::ApiKey { header, value }) => request.header(header, value),
+            None => request,
+        }
+    }
+
+    fn validate_redaction(patterns: &[String]) -> Result<()> {
+        RedactionRules::parse(patterns)
+            .map(|_| ())
+            .map_err(|e| NovaError::validation_error(format!("Invalid redaction rule {}", e)))
+    }
+
+    fn validate_request_template(template: &Value) -> Result<()> {
+        RequestTemplate::parse(template)
+            .map(|_| ())
+            .map_err(|e| NovaError::validation_error(format!("Invalid request_template: {}", e)))
+    }
+
+    /// `plugins.redact` plus the plugin's own rules.
+    fn plugin_redaction(&self, metadata: &PluginMetadata) -> Result<RedactionRules> {
+        let mut rules = RedactionRules::parse(&metadata.redact)
+            .map_err(|e| NovaError::internal(format!("Invalid stored redaction rule {}", e)))?;
+        rules.extend(&self.redaction);
+        Ok(rules)
+    }
+
     fn validate_schema(&self, schema: &Value, label: &str) -> Result<()> {
         if !schema.is_object() {
             return Err(NovaError::validation_error(format!(
@@ -433,60 +2165,33 @@ impl PluginManager {
                 label
             )));
         }
-        JSONSchema::options()
-            .with_draft(Draft::Draft7)
-            .compile(schema)
-            .map_err(|err| {
-                NovaError::validation_error(format!(
-                    "{} is not a valid JSON schema: {}",
-                    label, err
-                ))
-            })?;
-        Ok(())
+        schema::compile(schema).map_err(|err| {
+            NovaError::validation_error(format!("{} is not a valid JSON schema: {}", label, err))
+        })?;
+        schema::check_refs(schema)
+            .map_err(|err| NovaError::validation_error(format!("{}: {}", label, err)))
     }
 
     fn validate_instance(&self, schema: &Value, instance: &Value, label: &str) -> Result<()> {
-        let compiled = JSONSchema::options()
-            .with_draft(Draft::Draft7)
-            .compile(schema)
-            .map_err(|err| {
-                NovaError::validation_error(format!("{} schema compilation failed: {}", label, err))
-            })?;
-        if let Err(errors) = compiled.validate(instance) {
-            let messages: Vec<String> = errors.map(|e| e.to_string()).collect();
+        let compiled = schema::compile(schema).map_err(|err| {
+            NovaError::validation_error(format!("{} schema compilation failed: {}", label, err))
+        })?;
+        let errors = schema::field_errors(&compiled, instance);
+        if !errors.is_empty() {
+            let draft = schema::SchemaDraft::detect(schema).unwrap_or_default();
+            let messages: Vec<String> = errors.into_iter().map(|e| e.message).collect();
             return Err(NovaError::validation_error(format!(
-                "{} failed validation: {}",
+                "{} failed JSON Schema {} validation: {}",
                 label,
+                draft,
                 messages.join(", ")
             )));
         }
         Ok(())
     }
 
-    fn ensure_unique_name(
-        plugins: &HashMap<u64, StoredPluginRecord>,
-        context: &RequestContext,
-        name: &str,
-    ) -> Result<()> {
-        for record in plugins.values() {
-            if record.context_type == context.context_type
-                && record.context_id == context.context_id
-                && record.name.eq_ignore_ascii_case(name)
-            {
-                return Err(NovaError::validation_error(
-                    "A tool with this name already exists for the context",
-                ));
-            }
-        }
-        Ok(())
-    }
-
     fn ensure_unique_fq_name(&self, fq_name: &str) -> Result<()> {
-        let fq_index = self
-            .fq_index
-            .read()
-            .map_err(|_| NovaError::internal("Plugin registry lock poisoned"))?;
-        if fq_index.contains_key(fq_name) {
+        if self.fq_index.contains_key(fq_name) {
             return Err(NovaError::validation_error(
                 "A tool with this version already exists",
             ));
@@ -495,16 +2200,18 @@ impl PluginManager {
     }
 
     fn insert_fq_mapping(&self, version: &PluginVersionRecord, plugin_id: u64) {
-        if let Ok(mut map) = self.fq_index.write() {
-            map.insert(version.fq_name.clone(), (plugin_id, version.version));
-        }
+        self.fq_index
+            .insert(version.fq_name.clone(), (plugin_id, version.version));
+        // The schema passed validation already; a failure here resurfaces on the first call
+        let _ = self
+            .validators
+            .prepare(&version.fq_name, &version.input_schema);
     }
 
     fn remove_fq_mappings(&self, record: &StoredPluginRecord) {
-        if let Ok(mut map) = self.fq_index.write() {
-            for version in &record.versions {
-                map.remove(&version.fq_name);
-            }
+        for version in &record.versions {
+            self.fq_index.remove(&version.fq_name);
+            self.validators.remove(&version.fq_name);
         }
     }
 
@@ -518,44 +2225,49 @@ impl PluginManager {
     }
 
     fn ensure_owner_enablement(&self, record: &StoredPluginRecord) -> Result<()> {
-        match record.context_type {
-            PluginContextType::User => {
-                let key = Self::context_key(&record.context_id, record.plugin_id);
-                let now = Utc::now().timestamp();
-                let user_record = UserPluginRecord {
-                    enabled: true,
-                    consent_ts: now,
-                };
-                let encoded = serde_json::to_vec(&user_record).map_err(NovaError::from)?;
-                self.user_tree
-                    .insert(key, encoded)
-                    .map_err(NovaError::from)?;
-                self.user_tree.flush().map_err(NovaError::from)?;
-            }
-            PluginContextType::Group => {
-                let key = Self::context_key(&record.context_id, record.plugin_id);
-                let now = Utc::now().timestamp();
-                let group_record = GroupPluginRecord {
-                    enabled: true,
-                    added_by: None,
-                    consent_ts: now,
-                };
-                let encoded = serde_json::to_vec(&group_record).map_err(NovaError::from)?;
-                self.group_tree
-                    .insert(key, encoded)
-                    .map_err(NovaError::from)?;
-                self.group_tree.flush().map_err(NovaError::from)?;
-            }
+        // Owners are always enabled; the record only feeds stats and backups
+        let Some(tree) = self.enablement_tree(&record.context_type) else {
+            return Ok(());
+        };
+        let key = Self::context_key(&record.context_id, record.plugin_id);
+        let owner_record = GroupPluginRecord {
+            enabled: true,
+            added_by: None,
+            consent_ts: self.clock.timestamp(),
+        };
+        Self::write_enablement(tree, &record.context_type, key, &owner_record)
+    }
+
+    fn enablement_tree(&self, context_type: &PluginContextType) -> Option<&sled::Tree> {
+        match context_type {
+            PluginContextType::User => Some(&self.user_tree),
+            PluginContextType::Group => Some(&self.group_tree),
+            PluginContextType::Channel => self.channel_tree.as_ref(),
+            PluginContextType::Organization => self.organization_tree.as_ref(),
         }
+    }
+
+    /// User records keep their original shape without `added_by`.
+    fn write_enablement(
+        tree: &sled::Tree,
+        context_type: &PluginContextType,
+        key: Vec<u8>,
+        record: &GroupPluginRecord,
+    ) -> Result<()> {
+        let encoded = match context_type {
+            PluginContextType::User => serde_json::to_vec(&UserPluginRecord {
+                enabled: record.enabled,
+                consent_ts: record.consent_ts,
+            })?,
+            _ => serde_json::to_vec(record)?,
+        };
+        tree.insert(key, encoded).map_err(NovaError::from)?;
+        tree.flush().map_err(NovaError::from)?;
         Ok(())
     }
 
     fn ensure_plugin_exists(&self, plugin_id: u64) -> Result<()> {
-        let plugins = self
-            .plugins
-            .read()
-            .map_err(|_| NovaError::internal("Plugin registry lock poisoned"))?;
-        if plugins.contains_key(&plugin_id) {
+        if self.plugins.contains_key(&plugin_id) {
             Ok(())
         } else {
             Err(NovaError::plugin_not_found(plugin_id))
@@ -563,8 +2275,9 @@ impl PluginManager {
     }
 
     fn load_plugins(tree: &sled::Tree) -> Result<LoadedPluginState> {
-        let mut plugins: PluginStore = HashMap::new();
-        let mut index: PluginIndex = HashMap::new();
+        let plugins: PluginStore = DashMap::new();
+        let index: PluginIndex = DashMap::new();
+        let names: NameIndex = DashMap::new();
         let mut max_id = 0u64;
         for item in tree.iter() {
             let entry = item.map_err(NovaError::from)?;
@@ -581,122 +2294,21 @@ impl PluginManager {
             if plugin_id >= max_id {
                 max_id = plugin_id + 1;
             }
+            names.insert(
+                Self::name_key(&record.context_type, &record.context_id, &record.name),
+                plugin_id,
+            );
             plugins.insert(plugin_id, record);
         }
-        Ok((plugins, index, max_id.max(1)))
-    }
-
-    fn read_user_enablement(&self, context_id: &str, plugin_id: u64) -> Result<bool> {
-        let key = Self::context_key(context_id, plugin_id);
-        let value = self.user_tree.get(&key).map_err(NovaError::from)?;
-        if let Some(bytes) = value {
-            let record: UserPluginRecord =
-                serde_json::from_slice(&bytes).map_err(NovaError::from)?;
-            Ok(record.enabled)
-        } else {
-            Ok(false)
-        }
-    }
-
-    fn read_group_enablement(&self, context_id: &str, plugin_id: u64) -> Result<bool> {
-        let key = Self::context_key(context_id, plugin_id);
-        let value = self.group_tree.get(&key).map_err(NovaError::from)?;
-        if let Some(bytes) = value {
-            let record: GroupPluginRecord =
-                serde_json::from_slice(&bytes).map_err(NovaError::from)?;
-            Ok(record.enabled)
-        } else {
-            Ok(false)
-        }
+        Ok((plugins, index, names, max_id.max(1)))
     }
 
-    fn set_user_enablement(&self, request: &PluginEnableRequest) -> Result<PluginEnablementStatus> {
-        let key = Self::context_key(&request.context_id, request.plugin_id);
-        let now = Utc::now().timestamp();
-        let existing = self.user_tree.get(&key).map_err(NovaError::from)?;
-
-        let mut record = if let Some(value) = existing {
-            serde_json::from_slice::<UserPluginRecord>(&value).map_err(NovaError::from)?
-        } else {
-            UserPluginRecord {
-                enabled: false,
-                consent_ts: now,
-            }
-        };
-
-        if request.enable {
-            record.enabled = true;
-            record.consent_ts = now;
-        } else {
-            record.enabled = false;
-        }
-
-        let encoded = serde_json::to_vec(&record).map_err(NovaError::from)?;
-        self.user_tree
-            .insert(key, encoded)
-            .map_err(NovaError::from)?;
-        self.user_tree.flush().map_err(NovaError::from)?;
-
-        Ok(PluginEnablementStatus {
-            context_type: PluginContextType::User,
-            context_id: request.context_id.clone(),
-            plugin_id: request.plugin_id,
-            enabled: record.enabled,
-            consent_ts: record.consent_ts,
-            added_by: None,
-        })
-    }
-
-    fn set_group_enablement(
-        &self,
-        request: &PluginEnableRequest,
-    ) -> Result<PluginEnablementStatus> {
-        let key = Self::context_key(&request.context_id, request.plugin_id);
-        let now = Utc::now().timestamp();
-        let existing = self.group_tree.get(&key).map_err(NovaError::from)?;
-
-        let mut record = if let Some(value) = existing {
-            serde_json::from_slice::<GroupPluginRecord>(&value).map_err(NovaError::from)?
-        } else {
-            GroupPluginRecord {
-                enabled: false,
-                added_by: None,
-                consent_ts: now,
+    fn clear_plugin_entries(&self, plugin_id: u64) -> Result<()> {
+        for context_type in PluginContextType::ALL {
+            if let Some(tree) = self.enablement_tree(&context_type) {
+                self.clear_entries_for_tree(tree, plugin_id)?;
             }
-        };
-
-        if request.enable {
-            let added_by = request.added_by.clone().ok_or_else(|| {
-                NovaError::validation_error(
-                    "added_by is required when enabling a plugin for a group",
-                )
-            })?;
-            record.enabled = true;
-            record.added_by = Some(added_by);
-            record.consent_ts = now;
-        } else {
-            record.enabled = false;
         }
-
-        let encoded = serde_json::to_vec(&record).map_err(NovaError::from)?;
-        self.group_tree
-            .insert(key, encoded)
-            .map_err(NovaError::from)?;
-        self.group_tree.flush().map_err(NovaError::from)?;
-
-        Ok(PluginEnablementStatus {
-            context_type: PluginContextType::Group,
-            context_id: request.context_id.clone(),
-            plugin_id: request.plugin_id,
-            enabled: record.enabled,
-            consent_ts: record.consent_ts,
-            added_by: record.added_by.clone(),
-        })
-    }
-
-    fn clear_plugin_entries(&self, plugin_id: u64) -> Result<()> {
-        self.clear_entries_for_tree(&self.user_tree, plugin_id)?;
-        self.clear_entries_for_tree(&self.group_tree, plugin_id)?;
         Ok(())
     }
 
@@ -734,6 +2346,14 @@ impl PluginManager {
         }
     }
 
+    fn name_key(context_type: &PluginContextType, context_id: &str, name: &str) -> NameKey {
+        (
+            context_type.clone(),
+            context_id.to_string(),
+            name.to_ascii_lowercase(),
+        )
+    }
+
     fn context_key(context_id: &str, plugin_id: u64) -> Vec<u8> {
         format!("{}|{}", context_id, plugin_id).into_bytes()
     }
@@ -744,21 +2364,13 @@ impl PluginManager {
         name: &str,
         version: u32,
     ) -> String {
-        match context_type {
-            PluginContextType::User => {
-                format!("user_{}_{}_v{}", context_id, name, version)
-            }
-            PluginContextType::Group => {
-                format!("group_{}_{}_v{}", context_id, name, version)
-            }
-        }
-    }
-
-    fn context_type_label(context_type: &PluginContextType) -> String {
-        match context_type {
-            PluginContextType::User => "user".to_string(),
-            PluginContextType::Group => "group".to_string(),
-        }
+        format!(
+            "{}_{}_{}_v{}",
+            context_type,
+            escape_context_id(context_id),
+            name,
+            version
+        )
     }
 
     fn to_metadata(record: &StoredPluginRecord, version: &PluginVersionRecord) -> PluginMetadata {
@@ -774,8 +2386,31 @@ impl PluginManager {
             input_schema: version.input_schema.clone(),
             output_schema: version.output_schema.clone(),
             endpoint_url: version.endpoint_url.clone(),
+            script: version.script.clone(),
+            trust_level: record.trust_level,
+            mutual_tls: record.client_certificate.is_some(),
+            credential_headers: record.credential_headers.clone(),
+            redact: record.redact.clone(),
+            request_template: version.request_template.clone(),
+            listing: record.listing.clone(),
+            allowed_contexts: record.allowed_contexts.clone(),
+            tags: record.tags.clone(),
+            coerce_arguments: record.coerce_arguments,
+            installs: 0,
+            last_used_at: None,
+            revision: record.revision,
+            schema_changes: version.schema_changes.clone(),
+            schema_draft: schema::SchemaDraft::detect(&version.input_schema).unwrap_or_default(),
             created_at: record.created_at,
             updated_at: record.updated_at,
         }
     }
 }
+
+/// A lowercase slug (a-z, 0-9, -) of 1 to 32 chars: listing categories and tags.
+fn is_slug(value: &str) -> bool {
+    (1..=32).contains(&value.len())
+        && value
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
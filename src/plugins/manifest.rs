@@ -0,0 +1,209 @@
+//! `plugin.toml` / `plugin.json`: a plugin's definition kept in its own
+//! repository and registered with `POST /plugins/register-manifest` or
+//! `nova-cli register`.
+//!
+//! ```toml
+//! name = "weather"
+//! description = "Current weather for a city"
+//! version = 1
+//! scopes = ["user", "group"]    # context types that may enable it; all if absent
+//! tags = ["weather"]
+//!
+//! [endpoint]
+//! url = "https://weather.example.com/nova"
+//! trust_level = "standard"
+//! headers = { "x-team" = "forecasts" }
+//!
+//! [schemas.input]
+//! type = "object"
+//! required = ["city"]
+//! properties = { city = { type = "string" } }
+//!
+//! [auth]
+//! type = "bearer"
+//! token = "${WEATHER_TOKEN}"    # expanded by nova-cli from its environment
+//! ```
+//!
+//! A scripted plugin has a `[script]` table (`source`, and optionally
+//! `language`) in place of `[endpoint]`.
+//!
+//! Unknown keys are rejected so typos fail the registration instead of
+//! being dropped.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{NovaError, Result};
+
+use super::dto::{
+    PluginAuth, PluginContextType, PluginCredentials, PluginListing, PluginRegistrationRequest,
+    PluginScript, PluginTrustLevel,
+};
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PluginManifest {
+    pub name: String,
+    pub description: String,
+    #[serde(default = "default_version")]
+    pub version: u32,
+    #[serde(default)]
+    pub owner_id: Option<String>,
+    // One of `endpoint` and `script`
+    #[serde(default)]
+    pub endpoint: Option<ManifestEndpoint>,
+    #[serde(default)]
+    pub script: Option<PluginScript>,
+    pub schemas: ManifestSchemas,
+    /// Context types the plugin may be enabled in; empty allows all.
+    #[serde(default)]
+    pub scopes: Vec<PluginContextType>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub auth: Option<PluginAuth>,
+    #[serde(default)]
+    pub redact: Vec<String>,
+    #[serde(default)]
+    pub listing: Option<PluginListing>,
+    #[serde(default)]
+    pub coerce_arguments: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestEndpoint {
+    pub url: String,
+    #[serde(default)]
+    pub trust_level: PluginTrustLevel,
+    /// Sent with every call; stored encrypted like `[auth]`.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub request_template: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestSchemas {
+    pub input: Value,
+    #[serde(default)]
+    pub output: Option<Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestFormat {
+    Toml,
+    Json,
+}
+
+impl ManifestFormat {
+    /// TOML for `.toml` files, JSON otherwise.
+    pub fn from_path(path: &std::path::Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext == "toml" => ManifestFormat::Toml,
+            _ => ManifestFormat::Json,
+        }
+    }
+
+    /// From a request's `Content-Type`, or the body's first character
+    /// when the type names neither format.
+    pub fn detect(content_type: Option<&str>, body: &str) -> Self {
+        match content_type.map(str::to_ascii_lowercase) {
+            Some(ty) if ty.contains("toml") => ManifestFormat::Toml,
+            Some(ty) if ty.contains("json") => ManifestFormat::Json,
+            _ if body.trim_start().starts_with('{') => ManifestFormat::Json,
+            _ => ManifestFormat::Toml,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ManifestFormat::Toml => "application/toml",
+            ManifestFormat::Json => "application/json",
+        }
+    }
+}
+
+const fn default_version() -> u32 {
+    1
+}
+
+impl PluginManifest {
+    pub fn parse(text: &str, format: ManifestFormat) -> Result<Self> {
+        let parsed = match format {
+            ManifestFormat::Toml => toml::from_str(text).map_err(|e| e.to_string()),
+            ManifestFormat::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+        };
+        parsed.map_err(|e| NovaError::validation_error(format!("Invalid plugin manifest: {}", e)))
+    }
+
+    /// The registration this manifest describes; the registry validates it.
+    pub fn into_registration(self) -> PluginRegistrationRequest {
+        let endpoint = self.endpoint.unwrap_or_default();
+        let has_credentials = self.auth.is_some() || !endpoint.headers.is_empty();
+        let credentials = has_credentials.then_some(PluginCredentials {
+            headers: endpoint.headers,
+            auth: self.auth,
+        });
+        PluginRegistrationRequest {
+            name: self.name,
+            description: self.description,
+            owner_id: self.owner_id,
+            input_schema: self.schemas.input,
+            output_schema: self.schemas.output,
+            endpoint_url: endpoint.url,
+            script: self.script,
+            version: self.version,
+            trust_level: endpoint.trust_level,
+            client_certificate: None,
+            credentials,
+            redact: self.redact,
+            request_template: endpoint.request_template,
+            listing: self.listing,
+            allowed_contexts: self.scopes,
+            tags: self.tags,
+            coerce_arguments: self.coerce_arguments,
+        }
+    }
+}
+
+impl fmt::Debug for PluginManifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PluginManifest")
+            .field("name", &self.name)
+            .field("version", &self.version)
+            .field(
+                "endpoint",
+                &self.endpoint.as_ref().map(|endpoint| &endpoint.url),
+            )
+            .field("scripted", &self.script.is_some())
+            .field("scopes", &self.scopes)
+            .field("tags", &self.tags)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Replaces each `${NAME}` in `text` with `lookup(NAME)`, so secrets stay
+/// out of the committed manifest. Fails on the first name without a value.
+pub fn substitute_env(text: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 2..start + end];
+        let value = lookup(name).ok_or_else(|| {
+            NovaError::config_error(format!("Manifest references unset variable {}", name))
+        })?;
+        result.push_str(&rest[..start]);
+        result.push_str(&value);
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
@@ -1,15 +1,44 @@
 pub mod dto;
+pub mod egress;
+pub mod feedback;
 pub mod handler;
-mod helpers;
+pub(crate) mod helpers;
+pub mod jobs;
 pub mod manager;
+pub mod manifest;
+pub mod redaction;
+pub mod schema_refs;
+pub mod script;
+pub mod secrets;
+mod stream;
+pub mod template;
+mod validation;
+pub mod webhooks;
 
 pub use dto::{
-    ErrorResponse, PluginContextType, PluginEnableRequest, PluginEnablementStatus,
-    PluginInvocationPayload, PluginInvocationRequest, PluginMetadata, PluginRegistrationRequest,
-    PluginUpdateRequest, PluginVersionRecord, RequestContext, StoredPluginRecord,
+    escape_context_id, unescape_context_id, validate_context_pair, ContextIdFormat,
+    EnablementQuery, ErrorResponse, InvocationQuery, JobCallback, JobStatus, JobWebhook,
+    MarketplaceEntry, MarketplaceQuery, PluginAuth, PluginClientCertificate, PluginContextType,
+    PluginCredentials, PluginDetails, PluginEnableRequest, PluginEnablementStatus,
+    PluginInvocationPayload, PluginInvocationRequest, PluginJob, PluginJobTicket, PluginListing,
+    PluginMetadata, PluginRating, PluginRatingRequest, PluginRegistrationRequest, PluginReport,
+    PluginReportRequest, PluginScript, PluginTrustLevel, PluginUpdateRequest, PluginVersionRecord,
+    RatingSummary, RegistryChange, RegistrySnapshot, RegistryStats, RequestContext, ScriptLanguage,
+    StoredClientCertificate, StoredPluginRecord, WebhookState,
 };
+pub use egress::EgressPolicy;
+pub use feedback::FeedbackStore;
 pub(crate) use handler::{
-    invoke_plugin, list_plugins, register_plugin, set_plugin_enablement, unregister_plugin,
-    update_plugin,
+    get_job, get_plugin, get_plugin_enablement, install_listed_plugin, invoke_plugin, job_callback,
+    list_marketplace, list_plugins, plugin_ratings, rate_plugin, register_manifest,
+    register_plugin, report_plugin, set_plugin_enablement, unregister_plugin, update_plugin,
 };
+pub use jobs::PluginJobs;
 pub use manager::PluginManager;
+pub use manifest::{ManifestFormat, PluginManifest};
+pub use redaction::RedactionRules;
+pub use schema_refs::SchemaRefs;
+pub use script::ScriptLimits;
+pub use secrets::SecretBox;
+pub use template::RequestTemplate;
+pub use webhooks::Webhooks;
@@ -0,0 +1,217 @@
+//! Redaction of plugin responses before they reach agents or logs.
+//!
+//! A rule is either a path (`$.data.token`, `$.items[*].email`, `$..secret`)
+//! or a field-name pattern (`password`, `*_token`) matched case-insensitively
+//! against object keys at any depth.
+
+use serde_json::Value;
+
+/// Replacement for every redacted value.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// One step of a `$` path; tool-call selectors share the grammar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Segment {
+    Key(String),
+    Index(usize),
+    Wildcard,
+    // `..name`: `name` at this level or anywhere below
+    Descendant(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Rule {
+    Path(Vec<Segment>),
+    Field(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RedactionRules {
+    rules: Vec<Rule>,
+}
+
+impl RedactionRules {
+    /// Fails with the first invalid pattern and why.
+    pub fn parse<S: AsRef<str>>(patterns: &[S]) -> Result<Self, String> {
+        let rules = patterns
+            .iter()
+            .map(|pattern| {
+                let pattern = pattern.as_ref().trim();
+                if pattern.starts_with('$') {
+                    parse_path(pattern)
+                        .map(Rule::Path)
+                        .map_err(|e| format!("{}: {}", pattern, e))
+                } else if pattern.is_empty() {
+                    Err("redaction patterns cannot be empty".to_string())
+                } else {
+                    Ok(Rule::Field(pattern.to_lowercase()))
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { rules })
+    }
+
+    pub fn extend(&mut self, other: &RedactionRules) {
+        self.rules.extend(other.rules.iter().cloned());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Replaces every matched value with [`REDACTED`]; returns how many.
+    pub fn apply(&self, value: &mut Value) -> usize {
+        self.rules
+            .iter()
+            .map(|rule| match rule {
+                Rule::Path(segments) => redact_path(value, segments),
+                Rule::Field(pattern) => redact_fields(value, pattern),
+            })
+            .sum()
+    }
+}
+
+fn parse_path(pattern: &str) -> Result<Vec<Segment>, String> {
+    let segments = parse_segments(pattern)?;
+    if segments.is_empty() {
+        return Err("the whole response cannot be redacted".to_string());
+    }
+    Ok(segments)
+}
+
+/// Parses everything after the leading `$`; a bare `$` yields no segments.
+pub(crate) fn parse_segments(pattern: &str) -> Result<Vec<Segment>, String> {
+    let mut rest = &pattern[1..];
+    let mut segments = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("..") {
+            let (name, tail) = split_name(after);
+            if name.is_empty() {
+                return Err("expected a field name after '..'".to_string());
+            }
+            segments.push(Segment::Descendant(name.to_string()));
+            rest = tail;
+        } else if let Some(after) = rest.strip_prefix('.') {
+            let (name, tail) = split_name(after);
+            segments.push(match name {
+                "" => return Err("expected a field name after '.'".to_string()),
+                "*" => Segment::Wildcard,
+                name => Segment::Key(name.to_string()),
+            });
+            rest = tail;
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let (inner, tail) = after
+                .split_once(']')
+                .ok_or_else(|| "unclosed '['".to_string())?;
+            let inner = inner.trim();
+            segments.push(if inner == "*" {
+                Segment::Wildcard
+            } else if let Ok(index) = inner.parse() {
+                Segment::Index(index)
+            } else {
+                let quoted = inner
+                    .strip_prefix('\'')
+                    .and_then(|s| s.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')));
+                Segment::Key(
+                    quoted
+                        .ok_or_else(|| format!("invalid selector [{}]", inner))?
+                        .to_string(),
+                )
+            });
+            rest = tail;
+        } else {
+            return Err(format!("unexpected '{}'", rest));
+        }
+    }
+    Ok(segments)
+}
+
+fn split_name(input: &str) -> (&str, &str) {
+    let end = input.find(['.', '[']).unwrap_or(input.len());
+    input.split_at(end)
+}
+
+fn redact_path(value: &mut Value, segments: &[Segment]) -> usize {
+    let Some((segment, rest)) = segments.split_first() else {
+        *value = Value::String(REDACTED.to_string());
+        return 1;
+    };
+    match (segment, value) {
+        (Segment::Key(key), Value::Object(map)) => {
+            map.get_mut(key).map_or(0, |child| redact_path(child, rest))
+        }
+        (Segment::Index(index), Value::Array(items)) => items
+            .get_mut(*index)
+            .map_or(0, |child| redact_path(child, rest)),
+        (Segment::Wildcard, Value::Object(map)) => {
+            map.values_mut().map(|child| redact_path(child, rest)).sum()
+        }
+        (Segment::Wildcard, Value::Array(items)) => {
+            items.iter_mut().map(|child| redact_path(child, rest)).sum()
+        }
+        (Segment::Descendant(key), value) => redact_descendants(value, key, rest),
+        _ => 0,
+    }
+}
+
+fn redact_descendants(value: &mut Value, key: &str, rest: &[Segment]) -> usize {
+    match value {
+        Value::Object(map) => map
+            .iter_mut()
+            .map(|(name, child)| {
+                if name == key {
+                    redact_path(child, rest)
+                } else {
+                    redact_descendants(child, key, rest)
+                }
+            })
+            .sum(),
+        Value::Array(items) => items
+            .iter_mut()
+            .map(|child| redact_descendants(child, key, rest))
+            .sum(),
+        _ => 0,
+    }
+}
+
+fn redact_fields(value: &mut Value, pattern: &str) -> usize {
+    match value {
+        Value::Object(map) => map
+            .iter_mut()
+            .map(|(name, child)| {
+                if glob_matches(pattern, &name.to_lowercase()) {
+                    *child = Value::String(REDACTED.to_string());
+                    1
+                } else {
+                    redact_fields(child, pattern)
+                }
+            })
+            .sum(),
+        Value::Array(items) => items
+            .iter_mut()
+            .map(|child| redact_fields(child, pattern))
+            .sum(),
+        _ => 0,
+    }
+}
+
+/// `*` matches any run of characters; everything else is literal.
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
@@ -0,0 +1,266 @@
+//! Remote `$ref`s in plugin schemas (`plugins.schema_ref_hosts`).
+//!
+//! Registration and updates fetch each referenced document once, from
+//! allowlisted hosts only, and inline it under `definitions` (`$defs` for
+//! 2019-09 and 2020-12 schemas), rewriting the refs to local pointers. The stored schema is then self-contained, so
+//! validating calls never touches the network:
+//!
+//! `{"$ref": "https://schemas.example.com/geo.json#/definitions/point"}`
+//! becomes `{"$ref": "#/definitions/https_schemas.example.com_geo.json/definitions/point"}`
+//! with the fetched document at `definitions["https_schemas.example.com_geo.json"]`.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use dashmap::DashMap;
+use reqwest::{Client, Url};
+use serde_json::{Map, Value};
+
+use crate::config::PluginsConfig;
+use crate::error::{NovaError, Result};
+use crate::schema::SchemaDraft;
+
+use super::egress::{host_matches, EgressPolicy};
+
+/// Documents one schema may pull in, counting those referenced by others.
+const MAX_DOCUMENTS: usize = 16;
+const MAX_DOCUMENT_BYTES: usize = 256 * 1024;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+/// Fetched documents kept for later registrations; the cache starts over
+/// when full.
+const MAX_CACHED: usize = 256;
+
+#[derive(Debug, Default)]
+pub struct SchemaRefs {
+    // Hosts ("schemas.example.com" or "*.example.com") refs may be fetched
+    // from; empty rejects every remote ref
+    hosts: Vec<String>,
+    cache: DashMap<String, Value>,
+}
+
+impl SchemaRefs {
+    pub fn new(cfg: &PluginsConfig) -> Self {
+        Self {
+            hosts: cfg
+                .schema_ref_hosts
+                .iter()
+                .map(|host| host.trim().to_lowercase())
+                .filter(|host| !host.is_empty())
+                .collect(),
+            cache: DashMap::new(),
+        }
+    }
+
+    /// `schema` with every remote `$ref` inlined; schemas without any are
+    /// returned unchanged. Relative refs resolve against the schema's `$id`.
+    pub async fn bundle(
+        &self,
+        schema: &Value,
+        egress: &EgressPolicy,
+        client: &Client,
+    ) -> Result<Value> {
+        let root_id = schema
+            .get("$id")
+            .and_then(Value::as_str)
+            .and_then(|id| Url::parse(id).ok())
+            .map(without_fragment);
+        // An unknown `$schema` is reported when the bundle is validated
+        let defs = SchemaDraft::detect(schema)
+            .unwrap_or_default()
+            .definitions_keyword();
+        let mut bundled = schema.clone();
+        let mut keys = BTreeMap::new();
+        let root = Bundle {
+            defs,
+            root_id: root_id.as_ref(),
+        };
+        rewrite(&mut bundled, root_id.as_ref(), None, &root, &mut keys)?;
+
+        let mut documents = Map::new();
+        let mut fetched = 0;
+        while let Some((url, key)) = unfetched(&keys, &documents) {
+            fetched += 1;
+            if fetched > MAX_DOCUMENTS {
+                return Err(NovaError::validation_error(format!(
+                    "A schema may reference at most {} remote documents",
+                    MAX_DOCUMENTS
+                )));
+            }
+            let url = Url::parse(&url).map_err(|e| NovaError::internal(e.to_string()))?;
+            let mut document = self.fetch(&url, egress, client).await?;
+            if let Some(object) = document.as_object_mut() {
+                object.remove("$id");
+            }
+            rewrite(&mut document, Some(&url), Some(&key), &root, &mut keys)?;
+            documents.insert(key, document);
+        }
+        if documents.is_empty() {
+            return Ok(bundled);
+        }
+        let Some(object) = bundled.as_object_mut() else {
+            return Ok(bundled);
+        };
+        let definitions = object
+            .entry(defs)
+            .or_insert_with(|| Value::Object(Map::new()));
+        let Some(definitions) = definitions.as_object_mut() else {
+            return Err(NovaError::validation_error(format!(
+                "{} must be an object to bundle remote $refs into",
+                defs
+            )));
+        };
+        definitions.extend(documents);
+        Ok(bundled)
+    }
+
+    async fn fetch(&self, url: &Url, egress: &EgressPolicy, client: &Client) -> Result<Value> {
+        if let Some(document) = self.cache.get(url.as_str()) {
+            return Ok(document.clone());
+        }
+        let host = url.host_str().unwrap_or_default().to_lowercase();
+        if !self
+            .hosts
+            .iter()
+            .any(|pattern| host_matches(pattern, &host))
+        {
+            return Err(NovaError::validation_error(format!(
+                "Remote $ref {} is not allowed; its host must be in plugins.schema_ref_hosts",
+                url
+            )));
+        }
+        egress.check_fetch(url).await?;
+        let failed = |reason: String| {
+            NovaError::validation_error(format!("Could not fetch $ref {}: {}", url, reason))
+        };
+        let response = client
+            .get(url.clone())
+            .timeout(FETCH_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| failed(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(failed(response.status().to_string()));
+        }
+        let bytes = response.bytes().await.map_err(|e| failed(e.to_string()))?;
+        if bytes.len() > MAX_DOCUMENT_BYTES {
+            return Err(failed(format!("larger than {} bytes", MAX_DOCUMENT_BYTES)));
+        }
+        let document: Value =
+            serde_json::from_slice(&bytes).map_err(|e| failed(format!("not JSON: {}", e)))?;
+        if self.cache.len() >= MAX_CACHED {
+            self.cache.clear();
+        }
+        self.cache.insert(url.to_string(), document.clone());
+        Ok(document)
+    }
+}
+
+/// What every rewritten ref in one bundle shares.
+struct Bundle<'a> {
+    // The keyword documents are inlined under
+    defs: &'static str,
+    root_id: Option<&'a Url>,
+}
+
+/// Points every `$ref` in `value` into the bundle. `base` resolves relative
+/// refs; `key` is the definitions entry `value` will live under, or `None`
+/// for the root schema. New remote documents are added to `keys`.
+fn rewrite(
+    value: &mut Value,
+    base: Option<&Url>,
+    key: Option<&str>,
+    bundle: &Bundle<'_>,
+    keys: &mut BTreeMap<String, String>,
+) -> Result<()> {
+    match value {
+        Value::Object(map) => {
+            for (name, child) in map.iter_mut() {
+                match (name.as_str(), child) {
+                    ("$ref", Value::String(reference)) => {
+                        *reference = local_ref(reference, base, key, bundle, keys)?;
+                    }
+                    ("enum" | "const" | "default" | "examples", _) => {}
+                    (_, child) => rewrite(child, base, key, bundle, keys)?,
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                rewrite(item, base, key, bundle, keys)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn local_ref(
+    reference: &str,
+    base: Option<&Url>,
+    key: Option<&str>,
+    bundle: &Bundle<'_>,
+    keys: &mut BTreeMap<String, String>,
+) -> Result<String> {
+    if let Some(fragment) = reference.strip_prefix('#') {
+        return Ok(match key {
+            Some(key) => format!("#/{}/{}{}", bundle.defs, key, fragment),
+            None => reference.to_string(),
+        });
+    }
+    let url = match base {
+        Some(base) => base.join(reference),
+        None => Url::parse(reference),
+    }
+    .map_err(|_| {
+        NovaError::validation_error(format!(
+            "$ref {} is relative; give the schema an absolute $id",
+            reference
+        ))
+    })?;
+    let fragment = url.fragment().unwrap_or_default().to_string();
+    let document = without_fragment(url);
+    if Some(&document) == bundle.root_id {
+        return Ok(format!("#{}", fragment));
+    }
+    let key = match keys.get(document.as_str()) {
+        Some(key) => key.clone(),
+        None => {
+            let mut key = definition_key(&document);
+            if keys.values().any(|taken| *taken == key) {
+                key = format!("{}_{}", key, keys.len());
+            }
+            keys.insert(document.to_string(), key.clone());
+            key
+        }
+    };
+    Ok(format!("#/{}/{}{}", bundle.defs, key, fragment))
+}
+
+/// The document URL with anything outside `[A-Za-z0-9._-]` replaced, so the
+/// key needs no escaping in a JSON pointer.
+fn definition_key(url: &Url) -> String {
+    let name: String = url
+        .as_str()
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' | '_' => c,
+            _ => '_',
+        })
+        .collect();
+    name.replacen("___", "_", 1)
+}
+
+/// A referenced document not fetched yet, as `(url, key)`.
+fn unfetched(
+    keys: &BTreeMap<String, String>,
+    documents: &Map<String, Value>,
+) -> Option<(String, String)> {
+    keys.iter()
+        .find(|(_, key)| !documents.contains_key(key.as_str()))
+        .map(|(url, key)| (url.clone(), key.clone()))
+}
+
+fn without_fragment(mut url: Url) -> Url {
+    url.set_fragment(None);
+    url
+}
@@ -0,0 +1,219 @@
+//! Scripted plugins: small tools written in Rhai and stored in the registry
+//! with the plugin's version, for transformations that do not warrant an
+//! HTTP service of their own.
+//!
+//! A script sees the call's arguments as `args` and the caller as `context`
+//! (`context_type`, `context_id`, `actor_id`), and its last expression is the
+//! result; `throw "message"` fails the call. It runs on the blocking pool
+//! under `[plugins]` limits: an operation budget, a wall-clock timeout and
+//! caps on string, array and map sizes, which bound the memory it can hold.
+//! It has no file system, no `import` and no `eval`. The only way out is
+//! `http_get(url)` and `http_post(url, body)`, which return
+//! `#{ status, body }` (the body parsed as JSON when it is JSON), go through
+//! the egress policy for the plugin's trust level and count against
+//! `plugins.script_max_http_calls`.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use reqwest::Client;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Dynamic, Engine, EvalAltResult, Scope, AST};
+use serde_json::Value;
+use tokio::runtime::Handle;
+
+use crate::config::PluginsConfig;
+use crate::error::{NovaError, Result};
+
+use super::dto::{PluginScript, PluginTrustLevel, RequestContext};
+use super::egress::EgressPolicy;
+
+/// Largest script source a plugin version may store.
+pub const MAX_SCRIPT_BYTES: usize = 64 * 1024;
+
+/// Deepest nesting of calls and expressions a script may reach.
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_EXPR_DEPTH: usize = 64;
+
+/// What one script run may use; from `[plugins]`.
+#[derive(Debug, Clone)]
+pub struct ScriptLimits {
+    pub max_operations: u64,
+    pub timeout: Duration,
+    pub max_string_bytes: usize,
+    pub max_collection_items: usize,
+    pub max_http_calls: usize,
+}
+
+impl ScriptLimits {
+    pub fn new(cfg: &PluginsConfig) -> Self {
+        Self {
+            max_operations: cfg.script_max_operations,
+            timeout: Duration::from_millis(cfg.script_timeout_ms),
+            max_string_bytes: cfg.script_max_string_bytes,
+            max_collection_items: cfg.script_max_collection_items,
+            max_http_calls: cfg.script_max_http_calls,
+        }
+    }
+
+    /// An engine with these limits and nothing that reaches outside it.
+    fn engine(&self) -> Engine {
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(self.max_operations)
+            .set_max_string_size(self.max_string_bytes)
+            .set_max_array_size(self.max_collection_items)
+            .set_max_map_size(self.max_collection_items)
+            .set_max_call_levels(MAX_CALL_LEVELS)
+            .set_max_expr_depths(MAX_EXPR_DEPTH, MAX_EXPR_DEPTH)
+            .set_module_resolver(DummyModuleResolver::new())
+            .disable_symbol("eval")
+            .on_print(|text| tracing::debug!("Plugin script: {}", text))
+            .on_debug(|text, _, _| tracing::debug!("Plugin script: {}", text));
+        engine
+    }
+
+    /// Parses `script`, reporting syntax errors with their position.
+    pub fn compile(&self, script: &PluginScript) -> Result<AST> {
+        if script.source.len() > MAX_SCRIPT_BYTES {
+            return Err(NovaError::validation_error(format!(
+                "Script exceeds {} bytes",
+                MAX_SCRIPT_BYTES
+            )));
+        }
+        self.engine()
+            .compile(&script.source)
+            .map_err(|e| NovaError::validation_error(format!("Invalid script: {}", e)))
+    }
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        Self::new(&PluginsConfig::default())
+    }
+}
+
+/// What a run needs from the registry: the limits, and the egress policy
+/// and client behind `http_get` and `http_post`.
+pub(crate) struct ScriptRun {
+    pub limits: ScriptLimits,
+    pub egress: EgressPolicy,
+    pub client: Client,
+    pub trust_level: PluginTrustLevel,
+}
+
+impl ScriptRun {
+    /// Runs `ast` for `caller` on the blocking pool and returns its result
+    /// as JSON.
+    pub async fn run(
+        self,
+        ast: Arc<AST>,
+        arguments: Value,
+        caller: &RequestContext,
+    ) -> Result<Value> {
+        let args = rhai::serde::to_dynamic(&arguments).map_err(script_error)?;
+        let context = rhai::serde::to_dynamic(caller).map_err(script_error)?;
+        let handle = Handle::current();
+        tokio::task::spawn_blocking(move || {
+            let engine = self.engine(handle);
+            let mut scope = Scope::new();
+            scope.push_constant("args", args);
+            scope.push_constant("context", context);
+            let result = engine
+                .eval_ast_with_scope::<Dynamic>(&mut scope, &ast)
+                .map_err(script_error)?;
+            rhai::serde::from_dynamic::<Value>(&result).map_err(script_error)
+        })
+        .await
+        .map_err(|e| NovaError::internal(format!("Plugin script panicked: {}", e)))?
+    }
+
+    fn engine(self, handle: Handle) -> Engine {
+        let mut engine = self.limits.engine();
+        let deadline = Instant::now() + self.limits.timeout;
+        engine.on_progress(move |_| {
+            (Instant::now() >= deadline).then(|| Dynamic::from("Script timed out"))
+        });
+
+        let http = Arc::new(ScriptHttp {
+            handle,
+            egress: self.egress,
+            client: self.client,
+            trust_level: self.trust_level,
+            calls: AtomicUsize::new(0),
+            max_calls: self.limits.max_http_calls,
+            max_bytes: self.limits.max_string_bytes,
+        });
+        let get = Arc::clone(&http);
+        engine.register_fn("http_get", move |url: &str| get.send(url, None));
+        engine.register_fn("http_post", move |url: &str, body: Dynamic| {
+            http.send(url, Some(body))
+        });
+        engine
+    }
+}
+
+/// `http_get` and `http_post` for one run.
+struct ScriptHttp {
+    handle: Handle,
+    egress: EgressPolicy,
+    client: Client,
+    trust_level: PluginTrustLevel,
+    calls: AtomicUsize,
+    max_calls: usize,
+    max_bytes: usize,
+}
+
+impl ScriptHttp {
+    fn send(
+        &self,
+        url: &str,
+        body: Option<Dynamic>,
+    ) -> std::result::Result<Dynamic, Box<EvalAltResult>> {
+        if self.calls.fetch_add(1, Ordering::SeqCst) >= self.max_calls {
+            return Err(format!("Script made more than {} HTTP calls", self.max_calls).into());
+        }
+        let body = body
+            .map(|body| rhai::serde::from_dynamic::<Value>(&body))
+            .transpose()?;
+        let (status, bytes) = self
+            .handle
+            .block_on(self.exchange(url, body))
+            .map_err(|e| e.to_string())?;
+        let body = match serde_json::from_slice::<Value>(&bytes) {
+            Ok(json) => rhai::serde::to_dynamic(json)?,
+            Err(_) => Dynamic::from(String::from_utf8_lossy(&bytes).into_owned()),
+        };
+        let mut response = rhai::Map::new();
+        response.insert("status".into(), Dynamic::from(status as i64));
+        response.insert("body".into(), body);
+        Ok(response.into())
+    }
+
+    async fn exchange(&self, url: &str, body: Option<Value>) -> Result<(u16, Vec<u8>)> {
+        let url = self.egress.check_url(url, self.trust_level)?;
+        self.egress.check_resolved(&url).await?;
+        let request = match body {
+            Some(body) => self.client.post(url).json(&body),
+            None => self.client.get(url),
+        };
+        let mut response = request.send().await?;
+        let status = response.status().as_u16();
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if bytes.len() + chunk.len() > self.max_bytes {
+                return Err(NovaError::api_error(format!(
+                    "HTTP response exceeds {} bytes",
+                    self.max_bytes
+                )));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok((status, bytes))
+    }
+}
+
+fn script_error(error: impl std::fmt::Display) -> NovaError {
+    NovaError::api_error(format!("Plugin script failed: {}", error))
+}
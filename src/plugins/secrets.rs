@@ -0,0 +1,55 @@
+//! Encryption at rest for plugin credentials (`plugins.secrets_key`).
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::error::{NovaError, Result};
+
+const NONCE_LEN: usize = 12;
+
+/// AES-256-GCM with a fresh nonce per value. Sealed values are base64 of
+/// `nonce || ciphertext`.
+#[derive(Clone)]
+pub struct SecretBox {
+    cipher: Aes256Gcm,
+}
+
+impl SecretBox {
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)),
+        }
+    }
+
+    /// `None` unless `key` is base64 of exactly 32 bytes.
+    pub fn from_base64(key: &str) -> Option<Self> {
+        let bytes: [u8; 32] = STANDARD.decode(key.trim()).ok()?.try_into().ok()?;
+        Some(Self::new(&bytes))
+    }
+
+    pub fn seal(&self, plaintext: &[u8]) -> Result<String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| NovaError::internal("Failed to encrypt plugin secret"))?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(STANDARD.encode(sealed))
+    }
+
+    /// Fails for values sealed under another key or altered at rest.
+    pub fn open(&self, sealed: &str) -> Result<Vec<u8>> {
+        let bytes = STANDARD
+            .decode(sealed)
+            .map_err(|_| NovaError::internal("Malformed plugin secret"))?;
+        if bytes.len() < NONCE_LEN {
+            return Err(NovaError::internal("Malformed plugin secret"));
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| NovaError::internal("Failed to decrypt plugin secret"))
+    }
+}
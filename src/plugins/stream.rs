@@ -0,0 +1,101 @@
+//! Plugin endpoints that answer with `text/event-stream` or NDJSON
+//! (`application/x-ndjson`, `application/jsonl`) instead of one JSON body.
+//!
+//! Each SSE event's `data` or each line is one chunk: JSON when it parses,
+//! a string otherwise. Chunks are passed on as they arrive, and the last
+//! one is the call's result.
+
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StreamFormat {
+    EventStream,
+    Ndjson,
+}
+
+impl StreamFormat {
+    /// The format a response's `Content-Type` names, if it is a stream.
+    pub(crate) fn of(content_type: Option<&str>) -> Option<Self> {
+        let mime = content_type?.split(';').next()?.trim().to_ascii_lowercase();
+        match mime.as_str() {
+            "text/event-stream" => Some(StreamFormat::EventStream),
+            "application/x-ndjson" | "application/ndjson" | "application/jsonl" => {
+                Some(StreamFormat::Ndjson)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Splits body bytes into chunks, however the network cut them.
+#[derive(Debug)]
+pub(crate) struct ChunkDecoder {
+    format: StreamFormat,
+    // Bytes after the last complete line
+    pending: Vec<u8>,
+    // `data` lines of the SSE event being read
+    data: Vec<String>,
+}
+
+impl ChunkDecoder {
+    pub(crate) fn new(format: StreamFormat) -> Self {
+        Self {
+            format,
+            pending: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+
+    /// Chunks completed by `bytes`.
+    pub(crate) fn push(&mut self, bytes: &[u8]) -> Vec<Value> {
+        self.pending.extend_from_slice(bytes);
+        let mut chunks = Vec::new();
+        while let Some(end) = self.pending.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            if let Some(chunk) = self.line(line.trim_end_matches(['\n', '\r'])) {
+                chunks.push(chunk);
+            }
+        }
+        chunks
+    }
+
+    /// Chunks left when the body ends without a final newline (or, for SSE,
+    /// without the blank line closing the last event).
+    pub(crate) fn finish(mut self) -> Vec<Value> {
+        let rest = std::mem::take(&mut self.pending);
+        let mut chunks: Vec<Value> = self
+            .line(String::from_utf8_lossy(&rest).trim_end_matches('\r'))
+            .into_iter()
+            .collect();
+        chunks.extend(self.line(""));
+        chunks
+    }
+
+    fn line(&mut self, line: &str) -> Option<Value> {
+        match self.format {
+            StreamFormat::Ndjson => (!line.trim().is_empty()).then(|| chunk(line.trim())),
+            StreamFormat::EventStream => {
+                if line.is_empty() {
+                    if self.data.is_empty() {
+                        return None;
+                    }
+                    let data = std::mem::take(&mut self.data).join("\n");
+                    return Some(chunk(&data));
+                }
+                // `event`, `id`, `retry` and `:` comments carry nothing to pass on
+                if let Some(data) = line.strip_prefix("data:") {
+                    self.data
+                        .push(data.strip_prefix(' ').unwrap_or(data).to_string());
+                } else if line == "data" {
+                    self.data.push(String::new());
+                }
+                None
+            }
+        }
+    }
+}
+
+fn chunk(text: &str) -> Value {
+    serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string()))
+}
@@ -0,0 +1,149 @@
+//! Request templates: reshape tool arguments into the body a plugin's backend
+//! expects, without evaluating any code.
+//!
+//! A template is JSON. Strings of the form `"{{ path }}"` are replaced by the
+//! value at `path` (keeping its type); placeholders inside longer strings are
+//! interpolated as text. Paths are dotted lookups into the default invocation
+//! payload, e.g. `arguments.address`, `arguments.pairs.0` or `context_id`.
+//! Pipelines reuse the same syntax over their own scope.
+
+use serde_json::Value;
+
+/// Top-level names a path may start with; the fields of `PluginInvocationPayload`.
+const PAYLOAD_ROOTS: &[&str] = &["arguments", "context_type", "context_id", "actor_id"];
+
+#[derive(Debug, Clone)]
+pub struct RequestTemplate {
+    template: Value,
+}
+
+impl RequestTemplate {
+    /// Fails with the first malformed placeholder.
+    pub fn parse(template: &Value) -> Result<Self, String> {
+        Self::parse_with_roots(template, PAYLOAD_ROOTS)
+    }
+
+    /// Like [`parse`](Self::parse), for scopes other than the invocation payload.
+    pub fn parse_with_roots(template: &Value, roots: &[&str]) -> Result<Self, String> {
+        check(template, roots)?;
+        Ok(Self {
+            template: template.clone(),
+        })
+    }
+
+    /// Every placeholder path, in template order.
+    pub fn references(&self) -> Vec<String> {
+        let mut paths = Vec::new();
+        collect(&self.template, &mut paths);
+        paths
+    }
+
+    /// Missing values render as `null`, or as empty text inside a string.
+    pub fn render(&self, scope: &Value) -> Value {
+        render(&self.template, scope)
+    }
+}
+
+fn check(template: &Value, roots: &[&str]) -> Result<(), String> {
+    match template {
+        Value::String(text) => placeholders(text)?
+            .into_iter()
+            .try_for_each(|(_, path)| check_path(path, roots)),
+        Value::Array(items) => items.iter().try_for_each(|item| check(item, roots)),
+        Value::Object(map) => map.values().try_for_each(|value| check(value, roots)),
+        _ => Ok(()),
+    }
+}
+
+fn collect(template: &Value, paths: &mut Vec<String>) {
+    match template {
+        Value::String(text) => paths.extend(
+            placeholders(text)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(_, path)| path.to_string()),
+        ),
+        Value::Array(items) => items.iter().for_each(|item| collect(item, paths)),
+        Value::Object(map) => map.values().for_each(|value| collect(value, paths)),
+        _ => {}
+    }
+}
+
+fn check_path(path: &str, roots: &[&str]) -> Result<(), String> {
+    let root = path.split('.').next().unwrap_or_default();
+    if !roots.contains(&root) {
+        return Err(format!(
+            "{{{{{}}}}} must start with one of: {}",
+            path,
+            roots.join(", ")
+        ));
+    }
+    if path.split('.').any(str::is_empty) {
+        return Err(format!("{{{{{}}}}} has an empty segment", path));
+    }
+    Ok(())
+}
+
+/// `(byte range of the placeholder, trimmed path)` for each `{{ ... }}`.
+fn placeholders(text: &str) -> Result<Vec<(std::ops::Range<usize>, &str)>, String> {
+    let mut found = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = text[offset..].find("{{") {
+        let start = offset + start;
+        let end = text[start..]
+            .find("}}")
+            .map(|end| start + end + 2)
+            .ok_or_else(|| format!("unclosed placeholder in \"{}\"", text))?;
+        found.push((start..end, text[start + 2..end - 2].trim()));
+        offset = end;
+    }
+    Ok(found)
+}
+
+fn render(template: &Value, scope: &Value) -> Value {
+    match template {
+        Value::String(text) => render_string(text, scope),
+        Value::Array(items) => Value::Array(items.iter().map(|v| render(v, scope)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), render(value, scope)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn render_string(text: &str, scope: &Value) -> Value {
+    // Templates are checked when stored, so parsing cannot fail here
+    let found = placeholders(text).unwrap_or_default();
+    match found.as_slice() {
+        [] => Value::String(text.to_string()),
+        [(range, path)] if range.start == 0 && range.end == text.len() => {
+            lookup(scope, path).cloned().unwrap_or(Value::Null)
+        }
+        _ => {
+            let mut out = String::with_capacity(text.len());
+            let mut last = 0;
+            for (range, path) in &found {
+                out.push_str(&text[last..range.start]);
+                match lookup(scope, path) {
+                    Some(Value::String(s)) => out.push_str(s),
+                    Some(Value::Null) | None => {}
+                    Some(other) => out.push_str(&other.to_string()),
+                }
+                last = range.end;
+            }
+            out.push_str(&text[last..]);
+            Value::String(out)
+        }
+    }
+}
+
+fn lookup<'a>(scope: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(scope, |value, segment| match value {
+            Value::Object(map) => map.get(segment),
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        })
+}
@@ -0,0 +1,137 @@
+//! Field-level problems in plugin registrations, collected so a developer
+//! sees all of them in one response instead of one per attempt.
+//!
+//! Fields are named by their path in the JSON body (`name`,
+//! `listing.icon_url`, `allowed_contexts[1]`) and end up in
+//! [`NovaError::InvalidFields`].
+
+use std::collections::BTreeMap;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::error::{NovaError, Result};
+
+#[derive(Debug, Default)]
+pub(crate) struct FieldProblems {
+    fields: BTreeMap<String, String>,
+}
+
+impl FieldProblems {
+    /// Records `message` for `field`; the first problem found for a field is
+    /// the one reported.
+    pub(crate) fn add(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.fields
+            .entry(field.into())
+            .or_insert_with(|| message.into());
+    }
+
+    /// Records a failed check under `field`.
+    pub(crate) fn check(&mut self, field: &str, result: Result<()>) {
+        if let Err(err) = result {
+            let message = match err {
+                NovaError::ValidationError { message } => message,
+                other => other.to_string(),
+            };
+            self.add(field, message);
+        }
+    }
+
+    /// Records a missing top-level `field` and fills it with `placeholder`,
+    /// so the rest of the body can still be deserialized and checked.
+    pub(crate) fn require(&mut self, body: &mut Value, field: &str, placeholder: Value) {
+        let Some(object) = body.as_object_mut() else {
+            return;
+        };
+        if object.get(field).is_none_or(Value::is_null) {
+            self.add(field, "is required");
+            object.insert(field.to_string(), placeholder);
+        }
+    }
+
+    /// Records a top-level `field` that is not one of `expected` and drops it,
+    /// leaving the default in its place.
+    pub(crate) fn known<T: DeserializeOwned>(
+        &mut self,
+        body: &mut Value,
+        field: &str,
+        expected: &str,
+    ) {
+        let Some(object) = body.as_object_mut() else {
+            return;
+        };
+        let Some(value) = object.get(field).filter(|value| !value.is_null()) else {
+            return;
+        };
+        if serde_json::from_value::<T>(value.clone()).is_err() {
+            self.add(field, unknown_value(value, expected));
+            object.remove(field);
+        }
+    }
+
+    /// Like [`known`](Self::known) for every item of a top-level array,
+    /// reporting each unknown one as `field[index]`.
+    pub(crate) fn known_items<T: DeserializeOwned>(
+        &mut self,
+        body: &mut Value,
+        field: &str,
+        expected: &str,
+    ) {
+        let Some(object) = body.as_object_mut() else {
+            return;
+        };
+        let Some(Value::Array(items)) = object.get(field) else {
+            return;
+        };
+        let mut unknown = false;
+        for (index, item) in items.iter().enumerate() {
+            if serde_json::from_value::<T>(item.clone()).is_err() {
+                self.add(
+                    format!("{}[{}]", field, index),
+                    unknown_value(item, expected),
+                );
+                unknown = true;
+            }
+        }
+        if unknown {
+            object.remove(field);
+        }
+    }
+
+    /// Deserializes what is left of `body`. A value of the wrong type is
+    /// recorded under its path and yields `None`, since nothing further can
+    /// be checked.
+    pub(crate) fn deserialize<T: DeserializeOwned>(&mut self, body: Value) -> Option<T> {
+        match serde_path_to_error::deserialize(body) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                let field = match err.path().to_string() {
+                    root if root == "." => "body".to_string(),
+                    path => path,
+                };
+                self.add(field, err.into_inner().to_string());
+                None
+            }
+        }
+    }
+
+    /// `Ok` when nothing was recorded, otherwise every problem at once.
+    pub(crate) fn finish(self) -> Result<()> {
+        if self.fields.is_empty() {
+            Ok(())
+        } else {
+            Err(self.into_error())
+        }
+    }
+
+    pub(crate) fn into_error(self) -> NovaError {
+        NovaError::invalid_fields(self.fields)
+    }
+}
+
+fn unknown_value(value: &Value, expected: &str) -> String {
+    match value {
+        Value::String(text) => format!("unknown value '{}'; expected {}", text, expected),
+        other => format!("expected {}, got {}", expected, other),
+    }
+}
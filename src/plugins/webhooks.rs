@@ -0,0 +1,182 @@
+//! Webhooks that receive an async job's outcome, given as `webhook_url` on
+//! `POST /plugins/:id/call?async=true`.
+//!
+//! The finished [`PluginJob`] is POSTed as JSON with `x-nova-timestamp` and
+//! `x-nova-signature: sha256=<hex>`, the HMAC-SHA256 of `<timestamp>.<body>`
+//! under `plugins.webhook_secret`. Failed deliveries are retried with
+//! exponential backoff; the last failure becomes a
+//! [`DeadLetter`](crate::dead_letters::DeadLetter) admins can requeue.
+
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use super::dto::{PluginJob, PluginTrustLevel, RequestContext};
+use super::manager::PluginManager;
+use crate::clock::SharedClock;
+use crate::config::PluginsConfig;
+use crate::dead_letters::{DeadLetter, Delivery, DeliveryKind};
+use crate::error::{NovaError, Result};
+
+pub const TIMESTAMP_HEADER: &str = "x-nova-timestamp";
+pub const SIGNATURE_HEADER: &str = "x-nova-signature";
+
+/// Signs and sends job webhooks; the last failure goes to the plugin
+/// manager's [`DeadLetters`](crate::dead_letters::DeadLetters).
+pub struct Webhooks {
+    secret: Option<Vec<u8>>,
+    attempts: u32,
+    retry_base: Duration,
+    clock: SharedClock,
+}
+
+impl Webhooks {
+    pub fn new() -> Self {
+        let defaults = PluginsConfig::default();
+        Self {
+            secret: None,
+            attempts: defaults.webhook_attempts,
+            retry_base: Duration::from_millis(defaults.webhook_retry_base_ms),
+            clock: SharedClock::default(),
+        }
+    }
+
+    /// `plugins.webhook_secret`, `plugins.webhook_attempts` and
+    /// `plugins.webhook_retry_base_ms`.
+    pub fn with_config(mut self, config: &PluginsConfig) -> Self {
+        self.secret = config
+            .webhook_secret
+            .as_deref()
+            .filter(|secret| !secret.is_empty())
+            .map(|secret| secret.as_bytes().to_vec());
+        self.attempts = config.webhook_attempts.max(1);
+        self.retry_base = Duration::from_millis(config.webhook_retry_base_ms);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// `sha256=<hex>` for a body sent at `timestamp`; `None` without a secret.
+    pub fn sign(&self, timestamp: i64, body: &[u8]) -> Option<String> {
+        let secret = self.secret.as_deref()?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts any key length");
+        mac.update(timestamp.to_string().as_bytes());
+        mac.update(b".");
+        mac.update(body);
+        let digest: String = mac
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        Some(format!("sha256={}", digest))
+    }
+
+    /// Rejects a `webhook_url` before its job is stored: webhooks need a
+    /// secret to sign with and must pass the rules plugin endpoints do.
+    pub fn check_url(&self, manager: &PluginManager, url: &str) -> Result<()> {
+        if self.secret.is_none() {
+            return Err(NovaError::validation_error(
+                "webhook_url needs plugins.webhook_secret to be set",
+            ));
+        }
+        manager
+            .egress()
+            .check_url(url, PluginTrustLevel::Standard)
+            .map(drop)
+    }
+
+    /// Sends `job` to `url`, retrying with backoff, and dead-letters it when
+    /// every attempt fails.
+    pub(crate) async fn deliver(
+        &self,
+        manager: &PluginManager,
+        url: &str,
+        context: &RequestContext,
+        job: &PluginJob,
+    ) -> Result<()> {
+        let mut delay = self.retry_base;
+        let mut last_error = None;
+        for attempt in 1..=self.attempts {
+            match self.send(manager, url, job).await {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    tracing::debug!(
+                        "Webhook for job {} failed attempt {}: {}",
+                        job.job_id,
+                        attempt,
+                        e
+                    );
+                    last_error = Some(e);
+                }
+            }
+            if attempt < self.attempts {
+                tokio::time::sleep(delay).await;
+                delay = delay.saturating_mul(2);
+            }
+        }
+        let error = last_error.unwrap_or_else(|| NovaError::internal("Webhook not attempted"));
+        let delivery = Delivery {
+            kind: DeliveryKind::JobWebhook,
+            target: url.to_string(),
+            reference: job.job_id.clone(),
+            context: Some(context.key()),
+            payload: serde_json::to_value(job)?,
+        };
+        manager
+            .dead_letters()
+            .record(delivery, self.attempts, &error.to_string())?;
+        Err(error)
+    }
+
+    /// Requeues a dead-lettered job webhook, signed afresh.
+    pub async fn requeue(&self, manager: &PluginManager, id: &str) -> Result<DeadLetter> {
+        manager
+            .dead_letters()
+            .requeue(id, |delivery| async move {
+                let job: PluginJob = serde_json::from_value(delivery.payload)?;
+                self.send(manager, &delivery.target, &job).await
+            })
+            .await
+    }
+
+    async fn send(&self, manager: &PluginManager, url: &str, job: &PluginJob) -> Result<()> {
+        // Re-checked per attempt: the rules or the host's DNS may have changed
+        let url = manager
+            .egress()
+            .check_url(url, PluginTrustLevel::Standard)?;
+        manager.egress().check_resolved(&url).await?;
+        let body = serde_json::to_vec(job)?;
+        let timestamp = self.clock.timestamp();
+        let signature = self
+            .sign(timestamp, &body)
+            .ok_or_else(|| NovaError::config_error("plugins.webhook_secret is not set"))?;
+        let response = manager
+            .http_client()
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, signature)
+            .body(body)
+            .send()
+            .await
+            .map_err(NovaError::from)?;
+        if !response.status().is_success() {
+            return Err(NovaError::api_error(format!(
+                "Webhook returned {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for Webhooks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
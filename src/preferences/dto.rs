@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::error::{NovaError, Result};
+
+use super::markup::Markup;
+
+/// How numbers in text output are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum NumberFormat {
+    /// Grouped thousands: `1,234,567.89`.
+    #[default]
+    Standard,
+    /// Abbreviated magnitudes: `1.23M`.
+    Compact,
+    /// No grouping: `1234567.89`.
+    Plain,
+}
+
+/// How much of a tool result the text content carries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ResultFormat {
+    /// The whole result as JSON.
+    #[default]
+    Full,
+    /// A few readable lines, for tools with a summary formatter.
+    Summary,
+    /// The summary, or the JSON in a code block, as Telegram MarkdownV2.
+    #[serde(rename = "telegram_markdown")]
+    TelegramMarkdown,
+    /// As `telegram_markdown`, for Telegram's HTML parse mode.
+    #[serde(rename = "telegram_html")]
+    TelegramHtml,
+}
+
+impl ResultFormat {
+    /// Whether tools with a summary formatter return it instead of JSON.
+    pub fn summarizes(self) -> bool {
+        self != ResultFormat::Full
+    }
+
+    pub fn markup(self) -> Markup {
+        match self {
+            ResultFormat::Full | ResultFormat::Summary => Markup::Plain,
+            ResultFormat::TelegramMarkdown => Markup::TelegramMarkdown,
+            ResultFormat::TelegramHtml => Markup::TelegramHtml,
+        }
+    }
+}
+
+/// Display settings stored per context.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContextPreferences {
+    // ISO 4217 code; anything but USD needs a rate in `preferences.usd_rates`
+    pub currency: String,
+    // BCP 47 tag; its language picks the digit separators
+    pub locale: String,
+    // IANA name, e.g. "Europe/Berlin"
+    pub timezone: String,
+    pub number_format: NumberFormat,
+    // Used when a `tools/call` omits `format`
+    pub result_format: ResultFormat,
+}
+
+impl Default for ContextPreferences {
+    fn default() -> Self {
+        Self {
+            currency: "USD".to_string(),
+            locale: "en-US".to_string(),
+            timezone: "UTC".to_string(),
+            number_format: NumberFormat::Standard,
+            result_format: ResultFormat::Full,
+        }
+    }
+}
+
+/// Partial change; omitted fields keep their current value.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PreferencesUpdate {
+    #[serde(default)]
+    pub currency: Option<String>,
+    #[serde(default)]
+    pub locale: Option<String>,
+    #[serde(default)]
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub number_format: Option<NumberFormat>,
+    #[serde(default)]
+    pub result_format: Option<ResultFormat>,
+}
+
+impl ContextPreferences {
+    pub fn apply(&mut self, update: PreferencesUpdate) {
+        if let Some(currency) = update.currency {
+            self.currency = currency;
+        }
+        if let Some(locale) = update.locale {
+            self.locale = locale;
+        }
+        if let Some(timezone) = update.timezone {
+            self.timezone = timezone;
+        }
+        if let Some(number_format) = update.number_format {
+            self.number_format = number_format;
+        }
+        if let Some(result_format) = update.result_format {
+            self.result_format = result_format;
+        }
+    }
+
+    /// Normalizes the currency code and checks every field; `usd_rates` lists
+    /// the currencies besides USD that can be shown.
+    pub fn validate(&mut self, usd_rates: &HashMap<String, f64>) -> Result<()> {
+        self.currency = self.currency.trim().to_uppercase();
+        let mut problems = Vec::new();
+        if self.currency != "USD" && !usd_rates.contains_key(&self.currency) {
+            let mut supported: Vec<&str> = usd_rates.keys().map(String::as_str).collect();
+            supported.push("USD");
+            supported.sort_unstable();
+            problems.push(format!(
+                "currency: unsupported currency '{}' (supported: {})",
+                self.currency,
+                supported.join(", ")
+            ));
+        }
+        if !is_language_tag(&self.locale) {
+            problems.push(format!(
+                "locale: '{}' is not a language tag like en-US",
+                self.locale
+            ));
+        }
+        if self.timezone.parse::<chrono_tz::Tz>().is_err() {
+            problems.push(format!(
+                "timezone: unknown IANA timezone '{}'",
+                self.timezone
+            ));
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(NovaError::validation_error(problems.join("; ")))
+        }
+    }
+}
+
+fn is_language_tag(tag: &str) -> bool {
+    let mut parts = tag.split(['-', '_']);
+    let language_ok = parts.next().is_some_and(|lang| {
+        (2..=3).contains(&lang.len()) && lang.chars().all(|c| c.is_ascii_alphabetic())
+    });
+    language_ok
+        && parts.all(|part| {
+            (2..=8).contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
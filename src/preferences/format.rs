@@ -0,0 +1,179 @@
+use chrono::{DateTime, FixedOffset};
+use chrono_tz::Tz;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use super::dto::{ContextPreferences, NumberFormat};
+
+/// Rewrites tool output for display under one context's preferences.
+///
+/// Values under keys ending in `_usd` become currency strings in the preferred
+/// currency, and RFC 3339 timestamps move to the preferred timezone. Only the
+/// text content is localized; `structuredContent` keeps the raw values.
+pub struct Localizer {
+    number_format: NumberFormat,
+    group: &'static str,
+    decimal: char,
+    symbol: String,
+    symbol_after: bool,
+    rate: f64,
+    timezone: Tz,
+}
+
+impl Localizer {
+    /// Unknown currencies fall back to USD and unknown timezones to UTC.
+    pub fn new(preferences: &ContextPreferences, usd_rates: &HashMap<String, f64>) -> Self {
+        let (group, decimal) = separators(&preferences.locale);
+        let (currency, rate) = match usd_rates.get(&preferences.currency) {
+            Some(rate) => (preferences.currency.as_str(), *rate),
+            None => ("USD", 1.0),
+        };
+        Self {
+            number_format: preferences.number_format,
+            group,
+            decimal,
+            symbol: currency_symbol(currency),
+            // Comma-decimal locales write the symbol after the amount
+            symbol_after: decimal == ',',
+            rate,
+            timezone: preferences.timezone.parse().unwrap_or(Tz::UTC),
+        }
+    }
+
+    pub fn localize(&self, value: &Value) -> Value {
+        self.walk(value, false)
+    }
+
+    /// Converts a USD amount to the preferred currency and formats it.
+    pub fn money(&self, usd: f64) -> String {
+        let amount = self.number(usd * self.rate);
+        let (sign, amount) = match amount.strip_prefix('-') {
+            Some(rest) => ("-", rest.to_string()),
+            None => ("", amount),
+        };
+        let glue = if self.symbol.chars().count() > 1 {
+            " "
+        } else {
+            ""
+        };
+        if self.symbol_after {
+            format!("{}{} {}", sign, amount, self.symbol)
+        } else {
+            format!("{}{}{}{}", sign, self.symbol, glue, amount)
+        }
+    }
+
+    pub fn number(&self, value: f64) -> String {
+        if self.number_format == NumberFormat::Compact && value.abs() >= 1_000.0 {
+            const UNITS: [(f64, &str); 4] = [(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "K")];
+            let (scale, suffix) = UNITS
+                .iter()
+                .find(|(scale, _)| value.abs() >= *scale)
+                .copied()
+                .unwrap_or((1e3, "K"));
+            return format!("{}{}", self.decimal_text(value / scale, 2, false), suffix);
+        }
+        let grouped = self.number_format == NumberFormat::Standard;
+        self.decimal_text(value, decimals_for(value), grouped)
+    }
+
+    pub fn timestamp(&self, at: &DateTime<FixedOffset>) -> String {
+        at.with_timezone(&self.timezone)
+            .format("%Y-%m-%d %H:%M:%S %Z")
+            .to_string()
+    }
+
+    fn walk(&self, value: &Value, in_usd: bool) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(key, child)| {
+                        (
+                            key.clone(),
+                            self.walk(child, in_usd || key.ends_with("_usd")),
+                        )
+                    })
+                    .collect::<Map<String, Value>>(),
+            ),
+            Value::Array(items) => {
+                Value::Array(items.iter().map(|item| self.walk(item, in_usd)).collect())
+            }
+            Value::Number(number) if in_usd => match number.as_f64() {
+                Some(amount) => Value::String(self.money(amount)),
+                None => value.clone(),
+            },
+            Value::String(text) => {
+                // GeckoTerminal sends amounts as decimal strings
+                if in_usd {
+                    if let Some(amount) = text.parse::<f64>().ok().filter(|a| a.is_finite()) {
+                        return Value::String(self.money(amount));
+                    }
+                }
+                match DateTime::parse_from_rfc3339(text) {
+                    Ok(at) => Value::String(self.timestamp(&at)),
+                    Err(_) => value.clone(),
+                }
+            }
+            _ => value.clone(),
+        }
+    }
+
+    fn decimal_text(&self, value: f64, decimals: usize, grouped: bool) -> String {
+        let text = format!("{:.*}", decimals, value.abs());
+        let (integer, fraction) = text.split_once('.').unwrap_or((text.as_str(), ""));
+        let mut out = String::new();
+        if value < 0.0 && text.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            out.push('-');
+        }
+        for (i, digit) in integer.chars().enumerate() {
+            if grouped && i > 0 && (integer.len() - i) % 3 == 0 {
+                out.push_str(self.group);
+            }
+            out.push(digit);
+        }
+        if !fraction.is_empty() {
+            out.push(self.decimal);
+            out.push_str(fraction);
+        }
+        out
+    }
+}
+
+/// Two decimals from 1 up; smaller amounts (token prices) keep four significant digits.
+fn decimals_for(value: f64) -> usize {
+    let magnitude = value.abs();
+    if magnitude >= 1.0 || magnitude == 0.0 {
+        2
+    } else {
+        let leading_zeros = (-magnitude.log10().floor()) as usize;
+        (leading_zeros + 3).clamp(2, 12)
+    }
+}
+
+/// Thousands and decimal separators for a locale's language.
+fn separators(locale: &str) -> (&'static str, char) {
+    let locale = locale.to_lowercase().replace('_', "-");
+    if locale.starts_with("de-ch") {
+        return ("'", '.');
+    }
+    match locale.split('-').next().unwrap_or_default() {
+        "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" | "ro" | "hr" | "sl"
+        | "sr" => (".", ','),
+        "fr" | "ru" | "pl" | "uk" | "cs" | "sk" | "sv" | "fi" | "nb" | "nn" | "no" | "hu"
+        | "bg" | "lt" | "lv" | "et" => ("\u{a0}", ','),
+        _ => (",", '.'),
+    }
+}
+
+fn currency_symbol(code: &str) -> String {
+    match code {
+        "USD" => "$",
+        "EUR" => "€",
+        "GBP" => "£",
+        "JPY" | "CNY" => "¥",
+        "INR" => "₹",
+        "KRW" => "₩",
+        other => other,
+    }
+    .to_string()
+}
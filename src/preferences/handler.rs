@@ -0,0 +1,47 @@
+use axum::{extract::State, http::HeaderMap, http::StatusCode, Json};
+
+use crate::auth::SCOPE_PREFERENCES;
+use crate::http::{ApiJson, AppState};
+use crate::plugins::helpers::{authorize_request, map_error};
+use crate::plugins::ErrorResponse;
+
+use super::dto::ContextPreferences;
+
+pub(crate) async fn get_preferences(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ContextPreferences>, (StatusCode, Json<ErrorResponse>)> {
+    let context = authorize_request(&state, &headers, SCOPE_PREFERENCES).await?;
+    match state.server().preferences().get_or_default(&context) {
+        Ok(preferences) => Ok(Json(preferences)),
+        Err(err) => Err(map_error(err)),
+    }
+}
+
+/// Replaces the context's preferences; omitted fields reset to their defaults.
+pub(crate) async fn put_preferences(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiJson(mut preferences): ApiJson<ContextPreferences>,
+) -> Result<Json<ContextPreferences>, (StatusCode, Json<ErrorResponse>)> {
+    let context = authorize_request(&state, &headers, SCOPE_PREFERENCES).await?;
+    preferences
+        .validate(&state.config().preferences.usd_rates)
+        .map_err(map_error)?;
+    match state.server().preferences().put(&context, &preferences) {
+        Ok(()) => Ok(Json(preferences)),
+        Err(err) => Err(map_error(err)),
+    }
+}
+
+pub(crate) async fn delete_preferences(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    let context = authorize_request(&state, &headers, SCOPE_PREFERENCES).await?;
+    match state.server().preferences().remove(&context) {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Ok(StatusCode::NOT_FOUND),
+        Err(err) => Err(map_error(err)),
+    }
+}
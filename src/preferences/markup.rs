@@ -0,0 +1,104 @@
+//! Markup for text content sent straight to a chat client, chosen by the
+//! `telegram_markdown` and `telegram_html` result formats.
+//!
+//! Summaries build their text from these pieces, so every value a tool
+//! returns is escaped for the target parse mode and the message can be
+//! posted as is. Results without a summary go in a JSON code block.
+
+/// How text content is marked up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Markup {
+    /// Text as is; no emphasis or links.
+    #[default]
+    Plain,
+    /// Telegram's MarkdownV2 parse mode.
+    TelegramMarkdown,
+    /// Telegram's HTML parse mode.
+    TelegramHtml,
+}
+
+/// Characters MarkdownV2 reserves outside code entities.
+const MARKDOWN_RESERVED: &[char] = &[
+    '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}', '.', '!', '\\',
+];
+
+impl Markup {
+    /// Whether emphasis, addresses and links are drawn at all.
+    pub fn is_rich(self) -> bool {
+        self != Markup::Plain
+    }
+
+    /// Literal text, escaped.
+    pub fn text(self, text: &str) -> String {
+        match self {
+            Markup::Plain => text.to_string(),
+            Markup::TelegramMarkdown => escape_markdown(text, MARKDOWN_RESERVED),
+            Markup::TelegramHtml => escape_html(text),
+        }
+    }
+
+    pub fn bold(self, text: &str) -> String {
+        match self {
+            Markup::Plain => text.to_string(),
+            Markup::TelegramMarkdown => format!("*{}*", self.text(text)),
+            Markup::TelegramHtml => format!("<b>{}</b>", self.text(text)),
+        }
+    }
+
+    /// Inline code, for addresses and ids users copy.
+    pub fn code(self, text: &str) -> String {
+        match self {
+            Markup::Plain => text.to_string(),
+            Markup::TelegramMarkdown => format!("`{}`", escape_markdown(text, &['`', '\\'])),
+            Markup::TelegramHtml => format!("<code>{}</code>", escape_html(text)),
+        }
+    }
+
+    /// A link labelled `label`; just the label in plain text.
+    pub fn link(self, label: &str, url: &str) -> String {
+        match self {
+            Markup::Plain => label.to_string(),
+            Markup::TelegramMarkdown => format!(
+                "[{}]({})",
+                self.text(label),
+                escape_markdown(url, &[')', '\\'])
+            ),
+            Markup::TelegramHtml => format!(
+                "<a href=\"{}\">{}</a>",
+                escape_html(url).replace('"', "&quot;"),
+                self.text(label)
+            ),
+        }
+    }
+
+    /// Rendered JSON as a code block; unchanged in plain text.
+    pub fn json_block(self, json: String) -> String {
+        match self {
+            Markup::Plain => json,
+            Markup::TelegramMarkdown => {
+                format!("```json\n{}\n```", escape_markdown(&json, &['`', '\\']))
+            }
+            Markup::TelegramHtml => format!(
+                "<pre><code class=\"language-json\">{}</code></pre>",
+                escape_html(&json)
+            ),
+        }
+    }
+}
+
+fn escape_markdown(text: &str, reserved: &[char]) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if reserved.contains(&c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
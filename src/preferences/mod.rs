@@ -0,0 +1,11 @@
+pub mod dto;
+pub mod format;
+pub(crate) mod handler;
+pub mod markup;
+pub mod store;
+
+pub use dto::{ContextPreferences, NumberFormat, PreferencesUpdate, ResultFormat};
+pub use format::Localizer;
+pub(crate) use handler::{delete_preferences, get_preferences, put_preferences};
+pub use markup::Markup;
+pub use store::PreferenceStore;
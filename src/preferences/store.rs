@@ -0,0 +1,49 @@
+use super::dto::ContextPreferences;
+use crate::error::Result;
+use crate::plugins::RequestContext;
+use crate::storage::{KvStore, MemoryKv};
+
+/// Per-context display preferences; contexts without an entry use the defaults.
+pub struct PreferenceStore {
+    store: Box<dyn KvStore>,
+}
+
+impl PreferenceStore {
+    pub fn in_memory() -> Self {
+        Self {
+            store: Box::new(MemoryKv::default()),
+        }
+    }
+
+    /// Stores JSON-encoded preferences keyed by `<type>:<id>`.
+    pub fn persistent(tree: sled::Tree) -> Self {
+        Self {
+            store: Box::new(tree),
+        }
+    }
+
+    pub fn get(&self, context: &RequestContext) -> Result<Option<ContextPreferences>> {
+        self.store.get_json(context.key().as_bytes())
+    }
+
+    /// Stored preferences, or the defaults when the context has none.
+    pub fn get_or_default(&self, context: &RequestContext) -> Result<ContextPreferences> {
+        Ok(self.get(context)?.unwrap_or_default())
+    }
+
+    pub fn put(&self, context: &RequestContext, preferences: &ContextPreferences) -> Result<()> {
+        self.store
+            .insert_json(context.key().as_bytes(), preferences)
+    }
+
+    /// Returns whether the context had stored preferences.
+    pub fn remove(&self, context: &RequestContext) -> Result<bool> {
+        Ok(self.store.remove(context.key().as_bytes())?.is_some())
+    }
+}
+
+impl Default for PreferenceStore {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
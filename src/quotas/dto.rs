@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Calls counted in one period and what is left of its cap.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeriodUsage {
+    pub used: u64,
+    // None when the period has no cap
+    pub limit: Option<u64>,
+    pub remaining: Option<u64>,
+    // RFC 3339, UTC
+    pub resets_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScopeUsage {
+    pub daily: PeriodUsage,
+    pub monthly: PeriodUsage,
+}
+
+/// A context's quota standing, as returned by `get_my_usage` and
+/// `GET /admin/quotas/:type/:id`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaUsage {
+    pub context: String,
+    // Calls to any tool
+    pub daily: PeriodUsage,
+    pub monthly: PeriodUsage,
+    // Plugins called this month or with an override, by fq_name
+    #[serde(default)]
+    pub plugins: BTreeMap<String, ScopeUsage>,
+}
+
+/// Caps granted to one context; unset fields fall back to `[quotas]`, and 0
+/// lifts the cap.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaOverride {
+    #[serde(default)]
+    pub daily_calls: Option<u64>,
+    #[serde(default)]
+    pub monthly_calls: Option<u64>,
+}
+
+impl QuotaOverride {
+    pub fn is_empty(&self) -> bool {
+        self.daily_calls.is_none() && self.monthly_calls.is_none()
+    }
+}
@@ -0,0 +1,5 @@
+pub mod dto;
+pub mod store;
+
+pub use dto::{PeriodUsage, QuotaOverride, QuotaUsage, ScopeUsage};
+pub use store::QuotaStore;
@@ -0,0 +1,325 @@
+use chrono::{DateTime, Datelike, Months, NaiveDate, SecondsFormat, Utc};
+use std::collections::BTreeSet;
+
+use super::dto::{PeriodUsage, QuotaOverride, QuotaUsage, ScopeUsage};
+use crate::clock::SharedClock;
+use crate::config::{QuotaLimits, QuotasConfig};
+use crate::error::{NovaError, Result};
+use crate::plugins::RequestContext;
+use crate::storage::{KvStore, MemoryKv};
+
+// Scope of the context-wide counters; plugin counters use the fq_name
+const ALL_TOOLS: &str = "*";
+
+/// Daily and monthly call counters per context, plus admin overrides.
+///
+/// Counters are keyed `usage|<type>:<id>|<scope>|<period>`, so a new day or
+/// month starts from zero on its own; [`QuotaStore::sweep`] removes the
+/// counters of past periods. Overrides are keyed `override|<type>:<id>|<scope>`.
+pub struct QuotaStore {
+    store: Box<dyn KvStore>,
+    clock: SharedClock,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Period {
+    Daily,
+    Monthly,
+}
+
+impl QuotaStore {
+    pub fn in_memory() -> Self {
+        Self::with_store(Box::new(MemoryKv::default()))
+    }
+
+    pub fn persistent(tree: sled::Tree) -> Self {
+        Self::with_store(Box::new(tree))
+    }
+
+    fn with_store(store: Box<dyn KvStore>) -> Self {
+        Self {
+            store,
+            clock: SharedClock::default(),
+        }
+    }
+
+    /// The clock that decides which day and month a call counts against.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Counts one call against the context's caps, or against its caps for
+    /// `plugin` when given. Fails with `QuotaExceeded`, counting nothing, when
+    /// either period is used up.
+    pub fn consume(
+        &self,
+        context: &RequestContext,
+        plugin: Option<&str>,
+        config: &QuotasConfig,
+    ) -> Result<()> {
+        let owner = context.key();
+        let scope = plugin.unwrap_or(ALL_TOOLS);
+        let limits = self.limits(&owner, plugin, config)?;
+        let now = self.clock.now();
+        let mut counted = Vec::new();
+        for period in [Period::Daily, Period::Monthly] {
+            let key = usage_key(&owner, scope, &period.label(now));
+            let limit = period.limit(limits);
+            if self.increment(&key, limit)? {
+                counted.push(key);
+                continue;
+            }
+            for key in counted {
+                self.decrement(&key)?;
+            }
+            let scope = match plugin {
+                Some(plugin) => format!("{} ({})", owner, plugin),
+                None => owner,
+            };
+            return Err(NovaError::quota_exceeded(
+                scope,
+                period.name(),
+                limit,
+                timestamp(period.resets_at(now)),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn usage(&self, context: &RequestContext, config: &QuotasConfig) -> Result<QuotaUsage> {
+        let owner = context.key();
+        let now = self.clock.now();
+        let month = Period::Monthly.label(now);
+        let mut plugins = BTreeSet::new();
+        for (key, _) in self.scan(&format!("usage|{}|", owner))? {
+            if let Some((scope, period)) = split_scope(&key) {
+                if scope != ALL_TOOLS && period == month {
+                    plugins.insert(scope.to_string());
+                }
+            }
+        }
+        for (key, _) in self.scan(&format!("override|{}|", owner))? {
+            if let Some(scope) = key.rsplit('|').next().filter(|scope| *scope != ALL_TOOLS) {
+                plugins.insert(scope.to_string());
+            }
+        }
+
+        let overall = self.scope_usage(&owner, None, config, now)?;
+        let plugins = plugins
+            .into_iter()
+            .map(|plugin| {
+                let usage = self.scope_usage(&owner, Some(&plugin), config, now)?;
+                Ok((plugin, usage))
+            })
+            .collect::<Result<_>>()?;
+        Ok(QuotaUsage {
+            context: owner,
+            daily: overall.daily,
+            monthly: overall.monthly,
+            plugins,
+        })
+    }
+
+    /// Replaces the context's override (for `plugin` when given) and returns
+    /// the previous one; an empty override removes it.
+    pub fn set_override(
+        &self,
+        context: &RequestContext,
+        plugin: Option<&str>,
+        limits: QuotaOverride,
+    ) -> Result<Option<QuotaOverride>> {
+        let key = override_key(&context.key(), plugin.unwrap_or(ALL_TOOLS));
+        let value = if limits.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_vec(&limits)?)
+        };
+        let previous = self.replace(&key, value)?;
+        Ok(previous.and_then(|bytes| serde_json::from_slice(&bytes).ok()))
+    }
+
+    /// Drops the context's counters and overrides; returns how many records went.
+    pub fn remove(&self, context: &RequestContext) -> Result<usize> {
+        let owner = context.key();
+        let mut removed = 0;
+        for prefix in [format!("usage|{}|", owner), format!("override|{}|", owner)] {
+            for (key, _) in self.scan(&prefix)? {
+                self.replace(&key, None)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Removes the counters of past days and months; returns how many went.
+    pub fn sweep(&self) -> Result<usize> {
+        let now = self.clock.now();
+        let current = [Period::Daily.label(now), Period::Monthly.label(now)];
+        let mut removed = 0;
+        for (key, _) in self.scan("usage|")? {
+            let live = split_scope(&key)
+                .is_some_and(|(_, period)| current.iter().any(|label| label == period));
+            if !live {
+                self.replace(&key, None)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Configured caps for the scope with the context's override applied.
+    fn limits(
+        &self,
+        owner: &str,
+        plugin: Option<&str>,
+        config: &QuotasConfig,
+    ) -> Result<QuotaLimits> {
+        let mut limits = match plugin {
+            Some(plugin) => config.plugins.get(plugin).copied().unwrap_or_default(),
+            None => QuotaLimits {
+                daily_calls: config.daily_calls,
+                monthly_calls: config.monthly_calls,
+            },
+        };
+        let key = override_key(owner, plugin.unwrap_or(ALL_TOOLS));
+        if let Some(bytes) = self.get(&key)? {
+            let granted: QuotaOverride = serde_json::from_slice(&bytes)?;
+            limits.daily_calls = granted.daily_calls.unwrap_or(limits.daily_calls);
+            limits.monthly_calls = granted.monthly_calls.unwrap_or(limits.monthly_calls);
+        }
+        Ok(limits)
+    }
+
+    fn scope_usage(
+        &self,
+        owner: &str,
+        plugin: Option<&str>,
+        config: &QuotasConfig,
+        now: DateTime<Utc>,
+    ) -> Result<ScopeUsage> {
+        let limits = self.limits(owner, plugin, config)?;
+        let scope = plugin.unwrap_or(ALL_TOOLS);
+        let period_usage = |period: Period| -> Result<PeriodUsage> {
+            let used = self
+                .get(&usage_key(owner, scope, &period.label(now)))?
+                .map_or(0, |bytes| decode_count(&bytes));
+            let limit = Some(period.limit(limits)).filter(|limit| *limit > 0);
+            Ok(PeriodUsage {
+                used,
+                limit,
+                remaining: limit.map(|limit| limit.saturating_sub(used)),
+                resets_at: timestamp(period.resets_at(now)),
+            })
+        };
+        Ok(ScopeUsage {
+            daily: period_usage(Period::Daily)?,
+            monthly: period_usage(Period::Monthly)?,
+        })
+    }
+
+    /// Adds one call unless `limit` (0 = none) is reached; false when it is.
+    fn increment(&self, key: &str, limit: u64) -> Result<bool> {
+        let allowed = |count: u64| limit == 0 || count < limit;
+        let old = self.update(key, |old| {
+            let count = old.map_or(0, decode_count);
+            let count = if allowed(count) { count + 1 } else { count };
+            Some(count.to_be_bytes().to_vec())
+        })?;
+        Ok(allowed(old.as_deref().map_or(0, decode_count)))
+    }
+
+    fn decrement(&self, key: &str) -> Result<()> {
+        self.update(key, |old| {
+            let count = old.map_or(0, decode_count).saturating_sub(1);
+            Some(count.to_be_bytes().to_vec())
+        })?;
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        self.store.get(key.as_bytes())
+    }
+
+    /// Applies `next` atomically and returns the previous value.
+    fn update(
+        &self,
+        key: &str,
+        next: impl Fn(Option<&[u8]>) -> Option<Vec<u8>>,
+    ) -> Result<Option<Vec<u8>>> {
+        self.store
+            .fetch_and_update(key.as_bytes(), &mut |old| next(old))
+    }
+
+    fn replace(&self, key: &str, value: Option<Vec<u8>>) -> Result<Option<Vec<u8>>> {
+        self.update(key, |_| value.clone())
+    }
+
+    fn scan(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        self.store.scan_str(prefix)
+    }
+}
+
+impl Default for QuotaStore {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+impl Period {
+    fn name(self) -> &'static str {
+        match self {
+            Period::Daily => "daily",
+            Period::Monthly => "monthly",
+        }
+    }
+
+    fn label(self, now: DateTime<Utc>) -> String {
+        match self {
+            Period::Daily => now.format("day:%Y-%m-%d").to_string(),
+            Period::Monthly => now.format("month:%Y-%m").to_string(),
+        }
+    }
+
+    fn limit(self, limits: QuotaLimits) -> u64 {
+        match self {
+            Period::Daily => limits.daily_calls,
+            Period::Monthly => limits.monthly_calls,
+        }
+    }
+
+    /// Start of the next UTC day or calendar month.
+    fn resets_at(self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let today = now.date_naive();
+        let next = match self {
+            Period::Daily => today.succ_opt(),
+            Period::Monthly => NaiveDate::from_ymd_opt(today.year(), today.month(), 1)
+                .and_then(|first| first.checked_add_months(Months::new(1))),
+        };
+        next.and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map_or(now, |midnight| midnight.and_utc())
+    }
+}
+
+fn usage_key(owner: &str, scope: &str, period: &str) -> String {
+    format!("usage|{}|{}|{}", owner, scope, period)
+}
+
+fn override_key(owner: &str, scope: &str) -> String {
+    format!("override|{}|{}", owner, scope)
+}
+
+/// `(scope, period)` of a usage key.
+fn split_scope(key: &str) -> Option<(&str, &str)> {
+    let (rest, period) = key.rsplit_once('|')?;
+    let (_, scope) = rest.rsplit_once('|')?;
+    Some((scope, period))
+}
+
+fn decode_count(bytes: &[u8]) -> u64 {
+    bytes.try_into().map(u64::from_be_bytes).unwrap_or(0)
+}
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
@@ -0,0 +1,152 @@
+//! Per-minute request counters behind the HTTP rate limits.
+//!
+//! With a sled tree the counters survive restarts, so a deploy does not hand
+//! every caller a fresh budget. Counters from an earlier minute are treated
+//! as empty when read and removed by [`RateLimitStore::sweep`].
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::clock::SharedClock;
+use crate::error::Result;
+use crate::storage::{KvStore, MemoryKv};
+
+pub struct RateLimitStore {
+    store: Box<dyn KvStore>,
+    clock: SharedClock,
+    // Minute the memory map was last swept in
+    swept_minute: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct RateWindow {
+    // Minutes since the Unix epoch
+    minute: u64,
+    count: u32,
+}
+
+impl RateLimitStore {
+    pub fn in_memory() -> Self {
+        Self::with_store(Box::new(MemoryKv::default()))
+    }
+
+    pub fn persistent(tree: sled::Tree) -> Self {
+        Self::with_store(Box::new(tree))
+    }
+
+    fn with_store(store: Box<dyn KvStore>) -> Self {
+        Self {
+            store,
+            clock: SharedClock::default(),
+            swept_minute: AtomicU64::new(0),
+        }
+    }
+
+    /// The clock that decides which minute a request counts against.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn is_persistent(&self) -> bool {
+        self.store.is_persistent()
+    }
+
+    /// Counts a request against `key` unless it already made `limit` this
+    /// minute; false when it is over the limit.
+    pub fn try_acquire(&self, key: &str, limit: u32) -> Result<bool> {
+        let minute = self.current_minute();
+        let next = |old: Option<RateWindow>| {
+            let count = live_count(old, minute);
+            let allowed = count < limit;
+            let count = if allowed { count + 1 } else { count };
+            (RateWindow { minute, count }, allowed)
+        };
+        self.update(key, next)
+    }
+
+    /// Counts a request against `key` whatever its total.
+    pub fn record(&self, key: &str) -> Result<()> {
+        let minute = self.current_minute();
+        let next = |old: Option<RateWindow>| {
+            let count = live_count(old, minute).saturating_add(1);
+            (RateWindow { minute, count }, ())
+        };
+        self.update(key, next)
+    }
+
+    /// Requests counted against `key` this minute.
+    pub fn current(&self, key: &str) -> Result<u32> {
+        let minute = self.current_minute();
+        let window = self
+            .store
+            .get(key.as_bytes())?
+            .and_then(|bytes| decode(&bytes));
+        Ok(live_count(window, minute))
+    }
+
+    /// Keys starting with `prefix` that have requests this minute.
+    pub fn active(&self, prefix: &str) -> usize {
+        let minute = self.current_minute();
+        self.store
+            .scan_prefix(prefix.as_bytes())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|(_, bytes)| decode(bytes))
+            .filter(|window| window.minute == minute)
+            .count()
+    }
+
+    /// Removes counters from earlier minutes; returns how many went.
+    pub fn sweep(&self) -> Result<usize> {
+        let minute = self.current_minute();
+        let mut removed = 0;
+        for (key, bytes) in self.store.scan_prefix(b"")? {
+            let stale = decode(&bytes).is_none_or(|window| window.minute != minute);
+            // Only remove it if no request touched it meanwhile
+            if stale && self.store.remove_if(&key, &bytes)? {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn current_minute(&self) -> u64 {
+        self.clock.timestamp().max(0) as u64 / 60
+    }
+
+    fn update<T>(
+        &self,
+        key: &str,
+        next: impl Fn(Option<RateWindow>) -> (RateWindow, T),
+    ) -> Result<T> {
+        if !self.store.is_persistent() {
+            // Nothing else may clean the memory map, so the first request of
+            // each minute sweeps it
+            let minute = self.current_minute();
+            let last = self.swept_minute.load(Ordering::Relaxed);
+            if last != minute
+                && self
+                    .swept_minute
+                    .compare_exchange(last, minute, Ordering::Relaxed, Ordering::Relaxed)
+                    .is_ok()
+            {
+                self.sweep()?;
+            }
+        }
+        let old = self.store.fetch_and_update(key.as_bytes(), &mut |old| {
+            serde_json::to_vec(&next(old.and_then(decode)).0).ok()
+        })?;
+        Ok(next(old.as_deref().and_then(decode)).1)
+    }
+}
+
+fn decode(bytes: &[u8]) -> Option<RateWindow> {
+    serde_json::from_slice(bytes).ok()
+}
+
+fn live_count(window: Option<RateWindow>, minute: u64) -> u32 {
+    window
+        .filter(|window| window.minute == minute)
+        .map_or(0, |window| window.count)
+}
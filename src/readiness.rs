@@ -0,0 +1,165 @@
+//! `/readyz`: whether this instance can serve traffic, component by component.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::server::NovaServer;
+use crate::tools::gecko_terminal::helpers::GECKO_TERMINAL_API;
+use crate::tools::gecko_terminal::GetGeckoNetworksInput;
+use crate::tools::upstream_health::UpstreamState;
+
+/// While the canary upstream has no recent success, probe it at most this often.
+const CANARY_RETRY: Duration = Duration::from_secs(30);
+const CANARY_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentState {
+    Ok,
+    Failed,
+    /// Not configured for this instance.
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentStatus {
+    pub status: ComponentState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl ComponentStatus {
+    fn ok(detail: impl Into<String>) -> Self {
+        Self {
+            status: ComponentState::Ok,
+            detail: Some(detail.into()),
+        }
+    }
+
+    fn failed(detail: impl Into<String>) -> Self {
+        Self {
+            status: ComponentState::Failed,
+            detail: Some(detail.into()),
+        }
+    }
+
+    fn skipped() -> Self {
+        Self {
+            status: ComponentState::Skipped,
+            detail: None,
+        }
+    }
+}
+
+/// Body of `/readyz`; `ready` is false when any component failed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadinessReport {
+    pub status: String,
+    pub ready: bool,
+    pub components: BTreeMap<String, ComponentStatus>,
+    pub upstreams: BTreeMap<String, UpstreamState>,
+}
+
+/// State behind the checks: the sled tree written on each check and the
+/// time of the last canary probe.
+#[derive(Default)]
+pub struct Readiness {
+    probe_tree: Option<sled::Tree>,
+    last_canary: Mutex<Option<Instant>>,
+}
+
+impl Readiness {
+    /// Without a probe tree the storage check is skipped.
+    pub fn new(probe_tree: Option<sled::Tree>) -> Self {
+        Self {
+            probe_tree,
+            last_canary: Mutex::new(None),
+        }
+    }
+
+    pub async fn check(&self, server: &NovaServer) -> ReadinessReport {
+        let mut components = BTreeMap::new();
+        components.insert("storage".to_string(), self.check_storage());
+        let registry = match server.plugin_manager().check_registry() {
+            Ok(plugins) => ComponentStatus::ok(format!("{} plugins loaded", plugins)),
+            Err(e) => ComponentStatus::failed(e.to_string()),
+        };
+        components.insert("plugin_registry".to_string(), registry);
+        components.insert(
+            "upstream_canary".to_string(),
+            self.check_canary(server).await,
+        );
+
+        let upstreams = server
+            .upstream_statuses()
+            .into_iter()
+            .map(|status| (status.name, status.state))
+            .collect();
+        let ready = components
+            .values()
+            .all(|component| component.status != ComponentState::Failed);
+        ReadinessReport {
+            status: if ready { "ready" } else { "not_ready" }.to_string(),
+            ready,
+            components,
+            upstreams,
+        }
+    }
+
+    fn check_storage(&self) -> ComponentStatus {
+        let Some(tree) = &self.probe_tree else {
+            return ComponentStatus::skipped();
+        };
+        let stamp = Utc::now().timestamp_millis().to_be_bytes();
+        let written = tree.insert("probe", &stamp).and_then(|_| tree.get("probe"));
+        match written {
+            Ok(Some(value)) if value.as_ref() == stamp => ComponentStatus::ok("writable"),
+            Ok(_) => ComponentStatus::failed("probe write did not read back"),
+            Err(e) => ComponentStatus::failed(e.to_string()),
+        }
+    }
+
+    /// With `readiness.upstream_canary`, GeckoTerminal must have answered
+    /// within `canary_max_age_secs`; otherwise `/networks` is probed.
+    async fn check_canary(&self, server: &NovaServer) -> ComponentStatus {
+        let config = server.runtime().current().readiness.clone();
+        if !config.upstream_canary {
+            return ComponentStatus::skipped();
+        }
+        let last_success = server
+            .upstream_statuses()
+            .into_iter()
+            .find(|status| status.name == GECKO_TERMINAL_API)
+            .and_then(|status| status.last_success_at);
+        let age = last_success.map(|at| Utc::now().timestamp().saturating_sub(at).max(0) as u64);
+        if let Some(age) = age.filter(|&age| age <= config.canary_max_age_secs) {
+            return ComponentStatus::ok(format!("last success {}s ago", age));
+        }
+
+        let due = {
+            let mut last = self.last_canary.lock().unwrap_or_else(|e| e.into_inner());
+            let due = last.is_none_or(|at| at.elapsed() >= CANARY_RETRY);
+            if due {
+                *last = Some(Instant::now());
+            }
+            due
+        };
+        if !due {
+            return ComponentStatus::failed(format!(
+                "no successful {} call in the last {}s",
+                GECKO_TERMINAL_API, config.canary_max_age_secs
+            ));
+        }
+        let probe = server
+            .gecko_terminal_tools()
+            .get_networks(GetGeckoNetworksInput {});
+        match tokio::time::timeout(CANARY_TIMEOUT, probe).await {
+            Ok(Ok(_)) => ComponentStatus::ok("probe succeeded"),
+            Ok(Err(e)) => ComponentStatus::failed(format!("probe failed: {}", e)),
+            Err(_) => ComponentStatus::failed("probe timed out"),
+        }
+    }
+}
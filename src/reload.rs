@@ -0,0 +1,131 @@
+use crate::config::{CliArgs, NovaConfig};
+use crate::error::Result;
+use arc_swap::ArcSwap;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Applies a new `server.log_level`; installed by the binary around its tracing filter.
+pub type LogLevelHook = Arc<dyn Fn(&str) -> Result<()> + Send + Sync>;
+
+/// Live configuration shared by every transport.
+///
+/// Only rate limits, API keys, tool flags, currency rates and the log level
+/// are picked up on reload; everything else (ports, body limits, storage) keeps its startup value.
+#[derive(Clone)]
+pub struct RuntimeConfig {
+    current: Arc<ArcSwap<NovaConfig>>,
+    cli: Arc<CliArgs>,
+    log_level_hook: Option<LogLevelHook>,
+}
+
+/// Reloadable settings that differed from the running config.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReloadSummary {
+    pub changed: Vec<String>,
+}
+
+impl RuntimeConfig {
+    pub fn new(config: NovaConfig) -> Self {
+        Self {
+            current: Arc::new(ArcSwap::from_pointee(config)),
+            cli: Arc::new(CliArgs::default()),
+            log_level_hook: None,
+        }
+    }
+
+    /// Keeps startup CLI overrides (including `--config`) in effect across reloads.
+    pub fn with_cli_args(mut self, cli: CliArgs) -> Self {
+        self.cli = Arc::new(cli);
+        self
+    }
+
+    pub fn with_log_level_hook(mut self, hook: LogLevelHook) -> Self {
+        self.log_level_hook = Some(hook);
+        self
+    }
+
+    pub fn current(&self) -> Arc<NovaConfig> {
+        self.current.load_full()
+    }
+
+    /// Edits the live config in place, e.g. from an admin policy change.
+    pub fn update(&self, edit: impl Fn(&mut NovaConfig)) {
+        self.current.rcu(|current| {
+            let mut next = NovaConfig::clone(current);
+            edit(&mut next);
+            next
+        });
+    }
+
+    /// Re-reads the config file and environment and applies the reloadable subset.
+    pub fn reload(&self) -> Result<ReloadSummary> {
+        Ok(self.apply(NovaConfig::load_with(&self.cli)?))
+    }
+
+    pub fn apply(&self, source: NovaConfig) -> ReloadSummary {
+        let previous = self.current();
+        let mut next = NovaConfig::clone(&previous);
+        let mut summary = ReloadSummary::default();
+
+        if previous.apis.rate_limit_per_minute != source.apis.rate_limit_per_minute {
+            next.apis.rate_limit_per_minute = source.apis.rate_limit_per_minute;
+            summary
+                .changed
+                .push("apis.rate_limit_per_minute".to_string());
+        }
+        if previous.auth.allowed_keys != source.auth.allowed_keys {
+            next.auth.allowed_keys = source.auth.allowed_keys;
+            summary.changed.push("auth.allowed_keys".to_string());
+        }
+        if previous.auth.named_keys != source.auth.named_keys {
+            next.auth.named_keys = source.auth.named_keys;
+            summary.changed.push("auth.named_keys".to_string());
+        }
+        if previous.tools != source.tools {
+            next.tools = source.tools;
+            summary.changed.push("tools".to_string());
+        }
+        if previous.preferences != source.preferences {
+            next.preferences = source.preferences;
+            summary.changed.push("preferences".to_string());
+        }
+        let log_level_changed = previous.server.log_level != source.server.log_level;
+        if log_level_changed {
+            next.server.log_level = source.server.log_level;
+            summary.changed.push("server.log_level".to_string());
+        }
+
+        if log_level_changed {
+            if let Some(hook) = &self.log_level_hook {
+                if let Err(e) = hook(&next.server.log_level) {
+                    tracing::warn!("Failed to apply log level: {}", e);
+                }
+            }
+        }
+        self.current.store(Arc::new(next));
+        summary
+    }
+}
+
+/// Runs `on_reload` each time the process receives SIGHUP (no-op off Unix).
+pub fn spawn_sighup_listener<F>(on_reload: F)
+where
+    F: Fn() + Send + 'static,
+{
+    #[cfg(unix)]
+    tokio::spawn(async move {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(e) => {
+                tracing::warn!("SIGHUP reload unavailable: {}", e);
+                return;
+            }
+        };
+        while hangup.recv().await.is_some() {
+            on_reload();
+        }
+    });
+    #[cfg(not(unix))]
+    drop(on_reload);
+}
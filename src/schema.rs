@@ -0,0 +1,468 @@
+use dashmap::DashMap;
+use jsonschema::error::ValidationErrorKind;
+use jsonschema::{Draft, JSONSchema, SchemaResolver, SchemaResolverError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::error::{NovaError, Result};
+
+/// One argument that failed schema validation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    /// Dotted path to the offending value (`network`, `filters.0.name`);
+    /// `arguments` when the problem is with the object as a whole.
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// JSON Schema drafts a schema may declare with `$schema`; draft-07 when it
+/// declares none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum SchemaDraft {
+    #[serde(rename = "draft-04")]
+    Draft4,
+    #[serde(rename = "draft-06")]
+    Draft6,
+    #[default]
+    #[serde(rename = "draft-07")]
+    Draft7,
+    #[serde(rename = "2019-09")]
+    Draft201909,
+    #[serde(rename = "2020-12")]
+    Draft202012,
+}
+
+impl SchemaDraft {
+    pub const ALL: [SchemaDraft; 5] = [
+        SchemaDraft::Draft4,
+        SchemaDraft::Draft6,
+        SchemaDraft::Draft7,
+        SchemaDraft::Draft201909,
+        SchemaDraft::Draft202012,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SchemaDraft::Draft4 => "draft-04",
+            SchemaDraft::Draft6 => "draft-06",
+            SchemaDraft::Draft7 => "draft-07",
+            SchemaDraft::Draft201909 => "2019-09",
+            SchemaDraft::Draft202012 => "2020-12",
+        }
+    }
+
+    /// The draft `schema` declares in `$schema`, matched with or without the
+    /// trailing `#` and on either `http` or `https`. Fails on a `$schema` that
+    /// names no supported draft.
+    pub fn detect(schema: &Value) -> std::result::Result<Self, String> {
+        let Some(declared) = schema.get("$schema") else {
+            return Ok(Self::default());
+        };
+        let uri = declared.as_str().unwrap_or_default();
+        let normalized = uri
+            .trim()
+            .trim_end_matches('#')
+            .trim_start_matches("https://")
+            .trim_start_matches("http://");
+        let draft = match normalized {
+            "json-schema.org/draft-04/schema" => SchemaDraft::Draft4,
+            "json-schema.org/draft-06/schema" => SchemaDraft::Draft6,
+            "json-schema.org/draft-07/schema" => SchemaDraft::Draft7,
+            "json-schema.org/draft/2019-09/schema" => SchemaDraft::Draft201909,
+            "json-schema.org/draft/2020-12/schema" => SchemaDraft::Draft202012,
+            _ => {
+                let supported: Vec<&str> = Self::ALL.iter().map(SchemaDraft::as_str).collect();
+                return Err(format!(
+                    "unsupported $schema {}; use one of {}",
+                    declared,
+                    supported.join(", ")
+                ));
+            }
+        };
+        Ok(draft)
+    }
+
+    /// Where bundled subschemas go: `$defs` from 2019-09 on.
+    pub fn definitions_keyword(&self) -> &'static str {
+        match self {
+            SchemaDraft::Draft201909 | SchemaDraft::Draft202012 => "$defs",
+            _ => "definitions",
+        }
+    }
+
+    fn draft(self) -> Draft {
+        match self {
+            SchemaDraft::Draft4 => Draft::Draft4,
+            SchemaDraft::Draft6 => Draft::Draft6,
+            SchemaDraft::Draft7 => Draft::Draft7,
+            SchemaDraft::Draft201909 => Draft::Draft201909,
+            SchemaDraft::Draft202012 => Draft::Draft202012,
+        }
+    }
+}
+
+impl fmt::Display for SchemaDraft {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Compiles `schema` with the draft its `$schema` names (draft-07 without
+/// one). Errors carry the compiler's message, prefixed with the draft, for
+/// the caller to wrap. Remote `$ref`s are never fetched here; plugin schemas
+/// have them bundled at registration.
+pub fn compile(schema: &Value) -> std::result::Result<JSONSchema, String> {
+    let draft = SchemaDraft::detect(schema)?;
+    JSONSchema::options()
+        .with_draft(draft.draft())
+        .with_resolver(NoRemoteRefs)
+        .compile(schema)
+        .map_err(|err| format!("{} (checked as JSON Schema {})", err, draft))
+}
+
+/// Keeps validation offline and deterministic: the default resolver would
+/// fetch `http(s)` and `file` refs mid-validation.
+struct NoRemoteRefs;
+
+impl SchemaResolver for NoRemoteRefs {
+    fn resolve(
+        &self,
+        _root_schema: &Value,
+        url: &reqwest::Url,
+        _original_reference: &str,
+    ) -> std::result::Result<Arc<Value>, SchemaResolverError> {
+        Err(anyhow::anyhow!("remote $ref {} is not bundled", url))
+    }
+}
+
+/// Checks that every `$ref` in `schema` points inside it (`#/definitions/x`),
+/// failing with the first that is remote or points at nothing. Anchors
+/// (`#name`) are not supported.
+pub fn check_refs(schema: &Value) -> std::result::Result<(), String> {
+    let mut refs = Vec::new();
+    collect_refs(schema, &mut refs);
+    for reference in refs {
+        let Some(fragment) = reference.strip_prefix('#') else {
+            return Err(format!("$ref {} is remote and was not bundled", reference));
+        };
+        let pointer = urlencoding::decode(fragment)
+            .map_err(|_| format!("$ref {} is not a valid JSON pointer", reference))?;
+        if !pointer.is_empty() && !pointer.starts_with('/') {
+            return Err(format!(
+                "$ref {} uses an anchor; use a JSON pointer",
+                reference
+            ));
+        }
+        if schema.pointer(&pointer).is_none() {
+            return Err(format!("$ref {} points at nothing", reference));
+        }
+    }
+    Ok(())
+}
+
+/// Every `$ref` string in `schema`, skipping keywords that hold data rather
+/// than subschemas.
+pub(crate) fn collect_refs<'a>(schema: &'a Value, refs: &mut Vec<&'a str>) {
+    match schema {
+        Value::Object(map) => {
+            for (key, value) in map {
+                match (key.as_str(), value) {
+                    ("$ref", Value::String(reference)) => refs.push(reference),
+                    ("enum" | "const" | "default" | "examples", _) => {}
+                    _ => collect_refs(value, refs),
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_refs(item, refs)),
+        _ => {}
+    }
+}
+
+/// Checks `instance` against `schema`, returning one entry per violation.
+pub fn field_errors(schema: &JSONSchema, instance: &Value) -> Vec<FieldError> {
+    match schema.validate(instance) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors
+            .map(|error| {
+                let mut path = error.instance_path.clone().into_vec();
+                // A missing property is reported on its parent; point at the property itself.
+                if let ValidationErrorKind::Required { property } = &error.kind {
+                    path.push(property.as_str().unwrap_or_default().to_string());
+                }
+                FieldError {
+                    field: if path.is_empty() {
+                        "arguments".to_string()
+                    } else {
+                        path.join(".")
+                    },
+                    message: error.to_string(),
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Validates tool arguments, failing with [`NovaError::InvalidArguments`].
+/// Compiles `schema` on every call; [`CompiledSchemas`] keeps the result.
+pub fn validate_arguments(tool: &str, schema: &Value, arguments: &Value) -> Result<()> {
+    check_arguments(tool, &compile_input(tool, schema)?, arguments)
+}
+
+fn compile_input(tool: &str, schema: &Value) -> Result<JSONSchema> {
+    compile(schema).map_err(|err| {
+        NovaError::validation_error(format!("{} has an invalid input schema: {}", tool, err))
+    })
+}
+
+fn check_arguments(tool: &str, compiled: &JSONSchema, arguments: &Value) -> Result<()> {
+    let errors = field_errors(compiled, arguments);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(NovaError::invalid_arguments(tool, errors))
+    }
+}
+
+/// Compiled input schemas by tool name, each kept with the schema it was
+/// compiled from so a tool whose schema changed is compiled again.
+#[derive(Default)]
+pub struct CompiledSchemas {
+    compiled: DashMap<String, (Value, Arc<JSONSchema>)>,
+}
+
+impl CompiledSchemas {
+    /// Like [`validate_arguments`], compiling `schema` only when `tool` has
+    /// no compiled copy of it yet.
+    pub fn validate(&self, tool: &str, schema: &Value, arguments: &Value) -> Result<()> {
+        let compiled = self.compiled(tool, schema)?;
+        check_arguments(tool, &compiled, arguments)
+    }
+
+    /// Compiles `schema` for `tool` ahead of its first call.
+    pub fn prepare(&self, tool: &str, schema: &Value) -> Result<()> {
+        self.compiled(tool, schema).map(drop)
+    }
+
+    pub fn remove(&self, tool: &str) {
+        self.compiled.remove(tool);
+    }
+
+    fn compiled(&self, tool: &str, schema: &Value) -> Result<Arc<JSONSchema>> {
+        if let Some(entry) = self.compiled.get(tool) {
+            if entry.0 == *schema {
+                return Ok(Arc::clone(&entry.1));
+            }
+        }
+        let compiled = Arc::new(compile_input(tool, schema)?);
+        self.compiled
+            .insert(tool.to_string(), (schema.clone(), Arc::clone(&compiled)));
+        Ok(compiled)
+    }
+}
+
+/// Makes `arguments` fit `schema` where the intent is unambiguous, before
+/// validation: absent properties get their schema `default`, and strings
+/// become the integer, number or boolean a property asks for when they parse
+/// as one and the property does not also accept strings. Follows
+/// `properties` and `items` only (not `$ref`s or combinators); anything else
+/// is left for validation to reject. Missing arguments count as `{}` when
+/// the schema wants an object.
+pub fn coerce_arguments(schema: &Value, arguments: &mut Value) {
+    if arguments.is_null() && types(schema).is_some_and(|types| types.contains("object")) {
+        *arguments = Value::Object(serde_json::Map::new());
+    }
+    coerce(schema, arguments);
+}
+
+fn coerce(schema: &Value, value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+                return;
+            };
+            for (name, property) in properties {
+                match object.get_mut(name) {
+                    Some(child) => coerce(property, child),
+                    None => {
+                        if let Some(default) = property.get("default") {
+                            object.insert(name.clone(), default.clone());
+                        }
+                    }
+                }
+            }
+        }
+        Value::Array(items) => match schema.get("items") {
+            Some(Value::Array(positional)) => {
+                for (item, item_schema) in items.iter_mut().zip(positional) {
+                    coerce(item_schema, item);
+                }
+            }
+            Some(item_schema) => items.iter_mut().for_each(|item| coerce(item_schema, item)),
+            None => {}
+        },
+        Value::String(text) => {
+            if let Some(coerced) = coerce_string(schema, text) {
+                *value = coerced;
+            }
+        }
+        _ => {}
+    }
+}
+
+fn coerce_string(schema: &Value, text: &str) -> Option<Value> {
+    let types = types(schema)?;
+    if types.contains("string") {
+        return None;
+    }
+    let text = text.trim();
+    if types.contains("integer") || types.contains("number") {
+        if let Ok(integer) = text.parse::<i64>() {
+            return Some(Value::from(integer));
+        }
+    }
+    if types.contains("number") {
+        if let Some(number) = text
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+        {
+            return Some(Value::Number(number));
+        }
+    }
+    if types.contains("boolean") {
+        if text.eq_ignore_ascii_case("true") {
+            return Some(Value::Bool(true));
+        }
+        if text.eq_ignore_ascii_case("false") {
+            return Some(Value::Bool(false));
+        }
+    }
+    None
+}
+
+/// How a new input schema can reject arguments the old one accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaChangeKind {
+    RequiredAdded,
+    PropertyRemoved,
+    TypeChanged,
+}
+
+/// One breaking difference between two input schemas.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaChange {
+    /// Dotted path to the property (`city`, `filters.name`, `ids[]`);
+    /// `arguments` for the schema as a whole.
+    pub field: String,
+    pub kind: SchemaChangeKind,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Changes from `old` to `new` that can break callers written against `old`:
+/// newly required properties, removed properties and narrowed `type`s, at any
+/// depth of `properties` and `items`. Loosened constraints are not reported.
+pub fn breaking_changes(old: &Value, new: &Value) -> Vec<SchemaChange> {
+    let mut changes = Vec::new();
+    diff(old, new, "", &mut changes);
+    changes.sort_by(|a, b| a.field.cmp(&b.field));
+    changes
+}
+
+fn diff(old: &Value, new: &Value, path: &str, changes: &mut Vec<SchemaChange>) {
+    let field = |name: &str| match path {
+        "" => name.to_string(),
+        path => format!("{}.{}", path, name),
+    };
+    if let Some(message) = narrowed_type(old, new) {
+        changes.push(SchemaChange {
+            field: if path.is_empty() {
+                "arguments".to_string()
+            } else {
+                path.to_string()
+            },
+            kind: SchemaChangeKind::TypeChanged,
+            message,
+        });
+        return;
+    }
+
+    let old_required = required(old);
+    for name in required(new).difference(&old_required) {
+        changes.push(SchemaChange {
+            field: field(name),
+            kind: SchemaChangeKind::RequiredAdded,
+            message: "is now required".to_string(),
+        });
+    }
+    if let Some(old_properties) = old.get("properties").and_then(Value::as_object) {
+        let new_properties = new.get("properties").and_then(Value::as_object);
+        for (name, old_property) in old_properties {
+            match new_properties.and_then(|properties| properties.get(name)) {
+                Some(new_property) => diff(old_property, new_property, &field(name), changes),
+                None => changes.push(SchemaChange {
+                    field: field(name),
+                    kind: SchemaChangeKind::PropertyRemoved,
+                    message: "was removed".to_string(),
+                }),
+            }
+        }
+    }
+    if let (Some(old_items), Some(new_items)) = (old.get("items"), new.get("items")) {
+        let items = if path.is_empty() {
+            "[]".to_string()
+        } else {
+            format!("{}[]", path)
+        };
+        diff(old_items, new_items, &items, changes);
+    }
+}
+
+fn required(schema: &Value) -> BTreeSet<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// `None` when every value of the old `type` is still accepted. A missing
+/// `type` accepts anything, and `number` covers `integer`.
+fn narrowed_type(old: &Value, new: &Value) -> Option<String> {
+    let new_types = types(new)?;
+    let accepted =
+        |ty: &str| new_types.contains(ty) || (ty == "integer" && new_types.contains("number"));
+    let describe = |types: &BTreeSet<&str>| types.iter().copied().collect::<Vec<_>>().join(" or ");
+    match types(old) {
+        None => Some(format!("type narrowed to {}", describe(&new_types))),
+        Some(old_types) if !old_types.iter().all(|ty| accepted(ty)) => Some(format!(
+            "type changed from {} to {}",
+            describe(&old_types),
+            describe(&new_types)
+        )),
+        Some(_) => None,
+    }
+}
+
+fn types(schema: &Value) -> Option<BTreeSet<&str>> {
+    match schema.get("type")? {
+        Value::String(ty) => Some(BTreeSet::from([ty.as_str()])),
+        Value::Array(types) => Some(types.iter().filter_map(Value::as_str).collect()),
+        _ => None,
+    }
+}
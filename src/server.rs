@@ -1,37 +1,567 @@
-use crate::config::NovaConfig;
+use crate::audit::AuditLog;
+use crate::clock::SharedClock;
+use crate::config::{CliArgs, NovaConfig, TimeoutConfig};
 use crate::error::Result;
-use crate::mcp::dto::Tool;
-use crate::plugins::{PluginManager, RequestContext};
+use crate::events::{EventBus, EventCounters, EventWebhook};
+use crate::http::ApiLayer;
+use crate::jobs::JobScheduler;
+use crate::mcp::dto::{Tool, ToolAnnotations};
+use crate::mcp::limits::PayloadLimits;
+use crate::oauth::OAuthClientStore;
+use crate::outbound;
+use crate::pipeline::PipelineRegistry;
+use crate::plugins::{FeedbackStore, PluginJobs, PluginManager, RequestContext};
+use crate::preferences::PreferenceStore;
+use crate::quotas::QuotaStore;
+use crate::rate_limits::RateLimitStore;
+use crate::reload::{LogLevelHook, RuntimeConfig};
+use crate::schema;
+use crate::storage::{self, StorageUsage};
 // Re-export MCP DTOs under `server` for backward compatibility
 pub use crate::mcp::dto::{McpError, McpRequest, McpResponse, ToolCall, ToolResult};
-use crate::tools::gecko_terminal::GeckoTerminalTools;
+use crate::readiness::{Readiness, ReadinessReport};
+use crate::tools::concurrency::ToolConcurrency;
+use crate::tools::gecko_terminal::helpers::{DEFAULT_BASE_URL, GECKO_TERMINAL_API};
+use crate::tools::gecko_terminal::{
+    ArbitrageTools, CrossNetworkTools, GeckoTerminalTools, GetGeckoNetworksInput,
+    TokenMappingStore, WhaleTools, WhaleWatchStore,
+};
+use crate::tools::native::{NativeTools, ToolProvider};
+use crate::tools::negative_cache::NegativeCache;
 use crate::tools::new_pools::NewPoolsTools;
+use crate::tools::rate_limit::UpstreamRateLimiter;
 use crate::tools::search_pools::SearchPoolsTools;
+use crate::tools::solana::{SolanaTools, SOLANA_RPC_API};
+use crate::tools::token_unlocks::{TokenUnlockTools, TOKEN_UNLOCKS_API};
 use crate::tools::trending_pools::TrendingPoolsTools;
+use crate::tools::upstream_health::{UpstreamHealth, UpstreamStatus};
+use axum::{extract::Request, response::IntoResponse, routing::Route};
 use serde_json::json;
-use std::sync::Arc;
+use std::convert::Infallible;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tower::{Layer, Service};
+
+/// Names of the tools implemented in this crate (as opposed to registered plugins).
+pub const BUILTIN_TOOLS: &[&str] = &[
+    "get_gecko_networks",
+    "get_gecko_token",
+    "get_gecko_pool",
+    "get_trending_pools",
+    "search_pools",
+    "get_new_pools",
+    "find_token_across_networks",
+    "find_arbitrage_spreads",
+    "get_solana_token_holders",
+    "watch_whales",
+    "unwatch_whales",
+    "get_token_unlocks",
+    "set_my_preferences",
+    "get_my_usage",
+    "get_job_status",
+];
 
 pub struct NovaServer {
     gecko_terminal_tools: GeckoTerminalTools,
     trending_pools_tools: TrendingPoolsTools,
     search_pools_tools: SearchPoolsTools,
     new_pools_tools: NewPoolsTools,
+    cross_network_tools: CrossNetworkTools,
+    arbitrage_tools: ArbitrageTools,
+    whale_tools: WhaleTools,
+    solana_tools: SolanaTools,
+    token_unlock_tools: TokenUnlockTools,
+    // Built-in upstreams; plugin endpoints are tracked by the plugin manager
+    upstream_health: Arc<UpstreamHealth>,
     plugin_manager: Arc<PluginManager>,
+    pipelines: PipelineRegistry,
+    preferences: Arc<PreferenceStore>,
+    oauth_clients: Arc<OAuthClientStore>,
+    audit: Arc<AuditLog>,
+    event_counters: Arc<EventCounters>,
+    event_webhook: Option<Arc<EventWebhook>>,
+    rate_limits: Arc<RateLimitStore>,
+    quotas: Arc<QuotaStore>,
+    feedback: Arc<FeedbackStore>,
+    plugin_jobs: Arc<PluginJobs>,
+    readiness: Arc<Readiness>,
+    jobs: Arc<JobScheduler>,
+    database: Option<sled::Db>,
+    limits: PayloadLimits,
+    timeouts: TimeoutConfig,
+    tool_concurrency: ToolConcurrency,
+    runtime: RuntimeConfig,
+    clock: SharedClock,
+    // Embedder layers around the HTTP API routes, first added outermost
+    api_layers: Vec<ApiLayer>,
+    // Embedder tools listed after the built-in ones
+    native_tools: NativeTools,
+    schemas: schema::CompiledSchemas,
 }
 
 impl NovaServer {
-    pub fn new(_config: NovaConfig, plugin_manager: Arc<PluginManager>) -> Self {
-        let gecko_terminal_tools = GeckoTerminalTools::new();
-        let trending_pools_tools = TrendingPoolsTools::new();
-        let search_pools_tools = SearchPoolsTools::new();
-        let new_pools_tools = NewPoolsTools::new();
-        Self {
+    /// Like [`try_new`](Self::try_new), for a config that already passed
+    /// [`NovaConfig::validate`].
+    ///
+    /// # Panics
+    ///
+    /// If an outbound HTTP client cannot be built, e.g. from a bad CA file.
+    pub fn new(config: NovaConfig, plugin_manager: Arc<PluginManager>) -> Self {
+        match Self::try_new(config, plugin_manager) {
+            Ok(server) => server,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Fails when the `[outbound]` settings do not give a working HTTP client.
+    pub fn try_new(config: NovaConfig, plugin_manager: Arc<PluginManager>) -> Result<Self> {
+        // One bucket for every GeckoTerminal-backed tool: the upstream limit is per client IP.
+        let gecko_limiter = Arc::new(UpstreamRateLimiter::new(
+            GECKO_TERMINAL_API,
+            config.apis.gecko_terminal_rate_limit_per_minute,
+            Duration::from_millis(config.apis.upstream_max_wait_ms),
+        ));
+        let http = outbound::build_client(&config.outbound, GECKO_TERMINAL_API, |b| {
+            b.timeout(Duration::from_secs(10))
+                .user_agent("Nova-MCP/0.1.0")
+        })?;
+        let upstream_health = Arc::new(UpstreamHealth::default());
+        upstream_health.register(GECKO_TERMINAL_API);
+        upstream_health.publish_to(plugin_manager.events().clone());
+        let gecko_base_url = config
+            .apis
+            .gecko_terminal_url
+            .as_deref()
+            .unwrap_or(DEFAULT_BASE_URL);
+        let gecko_terminal_tools =
+            GeckoTerminalTools::with_rate_limiter(Arc::clone(&gecko_limiter))
+                .with_http_client(http.clone())
+                .with_base_url(gecko_base_url)
+                .with_upstream_health(Arc::clone(&upstream_health))
+                .with_networks_ttl(Duration::from_secs(config.cache.networks_ttl_seconds));
+        let trending_pools_tools =
+            TrendingPoolsTools::with_rate_limiter(Arc::clone(&gecko_limiter))
+                .with_http_client(http.clone())
+                .with_base_url(gecko_base_url)
+                .with_upstream_health(Arc::clone(&upstream_health));
+        let search_pools_tools = SearchPoolsTools::with_rate_limiter(Arc::clone(&gecko_limiter))
+            .with_http_client(http.clone())
+            .with_base_url(gecko_base_url)
+            .with_upstream_health(Arc::clone(&upstream_health));
+        let cross_network_tools = CrossNetworkTools::new(search_pools_tools.clone());
+        let mut arbitrage_tools = ArbitrageTools::new()
+            .with_cache_ttl(Duration::from_secs(config.cache.arbitrage_ttl_seconds));
+        if let Some(tool) = &config.apis.cex_price_tool {
+            arbitrage_tools = arbitrage_tools.with_cex_price_tool(tool);
+        }
+        let whale_tools = WhaleTools::with_rate_limiter(Arc::clone(&gecko_limiter))
+            .with_http_client(http.clone())
+            .with_base_url(gecko_base_url)
+            .with_upstream_health(Arc::clone(&upstream_health))
+            .with_events(plugin_manager.events().clone())
+            .with_clock(plugin_manager.clock().clone())
+            .with_config(&config.whales);
+        let new_pools_tools = NewPoolsTools::with_rate_limiter(gecko_limiter)
+            .with_http_client(http)
+            .with_base_url(gecko_base_url)
+            .with_upstream_health(Arc::clone(&upstream_health));
+        let solana_limiter = Arc::new(UpstreamRateLimiter::new(
+            SOLANA_RPC_API,
+            config.apis.solana_rpc_rate_limit_per_minute,
+            Duration::from_millis(config.apis.upstream_max_wait_ms),
+        ));
+        upstream_health.register(SOLANA_RPC_API);
+        let mut solana_tools = SolanaTools::with_rate_limiter(solana_limiter)
+            .with_http_client(outbound::build_client(
+                &config.outbound,
+                SOLANA_RPC_API,
+                |b| {
+                    b.timeout(Duration::from_secs(10))
+                        .user_agent("Nova-MCP/0.1.0")
+                },
+            )?)
+            .with_upstream_health(Arc::clone(&upstream_health));
+        if let Some(url) = &config.apis.solana_rpc_url {
+            solana_tools = solana_tools.with_rpc_url(url);
+        }
+        let token_unlocks_limiter = Arc::new(UpstreamRateLimiter::new(
+            TOKEN_UNLOCKS_API,
+            config.apis.token_unlocks_rate_limit_per_minute,
+            Duration::from_millis(config.apis.upstream_max_wait_ms),
+        ));
+        upstream_health.register(TOKEN_UNLOCKS_API);
+        let mut token_unlock_tools = TokenUnlockTools::with_rate_limiter(token_unlocks_limiter)
+            .with_http_client(outbound::build_client(
+                &config.outbound,
+                TOKEN_UNLOCKS_API,
+                |b| {
+                    b.timeout(Duration::from_secs(10))
+                        .user_agent("Nova-MCP/0.1.0")
+                },
+            )?)
+            .with_upstream_health(Arc::clone(&upstream_health))
+            .with_clock(plugin_manager.clock().clone());
+        if let Some(url) = &config.apis.token_unlocks_url {
+            token_unlock_tools = token_unlock_tools.with_base_url(url);
+        }
+        let limits = PayloadLimits::from(&config.limits);
+        let timeouts = config.timeouts.clone();
+        let tool_concurrency = ToolConcurrency::new(&config.limits.tool_concurrency);
+        // `NovaConfig::validate` reports broken pipelines; an unvalidated config runs without them
+        let pipelines = PipelineRegistry::new(config.pipelines.clone()).unwrap_or_else(|err| {
+            tracing::warn!("Ignoring pipelines: {}", err);
+            PipelineRegistry::default()
+        });
+        let clock = plugin_manager.clock().clone();
+        let jobs = Arc::new(JobScheduler::new().with_clock(clock.clone()));
+        if config.cache.networks_ttl_seconds > 0 {
+            // Keeps `network` aliases warm so tool calls rarely wait on the refetch
+            let tools = gecko_terminal_tools.clone();
+            let registered = jobs.register(
+                "gecko_networks_refresh",
+                Duration::from_secs(config.cache.networks_ttl_seconds),
+                move || {
+                    let tools = tools.clone();
+                    async move { tools.get_networks(GetGeckoNetworksInput {}).await.map(drop) }
+                },
+            );
+            if let Err(e) = registered {
+                tracing::warn!("{}", e);
+            }
+        }
+        let plugin_jobs = PluginJobs::in_memory()
+            .with_config(&config.plugins)
+            .with_clock(clock.clone());
+        let events = plugin_manager.events();
+        let event_counters = Arc::new(EventCounters::default());
+        events.attach(event_counters.clone());
+        let audit = Arc::new(AuditLog::in_memory().with_clock(clock.clone()));
+        events.attach(audit.clone());
+        let event_webhook = match &config.events.webhook_url {
+            Some(url) => {
+                let client = outbound::build_client(&config.outbound, url, |b| {
+                    b.timeout(Duration::from_secs(10))
+                })?;
+                EventWebhook::from_config(
+                    &config.events,
+                    client,
+                    plugin_manager.dead_letters().clone(),
+                )
+                .map(Arc::new)
+            }
+            None => None,
+        };
+        if let Some(webhook) = &event_webhook {
+            events.attach(webhook.clone());
+        }
+        let runtime = RuntimeConfig::new(config);
+        Ok(Self {
             gecko_terminal_tools,
             trending_pools_tools,
             search_pools_tools,
             new_pools_tools,
+            cross_network_tools,
+            arbitrage_tools,
+            whale_tools,
+            solana_tools,
+            token_unlock_tools,
+            upstream_health,
             plugin_manager,
+            pipelines,
+            preferences: Arc::new(PreferenceStore::in_memory()),
+            oauth_clients: Arc::new(OAuthClientStore::in_memory().with_clock(clock.clone())),
+            audit,
+            event_counters,
+            event_webhook,
+            rate_limits: Arc::new(RateLimitStore::in_memory().with_clock(clock.clone())),
+            quotas: Arc::new(QuotaStore::in_memory().with_clock(clock.clone())),
+            feedback: Arc::new(FeedbackStore::in_memory().with_clock(clock.clone())),
+            plugin_jobs: Arc::new(plugin_jobs),
+            readiness: Arc::new(Readiness::default()),
+            jobs,
+            database: None,
+            limits,
+            timeouts,
+            tool_concurrency,
+            runtime,
+            clock,
+            api_layers: Vec::new(),
+            native_tools: NativeTools::default(),
+            schemas: schema::CompiledSchemas::default(),
+        })
+    }
+
+    /// A server whose plugin registry lives in a temporary database; every
+    /// other store already defaults to memory.
+    pub fn in_memory(config: NovaConfig) -> Result<Self> {
+        Ok(Self::new(config, Arc::new(PluginManager::in_memory()?)))
+    }
+
+    pub fn with_cli_args(mut self, cli: CliArgs) -> Self {
+        self.runtime = self.runtime.with_cli_args(cli);
+        self
+    }
+
+    /// Lets config reloads change the log level of the hosting binary.
+    pub fn with_log_level_hook(mut self, hook: LogLevelHook) -> Self {
+        self.runtime = self.runtime.with_log_level_hook(hook);
+        self
+    }
+
+    /// Wraps the HTTP API routes (`/rpc`, plugins, admin, ...) in `layer`,
+    /// e.g. extra auth or tenant resolution. It runs before Nova
+    /// authenticates the caller and applies rate limits and quotas, inside
+    /// access rules and load shedding; see [`crate::http::layers`]. Layers run
+    /// in the order they are added.
+    pub fn with_api_layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Route> + Clone + Send + Sync + 'static,
+        L::Service: Service<Request> + Clone + Send + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.api_layers.push(ApiLayer::new(layer));
+        self
+    }
+
+    pub fn api_layers(&self) -> &[ApiLayer] {
+        &self.api_layers
+    }
+
+    pub fn runtime(&self) -> &RuntimeConfig {
+        &self.runtime
+    }
+
+    /// The plugin manager's clock, shared with the job scheduler, the default
+    /// rate-limit, quota, audit, feedback and OAuth client stores and the
+    /// HTTP transport.
+    pub fn clock(&self) -> &SharedClock {
+        &self.clock
+    }
+
+    /// The plugin manager's event bus, which also carries built-in tool
+    /// calls, rate limiting and upstream health.
+    pub fn events(&self) -> &Arc<EventBus> {
+        self.plugin_manager.events()
+    }
+
+    /// Events published since startup, by type.
+    pub fn event_counters(&self) -> &EventCounters {
+        &self.event_counters
+    }
+
+    /// The `events.webhook_url` subscriber, when configured.
+    pub fn event_webhook(&self) -> Option<&Arc<EventWebhook>> {
+        self.event_webhook.as_ref()
+    }
+
+    /// True for built-in tools switched off via `[tools]`; plugins are never affected.
+    pub fn is_tool_disabled(&self, name: &str) -> bool {
+        BUILTIN_TOOLS.contains(&name) && !self.runtime.current().tools.is_enabled(name)
+    }
+
+    /// Whether `tools.coerce_arguments` covers the built-in tool `name`.
+    pub fn coerces_arguments(&self, name: &str) -> bool {
+        self.runtime.current().tools.coerces_arguments(name)
+    }
+
+    /// Replaces the default in-memory 404 cache, e.g. with a sled-backed one.
+    pub fn with_negative_cache(mut self, cache: NegativeCache) -> Self {
+        self.gecko_terminal_tools = self
+            .gecko_terminal_tools
+            .with_negative_cache(Arc::new(cache));
+        self
+    }
+
+    /// Replaces the default in-memory token mapping table behind
+    /// `find_token_across_networks`, e.g. with a sled-backed one.
+    pub fn with_token_mappings(mut self, store: TokenMappingStore) -> Self {
+        self.cross_network_tools = self.cross_network_tools.with_mappings(Arc::new(store));
+        self
+    }
+
+    pub fn token_mappings(&self) -> &TokenMappingStore {
+        self.cross_network_tools.mappings()
+    }
+
+    /// Replaces the default in-memory whale watches and sightings, e.g. with
+    /// sled-backed ones that survive restarts.
+    pub fn with_whale_watches(mut self, store: WhaleWatchStore) -> Self {
+        self.whale_tools = self.whale_tools.with_watches(Arc::new(store));
+        self
+    }
+
+    pub fn whale_watches(&self) -> &WhaleWatchStore {
+        self.whale_tools.watches()
+    }
+
+    /// Replaces the pipelines loaded from `[[pipelines]]`.
+    pub fn with_pipelines(mut self, pipelines: PipelineRegistry) -> Self {
+        self.pipelines = pipelines;
+        self
+    }
+
+    pub fn pipelines(&self) -> &PipelineRegistry {
+        &self.pipelines
+    }
+
+    /// Replaces the default in-memory preference store, e.g. with a sled-backed one.
+    pub fn with_preferences(mut self, store: PreferenceStore) -> Self {
+        self.preferences = Arc::new(store);
+        self
+    }
+
+    pub fn preferences(&self) -> &PreferenceStore {
+        &self.preferences
+    }
+
+    /// Replaces the default in-memory OAuth client store, e.g. with a sled-backed one.
+    pub fn with_oauth_clients(mut self, store: OAuthClientStore) -> Self {
+        self.oauth_clients = Arc::new(store);
+        self
+    }
+
+    pub fn oauth_clients(&self) -> &OAuthClientStore {
+        &self.oauth_clients
+    }
+
+    /// Replaces the default in-memory audit log, e.g. with a sled-backed one.
+    pub fn with_audit_log(mut self, audit: AuditLog) -> Self {
+        self.audit = Arc::new(audit);
+        self.events().attach(self.audit.clone());
+        self
+    }
+
+    /// Replaces the default in-memory rate-limit counters, e.g. with sled-backed
+    /// ones that survive restarts, and schedules their cleanup.
+    pub fn with_rate_limits(mut self, store: RateLimitStore) -> Self {
+        let store = Arc::new(store);
+        let swept = Arc::clone(&store);
+        let registered =
+            self.jobs
+                .register("rate_limit_sweep", Duration::from_secs(60), move || {
+                    let store = Arc::clone(&swept);
+                    async move { store.sweep().map(drop) }
+                });
+        if let Err(e) = registered {
+            tracing::warn!("{}", e);
+        }
+        self.rate_limits = store;
+        self
+    }
+
+    pub fn rate_limits(&self) -> &RateLimitStore {
+        &self.rate_limits
+    }
+
+    /// Replaces the default in-memory quota counters, e.g. with sled-backed
+    /// ones, and schedules the removal of past periods.
+    pub fn with_quotas(mut self, store: QuotaStore) -> Self {
+        let store = Arc::new(store);
+        let swept = Arc::clone(&store);
+        let registered = self
+            .jobs
+            .register("quota_sweep", Duration::from_secs(3600), move || {
+                let store = Arc::clone(&swept);
+                async move { store.sweep().map(drop) }
+            });
+        if let Err(e) = registered {
+            tracing::warn!("{}", e);
         }
+        self.quotas = store;
+        self
+    }
+
+    pub fn quotas(&self) -> &QuotaStore {
+        &self.quotas
+    }
+
+    /// Replaces the default in-memory marketplace ratings and reports, e.g.
+    /// with a sled-backed store.
+    pub fn with_plugin_feedback(mut self, store: FeedbackStore) -> Self {
+        self.feedback = Arc::new(store);
+        self
+    }
+
+    pub fn plugin_feedback(&self) -> &FeedbackStore {
+        &self.feedback
+    }
+
+    /// Where `?async=true` plugin calls are kept; in memory by default, so
+    /// they do not survive a restart.
+    pub fn with_plugin_jobs(mut self, jobs: PluginJobs) -> Self {
+        self.plugin_jobs = Arc::new(jobs);
+        self
+    }
+
+    pub fn plugin_jobs(&self) -> &Arc<PluginJobs> {
+        &self.plugin_jobs
+    }
+
+    /// Tree `/readyz` writes to when checking storage; unchecked without one.
+    pub fn with_readiness_probe(mut self, probe_tree: sled::Tree) -> Self {
+        self.readiness = Arc::new(Readiness::new(Some(probe_tree)));
+        self
+    }
+
+    pub async fn readiness(&self) -> ReadinessReport {
+        self.readiness.check(self).await
+    }
+
+    /// The database behind the persistent stores, for usage figures.
+    pub fn with_database(mut self, db: sled::Db) -> Self {
+        self.database = Some(db);
+        self
+    }
+
+    pub fn storage_usage(&self) -> Result<Option<StorageUsage>> {
+        self.database.as_ref().map(storage::usage).transpose()
+    }
+
+    /// Background jobs; built-in ones are registered by [`Self::new`].
+    pub fn jobs(&self) -> &JobScheduler {
+        &self.jobs
+    }
+
+    /// Starts the background jobs and resumes unfinished async plugin calls;
+    /// call once from within the Tokio runtime.
+    pub fn start_jobs(&self) {
+        // Registered here rather than in `new` so it polls the final watch store
+        if let Some(interval) = self.whale_tools.poll_interval() {
+            let tools = self.whale_tools.clone();
+            let registered = self.jobs.register("whale_watch_poll", interval, move || {
+                let tools = tools.clone();
+                async move { tools.poll().await.map(drop) }
+            });
+            if let Err(e) = registered {
+                tracing::warn!("{}", e);
+            }
+        }
+        self.jobs.start();
+        match self.plugin_jobs.resume(&self.plugin_manager) {
+            Ok(0) => {}
+            Ok(resumed) => tracing::info!("Resumed {} async plugin jobs", resumed),
+            Err(e) => tracing::warn!("Failed to resume async plugin jobs: {}", e),
+        }
+    }
+
+    pub fn audit(&self) -> &AuditLog {
+        &self.audit
+    }
+
+    pub fn limits(&self) -> &PayloadLimits {
+        &self.limits
+    }
+
+    /// Caps on concurrent calls per tool, from `limits.tool_concurrency`.
+    pub fn tool_concurrency(&self) -> &ToolConcurrency {
+        &self.tool_concurrency
+    }
+
+    /// Execution budget for a single call of `tool_name`.
+    pub fn tool_timeout(&self, tool_name: &str) -> Duration {
+        self.timeouts.for_tool(tool_name)
     }
 
     pub fn gecko_terminal_tools(&self) -> &GeckoTerminalTools {
@@ -50,94 +580,72 @@ impl NovaServer {
         &self.new_pools_tools
     }
 
-    pub fn get_tools(&self, context: &RequestContext) -> Result<Vec<Tool>> {
-        let mut tools = vec![];
-
-        tools.push(Tool {
-            name: "get_gecko_networks".to_string(),
-            description: "List available networks from GeckoTerminal".to_string(),
-            input_schema: json!({
-                "type": "object",
-                "properties": {}
-            }),
-        });
+    pub fn cross_network_tools(&self) -> &CrossNetworkTools {
+        &self.cross_network_tools
+    }
 
-        tools.push(Tool {
-            name: "get_gecko_token".to_string(),
-            description: "Fetch token info from GeckoTerminal".to_string(),
-            input_schema: json!({
-                "type": "object",
-                "properties": {
-                    "network": { "type": "string" },
-                    "address": { "type": "string" }
-                },
-                "required": ["network", "address"],
-            }),
-        });
+    pub fn arbitrage_tools(&self) -> &ArbitrageTools {
+        &self.arbitrage_tools
+    }
 
-        tools.push(Tool {
-            name: "get_gecko_pool".to_string(),
-            description: "Fetch pool info from GeckoTerminal".to_string(),
-            input_schema: json!({
-                "type": "object",
-                "properties": {
-                    "network": { "type": "string" },
-                    "address": { "type": "string" }
-                },
-                "required": ["network", "address"],
-            }),
-        });
+    pub fn whale_tools(&self) -> &WhaleTools {
+        &self.whale_tools
+    }
 
-        tools.push(Tool {
-            name: "get_trending_pools".to_string(),
-            description: "Fetch trending DEX pools from GeckoTerminal".to_string(),
-            input_schema: json!({
-                "type": "object",
-                "properties": {
-                    "network": { "type": "string" },
-                    "limit": {
-                        "type": "integer",
-                        "minimum": 1,
-                        "maximum": 20,
-                        "default": 10
-                    },
-                    "page": { "type": "integer", "minimum": 1, "default": 1 },
-                    "duration": {
-                        "type": "string",
-                        "enum": ["5m", "1h", "6h", "24h"],
-                        "default": "24h"
-                    }
-                },
-                "required": ["network"],
-            }),
-        });
+    pub fn solana_tools(&self) -> &SolanaTools {
+        &self.solana_tools
+    }
 
-        tools.push(Tool {
-            name: "search_pools".to_string(),
-            description: "Search for DEX pools on GeckoTerminal".to_string(),
-            input_schema: json!({
-                "type": "object",
-                "properties": {
-                    "query": { "type": "string" },
-                    "network": { "type": "string" },
-                    "page": { "type": "integer", "minimum": 1, "default": 1 }
-                },
-                "required": ["query"],
-            }),
-        });
+    pub fn token_unlock_tools(&self) -> &TokenUnlockTools {
+        &self.token_unlock_tools
+    }
 
-        tools.push(Tool {
-            name: "get_new_pools".to_string(),
-            description: "Fetch newest DEX pools from GeckoTerminal".to_string(),
-            input_schema: json!({
-                "type": "object",
-                "properties": {
-                    "network": { "type": "string" },
-                    "page": { "type": "integer", "minimum": 1, "default": 1 }
-                },
-                "required": ["network"],
-            }),
-        });
+    /// Declared definition of a built-in tool, whether or not it is enabled.
+    /// A built-in or native tool's definition, whose schema `tools/call`
+    /// validates arguments against.
+    pub fn builtin_tool(&self, name: &str) -> Option<&Tool> {
+        builtin_tools()
+            .iter()
+            .find(|tool| tool.name == name)
+            .or_else(|| self.native_tools.definition(name))
+    }
+
+    /// Compiled input schemas of built-in, native and pipeline tools.
+    pub(crate) fn schemas(&self) -> &schema::CompiledSchemas {
+        &self.schemas
+    }
+
+    /// Adds a tool compiled into the host binary. It is listed after the
+    /// built-in tools and called like them; `[tools]` flags do not cover it.
+    /// Fails if a built-in tool, pipeline or another native tool already
+    /// has its name.
+    pub fn with_tool(mut self, provider: impl ToolProvider + 'static) -> Result<Self> {
+        let mut reserved = BUILTIN_TOOLS.to_vec();
+        reserved.extend(self.pipelines.definitions().map(|p| p.name.as_str()));
+        self.native_tools.register(Arc::new(provider), &reserved)?;
+        Ok(self)
+    }
+
+    pub fn native_tools(&self) -> &NativeTools {
+        &self.native_tools
+    }
+
+    pub fn get_tools(&self, context: &RequestContext) -> Result<Vec<Tool>> {
+        let mut tools = builtin_tools().to_vec();
+
+        let flags = &self.runtime.current().tools;
+        tools.retain(|tool| flags.is_enabled(&tool.name));
+        tools.extend(self.native_tools.definitions().cloned());
+
+        for pipeline in self.pipelines.definitions() {
+            tools.push(Tool {
+                name: pipeline.name.clone(),
+                description: pipeline.description.clone(),
+                input_schema: pipeline.input_schema.clone(),
+                annotations: None,
+                output_schema: None,
+            });
+        }
 
         let plugin_tools = self.plugin_manager.list_plugins_for_context(context)?;
         for plugin in plugin_tools {
@@ -145,12 +653,21 @@ impl NovaServer {
                 name: plugin.fq_name,
                 description: plugin.description,
                 input_schema: plugin.input_schema,
+                annotations: None,
+                output_schema: plugin.output_schema,
             });
         }
 
         Ok(tools)
     }
 
+    /// Built-in upstreams followed by plugin endpoints.
+    pub fn upstream_statuses(&self) -> Vec<UpstreamStatus> {
+        let mut statuses = self.upstream_health.snapshot();
+        statuses.extend(self.plugin_manager.upstream_health().snapshot());
+        statuses
+    }
+
     pub fn plugin_manager(&self) -> &PluginManager {
         self.plugin_manager.as_ref()
     }
@@ -170,3 +687,257 @@ impl NovaServer {
         crate::mcp::handler::handle_tool_call(self, tool_call, context).await
     }
 }
+
+/// Definitions (and argument schemas) of the built-in tools, in listing order.
+fn builtin_tools() -> &'static [Tool] {
+    static TOOLS: OnceLock<Vec<Tool>> = OnceLock::new();
+    TOOLS.get_or_init(define_builtin_tools)
+}
+
+fn define_builtin_tools() -> Vec<Tool> {
+    let mut tools = vec![];
+    tools.push(Tool {
+        name: "get_gecko_networks".to_string(),
+        description: "List available networks from GeckoTerminal".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {}
+        }),
+        annotations: Some(ToolAnnotations::read_only_lookup()),
+        output_schema: None,
+    });
+
+    tools.push(Tool {
+        name: "get_gecko_token".to_string(),
+        description: "Fetch token info from GeckoTerminal, with links to its GeckoTerminal, Dexscreener and block explorer pages".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "network": { "type": "string", "pattern": "\\S" },
+                "address": { "type": "string", "pattern": "\\S" }
+            },
+            "required": ["network", "address"],
+        }),
+        annotations: Some(ToolAnnotations::read_only_lookup()),
+        output_schema: None,
+    });
+
+    tools.push(Tool {
+        name: "get_gecko_pool".to_string(),
+        description: "Fetch pool info from GeckoTerminal, with links to its GeckoTerminal, Dexscreener and block explorer pages".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "network": { "type": "string", "pattern": "\\S" },
+                "address": { "type": "string", "pattern": "\\S" }
+            },
+            "required": ["network", "address"],
+        }),
+        annotations: Some(ToolAnnotations::read_only_lookup()),
+        output_schema: None,
+    });
+
+    tools.push(Tool {
+        name: "get_trending_pools".to_string(),
+        description: "Fetch trending DEX pools from GeckoTerminal".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "network": { "type": "string", "pattern": "\\S" },
+                "limit": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 20,
+                    "default": 10
+                },
+                "page": { "type": "integer", "minimum": 1, "default": 1 },
+                "duration": {
+                    "type": "string",
+                    "enum": ["5m", "1h", "6h", "24h"],
+                    "default": "24h"
+                }
+            },
+            "required": ["network"],
+        }),
+        annotations: Some(ToolAnnotations::read_only_lookup()),
+        output_schema: None,
+    });
+
+    tools.push(Tool {
+        name: "search_pools".to_string(),
+        description: "Search for DEX pools on GeckoTerminal".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string", "pattern": "\\S" },
+                "network": { "type": "string" },
+                "page": { "type": "integer", "minimum": 1, "default": 1 }
+            },
+            "required": ["query"],
+        }),
+        annotations: Some(ToolAnnotations::read_only_lookup()),
+        output_schema: None,
+    });
+
+    tools.push(Tool {
+        name: "get_new_pools".to_string(),
+        description: "Fetch newest DEX pools from GeckoTerminal".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "network": { "type": "string", "pattern": "\\S" },
+                "page": { "type": "integer", "minimum": 1, "default": 1 }
+            },
+            "required": ["network"],
+        }),
+        annotations: Some(ToolAnnotations::read_only_lookup()),
+        output_schema: None,
+    });
+
+    tools.push(Tool {
+        name: "find_token_across_networks".to_string(),
+        description: "Find a token's bridged and wrapped versions on other networks, each with its deepest pool, from a symbol or an address (with its network). Curated mappings come first, then GeckoTerminal search matches".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "token": { "type": "string", "pattern": "\\S" },
+                "network": { "type": "string" },
+                "limit": { "type": "integer", "minimum": 1, "maximum": 25, "default": 10 }
+            },
+            "required": ["token"],
+        }),
+        annotations: Some(ToolAnnotations::read_only_lookup()),
+        output_schema: None,
+    });
+
+    tools.push(Tool {
+        name: "find_arbitrage_spreads".to_string(),
+        description: "Compare a token's price across its deepest pools on a network, optionally its deepest pool on other networks and a configured CEX price, and list the price gaps above a threshold percent (before fees, gas and slippage)".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "network": { "type": "string", "pattern": "\\S" },
+                "address": { "type": "string", "pattern": "\\S" },
+                "threshold_percent": { "type": "number", "minimum": 0, "default": 1 },
+                "max_pools": { "type": "integer", "minimum": 1, "maximum": 20, "default": 5 },
+                "min_reserve_usd": { "type": "number", "minimum": 0, "default": 10000 },
+                "across_networks": { "type": "boolean", "default": false },
+                "include_cex": { "type": "boolean" }
+            },
+            "required": ["network", "address"],
+        }),
+        annotations: Some(ToolAnnotations::read_only_lookup()),
+        output_schema: None,
+    });
+
+    tools.push(Tool {
+        name: "get_solana_token_holders".to_string(),
+        description: "List the largest holders of a Solana token (base58 mint address) with their share of supply, from the Solana RPC".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "address": { "type": "string", "pattern": "\\S" },
+                "limit": { "type": "integer", "minimum": 1, "maximum": 20, "default": 10 }
+            },
+            "required": ["address"],
+        }),
+        annotations: Some(ToolAnnotations::read_only_lookup()),
+        output_schema: None,
+    });
+
+    tools.push(Tool {
+        name: "watch_whales".to_string(),
+        description: "Watch a pool for trades of at least min_volume_usd: each new one is recorded and announced to the event webhook and to the context's MCP sessions. Calling it again for the same pool changes the threshold; returns the pool's large trades of the last 24 hours and those recorded so far".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "network": { "type": "string", "pattern": "\\S" },
+                "pool": { "type": "string", "pattern": "\\S" },
+                "min_volume_usd": { "type": "number", "minimum": 0 }
+            },
+            "required": ["network", "pool", "min_volume_usd"],
+        }),
+        annotations: Some(ToolAnnotations::remote_update()),
+        output_schema: None,
+    });
+
+    tools.push(Tool {
+        name: "unwatch_whales".to_string(),
+        description: "Stop watching a pool for large trades and delete the trades recorded for it"
+            .to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "network": { "type": "string", "pattern": "\\S" },
+                "pool": { "type": "string", "pattern": "\\S" }
+            },
+            "required": ["network", "pool"],
+        }),
+        annotations: Some(ToolAnnotations::local_update()),
+        output_schema: None,
+    });
+
+    tools.push(Tool {
+        name: "get_token_unlocks".to_string(),
+        description: "List a token's upcoming unlocks (vesting cliffs and linear releases) with the amount and share of max supply of each, and the next unlock, from DefiLlama's emissions data. `token` is the DefiLlama protocol slug, e.g. \"arbitrum\"".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "token": { "type": "string", "pattern": "^[A-Za-z0-9_-]+$" },
+                "days": { "type": "integer", "minimum": 1, "maximum": 365, "default": 90 },
+                "limit": { "type": "integer", "minimum": 1, "maximum": 50, "default": 10 }
+            },
+            "required": ["token"],
+        }),
+        annotations: Some(ToolAnnotations::read_only_lookup()),
+        output_schema: None,
+    });
+
+    tools.push(Tool {
+        name: "set_my_preferences".to_string(),
+        description: "Set the currency, locale, timezone, number format and default result format (full, summary, telegram_markdown or telegram_html) used to display results for the calling context".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "currency": { "type": "string", "pattern": "^[A-Za-z]{3}$" },
+                "locale": { "type": "string", "pattern": "\\S" },
+                "timezone": { "type": "string", "pattern": "\\S" },
+                "number_format": { "type": "string", "enum": ["standard", "compact", "plain"] },
+                "result_format": { "type": "string", "enum": ["full", "summary", "telegram_markdown", "telegram_html"] }
+            },
+            "minProperties": 1,
+            "additionalProperties": false,
+        }),
+        annotations: Some(ToolAnnotations::local_update()),
+        output_schema: None,
+    });
+
+    tools.push(Tool {
+        name: "get_my_usage".to_string(),
+        description: "Show the calling context's tool calls today and this month, its daily and monthly quotas and what is left of them".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {},
+            "additionalProperties": false,
+        }),
+        annotations: Some(ToolAnnotations::local_read()),
+        output_schema: None,
+    });
+
+    tools.push(Tool {
+        name: "get_job_status".to_string(),
+        description: "Show the status of an asynchronous plugin call started by the calling context, with its result or error once finished".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "job_id": { "type": "string", "pattern": "\\S" }
+            },
+            "required": ["job_id"],
+            "additionalProperties": false,
+        }),
+        annotations: Some(ToolAnnotations::local_read()),
+        output_schema: None,
+    });
+
+    tools
+}
@@ -0,0 +1,444 @@
+use crate::mcp::dto::{McpError, McpResponse};
+use crate::mcp::handler;
+use crate::mcp::session::McpSession;
+use crate::mcp::{logging, progress};
+use crate::server::NovaServer;
+use serde_json::Value;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Largest `Content-Length` body, newline-framed message or header line
+/// accepted before it is discarded.
+const MAX_FRAME_BYTES: usize = 8 * 1024 * 1024;
+
+/// How JSON-RPC messages are delimited on stdin/stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framing {
+    /// Decide from the first message: a `Content-Length:` header selects LSP framing.
+    Auto,
+    /// One JSON document per line.
+    Newline,
+    /// LSP-style `Content-Length: N\r\n\r\n<body>` frames.
+    ContentLength,
+}
+
+impl Framing {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "auto" => Some(Framing::Auto),
+            "newline" | "ndjson" => Some(Framing::Newline),
+            "content-length" | "lsp" => Some(Framing::ContentLength),
+            _ => None,
+        }
+    }
+}
+
+/// One unit read from the transport.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Incoming {
+    Message(String),
+    /// A frame that could not be decoded; the reader has skipped past it.
+    Malformed(String),
+}
+
+pub struct FrameReader<R> {
+    reader: BufReader<R>,
+    framing: Framing,
+}
+
+impl<R: AsyncRead + Unpin> FrameReader<R> {
+    pub fn new(reader: R, framing: Framing) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            framing,
+        }
+    }
+
+    /// Framing in effect; stays `Auto` until the first message arrives.
+    pub fn framing(&self) -> Framing {
+        self.framing
+    }
+
+    /// Next message, or `None` at end of input.
+    pub async fn next(&mut self) -> io::Result<Option<Incoming>> {
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match self.read_line(&mut line).await? {
+                Some(0) => return Ok(None),
+                Some(_) => {}
+                None => {
+                    return Ok(Some(Incoming::Malformed(format!(
+                        "line exceeds limit of {} bytes",
+                        MAX_FRAME_BYTES
+                    ))))
+                }
+            }
+            let Ok(text) = std::str::from_utf8(&line) else {
+                return Ok(Some(Incoming::Malformed(
+                    "line is not valid UTF-8".to_string(),
+                )));
+            };
+            let trimmed = text.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            if self.framing == Framing::Auto {
+                self.framing = if header_value(trimmed, "content-length").is_some() {
+                    Framing::ContentLength
+                } else {
+                    Framing::Newline
+                };
+                tracing::debug!("Detected stdio framing: {:?}", self.framing);
+            }
+            return match self.framing {
+                Framing::ContentLength => self.read_frame(trimmed.to_string()).await,
+                _ => Ok(Some(Incoming::Message(trimmed.to_string()))),
+            };
+        }
+    }
+
+    /// Reads the rest of a header block starting at `first`, then the body.
+    async fn read_frame(&mut self, first: String) -> io::Result<Option<Incoming>> {
+        let mut length = None;
+        let mut oversized = false;
+        let mut header = first;
+        loop {
+            if let Some(value) = header_value(&header, "content-length") {
+                length = Some(value.parse::<usize>().map_err(|_| value.to_string()));
+            }
+            let mut next = Vec::new();
+            match self.read_line(&mut next).await? {
+                Some(0) => {
+                    return Ok(Some(Incoming::Malformed(
+                        "end of input inside frame headers".to_string(),
+                    )))
+                }
+                Some(_) => {}
+                // Read on to the body so the next frame starts cleanly
+                None => {
+                    oversized = true;
+                    header.clear();
+                    continue;
+                }
+            }
+            // Undecodable headers are simply not `Content-Length`
+            header = String::from_utf8_lossy(&next).trim().to_string();
+            if header.is_empty() {
+                break;
+            }
+        }
+
+        if oversized {
+            if let Some(Ok(length)) = length {
+                self.skip(length as u64).await?;
+            }
+            return Ok(Some(Incoming::Malformed(format!(
+                "frame header exceeds limit of {} bytes",
+                MAX_FRAME_BYTES
+            ))));
+        }
+        let length = match length {
+            Some(Ok(length)) => length,
+            Some(Err(value)) => {
+                return Ok(Some(Incoming::Malformed(format!(
+                    "invalid Content-Length: {}",
+                    value
+                ))))
+            }
+            None => {
+                return Ok(Some(Incoming::Malformed(
+                    "missing Content-Length header".to_string(),
+                )))
+            }
+        };
+        if length > MAX_FRAME_BYTES {
+            self.skip(length as u64).await?;
+            return Ok(Some(Incoming::Malformed(format!(
+                "frame of {} bytes exceeds limit of {} bytes",
+                length, MAX_FRAME_BYTES
+            ))));
+        }
+
+        let mut body = vec![0u8; length];
+        match self.reader.read_exact(&mut body).await {
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                return Ok(Some(Incoming::Malformed(
+                    "end of input inside frame body".to_string(),
+                )))
+            }
+            Err(e) => return Err(e),
+        }
+        match String::from_utf8(body) {
+            Ok(body) => Ok(Some(Incoming::Message(body))),
+            Err(_) => Ok(Some(Incoming::Malformed(
+                "frame body is not valid UTF-8".to_string(),
+            ))),
+        }
+    }
+
+    /// Reads one line of at most `MAX_FRAME_BYTES` into `line` and returns
+    /// the bytes read, 0 at end of input. A longer line is dropped through its
+    /// newline and gives `None`.
+    async fn read_line(&mut self, line: &mut Vec<u8>) -> io::Result<Option<usize>> {
+        let limit = MAX_FRAME_BYTES as u64 + 1;
+        let read = (&mut self.reader)
+            .take(limit)
+            .read_until(b'\n', line)
+            .await?;
+        if line.len() <= MAX_FRAME_BYTES || line.ends_with(b"\n") {
+            return Ok(Some(read));
+        }
+        line.clear();
+        loop {
+            let buffered = self.reader.fill_buf().await?;
+            if buffered.is_empty() {
+                break;
+            }
+            match buffered.iter().position(|byte| *byte == b'\n') {
+                Some(end) => {
+                    self.reader.consume(end + 1);
+                    break;
+                }
+                None => {
+                    let skipped = buffered.len();
+                    self.reader.consume(skipped);
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Discards the next `bytes` bytes, or the rest of the input if shorter.
+    async fn skip(&mut self, bytes: u64) -> io::Result<()> {
+        let mut skipped = (&mut self.reader).take(bytes);
+        tokio::io::copy(&mut skipped, &mut tokio::io::sink()).await?;
+        Ok(())
+    }
+}
+
+/// Tracing output target: stderr until [`use_stdout`](Self::use_stdout), so
+/// nothing logged before the transport is known can reach the stdio stream.
+#[derive(Debug, Clone, Default)]
+pub struct LogOutput {
+    stdout: Arc<AtomicBool>,
+}
+
+impl LogOutput {
+    /// Sends later output to stdout; only for transports that leave it free.
+    pub fn use_stdout(&self) {
+        self.stdout.store(true, Ordering::Relaxed);
+    }
+}
+
+pub enum LogWriter {
+    Stdout(io::Stdout),
+    Stderr(io::Stderr),
+}
+
+impl io::Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            LogWriter::Stdout(out) => out.write(buf),
+            LogWriter::Stderr(err) => err.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            LogWriter::Stdout(out) => out.flush(),
+            LogWriter::Stderr(err) => err.flush(),
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for LogOutput {
+    type Writer = LogWriter;
+
+    fn make_writer(&'a self) -> LogWriter {
+        if self.stdout.load(Ordering::Relaxed) {
+            LogWriter::Stdout(io::stdout())
+        } else {
+            LogWriter::Stderr(io::stderr())
+        }
+    }
+}
+
+/// Takes the process's stdout for MCP frames. On Unix the original stdout
+/// is duplicated for the returned writer and fd 1 is pointed at stderr, so
+/// a stray `println!`, or a dependency writing to stdout, cannot corrupt the
+/// stream. Elsewhere this is plain stdout.
+pub fn claim_stdout() -> io::Result<Box<dyn AsyncWrite + Send + Unpin>> {
+    #[cfg(unix)]
+    {
+        use std::os::fd::{AsRawFd, FromRawFd};
+        let stdout = io::stdout();
+        // Hold the lock so no buffered `print!` output is flushed mid-swap
+        let mut lock = stdout.lock();
+        io::Write::flush(&mut lock)?;
+        // SAFETY: fds 1 and 2 are open for the life of the process, and the
+        // duplicate returned by `dup` is owned solely by the new `File`.
+        let protocol = unsafe {
+            let fd = libc::dup(lock.as_raw_fd());
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if libc::dup2(io::stderr().as_raw_fd(), lock.as_raw_fd()) < 0 {
+                let err = io::Error::last_os_error();
+                libc::close(fd);
+                return Err(err);
+            }
+            std::fs::File::from_raw_fd(fd)
+        };
+        Ok(Box::new(tokio::fs::File::from_std(protocol)))
+    }
+    #[cfg(not(unix))]
+    {
+        Ok(Box::new(tokio::io::stdout()))
+    }
+}
+
+/// Writes `body` using `framing` (newline-delimited until auto-detection settles).
+pub async fn write_message<W>(writer: &mut W, framing: Framing, body: &str) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    if framing == Framing::ContentLength {
+        let header = format!("Content-Length: {}\r\n\r\n", body.len());
+        writer.write_all(header.as_bytes()).await?;
+        writer.write_all(body.as_bytes()).await?;
+    } else {
+        writer.write_all(body.as_bytes()).await?;
+        writer.write_all(b"\n").await?;
+    }
+    writer.flush().await
+}
+
+/// Serves JSON-RPC over `reader`/`writer` until end of input. Undecodable
+/// input is answered with a JSON-RPC error and the loop carries on; requests
+/// without an id are notifications and get no reply. Registry changes reach
+/// the client as `notifications/tools/list_changed` between requests, and
+/// fired alerts as `notifications/message` once it has set a log level.
+pub async fn serve<R, W>(
+    server: &NovaServer,
+    reader: R,
+    mut writer: W,
+    framing: Framing,
+) -> anyhow::Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut frames = FrameReader::new(reader, framing);
+    let mut session = McpSession::new();
+    let (notify_tx, mut notify_rx) = mpsc::unbounded_channel::<Value>();
+    let mut changes = BroadcastStream::new(server.plugin_manager().subscribe());
+    let mut events = BroadcastStream::new(server.events().subscribe());
+    loop {
+        // Framing stays fixed once the first message has been read.
+        let framing = frames.framing();
+        let incoming = {
+            let next = frames.next();
+            tokio::pin!(next);
+            loop {
+                tokio::select! {
+                    incoming = &mut next => break incoming?,
+                    Some(change) = changes.next() => {
+                        let change = change.ok();
+                        let note = handler::list_changed_notification(server, &session, change.as_ref());
+                        if let Some(note) = note {
+                            write_message(&mut writer, framing, &note.to_string()).await?;
+                        }
+                    }
+                    Some(Ok(event)) = events.next() => {
+                        if let Some(note) = handler::alert_notification(server, &session, &event) {
+                            write_message(&mut writer, framing, &note.to_string()).await?;
+                        }
+                    }
+                }
+            }
+        };
+        let Some(incoming) = incoming else {
+            break;
+        };
+        let response = match incoming {
+            Incoming::Message(message) => {
+                tracing::debug!("Received: {}", message);
+                match handler::parse_request(&message) {
+                    Ok(request) => {
+                        let notification = request.id.is_none();
+                        let logger = session.client_logger(&notify_tx);
+                        let call = progress::scope(
+                            notify_tx.clone(),
+                            logging::scope(
+                                logger,
+                                handler::handle_session_request(
+                                    server,
+                                    &mut session,
+                                    request,
+                                    None,
+                                ),
+                            ),
+                        );
+                        tokio::pin!(call);
+                        // Log notifications go out while the request is still running.
+                        let response = loop {
+                            tokio::select! {
+                                response = &mut call => break response,
+                                Some(note) = notify_rx.recv() => {
+                                    write_message(&mut writer, frames.framing(), &note.to_string()).await?;
+                                }
+                            }
+                        };
+                        while let Ok(note) = notify_rx.try_recv() {
+                            write_message(&mut writer, frames.framing(), &note.to_string()).await?;
+                        }
+                        if notification {
+                            continue;
+                        }
+                        response
+                    }
+                    Err(response) => {
+                        tracing::error!("Failed to parse request: {:?}", response.error);
+                        *response
+                    }
+                }
+            }
+            Incoming::Malformed(reason) => {
+                tracing::error!("Malformed frame: {}", reason);
+                parse_error(reason)
+            }
+        };
+        let response_json = serde_json::to_string(&response)?;
+        tracing::debug!("Sending: {}", response_json);
+        write_message(&mut writer, frames.framing(), &response_json).await?;
+    }
+    Ok(())
+}
+
+fn parse_error(details: String) -> McpResponse {
+    McpResponse {
+        jsonrpc: "2.0".to_string(),
+        id: None,
+        result: None,
+        error: Some(McpError {
+            code: -32700,
+            message: "Parse error".to_string(),
+            data: Some(serde_json::json!({ "details": details })),
+        }),
+    }
+}
+
+fn header_value<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let (key, value) = line.split_once(':')?;
+    key.trim()
+        .eq_ignore_ascii_case(name)
+        .then_some(value.trim())
+}
@@ -0,0 +1,219 @@
+//! Byte-keyed storage shared by the stores that run either in memory or on a
+//! sled tree (quotas, rate limits, jobs, audit, ...). Each store encodes its
+//! own keys and values; [`MemoryKv`] and `sled::Tree` keep them in key order.
+
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::sync::{Mutex, MutexGuard};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::{NovaError, Result};
+
+/// Keys and values, in key order.
+pub type KvPairs = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// Next value of a key from its current one; `None` removes it.
+pub type KvUpdate<'a> = dyn FnMut(Option<&[u8]>) -> Option<Vec<u8>> + 'a;
+
+pub trait KvStore: Send + Sync {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>>;
+
+    /// Stores `value` and returns the one it replaced.
+    fn insert(&self, key: &[u8], value: Vec<u8>) -> Result<Option<Vec<u8>>>;
+
+    fn remove(&self, key: &[u8]) -> Result<Option<Vec<u8>>>;
+
+    /// Sets the value to `next` of the current one in one atomic step and
+    /// returns the previous value. `next` may run more than once when writers
+    /// race.
+    fn fetch_and_update(&self, key: &[u8], next: &mut KvUpdate<'_>) -> Result<Option<Vec<u8>>>;
+
+    /// Removes `key` only while it still holds `expected`; false when it changed.
+    fn remove_if(&self, key: &[u8], expected: &[u8]) -> Result<bool>;
+
+    fn range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Result<KvPairs>;
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<KvPairs>;
+
+    /// The entry with the greatest key.
+    fn last(&self) -> Result<Option<(Vec<u8>, Vec<u8>)>>;
+
+    fn flush(&self) -> Result<()>;
+
+    /// Whether entries outlive the process.
+    fn is_persistent(&self) -> bool;
+}
+
+impl dyn KvStore {
+    pub fn get_json<T: DeserializeOwned>(&self, key: &[u8]) -> Result<Option<T>> {
+        self.get(key)?
+            .map(|bytes| Ok(serde_json::from_slice(&bytes)?))
+            .transpose()
+    }
+
+    pub fn insert_json<T: Serialize>(&self, key: &[u8], value: &T) -> Result<()> {
+        self.insert(key, serde_json::to_vec(value)?).map(drop)
+    }
+
+    /// Every value, decoded, in key order.
+    pub fn values_json<T: DeserializeOwned>(&self, prefix: &[u8]) -> Result<Vec<T>> {
+        self.scan_prefix(prefix)?
+            .into_iter()
+            .map(|(_, bytes)| Ok(serde_json::from_slice(&bytes)?))
+            .collect()
+    }
+
+    /// Like [`scan_prefix`](KvStore::scan_prefix), for stores with text keys.
+    pub fn scan_str(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        Ok(self
+            .scan_prefix(prefix.as_bytes())?
+            .into_iter()
+            .map(|(key, value)| (String::from_utf8_lossy(&key).into_owned(), value))
+            .collect())
+    }
+}
+
+/// Entries kept in a map for tests and runs without a database.
+#[derive(Default)]
+pub struct MemoryKv {
+    map: Mutex<BTreeMap<Vec<u8>, Vec<u8>>>,
+}
+
+impl MemoryKv {
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<Vec<u8>, Vec<u8>>> {
+        self.map.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl KvStore for MemoryKv {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(self.lock().get(key).cloned())
+    }
+
+    fn insert(&self, key: &[u8], value: Vec<u8>) -> Result<Option<Vec<u8>>> {
+        Ok(self.lock().insert(key.to_vec(), value))
+    }
+
+    fn remove(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(self.lock().remove(key))
+    }
+
+    fn fetch_and_update(&self, key: &[u8], next: &mut KvUpdate<'_>) -> Result<Option<Vec<u8>>> {
+        let mut map = self.lock();
+        let old = map.get(key).cloned();
+        match next(old.as_deref()) {
+            Some(value) => map.insert(key.to_vec(), value),
+            None => map.remove(key),
+        };
+        Ok(old)
+    }
+
+    fn remove_if(&self, key: &[u8], expected: &[u8]) -> Result<bool> {
+        let mut map = self.lock();
+        if map
+            .get(key)
+            .is_some_and(|value| value.as_slice() == expected)
+        {
+            map.remove(key);
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    fn range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Result<KvPairs> {
+        Ok(self
+            .lock()
+            .range::<[u8], _>((start, end))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<KvPairs> {
+        Ok(self
+            .lock()
+            .range::<[u8], _>((Bound::Included(prefix), Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+
+    fn last(&self) -> Result<Option<(Vec<u8>, Vec<u8>)>> {
+        Ok(self
+            .lock()
+            .last_key_value()
+            .map(|(key, value)| (key.clone(), value.clone())))
+    }
+
+    fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn is_persistent(&self) -> bool {
+        false
+    }
+}
+
+impl KvStore for sled::Tree {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(sled::Tree::get(self, key)
+            .map_err(NovaError::from)?
+            .map(|value| value.to_vec()))
+    }
+
+    fn insert(&self, key: &[u8], value: Vec<u8>) -> Result<Option<Vec<u8>>> {
+        Ok(sled::Tree::insert(self, key, value)
+            .map_err(NovaError::from)?
+            .map(|value| value.to_vec()))
+    }
+
+    fn remove(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(sled::Tree::remove(self, key)
+            .map_err(NovaError::from)?
+            .map(|value| value.to_vec()))
+    }
+
+    fn fetch_and_update(&self, key: &[u8], next: &mut KvUpdate<'_>) -> Result<Option<Vec<u8>>> {
+        Ok(sled::Tree::fetch_and_update(self, key, next)
+            .map_err(NovaError::from)?
+            .map(|value| value.to_vec()))
+    }
+
+    fn remove_if(&self, key: &[u8], expected: &[u8]) -> Result<bool> {
+        Ok(self
+            .compare_and_swap(key, Some(expected), None::<&[u8]>)
+            .map_err(NovaError::from)?
+            .is_ok())
+    }
+
+    fn range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Result<KvPairs> {
+        collect(sled::Tree::range::<&[u8], _>(self, (start, end)))
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<KvPairs> {
+        collect(sled::Tree::scan_prefix(self, prefix))
+    }
+
+    fn last(&self) -> Result<Option<(Vec<u8>, Vec<u8>)>> {
+        Ok(sled::Tree::last(self)
+            .map_err(NovaError::from)?
+            .map(|(key, value)| (key.to_vec(), value.to_vec())))
+    }
+
+    fn flush(&self) -> Result<()> {
+        sled::Tree::flush(self).map_err(NovaError::from).map(drop)
+    }
+
+    fn is_persistent(&self) -> bool {
+        true
+    }
+}
+
+fn collect(iter: sled::Iter) -> Result<KvPairs> {
+    iter.map(|item| {
+        let (key, value) = item.map_err(NovaError::from)?;
+        Ok((key.to_vec(), value.to_vec()))
+    })
+    .collect()
+}
@@ -0,0 +1,121 @@
+//! Versioned, ordered changes to the records stored in sled.
+//!
+//! The schema version lives in the `schema` tree. At startup every migration
+//! above it is applied in order and the version is stored after each one, so
+//! a crash mid-upgrade repeats at most the migration that was running;
+//! migrations must therefore be safe to apply twice. A database written by a
+//! newer build is refused rather than read with stale assumptions.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{NovaError, Result};
+
+const SCHEMA_TREE: &str = "schema";
+const VERSION_KEY: &str = "version";
+
+pub struct Migration {
+    /// Schema version after this migration; versions run 1, 2, 3, ...
+    pub version: u32,
+    pub description: &'static str,
+    pub apply: fn(&sled::Db) -> Result<()>,
+}
+
+/// Every migration of this build, oldest first. Append only.
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    description: "baseline: start tracking the schema version",
+    apply: |_| Ok(()),
+}];
+
+/// The schema version this build writes.
+pub fn supported_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+/// What [`migrate`] did.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub from: u32,
+    pub to: u32,
+    pub applied: Vec<AppliedMigration>,
+}
+
+/// Also stored in the `schema` tree under `migration:<version>`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedMigration {
+    pub version: u32,
+    pub description: String,
+    pub applied_at: i64,
+}
+
+/// Brings `db` up to date with [`MIGRATIONS`].
+pub fn run(db: &sled::Db) -> Result<MigrationReport> {
+    migrate(db, MIGRATIONS)
+}
+
+/// The stored schema version; 0 for databases from before versioning.
+pub fn current_version(db: &sled::Db) -> Result<u32> {
+    let tree = db.open_tree(SCHEMA_TREE)?;
+    match tree.get(VERSION_KEY)? {
+        Some(bytes) => {
+            let bytes: [u8; 4] = bytes.as_ref().try_into().map_err(|_| {
+                NovaError::internal(format!("Corrupt schema version ({} bytes)", bytes.len()))
+            })?;
+            Ok(u32::from_be_bytes(bytes))
+        }
+        None => Ok(0),
+    }
+}
+
+/// Applies the `migrations` above the stored version, failing with
+/// [`NovaError::SchemaTooNew`] when the database is ahead of them.
+pub fn migrate(db: &sled::Db, migrations: &[Migration]) -> Result<MigrationReport> {
+    for (index, migration) in migrations.iter().enumerate() {
+        if migration.version as usize != index + 1 {
+            return Err(NovaError::internal(format!(
+                "Migration {:?} is out of order; expected version {}",
+                migration.description,
+                index + 1
+            )));
+        }
+    }
+    let supported = migrations.last().map_or(0, |migration| migration.version);
+    let from = current_version(db)?;
+    if from > supported {
+        return Err(NovaError::SchemaTooNew {
+            found: from,
+            supported,
+        });
+    }
+
+    let tree = db.open_tree(SCHEMA_TREE)?;
+    let mut applied = Vec::new();
+    for migration in migrations.iter().filter(|m| m.version > from) {
+        tracing::info!(
+            "Applying storage migration {}: {}",
+            migration.version,
+            migration.description
+        );
+        (migration.apply)(db)?;
+        let record = AppliedMigration {
+            version: migration.version,
+            description: migration.description.to_string(),
+            applied_at: Utc::now().timestamp(),
+        };
+        let mut batch = sled::Batch::default();
+        batch.insert(
+            format!("migration:{:04}", migration.version).as_bytes(),
+            serde_json::to_vec(&record)?,
+        );
+        batch.insert(VERSION_KEY, &migration.version.to_be_bytes());
+        tree.apply_batch(batch)?;
+        db.flush()?;
+        applied.push(record);
+    }
+    Ok(MigrationReport {
+        from,
+        to: supported,
+        applied,
+    })
+}
@@ -0,0 +1,45 @@
+pub mod kv;
+pub mod migrations;
+
+pub use kv::{KvPairs, KvStore, KvUpdate, MemoryKv};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::StorageConfig;
+use crate::error::Result;
+
+/// Space taken by the sled database, for `GET /admin/stats`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageUsage {
+    pub size_on_disk_bytes: u64,
+    pub trees: usize,
+}
+
+pub fn usage(db: &sled::Db) -> Result<StorageUsage> {
+    Ok(StorageUsage {
+        size_on_disk_bytes: db.size_on_disk()?,
+        trees: db.tree_names().len(),
+    })
+}
+
+/// Opens a temporary database for tests and ephemeral runs. sled keeps it
+/// under `/dev/shm` where available and removes it once the last handle drops,
+/// so parallel callers never share files or locks.
+pub fn open_temporary() -> Result<sled::Db> {
+    open_db(&StorageConfig::in_memory())
+}
+
+/// Opens the sled database described by `cfg`.
+pub fn open_db(cfg: &StorageConfig) -> Result<sled::Db> {
+    let flush_every_ms = (cfg.flush_every_ms > 0).then_some(cfg.flush_every_ms);
+    let mut db = sled::Config::new()
+        .cache_capacity(cfg.cache_capacity_bytes)
+        .flush_every_ms(flush_every_ms)
+        .use_compression(cfg.compression);
+    db = if cfg.is_temporary() {
+        db.temporary(true)
+    } else {
+        db.path(&cfg.path)
+    };
+    Ok(db.open()?)
+}
@@ -0,0 +1,310 @@
+//! End-to-end test helpers, behind the `test-util` feature.
+//!
+//! [`TestServer`] runs [`run_http_server`] on an ephemeral port over a
+//! temporary sled registry, [`StubPlugin`] is a plugin endpoint that records
+//! what it receives, and [`TestClient`] speaks the REST and JSON-RPC routes
+//! as one context:
+//!
+//! ```no_run
+//! # async fn example() -> nova_mcp::Result<()> {
+//! use nova_mcp::plugins::{PluginContextType, RequestContext};
+//! use nova_mcp::test_util::{StubPlugin, TestServer};
+//!
+//! let stub = StubPlugin::start().await?;
+//! let server = TestServer::start().await?;
+//! let client = server.client(RequestContext {
+//!     context_type: PluginContextType::User,
+//!     context_id: "5".to_string(),
+//!     actor_id: None,
+//! });
+//! let tools = client.tools_list().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::{
+    http::{StatusCode, Uri},
+    response::IntoResponse,
+    Json, Router,
+};
+use reqwest::{Client, Method, RequestBuilder};
+use serde_json::{json, Value};
+use tokio::task::JoinHandle;
+
+use crate::client::{ClientConfig, NovaClient};
+use crate::clock::SharedClock;
+use crate::dead_letters::DeadLetters;
+use crate::error::{NovaError, Result};
+use crate::events::EventBus;
+use crate::http::run_http_server;
+use crate::mcp::dto::{McpResponse, Tool};
+use crate::metering::Metering;
+use crate::plugins::{
+    EgressPolicy, PluginDetails, PluginEnablementStatus, PluginManager, PluginMetadata,
+    PluginRegistrationRequest, PluginUpdateRequest, RequestContext, SchemaRefs, ScriptLimits,
+    SecretBox,
+};
+use crate::{NovaConfig, NovaServer};
+
+/// How long [`TestServer::start`] waits for `/healthz` to answer.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A Nova HTTP server on `127.0.0.1`, stopped when dropped.
+pub struct TestServer {
+    base_url: String,
+    plugin_manager: Arc<PluginManager>,
+    task: JoinHandle<()>,
+}
+
+impl TestServer {
+    /// A server with the default config.
+    pub async fn start() -> Result<Self> {
+        Self::with_config(NovaConfig::default()).await
+    }
+
+    /// A server with `config`, whose port is replaced by a free one. Plugins
+    /// may also call plain-http loopback endpoints, so [`StubPlugin`]s work,
+    /// `plugins.secrets_key` seals credentials as in `main`, and with
+    /// `metering.enabled` the ledger is kept in memory.
+    pub async fn with_config(config: NovaConfig) -> Result<Self> {
+        Self::with_clock(config, SharedClock::default()).await
+    }
+
+    /// Like [`with_config`](Self::with_config), with every timestamp and
+    /// rate-limit window read from `clock`, e.g. a
+    /// [`ManualClock`](crate::clock::ManualClock).
+    pub async fn with_clock(mut config: NovaConfig, clock: SharedClock) -> Result<Self> {
+        config.server.port = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .map_err(bind_error)?
+            .port();
+        if !config.plugins.allowed_schemes.iter().any(|s| s == "http") {
+            config.plugins.allowed_schemes.push("http".to_string());
+        }
+        config.plugins.allow_private_networks = true;
+
+        let dead_letters = Arc::new(DeadLetters::in_memory().with_clock(clock.clone()));
+        let mut plugin_manager = PluginManager::in_memory()?
+            .with_context_id_format(config.context.id_format())
+            .with_egress_policy(EgressPolicy::new(&config.plugins))
+            .with_schema_refs(SchemaRefs::new(&config.plugins))
+            .with_script_limits(ScriptLimits::new(&config.plugins))
+            .with_read_only(config.server.read_only)
+            .with_dead_letters(Arc::clone(&dead_letters))
+            .with_events(Arc::new(EventBus::new().with_clock(clock.clone())))
+            .with_clock(clock);
+        if let Some(secrets) = config
+            .plugins
+            .secrets_key
+            .as_deref()
+            .and_then(SecretBox::from_base64)
+        {
+            plugin_manager = plugin_manager.with_secret_box(secrets);
+        }
+        if config.metering.enabled {
+            let metering =
+                Metering::from_config(&config.metering, None, Client::new(), dead_letters)?;
+            plugin_manager = plugin_manager.with_metering(Arc::new(metering));
+        }
+        let server = NovaServer::try_new(config.clone(), Arc::new(plugin_manager))?;
+        let plugin_manager = server.plugin_manager_arc();
+        let base_url = format!("http://127.0.0.1:{}", config.server.port);
+        let task = tokio::spawn(async move {
+            if let Err(e) = run_http_server(server, config).await {
+                tracing::error!("Test server stopped: {}", e);
+            }
+        });
+
+        let probe = Client::new();
+        let started = tokio::time::Instant::now();
+        while probe
+            .get(format!("{}/healthz", base_url))
+            .send()
+            .await
+            .is_err()
+        {
+            if started.elapsed() > STARTUP_TIMEOUT {
+                task.abort();
+                return Err(NovaError::internal("Test server did not start"));
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        Ok(Self {
+            base_url,
+            plugin_manager,
+            task,
+        })
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// The registry behind the server, for setup and assertions that skip HTTP.
+    pub fn plugin_manager(&self) -> &PluginManager {
+        &self.plugin_manager
+    }
+
+    /// A client that sends `context` as `x-nova-context-*` headers.
+    pub fn client(&self, context: RequestContext) -> TestClient {
+        let client = NovaClient::new(ClientConfig {
+            context: Some(context.clone()),
+            ..ClientConfig::new(&self.base_url)
+        });
+        TestClient { client, context }
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// REST and JSON-RPC calls made as one context. Typed helpers turn non-2xx
+/// answers into errors; use [`request`](Self::request) to check statuses.
+#[derive(Clone)]
+pub struct TestClient {
+    client: NovaClient,
+    context: RequestContext,
+}
+
+impl TestClient {
+    pub fn context(&self) -> &RequestContext {
+        &self.context
+    }
+
+    /// A request to `path` carrying the context headers.
+    pub fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.client.request(method, path)
+    }
+
+    pub async fn register(&self, request: &PluginRegistrationRequest) -> Result<PluginMetadata> {
+        self.client.register(request).await
+    }
+
+    pub async fn update(
+        &self,
+        plugin_id: u64,
+        request: &PluginUpdateRequest,
+    ) -> Result<PluginMetadata> {
+        self.client.update(plugin_id, request).await
+    }
+
+    /// Enables or disables the plugin for this client's context, with the
+    /// actor as `added_by`.
+    pub async fn enable(&self, plugin_id: u64, enable: bool) -> Result<PluginEnablementStatus> {
+        self.client.enable(plugin_id, enable).await
+    }
+
+    pub async fn list_plugins(&self) -> Result<Vec<PluginMetadata>> {
+        self.client.list_plugins().await
+    }
+
+    pub async fn get_plugin(&self, plugin_id: u64) -> Result<PluginDetails> {
+        self.client.get_plugin(plugin_id).await
+    }
+
+    /// The plugin's enablement for `context`, or for this client's context.
+    pub async fn enablement(
+        &self,
+        plugin_id: u64,
+        context: Option<&RequestContext>,
+    ) -> Result<PluginEnablementStatus> {
+        self.client.enablement(plugin_id, context).await
+    }
+
+    pub async fn tools_list(&self) -> Result<Vec<Tool>> {
+        self.client.tools_list().await
+    }
+
+    /// The `tools/call` result: `{ content, isError, ... }`.
+    pub async fn tools_call(&self, name: &str, arguments: Value) -> Result<Value> {
+        self.client.tools_call(name, arguments).await
+    }
+
+    /// One JSON-RPC request on `/rpc`, without a session.
+    pub async fn rpc(&self, method: &str, params: Value) -> Result<McpResponse> {
+        self.client.rpc(method, params).await
+    }
+}
+
+fn bind_error(e: std::io::Error) -> NovaError {
+    NovaError::internal(format!("Failed to bind a test port: {}", e))
+}
+
+/// A request [`StubPlugin`] received.
+#[derive(Debug, Clone)]
+pub struct StubCall {
+    pub path: String,
+    pub body: Value,
+}
+
+/// A plugin endpoint on `127.0.0.1`, stopped when dropped.
+///
+/// Every `POST` is recorded and answered with `{ "path", "received" }`,
+/// where `received` is the request body. Paths starting with `/fail` answer
+/// `502`, to exercise failing versions.
+pub struct StubPlugin {
+    addr: SocketAddr,
+    calls: Arc<Mutex<Vec<StubCall>>>,
+    task: JoinHandle<()>,
+}
+
+impl StubPlugin {
+    pub async fn start() -> Result<Self> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(bind_error)?;
+        let addr = listener.local_addr().map_err(bind_error)?;
+        let calls: Arc<Mutex<Vec<StubCall>>> = Arc::default();
+        let recorded = calls.clone();
+        let app = Router::new().fallback(move |uri: Uri, Json(body): Json<Value>| {
+            let recorded = recorded.clone();
+            async move {
+                let path = uri.path().to_string();
+                recorded
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .push(StubCall {
+                        path: path.clone(),
+                        body: body.clone(),
+                    });
+                if path.starts_with("/fail") {
+                    return (StatusCode::BAD_GATEWAY, "stub failure").into_response();
+                }
+                Json(json!({ "path": path, "received": body })).into_response()
+            }
+        });
+        let task = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                tracing::error!("Stub plugin stopped: {}", e);
+            }
+        });
+        Ok(Self { addr, calls, task })
+    }
+
+    /// The endpoint URL for `path`, e.g. `stub.url("/v1")`.
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+
+    /// Requests received so far, oldest first.
+    pub fn calls(&self) -> Vec<StubCall> {
+        self.calls.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+impl Drop for StubPlugin {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
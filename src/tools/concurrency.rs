@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+/// Per-tool caps on concurrent calls (`limits.tool_concurrency`).
+///
+/// A call beyond its tool's cap waits for a slot inside the tool's time
+/// budget, so a flood of one expensive tool queues behind itself instead of
+/// crowding out the others. Freed slots go to waiting interactive calls
+/// before background ones, oldest first within a lane. Tools without a cap
+/// run unrestricted.
+#[derive(Debug, Default)]
+pub struct ToolConcurrency {
+    lanes: HashMap<String, Arc<Lanes>>,
+}
+
+/// How urgently a `tools/call` wants a slot; `interactive` unless the caller
+/// says otherwise.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallPriority {
+    /// A person is waiting on the answer.
+    #[default]
+    Interactive,
+    /// Scheduled jobs and bulk refreshes, which yield under contention.
+    Background,
+}
+
+/// One capped tool's usage, for `GET /admin/stats`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolSlots {
+    pub limit: usize,
+    pub in_use: usize,
+    #[serde(default)]
+    pub waiting_interactive: usize,
+    #[serde(default)]
+    pub waiting_background: usize,
+}
+
+#[derive(Debug)]
+struct Lanes {
+    limit: usize,
+    state: Mutex<LaneState>,
+}
+
+#[derive(Debug, Default)]
+struct LaneState {
+    in_use: usize,
+    interactive: VecDeque<oneshot::Sender<ToolSlot>>,
+    background: VecDeque<oneshot::Sender<ToolSlot>>,
+}
+
+/// A held slot; dropping it hands the slot to the next waiter.
+#[derive(Debug)]
+pub struct ToolSlot {
+    lanes: Option<Arc<Lanes>>,
+}
+
+impl ToolConcurrency {
+    pub fn new(caps: &HashMap<String, usize>) -> Self {
+        let lanes = caps
+            .iter()
+            .filter(|(_, limit)| **limit > 0)
+            .map(|(name, limit)| {
+                let lanes = Lanes {
+                    limit: *limit,
+                    state: Mutex::new(LaneState::default()),
+                };
+                (name.clone(), Arc::new(lanes))
+            })
+            .collect();
+        Self { lanes }
+    }
+
+    /// Waits for a slot for `tool`; `None` when the tool has no cap. The slot
+    /// is held until the returned value is dropped.
+    pub async fn acquire(&self, tool: &str, priority: CallPriority) -> Option<ToolSlot> {
+        let lanes = self.lanes.get(tool)?;
+        let waiting = {
+            let mut state = lanes.lock();
+            if state.in_use < lanes.limit {
+                state.in_use += 1;
+                return Some(ToolSlot {
+                    lanes: Some(Arc::clone(lanes)),
+                });
+            }
+            let (tx, rx) = oneshot::channel();
+            match priority {
+                CallPriority::Interactive => state.interactive.push_back(tx),
+                CallPriority::Background => state.background.push_back(tx),
+            }
+            rx
+        };
+        tracing::debug!(
+            "{} is at its cap of {} concurrent calls; waiting ({:?})",
+            tool,
+            lanes.limit,
+            priority
+        );
+        waiting.await.ok()
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, ToolSlots> {
+        self.lanes
+            .iter()
+            .map(|(name, lanes)| {
+                let state = lanes.lock();
+                let slots = ToolSlots {
+                    limit: lanes.limit,
+                    in_use: state.in_use,
+                    waiting_interactive: state.interactive.len(),
+                    waiting_background: state.background.len(),
+                };
+                (name.clone(), slots)
+            })
+            .collect()
+    }
+}
+
+impl Lanes {
+    fn lock(&self) -> std::sync::MutexGuard<'_, LaneState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for ToolSlot {
+    fn drop(&mut self) {
+        let Some(lanes) = self.lanes.take() else {
+            return;
+        };
+        let mut state = lanes.lock();
+        loop {
+            let next = match state.interactive.pop_front() {
+                Some(next) => next,
+                None => match state.background.pop_front() {
+                    Some(next) => next,
+                    None => break,
+                },
+            };
+            let slot = ToolSlot {
+                lanes: Some(Arc::clone(&lanes)),
+            };
+            // A waiter that gave up (timed out) is skipped
+            match next.send(slot) {
+                Ok(()) => return,
+                Err(mut slot) => slot.lanes = None,
+            }
+        }
+        state.in_use -= 1;
+    }
+}
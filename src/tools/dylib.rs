@@ -0,0 +1,223 @@
+//! Native tools loaded from shared libraries in `dylib_tools.dir` at
+//! startup, for custom tools at native speed without rebuilding Nova.
+//!
+//! Each library (`.so`, `.dylib` or `.dll`) provides one tool through a C
+//! ABI:
+//!
+//! ```c
+//! // Must return NOVA_TOOL_ABI_VERSION (1)
+//! uint32_t nova_tool_abi_version(void);
+//! // The tool's `tools/list` entry as JSON (`name`, `description`,
+//! // `inputSchema`, ...); owned by the library and valid while it is loaded
+//! const char *nova_tool_definition(void);
+//! // Runs the tool; returns `{"ok": <result>}` or `{"error": "<message>"}`
+//! // as JSON, to be released with `nova_tool_free`. NULL is a failure.
+//! char *nova_tool_call(const char *arguments_json, const char *context_json);
+//! void nova_tool_free(char *result);
+//! ```
+//!
+//! All strings are NUL-terminated UTF-8. `context_json` is the caller's
+//! [`RequestContext`]. Calls run on Tokio's blocking pool, and may run
+//! concurrently, so `nova_tool_call` must be thread-safe.
+//!
+//! # Safety
+//!
+//! Loading a library runs its initializers, and its tool runs inside the
+//! Nova process with Nova's privileges: it can read every secret in memory,
+//! bypass the egress policy and crash or corrupt the server. Nova cannot
+//! check that the exported symbols have the signatures above. Only point
+//! `dylib_tools.dir` at libraries you would link into Nova yourself, and keep
+//! the directory writable by the operator alone. A call that outlives the
+//! tool timeout is abandoned but keeps running on its blocking thread.
+
+use std::ffi::{c_char, CStr, CString};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use libloading::Library;
+use serde_json::Value;
+
+use crate::error::{NovaError, Result};
+use crate::mcp::dto::Tool;
+use crate::plugins::RequestContext;
+use crate::tools::native::ToolProvider;
+
+/// The `nova_tool_abi_version` a library must report to be loaded.
+pub const ABI_VERSION: u32 = 1;
+
+type AbiVersionFn = unsafe extern "C" fn() -> u32;
+type DefinitionFn = unsafe extern "C" fn() -> *const c_char;
+type CallFn = unsafe extern "C" fn(*const c_char, *const c_char) -> *mut c_char;
+type FreeFn = unsafe extern "C" fn(*mut c_char);
+
+/// A tool backed by a loaded shared library.
+#[derive(Clone)]
+pub struct DylibTool {
+    inner: Arc<Loaded>,
+}
+
+struct Loaded {
+    path: PathBuf,
+    definition: Tool,
+    call: CallFn,
+    free: FreeFn,
+    // Keeps `call` and `free` valid; dropped last
+    _library: Library,
+}
+
+impl DylibTool {
+    /// Loads the tool library at `path`, checking its ABI version and
+    /// definition.
+    ///
+    /// # Safety
+    ///
+    /// Runs the library's initializers and trusts its exports to match the
+    /// [module](self) ABI; see the module's safety notes.
+    pub unsafe fn load(path: &Path) -> Result<Self> {
+        let library = Library::new(path).map_err(|e| load_error(path, e))?;
+        let version = *library
+            .get::<AbiVersionFn>(b"nova_tool_abi_version\0")
+            .map_err(|e| load_error(path, e))?;
+        let definition = *library
+            .get::<DefinitionFn>(b"nova_tool_definition\0")
+            .map_err(|e| load_error(path, e))?;
+        let call = *library
+            .get::<CallFn>(b"nova_tool_call\0")
+            .map_err(|e| load_error(path, e))?;
+        let free = *library
+            .get::<FreeFn>(b"nova_tool_free\0")
+            .map_err(|e| load_error(path, e))?;
+
+        let found = version();
+        if found != ABI_VERSION {
+            return Err(load_error(
+                path,
+                format!("ABI version {} (expected {})", found, ABI_VERSION),
+            ));
+        }
+        let raw = definition();
+        if raw.is_null() {
+            return Err(load_error(path, "nova_tool_definition returned NULL"));
+        }
+        let definition: Tool = serde_json::from_slice(CStr::from_ptr(raw).to_bytes())
+            .map_err(|e| load_error(path, format!("invalid definition: {}", e)))?;
+
+        Ok(Self {
+            inner: Arc::new(Loaded {
+                path: path.to_path_buf(),
+                definition,
+                call,
+                free,
+                _library: library,
+            }),
+        })
+    }
+
+    /// Loads every shared library directly inside `dir`, in file name
+    /// order. Fails on the first library that does not load, so a bad
+    /// deploy stops startup instead of silently dropping a tool.
+    ///
+    /// # Safety
+    ///
+    /// As for [`DylibTool::load`], for every library in `dir`.
+    pub unsafe fn load_dir(dir: &Path) -> Result<Vec<Self>> {
+        let unreadable = |e: std::io::Error| {
+            NovaError::config_error(format!(
+                "Cannot read dylib tools directory {}: {}",
+                dir.display(),
+                e
+            ))
+        };
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(dir).map_err(unreadable)? {
+            let path = entry.map_err(unreadable)?.path();
+            if path.is_file()
+                && path.extension().and_then(|ext| ext.to_str())
+                    == Some(std::env::consts::DLL_EXTENSION)
+            {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        paths.iter().map(|path| Self::load(path)).collect()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.inner.path
+    }
+}
+
+impl Loaded {
+    fn invoke(&self, arguments: &CStr, context: &CStr) -> Result<Value> {
+        // SAFETY: both strings are NUL-terminated and outlive the call; the
+        // library was loaded under the caller's promise that `call` and
+        // `free` follow the ABI.
+        let text = unsafe {
+            let raw = (self.call)(arguments.as_ptr(), context.as_ptr());
+            if raw.is_null() {
+                return Err(self.call_error("returned NULL"));
+            }
+            let text = CStr::from_ptr(raw).to_string_lossy().into_owned();
+            (self.free)(raw);
+            text
+        };
+        let mut reply: Value = serde_json::from_str(&text)
+            .map_err(|e| self.call_error(format!("returned invalid JSON: {}", e)))?;
+        if let Some(message) = reply.get("error") {
+            let message = message
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| message.to_string());
+            return Err(NovaError::api_error(message));
+        }
+        match reply.get_mut("ok") {
+            Some(value) => Ok(value.take()),
+            None => Err(self.call_error("returned neither ok nor error")),
+        }
+    }
+
+    fn call_error(&self, message: impl std::fmt::Display) -> NovaError {
+        NovaError::api_error(format!(
+            "Tool {} ({}) {}",
+            self.definition.name,
+            self.path.display(),
+            message
+        ))
+    }
+}
+
+impl ToolProvider for DylibTool {
+    fn definition(&self) -> Tool {
+        self.inner.definition.clone()
+    }
+
+    fn call<'a>(
+        &'a self,
+        arguments: Value,
+        context: &'a RequestContext,
+    ) -> BoxFuture<'a, Result<Value>> {
+        Box::pin(async move {
+            let arguments = to_c_json(&arguments)?;
+            let context = to_c_json(context)?;
+            let inner = Arc::clone(&self.inner);
+            tokio::task::spawn_blocking(move || inner.invoke(&arguments, &context))
+                .await
+                .map_err(|e| NovaError::api_error(format!("Tool call panicked: {}", e)))?
+        })
+    }
+}
+
+fn to_c_json(value: &impl serde::Serialize) -> Result<CString> {
+    // Serialized JSON escapes control characters, so it has no interior NUL
+    CString::new(serde_json::to_vec(value)?)
+        .map_err(|e| NovaError::api_error(format!("Invalid tool input: {}", e)))
+}
+
+fn load_error(path: &Path, error: impl std::fmt::Display) -> NovaError {
+    NovaError::config_error(format!(
+        "Cannot load dylib tool {}: {}",
+        path.display(),
+        error
+    ))
+}
@@ -0,0 +1,115 @@
+//! Local checks on `address` arguments so malformed input fails before it
+//! costs an upstream request.
+//!
+//! EVM networks take `0x` + 40 hex digits (pools may also be 32-byte
+//! Uniswap v4 ids); mixed-case addresses must carry a valid EIP-55 checksum.
+//! Solana takes base58 32-byte keys. Other networks are not checked.
+
+use sha3::{Digest, Keccak256};
+
+use crate::error::{NovaError, Result};
+
+/// GeckoTerminal slugs of EVM-compatible networks.
+const EVM_NETWORKS: &[&str] = &[
+    "eth",
+    "bsc",
+    "polygon_pos",
+    "avax",
+    "ftm",
+    "arbitrum",
+    "arbitrum_nova",
+    "optimism",
+    "base",
+    "linea",
+    "scroll",
+    "zksync",
+    "blast",
+    "mantle",
+    "cro",
+    "celo",
+    "xdai",
+    "metis",
+    "moonbeam",
+    "kava",
+    "polygon-zkevm",
+    "mode",
+    "sonic",
+    "unichain",
+    "berachain",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressKind {
+    Token,
+    Pool,
+}
+
+/// Accepts `address` on `network` or explains why not; a wrong EIP-55
+/// checksum comes back with the correctly checksummed address.
+pub fn validate_address(network: &str, address: &str, kind: AddressKind) -> Result<()> {
+    if EVM_NETWORKS.contains(&network) {
+        validate_evm(address, kind)
+    } else if network == "solana" {
+        validate_solana(address)
+    } else {
+        Ok(())
+    }
+}
+
+/// Whether free text (a search query) is meant as an address rather than
+/// a name: `0x` hex, or 32 to 44 base58 characters as Solana keys are.
+pub fn looks_like_address(text: &str) -> bool {
+    if text.starts_with("0x") {
+        return !text.contains(char::is_whitespace);
+    }
+    (32..=44).contains(&text.len())
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() && !matches!(c, '0' | 'O' | 'I' | 'l'))
+}
+
+fn validate_evm(address: &str, kind: AddressKind) -> Result<()> {
+    let hex = address
+        .strip_prefix("0x")
+        .filter(|hex| hex.chars().all(|c| c.is_ascii_hexdigit()))
+        .ok_or_else(|| NovaError::invalid_address(address))?;
+    match (hex.len(), kind) {
+        (40, _) => {}
+        // Uniswap v4 pools are identified by a 32-byte pool id
+        (64, AddressKind::Pool) => return Ok(()),
+        _ => return Err(NovaError::invalid_address(address)),
+    }
+    let mixed_case =
+        hex.chars().any(|c| c.is_ascii_lowercase()) && hex.chars().any(|c| c.is_ascii_uppercase());
+    if mixed_case {
+        let checksummed = to_checksum(hex);
+        if checksummed != address {
+            return Err(NovaError::invalid_checksum(address, checksummed));
+        }
+    }
+    Ok(())
+}
+
+fn validate_solana(address: &str) -> Result<()> {
+    match bs58::decode(address).into_vec() {
+        Ok(bytes) if bytes.len() == 32 => Ok(()),
+        _ => Err(NovaError::invalid_address(address)),
+    }
+}
+
+/// EIP-55: uppercase each letter whose nibble in keccak256(lowercase hex) is >= 8.
+pub fn to_checksum(hex: &str) -> String {
+    let lower = hex.to_ascii_lowercase();
+    let hash = Keccak256::digest(lower.as_bytes());
+    let mut checksummed = String::with_capacity(lower.len() + 2);
+    checksummed.push_str("0x");
+    for (i, c) in lower.chars().enumerate() {
+        let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+        if c.is_ascii_alphabetic() && nibble >= 8 {
+            checksummed.push(c.to_ascii_uppercase());
+        } else {
+            checksummed.push(c);
+        }
+    }
+    checksummed
+}
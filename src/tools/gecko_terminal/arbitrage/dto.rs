@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FindArbitrageSpreadsInput {
+    pub network: String,
+    /// Token address on `network`.
+    pub address: String,
+    /// Smallest spread reported, in percent; default 1.
+    pub threshold_percent: Option<f64>,
+    /// Pools compared on `network`, deepest first; default 5.
+    pub max_pools: Option<u32>,
+    /// Pools with less liquidity are skipped, since their prices are mostly
+    /// noise; default 10,000.
+    pub min_reserve_usd: Option<f64>,
+    /// Also compare the token's deepest pool on other networks.
+    #[serde(default)]
+    pub across_networks: bool,
+    /// Ask `apis.cex_price_tool` too; default true when one is configured.
+    pub include_cex: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FindArbitrageSpreadsOutput {
+    pub network: String,
+    pub address: String,
+    pub symbol: Option<String>,
+    pub threshold_percent: f64,
+    /// Every price compared, deepest pools first and the CEX price last.
+    pub quotes: Vec<PriceQuote>,
+    /// Pairs of quotes at least `threshold_percent` apart, widest first.
+    pub spreads: Vec<Spread>,
+    /// Whether the prices came from the cache rather than fresh calls.
+    pub cached: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceQuote {
+    /// `{network}:{pool address}`, or `cex:{tool}`.
+    pub venue: String,
+    pub source: QuoteSource,
+    pub network: Option<String>,
+    pub pool: Option<String>,
+    pub name: Option<String>,
+    pub dex: Option<String>,
+    pub price_usd: f64,
+    pub reserve_in_usd: Option<f64>,
+    pub links: Option<Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteSource {
+    Dex,
+    Cex,
+}
+
+/// Buying at `buy` and selling at `sell`, before fees, gas and slippage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Spread {
+    pub buy: String,
+    pub sell: String,
+    pub buy_price_usd: f64,
+    pub sell_price_usd: f64,
+    /// `(sell - buy) / buy`, in percent.
+    pub spread_percent: f64,
+}
@@ -0,0 +1,16 @@
+use super::dto::{FindArbitrageSpreadsInput, FindArbitrageSpreadsOutput};
+use super::implementation::ArbitrageTools;
+use crate::error::Result;
+use futures::future::BoxFuture;
+use serde_json::Value;
+
+pub async fn find_arbitrage_spreads<'a, F>(
+    tools: &ArbitrageTools,
+    input: FindArbitrageSpreadsInput,
+    call: F,
+) -> Result<FindArbitrageSpreadsOutput>
+where
+    F: Fn(String, Value) -> BoxFuture<'a, Result<Value>>,
+{
+    tools.find_spreads(input, call).await
+}
@@ -0,0 +1,435 @@
+use super::dto::{
+    FindArbitrageSpreadsInput, FindArbitrageSpreadsOutput, PriceQuote, QuoteSource, Spread,
+};
+use crate::error::{NovaError, Result};
+use crate::tools::gecko_terminal::address::{validate_address, AddressKind};
+use crate::tools::gecko_terminal::cross_network::mappings::same_address;
+use crate::tools::gecko_terminal::cross_network::FindTokenAcrossNetworksOutput;
+use crate::tools::gecko_terminal::summary::amount;
+use futures::future::BoxFuture;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long fetched prices are reused when no TTL is supplied explicitly.
+const DEFAULT_CACHE_TTL_SECS: u64 = 30;
+/// Tokens whose prices are cached at once; the oldest entry makes room.
+const MAX_CACHED_TOKENS: usize = 256;
+const DEFAULT_THRESHOLD_PERCENT: f64 = 1.0;
+const DEFAULT_MAX_POOLS: u32 = 5;
+const MAX_POOLS: u32 = 20;
+const DEFAULT_MIN_RESERVE_USD: f64 = 10_000.0;
+const MAX_SPREADS: usize = 20;
+
+/// Compares a token's prices across venues by calling other tools:
+/// `search_pools` for its pools on one network, `find_token_across_networks`
+/// for other networks and `apis.cex_price_tool` for a CEX price. At most
+/// three calls go out per lookup, and their results are cached briefly so
+/// retrying with another threshold costs nothing upstream.
+#[derive(Clone)]
+pub struct ArbitrageTools {
+    cex_price_tool: Option<String>,
+    ttl: Duration,
+    cache: Arc<Mutex<HashMap<String, Cached>>>,
+}
+
+/// What the underlying tools returned for one token.
+struct Sources {
+    pools: Value,
+    across: Option<FindTokenAcrossNetworksOutput>,
+    cex_price: Option<f64>,
+    symbol: Option<String>,
+}
+
+struct Cached {
+    at: Instant,
+    sources: Arc<Sources>,
+}
+
+impl ArbitrageTools {
+    pub fn new() -> Self {
+        Self {
+            cex_price_tool: None,
+            ttl: Duration::from_secs(DEFAULT_CACHE_TTL_SECS),
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Tool asked for a CEX price with `{ "symbol": ... }`; its result needs
+    /// a `price_usd` or `price` member.
+    pub fn with_cex_price_tool(mut self, tool: impl Into<String>) -> Self {
+        self.cex_price_tool = Some(tool.into());
+        self
+    }
+
+    /// How long fetched prices are reused; zero disables the cache.
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn cex_price_tool(&self) -> Option<&str> {
+        self.cex_price_tool.as_deref()
+    }
+
+    /// Runs the lookup, calling other tools through `call(tool, arguments)`.
+    pub async fn find_spreads<'a, F>(
+        &self,
+        input: FindArbitrageSpreadsInput,
+        call: F,
+    ) -> Result<FindArbitrageSpreadsOutput>
+    where
+        F: Fn(String, Value) -> BoxFuture<'a, Result<Value>>,
+    {
+        let network = input.network.trim().to_string();
+        let address = input.address.trim().to_string();
+        validate_address(&network, &address, AddressKind::Token)?;
+        let threshold = input.threshold_percent.unwrap_or(DEFAULT_THRESHOLD_PERCENT);
+        if !threshold.is_finite() || threshold < 0.0 {
+            return Err(NovaError::validation_error(
+                "threshold_percent must be a non-negative number",
+            ));
+        }
+        let max_pools = input.max_pools.unwrap_or(DEFAULT_MAX_POOLS);
+        if max_pools == 0 || max_pools > MAX_POOLS {
+            return Err(NovaError::validation_error(format!(
+                "max_pools must be 1..={}",
+                MAX_POOLS
+            )));
+        }
+        let min_reserve = input.min_reserve_usd.unwrap_or(DEFAULT_MIN_RESERVE_USD);
+        if !min_reserve.is_finite() || min_reserve < 0.0 {
+            return Err(NovaError::validation_error(
+                "min_reserve_usd must be a non-negative number",
+            ));
+        }
+        let cex_tool = match (input.include_cex, &self.cex_price_tool) {
+            (Some(true), None) => {
+                return Err(NovaError::validation_error(
+                    "include_cex needs apis.cex_price_tool to be configured",
+                ))
+            }
+            (Some(false), _) => None,
+            (_, tool) => tool.as_deref(),
+        };
+
+        let key = format!(
+            "{}|{}|{}|{}",
+            network,
+            address.to_lowercase(),
+            input.across_networks,
+            cex_tool.unwrap_or_default()
+        );
+        let (sources, cached) = match self.cached(&key) {
+            Some(sources) => (sources, true),
+            None => {
+                let (sources, complete) = self
+                    .fetch(&network, &address, input.across_networks, cex_tool, &call)
+                    .await?;
+                let sources = Arc::new(sources);
+                if complete {
+                    self.store(key, Arc::clone(&sources));
+                }
+                (sources, false)
+            }
+        };
+
+        let quotes = quotes(
+            &sources,
+            &network,
+            &address,
+            max_pools as usize,
+            min_reserve,
+            cex_tool,
+        );
+        let spreads = spreads(&quotes, threshold);
+        Ok(FindArbitrageSpreadsOutput {
+            network,
+            address,
+            symbol: sources.symbol.clone(),
+            threshold_percent: threshold,
+            quotes,
+            spreads,
+            cached,
+        })
+    }
+
+    /// Calls the tools; `false` when an optional source failed, so the
+    /// partial result is not cached.
+    async fn fetch<'a, F>(
+        &self,
+        network: &str,
+        address: &str,
+        across_networks: bool,
+        cex_tool: Option<&str>,
+        call: &F,
+    ) -> Result<(Sources, bool)>
+    where
+        F: Fn(String, Value) -> BoxFuture<'a, Result<Value>>,
+    {
+        let mut complete = true;
+        let pools = call(
+            "search_pools".to_string(),
+            json!({ "query": address, "network": network }),
+        );
+        let across = async {
+            if !across_networks {
+                return None;
+            }
+            let arguments = json!({ "token": address, "network": network });
+            Some(
+                call("find_token_across_networks".to_string(), arguments)
+                    .await
+                    .and_then(|output| Ok(serde_json::from_value(output)?)),
+            )
+        };
+        let (pools, across) = futures::join!(pools, across);
+        let pools = pools?["pools"].take();
+        let across: Option<FindTokenAcrossNetworksOutput> = match across {
+            Some(Ok(across)) => Some(across),
+            Some(Err(e)) => {
+                tracing::warn!("Skipping other networks for {}: {}", address, e);
+                complete = false;
+                None
+            }
+            None => None,
+        };
+        let symbol = symbol_of(&pools, address)
+            .or_else(|| across.as_ref().map(|across| across.symbol.clone()));
+
+        let mut cex_price = None;
+        if let (Some(tool), Some(symbol)) = (cex_tool, &symbol) {
+            match call(tool.to_string(), json!({ "symbol": symbol })).await {
+                Ok(output) => {
+                    cex_price = amount(&output["price_usd"]).or_else(|| amount(&output["price"]));
+                    if cex_price.is_none() {
+                        tracing::warn!("{} returned no price_usd or price for {}", tool, symbol);
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Skipping the CEX price for {}: {}", symbol, e);
+                    complete = false;
+                }
+            }
+        }
+        Ok((
+            Sources {
+                pools,
+                across,
+                cex_price,
+                symbol,
+            },
+            complete,
+        ))
+    }
+
+    fn cached(&self, key: &str) -> Option<Arc<Sources>> {
+        if self.ttl.is_zero() {
+            return None;
+        }
+        let cache = lock(&self.cache);
+        cache
+            .get(key)
+            .filter(|entry| entry.at.elapsed() < self.ttl)
+            .map(|entry| Arc::clone(&entry.sources))
+    }
+
+    fn store(&self, key: String, sources: Arc<Sources>) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let mut cache = lock(&self.cache);
+        cache.retain(|_, entry| entry.at.elapsed() < self.ttl);
+        if cache.len() >= MAX_CACHED_TOKENS {
+            let oldest = cache
+                .iter()
+                .min_by_key(|(_, entry)| entry.at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                cache.remove(&oldest);
+            }
+        }
+        cache.insert(
+            key,
+            Cached {
+                at: Instant::now(),
+                sources,
+            },
+        );
+    }
+}
+
+impl Default for ArbitrageTools {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn lock(
+    cache: &Mutex<HashMap<String, Cached>>,
+) -> std::sync::MutexGuard<'_, HashMap<String, Cached>> {
+    cache.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Symbol of the token at `address` among a search document's tokens.
+fn symbol_of(document: &Value, address: &str) -> Option<String> {
+    document["included"]
+        .as_array()?
+        .iter()
+        .filter(|resource| resource["type"] == "token")
+        .find(|token| {
+            token["attributes"]["address"]
+                .as_str()
+                .is_some_and(|found| same_address(found, address))
+        })
+        .and_then(|token| token["attributes"]["symbol"].as_str())
+        .map(str::to_string)
+}
+
+/// The `max_pools` deepest pools on `network` pricing the token, then its
+/// deepest pool on each other network, then the CEX price.
+fn quotes(
+    sources: &Sources,
+    network: &str,
+    address: &str,
+    max_pools: usize,
+    min_reserve: f64,
+    cex_tool: Option<&str>,
+) -> Vec<PriceQuote> {
+    let mut pools: Vec<PriceQuote> = sources.pools["data"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|pool| pool_quote(&sources.pools, pool, address))
+        .filter(|quote| quote.reserve_in_usd.unwrap_or_default() >= min_reserve)
+        .collect();
+    pools.sort_by(|a, b| {
+        b.reserve_in_usd
+            .unwrap_or_default()
+            .total_cmp(&a.reserve_in_usd.unwrap_or_default())
+    });
+    pools.truncate(max_pools);
+
+    let mut elsewhere = vec![];
+    for found in sources
+        .across
+        .iter()
+        .flat_map(|across| &across.representations)
+    {
+        let Some(pool) = found
+            .best_pool
+            .as_ref()
+            .filter(|_| found.network != network)
+        else {
+            continue;
+        };
+        let price = pool
+            .price_usd
+            .as_deref()
+            .and_then(|price| price.parse().ok());
+        let reserve = pool
+            .reserve_in_usd
+            .as_deref()
+            .and_then(|reserve| reserve.parse().ok());
+        let (Some(price), Some(reserve)) = (price, reserve) else {
+            continue;
+        };
+        if price <= 0.0 || reserve < min_reserve {
+            continue;
+        }
+        elsewhere.push(PriceQuote {
+            venue: format!("{}:{}", found.network, pool.address),
+            source: QuoteSource::Dex,
+            network: Some(found.network.clone()),
+            pool: Some(pool.address.clone()),
+            name: pool.name.clone(),
+            dex: pool.dex.clone(),
+            price_usd: price,
+            reserve_in_usd: Some(reserve),
+            links: Some(pool.links.clone()),
+        });
+    }
+    pools.extend(elsewhere);
+
+    if let (Some(tool), Some(price)) = (cex_tool, sources.cex_price) {
+        pools.push(PriceQuote {
+            venue: format!("cex:{}", tool),
+            source: QuoteSource::Cex,
+            network: None,
+            pool: None,
+            name: None,
+            dex: None,
+            price_usd: price,
+            reserve_in_usd: None,
+            links: None,
+        });
+    }
+    pools
+}
+
+/// The token's price in one pool of a search document, from whichever side
+/// of the pair it is on.
+fn pool_quote(document: &Value, pool: &Value, address: &str) -> Option<PriceQuote> {
+    let (network, pool_address) = pool["id"].as_str()?.rsplit_once('_')?;
+    let attributes = &pool["attributes"];
+    let side = ["base_token", "quote_token"].into_iter().find(|side| {
+        pool["relationships"][side]["data"]["id"]
+            .as_str()
+            .and_then(|id| id.rsplit_once('_'))
+            .is_some_and(|(_, token)| same_address(token, address))
+    })?;
+    let price = amount(&attributes[format!("{}_price_usd", side)]).filter(|price| *price > 0.0)?;
+    let dex = pool["relationships"]["dex"]["data"]["id"]
+        .as_str()
+        .map(|id| {
+            document["included"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .find(|resource| resource["type"] == "dex" && resource["id"] == id)
+                .and_then(|dex| dex["attributes"]["name"].as_str())
+                .unwrap_or(id)
+                .to_string()
+        });
+    let pool_address = attributes["address"].as_str().unwrap_or(pool_address);
+    Some(PriceQuote {
+        venue: format!("{}:{}", network, pool_address),
+        source: QuoteSource::Dex,
+        network: Some(network.to_string()),
+        pool: Some(pool_address.to_string()),
+        name: attributes["name"].as_str().map(str::to_string),
+        dex,
+        price_usd: price,
+        reserve_in_usd: amount(&attributes["reserve_in_usd"]),
+        links: pool.get("links").cloned(),
+    })
+}
+
+/// Every pair of quotes at least `threshold` percent apart, widest first.
+fn spreads(quotes: &[PriceQuote], threshold: f64) -> Vec<Spread> {
+    let mut spreads = vec![];
+    for (i, a) in quotes.iter().enumerate() {
+        for b in &quotes[i + 1..] {
+            let (buy, sell) = if a.price_usd <= b.price_usd {
+                (a, b)
+            } else {
+                (b, a)
+            };
+            let percent = (sell.price_usd - buy.price_usd) / buy.price_usd * 100.0;
+            if percent >= threshold {
+                spreads.push(Spread {
+                    buy: buy.venue.clone(),
+                    sell: sell.venue.clone(),
+                    buy_price_usd: buy.price_usd,
+                    sell_price_usd: sell.price_usd,
+                    spread_percent: (percent * 100.0).round() / 100.0,
+                });
+            }
+        }
+    }
+    spreads.sort_by(|a, b| b.spread_percent.total_cmp(&a.spread_percent));
+    spreads.truncate(MAX_SPREADS);
+    spreads
+}
@@ -0,0 +1,9 @@
+pub mod dto;
+pub mod handler;
+pub mod implementation;
+
+pub use dto::{
+    FindArbitrageSpreadsInput, FindArbitrageSpreadsOutput, PriceQuote, QuoteSource, Spread,
+};
+pub use handler::find_arbitrage_spreads;
+pub use implementation::ArbitrageTools;
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FindTokenAcrossNetworksInput {
+    /// A symbol ("USDC") or a token address.
+    pub token: String,
+    /// Network of an address `token`; narrows the first lookup.
+    pub network: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FindTokenAcrossNetworksOutput {
+    /// Symbol the networks were searched for; an address's own symbol.
+    pub symbol: String,
+    /// Id of the curated mapping the token belongs to, if any.
+    pub mapping: Option<String>,
+    pub representations: Vec<Representation>,
+}
+
+/// One network's version of the token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Representation {
+    pub network: String,
+    pub address: String,
+    pub symbol: Option<String>,
+    pub name: Option<String>,
+    /// Always set for curated entries; search matches are only marked
+    /// `wrapped` when their symbol is the searched one with a `W` prefix.
+    pub kind: Option<RepresentationKind>,
+    pub bridge: Option<String>,
+    pub source: RepresentationSource,
+    /// The deepest pool the search returned for this token.
+    pub best_pool: Option<BestPool>,
+    pub links: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BestPool {
+    pub address: String,
+    pub name: Option<String>,
+    pub dex: Option<String>,
+    pub reserve_in_usd: Option<String>,
+    pub volume_24h_usd: Option<String>,
+    /// Price of the token in this pool.
+    pub price_usd: Option<String>,
+    pub links: Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepresentationSource {
+    /// From the curated mapping table.
+    Curated,
+    /// Found by GeckoTerminal search only.
+    Search,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepresentationKind {
+    /// Issued natively on the network.
+    Canonical,
+    /// Minted by a bridge against tokens locked elsewhere.
+    Bridged,
+    /// A wrapper around the network's native coin or another token.
+    Wrapped,
+}
+
+/// A curated token and its addresses on each network, edited through
+/// `/admin/token-mappings`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenMapping {
+    pub id: String,
+    pub symbol: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub representations: Vec<MappedToken>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MappedToken {
+    /// GeckoTerminal network slug.
+    pub network: String,
+    pub address: String,
+    pub kind: RepresentationKind,
+    /// Bridge behind a `bridged` token, e.g. "wormhole".
+    #[serde(default)]
+    pub bridge: Option<String>,
+}
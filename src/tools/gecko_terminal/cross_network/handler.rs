@@ -0,0 +1,10 @@
+use super::dto::{FindTokenAcrossNetworksInput, FindTokenAcrossNetworksOutput};
+use super::implementation::CrossNetworkTools;
+use crate::error::Result;
+
+pub async fn find_token_across_networks(
+    tools: &CrossNetworkTools,
+    input: FindTokenAcrossNetworksInput,
+) -> Result<FindTokenAcrossNetworksOutput> {
+    tools.find_token(input).await
+}
@@ -0,0 +1,331 @@
+use super::dto::{
+    BestPool, FindTokenAcrossNetworksInput, FindTokenAcrossNetworksOutput, Representation,
+    RepresentationKind, RepresentationSource, TokenMapping,
+};
+use super::mappings::{same_address, TokenMappingStore};
+use crate::error::{NovaError, Result};
+use crate::tools::gecko_terminal::address::{looks_like_address, validate_address, AddressKind};
+use crate::tools::gecko_terminal::links::{self, ResourceKind};
+use crate::tools::gecko_terminal::search_pools::{SearchPoolsInput, SearchPoolsTools};
+use crate::tools::gecko_terminal::summary::amount;
+use serde_json::Value;
+use std::sync::Arc;
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 25;
+
+/// Finds a token's versions on other networks from the curated mappings
+/// and one or two GeckoTerminal searches.
+#[derive(Clone)]
+pub struct CrossNetworkTools {
+    search: SearchPoolsTools,
+    mappings: Arc<TokenMappingStore>,
+}
+
+impl CrossNetworkTools {
+    /// Searches through `search`, sharing its client and rate limit.
+    pub fn new(search: SearchPoolsTools) -> Self {
+        Self {
+            search,
+            mappings: Arc::new(TokenMappingStore::in_memory()),
+        }
+    }
+
+    /// Replaces the default in-memory mapping table, e.g. with a sled-backed one.
+    pub fn with_mappings(mut self, mappings: Arc<TokenMappingStore>) -> Self {
+        self.mappings = mappings;
+        self
+    }
+
+    pub fn mappings(&self) -> &TokenMappingStore {
+        &self.mappings
+    }
+
+    /// An address is first looked up on its own to learn its symbol; the
+    /// symbol is then searched on every network. Curated representations
+    /// come first, then search matches by pool liquidity.
+    pub async fn find_token(
+        &self,
+        input: FindTokenAcrossNetworksInput,
+    ) -> Result<FindTokenAcrossNetworksOutput> {
+        let token = input.token.trim();
+        if token.is_empty() {
+            return Err(NovaError::api_error("token is required"));
+        }
+        let limit = input.limit.unwrap_or(DEFAULT_LIMIT);
+        if limit == 0 || limit > MAX_LIMIT {
+            return Err(NovaError::api_error(format!(
+                "limit must be 1..={}",
+                MAX_LIMIT
+            )));
+        }
+        let network = input
+            .network
+            .as_deref()
+            .map(str::trim)
+            .filter(|network| !network.is_empty());
+
+        let mut documents = vec![];
+        let (mapping, symbol) = if looks_like_address(token) {
+            if let Some(network) = network {
+                validate_address(network, token, AddressKind::Token)?;
+            }
+            match self.mappings.find_by_address(network, token)? {
+                Some(mapping) => {
+                    let symbol = mapping.symbol.clone();
+                    (Some(mapping), symbol)
+                }
+                None => {
+                    let document = self.search(token, network).await?;
+                    let symbol = symbol_of(&document, token)
+                        .ok_or_else(|| NovaError::token_not_found(token))?;
+                    documents.push(document);
+                    (None, symbol)
+                }
+            }
+        } else {
+            match self.mappings.find_by_symbol(token)? {
+                Some(mapping) => {
+                    let symbol = mapping.symbol.clone();
+                    (Some(mapping), symbol)
+                }
+                None => (None, token.to_string()),
+            }
+        };
+        documents.push(self.search(&symbol, None).await?);
+
+        let wanted = Wanted {
+            symbol: &symbol,
+            queried: token,
+            mapping: mapping.as_ref(),
+        };
+        let mut found = vec![];
+        for document in &documents {
+            collect_matches(document, &wanted, &mut found);
+        }
+        let mut representations = match &mapping {
+            Some(mapping) => curated(mapping, &mut found),
+            None => vec![],
+        };
+        found.sort_by(|a, b| b.reserve.total_cmp(&a.reserve));
+        representations.extend(
+            found
+                .into_iter()
+                .map(|found| found.into_representation(&symbol)),
+        );
+        representations.truncate(limit as usize);
+        Ok(FindTokenAcrossNetworksOutput {
+            symbol,
+            mapping: mapping.map(|mapping| mapping.id),
+            representations,
+        })
+    }
+
+    async fn search(&self, query: &str, network: Option<&str>) -> Result<Value> {
+        let output = self
+            .search
+            .search_pools(SearchPoolsInput {
+                query: query.to_string(),
+                network: network.map(str::to_string),
+                page: None,
+            })
+            .await?;
+        Ok(output.pools)
+    }
+}
+
+/// A token seen in search results, with its deepest pool so far.
+struct Found {
+    network: String,
+    address: String,
+    symbol: Option<String>,
+    name: Option<String>,
+    reserve: f64,
+    pool: BestPool,
+}
+
+impl Found {
+    fn into_representation(self, symbol: &str) -> Representation {
+        let wrapped = self
+            .symbol
+            .as_deref()
+            .is_some_and(|found| is_wrapper(found, symbol));
+        Representation {
+            links: links::links(&self.network, ResourceKind::Token, &self.address),
+            network: self.network,
+            address: self.address,
+            symbol: self.symbol,
+            name: self.name,
+            kind: wrapped.then_some(RepresentationKind::Wrapped),
+            bridge: None,
+            source: RepresentationSource::Search,
+            best_pool: Some(self.pool),
+        }
+    }
+}
+
+/// The mapping's tokens in table order, each taking its search match out
+/// of `found`.
+fn curated(mapping: &TokenMapping, found: &mut Vec<Found>) -> Vec<Representation> {
+    mapping
+        .representations
+        .iter()
+        .map(|token| {
+            let matched = found
+                .iter()
+                .position(|found| {
+                    found.network == token.network && same_address(&found.address, &token.address)
+                })
+                .map(|index| found.remove(index));
+            let (symbol, name, best_pool) = match matched {
+                Some(found) => (found.symbol, found.name, Some(found.pool)),
+                None => (Some(mapping.symbol.clone()), mapping.name.clone(), None),
+            };
+            Representation {
+                network: token.network.clone(),
+                address: token.address.clone(),
+                symbol,
+                name,
+                kind: Some(token.kind),
+                bridge: token.bridge.clone(),
+                source: RepresentationSource::Curated,
+                best_pool,
+                links: links::links(&token.network, ResourceKind::Token, &token.address),
+            }
+        })
+        .collect()
+}
+
+/// Symbol of the token at `address` among a search document's tokens.
+fn symbol_of(document: &Value, address: &str) -> Option<String> {
+    included(document, "token")
+        .find(|token| {
+            token["attributes"]["address"]
+                .as_str()
+                .is_some_and(|found| same_address(found, address))
+        })
+        .and_then(|token| token["attributes"]["symbol"].as_str())
+        .map(str::to_string)
+}
+
+fn included<'a>(document: &'a Value, kind: &'a str) -> impl Iterator<Item = &'a Value> {
+    document["included"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter(move |resource| resource["type"] == kind)
+}
+
+/// Which search results belong to the token.
+struct Wanted<'a> {
+    symbol: &'a str,
+    queried: &'a str,
+    mapping: Option<&'a TokenMapping>,
+}
+
+impl Wanted<'_> {
+    /// The symbol or its `W`-prefixed wrapper, the queried address, or a
+    /// curated address, whose symbol may differ ("USDC.e").
+    fn matches(&self, network: &str, address: &str, symbol: &str) -> bool {
+        symbol.eq_ignore_ascii_case(self.symbol)
+            || is_wrapper(symbol, self.symbol)
+            || same_address(address, self.queried)
+            || self.mapping.is_some_and(|mapping| {
+                mapping
+                    .representations
+                    .iter()
+                    .any(|token| token.network == network && same_address(&token.address, address))
+            })
+    }
+}
+
+fn is_wrapper(found: &str, symbol: &str) -> bool {
+    found
+        .strip_prefix(['W', 'w'])
+        .is_some_and(|rest| rest.eq_ignore_ascii_case(symbol))
+}
+
+/// Adds each pool side whose token is `wanted` to `found`, keeping the
+/// deepest pool per token.
+fn collect_matches(document: &Value, wanted: &Wanted, found: &mut Vec<Found>) {
+    let pools = document["data"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for pool in pools {
+        let Some((network, pool_address)) = pool["id"].as_str().and_then(|id| id.rsplit_once('_'))
+        else {
+            continue;
+        };
+        let attributes = &pool["attributes"];
+        let reserve = amount(&attributes["reserve_in_usd"]).unwrap_or_default();
+        for (side, price) in [
+            ("base_token", "base_token_price_usd"),
+            ("quote_token", "quote_token_price_usd"),
+        ] {
+            let Some(id) = pool["relationships"][side]["data"]["id"].as_str() else {
+                continue;
+            };
+            let Some(token) = included(document, "token").find(|token| token["id"] == id) else {
+                continue;
+            };
+            let token = &token["attributes"];
+            let token_symbol = token["symbol"].as_str().unwrap_or_default();
+            let address = token["address"]
+                .as_str()
+                .or_else(|| id.rsplit_once('_').map(|(_, address)| address))
+                .unwrap_or_default();
+            if address.is_empty() || !wanted.matches(network, address, token_symbol) {
+                continue;
+            }
+            let existing = found.iter().position(|found| {
+                found.network == network && same_address(&found.address, address)
+            });
+            if existing.is_some_and(|index| found[index].reserve >= reserve) {
+                continue;
+            }
+            let best_pool = BestPool {
+                address: attributes["address"]
+                    .as_str()
+                    .unwrap_or(pool_address)
+                    .to_string(),
+                name: text(&attributes["name"]),
+                dex: dex_name(document, pool),
+                reserve_in_usd: text(&attributes["reserve_in_usd"]),
+                volume_24h_usd: text(&attributes["volume_usd"]["h24"]),
+                price_usd: text(&attributes[price]),
+                links: pool["links"].clone(),
+            };
+            let entry = Found {
+                network: network.to_string(),
+                address: address.to_string(),
+                symbol: text(&token["symbol"]),
+                name: text(&token["name"]),
+                reserve,
+                pool: best_pool,
+            };
+            match existing {
+                Some(index) => found[index] = entry,
+                None => found.push(entry),
+            }
+        }
+    }
+}
+
+fn dex_name(document: &Value, pool: &Value) -> Option<String> {
+    let id = pool["relationships"]["dex"]["data"]["id"].as_str()?;
+    let name = included(document, "dex")
+        .find(|dex| dex["id"] == id)
+        .and_then(|dex| dex["attributes"]["name"].as_str());
+    Some(name.unwrap_or(id).to_string())
+}
+
+/// A string or number attribute as text.
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
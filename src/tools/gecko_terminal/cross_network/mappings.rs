@@ -0,0 +1,129 @@
+use super::dto::TokenMapping;
+use crate::error::{NovaError, Result};
+use crate::storage::{KvStore, MemoryKv};
+use crate::tools::gecko_terminal::address::{validate_address, AddressKind};
+
+/// Curated cross-network token mappings, keyed by mapping id.
+///
+/// Search alone cannot tell a bridged USDC from a lookalike with the same
+/// symbol, so `find_token_across_networks` lists these first and marks
+/// them `curated`.
+pub struct TokenMappingStore {
+    store: Box<dyn KvStore>,
+}
+
+impl TokenMappingStore {
+    pub fn in_memory() -> Self {
+        Self {
+            store: Box::new(MemoryKv::default()),
+        }
+    }
+
+    /// Stores JSON-encoded mappings keyed by id.
+    pub fn persistent(tree: sled::Tree) -> Self {
+        Self {
+            store: Box::new(tree),
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<TokenMapping>> {
+        self.store.get_json(id.as_bytes())
+    }
+
+    /// Every mapping, by id.
+    pub fn list(&self) -> Result<Vec<TokenMapping>> {
+        self.store.values_json(b"")
+    }
+
+    /// Validates and stores `mapping`, returning the one it replaced.
+    pub fn put(&self, mapping: &TokenMapping) -> Result<Option<TokenMapping>> {
+        validate(mapping)?;
+        let previous = self
+            .store
+            .insert(mapping.id.as_bytes(), serde_json::to_vec(mapping)?)?;
+        previous
+            .map(|bytes| Ok(serde_json::from_slice(&bytes)?))
+            .transpose()
+    }
+
+    /// Returns the removed mapping, if there was one.
+    pub fn remove(&self, id: &str) -> Result<Option<TokenMapping>> {
+        self.store
+            .remove(id.as_bytes())?
+            .map(|bytes| Ok(serde_json::from_slice(&bytes)?))
+            .transpose()
+    }
+
+    /// The mapping listing `address`, on `network` when given.
+    pub fn find_by_address(
+        &self,
+        network: Option<&str>,
+        address: &str,
+    ) -> Result<Option<TokenMapping>> {
+        Ok(self.list()?.into_iter().find(|mapping| {
+            mapping.representations.iter().any(|token| {
+                network.is_none_or(|network| token.network == network)
+                    && same_address(&token.address, address)
+            })
+        }))
+    }
+
+    /// The first mapping, by id, whose symbol is `symbol` in any case.
+    pub fn find_by_symbol(&self, symbol: &str) -> Result<Option<TokenMapping>> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .find(|mapping| mapping.symbol.eq_ignore_ascii_case(symbol)))
+    }
+}
+
+impl Default for TokenMappingStore {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+/// EVM addresses compare without case; base58 and other addresses exactly.
+pub(crate) fn same_address(a: &str, b: &str) -> bool {
+    if a.starts_with("0x") {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a == b
+    }
+}
+
+fn validate(mapping: &TokenMapping) -> Result<()> {
+    let id = mapping.id.as_str();
+    if id.is_empty() || id.contains(|c: char| c.is_whitespace() || c == '/') {
+        return Err(NovaError::validation_error(
+            "mapping id must be non-empty without spaces or slashes",
+        ));
+    }
+    if mapping.symbol.trim().is_empty() {
+        return Err(NovaError::validation_error("symbol is required"));
+    }
+    if mapping.representations.is_empty() {
+        return Err(NovaError::validation_error(
+            "representations cannot be empty",
+        ));
+    }
+    for (i, token) in mapping.representations.iter().enumerate() {
+        if token.network.trim().is_empty() {
+            return Err(NovaError::validation_error(format!(
+                "representations[{}].network is required",
+                i
+            )));
+        }
+        validate_address(&token.network, &token.address, AddressKind::Token)?;
+        let duplicate = mapping.representations[..i].iter().any(|earlier| {
+            earlier.network == token.network && same_address(&earlier.address, &token.address)
+        });
+        if duplicate {
+            return Err(NovaError::validation_error(format!(
+                "{} on {} is listed twice",
+                token.address, token.network
+            )));
+        }
+    }
+    Ok(())
+}
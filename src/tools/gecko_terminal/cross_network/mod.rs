@@ -0,0 +1,12 @@
+pub mod dto;
+pub mod handler;
+pub mod implementation;
+pub mod mappings;
+
+pub use dto::{
+    BestPool, FindTokenAcrossNetworksInput, FindTokenAcrossNetworksOutput, MappedToken,
+    Representation, RepresentationKind, RepresentationSource, TokenMapping,
+};
+pub use handler::find_token_across_networks;
+pub use implementation::CrossNetworkTools;
+pub use mappings::TokenMappingStore;
@@ -1,3 +1,30 @@
+use crate::error::{NovaError, Result};
+use crate::tools::rate_limit::{parse_retry_after, UpstreamRateLimiter};
+use crate::tools::upstream_health::UpstreamHealth;
+use reqwest::{header::RETRY_AFTER, StatusCode};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+pub const GECKO_TERMINAL_API: &str = "geckoterminal";
+/// GeckoTerminal's documented public limit.
+pub const DEFAULT_RATE_LIMIT_PER_MINUTE: u32 = 30;
+pub const DEFAULT_MAX_WAIT: Duration = Duration::from_secs(5);
+/// Public API root; `apis.gecko_terminal_url` points the tools elsewhere.
+pub const DEFAULT_BASE_URL: &str = "https://api.geckoterminal.com/api/v2";
+
+/// Process-wide limiter shared by tools built with `new()`, so independently
+/// constructed tool sets still respect the single upstream budget.
+pub(crate) fn default_limiter() -> Arc<UpstreamRateLimiter> {
+    static LIMITER: OnceLock<Arc<UpstreamRateLimiter>> = OnceLock::new();
+    Arc::clone(LIMITER.get_or_init(|| {
+        Arc::new(UpstreamRateLimiter::new(
+            GECKO_TERMINAL_API,
+            DEFAULT_RATE_LIMIT_PER_MINUTE,
+            DEFAULT_MAX_WAIT,
+        ))
+    }))
+}
+
 pub(crate) fn build_url(base: &str, segments: &[&str]) -> String {
     let mut url = base.trim_end_matches('/').to_string();
     for segment in segments {
@@ -6,3 +33,136 @@ pub(crate) fn build_url(base: &str, segments: &[&str]) -> String {
     }
     url
 }
+
+/// Non-success upstream reply, with the message extracted from the
+/// JSON:API `errors` array when GeckoTerminal provides one.
+#[derive(Debug)]
+pub(crate) struct UpstreamFailure {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl UpstreamFailure {
+    fn from_body(status: StatusCode, body: &str) -> Self {
+        let parsed = serde_json::from_str::<serde_json::Value>(body).ok();
+        let message = parsed
+            .as_ref()
+            .and_then(|value| value.get("errors"))
+            .and_then(|errors| errors.as_array())
+            .map(|errors| {
+                errors
+                    .iter()
+                    .filter_map(|error| {
+                        error
+                            .get("detail")
+                            .or_else(|| error.get("title"))
+                            .and_then(|text| text.as_str())
+                    })
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            })
+            .filter(|message| !message.is_empty())
+            .or_else(|| {
+                parsed
+                    .as_ref()
+                    .and_then(|value| value.get("error"))
+                    .and_then(|text| text.as_str())
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| {
+                status
+                    .canonical_reason()
+                    .unwrap_or("unknown error")
+                    .to_string()
+            });
+        Self { status, message }
+    }
+
+    fn mentions(&self, needle: &str) -> bool {
+        self.message.to_lowercase().contains(needle)
+    }
+
+    /// True for a 404 about the looked-up resource rather than the network slug.
+    pub fn is_resource_not_found(&self) -> bool {
+        self.status == StatusCode::NOT_FOUND && !self.mentions("network")
+    }
+
+    pub fn is_invalid_address(&self) -> bool {
+        matches!(
+            self.status,
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY
+        ) && self.mentions("address")
+    }
+
+    pub fn into_error(self) -> NovaError {
+        NovaError::api_error(format!(
+            "GeckoTerminal returned {}: {}",
+            self.status.as_u16(),
+            self.message
+        ))
+    }
+}
+
+/// Issues a rate-limited GET against GeckoTerminal and decodes the JSON body,
+/// turning upstream error payloads into `NovaError::ApiError`.
+pub(crate) async fn get_json(
+    http: &reqwest::Client,
+    limiter: &UpstreamRateLimiter,
+    health: &UpstreamHealth,
+    url: &str,
+) -> Result<serde_json::Value> {
+    fetch(http, limiter, health, url)
+        .await?
+        .map_err(UpstreamFailure::into_error)
+}
+
+/// Like `get_json`, but hands non-success replies back to the caller so
+/// lookups can map them to resource-specific errors.
+pub(crate) async fn fetch(
+    http: &reqwest::Client,
+    limiter: &UpstreamRateLimiter,
+    health: &UpstreamHealth,
+    url: &str,
+) -> Result<std::result::Result<serde_json::Value, UpstreamFailure>> {
+    let response = send(http, limiter, health, url).await?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Ok(Err(UpstreamFailure::from_body(status, &body)));
+    }
+    response
+        .json::<serde_json::Value>()
+        .await
+        .map(Ok)
+        .map_err(NovaError::NetworkError)
+}
+
+async fn send(
+    http: &reqwest::Client,
+    limiter: &UpstreamRateLimiter,
+    health: &UpstreamHealth,
+    url: &str,
+) -> Result<reqwest::Response> {
+    limiter.acquire().await?;
+    let started = Instant::now();
+    let response = match http.get(url).send().await {
+        Ok(response) => response,
+        Err(e) => {
+            health.record(limiter.api(), started.elapsed(), Some(e.to_string()));
+            return Err(NovaError::NetworkError(e));
+        }
+    };
+    let status = response.status();
+    let failure = (status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error())
+        .then(|| format!("HTTP {}", status.as_u16()));
+    health.record(limiter.api(), started.elapsed(), failure);
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        return Err(limiter.defer(retry_after).await);
+    }
+    Ok(response)
+}
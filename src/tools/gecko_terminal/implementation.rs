@@ -1,18 +1,39 @@
-use super::helpers::build_url;
+use super::address::{validate_address, AddressKind};
+use super::helpers::{build_url, default_limiter, fetch, get_json, DEFAULT_BASE_URL};
+use super::links;
+use super::networks::aliases::NetworkAliases;
 use super::networks::dto::{GetGeckoNetworksInput, GetGeckoNetworksOutput};
 use super::pool::dto::{GetGeckoPoolInput, GetGeckoPoolOutput};
 use super::token::dto::{GetGeckoTokenInput, GetGeckoTokenOutput};
 use crate::error::{NovaError, Result};
+use crate::tools::negative_cache::NegativeCache;
+use crate::tools::rate_limit::UpstreamRateLimiter;
+use crate::tools::upstream_health::{default_health, UpstreamHealth};
+use std::sync::Arc;
 use std::time::Duration;
 
 #[derive(Clone)]
 pub struct GeckoTerminalTools {
     http: reqwest::Client,
     base_url: String,
+    limiter: Arc<UpstreamRateLimiter>,
+    health: Arc<UpstreamHealth>,
+    not_found: Arc<NegativeCache>,
+    /// Learned from `get_networks`; resolves `network` arguments and completes them.
+    networks: Arc<NetworkAliases>,
 }
 
+/// TTL for cached upstream 404s when no cache is supplied explicitly.
+const DEFAULT_NEGATIVE_TTL_SECS: u64 = 60;
+/// How often the network list is refetched when no interval is supplied explicitly.
+const DEFAULT_NETWORKS_TTL_SECS: u64 = 3600;
+
 impl GeckoTerminalTools {
     pub fn new() -> Self {
+        Self::with_rate_limiter(default_limiter())
+    }
+
+    pub fn with_rate_limiter(limiter: Arc<UpstreamRateLimiter>) -> Self {
         let http = reqwest::Client::builder()
             .timeout(Duration::from_secs(10))
             .user_agent("Nova-MCP/0.1.0")
@@ -23,65 +44,121 @@ impl GeckoTerminalTools {
             });
         Self {
             http,
-            base_url: "https://api.geckoterminal.com/api/v2".to_string(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            limiter,
+            health: default_health(),
+            not_found: Arc::new(NegativeCache::in_memory(DEFAULT_NEGATIVE_TTL_SECS)),
+            networks: Arc::new(NetworkAliases::new(Duration::from_secs(
+                DEFAULT_NETWORKS_TTL_SECS,
+            ))),
         }
     }
 
+    /// Swaps in a preconfigured client (proxy, extra root CAs).
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Replaces the public API root, e.g. with a mirror or a mock.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Records call outcomes in a shared tracker, e.g. the server's.
+    pub fn with_upstream_health(mut self, health: Arc<UpstreamHealth>) -> Self {
+        self.health = health;
+        self
+    }
+
+    pub fn with_negative_cache(mut self, cache: Arc<NegativeCache>) -> Self {
+        self.not_found = cache;
+        self
+    }
+
+    /// Refetch interval for the network list behind [`Self::resolve_network`].
+    pub fn with_networks_ttl(mut self, ttl: Duration) -> Self {
+        self.networks = Arc::new(NetworkAliases::new(ttl));
+        self
+    }
+
     pub async fn get_networks(
         &self,
         _input: GetGeckoNetworksInput,
     ) -> Result<GetGeckoNetworksOutput> {
         let url = build_url(&self.base_url, &["networks"]);
-        let networks = self
-            .http
-            .get(&url)
-            .send()
-            .await
-            .map_err(NovaError::NetworkError)?
-            .error_for_status()
-            .map_err(NovaError::NetworkError)?
-            .json::<serde_json::Value>()
-            .await
-            .map_err(NovaError::NetworkError)?;
+        let networks = get_json(&self.http, &self.limiter, &self.health, &url).await?;
+        self.networks.learn(&networks);
         Ok(GetGeckoNetworksOutput { networks })
     }
 
+    /// Network slugs seen by the last `get_networks` call; empty until one succeeds.
+    pub fn cached_network_slugs(&self) -> Vec<String> {
+        self.networks.slugs()
+    }
+
+    /// Maps a user-supplied network ("Ethereum", "ETH") to its GeckoTerminal
+    /// slug, refetching the network list first when it is due.
+    pub async fn resolve_network(&self, network: &str) -> Result<String> {
+        if self.networks.claim_refresh() {
+            if let Err(e) = self.get_networks(GetGeckoNetworksInput {}).await {
+                tracing::warn!("Failed to refresh GeckoTerminal networks: {}", e);
+            }
+        }
+        self.networks.resolve(network)
+    }
+
     pub async fn get_token(&self, input: GetGeckoTokenInput) -> Result<GetGeckoTokenOutput> {
+        validate_address(&input.network, &input.address, AddressKind::Token)?;
+        let cache_key = NegativeCache::key("token", &input.network, &input.address);
+        if self.not_found.contains(&cache_key)? {
+            return Err(NovaError::token_not_found(input.address));
+        }
         let url = build_url(
             &self.base_url,
             &["networks", &input.network, "tokens", &input.address],
         );
-        let token = self
-            .http
-            .get(&url)
-            .send()
-            .await
-            .map_err(NovaError::NetworkError)?
-            .error_for_status()
-            .map_err(NovaError::NetworkError)?
-            .json::<serde_json::Value>()
-            .await
-            .map_err(NovaError::NetworkError)?;
-        Ok(GetGeckoTokenOutput { token })
+        match fetch(&self.http, &self.limiter, &self.health, &url).await? {
+            Ok(mut token) => {
+                links::enrich(&mut token);
+                Ok(GetGeckoTokenOutput { token })
+            }
+            Err(failure) if failure.is_resource_not_found() => {
+                self.not_found.insert(&cache_key)?;
+                Err(NovaError::token_not_found(input.address))
+            }
+            Err(failure) if failure.is_invalid_address() => {
+                Err(NovaError::invalid_address(input.address))
+            }
+            Err(failure) => Err(failure.into_error()),
+        }
     }
 
     pub async fn get_pool(&self, input: GetGeckoPoolInput) -> Result<GetGeckoPoolOutput> {
+        validate_address(&input.network, &input.address, AddressKind::Pool)?;
+        let cache_key = NegativeCache::key("pool", &input.network, &input.address);
+        if self.not_found.contains(&cache_key)? {
+            return Err(NovaError::pool_not_found(input.address));
+        }
         let url = build_url(
             &self.base_url,
             &["networks", &input.network, "pools", &input.address],
         );
-        let pool = self
-            .http
-            .get(&url)
-            .send()
-            .await
-            .map_err(NovaError::NetworkError)?
-            .error_for_status()
-            .map_err(NovaError::NetworkError)?
-            .json::<serde_json::Value>()
-            .await
-            .map_err(NovaError::NetworkError)?;
-        Ok(GetGeckoPoolOutput { pool })
+        match fetch(&self.http, &self.limiter, &self.health, &url).await? {
+            Ok(mut pool) => {
+                links::enrich(&mut pool);
+                Ok(GetGeckoPoolOutput { pool })
+            }
+            Err(failure) if failure.is_resource_not_found() => {
+                self.not_found.insert(&cache_key)?;
+                Err(NovaError::pool_not_found(input.address))
+            }
+            Err(failure) if failure.is_invalid_address() => {
+                Err(NovaError::invalid_address(input.address))
+            }
+            Err(failure) => Err(failure.into_error()),
+        }
     }
 }
 
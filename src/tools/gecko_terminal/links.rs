@@ -0,0 +1,214 @@
+//! Web links for pools and tokens, added to tool output as `links` so
+//! agents can hand users pages to open without knowing each site's URL
+//! scheme.
+//!
+//! Every pool and token gets its GeckoTerminal page. Networks in the table
+//! below also get their Dexscreener page and a block explorer link;
+//! [`address_url`] and [`tx_url`] build explorer links for wallets and
+//! transactions.
+
+use serde_json::{json, Map, Value};
+
+const GECKOTERMINAL: &str = "https://www.geckoterminal.com";
+const DEXSCREENER: &str = "https://dexscreener.com";
+
+/// A block explorer's pages for accounts, tokens and transactions.
+struct Explorer {
+    base: &'static str,
+    address: &'static str,
+    token: &'static str,
+    tx: &'static str,
+}
+
+const fn etherscan_like(base: &'static str) -> Explorer {
+    Explorer {
+        base,
+        address: "address",
+        token: "token",
+        tx: "tx",
+    }
+}
+
+/// Per-network link metadata, keyed by GeckoTerminal slug.
+struct NetworkLinks {
+    slug: &'static str,
+    /// Dexscreener's chain id, when it lists the network.
+    dexscreener: Option<&'static str>,
+    explorer: Option<Explorer>,
+}
+
+const NETWORKS: &[NetworkLinks] = &[
+    evm("eth", "ethereum", "https://etherscan.io"),
+    evm("bsc", "bsc", "https://bscscan.com"),
+    evm("polygon_pos", "polygon", "https://polygonscan.com"),
+    evm("avax", "avalanche", "https://snowtrace.io"),
+    evm("ftm", "fantom", "https://ftmscan.com"),
+    evm("arbitrum", "arbitrum", "https://arbiscan.io"),
+    evm("optimism", "optimism", "https://optimistic.etherscan.io"),
+    evm("base", "base", "https://basescan.org"),
+    evm("linea", "linea", "https://lineascan.build"),
+    evm("scroll", "scroll", "https://scrollscan.com"),
+    evm("zksync", "zksync", "https://era.zksync.network"),
+    evm("blast", "blast", "https://blastscan.io"),
+    evm("mantle", "mantle", "https://mantlescan.xyz"),
+    evm("cro", "cronos", "https://cronoscan.com"),
+    evm("celo", "celo", "https://celoscan.io"),
+    evm("sonic", "sonic", "https://sonicscan.org"),
+    evm("unichain", "unichain", "https://uniscan.xyz"),
+    evm("berachain", "berachain", "https://berascan.com"),
+    NetworkLinks {
+        slug: "solana",
+        dexscreener: Some("solana"),
+        explorer: Some(Explorer {
+            base: "https://solscan.io",
+            address: "account",
+            token: "token",
+            tx: "tx",
+        }),
+    },
+    NetworkLinks {
+        slug: "ton",
+        dexscreener: Some("ton"),
+        explorer: Some(Explorer {
+            base: "https://tonviewer.com",
+            address: "",
+            token: "",
+            tx: "transaction",
+        }),
+    },
+    NetworkLinks {
+        slug: "aptos",
+        dexscreener: Some("aptos"),
+        explorer: None,
+    },
+    NetworkLinks {
+        slug: "sui-network",
+        dexscreener: Some("sui"),
+        explorer: None,
+    },
+];
+
+const fn evm(
+    slug: &'static str,
+    dexscreener: &'static str,
+    explorer: &'static str,
+) -> NetworkLinks {
+    NetworkLinks {
+        slug,
+        dexscreener: Some(dexscreener),
+        explorer: Some(etherscan_like(explorer)),
+    }
+}
+
+fn find_network(slug: &str) -> Option<&'static NetworkLinks> {
+    NETWORKS.iter().find(|network| network.slug == slug)
+}
+
+impl Explorer {
+    fn page(&self, kind: &str, id: &str) -> String {
+        if kind.is_empty() {
+            format!("{}/{}", self.base, id)
+        } else {
+            format!("{}/{}/{}", self.base, kind, id)
+        }
+    }
+}
+
+/// Links for a pool or token on `network`: `geckoterminal`, and where the
+/// network is known `dexscreener` and `explorer`.
+pub fn links(network: &str, kind: ResourceKind, address: &str) -> Value {
+    let mut links = Map::new();
+    links.insert(
+        "geckoterminal".into(),
+        json!(format!(
+            "{}/{}/{}/{}",
+            GECKOTERMINAL,
+            network,
+            kind.path(),
+            address
+        )),
+    );
+    if let Some(network) = find_network(network) {
+        if let Some(chain) = network.dexscreener {
+            links.insert(
+                "dexscreener".into(),
+                json!(format!("{}/{}/{}", DEXSCREENER, chain, address)),
+            );
+        }
+        if let Some(explorer) = &network.explorer {
+            let page = match kind {
+                ResourceKind::Pool => explorer.address,
+                ResourceKind::Token => explorer.token,
+            };
+            links.insert("explorer".into(), json!(explorer.page(page, address)));
+        }
+    }
+    Value::Object(links)
+}
+
+/// Explorer link for a wallet or account on `network`, if it has one.
+pub fn address_url(network: &str, address: &str) -> Option<String> {
+    let explorer = find_network(network)?.explorer.as_ref()?;
+    Some(explorer.page(explorer.address, address))
+}
+
+/// Explorer link for transaction `hash` on `network`, if it has one.
+pub fn tx_url(network: &str, hash: &str) -> Option<String> {
+    let explorer = find_network(network)?.explorer.as_ref()?;
+    Some(explorer.page(explorer.tx, hash))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    Pool,
+    Token,
+}
+
+impl ResourceKind {
+    fn path(self) -> &'static str {
+        match self {
+            ResourceKind::Pool => "pools",
+            ResourceKind::Token => "tokens",
+        }
+    }
+
+    fn of(resource: &Value) -> Option<Self> {
+        match resource["type"].as_str()? {
+            "pool" => Some(ResourceKind::Pool),
+            "token" => Some(ResourceKind::Token),
+            _ => None,
+        }
+    }
+}
+
+/// Links for a JSON:API pool or token resource, whose id is
+/// `{network}_{address}`.
+pub fn resource_links(resource: &Value, kind: ResourceKind) -> Option<Value> {
+    let (network, address) = resource["id"].as_str()?.rsplit_once('_')?;
+    let address = resource["attributes"]["address"]
+        .as_str()
+        .unwrap_or(address);
+    Some(links(network, kind, address))
+}
+
+/// Adds `links` to every pool and token resource in a JSON:API document's
+/// `data`, single or list, and in its `included` resources.
+pub fn enrich(document: &mut Value) {
+    let Some(document) = document.as_object_mut() else {
+        return;
+    };
+    let mut resources = vec![];
+    for (member, value) in document.iter_mut() {
+        match (member.as_str(), value) {
+            ("data" | "included", Value::Array(items)) => resources.extend(items.iter_mut()),
+            ("data", resource @ Value::Object(_)) => resources.push(resource),
+            _ => {}
+        }
+    }
+    for resource in resources {
+        let links = ResourceKind::of(resource).and_then(|kind| resource_links(resource, kind));
+        if let (Some(links), Value::Object(fields)) = (links, resource) {
+            fields.insert("links".into(), links);
+        }
+    }
+}
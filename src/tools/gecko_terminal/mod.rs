@@ -1,15 +1,28 @@
+pub mod address;
+pub mod arbitrage;
+pub mod cross_network;
 pub mod helpers;
 pub mod implementation;
+pub mod links;
 pub mod networks;
 pub mod new_pools;
 pub mod pool;
 pub mod search_pools;
+pub mod summary;
 pub mod token;
 pub mod trending_pools;
+pub mod whales;
 
 // Re-export DTOs and handlers for base GeckoTerminal tools
+pub use arbitrage::{
+    find_arbitrage_spreads, ArbitrageTools, FindArbitrageSpreadsInput, FindArbitrageSpreadsOutput,
+};
+pub use cross_network::{
+    find_token_across_networks, CrossNetworkTools, FindTokenAcrossNetworksInput,
+    FindTokenAcrossNetworksOutput, TokenMappingStore,
+};
 pub use implementation::GeckoTerminalTools;
-pub use networks::{get_networks, GetGeckoNetworksInput, GetGeckoNetworksOutput};
+pub use networks::{get_networks, GetGeckoNetworksInput, GetGeckoNetworksOutput, NetworkAliases};
 pub use pool::{get_pool, GetGeckoPoolInput, GetGeckoPoolOutput};
 pub use token::{get_token, GetGeckoTokenInput, GetGeckoTokenOutput};
 // Re-export sub-tool modules for convenience
@@ -18,3 +31,6 @@ pub use search_pools::{search_pools, SearchPoolsInput, SearchPoolsOutput, Search
 pub use trending_pools::{
     get_trending_pools, GetTrendingPoolsInput, GetTrendingPoolsOutput, TrendingPoolsTools,
 };
+pub use whales::{
+    unwatch_whales, watch_whales, UnwatchWhalesInput, WatchWhalesInput, WhaleTools, WhaleWatchStore,
+};
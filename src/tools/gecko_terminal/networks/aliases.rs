@@ -0,0 +1,196 @@
+//! Maps what people call a network ("ethereum", "ETH", "Arbitrum One") to
+//! the GeckoTerminal slug (`eth`, `arbitrum`).
+//!
+//! A built-in table covers common names; slugs, display names and CoinGecko
+//! platform ids from `get_networks` are learned on top of it.
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use crate::error::{NovaError, Result};
+
+/// Common names and tickers for networks whose slug differs from them.
+const BUILTIN: &[(&str, &str)] = &[
+    ("ethereum", "eth"),
+    ("ether", "eth"),
+    ("mainnet", "eth"),
+    ("arbitrum one", "arbitrum"),
+    ("arb", "arbitrum"),
+    ("binance smart chain", "bsc"),
+    ("bnb", "bsc"),
+    ("bnb chain", "bsc"),
+    ("polygon", "polygon_pos"),
+    ("matic", "polygon_pos"),
+    ("avalanche", "avax"),
+    ("fantom", "ftm"),
+    ("sol", "solana"),
+    ("op", "optimism"),
+    ("apt", "aptos"),
+    ("sui", "sui-network"),
+];
+
+/// Suggestions returned with an unknown network.
+const MAX_SUGGESTIONS: usize = 3;
+
+#[derive(Default)]
+struct State {
+    /// Normalized name -> slug.
+    names: HashMap<String, String>,
+    /// Slugs from the last learned network list, in upstream order.
+    slugs: Vec<String>,
+    refreshed_at: Option<Instant>,
+}
+
+pub struct NetworkAliases {
+    state: RwLock<State>,
+    refresh_after: Duration,
+}
+
+impl NetworkAliases {
+    /// `refresh_after` of zero never asks for a refresh.
+    pub fn new(refresh_after: Duration) -> Self {
+        let names = BUILTIN
+            .iter()
+            .map(|(name, slug)| (normalize(name), slug.to_string()))
+            .collect();
+        Self {
+            state: RwLock::new(State {
+                names,
+                ..State::default()
+            }),
+            refresh_after,
+        }
+    }
+
+    /// Replaces the learned networks with a `get_networks` document and
+    /// restarts the refresh interval; an empty list keeps the previous one.
+    pub fn learn(&self, networks: &Value) {
+        let items = networks["data"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let mut names: HashMap<String, String> = BUILTIN
+            .iter()
+            .map(|(name, slug)| (normalize(name), slug.to_string()))
+            .collect();
+        let mut slugs = Vec::new();
+        for item in items {
+            let Some(slug) = item["id"].as_str() else {
+                continue;
+            };
+            let attributes = &item["attributes"];
+            for name in [
+                attributes["name"].as_str(),
+                attributes["coingecko_asset_platform_id"].as_str(),
+            ]
+            .into_iter()
+            .flatten()
+            {
+                names.insert(normalize(name), slug.to_string());
+            }
+            slugs.push(slug.to_string());
+        }
+        if slugs.is_empty() {
+            return;
+        }
+        // Slugs win over names that happen to match another network's slug
+        for slug in &slugs {
+            names.insert(normalize(slug), slug.clone());
+        }
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        state.names = names;
+        state.slugs = slugs;
+        state.refreshed_at = Some(Instant::now());
+    }
+
+    /// Slugs from the last learned list; empty until one is learned.
+    pub fn slugs(&self) -> Vec<String> {
+        self.state
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .slugs
+            .clone()
+    }
+
+    /// True once per refresh interval, for the caller that should refetch the
+    /// list; failed refetches are not retried before the next interval.
+    pub fn claim_refresh(&self) -> bool {
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        let due = match state.refreshed_at {
+            None => true,
+            Some(_) if self.refresh_after.is_zero() => false,
+            Some(at) => at.elapsed() >= self.refresh_after,
+        };
+        if due {
+            state.refreshed_at = Some(Instant::now());
+        }
+        due
+    }
+
+    /// The slug for `network`. Unknown names fail with suggestions once a
+    /// network list has been learned; before that they pass through as given.
+    pub fn resolve(&self, network: &str) -> Result<String> {
+        let key = normalize(network);
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        if let Some(slug) = state.names.get(&key) {
+            return Ok(slug.clone());
+        }
+        if key.is_empty() || state.slugs.is_empty() {
+            return Ok(network.trim().to_string());
+        }
+        Err(NovaError::unknown_network(
+            network.trim(),
+            suggestions(&state.names, &key),
+        ))
+    }
+}
+
+/// Case-, space- and separator-insensitive form: "Arbitrum-One" -> "arbitrum one".
+fn normalize(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Slugs whose names are close to `key`, nearest first.
+fn suggestions(names: &HashMap<String, String>, key: &str) -> Vec<String> {
+    let limit = (key.chars().count() / 3).max(1);
+    let mut close: Vec<(usize, &str)> = names
+        .iter()
+        .filter_map(|(name, slug)| {
+            let distance = if name.starts_with(key) || key.starts_with(name.as_str()) {
+                0
+            } else {
+                edit_distance(name, key)
+            };
+            (distance <= limit).then_some((distance, slug.as_str()))
+        })
+        .collect();
+    close.sort();
+    let mut slugs: Vec<String> = Vec::new();
+    for (_, slug) in close {
+        if !slugs.iter().any(|known| known == slug) {
+            slugs.push(slug.to_string());
+        }
+    }
+    slugs.truncate(MAX_SUGGESTIONS);
+    slugs
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
@@ -1,5 +1,8 @@
+pub mod aliases;
 pub mod dto;
 pub mod handler;
+pub mod summary;
 
+pub use aliases::NetworkAliases;
 pub use dto::{GetGeckoNetworksInput, GetGeckoNetworksOutput};
 pub use handler::get_networks;
@@ -0,0 +1,31 @@
+use serde_json::Value;
+
+use crate::preferences::Markup;
+
+/// Network ids listed in the summary; the rest are counted.
+const SHOWN: usize = 20;
+
+/// Network ids callers can pass as `network`.
+pub fn summarize(output: &Value, markup: Markup) -> String {
+    let networks = output["networks"]["data"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let ids: Vec<&str> = networks
+        .iter()
+        .filter_map(|network| network["id"].as_str())
+        .collect();
+    let mut text = format!(
+        "{} networks: {}",
+        ids.len(),
+        ids.iter()
+            .take(SHOWN)
+            .copied()
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    if ids.len() > SHOWN {
+        text.push_str(&format!(" and {} more", ids.len() - SHOWN));
+    }
+    markup.text(&text)
+}
@@ -1,16 +1,28 @@
 use super::dto::{GetNewPoolsInput, GetNewPoolsOutput};
 use crate::error::{NovaError, Result};
-use crate::tools::gecko_terminal::helpers::build_url;
+use crate::tools::gecko_terminal::helpers::{
+    build_url, default_limiter, get_json, DEFAULT_BASE_URL,
+};
+use crate::tools::gecko_terminal::links;
+use crate::tools::rate_limit::UpstreamRateLimiter;
+use crate::tools::upstream_health::{default_health, UpstreamHealth};
+use std::sync::Arc;
 use std::time::Duration;
 
 #[derive(Clone)]
 pub struct NewPoolsTools {
     http: reqwest::Client,
     base_url: String,
+    limiter: Arc<UpstreamRateLimiter>,
+    health: Arc<UpstreamHealth>,
 }
 
 impl NewPoolsTools {
     pub fn new() -> Self {
+        Self::with_rate_limiter(default_limiter())
+    }
+
+    pub fn with_rate_limiter(limiter: Arc<UpstreamRateLimiter>) -> Self {
         let http = reqwest::Client::builder()
             .timeout(Duration::from_secs(10))
             .user_agent("Nova-MCP/0.1.0")
@@ -19,9 +31,29 @@ impl NewPoolsTools {
                 tracing::error!("Failed to build HTTP client: {}", e);
                 reqwest::Client::new()
             });
-        let base_url = std::env::var("GECKO_TERMINAL_BASE_URL")
-            .unwrap_or_else(|_| "https://api.geckoterminal.com/api/v2".to_string());
-        Self { http, base_url }
+        Self {
+            http,
+            base_url: DEFAULT_BASE_URL.to_string(),
+            limiter,
+            health: default_health(),
+        }
+    }
+
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Replaces the public API root, e.g. with a mirror or a mock.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Records call outcomes in a shared tracker, e.g. the server's.
+    pub fn with_upstream_health(mut self, health: Arc<UpstreamHealth>) -> Self {
+        self.health = health;
+        self
     }
 
     pub async fn get_new_pools(&self, input: GetNewPoolsInput) -> Result<GetNewPoolsOutput> {
@@ -37,17 +69,8 @@ impl NewPoolsTools {
             "?page={}&include=base_token,quote_token,dex",
             page
         ));
-        let pools = self
-            .http
-            .get(&url)
-            .send()
-            .await
-            .map_err(NovaError::NetworkError)?
-            .error_for_status()
-            .map_err(NovaError::NetworkError)?
-            .json::<serde_json::Value>()
-            .await
-            .map_err(NovaError::NetworkError)?;
+        let mut pools = get_json(&self.http, &self.limiter, &self.health, &url).await?;
+        links::enrich(&mut pools);
         Ok(GetNewPoolsOutput { pools })
     }
 }
@@ -1,6 +1,7 @@
 pub mod dto;
 pub mod handler;
 pub mod implementation;
+pub mod summary;
 
 pub use dto::{GetNewPoolsInput, GetNewPoolsOutput};
 pub use handler::get_new_pools;
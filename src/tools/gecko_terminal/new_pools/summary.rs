@@ -0,0 +1,23 @@
+use chrono::DateTime;
+use serde_json::Value;
+
+use crate::preferences::{Localizer, Markup};
+use crate::tools::gecko_terminal::summary::{pool_line, pool_list_with};
+
+/// Newest pools with price, 24h volume and when each was created.
+pub fn summarize(output: &Value, localizer: &Localizer, markup: Markup) -> String {
+    pool_list_with("New pools", &output["pools"], markup, |pool| {
+        let line = pool_line(pool, localizer, markup);
+        match pool["attributes"]["pool_created_at"]
+            .as_str()
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+        {
+            Some(at) => format!(
+                "{} · {}",
+                line,
+                markup.text(&format!("created {}", localizer.timestamp(&at)))
+            ),
+            None => line,
+        }
+    })
+}
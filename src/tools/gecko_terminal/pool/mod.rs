@@ -1,5 +1,6 @@
 pub mod dto;
 pub mod handler;
+pub mod summary;
 
 pub use dto::{GetGeckoPoolInput, GetGeckoPoolOutput};
 pub use handler::get_pool;
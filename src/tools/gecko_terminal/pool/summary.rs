@@ -0,0 +1,38 @@
+use serde_json::Value;
+
+use crate::preferences::{Localizer, Markup};
+use crate::tools::gecko_terminal::links::ResourceKind;
+use crate::tools::gecko_terminal::summary::{amount, page_url, percent};
+
+/// Name, price, 24h volume and change, and liquidity of one pool; with
+/// markup, also its address and page.
+pub fn summarize(output: &Value, localizer: &Localizer, markup: Markup) -> String {
+    let pool = &output["pool"]["data"];
+    let attributes = &pool["attributes"];
+    let mut lines = vec![markup.bold(attributes["name"].as_str().unwrap_or("Unnamed pool"))];
+    for (label, value) in [
+        ("Price", &attributes["base_token_price_usd"]),
+        ("Volume 24h", &attributes["volume_usd"]["h24"]),
+        ("Liquidity", &attributes["reserve_in_usd"]),
+    ] {
+        if let Some(value) = amount(value) {
+            lines.push(markup.text(&format!("{}: {}", label, localizer.money(value))));
+        }
+    }
+    if let Some(change) = amount(&attributes["price_change_percentage"]["h24"]) {
+        lines.push(markup.text(&format!("Change 24h: {}", percent(change, localizer))));
+    }
+    if markup.is_rich() {
+        if let Some(address) = attributes["address"].as_str() {
+            lines.push(format!(
+                "{} {}",
+                markup.text("Address:"),
+                markup.code(address)
+            ));
+        }
+        if let Some(url) = page_url(pool, ResourceKind::Pool) {
+            lines.push(markup.link("View on GeckoTerminal", &url));
+        }
+    }
+    lines.join("\n")
+}
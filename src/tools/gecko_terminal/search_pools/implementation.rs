@@ -1,5 +1,11 @@
 use super::dto::{SearchPoolsInput, SearchPoolsOutput};
 use crate::error::{NovaError, Result};
+use crate::tools::gecko_terminal::address::{looks_like_address, validate_address, AddressKind};
+use crate::tools::gecko_terminal::helpers::{default_limiter, get_json, DEFAULT_BASE_URL};
+use crate::tools::gecko_terminal::links;
+use crate::tools::rate_limit::UpstreamRateLimiter;
+use crate::tools::upstream_health::{default_health, UpstreamHealth};
+use std::sync::Arc;
 use std::time::Duration;
 use urlencoding::encode;
 
@@ -7,10 +13,16 @@ use urlencoding::encode;
 pub struct SearchPoolsTools {
     http: reqwest::Client,
     base_url: String,
+    limiter: Arc<UpstreamRateLimiter>,
+    health: Arc<UpstreamHealth>,
 }
 
 impl SearchPoolsTools {
     pub fn new() -> Self {
+        Self::with_rate_limiter(default_limiter())
+    }
+
+    pub fn with_rate_limiter(limiter: Arc<UpstreamRateLimiter>) -> Self {
         let http = reqwest::Client::builder()
             .timeout(Duration::from_secs(10))
             .user_agent("Nova-MCP/0.1.0")
@@ -19,15 +31,42 @@ impl SearchPoolsTools {
                 tracing::error!("Failed to build HTTP client: {}", e);
                 reqwest::Client::new()
             });
-        let base_url = std::env::var("GECKO_TERMINAL_BASE_URL")
-            .unwrap_or_else(|_| "https://api.geckoterminal.com/api/v2".to_string());
-        Self { http, base_url }
+        Self {
+            http,
+            base_url: DEFAULT_BASE_URL.to_string(),
+            limiter,
+            health: default_health(),
+        }
+    }
+
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Replaces the public API root, e.g. with a mirror or a mock.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Records call outcomes in a shared tracker, e.g. the server's.
+    pub fn with_upstream_health(mut self, health: Arc<UpstreamHealth>) -> Self {
+        self.health = health;
+        self
     }
 
     pub async fn search_pools(&self, input: SearchPoolsInput) -> Result<SearchPoolsOutput> {
-        if input.query.trim().is_empty() {
+        let query = input.query.trim();
+        if query.is_empty() {
             return Err(NovaError::api_error("query is required"));
         }
+        // An address query is checked like a pool lookup's, e.g. base58 on `solana`
+        if let Some(network) = input.network.as_deref() {
+            if looks_like_address(query) {
+                validate_address(network.trim(), query, AddressKind::Pool)?;
+            }
+        }
         let page = input.page.unwrap_or(1);
         if page == 0 || page > 10 {
             return Err(NovaError::api_error("page must be 1..=10"));
@@ -35,7 +74,7 @@ impl SearchPoolsTools {
         let mut url = format!(
             "{}/search/pools?query={}&page={}",
             self.base_url.trim_end_matches('/'),
-            encode(&input.query),
+            encode(query),
             page
         );
         if let Some(network) = input.network {
@@ -44,17 +83,8 @@ impl SearchPoolsTools {
             }
         }
         url.push_str("&include=base_token,quote_token,dex");
-        let pools = self
-            .http
-            .get(&url)
-            .send()
-            .await
-            .map_err(NovaError::NetworkError)?
-            .error_for_status()
-            .map_err(NovaError::NetworkError)?
-            .json::<serde_json::Value>()
-            .await
-            .map_err(NovaError::NetworkError)?;
+        let mut pools = get_json(&self.http, &self.limiter, &self.health, &url).await?;
+        links::enrich(&mut pools);
         Ok(SearchPoolsOutput { pools })
     }
 }
@@ -0,0 +1,127 @@
+//! Compact text renderings of GeckoTerminal results for chat clients with
+//! message length limits. Each tool module formats its own output; the
+//! helpers here cover the JSON:API shapes they share. Text goes through a
+//! [`Markup`], which in the Telegram formats also bolds names, puts
+//! addresses in code and links pools and tokens to their GeckoTerminal page.
+
+use serde_json::Value;
+
+use crate::preferences::{Localizer, Markup};
+
+use super::links::{resource_links, ResourceKind};
+use super::{networks, new_pools, pool, search_pools, token, trending_pools};
+
+/// Pools listed by the list-tool summaries.
+pub const SUMMARY_POOLS: usize = 5;
+
+/// Summary text for a built-in GeckoTerminal tool's output; `None` for other tools.
+pub fn summarize(tool: &str, output: &Value, localizer: &Localizer) -> Option<String> {
+    summarize_as(tool, output, localizer, Markup::Plain)
+}
+
+/// [`summarize`] with `markup` applied.
+pub fn summarize_as(
+    tool: &str,
+    output: &Value,
+    localizer: &Localizer,
+    markup: Markup,
+) -> Option<String> {
+    let text = match tool {
+        "get_gecko_networks" => networks::summary::summarize(output, markup),
+        "get_gecko_token" => token::summary::summarize(output, localizer, markup),
+        "get_gecko_pool" => pool::summary::summarize(output, localizer, markup),
+        "get_trending_pools" => trending_pools::summary::summarize(output, localizer, markup),
+        "search_pools" => search_pools::summary::summarize(output, localizer, markup),
+        "get_new_pools" => new_pools::summary::summarize(output, localizer, markup),
+        _ => return None,
+    };
+    Some(text)
+}
+
+/// The first [`SUMMARY_POOLS`] pools of a JSON:API pool list, one line each.
+pub(crate) fn pool_list(
+    title: &str,
+    document: &Value,
+    localizer: &Localizer,
+    markup: Markup,
+) -> String {
+    pool_list_with(title, document, markup, |pool| {
+        pool_line(pool, localizer, markup)
+    })
+}
+
+/// [`pool_list`] with a custom, already marked up line per pool.
+pub(crate) fn pool_list_with(
+    title: &str,
+    document: &Value,
+    markup: Markup,
+    line: impl Fn(&Value) -> String,
+) -> String {
+    let pools = document["data"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    if pools.is_empty() {
+        return markup.text(&format!("{}: none found", title));
+    }
+    let shown = pools.len().min(SUMMARY_POOLS);
+    let mut lines = vec![markup.text(&if shown < pools.len() {
+        format!("{} (top {} of {}):", title, shown, pools.len())
+    } else {
+        format!("{}:", title)
+    })];
+    for (rank, pool) in pools.iter().take(shown).enumerate() {
+        lines.push(format!(
+            "{} {}",
+            markup.text(&format!("{}.", rank + 1)),
+            line(pool)
+        ));
+    }
+    lines.join("\n")
+}
+
+/// `name — price · vol 24h · 24h change`, skipping absent figures, then the
+/// pool's page when the markup draws links.
+pub(crate) fn pool_line(pool: &Value, localizer: &Localizer, markup: Markup) -> String {
+    let attributes = &pool["attributes"];
+    let mut parts = vec![];
+    if let Some(price) = amount(&attributes["base_token_price_usd"]) {
+        parts.push(markup.text(&localizer.money(price)));
+    }
+    if let Some(volume) = amount(&attributes["volume_usd"]["h24"]) {
+        parts.push(markup.text(&format!("vol 24h {}", localizer.money(volume))));
+    }
+    if let Some(change) = amount(&attributes["price_change_percentage"]["h24"]) {
+        parts.push(markup.text(&format!("24h {}", percent(change, localizer))));
+    }
+    if let Some(url) = page_url(pool, ResourceKind::Pool).filter(|_| markup.is_rich()) {
+        parts.push(markup.link("GeckoTerminal", &url));
+    }
+    let name = markup.bold(attributes["name"].as_str().unwrap_or("Unnamed pool"));
+    if parts.is_empty() {
+        name
+    } else {
+        format!("{} — {}", name, parts.join(" · "))
+    }
+}
+
+/// GeckoTerminal web page of a JSON:API pool or token resource.
+pub(crate) fn page_url(resource: &Value, kind: ResourceKind) -> Option<String> {
+    let links = resource_links(resource, kind)?;
+    links["geckoterminal"].as_str().map(str::to_string)
+}
+
+/// GeckoTerminal sends figures as decimal strings, sometimes as numbers.
+pub(crate) fn amount(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.parse().ok(),
+        _ => None,
+    }
+    .filter(|amount: &f64| amount.is_finite())
+}
+
+pub(crate) fn percent(change: f64, localizer: &Localizer) -> String {
+    let sign = if change > 0.0 { "+" } else { "" };
+    format!("{}{}%", sign, localizer.number(change))
+}
@@ -1,5 +1,6 @@
 pub mod dto;
 pub mod handler;
+pub mod summary;
 
 pub use dto::{GetGeckoTokenInput, GetGeckoTokenOutput};
 pub use handler::get_token;
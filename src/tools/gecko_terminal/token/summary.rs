@@ -0,0 +1,41 @@
+use serde_json::Value;
+
+use crate::preferences::{Localizer, Markup};
+use crate::tools::gecko_terminal::links::ResourceKind;
+use crate::tools::gecko_terminal::summary::{amount, page_url};
+
+/// Name, symbol, price and size figures of one token; with markup, also its
+/// address and page.
+pub fn summarize(output: &Value, localizer: &Localizer, markup: Markup) -> String {
+    let token = &output["token"]["data"];
+    let attributes = &token["attributes"];
+    let name = markup.bold(attributes["name"].as_str().unwrap_or("Unknown token"));
+    let mut lines = vec![match attributes["symbol"].as_str() {
+        Some(symbol) => format!("{} {}", name, markup.text(&format!("({})", symbol))),
+        None => name,
+    }];
+    for (label, value) in [
+        ("Price", &attributes["price_usd"]),
+        ("Market cap", &attributes["market_cap_usd"]),
+        ("FDV", &attributes["fdv_usd"]),
+        ("Volume 24h", &attributes["volume_usd"]["h24"]),
+        ("Liquidity", &attributes["total_reserve_in_usd"]),
+    ] {
+        if let Some(value) = amount(value) {
+            lines.push(markup.text(&format!("{}: {}", label, localizer.money(value))));
+        }
+    }
+    if markup.is_rich() {
+        if let Some(address) = attributes["address"].as_str() {
+            lines.push(format!(
+                "{} {}",
+                markup.text("Address:"),
+                markup.code(address)
+            ));
+        }
+        if let Some(url) = page_url(token, ResourceKind::Token) {
+            lines.push(markup.link("View on GeckoTerminal", &url));
+        }
+    }
+    lines.join("\n")
+}
@@ -1,6 +1,7 @@
 pub mod dto;
 pub mod handler;
 pub mod implementation;
+pub mod summary;
 
 pub use dto::{GetTrendingPoolsInput, GetTrendingPoolsOutput};
 pub use handler::get_trending_pools;
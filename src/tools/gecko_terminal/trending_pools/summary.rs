@@ -0,0 +1,9 @@
+use serde_json::Value;
+
+use crate::preferences::{Localizer, Markup};
+use crate::tools::gecko_terminal::summary::pool_list;
+
+/// Top trending pools with price, 24h volume and 24h change.
+pub fn summarize(output: &Value, localizer: &Localizer, markup: Markup) -> String {
+    pool_list("Trending pools", &output["pools"], localizer, markup)
+}
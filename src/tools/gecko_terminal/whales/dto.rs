@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WatchWhalesInput {
+    pub network: String,
+    /// Pool address on `network`.
+    pub pool: String,
+    /// Trades at least this large, in USD, are recorded and announced.
+    pub min_volume_usd: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WatchWhalesOutput {
+    pub watch: WhaleWatch,
+    /// False when an existing watch on the pool was updated.
+    pub created: bool,
+    /// Trades above the threshold in the last 24 hours, newest first; they
+    /// predate the watch, so they are neither recorded nor announced.
+    pub recent_trades: Vec<WhaleTrade>,
+    /// Trades recorded since the pool was first watched, newest first.
+    pub sightings: Vec<WhaleTrade>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnwatchWhalesInput {
+    pub network: String,
+    pub pool: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnwatchWhalesOutput {
+    /// The removed watch; null when the pool was not watched.
+    pub watch: Option<WhaleWatch>,
+    /// Recorded sightings removed with it.
+    pub sightings: usize,
+}
+
+/// One context's watch on a pool.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WhaleWatch {
+    /// `<network>_<pool>`, GeckoTerminal's pool id.
+    pub id: String,
+    /// `<type>:<id>` of the owning context.
+    pub context: String,
+    pub network: String,
+    pub pool: String,
+    pub min_volume_usd: f64,
+    pub created_at: i64,
+    /// When the poller last checked the pool, successfully or not.
+    pub last_checked_at: Option<i64>,
+    /// Block time of the newest trade seen; older trades are never announced.
+    pub last_trade_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WhaleTrade {
+    /// GeckoTerminal's trade id.
+    pub id: String,
+    pub tx_hash: String,
+    /// "buy" or "sell" of the pool's base token.
+    pub kind: Option<String>,
+    pub volume_usd: f64,
+    pub from_address: Option<String>,
+    pub block_timestamp: i64,
+    pub tx_url: Option<String>,
+}
@@ -0,0 +1,20 @@
+use super::dto::{UnwatchWhalesInput, UnwatchWhalesOutput, WatchWhalesInput, WatchWhalesOutput};
+use super::implementation::WhaleTools;
+use crate::error::Result;
+use crate::plugins::RequestContext;
+
+pub async fn watch_whales(
+    tools: &WhaleTools,
+    context: &RequestContext,
+    input: WatchWhalesInput,
+) -> Result<WatchWhalesOutput> {
+    tools.watch(context, input).await
+}
+
+pub fn unwatch_whales(
+    tools: &WhaleTools,
+    context: &RequestContext,
+    input: UnwatchWhalesInput,
+) -> Result<UnwatchWhalesOutput> {
+    tools.unwatch(context, input)
+}
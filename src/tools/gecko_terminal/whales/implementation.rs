@@ -0,0 +1,326 @@
+use super::dto::{
+    UnwatchWhalesInput, UnwatchWhalesOutput, WatchWhalesInput, WatchWhalesOutput, WhaleTrade,
+    WhaleWatch,
+};
+use super::watches::WhaleWatchStore;
+use crate::clock::SharedClock;
+use crate::config::WhalesConfig;
+use crate::error::{NovaError, Result};
+use crate::events::{EventBus, EventKind};
+use crate::plugins::RequestContext;
+use crate::tools::gecko_terminal::address::{validate_address, AddressKind};
+use crate::tools::gecko_terminal::helpers::{build_url, default_limiter, fetch, DEFAULT_BASE_URL};
+use crate::tools::gecko_terminal::links;
+use crate::tools::gecko_terminal::summary::amount;
+use crate::tools::rate_limit::UpstreamRateLimiter;
+use crate::tools::upstream_health::{default_health, UpstreamHealth};
+use chrono::DateTime;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Trades and sightings returned by `watch_whales`.
+const MAX_LISTED_TRADES: usize = 20;
+
+/// `watch_whales` and `unwatch_whales`, and the poller behind them.
+///
+/// The poller asks GeckoTerminal's trades endpoint for each watched pool's
+/// trades above the smallest threshold watching it, records the ones newer
+/// than the watch has seen and publishes a [`EventKind::WhaleTrade`] for each.
+#[derive(Clone)]
+pub struct WhaleTools {
+    http: reqwest::Client,
+    base_url: String,
+    limiter: Arc<UpstreamRateLimiter>,
+    health: Arc<UpstreamHealth>,
+    watches: Arc<WhaleWatchStore>,
+    events: Option<Arc<EventBus>>,
+    clock: SharedClock,
+    config: WhalesConfig,
+}
+
+impl WhaleTools {
+    pub fn new() -> Self {
+        Self::with_rate_limiter(default_limiter())
+    }
+
+    pub fn with_rate_limiter(limiter: Arc<UpstreamRateLimiter>) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .user_agent("Nova-MCP/0.1.0")
+            .build()
+            .unwrap_or_else(|e| {
+                tracing::error!("Failed to build HTTP client: {}", e);
+                reqwest::Client::new()
+            });
+        Self {
+            http,
+            base_url: DEFAULT_BASE_URL.to_string(),
+            limiter,
+            health: default_health(),
+            watches: Arc::new(WhaleWatchStore::in_memory()),
+            events: None,
+            clock: SharedClock::default(),
+            config: WhalesConfig::default(),
+        }
+    }
+
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Replaces the public API root, e.g. with a mirror or a mock.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Records call outcomes in a shared tracker, e.g. the server's.
+    pub fn with_upstream_health(mut self, health: Arc<UpstreamHealth>) -> Self {
+        self.health = health;
+        self
+    }
+
+    pub fn with_watches(mut self, watches: Arc<WhaleWatchStore>) -> Self {
+        self.watches = watches;
+        self
+    }
+
+    /// Where sightings are published; without a bus they are only recorded.
+    pub fn with_events(mut self, events: Arc<EventBus>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_config(mut self, config: &WhalesConfig) -> Self {
+        self.config = config.clone();
+        self
+    }
+
+    pub fn watches(&self) -> &WhaleWatchStore {
+        &self.watches
+    }
+
+    /// How often to run [`WhaleTools::poll`]; `None` when polling is off.
+    pub fn poll_interval(&self) -> Option<Duration> {
+        Some(Duration::from_secs(self.config.poll_interval_seconds)).filter(|d| !d.is_zero())
+    }
+
+    /// Watches a pool for `context`, or changes the threshold of its watch,
+    /// after checking the pool's recent trades.
+    pub async fn watch(
+        &self,
+        context: &RequestContext,
+        input: WatchWhalesInput,
+    ) -> Result<WatchWhalesOutput> {
+        let network = input.network.trim().to_string();
+        let pool = normalize(input.pool.trim());
+        validate_address(&network, &pool, AddressKind::Pool)?;
+        if !input.min_volume_usd.is_finite() || input.min_volume_usd < self.config.min_volume_usd {
+            return Err(NovaError::validation_error(format!(
+                "min_volume_usd must be at least {}",
+                self.config.min_volume_usd
+            )));
+        }
+        let id = format!("{}_{}", network, pool);
+        let existing = self.watches.get(context, &id)?;
+        if existing.is_none()
+            && self.watches.list_for(context)?.len() >= self.config.max_watches_per_context
+        {
+            return Err(NovaError::validation_error(format!(
+                "A context may watch at most {} pools; unwatch one first",
+                self.config.max_watches_per_context
+            )));
+        }
+
+        let trades = self.trades(&network, &pool, input.min_volume_usd).await?;
+        let now = self.clock.timestamp();
+        let created = existing.is_none();
+        let watch = match existing {
+            Some(watch) => WhaleWatch {
+                min_volume_usd: input.min_volume_usd,
+                ..watch
+            },
+            None => WhaleWatch {
+                id,
+                context: context.key(),
+                network,
+                pool,
+                min_volume_usd: input.min_volume_usd,
+                created_at: now,
+                last_checked_at: Some(now),
+                last_trade_at: trades.first().map_or(now, |trade| trade.block_timestamp),
+            },
+        };
+        self.watches.put(&watch)?;
+        let sightings = self.watches.sightings(&watch, MAX_LISTED_TRADES)?;
+        Ok(WatchWhalesOutput {
+            watch,
+            created,
+            recent_trades: trades.into_iter().take(MAX_LISTED_TRADES).collect(),
+            sightings,
+        })
+    }
+
+    pub fn unwatch(
+        &self,
+        context: &RequestContext,
+        input: UnwatchWhalesInput,
+    ) -> Result<UnwatchWhalesOutput> {
+        let id = format!("{}_{}", input.network.trim(), normalize(input.pool.trim()));
+        let removed = self.watches.remove(context, &id)?;
+        Ok(UnwatchWhalesOutput {
+            sightings: removed.as_ref().map_or(0, |(_, sightings)| *sightings),
+            watch: removed.map(|(watch, _)| watch),
+        })
+    }
+
+    /// Checks the least recently checked pools, at most
+    /// `whales.max_pools_per_poll`, and returns how many trades were announced.
+    /// A pool that fails to load is logged and retried on a later run.
+    pub async fn poll(&self) -> Result<usize> {
+        let mut pools: BTreeMap<String, Vec<WhaleWatch>> = BTreeMap::new();
+        for watch in self.watches.list()? {
+            pools.entry(watch.id.clone()).or_default().push(watch);
+        }
+        let mut pools: Vec<Vec<WhaleWatch>> = pools.into_values().collect();
+        pools.sort_by_key(|watches| watches.iter().map(|w| w.last_checked_at).min().flatten());
+        pools.truncate(self.config.max_pools_per_poll);
+
+        let mut announced = 0;
+        for watches in pools {
+            let (network, pool) = (&watches[0].network, &watches[0].pool);
+            let threshold = watches
+                .iter()
+                .map(|watch| watch.min_volume_usd)
+                .fold(f64::INFINITY, f64::min);
+            let trades = match self.trades(network, pool, threshold).await {
+                Ok(trades) => trades,
+                Err(e) => {
+                    tracing::warn!("Whale watch on {} {} failed: {}", network, pool, e);
+                    Vec::new()
+                }
+            };
+            let now = self.clock.timestamp();
+            for mut watch in watches {
+                for trade in trades.iter().rev() {
+                    if trade.block_timestamp <= watch.last_trade_at
+                        || trade.volume_usd < watch.min_volume_usd
+                    {
+                        continue;
+                    }
+                    if self
+                        .watches
+                        .record(&watch, trade, self.config.max_sightings_per_watch)?
+                    {
+                        self.announce(&watch, trade);
+                        announced += 1;
+                    }
+                }
+                if let Some(newest) = trades.first() {
+                    watch.last_trade_at = watch.last_trade_at.max(newest.block_timestamp);
+                }
+                watch.last_checked_at = Some(now);
+                // Skip watches removed while their pool was loading
+                if self.watches.contains(&watch)? {
+                    self.watches.put(&watch)?;
+                }
+            }
+        }
+        Ok(announced)
+    }
+
+    fn announce(&self, watch: &WhaleWatch, trade: &WhaleTrade) {
+        let Some(events) = &self.events else {
+            return;
+        };
+        events.publish(EventKind::WhaleTrade {
+            watch: watch.id.clone(),
+            context: watch.context.clone(),
+            network: watch.network.clone(),
+            pool: watch.pool.clone(),
+            tx_hash: trade.tx_hash.clone(),
+            trade_kind: trade.kind.clone(),
+            volume_usd: trade.volume_usd,
+            tx_url: trade.tx_url.clone(),
+        });
+    }
+
+    /// The pool's trades of at least `min_volume_usd` in the last 24 hours,
+    /// newest first.
+    async fn trades(
+        &self,
+        network: &str,
+        pool: &str,
+        min_volume_usd: f64,
+    ) -> Result<Vec<WhaleTrade>> {
+        let url = format!(
+            "{}?trade_volume_in_usd_greater_than={}",
+            build_url(
+                &self.base_url,
+                &["networks", network, "pools", pool, "trades"]
+            ),
+            min_volume_usd
+        );
+        let document = match fetch(&self.http, &self.limiter, &self.health, &url).await? {
+            Ok(document) => document,
+            Err(failure) if failure.is_resource_not_found() => {
+                return Err(NovaError::pool_not_found(pool))
+            }
+            Err(failure) if failure.is_invalid_address() => {
+                return Err(NovaError::invalid_address(pool))
+            }
+            Err(failure) => return Err(failure.into_error()),
+        };
+        let mut trades: Vec<WhaleTrade> = document["data"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|trade| parse_trade(network, trade))
+            .filter(|trade| trade.volume_usd >= min_volume_usd)
+            .collect();
+        trades.sort_by_key(|trade| std::cmp::Reverse(trade.block_timestamp));
+        Ok(trades)
+    }
+}
+
+impl Default for WhaleTools {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// EVM addresses are stored lowercase so a watch has one id.
+fn normalize(pool: &str) -> String {
+    if pool.starts_with("0x") {
+        pool.to_ascii_lowercase()
+    } else {
+        pool.to_string()
+    }
+}
+
+fn parse_trade(network: &str, trade: &Value) -> Option<WhaleTrade> {
+    let attributes = &trade["attributes"];
+    let tx_hash = attributes["tx_hash"].as_str()?;
+    let block_timestamp = attributes["block_timestamp"]
+        .as_str()
+        .and_then(|time| DateTime::parse_from_rfc3339(time).ok())?
+        .timestamp();
+    Some(WhaleTrade {
+        id: trade["id"].as_str().unwrap_or(tx_hash).to_string(),
+        tx_hash: tx_hash.to_string(),
+        kind: attributes["kind"].as_str().map(str::to_string),
+        volume_usd: amount(&attributes["volume_in_usd"])?,
+        from_address: attributes["tx_from_address"].as_str().map(str::to_string),
+        block_timestamp,
+        tx_url: links::tx_url(network, tx_hash),
+    })
+}
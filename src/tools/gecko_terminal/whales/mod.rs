@@ -0,0 +1,12 @@
+pub mod dto;
+pub mod handler;
+pub mod implementation;
+pub mod watches;
+
+pub use dto::{
+    UnwatchWhalesInput, UnwatchWhalesOutput, WatchWhalesInput, WatchWhalesOutput, WhaleTrade,
+    WhaleWatch,
+};
+pub use handler::{unwatch_whales, watch_whales};
+pub use implementation::WhaleTools;
+pub use watches::WhaleWatchStore;
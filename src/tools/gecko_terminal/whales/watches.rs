@@ -0,0 +1,156 @@
+use super::dto::{WhaleTrade, WhaleWatch};
+use crate::error::Result;
+use crate::plugins::RequestContext;
+use crate::storage::{KvStore, MemoryKv};
+
+/// Whale watches per context and the large trades seen on them.
+///
+/// Watches are keyed `watch|<type>:<id>|<network>_<pool>` and sightings
+/// `sighting|<type>:<id>|<network>_<pool>|<block time>|<trade id>`, so a
+/// watch's sightings sort oldest first and a trade is recorded once.
+pub struct WhaleWatchStore {
+    store: Box<dyn KvStore>,
+}
+
+impl WhaleWatchStore {
+    pub fn in_memory() -> Self {
+        Self {
+            store: Box::new(MemoryKv::default()),
+        }
+    }
+
+    pub fn persistent(tree: sled::Tree) -> Self {
+        Self {
+            store: Box::new(tree),
+        }
+    }
+
+    pub fn get(&self, context: &RequestContext, id: &str) -> Result<Option<WhaleWatch>> {
+        match self.read(&watch_key(&context.key(), id))? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Whether `watch` is still stored, e.g. not removed meanwhile.
+    pub fn contains(&self, watch: &WhaleWatch) -> Result<bool> {
+        Ok(self.read(&watch_key(&watch.context, &watch.id))?.is_some())
+    }
+
+    pub fn put(&self, watch: &WhaleWatch) -> Result<()> {
+        self.write(
+            &watch_key(&watch.context, &watch.id),
+            serde_json::to_vec(watch)?,
+        )
+    }
+
+    /// Every context's watches.
+    pub fn list(&self) -> Result<Vec<WhaleWatch>> {
+        self.watches("watch|")
+    }
+
+    pub fn list_for(&self, context: &RequestContext) -> Result<Vec<WhaleWatch>> {
+        self.watches(&format!("watch|{}|", context.key()))
+    }
+
+    /// Removes the watch and its sightings; returns the watch and how many
+    /// sightings went with it.
+    pub fn remove(
+        &self,
+        context: &RequestContext,
+        id: &str,
+    ) -> Result<Option<(WhaleWatch, usize)>> {
+        let Some(watch) = self.get(context, id)? else {
+            return Ok(None);
+        };
+        self.delete(&watch_key(&watch.context, &watch.id))?;
+        let sightings = self.scan(&sighting_prefix(&watch))?;
+        for (key, _) in &sightings {
+            self.delete(key)?;
+        }
+        Ok(Some((watch, sightings.len())))
+    }
+
+    /// Drops the context's watches and sightings; returns how many watches went.
+    pub fn remove_context(&self, context: &RequestContext) -> Result<usize> {
+        let owner = context.key();
+        let mut removed = 0;
+        for prefix in [format!("watch|{}|", owner), format!("sighting|{}|", owner)] {
+            for (key, _) in self.scan(&prefix)? {
+                self.delete(&key)?;
+                if key.starts_with("watch|") {
+                    removed += 1;
+                }
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Records `trade` for `watch` unless it already was, keeping the newest
+    /// `keep` sightings; false for a trade recorded before.
+    pub fn record(&self, watch: &WhaleWatch, trade: &WhaleTrade, keep: usize) -> Result<bool> {
+        let prefix = sighting_prefix(watch);
+        let key = format!(
+            "{}{:020}|{}",
+            prefix,
+            trade.block_timestamp.max(0),
+            trade.id
+        );
+        if self.read(&key)?.is_some() {
+            return Ok(false);
+        }
+        self.write(&key, serde_json::to_vec(trade)?)?;
+        let recorded = self.scan(&prefix)?;
+        for (key, _) in recorded.iter().take(recorded.len().saturating_sub(keep)) {
+            self.delete(key)?;
+        }
+        Ok(true)
+    }
+
+    /// The watch's newest `limit` sightings, newest first.
+    pub fn sightings(&self, watch: &WhaleWatch, limit: usize) -> Result<Vec<WhaleTrade>> {
+        self.scan(&sighting_prefix(watch))?
+            .into_iter()
+            .rev()
+            .take(limit)
+            .map(|(_, bytes)| Ok(serde_json::from_slice(&bytes)?))
+            .collect()
+    }
+
+    fn watches(&self, prefix: &str) -> Result<Vec<WhaleWatch>> {
+        self.scan(prefix)?
+            .into_iter()
+            .map(|(_, bytes)| Ok(serde_json::from_slice(&bytes)?))
+            .collect()
+    }
+
+    fn read(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        self.store.get(key.as_bytes())
+    }
+
+    fn write(&self, key: &str, value: Vec<u8>) -> Result<()> {
+        self.store.insert(key.as_bytes(), value).map(drop)
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        self.store.remove(key.as_bytes()).map(drop)
+    }
+
+    fn scan(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        self.store.scan_str(prefix)
+    }
+}
+
+impl Default for WhaleWatchStore {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+fn watch_key(context: &str, id: &str) -> String {
+    format!("watch|{}|{}", context, id)
+}
+
+fn sighting_prefix(watch: &WhaleWatch) -> String {
+    format!("sighting|{}|{}|", watch.context, watch.id)
+}
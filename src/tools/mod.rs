@@ -1,9 +1,29 @@
+pub mod concurrency;
+#[cfg(feature = "dylib-tools")]
+pub mod dylib;
 pub mod gecko_terminal;
+pub mod native;
+pub mod negative_cache;
+pub mod rate_limit;
+pub mod solana;
+pub mod token_unlocks;
+pub mod upstream_health;
 
+pub use concurrency::{CallPriority, ToolConcurrency, ToolSlot, ToolSlots};
 pub use gecko_terminal::{
-    get_networks, get_pool, get_token, GeckoTerminalTools, GetGeckoNetworksInput,
-    GetGeckoNetworksOutput, GetGeckoPoolInput, GetGeckoPoolOutput, GetGeckoTokenInput,
-    GetGeckoTokenOutput,
+    find_arbitrage_spreads, find_token_across_networks, get_networks, get_pool, get_token,
+    ArbitrageTools, CrossNetworkTools, FindArbitrageSpreadsInput, FindArbitrageSpreadsOutput,
+    FindTokenAcrossNetworksInput, FindTokenAcrossNetworksOutput, GeckoTerminalTools,
+    GetGeckoNetworksInput, GetGeckoNetworksOutput, GetGeckoPoolInput, GetGeckoPoolOutput,
+    GetGeckoTokenInput, GetGeckoTokenOutput, TokenMappingStore, UnwatchWhalesInput,
+    WatchWhalesInput, WhaleTools, WhaleWatchStore,
+};
+pub use native::{NativeTools, ToolProvider};
+pub use solana::{
+    get_solana_token_holders, GetSolanaTokenHoldersInput, GetSolanaTokenHoldersOutput, SolanaTools,
+};
+pub use token_unlocks::{
+    get_token_unlocks, GetTokenUnlocksInput, GetTokenUnlocksOutput, TokenUnlockTools,
 };
 // Re-export submodules so existing imports like `tools::new_pools::...` continue to work
 pub use gecko_terminal::new_pools;
@@ -0,0 +1,80 @@
+//! Tools compiled into the host binary by embedders, next to the built-in
+//! ones, without touching the MCP handler.
+
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use serde_json::Value;
+
+use crate::error::{NovaError, Result};
+use crate::mcp::dto::Tool;
+use crate::plugins::RequestContext;
+
+/// A native tool, added with [`NovaServer::with_tool`](crate::NovaServer::with_tool).
+///
+/// `tools/call` validates the arguments against `definition().input_schema`
+/// before `call` runs, and applies quotas, per-tool concurrency, timeouts,
+/// `select` and result formatting as it does for built-in tools.
+pub trait ToolProvider: Send + Sync {
+    /// Name, description and schemas as listed by `tools/list`.
+    fn definition(&self) -> Tool;
+
+    /// Runs the tool for `context`; the returned value becomes the result's
+    /// `structuredContent` and text.
+    fn call<'a>(
+        &'a self,
+        arguments: Value,
+        context: &'a RequestContext,
+    ) -> BoxFuture<'a, Result<Value>>;
+}
+
+/// Native tools by name, in registration order.
+#[derive(Clone, Default)]
+pub struct NativeTools {
+    providers: Vec<(Tool, Arc<dyn ToolProvider>)>,
+}
+
+impl NativeTools {
+    /// Adds `provider`, failing if its name is empty or already taken by
+    /// another native tool or by one of `reserved`.
+    pub fn register(&mut self, provider: Arc<dyn ToolProvider>, reserved: &[&str]) -> Result<()> {
+        let tool = provider.definition();
+        if tool.name.trim().is_empty() {
+            return Err(NovaError::config_error("Native tool names cannot be empty"));
+        }
+        if reserved.contains(&tool.name.as_str()) || self.contains(&tool.name) {
+            return Err(NovaError::config_error(format!(
+                "Tool name {} is already taken",
+                tool.name
+            )));
+        }
+        self.providers.push((tool, provider));
+        Ok(())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn ToolProvider>> {
+        self.providers
+            .iter()
+            .find(|(tool, _)| tool.name == name)
+            .map(|(_, provider)| provider)
+    }
+
+    pub fn definition(&self, name: &str) -> Option<&Tool> {
+        self.providers
+            .iter()
+            .map(|(tool, _)| tool)
+            .find(|tool| tool.name == name)
+    }
+
+    pub fn definitions(&self) -> impl Iterator<Item = &Tool> {
+        self.providers.iter().map(|(tool, _)| tool)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+}
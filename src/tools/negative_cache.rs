@@ -0,0 +1,74 @@
+use crate::clock::SharedClock;
+use crate::error::Result;
+use crate::storage::{KvStore, MemoryKv};
+
+/// Short-lived record of upstream lookups that returned 404, so repeated
+/// requests for nonexistent tokens/pools are answered locally.
+pub struct NegativeCache {
+    ttl_seconds: i64,
+    store: Box<dyn KvStore>,
+    clock: SharedClock,
+}
+
+impl NegativeCache {
+    pub fn in_memory(ttl_seconds: u64) -> Self {
+        Self {
+            ttl_seconds: ttl_seconds as i64,
+            store: Box::new(MemoryKv::default()),
+            clock: SharedClock::default(),
+        }
+    }
+
+    /// Entries survive restarts; expired keys are dropped lazily on lookup.
+    pub fn persistent(tree: sled::Tree, ttl_seconds: u64) -> Self {
+        Self {
+            ttl_seconds: ttl_seconds as i64,
+            store: Box::new(tree),
+            clock: SharedClock::default(),
+        }
+    }
+
+    /// The clock entries expire by.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn contains(&self, key: &str) -> Result<bool> {
+        let now = self.clock.timestamp();
+        let Some(bytes) = self.store.get(key.as_bytes())? else {
+            return Ok(false);
+        };
+        let expires_at = bytes
+            .as_slice()
+            .try_into()
+            .map(i64::from_be_bytes)
+            .unwrap_or(0);
+        if expires_at > now {
+            return Ok(true);
+        }
+        self.store.remove(key.as_bytes())?;
+        Ok(false)
+    }
+
+    pub fn insert(&self, key: &str) -> Result<()> {
+        if self.ttl_seconds <= 0 {
+            return Ok(());
+        }
+        let expires_at = self.clock.timestamp() + self.ttl_seconds;
+        self.store
+            .insert(key.as_bytes(), expires_at.to_be_bytes().to_vec())
+            .map(drop)
+    }
+
+    pub fn key(kind: &str, network: &str, address: &str) -> String {
+        let address = address.trim();
+        // EVM hex addresses are case-insensitive; base58 (e.g. Solana) is not.
+        let address = if address.starts_with("0x") {
+            address.to_lowercase()
+        } else {
+            address.to_string()
+        };
+        format!("{}:{}:{}", kind, network.trim().to_lowercase(), address)
+    }
+}
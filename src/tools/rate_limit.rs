@@ -0,0 +1,121 @@
+use crate::error::{NovaError, Result};
+use crate::mcp::logging::{self, LogLevel};
+use serde_json::json;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Fallback backoff when an upstream answers 429 without a usable `Retry-After`.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Token bucket guarding calls to a single upstream API.
+///
+/// Callers either queue until a token frees up (bounded by `max_wait`) or fail
+/// fast with `NovaError::RateLimitExceeded` carrying a wait hint.
+pub struct UpstreamRateLimiter {
+    api: String,
+    capacity: f64,
+    refill_per_sec: f64,
+    max_wait: Duration,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+    blocked_until: Option<Instant>,
+}
+
+impl UpstreamRateLimiter {
+    pub fn new(api: impl Into<String>, per_minute: u32, max_wait: Duration) -> Self {
+        let capacity = per_minute.max(1) as f64;
+        Self {
+            api: api.into(),
+            capacity,
+            refill_per_sec: capacity / 60.0,
+            max_wait,
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                last_refill: Instant::now(),
+                blocked_until: None,
+            }),
+        }
+    }
+
+    pub fn api(&self) -> &str {
+        &self.api
+    }
+
+    /// Takes one token, waiting up to `max_wait` for it to become available.
+    pub async fn acquire(&self) -> Result<()> {
+        let deadline = Instant::now() + self.max_wait;
+        loop {
+            let wait = {
+                let mut state = self.state.lock().await;
+                let now = Instant::now();
+                self.refill(&mut state, now);
+                match state.blocked_until {
+                    Some(until) if until > now => until - now,
+                    _ if state.tokens >= 1.0 => {
+                        state.tokens -= 1.0;
+                        return Ok(());
+                    }
+                    _ => Duration::from_secs_f64((1.0 - state.tokens) / self.refill_per_sec),
+                }
+            };
+
+            if Instant::now() + wait > deadline {
+                return Err(self.exceeded(wait));
+            }
+            tracing::debug!("Waiting {:?} for {} rate limit", wait, self.api);
+            logging::log(
+                LogLevel::Notice,
+                "upstream",
+                json!({ "message": "Waiting for upstream rate limit", "api": self.api, "waitMs": wait.as_millis() as u64 }),
+            );
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Records an upstream 429 so later callers back off instead of piling on,
+    /// and returns the error to surface to the current caller.
+    pub async fn defer(&self, retry_after: Option<Duration>) -> NovaError {
+        let wait = retry_after.unwrap_or(DEFAULT_RETRY_AFTER);
+        let mut state = self.state.lock().await;
+        let until = Instant::now() + wait;
+        if state.blocked_until.is_none_or(|current| current < until) {
+            state.blocked_until = Some(until);
+        }
+        state.tokens = 0.0;
+        tracing::warn!("{} returned 429; backing off for {:?}", self.api, wait);
+        logging::log(
+            LogLevel::Warning,
+            "upstream",
+            json!({ "message": "Upstream rate limited; backing off", "api": self.api, "retryAfterSecs": wait.as_secs() }),
+        );
+        self.exceeded(wait)
+    }
+
+    fn refill(&self, state: &mut BucketState, now: Instant) {
+        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        state.last_refill = now;
+        if matches!(state.blocked_until, Some(until) if until <= now) {
+            state.blocked_until = None;
+        }
+    }
+
+    fn exceeded(&self, wait: Duration) -> NovaError {
+        NovaError::rate_limit_exceeded(&self.api, Some(wait.as_secs_f64().ceil() as u64))
+    }
+}
+
+/// Parses a `Retry-After` header value given either as delta-seconds or an HTTP date.
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let delta = at.timestamp() - chrono::Utc::now().timestamp();
+    Some(Duration::from_secs(delta.max(0) as u64))
+}
@@ -0,0 +1,248 @@
+use super::token_holders::dto::{
+    GetSolanaTokenHoldersInput, GetSolanaTokenHoldersOutput, TokenHolder,
+};
+use crate::error::{NovaError, Result};
+use crate::tools::gecko_terminal::address::{validate_address, AddressKind};
+use crate::tools::gecko_terminal::links;
+use crate::tools::rate_limit::{parse_retry_after, UpstreamRateLimiter};
+use crate::tools::upstream_health::{default_health, UpstreamHealth};
+use reqwest::{header::RETRY_AFTER, StatusCode};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub const SOLANA_RPC_API: &str = "solana_rpc";
+pub const DEFAULT_RPC_URL: &str = "https://api.mainnet-beta.solana.com";
+/// Well under the public endpoint's per-IP limit.
+pub const DEFAULT_RATE_LIMIT_PER_MINUTE: u32 = 60;
+
+/// `getTokenLargestAccounts` returns at most this many accounts.
+const MAX_HOLDERS: u32 = 20;
+
+#[derive(Clone)]
+pub struct SolanaTools {
+    http: reqwest::Client,
+    rpc_url: String,
+    limiter: Arc<UpstreamRateLimiter>,
+    health: Arc<UpstreamHealth>,
+}
+
+impl SolanaTools {
+    pub fn new() -> Self {
+        Self::with_rate_limiter(Arc::new(UpstreamRateLimiter::new(
+            SOLANA_RPC_API,
+            DEFAULT_RATE_LIMIT_PER_MINUTE,
+            Duration::from_secs(5),
+        )))
+    }
+
+    pub fn with_rate_limiter(limiter: Arc<UpstreamRateLimiter>) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .user_agent("Nova-MCP/0.1.0")
+            .build()
+            .unwrap_or_else(|e| {
+                tracing::error!("Failed to build HTTP client: {}", e);
+                reqwest::Client::new()
+            });
+        Self {
+            http,
+            rpc_url: DEFAULT_RPC_URL.to_string(),
+            limiter,
+            health: default_health(),
+        }
+    }
+
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Overrides `apis.solana_rpc_url`, e.g. to point at a private RPC or a mock.
+    pub fn with_rpc_url(mut self, rpc_url: impl Into<String>) -> Self {
+        self.rpc_url = rpc_url.into();
+        self
+    }
+
+    /// Records call outcomes in a shared tracker, e.g. the server's.
+    pub fn with_upstream_health(mut self, health: Arc<UpstreamHealth>) -> Self {
+        self.health = health;
+        self
+    }
+
+    /// Largest token accounts of a mint, with their owners and share of supply.
+    pub async fn get_token_holders(
+        &self,
+        input: GetSolanaTokenHoldersInput,
+    ) -> Result<GetSolanaTokenHoldersOutput> {
+        let mint = input.address.trim();
+        validate_address("solana", mint, AddressKind::Token)?;
+        let limit = input.limit.unwrap_or(10);
+        if limit == 0 || limit > MAX_HOLDERS {
+            return Err(NovaError::api_error(format!(
+                "limit must be 1..={}",
+                MAX_HOLDERS
+            )));
+        }
+
+        let (supply, largest) = tokio::try_join!(
+            self.mint_call("getTokenSupply", mint),
+            self.mint_call("getTokenLargestAccounts", mint),
+        )?;
+        let decimals = supply["value"]["decimals"].as_u64().unwrap_or_default() as u8;
+        let raw_supply = raw_amount(&supply["value"]);
+        let accounts: Vec<&Value> = largest["value"]
+            .as_array()
+            .map(|accounts| accounts.iter().take(limit as usize).collect())
+            .unwrap_or_default();
+        let addresses: Vec<&str> = accounts
+            .iter()
+            .filter_map(|account| account["address"].as_str())
+            .collect();
+        let owners = if addresses.is_empty() {
+            json!({ "value": [] })
+        } else {
+            self.rpc(
+                "getMultipleAccounts",
+                json!([addresses, { "encoding": "jsonParsed" }]),
+            )
+            .await?
+            .map_err(RpcFailure::into_error)?
+        };
+
+        let mut holders = Vec::with_capacity(accounts.len());
+        for (i, account) in accounts.iter().enumerate() {
+            let token_account = account["address"].as_str().unwrap_or_default().to_string();
+            let owner = owners["value"][i]["data"]["parsed"]["info"]["owner"]
+                .as_str()
+                .map(str::to_string);
+            let explorer = links::address_url("solana", owner.as_deref().unwrap_or(&token_account));
+            holders.push(TokenHolder {
+                rank: i as u32 + 1,
+                owner,
+                token_account,
+                amount: ui_amount(account),
+                percent_of_supply: percent(raw_amount(account), raw_supply),
+                explorer,
+            });
+        }
+        let top_holders_percent = percent(
+            accounts.iter().map(|account| raw_amount(account)).sum(),
+            raw_supply,
+        );
+        Ok(GetSolanaTokenHoldersOutput {
+            mint: mint.to_string(),
+            decimals,
+            supply: ui_amount(&supply["value"]),
+            holders,
+            top_holders_percent,
+        })
+    }
+
+    /// A call taking a mint, where an unknown or non-mint account is
+    /// `token_not_found`.
+    async fn mint_call(&self, method: &str, mint: &str) -> Result<Value> {
+        match self.rpc(method, json!([mint])).await? {
+            Ok(result) => Ok(result),
+            Err(failure) if failure.is_not_a_mint() => Err(NovaError::token_not_found(mint)),
+            Err(failure) => Err(failure.into_error()),
+        }
+    }
+
+    /// One rate-limited JSON-RPC call; RPC-level errors are handed back so
+    /// callers can map them.
+    async fn rpc(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<std::result::Result<Value, RpcFailure>> {
+        self.limiter.acquire().await?;
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let started = Instant::now();
+        let response = match self.http.post(&self.rpc_url).json(&body).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                self.health
+                    .record(self.limiter.api(), started.elapsed(), Some(e.to_string()));
+                return Err(NovaError::NetworkError(e));
+            }
+        };
+        let status = response.status();
+        let failure = (status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error())
+            .then(|| format!("HTTP {}", status.as_u16()));
+        self.health
+            .record(self.limiter.api(), started.elapsed(), failure);
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_retry_after);
+            return Err(self.limiter.defer(retry_after).await);
+        }
+        if !status.is_success() {
+            return Err(NovaError::api_error(format!(
+                "Solana RPC returned {}",
+                status.as_u16()
+            )));
+        }
+        let mut reply: Value = response.json().await.map_err(NovaError::NetworkError)?;
+        if let Some(error) = reply.get("error") {
+            return Ok(Err(RpcFailure {
+                code: error["code"].as_i64().unwrap_or_default(),
+                message: error["message"]
+                    .as_str()
+                    .unwrap_or("unknown error")
+                    .to_string(),
+            }));
+        }
+        Ok(Ok(reply["result"].take()))
+    }
+}
+
+impl Default for SolanaTools {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A JSON-RPC `error` reply.
+#[derive(Debug)]
+struct RpcFailure {
+    code: i64,
+    message: String,
+}
+
+impl RpcFailure {
+    fn is_not_a_mint(&self) -> bool {
+        let message = self.message.to_lowercase();
+        self.code == -32602 && (message.contains("mint") || message.contains("could not find"))
+    }
+
+    fn into_error(self) -> NovaError {
+        NovaError::api_error(format!(
+            "Solana RPC returned {}: {}",
+            self.code, self.message
+        ))
+    }
+}
+
+/// Base-unit `amount` of a token amount object; RPC sends it as a string.
+fn raw_amount(value: &Value) -> u128 {
+    value["amount"]
+        .as_str()
+        .and_then(|amount| amount.parse().ok())
+        .unwrap_or_default()
+}
+
+fn ui_amount(value: &Value) -> String {
+    value["uiAmountString"].as_str().unwrap_or("0").to_string()
+}
+
+/// `part` of `whole` in percent, to four decimals.
+fn percent(part: u128, whole: u128) -> f64 {
+    if whole == 0 {
+        return 0.0;
+    }
+    (part as f64 / whole as f64 * 1_000_000.0).round() / 10_000.0
+}
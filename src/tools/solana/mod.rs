@@ -0,0 +1,11 @@
+//! Tools that read Solana over JSON-RPC, for what GeckoTerminal does not
+//! carry. Pool and token lookups on Solana stay with the GeckoTerminal
+//! tools under the `solana` network slug.
+
+pub mod implementation;
+pub mod token_holders;
+
+pub use implementation::{SolanaTools, SOLANA_RPC_API};
+pub use token_holders::{
+    get_solana_token_holders, GetSolanaTokenHoldersInput, GetSolanaTokenHoldersOutput, TokenHolder,
+};
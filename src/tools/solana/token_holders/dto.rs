@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetSolanaTokenHoldersInput {
+    /// Base58 mint address.
+    pub address: String,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetSolanaTokenHoldersOutput {
+    pub mint: String,
+    pub decimals: u8,
+    /// Total supply in whole tokens, as a decimal string.
+    pub supply: String,
+    /// Largest token accounts first.
+    pub holders: Vec<TokenHolder>,
+    /// Share of supply held by the listed accounts, in percent.
+    pub top_holders_percent: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenHolder {
+    pub rank: u32,
+    /// Wallet or program owning the token account; absent when the account
+    /// could not be read.
+    pub owner: Option<String>,
+    pub token_account: String,
+    /// Balance in whole tokens, as a decimal string.
+    pub amount: String,
+    pub percent_of_supply: f64,
+    /// Block explorer page of the owner, or of the token account without one.
+    pub explorer: Option<String>,
+}
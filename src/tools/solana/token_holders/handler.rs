@@ -0,0 +1,10 @@
+use super::dto::{GetSolanaTokenHoldersInput, GetSolanaTokenHoldersOutput};
+use crate::error::Result;
+use crate::tools::solana::implementation::SolanaTools;
+
+pub async fn get_solana_token_holders(
+    tools: &SolanaTools,
+    input: GetSolanaTokenHoldersInput,
+) -> Result<GetSolanaTokenHoldersOutput> {
+    tools.get_token_holders(input).await
+}
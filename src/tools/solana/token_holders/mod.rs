@@ -0,0 +1,5 @@
+pub mod dto;
+pub mod handler;
+
+pub use dto::{GetSolanaTokenHoldersInput, GetSolanaTokenHoldersOutput, TokenHolder};
+pub use handler::get_solana_token_holders;
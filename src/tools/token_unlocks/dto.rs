@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetTokenUnlocksInput {
+    /// DefiLlama protocol slug, e.g. "arbitrum" or "pyth-network".
+    pub token: String,
+    /// How far ahead to look; 1-365, default 90.
+    pub days: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetTokenUnlocksOutput {
+    pub token: String,
+    pub name: Option<String>,
+    /// Supply the percentages are taken of; absent when the source has none.
+    pub max_supply: Option<f64>,
+    pub days: u32,
+    /// Every unlock at the earliest upcoming time, combined.
+    pub next_unlock: Option<NextUnlock>,
+    /// Upcoming unlocks within `days`, soonest first.
+    pub unlocks: Vec<TokenUnlock>,
+    /// Share of supply unlocked within `days`, including unlocks past `limit`.
+    pub upcoming_percent_of_supply: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NextUnlock {
+    pub timestamp: i64,
+    pub date: String,
+    pub amount: f64,
+    pub percent_of_supply: Option<f64>,
+    /// Allocations unlocking, e.g. "insiders" or "privateSale".
+    pub categories: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenUnlock {
+    pub timestamp: i64,
+    /// `timestamp` as RFC 3339.
+    pub date: String,
+    /// Tokens unlocked, in whole tokens.
+    pub amount: f64,
+    pub percent_of_supply: Option<f64>,
+    pub category: Option<String>,
+    /// "cliff" or "linear".
+    pub kind: Option<String>,
+    /// How long a linear unlock runs from `timestamp`.
+    pub duration_days: Option<f64>,
+    pub description: Option<String>,
+}
@@ -0,0 +1,10 @@
+use super::dto::{GetTokenUnlocksInput, GetTokenUnlocksOutput};
+use super::implementation::TokenUnlockTools;
+use crate::error::Result;
+
+pub async fn get_token_unlocks(
+    tools: &TokenUnlockTools,
+    input: GetTokenUnlocksInput,
+) -> Result<GetTokenUnlocksOutput> {
+    tools.get_unlocks(input).await
+}
@@ -0,0 +1,244 @@
+use super::dto::{GetTokenUnlocksInput, GetTokenUnlocksOutput, NextUnlock, TokenUnlock};
+use crate::clock::SharedClock;
+use crate::error::{NovaError, Result};
+use crate::tools::rate_limit::{parse_retry_after, UpstreamRateLimiter};
+use crate::tools::upstream_health::{default_health, UpstreamHealth};
+use chrono::{DateTime, SecondsFormat};
+use reqwest::{header::RETRY_AFTER, StatusCode};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub const TOKEN_UNLOCKS_API: &str = "token_unlocks";
+pub const DEFAULT_URL: &str = "https://api.llama.fi";
+pub const DEFAULT_RATE_LIMIT_PER_MINUTE: u32 = 30;
+
+const MAX_DAYS: u32 = 365;
+const MAX_UNLOCKS: u32 = 50;
+
+#[derive(Clone)]
+pub struct TokenUnlockTools {
+    http: reqwest::Client,
+    base_url: String,
+    limiter: Arc<UpstreamRateLimiter>,
+    health: Arc<UpstreamHealth>,
+    clock: SharedClock,
+}
+
+impl TokenUnlockTools {
+    pub fn new() -> Self {
+        Self::with_rate_limiter(Arc::new(UpstreamRateLimiter::new(
+            TOKEN_UNLOCKS_API,
+            DEFAULT_RATE_LIMIT_PER_MINUTE,
+            Duration::from_secs(5),
+        )))
+    }
+
+    pub fn with_rate_limiter(limiter: Arc<UpstreamRateLimiter>) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .user_agent("Nova-MCP/0.1.0")
+            .build()
+            .unwrap_or_else(|e| {
+                tracing::error!("Failed to build HTTP client: {}", e);
+                reqwest::Client::new()
+            });
+        Self {
+            http,
+            base_url: DEFAULT_URL.to_string(),
+            limiter,
+            health: default_health(),
+            clock: SharedClock::default(),
+        }
+    }
+
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Overrides `apis.token_unlocks_url`, e.g. to point at DefiLlama's Pro API or a mock.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Records call outcomes in a shared tracker, e.g. the server's.
+    pub fn with_upstream_health(mut self, health: Arc<UpstreamHealth>) -> Self {
+        self.health = health;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Unlocks of the token in the next `days`, with their share of max supply.
+    pub async fn get_unlocks(&self, input: GetTokenUnlocksInput) -> Result<GetTokenUnlocksOutput> {
+        let token = input.token.trim().to_ascii_lowercase();
+        if token.is_empty()
+            || !token
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(NovaError::validation_error(
+                "token must be a DefiLlama protocol slug, e.g. \"arbitrum\"",
+            ));
+        }
+        let days = input.days.unwrap_or(90);
+        if days == 0 || days > MAX_DAYS {
+            return Err(NovaError::validation_error(format!(
+                "days must be 1..={}",
+                MAX_DAYS
+            )));
+        }
+        let limit = input.limit.unwrap_or(10);
+        if limit == 0 || limit > MAX_UNLOCKS {
+            return Err(NovaError::validation_error(format!(
+                "limit must be 1..={}",
+                MAX_UNLOCKS
+            )));
+        }
+
+        let document = self.emission(&token).await?;
+        let max_supply = ["maxSupply", "adjustedSupply"]
+            .iter()
+            .filter_map(|field| document["supplyMetrics"][field].as_f64())
+            .find(|supply| *supply > 0.0);
+        let share = |amount: f64| max_supply.map(|supply| percent(amount, supply));
+
+        let now = self.clock.timestamp();
+        let until = now + i64::from(days) * 86_400;
+        let mut unlocks: Vec<TokenUnlock> = document["metadata"]["events"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(parse_event)
+            .filter(|unlock| (now..until).contains(&unlock.timestamp))
+            .collect();
+        unlocks.sort_by(|a, b| {
+            a.timestamp
+                .cmp(&b.timestamp)
+                .then_with(|| a.category.cmp(&b.category))
+        });
+        for unlock in &mut unlocks {
+            unlock.percent_of_supply = share(unlock.amount);
+        }
+
+        let next_unlock = unlocks.first().map(|first| {
+            let at_once: Vec<&TokenUnlock> = unlocks
+                .iter()
+                .take_while(|unlock| unlock.timestamp == first.timestamp)
+                .collect();
+            let amount = at_once.iter().map(|unlock| unlock.amount).sum();
+            NextUnlock {
+                timestamp: first.timestamp,
+                date: first.date.clone(),
+                amount,
+                percent_of_supply: share(amount),
+                categories: at_once
+                    .iter()
+                    .filter_map(|unlock| unlock.category.clone())
+                    .collect(),
+            }
+        });
+        let upcoming_percent_of_supply = share(unlocks.iter().map(|unlock| unlock.amount).sum());
+        unlocks.truncate(limit as usize);
+        Ok(GetTokenUnlocksOutput {
+            name: document["name"].as_str().map(str::to_string),
+            token,
+            max_supply,
+            days,
+            next_unlock,
+            unlocks,
+            upcoming_percent_of_supply,
+        })
+    }
+
+    /// The protocol's emission document; an unknown slug is `token_not_found`.
+    async fn emission(&self, token: &str) -> Result<Value> {
+        self.limiter.acquire().await?;
+        let url = format!("{}/emission/{}", self.base_url.trim_end_matches('/'), token);
+        let started = Instant::now();
+        let response = match self.http.get(&url).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                self.health
+                    .record(self.limiter.api(), started.elapsed(), Some(e.to_string()));
+                return Err(NovaError::NetworkError(e));
+            }
+        };
+        let status = response.status();
+        let failure = (status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error())
+            .then(|| format!("HTTP {}", status.as_u16()));
+        self.health
+            .record(self.limiter.api(), started.elapsed(), failure);
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_retry_after);
+            return Err(self.limiter.defer(retry_after).await);
+        }
+        if status == StatusCode::NOT_FOUND {
+            return Err(NovaError::token_not_found(token));
+        }
+        if !status.is_success() {
+            return Err(NovaError::api_error(format!(
+                "Token unlocks source returned {}",
+                status.as_u16()
+            )));
+        }
+        let document: Value = response.json().await.map_err(NovaError::NetworkError)?;
+        // DefiLlama sends the document as a JSON string under `body`
+        let document = match document["body"].as_str() {
+            Some(body) => serde_json::from_str(body)?,
+            None => document,
+        };
+        if !document.is_object() {
+            return Err(NovaError::token_not_found(token));
+        }
+        Ok(document)
+    }
+}
+
+impl Default for TokenUnlockTools {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One `metadata.events` entry; `noOfTokens` holds the amounts unlocked,
+/// one per allocation the event covers.
+fn parse_event(event: &Value) -> Option<TokenUnlock> {
+    let timestamp = event["timestamp"]
+        .as_i64()
+        .or_else(|| event["timestamp"].as_f64().map(|secs| secs as i64))?;
+    let amount: f64 = event["noOfTokens"]
+        .as_array()?
+        .iter()
+        .filter_map(Value::as_f64)
+        .sum();
+    if amount <= 0.0 {
+        return None;
+    }
+    let text = |field: &str| event[field].as_str().map(str::to_string);
+    Some(TokenUnlock {
+        timestamp,
+        date: DateTime::from_timestamp(timestamp, 0)?.to_rfc3339_opts(SecondsFormat::Secs, true),
+        amount,
+        percent_of_supply: None,
+        category: text("category"),
+        kind: text("unlockType"),
+        duration_days: event["rateDurationDays"].as_f64(),
+        description: text("description"),
+    })
+}
+
+/// `part` of `whole` in percent, to four decimals.
+fn percent(part: f64, whole: f64) -> f64 {
+    (part / whole * 1_000_000.0).round() / 10_000.0
+}
@@ -0,0 +1,10 @@
+//! Token unlock calendars from DefiLlama's emissions data, for the vesting
+//! schedules GeckoTerminal and the chains themselves do not describe.
+
+pub mod dto;
+pub mod handler;
+pub mod implementation;
+
+pub use dto::{GetTokenUnlocksInput, GetTokenUnlocksOutput, NextUnlock, TokenUnlock};
+pub use handler::get_token_unlocks;
+pub use implementation::{TokenUnlockTools, TOKEN_UNLOCKS_API};
@@ -0,0 +1,247 @@
+use chrono::Utc;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::Duration;
+
+use crate::events::{EventBus, EventKind};
+
+/// Calls kept per upstream for error rates and latency percentiles.
+pub const DEFAULT_WINDOW: usize = 100;
+/// Consecutive failures after which an upstream counts as down.
+const DOWN_AFTER: usize = 3;
+/// Windowed error rate from which an upstream counts as degraded.
+const DEGRADED_ERROR_RATE: f64 = 0.25;
+
+/// Outcome and latency of recent calls to each upstream API, so operators
+/// can tell provider outages from Nova's own failures.
+///
+/// Network errors, timeouts, 429s and 5xx replies count as errors; other
+/// replies (including 404s for unknown tokens) mean the upstream answered.
+///
+/// With an event bus, each error is published as `upstream_error`, and an
+/// upstream turning down fires the `upstream_down` alert.
+pub struct UpstreamHealth {
+    window: usize,
+    upstreams: DashMap<String, Mutex<Window>>,
+    events: RwLock<Option<Arc<EventBus>>>,
+}
+
+#[derive(Default)]
+struct Window {
+    samples: VecDeque<Sample>,
+    total_calls: u64,
+    total_errors: u64,
+    last_success_at: Option<i64>,
+    last_error_at: Option<i64>,
+    last_error: Option<String>,
+}
+
+struct Sample {
+    latency_ms: u64,
+    failed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpstreamState {
+    Healthy,
+    Degraded,
+    Down,
+    /// No calls recorded yet.
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyStats {
+    pub avg_ms: u64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub max_ms: u64,
+}
+
+/// One upstream's health over its last `samples` calls.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpstreamStatus {
+    pub name: String,
+    pub state: UpstreamState,
+    pub samples: usize,
+    pub errors: usize,
+    pub error_rate: f64,
+    pub latency: Option<LatencyStats>,
+    pub total_calls: u64,
+    pub total_errors: u64,
+    pub last_success_at: Option<i64>,
+    pub last_error_at: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+impl UpstreamHealth {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            upstreams: DashMap::new(),
+            events: RwLock::new(None),
+        }
+    }
+
+    /// Publishes errors and outages to `events` from now on.
+    pub fn publish_to(&self, events: Arc<EventBus>) {
+        *self.events.write().unwrap_or_else(|e| e.into_inner()) = Some(events);
+    }
+
+    /// Lists `upstream` as unknown until its first call.
+    pub fn register(&self, upstream: &str) {
+        self.upstreams.entry(upstream.to_string()).or_default();
+    }
+
+    /// Records one call; `error` describes a failure the upstream is to blame for.
+    pub fn record(&self, upstream: &str, latency: Duration, error: Option<String>) {
+        let Some((error, went_down)) = self.update(upstream, latency, error) else {
+            return;
+        };
+        let events = self
+            .events
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        if let Some(events) = events {
+            events.publish(EventKind::UpstreamError {
+                upstream: upstream.to_string(),
+                error: error.clone(),
+            });
+            if went_down {
+                events.publish(EventKind::AlertFired {
+                    alert: "upstream_down".to_string(),
+                    message: format!("{} is down: {}", upstream, error),
+                });
+            }
+        }
+    }
+
+    /// Adds the call to the window; for a failure, returns the error and
+    /// whether it took the upstream down.
+    fn update(
+        &self,
+        upstream: &str,
+        latency: Duration,
+        error: Option<String>,
+    ) -> Option<(String, bool)> {
+        let entry = self.upstreams.entry(upstream.to_string()).or_default();
+        let mut window = entry.lock().unwrap_or_else(|e| e.into_inner());
+        let was_down = status(upstream, &window).state == UpstreamState::Down;
+        if window.samples.len() == self.window {
+            window.samples.pop_front();
+        }
+        window.samples.push_back(Sample {
+            latency_ms: latency.as_millis() as u64,
+            failed: error.is_some(),
+        });
+        window.total_calls += 1;
+        let now = Utc::now().timestamp();
+        match error {
+            Some(error) => {
+                window.total_errors += 1;
+                window.last_error_at = Some(now);
+                window.last_error = Some(error.clone());
+                let down = status(upstream, &window).state == UpstreamState::Down;
+                Some((error, down && !was_down))
+            }
+            None => {
+                window.last_success_at = Some(now);
+                None
+            }
+        }
+    }
+
+    /// `upstream`'s health; `unknown` when no call has been recorded.
+    pub fn status(&self, upstream: &str) -> UpstreamStatus {
+        match self.upstreams.get(upstream) {
+            Some(entry) => {
+                let window = entry.value().lock().unwrap_or_else(|e| e.into_inner());
+                status(upstream, &window)
+            }
+            None => status(upstream, &Window::default()),
+        }
+    }
+
+    /// Every known upstream, by name.
+    pub fn snapshot(&self) -> Vec<UpstreamStatus> {
+        let mut statuses: Vec<UpstreamStatus> = self
+            .upstreams
+            .iter()
+            .map(|entry| {
+                let window = entry.value().lock().unwrap_or_else(|e| e.into_inner());
+                status(entry.key(), &window)
+            })
+            .collect();
+        statuses.sort_by(|a, b| a.name.cmp(&b.name));
+        statuses
+    }
+}
+
+impl Default for UpstreamHealth {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW)
+    }
+}
+
+/// Process-wide tracker shared by tools built with `new()`.
+pub(crate) fn default_health() -> Arc<UpstreamHealth> {
+    static HEALTH: OnceLock<Arc<UpstreamHealth>> = OnceLock::new();
+    Arc::clone(HEALTH.get_or_init(|| Arc::new(UpstreamHealth::default())))
+}
+
+fn status(name: &str, window: &Window) -> UpstreamStatus {
+    let samples = window.samples.len();
+    let errors = window.samples.iter().filter(|s| s.failed).count();
+    let error_rate = if samples == 0 {
+        0.0
+    } else {
+        errors as f64 / samples as f64
+    };
+    let recent_failures = window
+        .samples
+        .iter()
+        .rev()
+        .take_while(|sample| sample.failed)
+        .count();
+    let state = if samples == 0 {
+        UpstreamState::Unknown
+    } else if recent_failures >= DOWN_AFTER.min(samples) {
+        UpstreamState::Down
+    } else if error_rate >= DEGRADED_ERROR_RATE {
+        UpstreamState::Degraded
+    } else {
+        UpstreamState::Healthy
+    };
+    UpstreamStatus {
+        name: name.to_string(),
+        state,
+        samples,
+        errors,
+        error_rate,
+        latency: latency(window),
+        total_calls: window.total_calls,
+        total_errors: window.total_errors,
+        last_success_at: window.last_success_at,
+        last_error_at: window.last_error_at,
+        last_error: window.last_error.clone(),
+    }
+}
+
+fn latency(window: &Window) -> Option<LatencyStats> {
+    let mut sorted: Vec<u64> = window.samples.iter().map(|s| s.latency_ms).collect();
+    if sorted.is_empty() {
+        return None;
+    }
+    sorted.sort_unstable();
+    let percentile = |p: usize| sorted[((sorted.len() * p).div_ceil(100)).max(1) - 1];
+    Some(LatencyStats {
+        avg_ms: sorted.iter().sum::<u64>() / sorted.len() as u64,
+        p50_ms: percentile(50),
+        p95_ms: percentile(95),
+        max_ms: sorted[sorted.len() - 1],
+    })
+}
@@ -0,0 +1,140 @@
+use nova_mcp::mcp::dto::McpRequest;
+use nova_mcp::mcp::handler::handle_session_request;
+use nova_mcp::mcp::session::McpSession;
+use nova_mcp::plugins::{
+    ContextIdFormat, PluginContextType, PluginInvocationPayload, RequestContext,
+};
+use nova_mcp::{NovaConfig, NovaServer, PluginManager};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn actors_only_attach_to_shared_contexts() {
+    let group = context(PluginContextType::Group, "-100");
+    let with_actor = group
+        .clone()
+        .with_actor(Some("42".into()), ContextIdFormat::Numeric)
+        .unwrap();
+    assert_eq!(with_actor.key(), "group:-100");
+    assert_eq!(with_actor.principal(), "group:-100|user:42");
+    assert_eq!(group.principal(), "group:-100");
+
+    assert!(group
+        .clone()
+        .with_actor(Some("alice".into()), ContextIdFormat::Numeric)
+        .is_err());
+    assert!(context(PluginContextType::User, "42")
+        .with_actor(Some("42".into()), ContextIdFormat::Numeric)
+        .is_err());
+}
+
+#[test]
+fn plugin_payload_carries_actor_only_when_known() {
+    let mut payload = PluginInvocationPayload {
+        context_type: PluginContextType::Group,
+        context_id: "-100".into(),
+        actor_id: None,
+        arguments: json!({}),
+        job: None,
+    };
+    assert!(serde_json::to_value(&payload)
+        .unwrap()
+        .get("actor_id")
+        .is_none());
+    payload.actor_id = Some("42".into());
+    assert_eq!(serde_json::to_value(&payload).unwrap()["actor_id"], "42");
+}
+
+#[tokio::test]
+async fn sessions_do_not_keep_the_actor() {
+    let server = test_server(NovaConfig::default());
+    let mut session = McpSession::new();
+    let init = McpRequest {
+        jsonrpc: "2.0".into(),
+        id: Some(json!(1)),
+        method: "initialize".into(),
+        params: Some(json!({})),
+        context_type: Some("group".into()),
+        context_id: Some("-100".into()),
+        actor_id: Some("42".into()),
+    };
+    let response = handle_session_request(&server, &mut session, init, None).await;
+    assert!(response.error.is_none());
+    let stored = session.context().unwrap();
+    assert_eq!(stored.context_id, "-100");
+    assert_eq!(stored.actor_id, None);
+}
+
+#[tokio::test]
+async fn http_rate_limits_group_members_separately() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig::default();
+    config.server.port = port;
+    config.apis.rate_limit_per_minute = 1;
+    let server = test_server(config.clone());
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let client = reqwest::Client::new();
+    let url = format!("http://127.0.0.1:{}/rpc", port);
+    let list = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" });
+    let send = |actor: &'static str| {
+        client
+            .post(&url)
+            .header("x-nova-context-type", "group")
+            .header("x-nova-context-id", "-100")
+            .header("x-nova-actor-id", actor)
+            .json(&list)
+            .send()
+    };
+
+    let mut first = None;
+    for _ in 0..50 {
+        match send("1").await {
+            Ok(resp) => {
+                first = Some(resp.json::<Value>().await.unwrap());
+                break;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(50)).await,
+        }
+    }
+    assert!(first.expect("server did not start")["result"].is_object());
+
+    let limited: Value = send("1").await.unwrap().json().await.unwrap();
+    assert!(limited["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("Rate limit"));
+
+    let other_member: Value = send("2").await.unwrap().json().await.unwrap();
+    assert!(other_member["result"].is_object());
+
+    let invalid: Value = send("bob").await.unwrap().json().await.unwrap();
+    assert!(invalid["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("x-nova-actor-id"));
+}
+
+fn context(context_type: PluginContextType, id: &str) -> RequestContext {
+    RequestContext {
+        context_type,
+        context_id: id.to_string(),
+        actor_id: None,
+    }
+}
+
+fn test_server(config: NovaConfig) -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager"),
+    );
+    NovaServer::new(config, plugin_manager)
+}
@@ -0,0 +1,70 @@
+use nova_mcp::error::NovaError;
+use nova_mcp::tools::gecko_terminal::address::{to_checksum, validate_address, AddressKind};
+use nova_mcp::tools::gecko_terminal::{GeckoTerminalTools, GetGeckoTokenInput};
+
+// Test vectors from EIP-55
+const CHECKSUMMED: &[&str] = &[
+    "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+    "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+    "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+    "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+];
+
+#[test]
+fn evm_addresses_follow_eip55() {
+    for address in CHECKSUMMED {
+        assert_eq!(to_checksum(&address[2..].to_lowercase()), *address);
+        validate_address("eth", address, AddressKind::Token).unwrap();
+        // Single-case addresses carry no checksum
+        validate_address("base", &address.to_lowercase(), AddressKind::Token).unwrap();
+    }
+
+    let mistyped = "0x5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+    match validate_address("eth", mistyped, AddressKind::Token).unwrap_err() {
+        NovaError::InvalidAddress {
+            address,
+            suggestion,
+        } => {
+            assert_eq!(address, mistyped);
+            assert_eq!(suggestion.as_deref(), Some(CHECKSUMMED[0]));
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+
+    for garbage in ["0x123", "5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed", "0xzz"] {
+        assert!(validate_address("bsc", garbage, AddressKind::Token).is_err());
+    }
+    let v4_pool = format!("0x{}", "ab".repeat(32));
+    validate_address("eth", &v4_pool, AddressKind::Pool).unwrap();
+    assert!(validate_address("eth", &v4_pool, AddressKind::Token).is_err());
+}
+
+#[test]
+fn solana_addresses_are_base58_keys() {
+    validate_address(
+        "solana",
+        "So11111111111111111111111111111111111111112",
+        AddressKind::Token,
+    )
+    .unwrap();
+    for garbage in [CHECKSUMMED[0], "So1111", "0OIl"] {
+        assert!(validate_address("solana", garbage, AddressKind::Pool).is_err());
+    }
+    // Networks without a known format are left to GeckoTerminal
+    validate_address("aptos", "0x1::aptos_coin::AptosCoin", AddressKind::Token).unwrap();
+}
+
+#[tokio::test]
+async fn token_lookups_reject_bad_addresses_before_the_request() {
+    let tools = GeckoTerminalTools::new();
+    let err = tools
+        .get_token(GetGeckoTokenInput {
+            network: "eth".to_string(),
+            address: "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d35A".to_string(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), "invalid_address");
+    assert!(err.to_string().contains("did you mean"), "{}", err);
+    assert!(err.details().unwrap()["suggestion"].is_string());
+}
@@ -0,0 +1,62 @@
+use nova_mcp::auth::redact;
+use nova_mcp::config::{AdminConfig, AuthConfig, NovaConfig};
+use nova_mcp::{AdminAuth, ApiKeyAuth};
+
+#[test]
+fn admin_auth_is_disabled_without_tokens() {
+    let auth = AdminAuth::new(&AdminConfig::default());
+    assert!(!auth.is_enabled());
+    assert!(!auth.validate(Some("anything")));
+}
+
+#[test]
+fn admin_tokens_are_separate_from_api_keys() {
+    let admin = AdminAuth::new(&AdminConfig {
+        tokens: vec!["ops-token".into()],
+        ..AdminConfig::default()
+    });
+    let keys = ApiKeyAuth::new(&AuthConfig {
+        enabled: true,
+        allowed_keys: vec!["devkey123".into()],
+        header_name: "x-api-key".into(),
+        ..AuthConfig::default()
+    });
+    assert!(admin.validate(Some("ops-token")));
+    assert!(!admin.validate(Some("devkey123")));
+    assert!(!keys.validate(Some("ops-token")));
+}
+
+#[test]
+fn api_keys_can_be_added_and_revoked() {
+    let auth = ApiKeyAuth::new(&AuthConfig {
+        enabled: true,
+        allowed_keys: vec!["devkey123".into()],
+        header_name: "x-api-key".into(),
+        ..AuthConfig::default()
+    });
+    assert!(auth.add_key("ci", "ci-secret-value"));
+    assert!(!auth.add_key("ci", "other"));
+    assert!(auth.validate(Some("ci-secret-value")));
+
+    let listed = auth.list_keys();
+    assert_eq!(listed.len(), 2);
+    assert!(listed.iter().all(|key| !key.hint.contains("secret")));
+
+    assert!(auth.remove_key("ci"));
+    assert!(!auth.validate(Some("ci-secret-value")));
+    assert!(!auth.remove_key("ci"));
+}
+
+#[test]
+fn redacted_config_hides_secrets() {
+    let mut config = NovaConfig::default();
+    config.auth.allowed_keys = vec!["devkey123456".into()];
+    config.admin.tokens = vec!["ops-token-long".into()];
+    config.apis.coingecko_api_key = Some("cg-secret-key".into());
+
+    let redacted = config.redacted();
+    assert_eq!(redacted.auth.allowed_keys, vec![redact("devkey123456")]);
+    assert_eq!(redacted.admin.tokens, vec![redact("ops-token-long")]);
+    assert_eq!(redacted.apis.coingecko_api_key.as_deref(), Some("cg-s****"));
+    assert_eq!(redact("short"), "****");
+}
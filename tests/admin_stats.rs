@@ -0,0 +1,168 @@
+use nova_mcp::config::{AdminConfig, NovaConfig};
+use nova_mcp::plugins::{
+    PluginContextType, PluginEnableRequest, PluginManager, PluginRegistrationRequest,
+    PluginTrustLevel, PluginUpdateRequest, RequestContext,
+};
+use nova_mcp::NovaServer;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn registry_counts_by_context_trust_and_status() {
+    let manager = PluginManager::in_memory().unwrap();
+    let alice = context(PluginContextType::User, "7");
+    let group = context(PluginContextType::Group, "-42");
+    let echo = manager
+        .register_plugin(&alice, registration("echo", PluginTrustLevel::Standard))
+        .unwrap();
+    let treasury = manager
+        .register_plugin(&group, registration("treasury", PluginTrustLevel::High))
+        .unwrap();
+    let updated = manager
+        .update_plugin(
+            &alice,
+            echo.plugin_id,
+            PluginUpdateRequest {
+                input_schema: Some(json!({ "type": "object", "required": ["q"] })),
+                breaking: true,
+                ..PluginUpdateRequest::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(updated.version, 2);
+
+    manager
+        .set_enablement(enable(echo.plugin_id, &alice, true))
+        .unwrap();
+    manager
+        .set_enablement(enable(
+            echo.plugin_id,
+            &context(PluginContextType::User, "8"),
+            false,
+        ))
+        .unwrap();
+
+    // Group plugins start enabled for their owner
+    manager
+        .set_enablement(enable(treasury.plugin_id, &group, false))
+        .unwrap();
+
+    let stats = manager.stats();
+    assert_eq!(stats.plugins, 2);
+    assert_eq!((stats.versions, stats.archived_versions), (3, 1));
+    assert_eq!(stats.by_context_type["user"], 1);
+    assert_eq!(stats.by_context_type["group"], 1);
+    assert_eq!(stats.by_context_type["channel"], 0);
+    assert_eq!(stats.by_trust_level["standard"], 1);
+    assert_eq!(stats.by_trust_level["high"], 1);
+    assert_eq!(stats.by_status["enabled"], 1);
+    assert_eq!(stats.by_status["not_enabled"], 1);
+    assert_eq!((stats.enabled_records, stats.disabled_records), (1, 2));
+    assert_eq!(stats.user_enablements, 2);
+}
+
+#[tokio::test]
+async fn admin_stats_reports_storage_and_backups() {
+    let backup_dir = std::env::temp_dir().join(format!("nova-stats-{}", std::process::id()));
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig {
+        admin: AdminConfig {
+            tokens: vec!["ops-token".into()],
+            backup_dir: backup_dir.display().to_string(),
+            ..AdminConfig::default()
+        },
+        ..NovaConfig::default()
+    };
+    config.server.port = port;
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(
+            db.open_tree("plugin_metadata").unwrap(),
+            db.open_tree("user_plugins").unwrap(),
+            db.open_tree("group_plugins").unwrap(),
+        )
+        .unwrap(),
+    );
+    let server = NovaServer::new(config.clone(), plugin_manager).with_database(db.clone());
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let base = format!("http://127.0.0.1:{}", port);
+    let client = reqwest::Client::new();
+    let admin_get = || {
+        client
+            .get(format!("{}/admin/stats", base))
+            .header("x-admin-token", "ops-token")
+            .send()
+    };
+    let mut before = None;
+    for _ in 0..50 {
+        if let Ok(response) = admin_get().await {
+            before = Some(response.json::<Value>().await.unwrap());
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let before = before.expect("server did not start");
+    assert_eq!(before["backups"], json!({ "files": 0, "bytes": 0 }));
+    assert!(before["storage"]["size_on_disk_bytes"].as_u64().is_some());
+    assert!(before["storage"]["trees"].as_u64().unwrap() >= 3);
+    assert_eq!(before["registry"]["by_status"]["not_enabled"], 0);
+
+    let backup = client
+        .post(format!("{}/admin/backup", base))
+        .header("x-admin-token", "ops-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(backup.status(), 201);
+    let after: Value = admin_get().await.unwrap().json().await.unwrap();
+    assert_eq!(after["backups"]["files"], 1);
+    assert!(after["backups"]["bytes"].as_u64().unwrap() > 0);
+
+    let _ = std::fs::remove_dir_all(backup_dir);
+}
+
+fn enable(plugin_id: u64, context: &RequestContext, enable: bool) -> PluginEnableRequest {
+    PluginEnableRequest {
+        plugin_id,
+        context_type: context.context_type.clone(),
+        context_id: context.context_id.clone(),
+        enable,
+        added_by: Some("admin".to_string()),
+    }
+}
+
+fn registration(name: &str, trust_level: PluginTrustLevel) -> PluginRegistrationRequest {
+    PluginRegistrationRequest {
+        name: name.to_string(),
+        description: "test".to_string(),
+        owner_id: None,
+        input_schema: json!({ "type": "object" }),
+        output_schema: None,
+        endpoint_url: "https://example.com/hook".to_string(),
+        script: None,
+        version: 1,
+        trust_level,
+        client_certificate: None,
+        credentials: None,
+        redact: Vec::new(),
+        request_template: None,
+        listing: None,
+        allowed_contexts: Vec::new(),
+        tags: Vec::new(),
+        coerce_arguments: false,
+    }
+}
+
+fn context(context_type: PluginContextType, id: &str) -> RequestContext {
+    RequestContext {
+        context_type,
+        context_id: id.to_string(),
+        actor_id: None,
+    }
+}
@@ -0,0 +1,125 @@
+use nova_mcp::auth::KeyIdentity;
+use nova_mcp::config::{AuthConfig, CliArgs, NovaConfig};
+use nova_mcp::{ApiKeyAuth, NovaServer};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+#[test]
+fn authenticate_names_the_matching_key() {
+    let cfg = AuthConfig {
+        enabled: true,
+        allowed_keys: vec!["legacy".into()],
+        named_keys: BTreeMap::from([("telegram-bot".to_string(), "bot-secret".to_string())]),
+        ..AuthConfig::default()
+    };
+    let auth = ApiKeyAuth::new(&cfg);
+    assert_eq!(
+        auth.authenticate(Some("legacy")),
+        Some(KeyIdentity::Key("key-1".into()))
+    );
+    assert_eq!(
+        auth.authenticate(Some("bot-secret")),
+        Some(KeyIdentity::Key("telegram-bot".into()))
+    );
+    assert_eq!(auth.authenticate(Some("nope")), None);
+    assert!(auth
+        .list_keys()
+        .iter()
+        .any(|key| key.id == "telegram-bot" && !key.hint.contains("bot-secret")));
+
+    let open = ApiKeyAuth::new(&AuthConfig::default());
+    assert_eq!(open.authenticate(None), Some(KeyIdentity::Open));
+    assert_eq!(KeyIdentity::Open.rate_key("user:1"), "user:1");
+    assert_eq!(KeyIdentity::Open.to_string(), "-");
+}
+
+#[test]
+fn named_keys_load_from_env() {
+    std::env::set_var("NOVA_MCP_NAMED_API_KEYS", "ci=ci-secret, bot=a=b");
+    let config = NovaConfig::load_with(&CliArgs::default()).unwrap();
+    std::env::remove_var("NOVA_MCP_NAMED_API_KEYS");
+    assert_eq!(config.auth.named_keys["ci"], "ci-secret");
+    assert_eq!(config.auth.named_keys["bot"], "a=b");
+    let redacted = config.redacted();
+    assert_ne!(redacted.auth.named_keys["ci"], "ci-secret");
+}
+
+#[tokio::test]
+async fn plugin_changes_record_the_key_and_keys_have_separate_budgets() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig::default();
+    config.server.port = port;
+    config.admin.tokens = vec!["ops-token".into()];
+    config.auth.enabled = true;
+    config.auth.named_keys = BTreeMap::from([
+        ("ci".to_string(), "ci-secret".to_string()),
+        ("telegram-bot".to_string(), "bot-secret".to_string()),
+    ]);
+    config.apis.rate_limit_per_minute = 1;
+    let server = NovaServer::in_memory(config.clone()).unwrap();
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let client = reqwest::Client::new();
+    let base = format!("http://127.0.0.1:{}", port);
+    let register = |key: &'static str, name: &'static str| {
+        client
+            .post(format!("{}/plugins/register", base))
+            .header("x-api-key", key)
+            .header("x-nova-context-type", "user")
+            .header("x-nova-context-id", "7")
+            .json(&json!({
+                "name": name,
+                "description": "test",
+                "input_schema": { "type": "object" },
+                "endpoint_url": "https://example.com/hook",
+                "version": 1
+            }))
+            .send()
+    };
+
+    let mut first = None;
+    for _ in 0..50 {
+        match register("bot-secret", "echo").await {
+            Ok(resp) => {
+                first = Some(resp);
+                break;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(50)).await,
+        }
+    }
+    assert!(first.expect("server did not start").status().is_success());
+    assert_eq!(
+        register("bot-secret", "echo-2").await.unwrap().status(),
+        429
+    );
+    assert!(register("ci-secret", "echo-3")
+        .await
+        .unwrap()
+        .status()
+        .is_success());
+    assert_eq!(register("wrong", "echo-4").await.unwrap().status(), 401);
+
+    let body: Value = client
+        .get(format!("{}/admin/audit?since=0", base))
+        .header("x-admin-token", "ops-token")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["chain_valid"], true);
+    let keys: Vec<&str> = body["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|entry| entry["action"] == "plugin.register")
+        .map(|entry| entry["api_key"].as_str().unwrap())
+        .collect();
+    assert_eq!(keys, ["telegram-bot", "ci"]);
+}
@@ -0,0 +1,120 @@
+use axum::extract::{Request, State};
+use axum::http::{HeaderValue, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use nova_mcp::{NovaConfig, NovaServer};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+type Trace = Arc<Mutex<Vec<String>>>;
+
+/// Records the request, then turns `x-tenant` into a user context.
+async fn resolve_tenant(State(trace): State<Trace>, mut request: Request, next: Next) -> Response {
+    trace
+        .lock()
+        .unwrap()
+        .push(format!("tenant {}", request.uri().path()));
+    if let Some(tenant) = request.headers().get("x-tenant").cloned() {
+        let headers = request.headers_mut();
+        headers.insert("x-nova-context-type", HeaderValue::from_static("user"));
+        headers.insert("x-nova-context-id", tenant);
+    }
+    next.run(request).await
+}
+
+/// Turns away requests without a tenant, and records Nova's status for the rest.
+async fn require_tenant(State(trace): State<Trace>, request: Request, next: Next) -> Response {
+    if !request.headers().contains_key("x-tenant") {
+        trace.lock().unwrap().push("rejected".to_string());
+        return (StatusCode::FORBIDDEN, "tenant required").into_response();
+    }
+    let response = next.run(request).await;
+    trace
+        .lock()
+        .unwrap()
+        .push(format!("nova {}", response.status().as_u16()));
+    response
+}
+
+#[tokio::test]
+async fn api_layers_run_in_order_before_nova_auth_and_rate_limit() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig::default();
+    config.server.port = port;
+    config.apis.rate_limit_per_minute = 1;
+    let trace: Trace = Arc::default();
+    let server = NovaServer::in_memory(config.clone())
+        .unwrap()
+        .with_api_layer(middleware::from_fn_with_state(
+            trace.clone(),
+            resolve_tenant,
+        ))
+        .with_api_layer(middleware::from_fn_with_state(
+            trace.clone(),
+            require_tenant,
+        ));
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let client = reqwest::Client::new();
+    let base = format!("http://127.0.0.1:{}", port);
+    let mut started = false;
+    for _ in 0..100 {
+        if client.get(format!("{}/healthz", base)).send().await.is_ok() {
+            started = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(started, "server did not start");
+    // Health checks are not wrapped
+    assert!(trace.lock().unwrap().is_empty());
+
+    let list = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" });
+    let rejected = client
+        .post(format!("{}/v1/rpc", base))
+        .json(&list)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(rejected.status(), 403);
+
+    let body: Value = client
+        .post(format!("{}/v1/rpc", base))
+        .header("x-tenant", "42")
+        .json(&list)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(body["result"]["tools"].is_array(), "{}", body);
+
+    // The tenant's one call a minute is used up; the layers see Nova's 429
+    let limited = client
+        .post(format!("{}/rpc", base))
+        .header("x-tenant", "42")
+        .json(&list)
+        .send()
+        .await
+        .unwrap();
+    let body: Value = limited.json().await.unwrap();
+    assert_eq!(body["error"]["code"], 429, "{}", body);
+
+    assert_eq!(
+        *trace.lock().unwrap(),
+        [
+            "tenant /rpc",
+            "rejected",
+            "tenant /rpc",
+            "nova 200",
+            "tenant /rpc",
+            "nova 200"
+        ]
+    );
+}
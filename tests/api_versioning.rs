@@ -0,0 +1,138 @@
+use nova_mcp::plugins::{PluginContextType, RequestContext};
+use nova_mcp::test_util::TestServer;
+use nova_mcp::NovaConfig;
+use reqwest::{Method, StatusCode};
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn legacy_routes_answer_like_v1_and_are_marked_deprecated() {
+    let server = TestServer::start().await.unwrap();
+    let client = server.client(context());
+    let list = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" });
+
+    let current = client
+        .request(Method::POST, "/v1/rpc")
+        .json(&list)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(current.status(), StatusCode::OK);
+    assert!(current.headers().get("deprecation").is_none());
+    assert!(current.headers().get("sunset").is_none());
+    let current: Value = current.json().await.unwrap();
+
+    let legacy = client
+        .request(Method::POST, "/rpc")
+        .json(&list)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(legacy.status(), StatusCode::OK);
+    assert_eq!(legacy.headers()["deprecation"], "true");
+    assert_eq!(legacy.headers()["sunset"], "Thu, 01 Jul 2027 00:00:00 GMT");
+    assert_eq!(
+        legacy.headers()["link"],
+        "</v1/rpc>; rel=\"successor-version\""
+    );
+    let legacy: Value = legacy.json().await.unwrap();
+    assert_eq!(legacy, current);
+
+    // Errors from the aliases are marked too
+    let missing = client
+        .request(Method::DELETE, "/plugins/999")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    assert_eq!(missing.headers()["deprecation"], "true");
+}
+
+#[tokio::test]
+async fn client_uses_v1_and_unversioned_surfaces_stay_current() {
+    let server = TestServer::start().await.unwrap();
+    let client = server.client(context());
+    assert!(client.list_plugins().await.unwrap().is_empty());
+    assert!(!client.tools_list().await.unwrap().is_empty());
+
+    for path in ["/healthz", "/readyz"] {
+        let response = reqwest::get(server.url(path)).await.unwrap();
+        assert!(response.headers().get("deprecation").is_none(), "{}", path);
+    }
+    let mcp = reqwest::Client::new()
+        .post(server.url("/mcp"))
+        .header("accept", "application/json")
+        .json(&json!({
+            "jsonrpc": "2.0", "id": 1, "method": "initialize",
+            "params": { "protocolVersion": "2025-06-18" },
+            "context_type": "user", "context_id": "5"
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(mcp.status(), StatusCode::OK);
+    assert!(mcp.headers().get("deprecation").is_none());
+
+    let unknown = reqwest::get(server.url("/v2/rpc")).await.unwrap();
+    assert_eq!(unknown.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn v1_routes_keep_access_and_body_limits() {
+    let mut config = NovaConfig::default();
+    config.admin.tokens = vec!["ops-token".into()];
+    config.access.admin_allow = vec!["10.0.0.0/8".into()];
+    config.server.legacy_sunset = String::new();
+    config
+        .server
+        .route_body_limits
+        .insert("/rpc".to_string(), 64);
+    let server = TestServer::with_config(config).await.unwrap();
+    let http = reqwest::Client::new();
+
+    for path in ["/admin/stats", "/v1/admin/stats"] {
+        let response = http
+            .get(server.url(path))
+            .header("x-admin-token", "ops-token")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN, "{}", path);
+    }
+
+    let padded = json!({
+        "jsonrpc": "2.0", "id": 1, "method": "ping", "params": { "pad": "x".repeat(128) }
+    });
+    for path in ["/rpc", "/v1/rpc"] {
+        let response = server
+            .client(context())
+            .request(Method::POST, path)
+            .json(&padded)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE, "{}", path);
+        // An empty `server.legacy_sunset` drops only the Sunset header
+        if path == "/rpc" {
+            assert_eq!(response.headers()["deprecation"], "true");
+            assert!(response.headers().get("sunset").is_none());
+        }
+    }
+}
+
+#[test]
+fn legacy_sunset_must_be_a_date() {
+    let mut config = NovaConfig::default();
+    config.server.legacy_sunset = "next summer".to_string();
+    let err = config.validate().unwrap_err();
+    assert!(err.to_string().contains("server.legacy_sunset"), "{}", err);
+    config.server.legacy_sunset = String::new();
+    config.validate().unwrap();
+}
+
+fn context() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
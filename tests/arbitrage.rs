@@ -0,0 +1,256 @@
+// find_arbitrage_spreads over canned results from the tools it composes.
+use futures::future::BoxFuture;
+use nova_mcp::tools::gecko_terminal::arbitrage::QuoteSource;
+use nova_mcp::tools::{ArbitrageTools, FindArbitrageSpreadsInput};
+use nova_mcp::{NovaConfig, NovaError};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const PEPE: &str = "0x6982508145454ce325ddbe47a25d4ec3d2311933";
+const WETH: &str = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+const USDT: &str = "0xdac17f958d2ee523a2206206994597c13d831ec7";
+
+/// A pool pricing PEPE at `price` against WETH, PEPE on the `side` given.
+fn pool(address: &str, reserve: &str, price: &str, side: &str) -> Value {
+    let (base, quote) = if side == "base" {
+        (PEPE, WETH)
+    } else {
+        (WETH, PEPE)
+    };
+    json!({
+        "id": format!("eth_{}", address),
+        "type": "pool",
+        "attributes": {
+            "address": address,
+            "name": "PEPE / WETH",
+            "reserve_in_usd": reserve,
+            format!("{}_token_price_usd", side): price,
+        },
+        "relationships": {
+            "base_token": { "data": { "id": format!("eth_{}", base), "type": "token" } },
+            "quote_token": { "data": { "id": format!("eth_{}", quote), "type": "token" } },
+            "dex": { "data": { "id": "uniswap_v3", "type": "dex" } }
+        },
+        "links": { "geckoterminal": format!("https://www.geckoterminal.com/eth/pools/{}", address) }
+    })
+}
+
+fn search_result() -> Value {
+    json!({
+        "pools": {
+            "data": [
+                pool("0xaaa", "5000000", "0.0000100", "base"),
+                pool("0xbbb", "2000000", "0.0000103", "quote"),
+                pool("0xccc", "900000", "0.0000100", "base"),
+                pool("0xddd", "500", "0.0000200", "base"),
+                // Another token's pool the search happened to return
+                {
+                    "id": "eth_0xeee",
+                    "attributes": { "reserve_in_usd": "9000000", "base_token_price_usd": "1" },
+                    "relationships": {
+                        "base_token": { "data": { "id": format!("eth_{}", USDT) } },
+                        "quote_token": { "data": { "id": format!("eth_{}", WETH) } }
+                    }
+                }
+            ],
+            "included": [
+                { "id": format!("eth_{}", PEPE), "type": "token", "attributes": { "address": PEPE, "symbol": "PEPE" } },
+                { "id": "uniswap_v3", "type": "dex", "attributes": { "name": "Uniswap V3" } }
+            ]
+        }
+    })
+}
+
+type Log<T> = Arc<Mutex<Vec<(String, T)>>>;
+
+/// Answers tool calls from `results` by tool name and records each call.
+#[derive(Clone, Default)]
+struct Calls {
+    results: Log<Result<Value, String>>,
+    made: Log<Value>,
+}
+
+impl Calls {
+    fn answer(self, tool: &str, result: Result<Value, &str>) -> Self {
+        self.results
+            .lock()
+            .unwrap()
+            .push((tool.to_string(), result.map_err(str::to_string)));
+        self
+    }
+
+    fn call(&self) -> impl Fn(String, Value) -> BoxFuture<'static, nova_mcp::Result<Value>> {
+        let calls = self.clone();
+        move |tool, arguments| {
+            calls.made.lock().unwrap().push((tool.clone(), arguments));
+            let result = calls
+                .results
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|(name, _)| *name == tool)
+                .map(|(_, result)| result.clone())
+                .unwrap_or_else(|| Err(format!("no result for {}", tool)));
+            Box::pin(async move { result.map_err(NovaError::api_error) })
+        }
+    }
+
+    fn made(&self) -> Vec<String> {
+        self.made
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(tool, _)| tool.clone())
+            .collect()
+    }
+}
+
+fn input() -> FindArbitrageSpreadsInput {
+    FindArbitrageSpreadsInput {
+        network: "eth".to_string(),
+        address: PEPE.to_string(),
+        threshold_percent: None,
+        max_pools: None,
+        min_reserve_usd: None,
+        across_networks: false,
+        include_cex: None,
+    }
+}
+
+#[tokio::test]
+async fn compares_the_deepest_pools_and_reports_wide_spreads() {
+    let calls = Calls::default().answer("search_pools", Ok(search_result()));
+    let tools = ArbitrageTools::new();
+
+    let output = tools.find_spreads(input(), calls.call()).await.unwrap();
+    assert_eq!(calls.made(), ["search_pools"]);
+    assert_eq!(
+        calls.made.lock().unwrap()[0].1,
+        json!({ "query": PEPE, "network": "eth" })
+    );
+    assert_eq!(output.symbol.as_deref(), Some("PEPE"));
+    assert!(!output.cached);
+    // The dust pool and the other token's pool are left out
+    let venues: Vec<_> = output.quotes.iter().map(|q| q.venue.as_str()).collect();
+    assert_eq!(venues, ["eth:0xaaa", "eth:0xbbb", "eth:0xccc"]);
+    assert_eq!(output.quotes[1].price_usd, 0.0000103);
+    assert_eq!(output.quotes[0].dex.as_deref(), Some("Uniswap V3"));
+    assert!(output.quotes[0].links.is_some());
+
+    assert_eq!(output.spreads.len(), 2);
+    assert_eq!(output.spreads[0].buy, "eth:0xaaa");
+    assert_eq!(output.spreads[0].sell, "eth:0xbbb");
+    assert_eq!(output.spreads[0].spread_percent, 3.0);
+
+    let mut narrow = input();
+    narrow.threshold_percent = Some(5.0);
+    narrow.max_pools = Some(1);
+    let output = tools.find_spreads(narrow, calls.call()).await.unwrap();
+    assert_eq!(calls.made().len(), 1, "served from the cache");
+    assert!(output.cached);
+    assert_eq!(output.quotes.len(), 1);
+    assert!(output.spreads.is_empty());
+}
+
+#[tokio::test]
+async fn adds_other_networks_and_the_cex_price() {
+    let across = json!({
+        "symbol": "PEPE",
+        "mapping": null,
+        "representations": [
+            {
+                "network": "eth", "address": PEPE, "source": "search", "links": {},
+                "best_pool": { "address": "0xaaa", "price_usd": "0.00001", "reserve_in_usd": "5000000", "links": {} }
+            },
+            {
+                "network": "bsc", "address": "0x25d887ce7a35172c62febfd67a1856f20faebb00",
+                "source": "search", "links": {},
+                "best_pool": { "address": "0xbsc", "dex": "pancakeswap", "price_usd": "0.0000098", "reserve_in_usd": "400000", "links": {} }
+            }
+        ]
+    });
+    let calls = Calls::default()
+        .answer("search_pools", Ok(search_result()))
+        .answer("find_token_across_networks", Ok(across))
+        .answer(
+            "cex_prices",
+            Ok(json!({ "symbol": "PEPE", "price": "0.0000105" })),
+        );
+    let tools = ArbitrageTools::new().with_cex_price_tool("cex_prices");
+    let mut input = input();
+    input.across_networks = true;
+    input.threshold_percent = Some(4.0);
+
+    let output = tools.find_spreads(input, calls.call()).await.unwrap();
+    assert_eq!(
+        calls.made(),
+        ["search_pools", "find_token_across_networks", "cex_prices"]
+    );
+    assert_eq!(calls.made.lock().unwrap()[2].1, json!({ "symbol": "PEPE" }));
+    let venues: Vec<_> = output.quotes.iter().map(|q| q.venue.as_str()).collect();
+    assert_eq!(
+        venues,
+        [
+            "eth:0xaaa",
+            "eth:0xbbb",
+            "eth:0xccc",
+            "bsc:0xbsc",
+            "cex:cex_prices"
+        ]
+    );
+    assert_eq!(output.quotes[4].source, QuoteSource::Cex);
+    assert_eq!(output.spreads[0].buy, "bsc:0xbsc");
+    assert_eq!(output.spreads[0].sell, "cex:cex_prices");
+    assert!(output.spreads.iter().all(|s| s.spread_percent >= 4.0));
+}
+
+#[tokio::test]
+async fn a_failing_cex_tool_is_skipped_and_not_cached() {
+    let calls = Calls::default()
+        .answer("search_pools", Ok(search_result()))
+        .answer("cex_prices", Err("exchange down"));
+    let tools = ArbitrageTools::new().with_cex_price_tool("cex_prices");
+
+    let output = tools.find_spreads(input(), calls.call()).await.unwrap();
+    assert_eq!(output.quotes.len(), 3);
+    let output = tools.find_spreads(input(), calls.call()).await.unwrap();
+    assert!(!output.cached);
+    assert_eq!(calls.made().len(), 4);
+
+    let mut dex_only = input();
+    dex_only.include_cex = Some(false);
+    tools.find_spreads(dex_only, calls.call()).await.unwrap();
+    assert_eq!(calls.made().len(), 5);
+
+    let failing = Calls::default().answer("search_pools", Err("upstream down"));
+    let err = tools
+        .find_spreads(input(), failing.call())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("upstream down"), "{}", err);
+}
+
+#[tokio::test]
+async fn rejects_bad_input_before_calling_anything() {
+    let calls = Calls::default().answer("search_pools", Ok(search_result()));
+    let tools = ArbitrageTools::new().with_cache_ttl(Duration::ZERO);
+
+    let mut cex = input();
+    cex.include_cex = Some(true);
+    let mut address = input();
+    address.address = "0x123".to_string();
+    let mut threshold = input();
+    threshold.threshold_percent = Some(-1.0);
+    let mut pools = input();
+    pools.max_pools = Some(0);
+    for bad in [cex, address, threshold, pools] {
+        assert!(tools.find_spreads(bad, calls.call()).await.is_err());
+    }
+    assert!(calls.made().is_empty());
+
+    let mut config = NovaConfig::default();
+    config.apis.cex_price_tool = Some("find_arbitrage_spreads".to_string());
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("apis.cex_price_tool"), "{}", err);
+}
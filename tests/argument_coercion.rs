@@ -0,0 +1,156 @@
+use nova_mcp::plugins::{PluginContextType, PluginRegistrationRequest, RequestContext};
+use nova_mcp::schema::coerce_arguments;
+use nova_mcp::test_util::{StubPlugin, TestServer};
+use nova_mcp::NovaConfig;
+use serde_json::{json, Value};
+
+#[test]
+fn coerces_unambiguous_strings_and_fills_defaults() {
+    let schema = forecast_schema();
+    let mut arguments = json!({
+        "city": "42",
+        "days": " 5 ",
+        "threshold": "0.5",
+        "alerts": "True",
+        "hours": ["6", "18", "noon"],
+        "location": { "lat": "41.1" }
+    });
+    coerce_arguments(&schema, &mut arguments);
+    assert_eq!(
+        arguments,
+        json!({
+            "city": "42",
+            "days": 5,
+            "threshold": 0.5,
+            "alerts": true,
+            "hours": [6, 18, "noon"],
+            "location": { "lat": 41.1, "lon": 0 },
+            "units": "metric"
+        })
+    );
+
+    // Strings that parse as nothing the property accepts are left for
+    // validation to reject
+    let mut arguments =
+        json!({ "city": "Porto", "days": "2.5", "alerts": "yes", "threshold": "inf" });
+    coerce_arguments(&schema, &mut arguments);
+    assert_eq!(arguments["days"], "2.5");
+    assert_eq!(arguments["alerts"], "yes");
+    assert_eq!(arguments["threshold"], "inf");
+
+    let mut missing = Value::Null;
+    coerce_arguments(&schema, &mut missing);
+    assert_eq!(missing, json!({ "units": "metric" }));
+}
+
+#[tokio::test]
+async fn plugins_opt_in_to_coercion() {
+    let stub = StubPlugin::start().await.unwrap();
+    let server = TestServer::start().await.unwrap();
+    let client = server.client(owner());
+
+    let mut request = registration(&stub.url("/invoke"));
+    let strict = client.register(&request).await.unwrap();
+    assert!(!strict.coerce_arguments);
+    assert!(client
+        .tools_call(&strict.fq_name, json!({ "city": "Porto", "days": "3" }))
+        .await
+        .is_err());
+
+    request.name = "lenient_forecast".to_string();
+    request.coerce_arguments = true;
+    let lenient = client.register(&request).await.unwrap();
+    assert!(lenient.coerce_arguments);
+    let result = client
+        .tools_call(&lenient.fq_name, json!({ "city": "Porto", "days": "3" }))
+        .await
+        .unwrap();
+    assert_eq!(result["isError"], false);
+    let sent = stub.calls().pop().unwrap().body;
+    assert_eq!(sent["arguments"]["days"], 3);
+    assert_eq!(sent["arguments"]["units"], "metric");
+}
+
+#[tokio::test]
+async fn built_in_tools_are_coerced_when_configured() {
+    let mut config = NovaConfig::default();
+    config.tools.coerce_arguments = vec!["get_trending_pools".to_string()];
+    let server = TestServer::with_config(config).await.unwrap();
+    let client = server.client(owner());
+
+    // Coerced to 50 and then rejected for the range, not the type
+    let arguments = json!({ "network": "eth", "limit": "50" });
+    let error = client
+        .rpc(
+            "tools/call",
+            json!({ "name": "get_trending_pools", "arguments": arguments }),
+        )
+        .await
+        .unwrap()
+        .error
+        .unwrap();
+    let message = error.data.unwrap()["details"]["errors"][0]["message"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert!(message.contains("maximum"), "{}", message);
+
+    let error = client
+        .rpc(
+            "tools/call",
+            json!({ "name": "search_pools", "arguments": { "query": "weth", "page": "2" } }),
+        )
+        .await
+        .unwrap()
+        .error
+        .unwrap();
+    let message = error.data.unwrap()["details"]["errors"][0]["message"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert!(message.contains("is not of type"), "{}", message);
+
+    let mut config = NovaConfig::default();
+    config.tools.coerce_arguments = vec!["get_everything".to_string()];
+    assert!(config.validate().is_err());
+}
+
+fn forecast_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["city"],
+        "properties": {
+            "city": { "type": "string" },
+            "days": { "type": "integer", "minimum": 1 },
+            "threshold": { "type": "number" },
+            "alerts": { "type": "boolean" },
+            "hours": { "type": "array", "items": { "type": "integer" } },
+            "units": { "type": "string", "default": "metric" },
+            "location": {
+                "type": "object",
+                "properties": {
+                    "lat": { "type": "number" },
+                    "lon": { "type": "number", "default": 0 }
+                }
+            }
+        }
+    })
+}
+
+fn registration(endpoint_url: &str) -> PluginRegistrationRequest {
+    serde_json::from_value(json!({
+        "name": "forecast",
+        "description": "Weather forecast",
+        "input_schema": forecast_schema(),
+        "endpoint_url": endpoint_url
+    }))
+    .unwrap()
+}
+
+fn owner() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
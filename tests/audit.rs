@@ -0,0 +1,129 @@
+use nova_mcp::audit::{AuditEntry, AuditEvent, AuditLog, GENESIS_HASH};
+use nova_mcp::clock::{ManualClock, SharedClock};
+use nova_mcp::{NovaConfig, NovaServer, PluginManager};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn event(action: &'static str, target: &str) -> AuditEvent {
+    AuditEvent {
+        who: "admin:ops-****".to_string(),
+        api_key: None,
+        action,
+        target: target.to_string(),
+        before: Some(json!(60)),
+        after: Some(json!(120)),
+    }
+}
+
+#[test]
+fn entries_chain_and_resume_after_reopen() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let clock = ManualClock::at(1_700_000_000);
+    let log = AuditLog::persistent(db.open_tree("audit_log").unwrap())
+        .unwrap()
+        .with_clock(SharedClock::new(clock.clone()));
+    let first = log.record(event("admin.policies.update", "a")).unwrap();
+    clock.advance(Duration::from_secs(60));
+    let second = log.record(event("admin.policies.update", "b")).unwrap();
+    assert_eq!((first.at, second.at), (1_700_000_000, 1_700_000_060));
+    assert_eq!(first.prev_hash, GENESIS_HASH);
+    assert_eq!(second.prev_hash, first.hash);
+    drop(log);
+
+    let reopened = AuditLog::persistent(db.open_tree("audit_log").unwrap()).unwrap();
+    let third = reopened.record(event("admin.key.delete", "ci")).unwrap();
+    assert_eq!(third.seq, 2);
+    assert_eq!(third.prev_hash, second.hash);
+    assert_eq!(reopened.verify().unwrap(), None);
+    assert_eq!(reopened.since(0, 2).unwrap().len(), 2);
+    assert_eq!(reopened.since(1_700_000_060, 10).unwrap()[0].seq, 1);
+    assert!(reopened.since(i64::MAX, 10).unwrap().is_empty());
+}
+
+#[test]
+fn tampering_breaks_the_chain() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let tree = db.open_tree("audit_log").unwrap();
+    let log = AuditLog::persistent(tree.clone()).unwrap();
+    log.record(event("plugin.update", "1")).unwrap();
+    log.record(event("plugin.update", "2")).unwrap();
+
+    let key = 0u64.to_be_bytes();
+    let mut entry: AuditEntry = serde_json::from_slice(&tree.get(key).unwrap().unwrap()).unwrap();
+    entry.who = "someone-else".to_string();
+    tree.insert(key, serde_json::to_vec(&entry).unwrap())
+        .unwrap();
+    assert_eq!(log.verify().unwrap(), Some(0));
+
+    tree.remove(key).unwrap();
+    assert_eq!(log.verify().unwrap(), Some(1));
+}
+
+#[tokio::test]
+async fn admin_changes_are_queryable() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig::default();
+    config.server.port = port;
+    config.admin.tokens = vec!["ops-token-long".into()];
+    let server = test_server(config.clone());
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let client = reqwest::Client::new();
+    let base = format!("http://127.0.0.1:{}", port);
+    let mut updated = None;
+    for _ in 0..50 {
+        match client
+            .put(format!("{}/admin/policies", base))
+            .header("x-admin-token", "ops-token-long")
+            .json(&json!({ "rate_limit_per_minute": 5 }))
+            .send()
+            .await
+        {
+            Ok(resp) => {
+                updated = Some(resp);
+                break;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(50)).await,
+        }
+    }
+    assert!(updated.expect("server did not start").status().is_success());
+
+    let body: Value = client
+        .get(format!("{}/admin/audit?since=0", base))
+        .header("x-admin-token", "ops-token-long")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["chain_valid"], true);
+    let entry = &body["entries"][0];
+    assert_eq!(entry["action"], "admin.policies.update");
+    assert_eq!(entry["who"], "admin:ops-****");
+    assert_eq!(entry["before"], 60);
+    assert_eq!(entry["after"], 5);
+
+    let unauthorized = client
+        .get(format!("{}/admin/audit", base))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(unauthorized.status(), 401);
+}
+
+fn test_server(config: NovaConfig) -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager"),
+    );
+    NovaServer::new(config, plugin_manager)
+}
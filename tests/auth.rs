@@ -6,6 +6,7 @@ fn disabled_auth_allows_any() {
         enabled: false,
         allowed_keys: vec!["a".into()],
         header_name: "x".into(),
+        ..AuthConfig::default()
     };
     let auth = ApiKeyAuth::new(&cfg);
     assert!(auth.validate(None));
@@ -18,6 +19,7 @@ fn enabled_auth_checks_keys() {
         enabled: true,
         allowed_keys: vec!["secret".into()],
         header_name: "x".into(),
+        ..AuthConfig::default()
     };
     let auth = ApiKeyAuth::new(&cfg);
     assert!(auth.validate(Some("secret")));
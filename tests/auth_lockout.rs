@@ -0,0 +1,247 @@
+use nova_mcp::auth::{AuthLockout, MAX_LOCKOUT_SOURCES};
+use nova_mcp::clock::{ManualClock, SharedClock};
+use nova_mcp::config::{AuthConfig, NovaConfig};
+use nova_mcp::{NovaServer, PluginManager};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn lockouts_start_at_the_threshold_and_double() {
+    let cfg = AuthConfig {
+        lockout_threshold: 2,
+        lockout_base_secs: 10,
+        lockout_max_secs: 25,
+        ..AuthConfig::default()
+    };
+    let clock = ManualClock::at(1_700_000_000);
+    let lockout = AuthLockout::new().with_clock(SharedClock::new(clock.clone()));
+    let sources = vec!["ip:192.0.2.7".to_string(), "key:abcd****".to_string()];
+    let other = vec!["ip:192.0.2.8".to_string()];
+
+    assert_eq!(lockout.record_failure(&sources, &cfg), None);
+    assert_eq!(lockout.retry_after(&sources), None);
+    assert_eq!(
+        lockout.record_failure(&sources, &cfg),
+        Some(Duration::from_secs(10))
+    );
+    clock.advance(Duration::from_secs(4));
+    assert_eq!(lockout.retry_after(&sources), Some(Duration::from_secs(6)));
+    assert_eq!(lockout.retry_after(&other), None);
+    assert_eq!(
+        lockout.record_failure(&sources, &cfg),
+        Some(Duration::from_secs(20))
+    );
+    // Capped at lockout_max_secs
+    assert_eq!(
+        lockout.record_failure(&sources, &cfg),
+        Some(Duration::from_secs(25))
+    );
+
+    let stats = lockout.stats();
+    assert_eq!(stats.failures_total, 4);
+    assert_eq!(stats.lockouts_total, 6);
+    assert_eq!(stats.locked_sources, 2);
+    clock.advance(Duration::from_secs(25));
+    assert_eq!(lockout.retry_after(&sources), None);
+    assert_eq!(lockout.stats().locked_sources, 0);
+
+    lockout.record_success(&sources);
+    assert_eq!(lockout.retry_after(&sources), None);
+
+    let disabled = AuthConfig {
+        lockout_threshold: 0,
+        ..AuthConfig::default()
+    };
+    for _ in 0..10 {
+        assert_eq!(lockout.record_failure(&other, &disabled), None);
+    }
+}
+
+#[test]
+fn lockout_settings_are_validated() {
+    let mut config = NovaConfig::default();
+    config.auth.lockout_max_secs = 10;
+    config.auth.lockout_base_secs = 30;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("auth.lockout_max_secs"));
+
+    config.auth.lockout_threshold = 0;
+    assert!(config.validate().is_ok());
+}
+
+#[tokio::test]
+async fn repeated_wrong_keys_lock_the_client_out() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig::default();
+    config.server.port = port;
+    config.admin.tokens = vec!["ops-token".into()];
+    config.auth.enabled = true;
+    config.auth.allowed_keys = vec!["right-key-123".into()];
+    config.auth.lockout_threshold = 2;
+    config.auth.lockout_base_secs = 1;
+    config.auth.lockout_max_secs = 60;
+    let clock = ManualClock::at(1_700_000_000);
+    let server = NovaServer::new(
+        config.clone(),
+        Arc::new(
+            PluginManager::in_memory()
+                .unwrap()
+                .with_clock(SharedClock::new(clock.clone())),
+        ),
+    );
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let client = reqwest::Client::new();
+    let base = format!("http://127.0.0.1:{}", port);
+    let call = |key: &'static str| {
+        client
+            .post(format!("{}/rpc", base))
+            .header("x-api-key", key)
+            .header("x-nova-context-type", "user")
+            .header("x-nova-context-id", "7")
+            .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }))
+            .send()
+    };
+
+    let mut first = None;
+    for _ in 0..50 {
+        match call("first-guess").await {
+            Ok(resp) => {
+                first = Some(resp);
+                break;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(50)).await,
+        }
+    }
+    let first: Value = first.expect("server did not start").json().await.unwrap();
+    assert_eq!(first["error"]["message"], "Unauthorized");
+    call("other-guess").await.unwrap();
+
+    // Even the right key is refused from a locked-out address
+    let locked = call("right-key-123").await.unwrap();
+    assert_eq!(locked.status(), 429);
+    assert_eq!(locked.headers()["retry-after"], "1");
+
+    let stats: Value = client
+        .get(format!("{}/admin/stats", base))
+        .header("x-admin-token", "ops-token")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(stats["auth_failures"]["failures_total"], 2);
+    assert_eq!(stats["auth_failures"]["locked_sources"], 1);
+
+    clock.advance(Duration::from_secs(1));
+    let ok: Value = call("right-key-123").await.unwrap().json().await.unwrap();
+    assert!(ok["result"]["tools"].is_array());
+}
+
+#[tokio::test]
+async fn wrong_keys_sharing_a_prefix_never_lock_out_the_right_key() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig::default();
+    config.server.port = port;
+    config.auth.enabled = true;
+    config.auth.allowed_keys = vec!["right-key-123".into(), "short1".into()];
+    config.auth.lockout_threshold = 2;
+    config.auth.lockout_base_secs = 60;
+    config.auth.lockout_max_secs = 600;
+    // Lets each guess come from its own address
+    config.access.trusted_proxies = vec!["127.0.0.1".into()];
+    tokio::spawn(nova_mcp::http::run_http_server(
+        NovaServer::new(
+            config.clone(),
+            Arc::new(PluginManager::in_memory().unwrap()),
+        ),
+        config,
+    ));
+
+    let client = reqwest::Client::new();
+    let call = |key: &str, client_ip: String| {
+        client
+            .post(format!("http://127.0.0.1:{}/rpc", port))
+            .header("x-api-key", key)
+            .header("x-forwarded-for", client_ip)
+            .header("x-nova-context-type", "user")
+            .header("x-nova-context-id", "7")
+            .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }))
+            .send()
+    };
+    for _ in 0..50 {
+        if call("warm-up", "192.0.2.200".into()).await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    for i in 0..10 {
+        for guess in ["right-key-999", "short2"] {
+            let resp = call(guess, format!("192.0.2.{}", i)).await.unwrap();
+            // Unauthorized at first, then locked out as that key
+            if resp.status() != 429 {
+                let body: Value = resp.json().await.unwrap();
+                assert_eq!(body["error"]["message"], "Unauthorized");
+            }
+        }
+    }
+    let locked = call("right-key-999", "192.0.2.100".into()).await.unwrap();
+    assert_eq!(locked.status(), 429);
+
+    for key in ["right-key-123", "short1"] {
+        let ok: Value = call(key, "192.0.2.101".into())
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert!(ok["result"]["tools"].is_array(), "{}: {}", key, ok);
+    }
+}
+
+#[test]
+fn aged_out_sources_are_pruned_once_a_minute_and_capped() {
+    let cfg = AuthConfig {
+        lockout_threshold: 3,
+        lockout_base_secs: 10,
+        lockout_max_secs: 30,
+        ..AuthConfig::default()
+    };
+    let clock = ManualClock::at(1_700_000_000);
+    let lockout = AuthLockout::new().with_clock(SharedClock::new(clock.clone()));
+    let guess = |n: usize| vec![format!("ip:192.0.2.{}", n % 200), format!("key:{:024x}", n)];
+
+    for n in 0..100 {
+        lockout.record_failure(&guess(n), &cfg);
+    }
+    assert_eq!(lockout.tracked_sources(), 200);
+
+    // Aged out, but the last prune was under a minute ago
+    clock.advance(Duration::from_secs(31));
+    lockout.record_failure(&guess(100), &cfg);
+    assert_eq!(lockout.tracked_sources(), 202);
+    clock.advance(Duration::from_secs(29));
+    lockout.record_failure(&guess(101), &cfg);
+    assert_eq!(lockout.tracked_sources(), 4);
+
+    // Past the cap new sources go uncounted, known ones still count
+    for n in 0..MAX_LOCKOUT_SOURCES {
+        lockout.record_failure(&[format!("key:{:024x}", n + 1_000)], &cfg);
+    }
+    assert_eq!(lockout.tracked_sources(), MAX_LOCKOUT_SOURCES);
+    let known = guess(101);
+    lockout.record_failure(&known, &cfg);
+    assert!(lockout.record_failure(&known, &cfg).is_some());
+    assert_eq!(lockout.tracked_sources(), MAX_LOCKOUT_SOURCES);
+}
@@ -0,0 +1,63 @@
+use nova_mcp::{NovaConfig, NovaServer};
+use std::time::Duration;
+
+#[test]
+fn bind_address_is_validated() {
+    let mut config = NovaConfig::default();
+    assert_eq!(config.server.bind_address, "0.0.0.0");
+    for address in ["::", "[::1]", "127.0.0.1", "::ffff:127.0.0.1"] {
+        config.server.bind_address = address.to_string();
+        config.validate().unwrap();
+    }
+    for address in ["", "localhost", "0.0.0.0:8080", "[::1]:8080", "256.0.0.1"] {
+        config.server.bind_address = address.to_string();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("server.bind_address"), "{}", err);
+    }
+}
+
+#[tokio::test]
+async fn ipv6_wildcard_also_accepts_ipv4_clients() {
+    let port = start("::").await;
+    assert_eq!(
+        health(&format!("http://127.0.0.1:{}", port)).await,
+        Some(200)
+    );
+    assert_eq!(health(&format!("http://[::1]:{}", port)).await, Some(200));
+}
+
+#[tokio::test]
+async fn loopback_binding_is_not_reachable_over_other_families() {
+    let port = start("::1").await;
+    assert_eq!(health(&format!("http://[::1]:{}", port)).await, Some(200));
+    assert_eq!(health(&format!("http://127.0.0.1:{}", port)).await, None);
+}
+
+/// Serves on a free port of `bind_address` and waits until it answers.
+async fn start(bind_address: &str) -> u16 {
+    let port = std::net::TcpListener::bind("[::]:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig::default();
+    config.server.port = port;
+    config.server.bind_address = bind_address.to_string();
+    let server = NovaServer::in_memory(config.clone()).unwrap();
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+    let probe = format!("http://[::1]:{}", port);
+    for _ in 0..100 {
+        if health(&probe).await.is_some() {
+            return port;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("server did not start on {}", bind_address);
+}
+
+async fn health(base_url: &str) -> Option<u16> {
+    reqwest::get(format!("{}/healthz", base_url))
+        .await
+        .ok()
+        .map(|response| response.status().as_u16())
+}
@@ -0,0 +1,166 @@
+use nova_mcp::mcp::{dto::McpRequest, handler};
+use nova_mcp::plugins::{
+    PluginContextType, PluginManager, PluginRegistrationRequest, RequestContext,
+};
+use nova_mcp::{NovaConfig, NovaServer};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+fn complete(reference: Value, argument: &str, value: &str) -> McpRequest {
+    McpRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: "completion/complete".to_string(),
+        params: Some(json!({
+            "ref": reference,
+            "argument": { "name": argument, "value": value }
+        })),
+        context_type: Some("user".to_string()),
+        context_id: Some("7".to_string()),
+        actor_id: None,
+    }
+}
+
+async fn values(server: &NovaServer, tool: &str, argument: &str, value: &str) -> Vec<Value> {
+    let request = complete(json!({ "type": "ref/tool", "name": tool }), argument, value);
+    let response = handler::handle_request(server, request, None).await;
+    let result = response.result.expect("completion result");
+    result["completion"]["values"].as_array().unwrap().clone()
+}
+
+#[tokio::test]
+async fn completes_enum_values_from_builtin_schemas() {
+    let server = test_server(NovaConfig::default());
+    assert_eq!(
+        values(&server, "get_trending_pools", "duration", "").await,
+        vec![json!("5m"), json!("1h"), json!("6h"), json!("24h")]
+    );
+    assert_eq!(
+        values(&server, "get_trending_pools", "duration", "2").await,
+        vec![json!("24h")]
+    );
+    assert!(values(&server, "search_pools", "query", "")
+        .await
+        .is_empty());
+}
+
+#[tokio::test]
+async fn completes_enum_values_from_plugin_schemas() {
+    let server = test_server(NovaConfig::default());
+    let owner = RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "7".to_string(),
+        actor_id: None,
+    };
+    let metadata = server
+        .plugin_manager()
+        .register_plugin(
+            &owner,
+            PluginRegistrationRequest {
+                name: "chart".to_string(),
+                description: "price chart".to_string(),
+                owner_id: None,
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "interval": { "type": "string", "enum": ["day", "hour", "minute"] }
+                    }
+                }),
+                output_schema: None,
+                endpoint_url: "https://example.com/chart".to_string(),
+                script: None,
+                version: 1,
+                trust_level: Default::default(),
+                client_certificate: None,
+                credentials: None,
+                redact: Vec::new(),
+                request_template: None,
+                listing: None,
+                allowed_contexts: Vec::new(),
+                tags: Vec::new(),
+                coerce_arguments: false,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        values(&server, &metadata.fq_name, "interval", "H").await,
+        vec![json!("hour")]
+    );
+}
+
+#[tokio::test]
+async fn network_slugs_come_from_the_cached_networks_result() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let body = r#"{"data":[{"id":"eth","type":"network"},{"id":"bsc","type":"network"},{"id":"eth-sepolia","type":"network"}]}"#;
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut buf = [0u8; 4096];
+            let _ = socket.read(&mut buf).await;
+            let reply = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = socket.write_all(reply.as_bytes()).await;
+        }
+    });
+    let mut config = NovaConfig::default();
+    config.apis.gecko_terminal_url = Some(format!("http://127.0.0.1:{}", port));
+
+    let server = test_server(config);
+    assert!(values(&server, "get_new_pools", "network", "")
+        .await
+        .is_empty());
+
+    let call = McpRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(2)),
+        method: "tools/call".to_string(),
+        params: Some(json!({ "name": "get_gecko_networks", "arguments": {} })),
+        context_type: Some("user".to_string()),
+        context_id: Some("7".to_string()),
+        actor_id: None,
+    };
+    let response = handler::handle_request(&server, call, None).await;
+    assert!(response.error.is_none(), "{:?}", response.error);
+
+    assert_eq!(
+        values(&server, "get_new_pools", "network", "eth").await,
+        vec![json!("eth"), json!("eth-sepolia")]
+    );
+}
+
+#[tokio::test]
+async fn rejects_unsupported_references() {
+    let server = test_server(NovaConfig::default());
+    for (reference, argument) in [
+        (json!({ "type": "ref/prompt", "name": "x" }), "network"),
+        (
+            json!({ "type": "ref/tool", "name": "no_such_tool" }),
+            "network",
+        ),
+        (json!({ "type": "ref/tool", "name": "get_new_pools" }), ""),
+    ] {
+        let mut request = complete(reference, argument, "");
+        if argument.is_empty() {
+            request.params =
+                Some(json!({ "ref": { "type": "ref/tool", "name": "get_new_pools" } }));
+        }
+        let response = handler::handle_request(&server, request, None).await;
+        assert_eq!(response.error.unwrap().code, -32602);
+    }
+}
+
+fn test_server(config: NovaConfig) -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager"),
+    );
+    NovaServer::new(config, plugin_manager)
+}
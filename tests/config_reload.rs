@@ -0,0 +1,98 @@
+use nova_mcp::config::AuthConfig;
+use nova_mcp::plugins::{PluginContextType, RequestContext};
+use nova_mcp::reload::RuntimeConfig;
+use nova_mcp::server::ToolCall;
+use nova_mcp::{ApiKeyAuth, NovaConfig, NovaServer, PluginManager};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn reload_applies_only_reloadable_settings() {
+    let runtime = RuntimeConfig::new(NovaConfig::default());
+    let mut source = NovaConfig::default();
+    source.apis.rate_limit_per_minute = 5;
+    source.auth.allowed_keys = vec!["rotated".into()];
+    source.server.port = 9999;
+
+    let summary = runtime.apply(source);
+    assert_eq!(
+        summary.changed,
+        vec!["apis.rate_limit_per_minute", "auth.allowed_keys"]
+    );
+    let current = runtime.current();
+    assert_eq!(current.apis.rate_limit_per_minute, 5);
+    assert_eq!(current.auth.allowed_keys, vec!["rotated".to_string()]);
+    assert_eq!(current.server.port, 8080);
+}
+
+#[test]
+fn log_level_change_invokes_hook() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let recorder = Arc::clone(&seen);
+    let runtime = RuntimeConfig::new(NovaConfig::default()).with_log_level_hook(Arc::new(
+        move |level: &str| {
+            recorder.lock().unwrap().push(level.to_string());
+            Ok(())
+        },
+    ));
+
+    let mut source = NovaConfig::default();
+    runtime.apply(source.clone());
+    source.server.log_level = "debug".into();
+    runtime.apply(source);
+    assert_eq!(*seen.lock().unwrap(), vec!["debug".to_string()]);
+}
+
+#[tokio::test]
+async fn disabled_tools_are_hidden_and_rejected() {
+    let server = test_server();
+    let context = RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "1".into(),
+        actor_id: None,
+    };
+    let mut source = NovaConfig::default();
+    source.tools.disabled = vec!["get_new_pools".into()];
+    server.runtime().apply(source);
+
+    let tools = server.get_tools(&context).unwrap();
+    assert!(tools.iter().all(|tool| tool.name != "get_new_pools"));
+    let err = server
+        .handle_tool_call(
+            ToolCall {
+                name: "get_new_pools".into(),
+                arguments: json!({ "network": "eth" }),
+                select: None,
+                format: None,
+                priority: None,
+            },
+            &context,
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("disabled"));
+}
+
+#[test]
+fn replacing_keys_rotates_credentials() {
+    let auth = ApiKeyAuth::new(&AuthConfig {
+        enabled: true,
+        allowed_keys: vec!["old".into()],
+        header_name: "x-api-key".into(),
+        ..AuthConfig::default()
+    });
+    auth.replace_keys(&["new".to_string()]);
+    assert!(auth.validate(Some("new")));
+    assert!(!auth.validate(Some("old")));
+}
+
+fn test_server() -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager"),
+    );
+    NovaServer::new(NovaConfig::default(), plugin_manager)
+}
@@ -0,0 +1,78 @@
+use nova_mcp::config::{CliArgs, NovaConfig};
+use nova_mcp::NovaError;
+
+#[test]
+fn validate_reports_every_problem() {
+    let mut config = NovaConfig::default();
+    config.server.port = 0;
+    config.server.transport = "carrier-pigeon".into();
+    config.auth.enabled = true;
+    config.auth.allowed_keys.clear();
+
+    match config.validate() {
+        Err(NovaError::InvalidConfig { issues }) => {
+            let fields: Vec<_> = issues.iter().map(|i| i.field.as_str()).collect();
+            assert_eq!(
+                fields,
+                vec!["server.port", "server.transport", "auth.allowed_keys"]
+            );
+        }
+        other => panic!("expected InvalidConfig, got {:?}", other),
+    }
+    assert!(NovaConfig::default().validate().is_ok());
+}
+
+#[test]
+fn cli_args_accept_both_flag_forms() {
+    let cli = CliArgs::parse(
+        [
+            "--config=nova.toml",
+            "--port",
+            "9000",
+            "--transport",
+            "http",
+        ]
+        .into_iter()
+        .map(String::from),
+    )
+    .unwrap();
+    assert_eq!(cli.config_path.as_deref(), Some("nova.toml"));
+    assert_eq!(cli.port, Some(9000));
+    assert_eq!(cli.transport.as_deref(), Some("http"));
+
+    assert!(CliArgs::parse(["--port".to_string()]).is_err());
+    assert!(CliArgs::parse(["--verbose".to_string()]).is_err());
+}
+
+#[test]
+fn cli_overrides_env_which_overrides_file() {
+    let path = std::env::temp_dir().join(format!("nova-config-{}.toml", std::process::id()));
+    std::fs::write(
+        &path,
+        "[server]\nport = 7000\ntransport = \"http\"\nlog_level = \"warn\"\n",
+    )
+    .unwrap();
+    std::env::set_var("NOVA_MCP_PORT", "7100");
+    std::env::remove_var("NOVA_MCP_TRANSPORT");
+    std::env::remove_var("NOVA_MCP_LOG_LEVEL");
+
+    let from_env = NovaConfig::load_with(&CliArgs {
+        config_path: Some(path.display().to_string()),
+        ..CliArgs::default()
+    })
+    .unwrap();
+    assert_eq!(from_env.server.port, 7100);
+    assert_eq!(from_env.server.transport, "http");
+    assert_eq!(from_env.server.log_level, "warn");
+
+    let from_cli = NovaConfig::load_with(&CliArgs {
+        config_path: Some(path.display().to_string()),
+        port: Some(7200),
+        ..CliArgs::default()
+    })
+    .unwrap();
+    assert_eq!(from_cli.server.port, 7200);
+
+    std::env::remove_var("NOVA_MCP_PORT");
+    let _ = std::fs::remove_file(path);
+}
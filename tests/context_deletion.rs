@@ -0,0 +1,222 @@
+use nova_mcp::metering::{Metering, UsageEvent, UsageLedger, UsageQuery, DELETED_CONTEXT};
+use nova_mcp::plugins::{
+    PluginContextType, PluginEnableRequest, PluginManager, PluginRegistrationRequest,
+    RequestContext,
+};
+use nova_mcp::preferences::ContextPreferences;
+use nova_mcp::{NovaConfig, NovaServer};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn purge_removes_owned_plugins_and_enablements() {
+    let server = test_server(NovaConfig::default());
+    let manager = server.plugin_manager();
+    let group = context(PluginContextType::Group, "-100");
+    let neighbour = context(PluginContextType::Group, "-1001");
+    let user = context(PluginContextType::User, "7");
+
+    let owned = manager
+        .register_plugin(&group, registration("echo"))
+        .unwrap();
+    let foreign = manager
+        .register_plugin(&user, registration("echo"))
+        .unwrap();
+    for target in [&group, &neighbour] {
+        manager
+            .set_enablement(PluginEnableRequest {
+                plugin_id: foreign.plugin_id,
+                context_type: target.context_type.clone(),
+                context_id: target.context_id.clone(),
+                enable: true,
+                added_by: Some("admin".into()),
+            })
+            .unwrap();
+    }
+
+    let (plugins, enablements) = manager.purge_context(&group).unwrap();
+    assert_eq!(plugins, vec![owned.plugin_id]);
+    // The owner record of "echo" plus the enablement of the user's plugin
+    assert_eq!(enablements, 2);
+    assert!(manager.get_plugin(owned.plugin_id).is_err());
+    assert!(!manager
+        .is_enabled(foreign.plugin_id, PluginContextType::Group, "-100")
+        .unwrap());
+    // Other contexts keep their records
+    assert!(manager
+        .is_enabled(foreign.plugin_id, PluginContextType::Group, "-1001")
+        .unwrap());
+
+    assert_eq!(manager.purge_context(&group).unwrap(), (vec![], 0));
+}
+
+#[tokio::test]
+async fn delete_endpoint_reports_removed_data() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig::default();
+    config.server.port = port;
+    config.admin.tokens = vec!["ops-token".into()];
+    let server = test_server(config.clone());
+    let group = context(PluginContextType::Group, "-100");
+    let plugin = server
+        .plugin_manager()
+        .register_plugin(&group, registration("echo"))
+        .unwrap();
+    server
+        .preferences()
+        .put(&group, &ContextPreferences::default())
+        .unwrap();
+    server
+        .oauth_clients()
+        .create(&group, vec!["plugins:read".into()])
+        .unwrap();
+    server
+        .quotas()
+        .consume(&group, None, &config.quotas)
+        .unwrap();
+    let plugin_manager = server.plugin_manager_arc();
+    let metering = plugin_manager.metering().unwrap();
+    // The group's own call, another context's call to its plugin, and an unrelated one
+    let echo = ("group_-100_echo_v1", "group:-100");
+    metering.record(usage_event("1", "group:-100", Some("42"), echo));
+    metering.record(usage_event("2", "user:7", None, echo));
+    metering.record(usage_event(
+        "3",
+        "user:7",
+        None,
+        ("user_8_price_v1", "user:8"),
+    ));
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let client = reqwest::Client::new();
+    let url = format!("http://127.0.0.1:{}/contexts/group/-100", port);
+    let mut response = None;
+    for _ in 0..50 {
+        match client.delete(&url).send().await {
+            Ok(resp) => {
+                response = Some(resp);
+                break;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(50)).await,
+        }
+    }
+    assert_eq!(response.expect("server did not start").status(), 401);
+
+    let report: Value = client
+        .delete(&url)
+        .header("x-admin-token", "ops-token")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(report["context_type"], "group");
+    assert_eq!(report["plugins"], json!([plugin.plugin_id]));
+    assert_eq!(report["enablements"], 1);
+    assert_eq!(report["preferences"], true);
+    assert_eq!(report["oauth_clients"], 1);
+    // Today's and this month's counters
+    assert_eq!(report["quota_records"], 2);
+    assert_eq!(report["usage_events"], 2);
+
+    // Billing totals survive, without the deleted context
+    let ledger = plugin_manager.metering().unwrap().ledger().unwrap();
+    let events = ledger.events(&UsageQuery::default()).unwrap();
+    assert_eq!(events.len(), 3);
+    let who: Vec<_> = events
+        .iter()
+        .map(|event| (event.context.as_str(), event.owner.as_str()))
+        .collect();
+    assert_eq!(
+        who,
+        [
+            (DELETED_CONTEXT, DELETED_CONTEXT),
+            ("user:7", DELETED_CONTEXT),
+            ("user:7", "user:8"),
+        ]
+    );
+    assert_eq!(events[0].actor_id, None);
+    assert_eq!(events[1].plugin, "deleted_1");
+    assert_eq!(events[2].plugin, "user_8_price_v1");
+    assert!(!serde_json::to_string(&events).unwrap().contains("-100"));
+
+    let invalid = client
+        .delete(format!("http://127.0.0.1:{}/contexts/team/1", port))
+        .header("x-admin-token", "ops-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(invalid.status(), 400);
+}
+
+fn registration(name: &str) -> PluginRegistrationRequest {
+    PluginRegistrationRequest {
+        name: name.to_string(),
+        description: "test".to_string(),
+        owner_id: None,
+        input_schema: json!({ "type": "object" }),
+        output_schema: None,
+        endpoint_url: "https://example.com/hook".to_string(),
+        script: None,
+        version: 1,
+        trust_level: Default::default(),
+        client_certificate: None,
+        credentials: None,
+        redact: Vec::new(),
+        request_template: None,
+        listing: None,
+        allowed_contexts: Vec::new(),
+        tags: Vec::new(),
+        coerce_arguments: false,
+    }
+}
+
+fn usage_event(
+    id: &str,
+    context: &str,
+    actor_id: Option<&str>,
+    (plugin, owner): (&str, &str),
+) -> UsageEvent {
+    UsageEvent {
+        id: id.to_string(),
+        at: 1_700_000_000 + id.parse::<i64>().unwrap(),
+        context: context.to_string(),
+        actor_id: actor_id.map(str::to_string),
+        plugin_id: 1,
+        plugin: plugin.to_string(),
+        owner: owner.to_string(),
+        duration_ms: 10,
+        request_bytes: 100,
+        response_bytes: 40,
+        success: true,
+    }
+}
+
+fn context(context_type: PluginContextType, id: &str) -> RequestContext {
+    RequestContext {
+        context_type,
+        context_id: id.to_string(),
+        actor_id: None,
+    }
+}
+
+fn test_server(config: NovaConfig) -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree)
+            .expect("init plugin manager")
+            .with_metering(Arc::new(
+                Metering::new().with_ledger(UsageLedger::in_memory()),
+            )),
+    );
+    NovaServer::new(config, plugin_manager)
+}
@@ -0,0 +1,137 @@
+use nova_mcp::mcp::{dto::McpRequest, handler};
+use nova_mcp::plugins::{
+    escape_context_id, unescape_context_id, ContextIdFormat, PluginContextType, PluginManager,
+    PluginRegistrationRequest, RequestContext,
+};
+use nova_mcp::{NovaConfig, NovaServer};
+use serde_json::json;
+use std::sync::Arc;
+
+#[test]
+fn formats_validate_ids_per_strategy() {
+    let user = PluginContextType::User;
+    assert!(ContextIdFormat::Numeric.validate(&user, "42").is_ok());
+    assert!(ContextIdFormat::Numeric
+        .validate(&user, "T024BE7LD")
+        .is_err());
+
+    let uuid = "6f1c2b1e-9a4d-4a51-8c1e-2b7f0c9d3e10";
+    assert!(ContextIdFormat::Uuid.validate(&user, uuid).is_ok());
+    assert!(ContextIdFormat::Uuid.validate(&user, "42").is_err());
+
+    assert!(ContextIdFormat::Opaque.validate(&user, "T024BE7LD").is_ok());
+    assert!(ContextIdFormat::Opaque
+        .validate(&user, "81384788765712384:175928847299117063")
+        .is_ok());
+    assert!(ContextIdFormat::Opaque
+        .validate(&user, "two words")
+        .is_err());
+    assert!(ContextIdFormat::Opaque.validate(&user, "").is_err());
+
+    // Organizations keep their slug rule under every format
+    let organization = PluginContextType::Organization;
+    assert!(ContextIdFormat::Opaque
+        .validate(&organization, "Acme Labs")
+        .is_err());
+}
+
+#[test]
+fn escaping_round_trips_and_keeps_legacy_ids() {
+    assert_eq!(escape_context_id("-1001234"), "-1001234");
+    assert_eq!(escape_context_id("acme-labs"), "acme-labs");
+    assert_eq!(escape_context_id("U_1:T0"), "U.5F1.3AT0");
+
+    for id in ["U_1:T0", "guild/123", "ünïcode", "a.b"] {
+        assert_eq!(
+            unescape_context_id(&escape_context_id(id)).as_deref(),
+            Some(id)
+        );
+    }
+    assert_eq!(unescape_context_id("bad.Z"), None);
+}
+
+#[tokio::test]
+async fn opaque_ids_get_escaped_tool_names() {
+    let mut config = NovaConfig::default();
+    config.context.id_format = "opaque".to_string();
+    let server = test_server(config);
+    let owner = RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "U_1:T0".to_string(),
+        actor_id: None,
+    };
+    let metadata = server
+        .plugin_manager()
+        .register_plugin(&owner, registration("echo"))
+        .unwrap();
+    assert_eq!(metadata.fq_name, "user_U.5F1.3AT0_echo_v1");
+
+    let response = handler::handle_request(&server, list_tools("U_1:T0"), None).await;
+    let tools = response.result.expect("tools/list result")["tools"].clone();
+    assert!(tools
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|tool| tool["name"] == "user_U.5F1.3AT0_echo_v1"));
+}
+
+#[tokio::test]
+async fn numeric_format_rejects_platform_ids() {
+    let server = test_server(NovaConfig::default());
+    let response = handler::handle_request(&server, list_tools("T024BE7LD"), None).await;
+    let error = response.error.expect("context error");
+    assert!(error.message.contains("must be numeric"));
+}
+
+#[test]
+fn unknown_id_format_fails_validation() {
+    let mut config = NovaConfig::default();
+    config.context.id_format = "snowflake".to_string();
+    let err = config.validate().unwrap_err();
+    assert!(err.to_string().contains("context.id_format"));
+}
+
+fn list_tools(context_id: &str) -> McpRequest {
+    McpRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: "tools/list".to_string(),
+        params: None,
+        context_type: Some("user".to_string()),
+        context_id: Some(context_id.to_string()),
+        actor_id: None,
+    }
+}
+
+fn registration(name: &str) -> PluginRegistrationRequest {
+    PluginRegistrationRequest {
+        name: name.to_string(),
+        description: "test".to_string(),
+        owner_id: None,
+        input_schema: json!({ "type": "object" }),
+        output_schema: None,
+        endpoint_url: "https://example.com/hook".to_string(),
+        script: None,
+        version: 1,
+        trust_level: Default::default(),
+        client_certificate: None,
+        credentials: None,
+        redact: Vec::new(),
+        request_template: None,
+        listing: None,
+        allowed_contexts: Vec::new(),
+        tags: Vec::new(),
+        coerce_arguments: false,
+    }
+}
+
+fn test_server(config: NovaConfig) -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager"),
+    );
+    NovaServer::new(config, plugin_manager)
+}
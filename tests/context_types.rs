@@ -0,0 +1,176 @@
+use nova_mcp::plugins::{
+    validate_context_pair, PluginContextType, PluginEnableRequest, PluginManager,
+    PluginRegistrationRequest, RequestContext,
+};
+use serde_json::json;
+
+#[test]
+fn context_ids_follow_per_type_rules() {
+    assert!(validate_context_pair(&PluginContextType::User, "42").is_ok());
+    assert!(validate_context_pair(&PluginContextType::Group, "-42").is_ok());
+    assert!(validate_context_pair(&PluginContextType::User, "abc").is_err());
+
+    assert!(validate_context_pair(&PluginContextType::Channel, "-1001234567890").is_ok());
+    assert!(validate_context_pair(&PluginContextType::Channel, "-42").is_err());
+    assert!(validate_context_pair(&PluginContextType::Channel, "-100").is_err());
+
+    assert!(validate_context_pair(&PluginContextType::Organization, "acme-labs").is_ok());
+    assert!(validate_context_pair(&PluginContextType::Organization, "Acme").is_err());
+    assert!(validate_context_pair(&PluginContextType::Organization, "acme_labs").is_err());
+    assert!(validate_context_pair(&PluginContextType::Organization, "-acme").is_err());
+}
+
+#[test]
+fn context_types_parse_case_insensitively() {
+    for context_type in PluginContextType::ALL {
+        let upper = context_type.as_str().to_uppercase();
+        assert_eq!(PluginContextType::parse(&upper), Some(context_type));
+    }
+    assert_eq!(PluginContextType::parse("team"), None);
+}
+
+#[test]
+fn new_context_types_own_and_enable_plugins() {
+    let manager = test_manager();
+    let channel = context(PluginContextType::Channel, "-1001234567890");
+    let organization = context(PluginContextType::Organization, "acme");
+
+    let metadata = manager
+        .register_plugin(&channel, registration("echo"))
+        .unwrap();
+    assert_eq!(metadata.fq_name, "channel_-1001234567890_echo_v1");
+
+    let org_metadata = manager
+        .register_plugin(&organization, registration("echo"))
+        .unwrap();
+    assert_eq!(org_metadata.fq_name, "organization_acme_echo_v1");
+
+    let mut request = enable_request(metadata.plugin_id, &organization);
+    request.added_by = None;
+    assert!(manager.set_enablement(request).is_err());
+
+    let status = manager
+        .set_enablement(enable_request(metadata.plugin_id, &organization))
+        .unwrap();
+    assert!(status.enabled);
+    assert_eq!(status.added_by.as_deref(), Some("admin"));
+    assert!(manager
+        .is_enabled(metadata.plugin_id, PluginContextType::Organization, "acme")
+        .unwrap());
+
+    let stats = manager.stats();
+    assert_eq!(stats.channel_owned, 1);
+    assert_eq!(stats.organization_owned, 1);
+    assert_eq!(stats.organization_enablements, 2);
+
+    let snapshot = manager.snapshot().unwrap();
+    assert_eq!(snapshot.channel_enablements.len(), 1);
+    assert_eq!(snapshot.organization_enablements.len(), 2);
+}
+
+#[test]
+fn enablement_rejects_mismatched_ids() {
+    let manager = test_manager();
+    let metadata = manager
+        .register_plugin(&context(PluginContextType::User, "7"), registration("echo"))
+        .unwrap();
+    let err = manager
+        .set_enablement(enable_request(
+            metadata.plugin_id,
+            &context(PluginContextType::Channel, "12"),
+        ))
+        .unwrap_err();
+    assert!(err.to_string().contains("-100"));
+}
+
+#[test]
+fn managers_without_extra_trees_reject_new_enablements() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let manager = PluginManager::new(
+        db.open_tree("plugin_metadata").unwrap(),
+        db.open_tree("user_plugins").unwrap(),
+        db.open_tree("group_plugins").unwrap(),
+    )
+    .unwrap();
+    let channel = context(PluginContextType::Channel, "-1009");
+    let metadata = manager
+        .register_plugin(&channel, registration("echo"))
+        .unwrap();
+    assert!(manager
+        .set_enablement(enable_request(
+            metadata.plugin_id,
+            &context(PluginContextType::Channel, "-1008"),
+        ))
+        .is_err());
+    assert!(!manager
+        .is_enabled(metadata.plugin_id, PluginContextType::Channel, "-1008")
+        .unwrap());
+}
+
+#[test]
+fn stats_from_older_snapshots_default_new_counts() {
+    let stats: nova_mcp::plugins::RegistryStats = serde_json::from_value(json!({
+        "plugins": 1,
+        "versions": 1,
+        "user_owned": 1,
+        "group_owned": 0,
+        "user_enablements": 1,
+        "group_enablements": 0
+    }))
+    .unwrap();
+    assert_eq!(stats.channel_owned, 0);
+    assert_eq!(stats.organization_enablements, 0);
+}
+
+fn enable_request(plugin_id: u64, context: &RequestContext) -> PluginEnableRequest {
+    PluginEnableRequest {
+        plugin_id,
+        context_type: context.context_type.clone(),
+        context_id: context.context_id.clone(),
+        enable: true,
+        added_by: Some("admin".to_string()),
+    }
+}
+
+fn registration(name: &str) -> PluginRegistrationRequest {
+    PluginRegistrationRequest {
+        name: name.to_string(),
+        description: "test".to_string(),
+        owner_id: None,
+        input_schema: json!({ "type": "object" }),
+        output_schema: None,
+        endpoint_url: "https://example.com/hook".to_string(),
+        script: None,
+        version: 1,
+        trust_level: Default::default(),
+        client_certificate: None,
+        credentials: None,
+        redact: Vec::new(),
+        request_template: None,
+        listing: None,
+        allowed_contexts: Vec::new(),
+        tags: Vec::new(),
+        coerce_arguments: false,
+    }
+}
+
+fn context(context_type: PluginContextType, id: &str) -> RequestContext {
+    RequestContext {
+        context_type,
+        context_id: id.to_string(),
+        actor_id: None,
+    }
+}
+
+fn test_manager() -> PluginManager {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    PluginManager::new(metadata_tree, user_tree, group_tree)
+        .expect("init plugin manager")
+        .with_context_trees(
+            db.open_tree("channel_plugins").unwrap(),
+            db.open_tree("organization_plugins").unwrap(),
+        )
+}
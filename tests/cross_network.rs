@@ -0,0 +1,425 @@
+// find_token_across_networks over mocked GeckoTerminal searches, and the
+// admin endpoints that curate its mapping table.
+use nova_mcp::tools::gecko_terminal::cross_network::{
+    MappedToken, RepresentationKind, RepresentationSource, TokenMapping,
+};
+use nova_mcp::tools::rate_limit::UpstreamRateLimiter;
+use nova_mcp::tools::{
+    CrossNetworkTools, FindTokenAcrossNetworksInput, SearchPoolsTools, TokenMappingStore,
+};
+use nova_mcp::{NovaConfig, NovaError, NovaServer};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const USDC_ETH: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+const USDC_E_ARBITRUM: &str = "0xff970a61a04b1ca14834a43f5de4533ebddb5cc8";
+const USDC_E_POLYGON: &str = "0x2791bca1f2de4661ed88a30c99a7a9449aa84174";
+const USDC_BASE: &str = "0x833589fcd6edb6e08f4c7c32d4f71b54bda02913";
+const WETH_ETH: &str = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+const PEPE_ETH: &str = "0x6982508145454ce325ddbe47a25d4ec3d2311933";
+const PEPE_BSC: &str = "0x25d887ce7a35172c62febfd67a1856f20faebb00";
+const WPEPE_BASE: &str = "0x52b492a33e447cdb854c7fc19f1e57e8bfa1777d";
+
+fn tools(upstream: &MockServer) -> CrossNetworkTools {
+    let limiter = Arc::new(UpstreamRateLimiter::new(
+        "geckoterminal",
+        600,
+        Duration::ZERO,
+    ));
+    CrossNetworkTools::new(
+        SearchPoolsTools::with_rate_limiter(limiter).with_base_url(upstream.uri()),
+    )
+}
+
+fn token(network: &str, address: &str, symbol: &str) -> Value {
+    json!({
+        "id": format!("{}_{}", network, address),
+        "type": "token",
+        "attributes": { "address": address, "name": format!("{} token", symbol), "symbol": symbol }
+    })
+}
+
+/// A pool of `base` / `quote`, each `(address, symbol)` on `network`.
+fn pool(
+    network: &str,
+    address: &str,
+    reserve: &str,
+    base: (&str, &str),
+    quote: (&str, &str),
+) -> (Value, [Value; 2]) {
+    let pool = json!({
+        "id": format!("{}_{}", network, address),
+        "type": "pool",
+        "attributes": {
+            "address": address,
+            "name": format!("{} / {}", base.1, quote.1),
+            "reserve_in_usd": reserve,
+            "base_token_price_usd": "1.0001",
+            "quote_token_price_usd": "3120.5",
+            "volume_usd": { "h24": "1000000" }
+        },
+        "relationships": {
+            "base_token": { "data": { "id": format!("{}_{}", network, base.0), "type": "token" } },
+            "quote_token": { "data": { "id": format!("{}_{}", network, quote.0), "type": "token" } },
+            "dex": { "data": { "id": "uniswap_v3", "type": "dex" } }
+        }
+    });
+    (
+        pool,
+        [
+            token(network, base.0, base.1),
+            token(network, quote.0, quote.1),
+        ],
+    )
+}
+
+async fn search(upstream: &MockServer, query: &str, pools: Vec<(Value, [Value; 2])>) {
+    let mut data = vec![];
+    let mut included = vec![json!({
+        "id": "uniswap_v3",
+        "type": "dex",
+        "attributes": { "name": "Uniswap V3" }
+    })];
+    for (pool, tokens) in pools {
+        data.push(pool);
+        included.extend(tokens);
+    }
+    Mock::given(method("GET"))
+        .and(path("/search/pools"))
+        .and(query_param("query", query))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "data": data, "included": included })),
+        )
+        .mount(upstream)
+        .await;
+}
+
+fn usdc_mapping() -> TokenMapping {
+    let mapped = |network: &str, address: &str, kind, bridge: Option<&str>| MappedToken {
+        network: network.into(),
+        address: address.into(),
+        kind,
+        bridge: bridge.map(str::to_string),
+    };
+    TokenMapping {
+        id: "usdc".into(),
+        symbol: "USDC".into(),
+        name: Some("USD Coin".into()),
+        representations: vec![
+            mapped("eth", USDC_ETH, RepresentationKind::Canonical, None),
+            mapped(
+                "arbitrum",
+                USDC_E_ARBITRUM,
+                RepresentationKind::Bridged,
+                Some("arbitrum-bridge"),
+            ),
+            mapped(
+                "polygon_pos",
+                USDC_E_POLYGON,
+                RepresentationKind::Bridged,
+                Some("pos-bridge"),
+            ),
+        ],
+    }
+}
+
+#[tokio::test]
+async fn curated_representations_come_before_search_matches() {
+    let upstream = MockServer::start().await;
+    search(
+        &upstream,
+        "USDC",
+        vec![
+            pool(
+                "eth",
+                "0x3416cf6c708da44db2624d63ea0aaef7113527c6",
+                "10000000",
+                (USDC_ETH, "USDC"),
+                ("0xdac17f958d2ee523a2206206994597c13d831ec7", "USDT"),
+            ),
+            pool(
+                "eth",
+                "0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640",
+                "163421905.52",
+                (USDC_ETH, "USDC"),
+                (WETH_ETH, "WETH"),
+            ),
+            pool(
+                "arbitrum",
+                "0xc31e54c7a869b9fcbecc14363cf510d1c41fa443",
+                "5000000",
+                ("0x82af49447d8a07e3bd95bd0d56f35241523fbab1", "WETH"),
+                (USDC_E_ARBITRUM, "USDC.e"),
+            ),
+            pool(
+                "base",
+                "0xd0b53d9277642d899df5c87a3966a349a798f224",
+                "20000000",
+                ("0x4200000000000000000000000000000000000006", "WETH"),
+                (USDC_BASE, "USDC"),
+            ),
+        ],
+    )
+    .await;
+    let tools = tools(&upstream);
+    tools.mappings().put(&usdc_mapping()).unwrap();
+
+    let output = tools
+        .find_token(FindTokenAcrossNetworksInput {
+            token: "usdc".into(),
+            network: None,
+            limit: None,
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(output.symbol, "USDC");
+    assert_eq!(output.mapping.as_deref(), Some("usdc"));
+    let networks: Vec<&str> = output
+        .representations
+        .iter()
+        .map(|found| found.network.as_str())
+        .collect();
+    assert_eq!(networks, ["eth", "arbitrum", "polygon_pos", "base"]);
+
+    let eth = &output.representations[0];
+    assert_eq!(eth.source, RepresentationSource::Curated);
+    assert_eq!(eth.kind, Some(RepresentationKind::Canonical));
+    let best = eth.best_pool.as_ref().unwrap();
+    assert_eq!(best.address, "0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640");
+    assert_eq!(best.dex.as_deref(), Some("Uniswap V3"));
+    assert_eq!(best.price_usd.as_deref(), Some("1.0001"));
+    assert_eq!(
+        best.links["geckoterminal"],
+        "https://www.geckoterminal.com/eth/pools/0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640"
+    );
+    assert_eq!(
+        eth.links["explorer"],
+        format!("https://etherscan.io/token/{}", USDC_ETH)
+    );
+
+    // Matched by its curated address despite the different symbol
+    let arbitrum = &output.representations[1];
+    assert_eq!(arbitrum.symbol.as_deref(), Some("USDC.e"));
+    assert_eq!(arbitrum.bridge.as_deref(), Some("arbitrum-bridge"));
+    let best = arbitrum.best_pool.as_ref().unwrap();
+    assert_eq!(best.price_usd.as_deref(), Some("3120.5"));
+
+    // Curated but not in the search results
+    let polygon = &output.representations[2];
+    assert_eq!(polygon.kind, Some(RepresentationKind::Bridged));
+    assert!(polygon.best_pool.is_none());
+    assert_eq!(polygon.name.as_deref(), Some("USD Coin"));
+
+    let base = &output.representations[3];
+    assert_eq!(base.source, RepresentationSource::Search);
+    assert_eq!(base.address, USDC_BASE);
+    assert_eq!(base.kind, None);
+}
+
+#[tokio::test]
+async fn addresses_are_looked_up_by_their_symbol() {
+    let upstream = MockServer::start().await;
+    search(
+        &upstream,
+        PEPE_ETH,
+        vec![pool(
+            "eth",
+            "0xa43fe16908251ee70ef74718545e4fe6c5ccec9f",
+            "30000000",
+            (PEPE_ETH, "PEPE"),
+            (WETH_ETH, "WETH"),
+        )],
+    )
+    .await;
+    search(
+        &upstream,
+        "PEPE",
+        vec![
+            pool(
+                "bsc",
+                "0x6f7a1e7a7d3e1a56b4d4c2b7f0f3b8f9a2b1c0d9",
+                "900000",
+                (PEPE_BSC, "PEPE"),
+                ("0xbb4cdb9cbd36b01bd1cbaebf2de08d9173bc095c", "WBNB"),
+            ),
+            pool(
+                "base",
+                "0x1f2e3d4c5b6a79808f9e0d1c2b3a495867768594",
+                "2500000",
+                (WPEPE_BASE, "wPEPE"),
+                ("0x4200000000000000000000000000000000000006", "WETH"),
+            ),
+        ],
+    )
+    .await;
+    search(&upstream, PEPE_BSC, vec![]).await;
+    let tools = tools(&upstream);
+
+    let output = tools
+        .find_token(FindTokenAcrossNetworksInput {
+            token: format!(" {} ", PEPE_ETH),
+            network: Some("eth".into()),
+            limit: Some(2),
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(output.symbol, "PEPE");
+    assert!(output.mapping.is_none());
+    let found: Vec<(&str, Option<RepresentationKind>)> = output
+        .representations
+        .iter()
+        .map(|found| (found.network.as_str(), found.kind))
+        .collect();
+    assert_eq!(
+        found,
+        [("eth", None), ("base", Some(RepresentationKind::Wrapped))]
+    );
+
+    let err = tools
+        .find_token(FindTokenAcrossNetworksInput {
+            token: "0x6982508145454ce325ddbe47a25d4ec3d231193".into(),
+            network: Some("eth".into()),
+            limit: None,
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, NovaError::InvalidAddress { .. }));
+
+    let err = tools
+        .find_token(FindTokenAcrossNetworksInput {
+            token: PEPE_BSC.into(),
+            network: Some("eth".into()),
+            limit: None,
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, NovaError::TokenNotFound { .. }));
+}
+
+#[test]
+fn mappings_persist_and_are_validated() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let store = TokenMappingStore::persistent(db.open_tree("token_mappings").unwrap());
+    assert!(store.put(&usdc_mapping()).unwrap().is_none());
+    assert!(store.put(&usdc_mapping()).unwrap().is_some());
+    assert_eq!(
+        store
+            .find_by_address(
+                Some("arbitrum"),
+                &USDC_E_ARBITRUM.to_uppercase().replace("0X", "0x")
+            )
+            .unwrap()
+            .map(|mapping| mapping.id),
+        Some("usdc".to_string())
+    );
+
+    let mut broken = usdc_mapping();
+    broken.representations[1].address = "0x1234".into();
+    assert!(matches!(
+        store.put(&broken).unwrap_err(),
+        NovaError::InvalidAddress { .. }
+    ));
+    let mut twice = usdc_mapping();
+    twice.representations.push(twice.representations[0].clone());
+    assert!(matches!(
+        store.put(&twice).unwrap_err(),
+        NovaError::ValidationError { .. }
+    ));
+
+    assert_eq!(store.remove("usdc").unwrap().unwrap(), usdc_mapping());
+    assert!(store.list().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn admins_curate_mappings_over_http() {
+    let mut config = NovaConfig::default();
+    config.admin.tokens = vec!["ops-token".into()];
+    let base = start(config).await;
+    let client = reqwest::Client::new();
+    let url = format!("{}/admin/token-mappings/usdc", base);
+    let body = json!({
+        "symbol": "USDC",
+        "name": "USD Coin",
+        "representations": [
+            { "network": "eth", "address": USDC_ETH, "kind": "canonical" },
+            { "network": "arbitrum", "address": USDC_E_ARBITRUM, "kind": "bridged", "bridge": "arbitrum-bridge" }
+        ]
+    });
+
+    let unauthorized = client.put(&url).json(&body).send().await.unwrap();
+    assert_eq!(unauthorized.status(), 401);
+
+    let created = client
+        .put(&url)
+        .header("x-admin-token", "ops-token")
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(created.status(), 201);
+    let replaced = client
+        .put(&url)
+        .header("x-admin-token", "ops-token")
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(replaced.status(), 200);
+
+    let mut invalid = body.clone();
+    invalid["representations"][0]["address"] = json!("not-an-address");
+    let rejected = client
+        .put(&url)
+        .header("x-admin-token", "ops-token")
+        .json(&invalid)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(rejected.status(), 400);
+
+    let listed: Value = client
+        .get(format!("{}/admin/token-mappings", base))
+        .header("x-admin-token", "ops-token")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(listed[0]["id"], "usdc");
+    assert_eq!(listed[0]["representations"][1]["bridge"], "arbitrum-bridge");
+
+    for expected in [204, 404] {
+        let deleted = client
+            .delete(&url)
+            .header("x-admin-token", "ops-token")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(deleted.status(), expected);
+    }
+}
+
+async fn start(mut config: NovaConfig) -> String {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    config.server.port = port;
+    let server = NovaServer::in_memory(config.clone()).unwrap();
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+    let base = format!("http://127.0.0.1:{}", port);
+    for _ in 0..50 {
+        if reqwest::get(format!("{}/healthz", base)).await.is_ok() {
+            return base;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("server did not start");
+}
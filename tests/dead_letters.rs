@@ -0,0 +1,211 @@
+use axum::http::StatusCode as AxumStatus;
+use axum::routing::post;
+use axum::{Json, Router};
+use nova_mcp::dead_letters::{DeadLetter, DeadLetters, Delivery, DeliveryKind};
+use nova_mcp::plugins::{PluginContextType, PluginRegistrationRequest, RequestContext};
+use nova_mcp::test_util::{StubPlugin, TestServer};
+use nova_mcp::{NovaConfig, NovaError};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const ADMIN_TOKEN: &str = "ops-token";
+
+#[tokio::test]
+async fn letters_keep_their_retry_history_across_restarts() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let letters = DeadLetters::persistent(db.open_tree("dead_letters").unwrap());
+    let letter = letters
+        .record(delivery("evt-1", Some("user:5")), 3, "Webhook returned 500")
+        .unwrap();
+    letters
+        .record(delivery("evt-2", None), 1, "connection refused")
+        .unwrap();
+
+    let failed = letters
+        .requeue(&letter.id, |_| async {
+            Err(NovaError::api_error("Webhook returned 502"))
+        })
+        .await;
+    assert!(failed.is_err());
+
+    // A new store over the same tree, as after a restart
+    let reopened = DeadLetters::persistent(db.open_tree("dead_letters").unwrap());
+    let kept = reopened.get(&letter.id).unwrap().unwrap();
+    assert_eq!((kept.attempts, kept.requeues), (4, 1));
+    assert!(kept.last_error.contains("502"), "{}", kept.last_error);
+    assert_eq!(kept.first_failed_at, letter.first_failed_at);
+    assert_eq!(
+        reopened.list(Some(DeliveryKind::JobWebhook)).unwrap().len(),
+        0
+    );
+    assert_eq!(
+        reopened
+            .list(Some(DeliveryKind::MeteringWebhook))
+            .unwrap()
+            .len(),
+        2
+    );
+
+    let sent: Arc<Mutex<Vec<Delivery>>> = Arc::default();
+    let seen = sent.clone();
+    let requeued = reopened
+        .requeue(&letter.id, |delivery| async move {
+            seen.lock().unwrap().push(delivery);
+            Ok(())
+        })
+        .await
+        .unwrap();
+    assert_eq!(requeued.attempts, 5);
+    assert_eq!(sent.lock().unwrap()[0].payload["id"], "evt-1");
+    assert!(reopened.get(&letter.id).unwrap().is_none());
+
+    assert_eq!(reopened.remove_context("user:5").unwrap(), 0);
+    assert_eq!(reopened.list(None).unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn failed_metering_events_can_be_requeued_by_admins() {
+    let stub = StubPlugin::start().await.unwrap();
+    let (receiver, events, up) = usage_receiver().await;
+    up.store(false, Ordering::SeqCst);
+    let mut config = NovaConfig::default();
+    config.admin.tokens = vec![ADMIN_TOKEN.to_string()];
+    config.metering.enabled = true;
+    config.metering.sinks = vec!["webhook".to_string()];
+    config.metering.webhook_url = Some(format!("http://{}/usage", receiver));
+    let server = TestServer::with_config(config).await.unwrap();
+    let client = server.client(owner());
+    let plugin = client
+        .register(&registration(&stub.url("/invoke")))
+        .await
+        .unwrap();
+    let result = client
+        .tools_call(&plugin.fq_name, json!({ "city": "Porto" }))
+        .await
+        .unwrap();
+    assert_eq!(result["isError"], false, "{}", result);
+
+    let http = reqwest::Client::new();
+    let list = server.url("/v1/admin/dead-letters?kind=metering_webhook");
+    let mut letters: Vec<DeadLetter> = Vec::new();
+    for _ in 0..250 {
+        letters = http
+            .get(&list)
+            .header("x-admin-token", ADMIN_TOKEN)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        if !letters.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(letters.len(), 1, "the failed event was not dead-lettered");
+    let letter = &letters[0];
+    assert_eq!(letter.delivery.kind, DeliveryKind::MeteringWebhook);
+    assert_eq!(letter.delivery.payload["plugin"], plugin.fq_name.as_str());
+    assert_eq!(letter.attempts, 1);
+
+    // Admin routes need the admin token
+    let requeue = server.url(&format!("/v1/admin/dead-letters/{}/requeue", letter.id));
+    let response = http.post(&requeue).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    up.store(true, Ordering::SeqCst);
+    let response = http
+        .post(&requeue)
+        .header("x-admin-token", ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let delivered = events.lock().unwrap().clone();
+    assert_eq!(delivered.last().unwrap()["id"], letter.delivery.reference);
+
+    let response = http
+        .post(&requeue)
+        .header("x-admin-token", ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let audit: Value = http
+        .get(server.url("/v1/admin/audit"))
+        .header("x-admin-token", ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let actions: Vec<&str> = audit["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|entry| entry["action"].as_str())
+        .collect();
+    assert!(
+        actions.contains(&"admin.dead_letters.requeue"),
+        "{:?}",
+        actions
+    );
+}
+
+/// Records every usage event POSTed to `/usage`; answers `503` while the
+/// returned flag is false.
+async fn usage_receiver() -> (SocketAddr, Arc<Mutex<Vec<Value>>>, Arc<AtomicBool>) {
+    let events: Arc<Mutex<Vec<Value>>> = Arc::default();
+    let up = Arc::new(AtomicBool::new(true));
+    let (seen, healthy) = (events.clone(), up.clone());
+    let app = Router::new().route(
+        "/usage",
+        post(move |Json(event): Json<Value>| {
+            let status = if healthy.load(Ordering::SeqCst) {
+                seen.lock().unwrap().push(event);
+                AxumStatus::OK
+            } else {
+                AxumStatus::SERVICE_UNAVAILABLE
+            };
+            async move { status }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    (addr, events, up)
+}
+
+fn delivery(reference: &str, context: Option<&str>) -> Delivery {
+    Delivery {
+        kind: DeliveryKind::MeteringWebhook,
+        target: "https://billing.example.com/usage".to_string(),
+        reference: reference.to_string(),
+        context: context.map(str::to_string),
+        payload: json!({ "id": reference }),
+    }
+}
+
+fn registration(endpoint_url: &str) -> PluginRegistrationRequest {
+    serde_json::from_value(json!({
+        "name": "echo",
+        "description": "Echoes its arguments",
+        "input_schema": { "type": "object" },
+        "endpoint_url": endpoint_url
+    }))
+    .unwrap()
+}
+
+fn owner() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
@@ -0,0 +1,89 @@
+use nova_mcp::config::CliArgs;
+use nova_mcp::doctor::{self, CheckStatus};
+use nova_mcp::{NovaConfig, NovaServer};
+
+#[tokio::test]
+async fn stdio_passes_every_check() {
+    let config = NovaConfig::default();
+    let report = doctor::run(NovaServer::in_memory(config.clone()).unwrap(), &config)
+        .await
+        .unwrap();
+    assert!(report.passed(), "{}", report);
+    assert_eq!(report.transport, "stdio");
+    for name in [
+        "initialize",
+        "notifications/initialized",
+        "ping",
+        "tools/list",
+        "tools/call",
+        "tools/call (unknown tool)",
+        "unknown method",
+    ] {
+        let check = report.check(name).unwrap();
+        assert_eq!(check.status, CheckStatus::Pass, "{}", report);
+    }
+    // Generic clients send no context and need `context.default`
+    let generic = report.check("client without context").unwrap();
+    assert_eq!(generic.status, CheckStatus::Warn);
+    assert!(generic.detail.contains("context.default"));
+    assert!(report
+        .to_string()
+        .ends_with("7 passed, 1 warnings, 0 failed"));
+}
+
+#[tokio::test]
+async fn http_uses_the_configured_key_and_default_context() {
+    let mut config = NovaConfig::default();
+    config.server.transport = "http".to_string();
+    config.auth.enabled = true;
+    config.auth.allowed_keys = vec!["doctor-key".to_string()];
+    config.context.default = Some("group:-100".to_string());
+    config.validate().unwrap();
+    let report = doctor::run(NovaServer::in_memory(config.clone()).unwrap(), &config)
+        .await
+        .unwrap();
+    assert!(report.passed(), "{}", report);
+    assert_eq!(report.checks.len(), 8);
+    assert!(report
+        .checks
+        .iter()
+        .all(|check| check.status == CheckStatus::Pass));
+    let tools = report.check("tools/list").unwrap();
+    assert!(tools.detail.ends_with("tools for group:-100"), "{}", report);
+}
+
+#[tokio::test]
+async fn failed_initialize_fails_the_report() {
+    let mut config = NovaConfig::default();
+    config.server.transport = "http".to_string();
+    config.auth.enabled = true;
+    config.auth.mode = "jwt".to_string();
+    config.auth.jwt_secret = Some("secret".to_string());
+    let report = doctor::run(NovaServer::in_memory(config.clone()).unwrap(), &config)
+        .await
+        .unwrap();
+    assert!(!report.passed());
+    let initialize = report.check("initialize").unwrap();
+    assert_eq!(initialize.status, CheckStatus::Fail);
+    assert!(initialize.detail.contains("-32001"), "{}", report);
+    assert_eq!(report.checks.len(), 1);
+}
+
+#[test]
+fn default_context_is_validated_and_doctor_parsed() {
+    let mut config = NovaConfig::default();
+    for value in ["user", "planet:1", "user:abc"] {
+        config.context.default = Some(value.to_string());
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("context.default"), "{}", err);
+    }
+    config.context.default = Some("user:42".to_string());
+    assert_eq!(
+        config.context.default_context().unwrap().principal(),
+        "user:42"
+    );
+
+    let cli = CliArgs::parse(["doctor", "--transport=http"].map(String::from)).unwrap();
+    assert!(cli.doctor);
+    assert!(CliArgs::parse(["doctor=1".to_string()]).is_err());
+}
@@ -0,0 +1,140 @@
+#![cfg(all(feature = "dylib-tools", unix))]
+
+use nova_mcp::embed::build_server;
+use nova_mcp::plugins::{PluginContextType, RequestContext};
+use nova_mcp::server::ToolCall;
+use nova_mcp::storage;
+use nova_mcp::{NovaConfig, RegistryHandles};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Echoes its arguments and the caller's context, or fails on `"fail": true`.
+const ECHO: &str = r#"
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+uint32_t nova_tool_abi_version(void) { return 1; }
+
+const char *nova_tool_definition(void) {
+    return "{\"name\":\"echo_native\",\"description\":\"Echoes its input\","
+           "\"inputSchema\":{\"type\":\"object\",\"properties\":{\"fail\":{\"type\":\"boolean\"}}}}";
+}
+
+char *nova_tool_call(const char *arguments, const char *context) {
+    if (strstr(arguments, "\"fail\":true")) {
+        return strdup("{\"error\":\"asked to fail\"}");
+    }
+    size_t len = strlen(arguments) + strlen(context) + 32;
+    char *out = malloc(len);
+    snprintf(out, len, "{\"ok\":{\"args\":%s,\"context\":%s}}", arguments, context);
+    return out;
+}
+
+void nova_tool_free(char *result) { free(result); }
+"#;
+
+/// Built against a future ABI.
+const FUTURE_ABI: &str = r#"
+#include <stdint.h>
+uint32_t nova_tool_abi_version(void) { return 99; }
+const char *nova_tool_definition(void) { return "{}"; }
+char *nova_tool_call(const char *a, const char *c) { return 0; }
+void nova_tool_free(char *r) {}
+"#;
+
+#[tokio::test]
+async fn dylib_tools_load_from_the_configured_directory() {
+    let Some(dir) = plugins_dir("echo", &[("echo", ECHO)]) else {
+        return;
+    };
+    let mut config = NovaConfig::default();
+    config.dylib_tools.dir = Some(dir.display().to_string());
+    let handles = RegistryHandles::from_db(storage::open_temporary().unwrap()).unwrap();
+    let server = build_server(&config, &handles).unwrap();
+
+    let tools = server.get_tools(&context()).unwrap();
+    assert!(tools.iter().any(|tool| tool.name == "echo_native"));
+
+    let result = server
+        .handle_tool_call(call(json!({ "word": "hi" })), &context())
+        .await
+        .unwrap();
+    assert!(!result.is_error);
+    let structured = result.structured_content.unwrap();
+    assert_eq!(structured["args"], json!({ "word": "hi" }));
+    assert_eq!(structured["context"]["context_id"], "7");
+
+    let err = server
+        .handle_tool_call(call(json!({ "fail": true })), &context())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("asked to fail"), "{}", err);
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn dylib_tools_with_another_abi_version_stop_startup() {
+    let Some(dir) = plugins_dir("future", &[("echo", ECHO), ("future", FUTURE_ABI)]) else {
+        return;
+    };
+    let mut config = NovaConfig::default();
+    config.dylib_tools.dir = Some(dir.display().to_string());
+    let handles = RegistryHandles::from_db(storage::open_temporary().unwrap()).unwrap();
+    let err = build_server(&config, &handles).err().unwrap();
+    assert!(err.to_string().contains("ABI version 99"), "{}", err);
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+/// Compiles `sources` into a fresh directory, or returns None (skipping the
+/// test) when no C compiler is available.
+fn plugins_dir(name: &str, sources: &[(&str, &str)]) -> Option<PathBuf> {
+    let dir = std::env::temp_dir().join(format!("nova-dylib-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    for (stem, source) in sources {
+        if !compile(&dir, stem, source) {
+            eprintln!("skipping: no C compiler");
+            return None;
+        }
+    }
+    Some(dir)
+}
+
+fn compile(dir: &Path, stem: &str, source: &str) -> bool {
+    let src = dir.join(format!("{}.c", stem));
+    std::fs::write(&src, source).unwrap();
+    let lib = dir.join(format!("lib{}.{}", stem, std::env::consts::DLL_EXTENSION));
+    let status = Command::new("cc")
+        .args(["-shared", "-fPIC", "-o"])
+        .arg(&lib)
+        .arg(&src)
+        .status();
+    match status {
+        Ok(status) => {
+            assert!(status.success(), "cc failed for {}", stem);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+fn call(arguments: Value) -> ToolCall {
+    ToolCall {
+        name: "echo_native".into(),
+        arguments,
+        select: None,
+        format: None,
+        priority: None,
+    }
+}
+
+fn context() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "7".into(),
+        actor_id: None,
+    }
+}
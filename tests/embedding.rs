@@ -0,0 +1,65 @@
+use axum::routing::get;
+use axum::Router;
+use nova_mcp::client::{ClientConfig, NovaClient};
+use nova_mcp::embed::build_server;
+use nova_mcp::http::{router, AppState};
+use nova_mcp::plugins::{PluginContextType, PluginRegistrationRequest, RequestContext};
+use nova_mcp::storage::{self, migrations};
+use nova_mcp::{NovaConfig, RegistryHandles};
+use serde_json::json;
+use std::net::SocketAddr;
+
+#[tokio::test]
+async fn nova_routes_mount_in_a_host_app() {
+    let handles = RegistryHandles::from_db(storage::open_temporary().unwrap()).unwrap();
+    assert_eq!(
+        migrations::current_version(handles.db()).unwrap(),
+        migrations::supported_version()
+    );
+    let mut config = NovaConfig::default();
+    config.plugins.allowed_schemes.push("http".to_string());
+    config.plugins.allow_private_networks = true;
+    let server = build_server(&config, &handles).unwrap();
+
+    let app = Router::new()
+        .route("/host/status", get(|| async { "host" }))
+        .merge(router(AppState::new(server, &config)));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+    });
+
+    let host = reqwest::get(format!("{}/host/status", base_url))
+        .await
+        .unwrap();
+    assert_eq!(host.text().await.unwrap(), "host");
+    let health = reqwest::get(format!("{}/healthz", base_url)).await.unwrap();
+    assert_eq!(health.status(), 200);
+
+    let client = NovaClient::new(ClientConfig {
+        context: Some(RequestContext {
+            context_type: PluginContextType::User,
+            context_id: "5".to_string(),
+            actor_id: None,
+        }),
+        ..ClientConfig::new(&base_url)
+    });
+    let request: PluginRegistrationRequest = serde_json::from_value(json!({
+        "name": "echo",
+        "description": "Echoes its arguments",
+        "input_schema": { "type": "object" },
+        "endpoint_url": "http://127.0.0.1:9/invoke"
+    }))
+    .unwrap();
+    let plugin = client.register(&request).await.unwrap();
+    let tools = client.tools_list().await.unwrap();
+    assert!(tools.iter().any(|tool| tool.name == plugin.fq_name));
+    // The registry lives in the host's database
+    let metadata = handles.db().open_tree("plugin_metadata").unwrap();
+    assert!(!metadata.is_empty());
+}
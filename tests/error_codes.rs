@@ -0,0 +1,113 @@
+use nova_mcp::error::ErrorCategory;
+use nova_mcp::mcp::{dto::McpRequest, handler};
+use nova_mcp::plugins::PluginManager;
+use nova_mcp::{NovaConfig, NovaError, NovaServer};
+use serde_json::json;
+use std::sync::Arc;
+
+#[test]
+fn variants_map_to_stable_codes_and_categories() {
+    let cases = [
+        (
+            NovaError::rate_limit_exceeded("geckoterminal", Some(7)),
+            "rate_limited",
+            ErrorCategory::RateLimited,
+            true,
+        ),
+        (
+            NovaError::validation_error("bad"),
+            "validation_failed",
+            ErrorCategory::Validation,
+            false,
+        ),
+        (
+            NovaError::api_error("upstream down"),
+            "upstream_error",
+            ErrorCategory::Upstream,
+            false,
+        ),
+        (
+            NovaError::pool_not_found("0xabc"),
+            "pool_not_found",
+            ErrorCategory::NotFound,
+            false,
+        ),
+        (
+            NovaError::tool_disabled("search_pools"),
+            "tool_disabled",
+            ErrorCategory::PermissionDenied,
+            false,
+        ),
+        (
+            NovaError::tool_timeout("search_pools", 3),
+            "tool_timeout",
+            ErrorCategory::Timeout,
+            true,
+        ),
+        (
+            NovaError::internal("boom"),
+            "internal_error",
+            ErrorCategory::Internal,
+            false,
+        ),
+    ];
+    for (error, code, category, retryable) in cases {
+        assert_eq!(error.code(), code);
+        assert_eq!(error.category(), category);
+        assert_eq!(error.is_retryable(), retryable, "{}", code);
+    }
+}
+
+#[test]
+fn data_carries_variant_details() {
+    let data = NovaError::rate_limit_exceeded("geckoterminal", Some(7)).to_data();
+    assert_eq!(
+        data,
+        json!({
+            "code": "rate_limited",
+            "category": "rate_limited",
+            "retryable": true,
+            "details": { "api": "geckoterminal", "retry_after_secs": 7 }
+        })
+    );
+    assert_eq!(
+        NovaError::internal("boom").to_data()["details"],
+        json!(null)
+    );
+}
+
+#[tokio::test]
+async fn tool_errors_expose_data_over_json_rpc() {
+    let mut config = NovaConfig::default();
+    config.tools.disabled = vec!["search_pools".to_string()];
+    let server = test_server(config);
+    let req = McpRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: "tools/call".to_string(),
+        params: Some(json!({ "name": "search_pools", "arguments": { "query": "eth" } })),
+        context_type: Some("user".to_string()),
+        context_id: Some("1".to_string()),
+        actor_id: None,
+    };
+    let err = handler::handle_request(&server, req, None)
+        .await
+        .error
+        .expect("expected error response");
+    assert_eq!(err.code, -32603);
+    let data = err.data.unwrap();
+    assert_eq!(data["code"], "tool_disabled");
+    assert_eq!(data["category"], "permission_denied");
+    assert_eq!(data["details"]["tool"], "search_pools");
+}
+
+fn test_server(config: NovaConfig) -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager"),
+    );
+    NovaServer::new(config, plugin_manager)
+}
@@ -0,0 +1,139 @@
+use nova_mcp::plugins::{ErrorResponse, PluginContextType, RequestContext};
+use nova_mcp::test_util::TestServer;
+use nova_mcp::NovaConfig;
+use reqwest::{Method, Response, StatusCode};
+use serde_json::json;
+
+#[tokio::test]
+async fn extractor_rejections_are_json_with_a_code() {
+    let server = TestServer::start().await.unwrap();
+    let client = server.client(context());
+
+    let malformed = client
+        .request(Method::POST, "/v1/plugins/register")
+        .header("content-type", "application/json")
+        .body("{ not json")
+        .send()
+        .await
+        .unwrap();
+    assert_error(malformed, StatusCode::BAD_REQUEST, "invalid_json").await;
+
+    let wrong_shape = client
+        .request(Method::POST, "/v1/plugins/enable")
+        .json(&json!({ "plugin_id": "seven" }))
+        .send()
+        .await
+        .unwrap();
+    let body = assert_error(
+        wrong_shape,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "invalid_body",
+    )
+    .await;
+    assert_eq!(body.details.unwrap()["category"], "validation");
+
+    let not_json = client
+        .request(Method::POST, "/v1/plugins/enable")
+        .body("plugin_id=1")
+        .send()
+        .await
+        .unwrap();
+    assert_error(
+        not_json,
+        StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        "unsupported_media_type",
+    )
+    .await;
+
+    let bad_id = client
+        .request(Method::DELETE, "/v1/plugins/not-a-number")
+        .send()
+        .await
+        .unwrap();
+    assert_error(bad_id, StatusCode::BAD_REQUEST, "invalid_path").await;
+
+    let bad_query = client
+        .request(Method::GET, "/v1/admin/plugins/stale?days=many")
+        .send()
+        .await
+        .unwrap();
+    assert_error(bad_query, StatusCode::BAD_REQUEST, "invalid_query").await;
+
+    let rpc = client
+        .request(Method::POST, "/v1/rpc")
+        .header("content-type", "application/json")
+        .body("[")
+        .send()
+        .await
+        .unwrap();
+    assert_error(rpc, StatusCode::BAD_REQUEST, "invalid_json").await;
+}
+
+#[tokio::test]
+async fn framework_errors_are_wrapped() {
+    let mut config = NovaConfig::default();
+    config
+        .server
+        .route_body_limits
+        .insert("/plugins/register".to_string(), 32);
+    let server = TestServer::with_config(config).await.unwrap();
+    let client = server.client(context());
+
+    let unknown = client
+        .request(Method::GET, "/v1/nowhere")
+        .send()
+        .await
+        .unwrap();
+    let body = assert_error(unknown, StatusCode::NOT_FOUND, "not_found").await;
+    assert_eq!(body.error, "Not Found");
+
+    let wrong_method = client
+        .request(Method::PATCH, "/v1/plugins")
+        .send()
+        .await
+        .unwrap();
+    assert!(wrong_method.headers().contains_key("allow"));
+    assert_error(
+        wrong_method,
+        StatusCode::METHOD_NOT_ALLOWED,
+        "method_not_allowed",
+    )
+    .await;
+
+    let oversized = client
+        .request(Method::POST, "/v1/plugins/register")
+        .json(&json!({ "name": "x".repeat(64) }))
+        .send()
+        .await
+        .unwrap();
+    assert_error(
+        oversized,
+        StatusCode::PAYLOAD_TOO_LARGE,
+        "payload_too_large",
+    )
+    .await;
+
+    // Hand-built errors carry a code too
+    let no_context = reqwest::get(server.url("/v1/plugins")).await.unwrap();
+    assert_error(no_context, StatusCode::BAD_REQUEST, "bad_request").await;
+}
+
+/// Checks the status, the JSON content type and `details.code`.
+async fn assert_error(response: Response, status: StatusCode, code: &str) -> ErrorResponse {
+    assert_eq!(response.status(), status);
+    assert_eq!(response.headers()["content-type"], "application/json");
+    let body: ErrorResponse = response.json().await.unwrap();
+    assert!(!body.error.is_empty());
+    let details = body.details.as_ref().expect("details");
+    assert_eq!(details["code"], code, "{}", body.error);
+    assert!(details["retryable"].is_boolean());
+    body
+}
+
+fn context() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
@@ -0,0 +1,165 @@
+use axum::routing::post;
+use axum::{Json, Router};
+use nova_mcp::audit::AuditLog;
+use nova_mcp::events::{EventBus, EventCounters, EventKind, EventSubscriber, ServerEvent};
+use nova_mcp::plugins::{PluginContextType, PluginRegistrationRequest, RequestContext};
+use nova_mcp::test_util::{StubPlugin, TestServer};
+use nova_mcp::tools::upstream_health::UpstreamHealth;
+use nova_mcp::{NovaConfig, NovaError};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const ADMIN_TOKEN: &str = "ops-token";
+
+/// Fails every event, to show a broken subscriber does not stop the rest.
+struct Broken;
+
+impl EventSubscriber for Broken {
+    fn name(&self) -> &str {
+        "broken"
+    }
+
+    fn handle(&self, _event: &ServerEvent) -> nova_mcp::Result<()> {
+        Err(NovaError::internal("subscriber is down"))
+    }
+}
+
+#[tokio::test]
+async fn upstream_outages_reach_subscribers_and_streams() {
+    let bus = Arc::new(EventBus::new());
+    let counters = Arc::new(EventCounters::default());
+    let audit = Arc::new(AuditLog::in_memory());
+    bus.attach(Arc::new(Broken));
+    bus.attach(counters.clone());
+    bus.attach(audit.clone());
+    // Same name: replaces the counters above instead of counting twice
+    let fresh = Arc::new(EventCounters::default());
+    bus.attach(fresh.clone());
+    let mut stream = bus.subscribe();
+
+    let health = UpstreamHealth::default();
+    health.publish_to(bus.clone());
+    for _ in 0..4 {
+        health.record(
+            "api.example.com",
+            Duration::from_millis(5),
+            Some("HTTP 503".to_string()),
+        );
+    }
+
+    let counts = fresh.snapshot();
+    assert_eq!(counts.get("upstream_error"), Some(&4));
+    // Only the transition to down alerts
+    assert_eq!(counts.get("alert_fired"), Some(&1));
+    assert!(counters.snapshot().is_empty());
+
+    let mut alerts = Vec::new();
+    while let Ok(event) = stream.try_recv() {
+        if let EventKind::AlertFired { alert, message } = event.kind {
+            alerts.push((alert, message));
+        }
+    }
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].0, "upstream_down");
+    assert!(alerts[0].1.contains("api.example.com"), "{}", alerts[0].1);
+
+    let entries = audit.since(0, 10).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].action, "alert.fired");
+    assert_eq!(entries[0].target, "upstream_down");
+}
+
+#[tokio::test]
+async fn event_webhook_and_stats_see_tool_calls() {
+    let stub = StubPlugin::start().await.unwrap();
+    let (receiver, received) = event_receiver().await;
+    let mut config = NovaConfig::default();
+    config.admin.tokens = vec![ADMIN_TOKEN.to_string()];
+    config.events.webhook_url = Some(format!("http://{}/events", receiver));
+    config.events.webhook_events = vec!["plugin_registered".into(), "tool_called".into()];
+    let server = TestServer::with_config(config).await.unwrap();
+    let client = server.client(owner());
+    let plugin = client
+        .register(&registration(&stub.url("/invoke")))
+        .await
+        .unwrap();
+    let result = client
+        .tools_call(&plugin.fq_name, json!({ "city": "Porto" }))
+        .await
+        .unwrap();
+    assert_eq!(result["isError"], false, "{}", result);
+    server
+        .plugin_manager()
+        .events()
+        .publish(EventKind::RateLimited {
+            key: "user:5".to_string(),
+        });
+
+    let mut events = Vec::new();
+    for _ in 0..250 {
+        events = received.lock().unwrap().clone();
+        if events.len() >= 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let types: Vec<&str> = events.iter().filter_map(|e| e["type"].as_str()).collect();
+    // rate_limited is not in webhook_events
+    assert_eq!(types, ["plugin_registered", "tool_called"]);
+    assert_eq!(events[0]["fq_name"], plugin.fq_name.as_str());
+    assert_eq!(events[0]["owner"], "user:5");
+    assert_eq!(events[1]["tool"], plugin.fq_name.as_str());
+    assert_eq!(events[1]["context"], "user:5");
+    assert_eq!(events[1]["success"], true);
+    assert!(events[1]["id"].is_string());
+
+    let stats: Value = reqwest::Client::new()
+        .get(server.url("/v1/admin/stats"))
+        .header("x-admin-token", ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(stats["events"]["plugin_registered"], 1);
+    assert_eq!(stats["events"]["tool_called"], 1);
+    assert_eq!(stats["events"]["rate_limited"], 1);
+}
+
+/// Records every event POSTed to `/events`.
+async fn event_receiver() -> (SocketAddr, Arc<Mutex<Vec<Value>>>) {
+    let events: Arc<Mutex<Vec<Value>>> = Arc::default();
+    let seen = events.clone();
+    let app = Router::new().route(
+        "/events",
+        post(move |Json(event): Json<Value>| {
+            seen.lock().unwrap().push(event);
+            async {}
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    (addr, events)
+}
+
+fn registration(endpoint_url: &str) -> PluginRegistrationRequest {
+    serde_json::from_value(json!({
+        "name": "echo",
+        "description": "Echoes its arguments",
+        "input_schema": { "type": "object" },
+        "endpoint_url": endpoint_url
+    }))
+    .unwrap()
+}
+
+fn owner() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
@@ -0,0 +1,154 @@
+use nova_mcp::clock::{ManualClock, SharedClock};
+use nova_mcp::plugins::{
+    PluginContextType, PluginRegistrationRequest, PluginUpdateRequest, RequestContext,
+};
+use nova_mcp::test_util::{StubPlugin, TestClient, TestServer};
+use nova_mcp::NovaConfig;
+use reqwest::Method;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+#[tokio::test]
+async fn register_enable_call_update_and_roll_back() {
+    let stub = StubPlugin::start().await.unwrap();
+    let clock = ManualClock::at(1_700_000_000);
+    let server = TestServer::with_clock(NovaConfig::default(), SharedClock::new(clock.clone()))
+        .await
+        .unwrap();
+    let owner = server.client(user("5"));
+    let member = server.client(user("7"));
+
+    let v1 = owner
+        .register(&registration("echo", &stub.url("/v1")))
+        .await
+        .unwrap();
+    assert_eq!(v1.version, 1);
+    assert_eq!(v1.created_at, 1_700_000_000);
+    assert!(!tool_names(&member).await.contains(&v1.fq_name));
+
+    let status = member.enable(v1.plugin_id, true).await.unwrap();
+    assert!(status.enabled);
+    assert!(tool_names(&member).await.contains(&v1.fq_name));
+    let result = member
+        .tools_call(&v1.fq_name, json!({ "city": "Lisbon" }))
+        .await
+        .unwrap();
+    assert_eq!(result["isError"], false);
+    let calls = stub.calls();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].path, "/v1");
+    assert_eq!(calls[0].body["context_id"], "7");
+    assert_eq!(calls[0].body["arguments"]["city"], "Lisbon");
+
+    // A broken release: v2 points at a failing endpoint
+    let v2 = owner
+        .update(
+            v1.plugin_id,
+            &PluginUpdateRequest {
+                endpoint_url: Some(stub.url("/fail")),
+                ..PluginUpdateRequest::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(v2.version, 2);
+    let names = tool_names(&member).await;
+    assert!(names.contains(&v2.fq_name) && !names.contains(&v1.fq_name));
+    assert!(call_failed(&member, &v2.fq_name).await);
+
+    // Rolling back publishes v3 with the v1 endpoint; v1 stays callable
+    let v3 = owner
+        .update(
+            v1.plugin_id,
+            &PluginUpdateRequest {
+                endpoint_url: Some(stub.url("/v1")),
+                ..PluginUpdateRequest::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(v3.version, 3);
+    assert!(!call_failed(&member, &v3.fq_name).await);
+    assert!(!call_failed(&member, &v1.fq_name).await);
+    let paths: Vec<String> = stub.calls().into_iter().map(|call| call.path).collect();
+    assert_eq!(paths, ["/v1", "/fail", "/v1", "/v1"]);
+
+    clock.advance(Duration::from_secs(30));
+    assert!(!call_failed(&member, &v3.fq_name).await);
+    let listed = owner.list_plugins().await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].installs, 1);
+    assert_eq!(listed[0].last_used_at, Some(1_700_000_030));
+
+    member.enable(v1.plugin_id, false).await.unwrap();
+    assert!(!tool_names(&member).await.contains(&v3.fq_name));
+    assert!(call_failed(&member, &v3.fq_name).await);
+}
+
+#[tokio::test]
+async fn typed_helpers_report_http_failures() {
+    let server = TestServer::start().await.unwrap();
+    let owner = server.client(user("5"));
+
+    let mut invalid = registration("echo", "https://example.com/hook");
+    invalid.name = String::new();
+    let err = owner.register(&invalid).await.unwrap_err().to_string();
+    assert!(err.contains("400"), "{}", err);
+
+    let response = owner
+        .request(Method::PUT, "/plugins/999")
+        .json(&json!({ "description": "missing" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+    assert!(server.plugin_manager().list_plugins().unwrap().is_empty());
+}
+
+async fn tool_names(client: &TestClient) -> Vec<String> {
+    client
+        .tools_list()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|tool| tool.name)
+        .collect()
+}
+
+/// Whether the call failed, as a JSON-RPC error or an error result.
+async fn call_failed(client: &TestClient, name: &str) -> bool {
+    match client.tools_call(name, json!({})).await {
+        Ok(result) => result["isError"] == Value::Bool(true),
+        Err(_) => true,
+    }
+}
+
+fn user(context_id: &str) -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: context_id.to_string(),
+        actor_id: None,
+    }
+}
+
+fn registration(name: &str, endpoint: &str) -> PluginRegistrationRequest {
+    PluginRegistrationRequest {
+        name: name.to_string(),
+        description: "Echoes its arguments".to_string(),
+        owner_id: None,
+        input_schema: json!({ "type": "object" }),
+        output_schema: None,
+        endpoint_url: endpoint.to_string(),
+        script: None,
+        version: 1,
+        trust_level: Default::default(),
+        client_certificate: None,
+        credentials: None,
+        redact: Vec::new(),
+        request_template: None,
+        listing: None,
+        allowed_contexts: Vec::new(),
+        tags: Vec::new(),
+        coerce_arguments: false,
+    }
+}
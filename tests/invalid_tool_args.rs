@@ -17,16 +17,68 @@ async fn invalid_arguments_return_error() {
         })),
         context_type: Some("user".to_string()),
         context_id: Some("0".to_string()),
+        actor_id: None,
     };
     let resp = handler::handle_request(&server, req, None).await;
     assert!(resp.result.is_none());
     if let Some(err) = resp.error {
-        assert_eq!(err.code, -32603);
+        assert_eq!(err.code, -32602);
+        let data = err.data.unwrap();
+        assert_eq!(data["code"], "invalid_arguments");
+        assert_eq!(data["category"], "validation");
+        assert_eq!(data["retryable"], false);
+        assert_eq!(data["details"]["tool"], "get_gecko_networks");
+        assert_eq!(data["details"]["errors"][0]["field"], "arguments");
     } else {
         panic!("expected error response");
     }
 }
 
+#[tokio::test]
+async fn schema_violations_are_reported_per_field() {
+    let server = test_server();
+    let req = call(
+        "get_trending_pools",
+        json!({ "network": "  ", "limit": 50, "duration": "7d" }),
+    );
+    let err = handler::handle_request(&server, req, None)
+        .await
+        .error
+        .expect("expected error response");
+    assert_eq!(err.code, -32602);
+    let data = err.data.unwrap();
+    let mut fields: Vec<&str> = data["details"]["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["field"].as_str().unwrap())
+        .collect();
+    fields.sort();
+    assert_eq!(fields, vec!["duration", "limit", "network"]);
+
+    let req = call("get_gecko_pool", json!({ "network": "eth" }));
+    let err = handler::handle_request(&server, req, None)
+        .await
+        .error
+        .unwrap();
+    let data = err.data.unwrap();
+    let errors = &data["details"]["errors"];
+    assert_eq!(errors[0]["field"], "address");
+    assert!(errors[0]["message"].as_str().unwrap().contains("required"));
+}
+
+fn call(name: &str, arguments: serde_json::Value) -> McpRequest {
+    McpRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: "tools/call".to_string(),
+        params: Some(json!({ "name": name, "arguments": arguments })),
+        context_type: Some("user".to_string()),
+        context_id: Some("0".to_string()),
+        actor_id: None,
+    }
+}
+
 fn test_server() -> NovaServer {
     let config = NovaConfig::default();
     let db = sled::Config::new().temporary(true).open().unwrap();
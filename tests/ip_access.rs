@@ -0,0 +1,120 @@
+use axum::http::HeaderMap;
+use nova_mcp::config::AccessConfig;
+use nova_mcp::http::access::AccessRules;
+use nova_mcp::{NovaConfig, NovaServer, PluginManager};
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn ip(value: &str) -> IpAddr {
+    value.parse().unwrap()
+}
+
+#[test]
+fn deny_wins_and_admin_routes_are_stricter() {
+    let rules = AccessRules::new(&AccessConfig {
+        allow: vec!["10.0.0.0/8".into(), "192.0.2.7".into()],
+        deny: vec!["10.6.6.0/24".into()],
+        admin_allow: vec!["10.1.0.0/16".into()],
+        ..AccessConfig::default()
+    });
+    assert!(rules.permits(ip("10.2.3.4"), false));
+    assert!(rules.permits(ip("192.0.2.7"), false));
+    assert!(!rules.permits(ip("192.0.2.8"), false));
+    assert!(!rules.permits(ip("10.6.6.1"), false));
+    assert!(!rules.permits(ip("10.2.3.4"), true));
+    assert!(rules.permits(ip("10.1.9.9"), true));
+    // IPv4-mapped IPv6 peers match IPv4 rules
+    assert!(rules.permits(ip("::ffff:10.2.3.4"), false));
+
+    assert!(AccessRules::new(&AccessConfig::default()).permits(ip("203.0.113.1"), true));
+}
+
+#[test]
+fn forwarded_for_is_only_trusted_from_proxies() {
+    let rules = AccessRules::new(&AccessConfig {
+        trusted_proxies: vec!["10.0.0.0/8".into()],
+        ..AccessConfig::default()
+    });
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "x-forwarded-for",
+        "198.51.100.1, 203.0.113.9, 10.0.0.2".parse().unwrap(),
+    );
+    assert_eq!(rules.client_ip(ip("10.0.0.1"), &headers), ip("203.0.113.9"));
+    assert_eq!(
+        rules.client_ip(ip("203.0.113.50"), &headers),
+        ip("203.0.113.50")
+    );
+    assert_eq!(
+        rules.client_ip(ip("10.0.0.1"), &HeaderMap::new()),
+        ip("10.0.0.1")
+    );
+}
+
+#[tokio::test]
+async fn rejected_clients_get_403() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig::default();
+    config.server.port = port;
+    config.admin.tokens = vec!["ops-token".into()];
+    config.access.admin_allow = vec!["10.0.0.0/8".into()];
+    config.access.deny = vec!["198.51.100.0/24".into()];
+    config.access.trusted_proxies = vec!["127.0.0.1".into()];
+    let server = test_server(config.clone());
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let client = reqwest::Client::new();
+    let base = format!("http://127.0.0.1:{}", port);
+    let mut health = None;
+    for _ in 0..50 {
+        match client.get(format!("{}/healthz", base)).send().await {
+            Ok(resp) => {
+                health = Some(resp);
+                break;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(50)).await,
+        }
+    }
+    assert!(health.expect("server did not start").status().is_success());
+
+    let admin = client
+        .get(format!("{}/admin/stats", base))
+        .header("x-admin-token", "ops-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(admin.status(), 403);
+
+    let via_proxy = client
+        .get(format!("{}/admin/stats", base))
+        .header("x-admin-token", "ops-token")
+        .header("x-forwarded-for", "10.1.2.3")
+        .send()
+        .await
+        .unwrap();
+    assert!(via_proxy.status().is_success());
+
+    let denied = client
+        .get(format!("{}/healthz", base))
+        .header("x-forwarded-for", "198.51.100.4")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(denied.status(), 403);
+}
+
+fn test_server(config: NovaConfig) -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager"),
+    );
+    NovaServer::new(config, plugin_manager)
+}
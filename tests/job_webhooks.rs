@@ -0,0 +1,281 @@
+use axum::http::{HeaderMap, StatusCode as AxumStatus};
+use axum::routing::post;
+use axum::Router;
+use hmac::{Hmac, Mac};
+use nova_mcp::dead_letters::{DeadLetter, DeliveryKind};
+use nova_mcp::plugins::{
+    ErrorResponse, JobStatus, PluginContextType, PluginJob, PluginRegistrationRequest,
+    RequestContext, WebhookState,
+};
+use nova_mcp::test_util::{StubPlugin, TestServer};
+use nova_mcp::NovaConfig;
+use reqwest::{Method, StatusCode};
+use serde_json::json;
+use sha2::Sha256;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const SECRET: &str = "webhook-secret";
+const ADMIN_TOKEN: &str = "ops-token";
+
+type Deliveries = Arc<Mutex<Vec<(HeaderMap, String)>>>;
+
+#[tokio::test]
+async fn finished_jobs_are_posted_signed_to_the_webhook() {
+    let stub = StubPlugin::start().await.unwrap();
+    let (receiver, deliveries, _) = webhook_receiver().await;
+    let server = TestServer::with_config(config()).await.unwrap();
+    let client = server.client(owner());
+    let plugin = client
+        .register(&registration(&stub.url("/invoke")))
+        .await
+        .unwrap();
+
+    let url = format!("http://{}/hook", receiver);
+    let job = submit(&server, plugin.plugin_id, &url).await;
+    assert_eq!(job.webhook.unwrap().state, WebhookState::Pending);
+    wait_until(|| !deliveries.lock().unwrap().is_empty()).await;
+
+    let (headers, body) = deliveries.lock().unwrap().remove(0);
+    let timestamp = headers["x-nova-timestamp"].to_str().unwrap();
+    let signature = headers["x-nova-signature"].to_str().unwrap();
+    assert_eq!(signature, sign(timestamp, &body));
+    let delivered: PluginJob = serde_json::from_str(&body).unwrap();
+    assert_eq!(delivered.job_id, job.job_id);
+    assert_eq!(delivered.status, JobStatus::Succeeded);
+    assert_eq!(
+        delivered.result.unwrap()["received"]["arguments"]["city"],
+        "Porto"
+    );
+
+    wait_until_state(&server, &job.job_id, WebhookState::Delivered).await;
+}
+
+#[tokio::test]
+async fn failed_deliveries_are_dead_lettered_until_requeued() {
+    let stub = StubPlugin::start().await.unwrap();
+    let (receiver, deliveries, up) = webhook_receiver().await;
+    up.store(false, Ordering::SeqCst);
+    let server = TestServer::with_config(config()).await.unwrap();
+    let client = server.client(owner());
+    let plugin = client
+        .register(&registration(&stub.url("/invoke")))
+        .await
+        .unwrap();
+
+    let url = format!("http://{}/hook", receiver);
+    let job = submit(&server, plugin.plugin_id, &url).await;
+    wait_until_state(&server, &job.job_id, WebhookState::DeadLettered).await;
+    // Every attempt reached the receiver before giving up
+    assert_eq!(deliveries.lock().unwrap().len(), 3);
+
+    let http = reqwest::Client::new();
+    let letters: Vec<DeadLetter> = http
+        .get(server.url("/v1/admin/dead-letters"))
+        .header("x-admin-token", ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(letters.len(), 1);
+    let letter = &letters[0];
+    assert_eq!(letter.delivery.kind, DeliveryKind::JobWebhook);
+    assert_eq!(letter.delivery.reference, job.job_id);
+    assert_eq!(letter.delivery.context.as_deref(), Some("user:5"));
+    assert_eq!(letter.attempts, 3);
+    assert!(letter.last_error.contains("500"), "{}", letter.last_error);
+
+    let requeue = server.url(&format!("/v1/admin/dead-letters/{}/requeue", letter.id));
+    let response = http
+        .post(&requeue)
+        .header("x-admin-token", ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert!(!response.status().is_success());
+    let letters: Vec<DeadLetter> = http
+        .get(server.url("/v1/admin/dead-letters"))
+        .header("x-admin-token", ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!((letters[0].attempts, letters[0].requeues), (4, 1));
+
+    up.store(true, Ordering::SeqCst);
+    let response = http
+        .post(&requeue)
+        .header("x-admin-token", ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    wait_until_state(&server, &job.job_id, WebhookState::Delivered).await;
+    let letters: Vec<DeadLetter> = http
+        .get(server.url("/v1/admin/dead-letters"))
+        .header("x-admin-token", ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(letters.is_empty());
+    let response = http
+        .delete(server.url(&format!("/v1/admin/dead-letters/{}", letter.id)))
+        .header("x-admin-token", ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn webhooks_need_a_secret_and_an_async_call() {
+    let stub = StubPlugin::start().await.unwrap();
+    let mut unsigned = config();
+    unsigned.plugins.webhook_secret = None;
+    let server = TestServer::with_config(unsigned).await.unwrap();
+    let client = server.client(owner());
+    let plugin = client
+        .register(&registration(&stub.url("/invoke")))
+        .await
+        .unwrap();
+
+    for path in [
+        format!("/v1/plugins/{}/call?async=true", plugin.plugin_id),
+        format!("/v1/plugins/{}/call", plugin.plugin_id),
+    ] {
+        let response = client
+            .request(Method::POST, &path)
+            .json(&json!({
+                "arguments": { "city": "Porto" },
+                "webhook_url": "http://127.0.0.1:9/hook"
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", path);
+        let error: ErrorResponse = response.json().await.unwrap();
+        assert!(error.error.contains("webhook_url"), "{}", error.error);
+    }
+}
+
+/// Records every POST; answers `500` while the returned flag is false.
+async fn webhook_receiver() -> (SocketAddr, Deliveries, Arc<AtomicBool>) {
+    let deliveries: Deliveries = Arc::default();
+    let up = Arc::new(AtomicBool::new(true));
+    let (seen, healthy) = (deliveries.clone(), up.clone());
+    let app = Router::new().route(
+        "/hook",
+        post(move |headers: HeaderMap, body: String| {
+            seen.lock().unwrap().push((headers, body));
+            let status = if healthy.load(Ordering::SeqCst) {
+                AxumStatus::OK
+            } else {
+                AxumStatus::INTERNAL_SERVER_ERROR
+            };
+            async move { status }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    (addr, deliveries, up)
+}
+
+fn sign(timestamp: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    let digest: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("sha256={}", digest)
+}
+
+fn config() -> NovaConfig {
+    let mut config = NovaConfig::default();
+    config.admin.tokens = vec![ADMIN_TOKEN.to_string()];
+    config.plugins.webhook_secret = Some(SECRET.to_string());
+    config.plugins.webhook_attempts = 3;
+    config.plugins.webhook_retry_base_ms = 10;
+    config
+}
+
+async fn submit(server: &TestServer, plugin_id: u64, webhook_url: &str) -> PluginJob {
+    let response = server
+        .client(owner())
+        .request(
+            Method::POST,
+            &format!("/v1/plugins/{}/call?async=true", plugin_id),
+        )
+        .json(&json!({ "arguments": { "city": "Porto" }, "webhook_url": webhook_url }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    response.json().await.unwrap()
+}
+
+async fn read_job(server: &TestServer, job_id: &str) -> PluginJob {
+    server
+        .client(owner())
+        .request(Method::GET, &format!("/v1/jobs/{}", job_id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+async fn wait_until_state(server: &TestServer, job_id: &str, state: WebhookState) {
+    for _ in 0..250 {
+        let job = read_job(server, job_id).await;
+        if job.webhook.as_ref().map(|webhook| webhook.state) == Some(state) {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("webhook of job {} never reached {:?}", job_id, state);
+}
+
+async fn wait_until(done: impl Fn() -> bool) {
+    for _ in 0..250 {
+        if done() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("condition not met within 5s");
+}
+
+fn registration(endpoint_url: &str) -> PluginRegistrationRequest {
+    serde_json::from_value(json!({
+        "name": "echo",
+        "description": "Echoes its arguments",
+        "input_schema": {
+            "type": "object",
+            "properties": { "city": { "type": "string" } }
+        },
+        "endpoint_url": endpoint_url
+    }))
+    .unwrap()
+}
+
+fn owner() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
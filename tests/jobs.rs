@@ -0,0 +1,159 @@
+use nova_mcp::clock::{ManualClock, SharedClock};
+use nova_mcp::config::{AdminConfig, NovaConfig};
+use nova_mcp::jobs::{JobOutcome, JobScheduler};
+use nova_mcp::plugins::PluginManager;
+use nova_mcp::{NovaError, NovaServer};
+use serde_json::Value;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+// Paused Tokio time runs the 20ms intervals instantly, in a fixed order
+#[tokio::test(start_paused = true)]
+async fn failing_and_panicking_jobs_keep_their_schedule() {
+    let clock = ManualClock::at(1_700_000_000);
+    let scheduler = JobScheduler::new().with_clock(SharedClock::new(clock.clone()));
+    let ticks = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&ticks);
+    scheduler
+        .register("tick", Duration::from_millis(20), move || {
+            let counter = Arc::clone(&counter);
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        })
+        .unwrap();
+    scheduler
+        .register("fail", Duration::from_millis(20), || async {
+            Err(NovaError::api_error("upstream said no"))
+        })
+        .unwrap();
+    scheduler
+        .register_with_jitter(
+            "panic",
+            Duration::from_millis(20),
+            Duration::ZERO,
+            || async { panic!("boom") },
+        )
+        .unwrap();
+    let duplicate = scheduler.register("tick", Duration::from_secs(1), || async { Ok(()) });
+    assert!(duplicate.is_err());
+    assert!(scheduler
+        .register("never", Duration::ZERO, || async { Ok(()) })
+        .is_err());
+
+    // Nothing runs before start
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert_eq!(ticks.load(Ordering::SeqCst), 0);
+    assert!(scheduler
+        .snapshot()
+        .iter()
+        .all(|job| job.next_run_at.is_none()));
+
+    scheduler.start();
+    scheduler.start();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let jobs = scheduler.snapshot();
+    let names: Vec<&str> = jobs.iter().map(|job| job.name.as_str()).collect();
+    assert_eq!(names, ["fail", "panic", "tick"]);
+
+    let fail = &jobs[0];
+    assert!(fail.runs >= 2, "{:?}", fail);
+    assert_eq!(fail.failures, fail.runs);
+    assert_eq!(fail.last_outcome, Some(JobOutcome::Failed));
+    assert!(fail
+        .last_error
+        .as_ref()
+        .unwrap()
+        .contains("upstream said no"));
+
+    let panic = &jobs[1];
+    assert!(panic.runs >= 2, "{:?}", panic);
+    assert_eq!(panic.last_outcome, Some(JobOutcome::Panicked));
+    assert_eq!(panic.last_error.as_deref(), Some("panicked: boom"));
+
+    let tick = &jobs[2];
+    assert_eq!(tick.last_outcome, Some(JobOutcome::Succeeded));
+    assert_eq!(tick.last_started_at, Some(1_700_000_000));
+    assert_eq!(tick.last_finished_at, Some(1_700_000_000));
+    assert_eq!(tick.failures, 0);
+    assert!(tick.last_error.is_none());
+    assert!(ticks.load(Ordering::SeqCst) >= 2);
+
+    // Jobs registered after start run too
+    let late = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&late);
+    scheduler
+        .register("late", Duration::from_millis(20), move || {
+            let counter = Arc::clone(&counter);
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        })
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(late.load(Ordering::SeqCst) >= 1);
+}
+
+#[tokio::test]
+async fn admin_jobs_lists_builtin_jobs() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig {
+        admin: AdminConfig {
+            tokens: vec!["ops-token".into()],
+            ..AdminConfig::default()
+        },
+        ..NovaConfig::default()
+    };
+    config.server.port = port;
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(
+            db.open_tree("plugin_metadata").unwrap(),
+            db.open_tree("user_plugins").unwrap(),
+            db.open_tree("group_plugins").unwrap(),
+        )
+        .expect("init plugin manager"),
+    );
+    let server = NovaServer::new(config.clone(), plugin_manager);
+    server.start_jobs();
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let base = format!("http://127.0.0.1:{}", port);
+    let client = reqwest::Client::new();
+    let mut response = None;
+    for _ in 0..50 {
+        if let Ok(r) = client
+            .get(format!("{}/admin/jobs", base))
+            .header("x-admin-token", "ops-token")
+            .send()
+            .await
+        {
+            response = Some(r);
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let response = response.expect("server did not start");
+    assert_eq!(response.status(), 200);
+    let jobs: Value = response.json().await.unwrap();
+    let refresh = &jobs[0];
+    assert_eq!(refresh["name"], "gecko_networks_refresh");
+    assert_eq!(refresh["interval_secs"], 3600);
+    assert_eq!(refresh["runs"], 0);
+    assert!(refresh["next_run_at"].as_i64().is_some());
+    assert!(refresh["last_outcome"].is_null());
+
+    let unauthorized = client
+        .get(format!("{}/admin/jobs", base))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(unauthorized.status(), 401);
+}
@@ -0,0 +1,92 @@
+use axum::{routing::get, Json, Router};
+use nova_mcp::{NovaConfig, NovaServer};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+#[tokio::test]
+async fn bursts_beyond_the_queue_are_shed() {
+    // A slow upstream keeps the first call in flight
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let upstream = format!("http://{}", listener.local_addr().unwrap());
+    let app = Router::new().route(
+        "/networks",
+        get(|| async {
+            tokio::time::sleep(Duration::from_millis(800)).await;
+            Json(json!({ "data": [] }))
+        }),
+    );
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig::default();
+    config.server.port = port;
+    config.apis.gecko_terminal_url = Some(upstream);
+    config.server.max_concurrent_requests = 1;
+    config.server.max_queued_requests = 1;
+    config.server.queue_timeout_ms = 200;
+    config.admin.tokens = vec!["ops-token".into()];
+    let server = NovaServer::in_memory(config.clone()).unwrap();
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let client = reqwest::Client::new();
+    let base = format!("http://127.0.0.1:{}", port);
+    for _ in 0..50 {
+        if client.get(format!("{}/healthz", base)).send().await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let call = || {
+        client
+            .post(format!("{}/rpc", base))
+            .header("x-nova-context-type", "user")
+            .header("x-nova-context-id", "7")
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "tools/call",
+                "params": { "name": "get_gecko_networks", "arguments": {} }
+            }))
+            .send()
+    };
+
+    let first = tokio::spawn(call());
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let queued = tokio::spawn(call());
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let shed = call().await.unwrap();
+    assert_eq!(shed.status(), 503);
+    assert_eq!(shed.headers()["retry-after"], "1");
+    // Probes bypass the queue
+    let health = client
+        .get(format!("{}/healthz", base))
+        .send()
+        .await
+        .unwrap();
+    assert!(health.status().is_success());
+
+    assert_eq!(queued.await.unwrap().unwrap().status(), 503);
+    let first: Value = first.await.unwrap().unwrap().json().await.unwrap();
+    assert!(first["error"].is_null(), "{}", first);
+
+    let stats: Value = client
+        .get(format!("{}/admin/stats", base))
+        .header("x-admin-token", "ops-token")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let load = &stats["load"];
+    assert_eq!(load["max_in_flight"], 1);
+    assert_eq!(load["in_flight"], 1);
+    assert_eq!(load["queued"], 0);
+    assert_eq!(load["peak_queued"], 1);
+    assert_eq!(load["shed_queue_full"], 1);
+    assert_eq!(load["shed_timed_out"], 1);
+}
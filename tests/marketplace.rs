@@ -0,0 +1,260 @@
+use nova_mcp::plugins::{
+    MarketplaceQuery, PluginContextType, PluginListing, PluginManager, PluginRegistrationRequest,
+    PluginUpdateRequest, RequestContext,
+};
+use nova_mcp::{NovaConfig, NovaServer};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn only_approved_listings_are_catalogued() {
+    let manager = PluginManager::in_memory().unwrap();
+    let owner = context(PluginContextType::User, "5");
+    let listed = manager
+        .register_plugin(&owner, registration("weather", Some(listing(&["data"]))))
+        .unwrap();
+    manager
+        .register_plugin(&owner, registration("private", None))
+        .unwrap();
+    // Owners cannot approve their own listing
+    assert!(!listed.listing.unwrap().approved);
+    assert!(manager
+        .marketplace(&MarketplaceQuery::default())
+        .unwrap()
+        .is_empty());
+
+    manager.review_listing(listed.plugin_id, true).unwrap();
+    for id in ["-100", "-200"] {
+        manager
+            .install_listed(
+                listed.plugin_id,
+                &RequestContext {
+                    actor_id: Some("7".to_string()),
+                    ..context(PluginContextType::Group, id)
+                },
+            )
+            .unwrap();
+    }
+    let entries = manager.marketplace(&MarketplaceQuery::default()).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].name, "weather");
+    // The owner's own record does not count as an install
+    assert_eq!(entries[0].installs, 2);
+    assert_eq!(entries[0].categories, ["data"]);
+
+    let query = |category: Option<&str>, q: Option<&str>| MarketplaceQuery {
+        category: category.map(str::to_string),
+        q: q.map(str::to_string),
+    };
+    assert_eq!(
+        manager
+            .marketplace(&query(Some("defi"), None))
+            .unwrap()
+            .len(),
+        0
+    );
+    assert_eq!(
+        manager
+            .marketplace(&query(None, Some("WEATH")))
+            .unwrap()
+            .len(),
+        1
+    );
+
+    // A description change keeps the approval, a new endpoint does not
+    manager
+        .update_plugin(
+            &owner,
+            listed.plugin_id,
+            PluginUpdateRequest {
+                description: Some("Forecasts".to_string()),
+                ..PluginUpdateRequest::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        manager
+            .marketplace(&query(None, Some("forecast")))
+            .unwrap()
+            .len(),
+        1
+    );
+    let moved = manager
+        .update_plugin(
+            &owner,
+            listed.plugin_id,
+            PluginUpdateRequest {
+                endpoint_url: Some("https://example.org/weather".to_string()),
+                ..PluginUpdateRequest::default()
+            },
+        )
+        .unwrap();
+    assert!(!moved.listing.unwrap().approved);
+    assert!(manager
+        .install_listed(listed.plugin_id, &context(PluginContextType::User, "8"))
+        .is_err());
+    assert_eq!(manager.listed_plugins().len(), 1);
+
+    let mut invalid = registration("bad", Some(listing(&["Not A Slug"])));
+    assert!(manager.register_plugin(&owner, invalid.clone()).is_err());
+    invalid.listing = Some(PluginListing {
+        icon_url: Some("http://example.com/icon.png".to_string()),
+        ..listing(&[])
+    });
+    assert!(manager.register_plugin(&owner, invalid).is_err());
+}
+
+#[tokio::test]
+async fn browse_install_and_review_over_http() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig::default();
+    config.server.port = port;
+    config.admin.tokens = vec!["ops-token".into()];
+    let server = NovaServer::new(
+        config.clone(),
+        Arc::new(PluginManager::in_memory().unwrap()),
+    );
+    let plugin = server
+        .plugin_manager()
+        .register_plugin(
+            &context(PluginContextType::User, "5"),
+            registration("weather", Some(listing(&["data"]))),
+        )
+        .unwrap();
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let client = reqwest::Client::new();
+    let base = format!("http://127.0.0.1:{}", port);
+    for _ in 0..50 {
+        if client.get(format!("{}/healthz", base)).send().await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let install = format!("{}/marketplace/{}/install", base, plugin.plugin_id);
+    let group = |builder: reqwest::RequestBuilder| {
+        builder
+            .header("x-nova-context-type", "group")
+            .header("x-nova-context-id", "-100")
+            .header("x-nova-actor-id", "7")
+    };
+
+    // Pending review: neither listed nor installable
+    let catalog: Value = client
+        .get(format!("{}/marketplace", base))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(catalog, json!([]));
+    let pending = group(client.post(&install)).send().await.unwrap();
+    assert_eq!(pending.status(), 404);
+
+    let review = format!("{}/admin/marketplace/{}", base, plugin.plugin_id);
+    let unauthorized = client
+        .put(&review)
+        .json(&json!({ "approved": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(unauthorized.status(), 401);
+    let reviewed: Value = client
+        .put(&review)
+        .header("x-admin-token", "ops-token")
+        .json(&json!({ "approved": true }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(reviewed["listing"]["approved"], true);
+
+    let status: Value = group(client.post(&install))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(status["enabled"], true);
+    assert_eq!(status["added_by"], "7");
+
+    let catalog: Value = client
+        .get(format!("{}/marketplace?category=data", base))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(catalog[0]["installs"], 1);
+    assert_eq!(catalog[0]["publisher"], "acme");
+    // The catalog does not disclose endpoints or owner contexts
+    assert!(catalog[0].get("endpoint_url").is_none());
+    assert!(catalog[0].get("context_id").is_none());
+
+    let audit: Value = client
+        .get(format!("{}/admin/audit", base))
+        .header("x-admin-token", "ops-token")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let actions: Vec<&str> = audit["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|entry| entry["action"].as_str())
+        .collect();
+    assert!(actions.contains(&"admin.marketplace.review"));
+    assert!(actions.contains(&"plugin.install"));
+}
+
+fn context(context_type: PluginContextType, context_id: &str) -> RequestContext {
+    RequestContext {
+        context_type,
+        context_id: context_id.to_string(),
+        actor_id: None,
+    }
+}
+
+fn listing(categories: &[&str]) -> PluginListing {
+    PluginListing {
+        categories: categories.iter().map(|c| c.to_string()).collect(),
+        icon_url: Some("https://example.com/icon.png".to_string()),
+        approved: true,
+        flagged: false,
+    }
+}
+
+fn registration(name: &str, listing: Option<PluginListing>) -> PluginRegistrationRequest {
+    PluginRegistrationRequest {
+        name: name.to_string(),
+        description: "Weather data".to_string(),
+        owner_id: Some("acme".to_string()),
+        input_schema: json!({ "type": "object" }),
+        output_schema: None,
+        endpoint_url: "https://example.com/hook".to_string(),
+        script: None,
+        version: 1,
+        trust_level: Default::default(),
+        client_certificate: None,
+        credentials: None,
+        redact: Vec::new(),
+        request_template: None,
+        listing,
+        allowed_contexts: Vec::new(),
+        tags: Vec::new(),
+        coerce_arguments: false,
+    }
+}
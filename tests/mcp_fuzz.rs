@@ -0,0 +1,334 @@
+// Property-based fuzzing of request parsing and dispatch: whatever arrives,
+// the server answers with a well-formed JSON-RPC 2.0 response and never
+// panics. `fuzz/` runs the same entry points under cargo-fuzz.
+use nova_mcp::mcp::handler::{handle_request, parse_request, request_from_value};
+use nova_mcp::stdio::{serve, Framing};
+use nova_mcp::{NovaConfig, NovaServer, PluginManager};
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestRunner};
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+const METHODS: &[&str] = &[
+    "initialize",
+    "ping",
+    "tools/list",
+    "tools/call",
+    "completion/complete",
+    "logging/setLevel",
+    "notifications/initialized",
+];
+
+#[test]
+fn envelopes_get_spec_compliant_responses() {
+    let (runtime, server) = setup();
+    runner()
+        .run(&envelope(), |value| {
+            let response = match request_from_value(value.clone()) {
+                Ok(request) => {
+                    let id = request.id.clone();
+                    let response = runtime.block_on(handle_request(&server, request, None));
+                    assert_eq!(response.id, id, "the request id is echoed");
+                    response
+                }
+                Err(response) => {
+                    assert_eq!(response.error.as_ref().map(|e| e.code), Some(-32600));
+                    *response
+                }
+            };
+            assert_compliant(&serde_json::to_value(&response).unwrap());
+            Ok(())
+        })
+        .unwrap();
+}
+
+#[test]
+fn stdio_answers_every_line() {
+    let (runtime, server) = setup();
+    runner()
+        .run(&prop::collection::vec(line(), 1..8), |lines| {
+            let mut input = Vec::new();
+            let mut expected = 0;
+            for line in &lines {
+                input.extend_from_slice(line);
+                input.push(b'\n');
+                expected += usize::from(expects_reply(line));
+            }
+            let mut output = Vec::new();
+            runtime
+                .block_on(serve(&server, &input[..], &mut output, Framing::Newline))
+                .unwrap();
+            let responses: Vec<Value> = String::from_utf8(output)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str::<Value>(line).unwrap())
+                .filter(|message| message.get("method").is_none())
+                .collect();
+            assert_eq!(responses.len(), expected, "{:?}", lines);
+            responses.iter().for_each(assert_compliant);
+            Ok(())
+        })
+        .unwrap();
+}
+
+#[test]
+fn stdio_survives_arbitrary_frames() {
+    let (runtime, server) = setup();
+    let chunk = prop_oneof![
+        prop::collection::vec(any::<u8>(), 0..64),
+        envelope().prop_map(|value| {
+            let body = value.to_string();
+            format!("Content-Length: {}\r\n\r\n{}", body.len(), body).into_bytes()
+        }),
+        (0usize..64).prop_map(|length| format!("Content-Length: {}\r\n\r\n", length).into_bytes()),
+        Just(b"Content-Length: 99999999999999999999\r\n\r\n".to_vec()),
+        Just(b"Content-Length: 2\r\n\r\n\xff\xfe".to_vec()),
+    ];
+    runner()
+        .run(&prop::collection::vec(chunk, 1..6), |chunks| {
+            let input = chunks.concat();
+            for framing in [Framing::Auto, Framing::ContentLength] {
+                let mut output = Vec::new();
+                runtime
+                    .block_on(serve(&server, &input[..], &mut output, framing))
+                    .unwrap();
+                for message in messages(&output) {
+                    if message.get("method").is_none() {
+                        assert_compliant(&message);
+                    }
+                }
+            }
+            Ok(())
+        })
+        .unwrap();
+}
+
+#[tokio::test]
+async fn malformed_input_gets_the_matching_error() {
+    let cases = [
+        ("{\"jsonrpc\":\"2.0\",", -32700, Value::Null),
+        ("[]", -32600, Value::Null),
+        (
+            r#"{"jsonrpc":"1.0","id":3,"method":"ping"}"#,
+            -32600,
+            json!(3),
+        ),
+        (
+            r#"{"jsonrpc":"2.0","id":{"a":1},"method":"ping"}"#,
+            -32600,
+            Value::Null,
+        ),
+        (
+            r#"{"jsonrpc":"2.0","id":"x","method":7}"#,
+            -32600,
+            json!("x"),
+        ),
+        (
+            r#"{"jsonrpc":"2.0","id":4,"method":"nope"}"#,
+            -32601,
+            json!(4),
+        ),
+    ];
+    let server = test_server();
+    for (message, code, id) in cases {
+        let response = match parse_request(message) {
+            Ok(request) => handle_request(&server, request, None).await,
+            Err(response) => *response,
+        };
+        let response = serde_json::to_value(&response).unwrap();
+        assert_compliant(&response);
+        assert_eq!(response["error"]["code"], code, "{}", message);
+        assert_eq!(response["id"], id, "{}", message);
+    }
+
+    // Deep nesting hits the parser's recursion limit instead of the stack,
+    // and neither it nor invalid UTF-8 ends the session
+    let mut input = "[".repeat(100_000).into_bytes();
+    input.extend_from_slice(b"\n\xff\xfe{}\n");
+    input.extend_from_slice(br#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#);
+    input.extend_from_slice(b"\n{\"jsonrpc\":\"2.0\",\"id\":9,\"method\":\"ping\"}\n");
+    let mut output = Vec::new();
+    serve(&server, &input[..], &mut output, Framing::Newline)
+        .await
+        .unwrap();
+    let responses = messages(&output);
+    let codes: Vec<&Value> = responses.iter().map(|r| &r["error"]["code"]).collect();
+    assert_eq!(codes, [&json!(-32700), &json!(-32700), &Value::Null]);
+    assert_eq!(responses[2]["id"], 9);
+    assert!(responses[2].get("error").is_none());
+}
+
+fn setup() -> (Runtime, NovaServer) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    (runtime, test_server())
+}
+
+fn test_server() -> NovaServer {
+    NovaServer::new(
+        NovaConfig::default(),
+        Arc::new(PluginManager::in_memory().unwrap()),
+    )
+}
+
+fn runner() -> TestRunner {
+    TestRunner::new(Config {
+        cases: 128,
+        failure_persistence: None,
+        ..Config::default()
+    })
+}
+
+/// A JSON-RPC 2.0 response: `jsonrpc`, an `id`, and exactly one of `result`
+/// and `error`, with error codes in the reserved range only where defined.
+fn assert_compliant(response: &Value) {
+    assert_eq!(response["jsonrpc"], "2.0", "{}", response);
+    let id = response.get("id").expect("id is always present");
+    assert!(
+        matches!(id, Value::Null | Value::String(_) | Value::Number(_)),
+        "{}",
+        response
+    );
+    match (response.get("result"), response.get("error")) {
+        (Some(_), None) => {}
+        (None, Some(error)) => {
+            let code = error["code"].as_i64().expect("integer code");
+            if (-32768..=-32000).contains(&code) {
+                assert!(
+                    matches!(code, -32700 | -32603..=-32600 | -32099..=-32000),
+                    "{}",
+                    response
+                );
+            }
+            assert!(error["message"].as_str().is_some_and(|m| !m.is_empty()));
+        }
+        _ => panic!("exactly one of result and error: {}", response),
+    }
+}
+
+/// Decodes newline- or `Content-Length`-framed output.
+fn messages(output: &[u8]) -> Vec<Value> {
+    let text = String::from_utf8(output.to_vec()).unwrap();
+    let mut messages = Vec::new();
+    let mut rest = text.as_str();
+    while !rest.is_empty() {
+        let (message, tail) = match rest.strip_prefix("Content-Length: ") {
+            Some(framed) => {
+                let (length, body) = framed.split_once("\r\n\r\n").unwrap();
+                body.split_at(length.parse().unwrap())
+            }
+            None => rest.split_once('\n').unwrap(),
+        };
+        messages.push(serde_json::from_str(message).unwrap());
+        rest = tail;
+    }
+    messages
+}
+
+/// Whether `line` is owed a reply: everything but blank lines and notifications.
+fn expects_reply(line: &[u8]) -> bool {
+    match std::str::from_utf8(line) {
+        Ok(text) if text.trim().is_empty() => false,
+        Ok(text) => !matches!(parse_request(text.trim()), Ok(request) if request.id.is_none()),
+        Err(_) => true,
+    }
+}
+
+fn json_value() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::from),
+        any::<u64>().prop_map(Value::from),
+        any::<f64>().prop_map(Value::from),
+        ".{0,16}".prop_map(Value::String),
+    ];
+    leaf.prop_recursive(24, 96, 4, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..4).prop_map(Value::Array),
+            prop::collection::btree_map(".{0,8}", inner, 0..4)
+                .prop_map(|map| Value::Object(map.into_iter().collect())),
+        ]
+    })
+}
+
+fn absurd_id() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        Just(json!(u64::MAX)),
+        Just(json!(i64::MIN)),
+        Just(json!(f64::MAX)),
+        Just(json!(-0.0)),
+        Just(json!("")),
+        "\\PC{0,64}".prop_map(Value::String),
+        (1usize..4096).prop_map(|len| Value::String("9".repeat(len))),
+        json_value(),
+    ]
+}
+
+/// Request-shaped objects with each member possibly missing or mistyped.
+fn envelope() -> impl Strategy<Value = Value> {
+    let method = prop_oneof![
+        prop::sample::select(METHODS).prop_map(Value::from),
+        "[a-z/_]{0,16}".prop_map(Value::String),
+        json_value(),
+    ];
+    let params = prop_oneof![
+        json_value(),
+        ("[a-z_]{0,12}", json_value())
+            .prop_map(|(name, arguments)| json!({ "name": name, "arguments": arguments })),
+        prop::sample::select(vec!["debug", "shout"]).prop_map(|level| json!({ "level": level })),
+    ];
+    let jsonrpc = prop_oneof![Just(json!("2.0")), Just(json!("1.0")), json_value()];
+    (
+        prop::option::weighted(0.9, jsonrpc),
+        prop::option::weighted(0.8, absurd_id()),
+        prop::option::weighted(0.95, method),
+        prop::option::of(params),
+        prop::option::of(prop::sample::select(vec!["user", "group", "bogus"])),
+        prop::option::of("[0-9a-z-]{0,12}"),
+        prop::bool::weighted(0.05),
+    )
+        .prop_map(
+            |(jsonrpc, id, method, params, context_type, context_id, wrap)| {
+                let mut object = Map::new();
+                let members = [
+                    ("jsonrpc", jsonrpc),
+                    ("id", id),
+                    ("method", method),
+                    ("params", params),
+                    ("context_type", context_type.map(Value::from)),
+                    ("context_id", context_id.map(Value::from)),
+                ];
+                for (key, value) in members {
+                    if let Some(value) = value {
+                        object.insert(key.to_string(), value);
+                    }
+                }
+                // Batches are not part of the stdio transport
+                if wrap {
+                    Value::Array(vec![Value::Object(object)])
+                } else {
+                    Value::Object(object)
+                }
+            },
+        )
+}
+
+/// One stdio line: an envelope, invalid UTF-8, absurd nesting or noise.
+fn line() -> impl Strategy<Value = Vec<u8>> {
+    prop_oneof![
+        4 => envelope().prop_map(|value| value.to_string().into_bytes()),
+        1 => prop::collection::vec(any::<u8>(), 0..32).prop_map(|mut bytes| {
+            bytes.retain(|&b| b != b'\n');
+            bytes.push(0xff);
+            bytes
+        }),
+        1 => (1usize..512).prop_map(|depth| {
+            format!("{}{}", "{\"a\":[".repeat(depth), "]}".repeat(depth)).into_bytes()
+        }),
+        1 => "[^\n]{0,32}".prop_map(String::into_bytes),
+    ]
+}
@@ -0,0 +1,107 @@
+use nova_mcp::mcp::dto::McpRequest;
+use nova_mcp::mcp::handler::handle_session_request;
+use nova_mcp::mcp::logging::{self, ClientLogger, LogLevel};
+use nova_mcp::mcp::session::McpSession;
+use nova_mcp::stdio::{serve, Framing};
+use nova_mcp::{NovaConfig, NovaServer, PluginManager};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+fn set_level(level: &str) -> McpRequest {
+    McpRequest {
+        jsonrpc: "2.0".into(),
+        id: Some(json!(1)),
+        method: "logging/setLevel".into(),
+        params: Some(json!({ "level": level })),
+        context_type: None,
+        context_id: None,
+        actor_id: None,
+    }
+}
+
+#[tokio::test]
+async fn set_level_is_stored_per_session() {
+    let server = test_server();
+    let mut session = McpSession::new();
+    assert_eq!(session.log_level(), None);
+
+    let response = handle_session_request(&server, &mut session, set_level("warning"), None).await;
+    assert!(response.error.is_none());
+    assert_eq!(session.log_level(), Some(LogLevel::Warning));
+
+    let response = handle_session_request(&server, &mut session, set_level("loud"), None).await;
+    assert_eq!(response.error.unwrap().code, -32602);
+    assert_eq!(session.log_level(), Some(LogLevel::Warning));
+}
+
+#[tokio::test]
+async fn log_filters_by_level_and_needs_a_scope() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    logging::log(LogLevel::Error, "test", json!("outside"));
+
+    let logger = ClientLogger::new(LogLevel::Warning, tx);
+    logging::scope(Some(logger), async {
+        logging::log(LogLevel::Info, "test", json!("too quiet"));
+        logging::log(LogLevel::Error, "test", json!("kept"));
+    })
+    .await;
+
+    let note = rx.try_recv().unwrap();
+    assert_eq!(note["method"], "notifications/message");
+    assert_eq!(note["params"]["level"], "error");
+    assert_eq!(note["params"]["data"], "kept");
+    assert!(rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn stdio_sends_notifications_before_the_response() {
+    let server = test_server();
+    let input = concat!(
+        r#"{"jsonrpc":"2.0","id":1,"method":"logging/setLevel","params":{"level":"debug"}}"#,
+        "\n",
+        r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"no_such_tool","arguments":{}},"context_type":"user","context_id":"42"}"#,
+        "\n",
+    );
+    let mut output = Vec::new();
+    serve(&server, input.as_bytes(), &mut output, Framing::Newline)
+        .await
+        .unwrap();
+
+    let lines: Vec<Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0]["id"], 1);
+    assert_eq!(lines[1]["method"], "notifications/message");
+    assert_eq!(lines[1]["params"]["data"]["message"], "Tool started");
+    assert_eq!(lines[1]["params"]["data"]["tool"], "no_such_tool");
+    assert_eq!(lines[2]["id"], 2);
+}
+
+#[tokio::test]
+async fn stdio_stays_quiet_without_set_level() {
+    let server = test_server();
+    let input = concat!(
+        r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"no_such_tool","arguments":{}},"context_type":"user","context_id":"42"}"#,
+        "\n",
+    );
+    let mut output = Vec::new();
+    serve(&server, input.as_bytes(), &mut output, Framing::Newline)
+        .await
+        .unwrap();
+    assert_eq!(String::from_utf8(output).unwrap().lines().count(), 1);
+}
+
+fn test_server() -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager"),
+    );
+    NovaServer::new(NovaConfig::default(), plugin_manager)
+}
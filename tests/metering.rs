@@ -0,0 +1,251 @@
+use axum::{http::StatusCode, routing::post, Json, Router};
+use nova_mcp::config::PluginsConfig;
+use nova_mcp::metering::{Metering, MeteringSink, UsageEvent, UsageLedger, UsageQuery};
+use nova_mcp::plugins::{
+    EgressPolicy, PluginContextType, PluginManager, PluginRegistrationRequest, RequestContext,
+};
+use nova_mcp::{NovaConfig, NovaServer};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn event(id: &str, at: i64, context: &str, plugin: &str, success: bool) -> UsageEvent {
+    UsageEvent {
+        id: id.to_string(),
+        at,
+        context: context.to_string(),
+        actor_id: None,
+        plugin_id: 1,
+        plugin: plugin.to_string(),
+        owner: "user:5".to_string(),
+        duration_ms: 10,
+        request_bytes: 100,
+        response_bytes: 40,
+        success,
+    }
+}
+
+#[test]
+fn ledger_totals_a_billing_period() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let ledger = UsageLedger::persistent(db.open_tree("metering_ledger").unwrap());
+    ledger
+        .append(&event("a", 1_000, "user:1", "user_5_hook_v1", true))
+        .unwrap();
+    ledger
+        .append(&event("b", 2_000, "user:1", "user_5_hook_v1", false))
+        .unwrap();
+    ledger
+        .append(&event("c", 2_500, "group:-100", "user_5_hook_v1", true))
+        .unwrap();
+    ledger
+        .append(&event("d", 3_000, "user:1", "user_5_hook_v1", true))
+        .unwrap();
+
+    let period = UsageQuery {
+        since: Some(1_000),
+        until: Some(3_000),
+        ..UsageQuery::default()
+    };
+    let lines = ledger.summarize(&period).unwrap();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0].context, "group:-100");
+    let user = &lines[1];
+    assert_eq!((user.calls, user.failed_calls), (2, 1));
+    assert_eq!((user.request_bytes, user.response_bytes), (200, 80));
+    assert_eq!(user.duration_ms, 20);
+
+    let filtered = UsageQuery {
+        context: Some("user:1".to_string()),
+        ..UsageQuery::default()
+    };
+    let ids: Vec<String> = ledger
+        .events(&filtered)
+        .unwrap()
+        .into_iter()
+        .map(|event| event.id)
+        .collect();
+    assert_eq!(ids, ["a", "b", "d"]);
+}
+
+#[derive(Default)]
+struct Recorder(Mutex<Vec<UsageEvent>>);
+
+impl MeteringSink for Recorder {
+    fn name(&self) -> &str {
+        "recorder"
+    }
+
+    fn record(&self, event: &UsageEvent) -> nova_mcp::Result<()> {
+        self.0.lock().unwrap().push(event.clone());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn plugin_calls_emit_usage_events() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let app = Router::new()
+        .route("/hook", post(|| async { Json(json!({ "ok": true })) }))
+        .route(
+            "/broken",
+            post(|| async { (StatusCode::BAD_GATEWAY, "down") }),
+        );
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let recorder = Arc::new(Recorder::default());
+    let metering = Metering::new()
+        .with_ledger(UsageLedger::in_memory())
+        .with_sink(recorder.clone());
+    let config = PluginsConfig {
+        allowed_schemes: vec!["http".into()],
+        allow_private_networks: true,
+        ..PluginsConfig::default()
+    };
+    let manager = PluginManager::in_memory()
+        .unwrap()
+        .with_egress_policy(EgressPolicy::new(&config))
+        .with_metering(Arc::new(metering));
+
+    let hook = manager
+        .register_plugin(
+            &owner(),
+            registration("hook", &format!("http://127.0.0.1:{}/hook", port)),
+        )
+        .unwrap();
+    let broken = manager
+        .register_plugin(
+            &owner(),
+            registration("broken", &format!("http://127.0.0.1:{}/broken", port)),
+        )
+        .unwrap();
+    manager
+        .invoke_plugin(&hook, &owner(), json!({}))
+        .await
+        .unwrap();
+    assert!(manager
+        .invoke_plugin(&broken, &owner(), json!({}))
+        .await
+        .is_err());
+    // Refused before the endpoint is reached, so not metered
+    let stranger = RequestContext {
+        context_id: "6".to_string(),
+        ..owner()
+    };
+    assert!(manager
+        .invoke_plugin(&hook, &stranger, json!({}))
+        .await
+        .is_err());
+
+    let events = recorder.0.lock().unwrap().clone();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].plugin, hook.fq_name);
+    assert_eq!(events[0].context, "user:5");
+    assert_eq!(events[0].owner, "user:5");
+    assert!(events[0].success);
+    assert!(events[0].request_bytes > 0);
+    assert_eq!(events[0].response_bytes, 11);
+    assert!(!events[1].success);
+    assert_eq!(events[1].response_bytes, 4);
+    assert_ne!(events[0].id, events[1].id);
+
+    // Events within one second are ordered by id in the ledger
+    let ledger = manager.metering().unwrap().ledger().unwrap();
+    let mut stored = ledger.events(&UsageQuery::default()).unwrap();
+    let mut recorded = events;
+    stored.sort_by(|a, b| a.id.cmp(&b.id));
+    recorded.sort_by(|a, b| a.id.cmp(&b.id));
+    assert_eq!(stored, recorded);
+}
+
+#[tokio::test]
+async fn admins_read_usage_reports() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig::default();
+    config.server.port = port;
+    config.admin.tokens = vec!["ops-token".into()];
+    let ledger = UsageLedger::in_memory();
+    ledger
+        .append(&event("a", 1_000, "user:1", "user_5_hook_v1", true))
+        .unwrap();
+    ledger
+        .append(&event("b", 1_500, "user:1", "user_5_other_v1", true))
+        .unwrap();
+    let manager = PluginManager::in_memory()
+        .unwrap()
+        .with_metering(Arc::new(Metering::new().with_ledger(ledger)));
+    let server = NovaServer::new(config.clone(), Arc::new(manager));
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let client = reqwest::Client::new();
+    let base = format!("http://127.0.0.1:{}", port);
+    for _ in 0..50 {
+        if client.get(format!("{}/healthz", base)).send().await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let url = format!("{}/admin/metering/usage", base);
+
+    let unauthorized = client.get(&url).send().await.unwrap();
+    assert_eq!(unauthorized.status(), 401);
+
+    let report: Value = client
+        .get(format!("{}?since=1000&plugin=user_5_hook_v1", url))
+        .header("x-admin-token", "ops-token")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(report["since"], 1000);
+    let lines = report["lines"].as_array().unwrap();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["calls"], 1);
+    assert_eq!(lines[0]["request_bytes"], 100);
+
+    let mut invalid = NovaConfig::default();
+    invalid.metering.enabled = true;
+    invalid.metering.sinks = vec!["kafka".to_string()];
+    let err = invalid.validate().unwrap_err().to_string();
+    assert!(err.contains("metering.sinks"), "{}", err);
+    invalid.metering.sinks = vec!["webhook".to_string()];
+    let err = invalid.validate().unwrap_err().to_string();
+    assert!(err.contains("metering.webhook_url"), "{}", err);
+}
+
+fn owner() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
+
+fn registration(name: &str, endpoint: &str) -> PluginRegistrationRequest {
+    PluginRegistrationRequest {
+        name: name.to_string(),
+        description: "test".to_string(),
+        owner_id: None,
+        input_schema: json!({ "type": "object" }),
+        output_schema: None,
+        endpoint_url: endpoint.to_string(),
+        script: None,
+        version: 1,
+        trust_level: Default::default(),
+        client_certificate: None,
+        credentials: None,
+        redact: Vec::new(),
+        request_template: None,
+        listing: None,
+        allowed_contexts: Vec::new(),
+        tags: Vec::new(),
+        coerce_arguments: false,
+    }
+}
@@ -0,0 +1,100 @@
+use nova_mcp::error::{NovaError, Result};
+use nova_mcp::storage::migrations::{self, current_version, migrate, Migration};
+
+fn temp_db() -> sled::Db {
+    sled::Config::new().temporary(true).open().unwrap()
+}
+
+/// Version 2 moves enablement keys from `<id>` to `user:<id>`.
+fn prefix_user_keys(db: &sled::Db) -> Result<()> {
+    let tree = db.open_tree("user_plugins")?;
+    for entry in tree.iter() {
+        let (key, value) = entry?;
+        if !key.starts_with(b"user:") {
+            let mut prefixed = b"user:".to_vec();
+            prefixed.extend_from_slice(&key);
+            tree.insert(prefixed, value)?;
+            tree.remove(key)?;
+        }
+    }
+    Ok(())
+}
+
+const UPGRADED: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "baseline",
+        apply: |_| Ok(()),
+    },
+    Migration {
+        version: 2,
+        description: "prefix user enablement keys",
+        apply: prefix_user_keys,
+    },
+];
+
+#[test]
+fn fresh_databases_get_the_current_version() {
+    let db = temp_db();
+    assert_eq!(current_version(&db).unwrap(), 0);
+    let report = migrations::run(&db).unwrap();
+    assert_eq!(
+        (report.from, report.to),
+        (0, migrations::supported_version())
+    );
+    assert_eq!(report.applied.len(), migrations::MIGRATIONS.len());
+    assert_eq!(
+        current_version(&db).unwrap(),
+        migrations::supported_version()
+    );
+
+    let again = migrations::run(&db).unwrap();
+    assert!(again.applied.is_empty());
+    assert_eq!(again.from, again.to);
+}
+
+#[test]
+fn pending_migrations_apply_in_order_once() {
+    let db = temp_db();
+    migrate(&db, &UPGRADED[..1]).unwrap();
+    let tree = db.open_tree("user_plugins").unwrap();
+    tree.insert("42", "enabled").unwrap();
+
+    let report = migrate(&db, UPGRADED).unwrap();
+    assert_eq!((report.from, report.to), (1, 2));
+    let applied: Vec<u32> = report.applied.iter().map(|m| m.version).collect();
+    assert_eq!(applied, [2]);
+    assert_eq!(
+        tree.get("user:42").unwrap().as_deref(),
+        Some(&b"enabled"[..])
+    );
+    assert!(tree.get("42").unwrap().is_none());
+
+    // Applied migrations are recorded next to the version
+    let history = db.open_tree("schema").unwrap();
+    assert!(history.get("migration:0002").unwrap().is_some());
+    assert!(migrate(&db, UPGRADED).unwrap().applied.is_empty());
+}
+
+#[test]
+fn newer_schemas_are_refused() {
+    let db = temp_db();
+    migrate(&db, UPGRADED).unwrap();
+    let err = migrate(&db, &UPGRADED[..1]).unwrap_err();
+    match &err {
+        NovaError::SchemaTooNew { found, supported } => {
+            assert_eq!((*found, *supported), (2, 1));
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+    assert_eq!(err.code(), "schema_too_new");
+    assert_eq!(current_version(&db).unwrap(), 2);
+}
+
+#[test]
+fn gaps_in_the_migration_list_are_rejected() {
+    let db = temp_db();
+    let err = migrate(&db, &UPGRADED[1..]).unwrap_err();
+    assert!(err.to_string().contains("out of order"), "{}", err);
+    assert_eq!(current_version(&db).unwrap(), 0);
+}
@@ -0,0 +1,130 @@
+use futures::future::BoxFuture;
+use nova_mcp::mcp::dto::Tool;
+use nova_mcp::plugins::{PluginContextType, RequestContext};
+use nova_mcp::server::ToolCall;
+use nova_mcp::tools::ToolProvider;
+use nova_mcp::{NovaConfig, NovaError, NovaServer};
+use serde_json::{json, Value};
+
+/// Greets the calling context by name.
+struct Greeter;
+
+impl ToolProvider for Greeter {
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "greet".to_string(),
+            description: "Greets the caller".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": { "name": { "type": "string" } },
+                "required": ["name"]
+            }),
+            annotations: None,
+            output_schema: None,
+        }
+    }
+
+    fn call<'a>(
+        &'a self,
+        arguments: Value,
+        context: &'a RequestContext,
+    ) -> BoxFuture<'a, nova_mcp::Result<Value>> {
+        Box::pin(async move {
+            Ok(json!({
+                "greeting": format!("Hello, {}", arguments["name"].as_str().unwrap_or_default()),
+                "context": context.key(),
+            }))
+        })
+    }
+}
+
+/// Claims the name of a built-in tool.
+struct Impostor;
+
+impl ToolProvider for Impostor {
+    fn definition(&self) -> Tool {
+        Tool {
+            name: "get_gecko_pool".to_string(),
+            ..Greeter.definition()
+        }
+    }
+
+    fn call<'a>(
+        &'a self,
+        _arguments: Value,
+        _context: &'a RequestContext,
+    ) -> BoxFuture<'a, nova_mcp::Result<Value>> {
+        Box::pin(async { Ok(Value::Null) })
+    }
+}
+
+#[tokio::test]
+async fn native_tools_are_listed_validated_and_called() {
+    let server = NovaServer::in_memory(NovaConfig::default())
+        .unwrap()
+        .with_tool(Greeter)
+        .unwrap();
+
+    let names: Vec<_> = server
+        .get_tools(&context())
+        .unwrap()
+        .into_iter()
+        .map(|tool| tool.name)
+        .collect();
+    let greet = names.iter().position(|name| name == "greet").unwrap();
+    let builtin = names
+        .iter()
+        .position(|name| name == "get_gecko_pool")
+        .unwrap();
+    assert!(builtin < greet, "{:?}", names);
+
+    let result = server
+        .handle_tool_call(call(json!({ "name": "Ada" })), &context())
+        .await
+        .unwrap();
+    assert!(!result.is_error);
+    let structured = result.structured_content.unwrap();
+    assert_eq!(structured["greeting"], "Hello, Ada");
+    assert_eq!(structured["context"], "user:1");
+
+    let err = server
+        .handle_tool_call(call(json!({})), &context())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, NovaError::InvalidArguments { ref tool, .. } if tool == "greet"),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn native_tools_cannot_take_existing_names() {
+    let server = NovaServer::in_memory(NovaConfig::default()).unwrap();
+    let err = server.with_tool(Impostor).err().unwrap();
+    assert!(err.to_string().contains("get_gecko_pool"), "{}", err);
+
+    let server = NovaServer::in_memory(NovaConfig::default())
+        .unwrap()
+        .with_tool(Greeter)
+        .unwrap();
+    assert!(server.with_tool(Greeter).is_err());
+}
+
+fn call(arguments: Value) -> ToolCall {
+    ToolCall {
+        name: "greet".into(),
+        arguments,
+        select: None,
+        format: None,
+        priority: None,
+    }
+}
+
+fn context() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "1".into(),
+        actor_id: None,
+    }
+}
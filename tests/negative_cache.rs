@@ -0,0 +1,37 @@
+use nova_mcp::tools::negative_cache::NegativeCache;
+
+#[test]
+fn remembers_not_found_lookups() {
+    let cache = NegativeCache::in_memory(60);
+    let key = NegativeCache::key("token", "ETH", "0xABC");
+    assert!(!cache.contains(&key).unwrap());
+    cache.insert(&key).unwrap();
+    assert!(cache
+        .contains(&NegativeCache::key("token", "eth", "0xabc"))
+        .unwrap());
+    assert!(!cache
+        .contains(&NegativeCache::key("pool", "eth", "0xabc"))
+        .unwrap());
+}
+
+#[test]
+fn persistent_cache_survives_reopen() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let key = NegativeCache::key("pool", "solana", "So1anaAddr");
+    NegativeCache::persistent(db.open_tree("negative_cache").unwrap(), 60)
+        .insert(&key)
+        .unwrap();
+    let reopened = NegativeCache::persistent(db.open_tree("negative_cache").unwrap(), 60);
+    assert!(reopened.contains(&key).unwrap());
+    // base58 addresses are case-sensitive
+    assert!(!reopened
+        .contains(&NegativeCache::key("pool", "solana", "so1anaaddr"))
+        .unwrap());
+}
+
+#[test]
+fn zero_ttl_disables_caching() {
+    let cache = NegativeCache::in_memory(0);
+    cache.insert("token:eth:0x1").unwrap();
+    assert!(!cache.contains("token:eth:0x1").unwrap());
+}
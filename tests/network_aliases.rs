@@ -0,0 +1,134 @@
+use axum::{extract::Path, routing::get, Json, Router};
+use nova_mcp::error::NovaError;
+use nova_mcp::mcp::{dto::McpRequest, handler};
+use nova_mcp::plugins::PluginManager;
+use nova_mcp::tools::gecko_terminal::NetworkAliases;
+use nova_mcp::{NovaConfig, NovaServer};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+const POOL: &str = "0xc6962004f452be9203591991d15f6b388e09e8d0";
+
+fn networks() -> Value {
+    json!({ "data": [
+        { "id": "eth", "attributes": { "name": "Ethereum", "coingecko_asset_platform_id": "ethereum" } },
+        { "id": "arbitrum", "attributes": { "name": "Arbitrum", "coingecko_asset_platform_id": "arbitrum-one" } },
+        { "id": "bsc", "attributes": { "name": "BNB Chain", "coingecko_asset_platform_id": "binance-smart-chain" } },
+        { "id": "polygon_pos", "attributes": { "name": "Polygon POS", "coingecko_asset_platform_id": "polygon-pos" } }
+    ] })
+}
+
+#[test]
+fn resolves_names_tickers_and_slugs() {
+    let aliases = NetworkAliases::new(Duration::ZERO);
+    // Built-in aliases work before any list is learned; unknown names pass through
+    assert_eq!(aliases.resolve("Ethereum").unwrap(), "eth");
+    assert_eq!(aliases.resolve("zksync").unwrap(), "zksync");
+
+    aliases.learn(&networks());
+    for (input, slug) in [
+        ("ETH", "eth"),
+        (" ethereum ", "eth"),
+        ("Arbitrum One", "arbitrum"),
+        ("arbitrum-one", "arbitrum"),
+        ("bnb chain", "bsc"),
+        ("MATIC", "polygon_pos"),
+        ("polygon pos", "polygon_pos"),
+    ] {
+        assert_eq!(aliases.resolve(input).unwrap(), slug, "{}", input);
+    }
+
+    match aliases.resolve("etherium").unwrap_err() {
+        NovaError::UnknownNetwork {
+            network,
+            suggestions,
+        } => {
+            assert_eq!(network, "etherium");
+            assert_eq!(suggestions.first().map(String::as_str), Some("eth"));
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+    assert_eq!(
+        aliases.slugs(),
+        vec!["eth", "arbitrum", "bsc", "polygon_pos"]
+    );
+
+    // A learned list restarts the refresh interval
+    assert!(!aliases.claim_refresh());
+    let refreshing = NetworkAliases::new(Duration::from_secs(3600));
+    assert!(refreshing.claim_refresh());
+    refreshing.learn(&networks());
+    assert!(!refreshing.claim_refresh());
+
+    // A zero interval fetches the list once
+    let once = NetworkAliases::new(Duration::ZERO);
+    assert!(once.claim_refresh());
+    assert!(!once.claim_refresh());
+}
+
+#[tokio::test]
+async fn tools_call_normalizes_network_arguments() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let app = Router::new()
+        .route("/networks", get(|| async { Json(networks()) }))
+        .route(
+            "/networks/:network/pools/:address",
+            get(
+                |Path((network, address)): Path<(String, String)>| async move {
+                    Json(json!({ "data": { "id": format!("{}_{}", network, address) } }))
+                },
+            ),
+        );
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let mut config = NovaConfig::default();
+    config.apis.gecko_terminal_url = Some(format!("http://127.0.0.1:{}", port));
+    let server = test_server(config);
+
+    let response = handler::handle_request(&server, get_pool("Arbitrum One"), None).await;
+    let text = response.result.expect("pool result")["content"][0]["text"].clone();
+    let result: Value = serde_json::from_str(text.as_str().unwrap()).unwrap();
+    assert_eq!(result["pool"]["data"]["id"], format!("arbitrum_{}", POOL));
+
+    let response = handler::handle_request(&server, get_pool("arbitrun"), None).await;
+    let error = response.error.expect("unknown network");
+    assert_eq!(error.code, -32602);
+    let data = error.data.unwrap();
+    assert_eq!(data["code"], "unknown_network");
+    assert_eq!(data["details"]["suggestions"][0], "arbitrum");
+}
+
+#[test]
+fn gecko_terminal_url_must_be_http() {
+    let mut config = NovaConfig::default();
+    config.apis.gecko_terminal_url = Some("ftp://gecko".to_string());
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("apis.gecko_terminal_url"), "{}", err);
+}
+
+fn get_pool(network: &str) -> McpRequest {
+    McpRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: "tools/call".to_string(),
+        params: Some(json!({
+            "name": "get_gecko_pool",
+            "arguments": { "network": network, "address": POOL }
+        })),
+        context_type: Some("user".to_string()),
+        context_id: Some("3".to_string()),
+        actor_id: None,
+    }
+}
+
+fn test_server(config: NovaConfig) -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager"),
+    );
+    NovaServer::new(config, plugin_manager)
+}
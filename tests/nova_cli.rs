@@ -0,0 +1,236 @@
+// Runs the `nova-cli` binary against a `TestServer`.
+use nova_mcp::test_util::{StubPlugin, TestServer};
+use nova_mcp::NovaConfig;
+use std::path::PathBuf;
+use std::process::{Output, Stdio};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+const ADMIN_TOKEN: &str = "ops-token";
+
+#[tokio::test]
+async fn register_enable_call_and_read_logs() {
+    let stub = StubPlugin::start().await.unwrap();
+    let server = server().await;
+    let manifest = temp_file(
+        "echo.toml",
+        &format!(
+            r#"
+name = "echo"
+description = "Echoes its arguments"
+tags = ["testing"]
+
+[endpoint]
+url = "{}"
+
+[schemas.input]
+type = "object"
+
+[auth]
+type = "bearer"
+token = "${{ECHO_TOKEN}}"
+"#,
+            stub.url("/v1")
+        ),
+    );
+
+    // Placeholders must be set
+    let unset = cli(&server, &["--context", "user:5", "register"], &manifest).await;
+    assert_eq!(unset.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&unset.stderr).contains("unset variable ECHO_TOKEN"));
+    let registered = command(
+        &server,
+        &[
+            "--context",
+            "user:5",
+            "register",
+            manifest.to_str().unwrap(),
+        ],
+    )
+    .env("ECHO_TOKEN", "s3cret")
+    .output()
+    .await
+    .unwrap();
+    assert_success(&registered);
+    assert!(stdout(&registered).starts_with("Registered user_5_echo_v1 (plugin "));
+    let plugin = &server.plugin_manager().list_plugins().unwrap()[0];
+    assert_eq!(plugin.credential_headers, vec!["authorization"]);
+    assert_eq!(plugin.tags, vec!["testing"]);
+    let plugin_id = plugin.plugin_id;
+
+    let enabled = run(
+        &server,
+        &["--context=user:7", "enable", &plugin_id.to_string()],
+    )
+    .await;
+    assert_success(&enabled);
+    assert_eq!(
+        stdout(&enabled),
+        format!("Plugin {} enabled for user:7\n", plugin_id)
+    );
+    let tools = run(&server, &["--context", "user:7", "tools"]).await;
+    assert!(stdout(&tools).contains("user_5_echo_v1"));
+
+    let called = run(
+        &server,
+        &[
+            "--context",
+            "user:7",
+            "call",
+            "user_5_echo_v1",
+            r#"{"city": "Lisbon"}"#,
+        ],
+    )
+    .await;
+    assert_success(&called);
+    assert!(stdout(&called).contains("Lisbon"));
+    assert_eq!(stub.calls()[0].body["arguments"]["city"], "Lisbon");
+
+    // Invocation logs come from the metering ledger and need the admin token
+    let denied = run(&server, &["logs"]).await;
+    assert_eq!(denied.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&denied.stderr).contains("401"));
+    let logs = run(&server, &["--admin-token", ADMIN_TOKEN, "logs"]).await;
+    assert_success(&logs);
+    let lines: Vec<String> = stdout(&logs).lines().map(str::to_string).collect();
+    assert_eq!(lines.len(), 1, "{:?}", lines);
+    assert!(lines[0].contains("user:7") && lines[0].contains("user_5_echo_v1"));
+    assert!(lines[0].contains(" ok "));
+    let filtered = run(
+        &server,
+        &[
+            "--admin-token",
+            ADMIN_TOKEN,
+            "logs",
+            "--caller",
+            "user:5",
+            "--plugin",
+            "user_5_echo_v1",
+        ],
+    )
+    .await;
+    assert_eq!(stdout(&filtered), "");
+
+    let disabled = run(
+        &server,
+        &["--context", "user:7", "disable", &plugin_id.to_string()],
+    )
+    .await;
+    assert!(stdout(&disabled).contains("disabled for user:7"));
+}
+
+#[tokio::test]
+async fn reads_commands_from_stdin() {
+    let server = server().await;
+    let input = "\
+tools
+call get_my_usage {}
+
+call get_my_usage {\"unexpected\": 1}
+frobnicate
+exit
+tools
+";
+    let mut child = command(&server, &["--context", "user:9", "--json"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(input.as_bytes()).await.unwrap();
+    drop(stdin);
+    let output = child.wait_with_output().await.unwrap();
+
+    // A failed line does not end the session, but sets the exit code
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("JSON-RPC error -32602"), "{}", stderr);
+    assert!(stderr.contains("Invalid command: frobnicate"), "{}", stderr);
+    // Nothing after `exit` runs
+    let stdout = stdout(&output);
+    assert_eq!(stdout.matches("\"name\": \"get_my_usage\"").count(), 1);
+    assert_eq!(stdout.matches("\"isError\": false").count(), 1);
+}
+
+#[tokio::test]
+async fn rejects_bad_usage() {
+    let server = server().await;
+    for (args, message) in [
+        (&["--verbose", "tools"][..], "Unknown option: --verbose"),
+        (
+            &["--context", "planet:1", "tools"][..],
+            "Unknown context type",
+        ),
+        (&["enable", "abc"][..], "Invalid plugin id"),
+        (&["call", "get_my_usage", "{"][..], "not valid JSON"),
+        (
+            &["register", "/nonexistent/echo.toml"][..],
+            "Failed to read",
+        ),
+    ] {
+        let output = run(&server, args).await;
+        assert_eq!(output.status.code(), Some(1), "{:?}", args);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(message), "{:?}: {}", args, stderr);
+    }
+    let help = run(&server, &["help"]).await;
+    assert!(stdout(&help).starts_with("Usage: nova-cli"));
+}
+
+async fn server() -> TestServer {
+    let mut config = NovaConfig::default();
+    config.admin.tokens = vec![ADMIN_TOKEN.to_string()];
+    config.metering.enabled = true;
+    config.plugins.secrets_key = Some("MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=".to_string());
+    TestServer::with_config(config).await.unwrap()
+}
+
+fn command(server: &TestServer, args: &[&str]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_nova-cli"));
+    command
+        .args(["--url", server.base_url()])
+        .args(args)
+        .stdin(Stdio::null());
+    for name in [
+        "NOVA_MCP_URL",
+        "NOVA_MCP_API_KEY",
+        "NOVA_MCP_ADMIN_TOKEN",
+        "NOVA_MCP_BEARER_TOKEN",
+        "NOVA_MCP_CONTEXT",
+        "NOVA_MCP_ACTOR_ID",
+    ] {
+        command.env_remove(name);
+    }
+    command
+}
+
+async fn run(server: &TestServer, args: &[&str]) -> Output {
+    command(server, args).output().await.unwrap()
+}
+
+async fn cli(server: &TestServer, args: &[&str], path: &std::path::Path) -> Output {
+    let path = path.to_str().unwrap();
+    let args: Vec<&str> = args.iter().copied().chain([path]).collect();
+    run(server, &args).await
+}
+
+fn temp_file(name: &str, contents: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("nova-cli-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn assert_success(output: &Output) {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
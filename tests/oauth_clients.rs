@@ -0,0 +1,224 @@
+use nova_mcp::clock::{ManualClock, SharedClock};
+use nova_mcp::oauth::OAuthClientStore;
+use nova_mcp::plugins::{PluginContextType, RequestContext};
+use nova_mcp::test_util::TestServer;
+use nova_mcp::{NovaConfig, NovaServer, PluginManager};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn secrets_are_checked_against_their_hash() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let store = OAuthClientStore::persistent(db.open_tree("oauth_clients").unwrap());
+    let context = RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "7".into(),
+        actor_id: None,
+    };
+    let (client, secret) = store.create(&context, vec!["plugins:read".into()]).unwrap();
+    assert_ne!(client.secret_hash, secret);
+    assert!(store
+        .authenticate(&client.client_id, &secret)
+        .unwrap()
+        .is_some());
+    assert!(store
+        .authenticate(&client.client_id, "wrong")
+        .unwrap()
+        .is_none());
+
+    assert!(store.remove(&client.client_id).unwrap());
+    assert!(store.list().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn developers_exchange_credentials_for_scoped_tokens() {
+    let mut config = NovaConfig::default();
+    config.auth.mode = "jwt".into();
+    config.auth.jwt_secret = Some("oauth-test-secret".into());
+    config.admin.tokens = vec!["ops-token".into()];
+    let base = start(config).await;
+    let client = reqwest::Client::new();
+
+    let created: Value = client
+        .post(format!("{}/admin/oauth/clients", base))
+        .header("x-admin-token", "ops-token")
+        .json(&json!({ "context_type": "user", "context_id": "7" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let client_id = created["client_id"].as_str().unwrap().to_string();
+    let client_secret = created["client_secret"].as_str().unwrap().to_string();
+    assert_eq!(created["scopes"], json!(["plugins:read", "plugins:write"]));
+
+    let granted: Value = client
+        .post(format!("{}/oauth/token", base))
+        .basic_auth(&client_id, Some(&client_secret))
+        .form(&[("grant_type", "client_credentials")])
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(granted["token_type"], "Bearer");
+    assert_eq!(granted["expires_in"], 900);
+    let token = granted["access_token"].as_str().unwrap();
+
+    let registered: Value = client
+        .post(format!("{}/plugins/register", base))
+        .bearer_auth(token)
+        .json(&json!({
+            "name": "echo",
+            "description": "test",
+            "input_schema": { "type": "object" },
+            "endpoint_url": "https://example.com/hook",
+            "version": 1
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(registered["context_type"], "user");
+    assert_eq!(registered["context_id"], "7");
+
+    // Plugin-developer tokens do not reach the tool-calling surface
+    let rpc: Value = client
+        .post(format!("{}/rpc", base))
+        .bearer_auth(token)
+        .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(rpc["error"]["message"].as_str().unwrap().contains("scope"));
+
+    let widened = client
+        .post(format!("{}/oauth/token", base))
+        .form(&[
+            ("grant_type", "client_credentials"),
+            ("client_id", client_id.as_str()),
+            ("client_secret", client_secret.as_str()),
+            ("scope", "tools"),
+        ])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(widened.status(), 400);
+
+    let deleted = client
+        .delete(format!("{}/admin/oauth/clients/{}", base, client_id))
+        .header("x-admin-token", "ops-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(deleted.status(), 204);
+    let revoked: Value = client
+        .post(format!("{}/oauth/token", base))
+        .basic_auth(&client_id, Some(&client_secret))
+        .form(&[("grant_type", "client_credentials")])
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(revoked["error"], "invalid_client");
+}
+
+async fn start(mut config: NovaConfig) -> String {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    config.server.port = port;
+    let server = test_server(config.clone());
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+    let base = format!("http://127.0.0.1:{}", port);
+    for _ in 0..50 {
+        if reqwest::get(format!("{}/healthz", base)).await.is_ok() {
+            return base;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("server did not start");
+}
+
+fn test_server(config: NovaConfig) -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager"),
+    );
+    NovaServer::new(config, plugin_manager)
+}
+
+#[tokio::test]
+async fn bearer_tokens_need_no_api_key_and_expire_on_the_server_clock() {
+    let clock = ManualClock::at(1_700_000_000);
+    let mut config = NovaConfig::default();
+    config.auth.enabled = true;
+    config.auth.allowed_keys = vec!["tenant-key".into()];
+    config.auth.mode = "jwt".into();
+    config.auth.jwt_secret = Some("oauth-test-secret".into());
+    config.admin.tokens = vec!["ops-token".into()];
+    let server = TestServer::with_clock(config, SharedClock::new(clock.clone()))
+        .await
+        .unwrap();
+    let client = reqwest::Client::new();
+
+    let created: Value = client
+        .post(server.url("/admin/oauth/clients"))
+        .header("x-admin-token", "ops-token")
+        .json(&json!({ "context_type": "user", "context_id": "7" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let granted: Value = client
+        .post(server.url("/oauth/token"))
+        .basic_auth(
+            created["client_id"].as_str().unwrap(),
+            created["client_secret"].as_str(),
+        )
+        .form(&[("grant_type", "client_credentials")])
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let token = granted["access_token"].as_str().unwrap().to_string();
+    let list = || {
+        client
+            .get(server.url("/plugins"))
+            .bearer_auth(&token)
+            .send()
+    };
+
+    // The verified token alone is enough, without the shared API key
+    assert_eq!(list().await.unwrap().status(), 200);
+    let forged = client
+        .get(server.url("/plugins"))
+        .bearer_auth("not-a-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(forged.status(), 401);
+
+    // `exp` was stamped from the server clock, so moving it expires the token
+    clock.advance(Duration::from_secs(900 + 61));
+    assert_eq!(list().await.unwrap().status(), 401);
+}
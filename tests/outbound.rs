@@ -0,0 +1,104 @@
+use nova_mcp::config::{NovaConfig, OutboundConfig, UpstreamOutboundConfig};
+use nova_mcp::outbound::client_builder;
+use nova_mcp::{NovaError, NovaServer, PluginManager};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+#[tokio::test]
+async fn requests_go_through_configured_proxy() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let proxy_url = format!("http://{}", listener.local_addr().unwrap());
+    let proxy = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = vec![0u8; 1024];
+        let n = socket.read(&mut buf).await.unwrap();
+        socket
+            .write_all(b"HTTP/1.1 204 No Content\r\ncontent-length: 0\r\n\r\n")
+            .await
+            .unwrap();
+        String::from_utf8_lossy(&buf[..n]).to_string()
+    });
+
+    let cfg = OutboundConfig {
+        proxy: Some(proxy_url),
+        ..OutboundConfig::default()
+    };
+    let client = client_builder(&cfg, "geckoterminal")
+        .unwrap()
+        .build()
+        .unwrap();
+    let response = client
+        .get("http://upstream.invalid/networks")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 204);
+    assert!(proxy
+        .await
+        .unwrap()
+        .starts_with("GET http://upstream.invalid/networks"));
+}
+
+#[test]
+fn upstream_overrides_replace_global_settings() {
+    let mut cfg = OutboundConfig {
+        proxy: Some("not a url".into()),
+        ca_certs: vec!["/nonexistent/ca.pem".into()],
+        ..OutboundConfig::default()
+    };
+    assert!(client_builder(&cfg, "plugins").is_err());
+
+    cfg.upstreams.insert(
+        "plugins".into(),
+        UpstreamOutboundConfig {
+            direct: true,
+            ca_certs: Some(vec![]),
+            ..UpstreamOutboundConfig::default()
+        },
+    );
+    assert!(client_builder(&cfg, "plugins").is_ok());
+    assert!(client_builder(&cfg, "geckoterminal").is_err());
+}
+
+#[test]
+fn validate_flags_bad_proxy_and_missing_ca() {
+    let mut config = NovaConfig::default();
+    config.outbound.proxy = Some("not a url".into());
+    config.outbound.ca_certs = vec!["/nonexistent/ca.pem".into()];
+    match config.validate() {
+        Err(NovaError::InvalidConfig { issues }) => {
+            let fields: Vec<_> = issues.iter().map(|i| i.field.as_str()).collect();
+            assert_eq!(fields, vec!["outbound.proxy", "outbound.ca_certs"]);
+        }
+        other => panic!("expected InvalidConfig, got {:?}", other),
+    }
+
+    config.outbound.proxy = Some("socks5h://127.0.0.1:1080".into());
+    config.outbound.ca_certs.clear();
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn validate_parses_ca_files_and_startup_refuses_broken_ones() {
+    let empty = std::env::temp_dir().join(format!("nova-empty-ca-{}.pem", std::process::id()));
+    std::fs::write(&empty, "not a certificate\n").unwrap();
+    let mut config = NovaConfig::default();
+    config.outbound.ca_certs = vec![empty.to_string_lossy().into_owned()];
+    match config.validate() {
+        Err(NovaError::InvalidConfig { issues }) => {
+            assert_eq!(issues[0].field, "outbound.ca_certs");
+        }
+        other => panic!("expected InvalidConfig, got {:?}", other),
+    }
+
+    // Building the server without validating still fails instead of
+    // quietly dropping the extra roots
+    let manager = Arc::new(PluginManager::in_memory().unwrap());
+    match NovaServer::try_new(config, manager) {
+        Err(NovaError::ConfigError(message)) => assert!(message.contains("no certificates")),
+        Err(other) => panic!("expected a config error, got {:?}", other),
+        Ok(_) => panic!("expected a config error"),
+    }
+    std::fs::remove_file(empty).unwrap();
+}
@@ -0,0 +1,71 @@
+use nova_mcp::mcp::limits::{json_depth, PayloadLimits};
+use nova_mcp::mcp::{dto::McpRequest, handler};
+use nova_mcp::plugins::PluginManager;
+use nova_mcp::{NovaConfig, NovaServer};
+use serde_json::json;
+use std::sync::Arc;
+
+#[test]
+fn measures_json_depth() {
+    assert_eq!(json_depth(&json!(1)), 0);
+    assert_eq!(json_depth(&json!({})), 1);
+    assert_eq!(json_depth(&json!({"a": [1, {"b": []}]})), 4);
+}
+
+#[test]
+fn render_truncates_on_char_boundary() {
+    let limits = PayloadLimits {
+        max_argument_bytes: 1024,
+        max_json_depth: 8,
+        max_response_bytes: 10,
+    };
+    let (text, total) = limits.render(&json!("ééééééééé")).unwrap();
+    assert!(text.len() <= 10);
+    assert!(text.starts_with("\"é"));
+    assert_eq!(total, Some(20));
+
+    let (text, total) = limits.render(&json!("ok")).unwrap();
+    assert_eq!(text, "\"ok\"");
+    assert_eq!(total, None);
+}
+
+#[tokio::test]
+async fn oversized_arguments_are_rejected() {
+    let mut config = NovaConfig::default();
+    config.limits.max_argument_bytes = 32;
+    config.limits.max_json_depth = 3;
+    let server = test_server(config);
+
+    let big = call(json!({ "query": "x".repeat(64) }));
+    let resp = handler::handle_request(&server, big, None).await;
+    assert_eq!(resp.error.unwrap().code, -32602);
+
+    let deep = call(json!({ "a": { "b": { "c": { "d": 1 } } } }));
+    let resp = handler::handle_request(&server, deep, None).await;
+    let err = resp.error.unwrap();
+    assert_eq!(err.code, -32602);
+    assert!(err.message.contains("depth"));
+}
+
+fn call(arguments: serde_json::Value) -> McpRequest {
+    McpRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: "tools/call".to_string(),
+        params: Some(json!({ "name": "search_pools", "arguments": arguments })),
+        context_type: Some("user".to_string()),
+        context_id: Some("0".to_string()),
+        actor_id: None,
+    }
+}
+
+fn test_server(config: NovaConfig) -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager"),
+    );
+    NovaServer::new(config, plugin_manager)
+}
@@ -0,0 +1,262 @@
+use axum::{http::StatusCode, routing::post, Json, Router};
+use nova_mcp::config::PluginsConfig;
+use nova_mcp::mcp::{dto::McpRequest, handler};
+use nova_mcp::pipeline::{PipelineDefinition, PipelineRegistry};
+use nova_mcp::plugins::{
+    EgressPolicy, PluginContextType, PluginManager, PluginRegistrationRequest, RequestContext,
+};
+use nova_mcp::{NovaConfig, NovaServer};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+#[test]
+fn definitions_must_form_a_dag_of_known_steps() {
+    let valid = pipeline(json!({
+        "name": "pool_report",
+        "description": "Search, then fetch the first pool",
+        "steps": [
+            { "id": "search", "tool": "search_pools", "arguments": { "query": "{{ input.query }}" } },
+            {
+                "id": "pool",
+                "tool": "get_gecko_pool",
+                "arguments": { "network": "aptos", "address": "{{ steps.search.pools.0.address }}" }
+            }
+        ]
+    }));
+    let registry = PipelineRegistry::new(vec![valid.clone()]).unwrap();
+    assert!(registry.contains("pool_report"));
+
+    let cases = [
+        (
+            json!({ "steps": [
+            { "id": "a", "tool": "search_pools", "arguments": { "q": "{{ steps.b }}" } },
+            { "id": "b", "tool": "search_pools", "after": ["a"] }
+        ] }),
+            "cycle",
+        ),
+        (
+            json!({ "steps": [
+            { "id": "a", "tool": "search_pools", "arguments": { "q": "{{ steps.nope.x }}" } }
+        ] }),
+            "unknown step nope",
+        ),
+        (
+            json!({ "steps": [
+            { "id": "a", "tool": "search_pools", "arguments": { "q": "{{ env.HOME }}" } }
+        ] }),
+            "must start with",
+        ),
+        (
+            json!({ "steps": [
+            { "id": "a", "tool": "pool_report" }
+        ] }),
+            "another pipeline",
+        ),
+        (json!({ "steps": [] }), "at least one step"),
+        (json!({ "name": "search_pools" }), "built-in"),
+    ];
+    for (overrides, expected) in cases {
+        let mut definition = serde_json::to_value(&valid).unwrap();
+        definition["name"] = json!("broken");
+        for (key, value) in overrides.as_object().unwrap() {
+            definition[key] = value.clone();
+        }
+        let err = PipelineRegistry::new(vec![valid.clone(), pipeline(definition)]).unwrap_err();
+        assert!(err.contains(expected), "{}: {}", expected, err);
+    }
+
+    let config = NovaConfig {
+        pipelines: vec![valid.clone(), valid],
+        ..NovaConfig::default()
+    };
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("pipelines"), "{}", err);
+}
+
+#[tokio::test]
+async fn steps_feed_each_other_and_follow_their_error_policy() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let app = Router::new()
+        .route(
+            "/search",
+            post(|Json(body): Json<Value>| async move {
+                let query = body["arguments"]["query"].as_str().unwrap_or_default();
+                Json(json!({ "pools": [{ "address": format!("0x{}", query) }] }))
+            }),
+        )
+        .route(
+            "/pool",
+            post(|Json(body): Json<Value>| async move {
+                Json(json!({ "address": body["arguments"]["address"], "symbol": "APT" }))
+            }),
+        )
+        .route(
+            "/security",
+            post(|| async { (StatusCode::BAD_GATEWAY, "scanner down") }),
+        );
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let plugins = plugin_manager();
+    let base = format!("http://127.0.0.1:{}", port);
+    let mut tools = Vec::new();
+    for name in ["search", "pool", "security"] {
+        let metadata = plugins
+            .register_plugin(&owner(), registration(name, &format!("{}/{}", base, name)))
+            .unwrap();
+        tools.push(metadata.fq_name);
+    }
+    let [search, pool, security] = [&tools[0], &tools[1], &tools[2]];
+
+    let pipelines = vec![
+        pipeline(json!({
+            "name": "pool_report",
+            "description": "Search pools and check the first one",
+            "input_schema": {
+                "type": "object",
+                "properties": { "query": { "type": "string" } },
+                "required": ["query"]
+            },
+            "steps": [
+                { "id": "search", "tool": search, "arguments": { "query": "{{ input.query }}" } },
+                { "id": "pool", "tool": pool, "arguments": { "address": "{{ steps.search.pools.0.address }}" } },
+                { "id": "security", "tool": security, "after": ["pool"], "on_error": "skip" },
+                { "id": "verdict", "tool": pool, "arguments": { "address": "{{ steps.security.score }}" } },
+                { "id": "fallback", "tool": security, "on_error": "continue", "fallback": { "score": 0 } }
+            ]
+        })),
+        pipeline(json!({
+            "name": "strict_report",
+            "description": "Fails with the scanner",
+            "steps": [
+                { "id": "pool", "tool": pool, "arguments": { "address": "0x1" } },
+                { "id": "security", "tool": security, "after": ["pool"] }
+            ],
+            "output": { "symbol": "{{ steps.pool.symbol }}" }
+        })),
+    ];
+    let config = NovaConfig {
+        pipelines,
+        ..NovaConfig::default()
+    };
+    let server = NovaServer::new(config, Arc::new(plugins));
+
+    let listed = handler::handle_request(&server, request("tools/list", json!({})), None).await;
+    let names: Vec<Value> = listed.result.unwrap()["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].clone())
+        .collect();
+    assert!(names.contains(&json!("pool_report")));
+
+    let response = handler::handle_request(
+        &server,
+        request(
+            "tools/call",
+            json!({ "name": "pool_report", "arguments": { "query": "apt" } }),
+        ),
+        None,
+    )
+    .await;
+    let text = response.result.expect("pipeline result")["content"][0]["text"].clone();
+    let result: Value = serde_json::from_str(text.as_str().unwrap()).unwrap();
+    assert_eq!(result["steps"]["search"]["pools"][0]["address"], "0xapt");
+    assert_eq!(
+        result["steps"]["pool"],
+        json!({ "address": "0xapt", "symbol": "APT" })
+    );
+    assert_eq!(result["steps"]["fallback"], json!({ "score": 0 }));
+    assert!(result["steps"].get("security").is_none());
+    assert!(result["errors"]["security"]
+        .as_str()
+        .unwrap()
+        .contains("502"));
+    assert_eq!(result["skipped"], json!(["verdict"]));
+
+    let invalid = handler::handle_request(
+        &server,
+        request(
+            "tools/call",
+            json!({ "name": "pool_report", "arguments": {} }),
+        ),
+        None,
+    )
+    .await;
+    assert_eq!(invalid.error.unwrap().code, -32602);
+
+    let failed = handler::handle_request(
+        &server,
+        request(
+            "tools/call",
+            json!({ "name": "strict_report", "arguments": {} }),
+        ),
+        None,
+    )
+    .await;
+    let data = failed.error.expect("step failure").data.unwrap();
+    assert_eq!(data["code"], "pipeline_step_failed");
+    assert_eq!(data["category"], "upstream");
+    assert_eq!(data["details"]["step"], "security");
+    assert_eq!(data["details"]["error"]["code"], "upstream_error");
+}
+
+fn pipeline(definition: Value) -> PipelineDefinition {
+    serde_json::from_value(definition).unwrap()
+}
+
+fn request(method: &str, params: Value) -> McpRequest {
+    McpRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: method.to_string(),
+        params: Some(params),
+        context_type: Some("user".to_string()),
+        context_id: Some("5".to_string()),
+        actor_id: None,
+    }
+}
+
+fn owner() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
+
+fn registration(name: &str, endpoint: &str) -> PluginRegistrationRequest {
+    PluginRegistrationRequest {
+        name: name.to_string(),
+        description: "test".to_string(),
+        owner_id: None,
+        input_schema: json!({ "type": "object" }),
+        output_schema: None,
+        endpoint_url: endpoint.to_string(),
+        script: None,
+        version: 1,
+        trust_level: Default::default(),
+        client_certificate: None,
+        credentials: None,
+        redact: Vec::new(),
+        request_template: None,
+        listing: None,
+        allowed_contexts: Vec::new(),
+        tags: Vec::new(),
+        coerce_arguments: false,
+    }
+}
+
+fn plugin_manager() -> PluginManager {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    PluginManager::new(metadata_tree, user_tree, group_tree)
+        .expect("init plugin manager")
+        .with_egress_policy(EgressPolicy::new(&PluginsConfig {
+            allowed_schemes: vec!["http".into()],
+            allow_private_networks: true,
+            ..PluginsConfig::default()
+        }))
+}
@@ -0,0 +1,184 @@
+use axum::{routing::post, Json, Router};
+use nova_mcp::clock::{ManualClock, SharedClock};
+use nova_mcp::config::PluginsConfig;
+use nova_mcp::plugins::{
+    EgressPolicy, MarketplaceQuery, PluginContextType, PluginEnableRequest, PluginListing,
+    PluginManager, PluginRegistrationRequest, RequestContext,
+};
+use nova_mcp::{NovaConfig, NovaServer};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+const NOW: i64 = 1_700_000_000;
+
+#[tokio::test]
+async fn installs_and_last_use_are_tracked() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let app = Router::new().route("/hook", post(|| async { Json(json!({ "ok": true })) }));
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let config = PluginsConfig {
+        allowed_schemes: vec!["http".into()],
+        allow_private_networks: true,
+        ..PluginsConfig::default()
+    };
+    let clock = ManualClock::at(NOW);
+    let manager = PluginManager::in_memory()
+        .unwrap()
+        .with_egress_policy(EgressPolicy::new(&config))
+        .with_clock(SharedClock::new(clock.clone()));
+    let endpoint = format!("http://127.0.0.1:{}/hook", port);
+    let used = manager
+        .register_plugin(&user("5"), registration("used", &endpoint))
+        .unwrap();
+    let idle = manager
+        .register_plugin(&user("5"), registration("idle", &endpoint))
+        .unwrap();
+    assert_eq!(used.created_at, NOW);
+    manager.review_listing(used.plugin_id, true).unwrap();
+    clock.advance(Duration::from_secs(60));
+    for id in ["7", "8"] {
+        let status = manager
+            .set_enablement(PluginEnableRequest {
+                context_type: PluginContextType::User,
+                context_id: id.to_string(),
+                plugin_id: used.plugin_id,
+                enable: true,
+                added_by: None,
+            })
+            .unwrap();
+        assert_eq!(status.consent_ts, NOW + 60);
+    }
+    clock.advance(Duration::from_secs(60));
+    manager
+        .invoke_plugin(&used, &user("7"), json!({}))
+        .await
+        .unwrap();
+
+    let mut plugins = vec![
+        manager.get_plugin(used.plugin_id).unwrap(),
+        manager.get_plugin(idle.plugin_id).unwrap(),
+    ];
+    // Only filled in on request
+    assert_eq!(plugins[0].installs, 0);
+    manager.annotate_usage(&mut plugins);
+    assert_eq!(plugins[0].installs, 2);
+    let last_used = plugins[0].last_used_at.expect("call is tracked");
+    assert_eq!(last_used, NOW + 120);
+    assert_eq!((plugins[1].installs, plugins[1].last_used_at), (0, None));
+    let listed = manager.marketplace(&MarketplaceQuery::default()).unwrap();
+    assert_eq!(listed[0].last_used_at, Some(last_used));
+
+    // Never-called plugins come first
+    let stale = manager.stale_plugins(NOW + 180).unwrap();
+    let ids: Vec<u64> = stale.iter().map(|metadata| metadata.plugin_id).collect();
+    assert_eq!(ids, [idle.plugin_id, used.plugin_id]);
+    // Plugins younger than the window are not stale yet
+    assert!(manager.stale_plugins(NOW).unwrap().is_empty());
+
+    // The caller's activity goes with the context
+    manager.purge_context(&user("7")).unwrap();
+    let mut plugins = vec![manager.get_plugin(used.plugin_id).unwrap()];
+    manager.annotate_usage(&mut plugins);
+    assert_eq!((plugins[0].installs, plugins[0].last_used_at), (1, None));
+}
+
+#[tokio::test]
+async fn admins_list_stale_plugins() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig::default();
+    config.server.port = port;
+    config.admin.tokens = vec!["ops-token".into()];
+    let server = NovaServer::new(
+        config.clone(),
+        Arc::new(PluginManager::in_memory().unwrap()),
+    );
+    server
+        .plugin_manager()
+        .register_plugin(
+            &user("5"),
+            registration("fresh", "https://example.com/hook"),
+        )
+        .unwrap();
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let client = reqwest::Client::new();
+    let base = format!("http://127.0.0.1:{}", port);
+    for _ in 0..50 {
+        if client.get(format!("{}/healthz", base)).send().await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let url = format!("{}/admin/plugins/stale", base);
+
+    let unauthorized = client.get(&url).send().await.unwrap();
+    assert_eq!(unauthorized.status(), 401);
+    let report: Value = client
+        .get(&url)
+        .header("x-admin-token", "ops-token")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(report["days"], 30);
+    assert_eq!(report["plugins"], json!([]));
+    let invalid = client
+        .get(format!("{}?days=0", url))
+        .header("x-admin-token", "ops-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(invalid.status(), 400);
+
+    let plugins: Value = client
+        .get(format!("{}/plugins", base))
+        .header("x-nova-context-type", "user")
+        .header("x-nova-context-id", "5")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(plugins[0]["installs"], 0);
+    assert_eq!(plugins[0]["last_used_at"], Value::Null);
+}
+
+fn user(context_id: &str) -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: context_id.to_string(),
+        actor_id: None,
+    }
+}
+
+fn registration(name: &str, endpoint: &str) -> PluginRegistrationRequest {
+    PluginRegistrationRequest {
+        name: name.to_string(),
+        description: "test".to_string(),
+        owner_id: None,
+        input_schema: json!({ "type": "object" }),
+        output_schema: None,
+        endpoint_url: endpoint.to_string(),
+        script: None,
+        version: 1,
+        trust_level: Default::default(),
+        client_certificate: None,
+        credentials: None,
+        redact: Vec::new(),
+        request_template: None,
+        listing: Some(PluginListing::default()),
+        allowed_contexts: Vec::new(),
+        tags: Vec::new(),
+        coerce_arguments: false,
+    }
+}
@@ -0,0 +1,183 @@
+use axum::{http::HeaderMap, routing::post, Json, Router};
+use nova_mcp::config::PluginsConfig;
+use nova_mcp::plugins::{
+    EgressPolicy, PluginAuth, PluginContextType, PluginCredentials, PluginManager,
+    PluginRegistrationRequest, PluginUpdateRequest, RequestContext, SecretBox,
+};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+const KEY: &str = "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=";
+
+#[test]
+fn sealed_values_only_open_with_their_key() {
+    let secrets = SecretBox::from_base64(KEY).unwrap();
+    let sealed = secrets.seal(b"token").unwrap();
+    assert_ne!(secrets.seal(b"token").unwrap(), sealed);
+    assert_eq!(secrets.open(&sealed).unwrap(), b"token");
+
+    let other = SecretBox::new(&[7; 32]);
+    assert!(other.open(&sealed).is_err());
+    assert!(SecretBox::from_base64("c2hvcnQ=").is_none());
+}
+
+#[test]
+fn credentials_need_a_key_and_valid_headers() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let without_key = manager(&db);
+    let err = without_key
+        .register_plugin(&owner(), registration("https://example.com/hook"))
+        .unwrap_err();
+    assert!(err.to_string().contains("secrets_key"));
+
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let manager = manager(&db).with_secret_box(SecretBox::from_base64(KEY).unwrap());
+    let mut reserved = registration("https://example.com/hook");
+    reserved.credentials = Some(PluginCredentials {
+        headers: BTreeMap::from([("Content-Type".to_string(), "text/plain".to_string())]),
+        auth: None,
+    });
+    assert!(manager.register_plugin(&owner(), reserved).is_err());
+
+    let metadata = manager
+        .register_plugin(&owner(), registration("https://example.com/hook"))
+        .unwrap();
+    assert_eq!(metadata.credential_headers, vec!["authorization", "x-team"]);
+    let stored = db.open_tree("plugin_metadata").unwrap();
+    for item in stored.iter() {
+        let (_, value) = item.unwrap();
+        assert!(!String::from_utf8_lossy(&value).contains("s3cret-token"));
+    }
+}
+
+#[tokio::test]
+async fn calls_carry_the_configured_headers() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let app = Router::new().route(
+        "/hook",
+        post(|headers: HeaderMap| async move {
+            let header = |name: &str| {
+                headers
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string)
+            };
+            Json(json!({
+                "authorization": header("authorization"),
+                "team": header("x-team"),
+                "api_key": header("x-api-key"),
+            }))
+        }),
+    );
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let manager = manager(&db)
+        .with_egress_policy(EgressPolicy::new(&PluginsConfig {
+            allowed_schemes: vec!["http".into()],
+            allow_private_networks: true,
+            ..PluginsConfig::default()
+        }))
+        .with_secret_box(SecretBox::from_base64(KEY).unwrap());
+    let endpoint = format!("http://127.0.0.1:{}/hook", port);
+    let metadata = manager
+        .register_plugin(&owner(), registration(&endpoint))
+        .unwrap();
+    let echoed: Value = manager
+        .invoke_plugin(&metadata, &owner(), json!({}))
+        .await
+        .unwrap();
+    assert_eq!(echoed["authorization"], "Bearer s3cret-token");
+    assert_eq!(echoed["team"], "nova");
+
+    let api_key = PluginUpdateRequest {
+        credentials: Some(Some(PluginCredentials {
+            headers: BTreeMap::new(),
+            auth: Some(PluginAuth::ApiKey {
+                header: "x-api-key".to_string(),
+                value: "k-123".to_string(),
+            }),
+        })),
+        ..PluginUpdateRequest::default()
+    };
+    let metadata = manager
+        .update_plugin(&owner(), metadata.plugin_id, api_key)
+        .unwrap();
+    let echoed: Value = manager
+        .invoke_plugin(&metadata, &owner(), json!({}))
+        .await
+        .unwrap();
+    assert_eq!(echoed["api_key"], "k-123");
+    assert_eq!(echoed["authorization"], Value::Null);
+
+    let basic = PluginUpdateRequest {
+        credentials: Some(Some(PluginCredentials {
+            headers: BTreeMap::new(),
+            auth: Some(PluginAuth::Basic {
+                username: "nova".to_string(),
+                password: "pw".to_string(),
+            }),
+        })),
+        ..PluginUpdateRequest::default()
+    };
+    let metadata = manager
+        .update_plugin(&owner(), metadata.plugin_id, basic)
+        .unwrap();
+    let echoed: Value = manager
+        .invoke_plugin(&metadata, &owner(), json!({}))
+        .await
+        .unwrap();
+    assert_eq!(echoed["authorization"], "Basic bm92YTpwdw==");
+
+    let removed = PluginUpdateRequest {
+        credentials: Some(None),
+        ..PluginUpdateRequest::default()
+    };
+    let metadata = manager
+        .update_plugin(&owner(), metadata.plugin_id, removed)
+        .unwrap();
+    assert!(metadata.credential_headers.is_empty());
+}
+
+fn owner() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
+
+fn registration(endpoint: &str) -> PluginRegistrationRequest {
+    PluginRegistrationRequest {
+        name: "hook".to_string(),
+        description: "test".to_string(),
+        owner_id: None,
+        input_schema: json!({ "type": "object" }),
+        output_schema: None,
+        endpoint_url: endpoint.to_string(),
+        script: None,
+        version: 1,
+        trust_level: Default::default(),
+        client_certificate: None,
+        credentials: Some(PluginCredentials {
+            headers: BTreeMap::from([("x-team".to_string(), "nova".to_string())]),
+            auth: Some(PluginAuth::Bearer {
+                token: "s3cret-token".to_string(),
+            }),
+        }),
+        redact: Vec::new(),
+        request_template: None,
+        listing: None,
+        allowed_contexts: Vec::new(),
+        tags: Vec::new(),
+        coerce_arguments: false,
+    }
+}
+
+fn manager(db: &sled::Db) -> PluginManager {
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager")
+}
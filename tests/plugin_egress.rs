@@ -0,0 +1,355 @@
+use axum::{http::header, http::StatusCode, routing::post, Json, Router};
+use nova_mcp::config::{OutboundConfig, PluginsConfig};
+use nova_mcp::plugins::{
+    EgressPolicy, PluginContextType, PluginManager, PluginRegistrationRequest, PluginTrustLevel,
+    PluginUpdateRequest, RequestContext,
+};
+use serde_json::json;
+use std::collections::HashMap;
+
+#[test]
+fn internal_addresses_and_other_schemes_are_rejected() {
+    let policy = EgressPolicy::default();
+    for endpoint in [
+        "http://api.example.com/hook",
+        "ftp://api.example.com/hook",
+        "https://169.254.169.254/latest/meta-data",
+        "https://127.0.0.1:8443/hook",
+        "https://10.1.2.3/hook",
+        "https://[::1]/hook",
+        "https://[::ffff:192.168.0.1]/hook",
+        "https://[fd00::1]/hook",
+        "https://[64:ff9b::a9fe:a9fe]/hook",
+        "not a url",
+    ] {
+        assert!(
+            policy
+                .check_url(endpoint, PluginTrustLevel::Standard)
+                .is_err(),
+            "{} should be rejected",
+            endpoint
+        );
+    }
+    assert!(policy
+        .check_url("https://203.0.113.10/hook", PluginTrustLevel::Standard)
+        .is_ok());
+
+    let relaxed = EgressPolicy::new(&PluginsConfig {
+        allowed_schemes: vec!["http".into(), "https".into()],
+        allow_private_networks: true,
+        ..PluginsConfig::default()
+    });
+    assert!(relaxed
+        .check_url("http://10.1.2.3/hook", PluginTrustLevel::Standard)
+        .is_ok());
+}
+
+#[test]
+fn domain_allowlists_apply_per_trust_level() {
+    let policy = EgressPolicy::new(&PluginsConfig {
+        allowed_domains: HashMap::from([(
+            "high".to_string(),
+            vec![
+                "*.treasury.example".to_string(),
+                "vault.example.org".to_string(),
+            ],
+        )]),
+        ..PluginsConfig::default()
+    });
+    let high = PluginTrustLevel::High;
+    assert!(policy
+        .check_url("https://api.treasury.example/sign", high)
+        .is_ok());
+    assert!(policy.check_url("https://vault.example.org/", high).is_ok());
+    assert!(policy.check_url("https://treasury.example/", high).is_err());
+    assert!(policy
+        .check_url("https://api.treasury.example.evil.com/", high)
+        .is_err());
+    assert!(policy
+        .check_url("https://anything.example.com/", PluginTrustLevel::Standard)
+        .is_ok());
+}
+
+#[tokio::test]
+async fn names_resolving_to_internal_addresses_are_rejected() {
+    let manager = manager(EgressPolicy::default());
+    let err = manager
+        .check_endpoint("https://localhost/hook", PluginTrustLevel::Standard)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("blocked address"));
+
+    // Registration only checks the URL; the call re-checks DNS before sending
+    let metadata = manager
+        .register_plugin(&owner(), registration("https://localhost/hook"))
+        .unwrap();
+    let err = manager
+        .invoke_plugin(&metadata, &owner(), json!({}))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("blocked address"));
+}
+
+#[tokio::test]
+async fn plugin_clients_check_the_addresses_they_connect_to() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let app = Router::new().route("/hook", post(|| async { Json(json!({ "ok": true })) }));
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    // The connection itself is refused, whatever an earlier lookup returned
+    let policy = EgressPolicy::new(&PluginsConfig {
+        allowed_schemes: vec!["http".into()],
+        ..PluginsConfig::default()
+    });
+    let client = policy
+        .configure(reqwest::Client::builder())
+        .build()
+        .unwrap();
+    let err = client
+        .post(format!("http://localhost:{}/hook", port))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{:?}", err).contains("blocked address"),
+        "{:?}",
+        err
+    );
+
+    // A proxy on an internal address is still reachable; it resolves plugin hosts
+    let outbound = OutboundConfig {
+        proxy: Some(format!("http://localhost:{}", port)),
+        ..OutboundConfig::default()
+    };
+    let proxied = policy
+        .with_outbound_proxy(&outbound)
+        .configure(nova_mcp::outbound::client_builder(&outbound, "plugins").unwrap())
+        .build()
+        .unwrap();
+    let response = proxied
+        .post("http://plugin.example/hook")
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+}
+
+#[test]
+fn endpoints_are_stored_in_canonical_form() {
+    let policy = EgressPolicy::default();
+    for (endpoint, canonical) in [
+        (
+            "HTTPS://Api.Example.com:443/nova/",
+            "https://api.example.com/nova",
+        ),
+        (
+            " https://api.example.com/nova//?v=2#top ",
+            "https://api.example.com/nova?v=2",
+        ),
+        ("https://api.example.com", "https://api.example.com/"),
+        (
+            "https://api.example.com:8443/",
+            "https://api.example.com:8443/",
+        ),
+    ] {
+        assert_eq!(
+            policy
+                .canonical_url(endpoint, PluginTrustLevel::Standard)
+                .unwrap(),
+            canonical
+        );
+    }
+    for endpoint in ["notaurl", "https://", "mailto:ops@example.com"] {
+        assert!(policy
+            .canonical_url(endpoint, PluginTrustLevel::Standard)
+            .is_err());
+    }
+
+    let manager = manager(EgressPolicy::default());
+    let metadata = manager
+        .register_plugin(&owner(), registration("https://Example.com/hook/"))
+        .unwrap();
+    assert_eq!(metadata.endpoint_url, "https://example.com/hook");
+    let moved = PluginUpdateRequest {
+        endpoint_url: Some("https://EXAMPLE.com:443/v2/hook/#frag".to_string()),
+        ..PluginUpdateRequest::default()
+    };
+    let updated = manager
+        .update_plugin(&owner(), metadata.plugin_id, moved)
+        .unwrap();
+    assert_eq!(updated.endpoint_url, "https://example.com/v2/hook");
+    assert!(manager
+        .register_plugin(&owner(), registration("notaurl"))
+        .is_err());
+}
+
+#[tokio::test]
+async fn resolve_endpoints_rejects_unknown_hosts() {
+    let unresolved = "https://nova-plugin.invalid/hook";
+    let lenient = manager(EgressPolicy::default());
+    lenient
+        .check_endpoint(unresolved, PluginTrustLevel::Standard)
+        .await
+        .unwrap();
+
+    let strict = manager(EgressPolicy::new(&PluginsConfig {
+        resolve_endpoints: true,
+        ..PluginsConfig::default()
+    }));
+    let err = strict
+        .check_endpoint(unresolved, PluginTrustLevel::Standard)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("does not resolve"), "{}", err);
+    // Literal addresses need no lookup
+    strict
+        .check_endpoint("https://203.0.113.10/hook", PluginTrustLevel::Standard)
+        .await
+        .unwrap();
+}
+
+#[test]
+fn raising_the_trust_level_rechecks_the_endpoint() {
+    let manager = manager(EgressPolicy::new(&PluginsConfig {
+        allowed_domains: HashMap::from([(
+            "high".to_string(),
+            vec!["vault.example.org".to_string()],
+        )]),
+        ..PluginsConfig::default()
+    }));
+    let metadata = manager
+        .register_plugin(&owner(), registration("https://example.com/hook"))
+        .unwrap();
+    assert_eq!(metadata.trust_level, PluginTrustLevel::Standard);
+
+    let raise = PluginUpdateRequest {
+        trust_level: Some(PluginTrustLevel::High),
+        ..PluginUpdateRequest::default()
+    };
+    assert!(manager
+        .update_plugin(&owner(), metadata.plugin_id, raise.clone())
+        .is_err());
+
+    let moved = PluginUpdateRequest {
+        endpoint_url: Some("https://vault.example.org/hook".to_string()),
+        ..raise
+    };
+    let updated = manager
+        .update_plugin(&owner(), metadata.plugin_id, moved)
+        .unwrap();
+    assert_eq!(updated.trust_level, PluginTrustLevel::High);
+}
+
+#[tokio::test]
+async fn redirects_stay_on_the_endpoint_host() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let elsewhere = format!("http://localhost:{}/final", port);
+    let app = Router::new()
+        .route(
+            "/hook",
+            post(|| async {
+                (
+                    StatusCode::TEMPORARY_REDIRECT,
+                    [(header::LOCATION, "/final")],
+                )
+            }),
+        )
+        .route(
+            "/away",
+            post(move || async move {
+                (
+                    StatusCode::TEMPORARY_REDIRECT,
+                    [(header::LOCATION, elsewhere)],
+                )
+            }),
+        )
+        .route("/final", post(|| async { Json(json!({ "ok": true })) }));
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let local = PluginsConfig {
+        allowed_schemes: vec!["http".into()],
+        allow_private_networks: true,
+        ..PluginsConfig::default()
+    };
+    let hook = format!("http://127.0.0.1:{}/hook", port);
+    let away = format!("http://127.0.0.1:{}/away", port);
+
+    // Redirects are off by default
+    let strict = manager(EgressPolicy::new(&local));
+    let metadata = strict
+        .register_plugin(&owner(), registration(&hook))
+        .unwrap();
+    let err = strict
+        .invoke_plugin(&metadata, &owner(), json!({}))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("307"));
+
+    let policy = EgressPolicy::new(&PluginsConfig {
+        max_redirects: 1,
+        ..local
+    });
+    let client = policy
+        .configure(reqwest::Client::builder())
+        .build()
+        .unwrap();
+    let following = manager(policy).with_http_client(client);
+    let metadata = following
+        .register_plugin(&owner(), registration(&hook))
+        .unwrap();
+    let result = following
+        .invoke_plugin(&metadata, &owner(), json!({}))
+        .await
+        .unwrap();
+    assert_eq!(result, json!({ "ok": true }));
+
+    let mut request = registration(&away);
+    request.name = "away".to_string();
+    let metadata = following.register_plugin(&owner(), request).unwrap();
+    assert!(following
+        .invoke_plugin(&metadata, &owner(), json!({}))
+        .await
+        .is_err());
+}
+
+fn owner() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
+
+fn registration(endpoint: &str) -> PluginRegistrationRequest {
+    PluginRegistrationRequest {
+        name: "hook".to_string(),
+        description: "test".to_string(),
+        owner_id: None,
+        input_schema: json!({ "type": "object" }),
+        output_schema: None,
+        endpoint_url: endpoint.to_string(),
+        script: None,
+        version: 1,
+        trust_level: PluginTrustLevel::Standard,
+        client_certificate: None,
+        credentials: None,
+        redact: Vec::new(),
+        request_template: None,
+        listing: None,
+        allowed_contexts: Vec::new(),
+        tags: Vec::new(),
+        coerce_arguments: false,
+    }
+}
+
+fn manager(egress: EgressPolicy) -> PluginManager {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    PluginManager::new(metadata_tree, user_tree, group_tree)
+        .expect("init plugin manager")
+        .with_egress_policy(egress)
+}
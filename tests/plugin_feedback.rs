@@ -0,0 +1,247 @@
+use nova_mcp::plugins::{
+    FeedbackStore, PluginContextType, PluginListing, PluginManager, PluginRatingRequest,
+    PluginRegistrationRequest, PluginReportRequest, RequestContext,
+};
+use nova_mcp::{NovaConfig, NovaServer};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn one_rating_and_report_per_context() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let store = FeedbackStore::persistent(db.open_tree("plugin_feedback").unwrap());
+    let rate = |stars: u8| PluginRatingRequest {
+        stars,
+        comment: None,
+    };
+    store.rate(1, &user("7"), rate(2)).unwrap();
+    // Rating again replaces the earlier rating
+    store.rate(1, &user("7"), rate(4)).unwrap();
+    store.rate(1, &user("8"), rate(5)).unwrap();
+    store.rate(2, &user("8"), rate(1)).unwrap();
+    assert!(store.rate(1, &user("9"), rate(6)).is_err());
+    let long = PluginRatingRequest {
+        stars: 3,
+        comment: Some("x".repeat(501)),
+    };
+    assert!(store.rate(1, &user("9"), long).is_err());
+
+    let summary = store.ratings(1).unwrap();
+    assert_eq!(summary.ratings, 2);
+    assert_eq!(summary.average_stars, Some(4.5));
+    assert_eq!(summary.stars[&4], 1);
+    assert_eq!(summary.stars[&1], 0);
+    assert_eq!(store.ratings(3).unwrap().average_stars, None);
+
+    let report = |reason: &str| PluginReportRequest {
+        reason: reason.to_string(),
+    };
+    assert_eq!(store.report(1, &user("7"), report("spam")).unwrap(), 1);
+    assert_eq!(store.report(1, &user("7"), report("phishing")).unwrap(), 1);
+    assert_eq!(store.report(1, &user("8"), report("spam")).unwrap(), 2);
+    assert!(store.report(1, &user("9"), report("  ")).is_err());
+    let reports = store.reports(1).unwrap();
+    assert_eq!(reports.len(), 2);
+    assert!(reports
+        .iter()
+        .any(|report| report.context == "user:7" && report.reason == "phishing"));
+
+    // Ratings on two plugins and one report
+    assert_eq!(store.remove_context(&user("8")).unwrap(), 3);
+    assert_eq!(store.ratings(1).unwrap().ratings, 1);
+    assert_eq!(store.clear_reports(1).unwrap(), 1);
+    assert_eq!(store.remove_plugin(1).unwrap(), 1);
+}
+
+#[tokio::test]
+async fn reports_withdraw_a_listing_until_reviewed() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig::default();
+    config.server.port = port;
+    config.admin.tokens = vec!["ops-token".into()];
+    config.plugins.report_threshold = 2;
+    let server = NovaServer::new(
+        config.clone(),
+        Arc::new(PluginManager::in_memory().unwrap()),
+    );
+    let manager = server.plugin_manager();
+    let plugin = manager.register_plugin(&user("5"), registration()).unwrap();
+    manager.review_listing(plugin.plugin_id, true).unwrap();
+    manager
+        .install_listed(plugin.plugin_id, &user("7"))
+        .unwrap();
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let client = reqwest::Client::new();
+    let base = format!("http://127.0.0.1:{}", port);
+    for _ in 0..50 {
+        if client.get(format!("{}/healthz", base)).send().await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let as_user = |builder: reqwest::RequestBuilder, id: &str| {
+        builder
+            .header("x-nova-context-type", "user")
+            .header("x-nova-context-id", id)
+    };
+    let rating = format!("{}/marketplace/{}/rating", base, plugin.plugin_id);
+    let reports = format!("{}/marketplace/{}/reports", base, plugin.plugin_id);
+
+    let rated: Value = as_user(client.put(&rating), "7")
+        .json(&json!({ "stars": 4, "comment": "Works" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(rated["average_stars"], 4.0);
+    // Only contexts that installed the plugin rate it, and never the owner
+    let not_installed = as_user(client.put(&rating), "8")
+        .json(&json!({ "stars": 1 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(not_installed.status(), 403);
+    let own = as_user(client.put(&rating), "5")
+        .json(&json!({ "stars": 5 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(own.status(), 400);
+
+    let catalog: Value = client
+        .get(format!("{}/marketplace", base))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(catalog[0]["ratings"], 1);
+    let public: Value = client
+        .get(format!("{}/marketplace/{}/ratings", base, plugin.plugin_id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(public["stars"]["4"], 1);
+
+    for id in ["8", "9"] {
+        let accepted = as_user(client.post(&reports), id)
+            .json(&json!({ "reason": "Asks for seed phrases" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(accepted.status(), 202);
+    }
+    let catalog: Value = client
+        .get(format!("{}/marketplace", base))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(catalog, json!([]));
+    let install = as_user(
+        client.post(format!("{}/marketplace/{}/install", base, plugin.plugin_id)),
+        "10",
+    )
+    .send()
+    .await
+    .unwrap();
+    assert_eq!(install.status(), 404);
+
+    let admin_reports = format!("{}/admin/marketplace/{}/reports", base, plugin.plugin_id);
+    let pending: Value = client
+        .get(&admin_reports)
+        .header("x-admin-token", "ops-token")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(pending["listing"]["flagged"], true);
+    assert_eq!(pending["reports"].as_array().unwrap().len(), 2);
+
+    // Reinstating the listing clears the flag and the reviewed reports
+    let reviewed: Value = client
+        .put(format!("{}/admin/marketplace/{}", base, plugin.plugin_id))
+        .header("x-admin-token", "ops-token")
+        .json(&json!({ "approved": true }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(reviewed["listing"]["approved"], true);
+    assert_eq!(reviewed["listing"]["flagged"], false);
+    let pending: Value = client
+        .get(&admin_reports)
+        .header("x-admin-token", "ops-token")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(pending["reports"], json!([]));
+
+    let audit: Value = client
+        .get(format!("{}/admin/audit", base))
+        .header("x-admin-token", "ops-token")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let flagged = audit["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|entry| entry["action"] == "marketplace.flag")
+        .expect("flag is audited");
+    assert_eq!(flagged["who"], "system:reports");
+}
+
+fn user(context_id: &str) -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: context_id.to_string(),
+        actor_id: None,
+    }
+}
+
+fn registration() -> PluginRegistrationRequest {
+    PluginRegistrationRequest {
+        name: "airdrops".to_string(),
+        description: "Airdrop checker".to_string(),
+        owner_id: None,
+        input_schema: json!({ "type": "object" }),
+        output_schema: None,
+        endpoint_url: "https://example.com/hook".to_string(),
+        script: None,
+        version: 1,
+        trust_level: Default::default(),
+        client_certificate: None,
+        credentials: None,
+        redact: Vec::new(),
+        request_template: None,
+        listing: Some(PluginListing::default()),
+        allowed_contexts: Vec::new(),
+        tags: Vec::new(),
+        coerce_arguments: false,
+    }
+}
@@ -0,0 +1,407 @@
+use axum::http::StatusCode as AxumStatus;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use nova_mcp::config::PluginsConfig;
+use nova_mcp::plugins::{
+    EgressPolicy, ErrorResponse, JobCallback, JobStatus, PluginContextType, PluginJob, PluginJobs,
+    PluginManager, PluginRegistrationRequest, RequestContext,
+};
+use nova_mcp::test_util::{StubPlugin, TestServer};
+use nova_mcp::NovaConfig;
+use reqwest::{header, Method, StatusCode};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[tokio::test]
+async fn async_calls_answer_with_a_job_at_once() {
+    let stub = StubPlugin::start().await.unwrap();
+    let server = TestServer::start().await.unwrap();
+    let client = server.client(owner());
+    let plugin = client
+        .register(&registration("echo", &stub.url("/invoke")))
+        .await
+        .unwrap();
+
+    let response = client
+        .request(
+            Method::POST,
+            &format!("/v1/plugins/{}/call?async=true", plugin.plugin_id),
+        )
+        .json(&json!({ "arguments": { "city": "Porto" } }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let location = response.headers()[header::LOCATION]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let job: PluginJob = response.json().await.unwrap();
+    assert_eq!(location, format!("/v1/jobs/{}", job.job_id));
+    assert_eq!(job.status, JobStatus::Pending);
+    assert_eq!(job.plugin, plugin.fq_name);
+
+    let done = wait_for(&server, &owner(), &job.job_id).await;
+    assert_eq!(done.status, JobStatus::Succeeded);
+    let result = done.result.unwrap();
+    assert_eq!(result["received"]["arguments"]["city"], "Porto");
+    // Without `plugins.public_url` the plugin only learns the job id
+    assert!(result["received"]["job"]["job_id"].is_string());
+    assert!(result["received"]["job"].get("callback_url").is_none());
+
+    // The same job through the MCP tool; other contexts cannot see it
+    let status = client
+        .tools_call("get_job_status", json!({ "job_id": job.job_id }))
+        .await
+        .unwrap();
+    let text = status["content"][0]["text"].as_str().unwrap();
+    assert!(text.contains("succeeded"), "{}", text);
+    let stranger = server.client(RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "6".to_string(),
+        actor_id: None,
+    });
+    let response = stranger
+        .request(Method::GET, &location)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let error: ErrorResponse = response.json().await.unwrap();
+    assert_eq!(error.details.unwrap()["code"], "job_not_found");
+
+    // Arguments are checked before any job exists
+    let response = client
+        .request(
+            Method::POST,
+            &format!("/v1/plugins/{}/call?async=true", plugin.plugin_id),
+        )
+        .json(&json!({ "arguments": { "city": 7 } }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn accepted_jobs_are_polled_until_the_plugin_answers() {
+    let (addr, _) = deferring_plugin().await;
+    let mut config = NovaConfig::default();
+    config.plugins.job_poll_interval_ms = 20;
+    let server = TestServer::with_config(config).await.unwrap();
+    let client = server.client(owner());
+    let mut request = registration("slow", &format!("http://{}/slow", addr));
+    request.redact = vec!["secret".to_string()];
+    let plugin = client.register(&request).await.unwrap();
+
+    let job = submit(&server, plugin.plugin_id).await;
+    let done = wait_for(&server, &owner(), &job.job_id).await;
+    assert_eq!(done.status, JobStatus::Succeeded, "{:?}", done.error);
+    let result = done.result.unwrap();
+    assert_eq!(result["polls"], 3);
+    assert_ne!(result["secret"], "hunter2");
+
+    let failing = client
+        .register(&registration("broken", &format!("http://{}/broken", addr)))
+        .await
+        .unwrap();
+    let job = submit(&server, failing.plugin_id).await;
+    let done = wait_for(&server, &owner(), &job.job_id).await;
+    assert_eq!(done.status, JobStatus::Failed);
+    let error = done.error.unwrap();
+    assert_eq!(error.details.unwrap()["code"], "upstream_error");
+}
+
+#[tokio::test]
+async fn plugins_can_post_results_back() {
+    let (addr, tickets) = deferring_plugin().await;
+    let mut config = NovaConfig::default();
+    config.plugins.public_url = Some("https://nova.example.com/".to_string());
+    let server = TestServer::with_config(config).await.unwrap();
+    let client = server.client(owner());
+    let plugin = client
+        .register(&registration("later", &format!("http://{}/later", addr)))
+        .await
+        .unwrap();
+
+    let job = submit(&server, plugin.plugin_id).await;
+    let ticket = next_ticket(&tickets).await;
+    assert_eq!(ticket["job_id"], job.job_id.as_str());
+    assert_eq!(
+        ticket["callback_url"],
+        format!("https://nova.example.com/v1/jobs/{}/callback", job.job_id)
+    );
+    let callback = server.url(&format!("/v1/jobs/{}/callback", job.job_id));
+    let http = reqwest::Client::new();
+
+    let response = http
+        .post(&callback)
+        .header("x-nova-job-token", "guess")
+        .json(&json!({ "result": { "forecast": "sunny" } }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let token = ticket["callback_token"].as_str().unwrap();
+    let response = http
+        .post(&callback)
+        .header("x-nova-job-token", token)
+        .json(&json!({ "result": { "forecast": "sunny" } }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let posted: PluginJob = response.json().await.unwrap();
+    assert_eq!(posted.status, JobStatus::Succeeded);
+    let read = wait_for(&server, &owner(), &job.job_id).await;
+    assert_eq!(read.result.unwrap()["forecast"], "sunny");
+
+    // A second outcome does not replace the first
+    let response = http
+        .post(&callback)
+        .header("x-nova-job-token", token)
+        .json(&json!({ "error": "changed my mind" }))
+        .send()
+        .await
+        .unwrap();
+    let again: PluginJob = response.json().await.unwrap();
+    assert_eq!(again.status, JobStatus::Succeeded);
+}
+
+#[tokio::test]
+async fn callbacks_never_count_against_the_pre_auth_limit() {
+    let (addr, tickets) = deferring_plugin().await;
+    let mut config = NovaConfig::default();
+    config.plugins.public_url = Some("https://nova.example.com".to_string());
+    config.apis.pre_auth_rate_limit_per_minute = 3;
+    config.auth.enabled = true;
+    config.auth.allowed_keys = vec!["test-key".to_string()];
+    let header = config.auth.header_name.clone();
+    let server = TestServer::with_config(config).await.unwrap();
+    let plugin = server
+        .plugin_manager()
+        .register_plugin(
+            &owner(),
+            registration("later", &format!("http://{}/later", addr)),
+        )
+        .unwrap();
+    let response = server
+        .client(owner())
+        .request(
+            Method::POST,
+            &format!("/v1/plugins/{}/call?async=true", plugin.plugin_id),
+        )
+        .header(header.as_str(), "test-key")
+        .json(&json!({ "arguments": { "city": "Porto" } }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let job: PluginJob = response.json().await.unwrap();
+    let ticket = next_ticket(&tickets).await;
+    let token = ticket["callback_token"].as_str().unwrap();
+
+    // Callbacks carry a job token instead of an API key
+    let http = reqwest::Client::new();
+    for _ in 0..10 {
+        let response = http
+            .post(server.url(&format!("/v1/jobs/{}/callback", job.job_id)))
+            .header("x-nova-job-token", token)
+            .json(&json!({ "result": { "forecast": "sunny" } }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn jobs_survive_a_restart() {
+    let (addr, tickets) = deferring_plugin().await;
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let mut plugins = PluginsConfig {
+        public_url: Some("https://nova.example.com".to_string()),
+        allow_private_networks: true,
+        ..PluginsConfig::default()
+    };
+    plugins.allowed_schemes.push("http".to_string());
+    let manager = Arc::new(
+        PluginManager::in_memory()
+            .unwrap()
+            .with_egress_policy(EgressPolicy::new(&plugins)),
+    );
+    let plugin = manager
+        .register_plugin(
+            &owner(),
+            registration("later", &format!("http://{}/later", addr)),
+        )
+        .unwrap();
+
+    let before = Arc::new(
+        PluginJobs::persistent(db.open_tree("plugin_jobs").unwrap()).with_config(&plugins),
+    );
+    let job = before
+        .submit(
+            &manager,
+            &plugin,
+            &owner(),
+            json!({ "city": "Porto" }),
+            None,
+        )
+        .unwrap();
+    let ticket = next_ticket(&tickets).await;
+    wait_until(|| before.get(&job.job_id, &owner()).unwrap().status == JobStatus::Running).await;
+
+    // A new store over the same tree, as after a restart
+    let after = Arc::new(
+        PluginJobs::persistent(db.open_tree("plugin_jobs").unwrap()).with_config(&plugins),
+    );
+    assert_eq!(
+        after.get(&job.job_id, &owner()).unwrap().status,
+        JobStatus::Running
+    );
+    assert_eq!(after.resume(&manager).unwrap(), 1);
+    let token = ticket["callback_token"].as_str().unwrap();
+    let done = after
+        .complete(
+            &manager,
+            &job.job_id,
+            token,
+            JobCallback {
+                result: Some(json!({ "forecast": "rain" })),
+                error: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(done.status, JobStatus::Succeeded);
+    assert_eq!(after.resume(&manager).unwrap(), 0);
+}
+
+/// A plugin whose `/slow` is polled twice at `/slow/status` before it
+/// answers, `/broken` fails its first poll, and `/later` only ever accepts,
+/// sending the job ticket it received to the returned list.
+async fn deferring_plugin() -> (SocketAddr, Arc<Mutex<Vec<Value>>>) {
+    let polls = Arc::new(AtomicUsize::new(0));
+    let tickets: Arc<Mutex<Vec<Value>>> = Arc::default();
+    let seen = tickets.clone();
+    let app = Router::new()
+        .route(
+            "/slow",
+            post(|| async {
+                (
+                    AxumStatus::ACCEPTED,
+                    Json(json!({ "poll_url": "slow/status", "retry_after_secs": 0 })),
+                )
+            }),
+        )
+        .route(
+            "/slow/status",
+            get(move || {
+                let count = polls.fetch_add(1, Ordering::SeqCst) + 1;
+                async move {
+                    if count < 3 {
+                        (AxumStatus::ACCEPTED, Json(json!({})))
+                    } else {
+                        (
+                            AxumStatus::OK,
+                            Json(json!({ "polls": count, "secret": "hunter2" })),
+                        )
+                    }
+                }
+            }),
+        )
+        .route(
+            "/broken",
+            post(|| async { (AxumStatus::ACCEPTED, [("location", "/broken/status")]) }),
+        )
+        .route(
+            "/broken/status",
+            get(|| async { (AxumStatus::NOT_FOUND, "no such job") }),
+        )
+        .route(
+            "/later",
+            post(move |Json(body): Json<Value>| {
+                seen.lock().unwrap().push(body["job"].clone());
+                async { (AxumStatus::ACCEPTED, Json(json!({}))) }
+            }),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    (addr, tickets)
+}
+
+/// Starts an async call of `plugin_id` as the owner.
+async fn submit(server: &TestServer, plugin_id: u64) -> PluginJob {
+    let response = server
+        .client(owner())
+        .request(
+            Method::POST,
+            &format!("/v1/plugins/{}/call?async=true", plugin_id),
+        )
+        .json(&json!({ "arguments": { "city": "Porto" } }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    response.json().await.unwrap()
+}
+
+async fn wait_for(server: &TestServer, context: &RequestContext, job_id: &str) -> PluginJob {
+    let client = server.client(context.clone());
+    for _ in 0..250 {
+        let job: PluginJob = client
+            .request(Method::GET, &format!("/v1/jobs/{}", job_id))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        if job.status == JobStatus::Succeeded || job.status == JobStatus::Failed {
+            return job;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("job {} did not finish", job_id);
+}
+
+async fn wait_until(done: impl Fn() -> bool) {
+    for _ in 0..250 {
+        if done() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("condition not met within 5s");
+}
+
+async fn next_ticket(tickets: &Arc<Mutex<Vec<Value>>>) -> Value {
+    wait_until(|| !tickets.lock().unwrap().is_empty()).await;
+    tickets.lock().unwrap().remove(0)
+}
+
+fn registration(name: &str, endpoint_url: &str) -> PluginRegistrationRequest {
+    serde_json::from_value(json!({
+        "name": name,
+        "description": "Answers later",
+        "input_schema": {
+            "type": "object",
+            "properties": { "city": { "type": "string" } }
+        },
+        "endpoint_url": endpoint_url
+    }))
+    .unwrap()
+}
+
+fn owner() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
@@ -0,0 +1,162 @@
+use nova_mcp::plugins::manifest::substitute_env;
+use nova_mcp::plugins::{
+    ManifestFormat, PluginContextType, PluginEnableRequest, PluginManager, PluginManifest,
+    PluginMetadata, PluginUpdateRequest, RequestContext,
+};
+use nova_mcp::test_util::TestServer;
+use reqwest::{Method, StatusCode};
+use serde_json::{json, Value};
+
+const MANIFEST: &str = r#"
+name = "weather"
+description = "Current weather for a city"
+version = 2
+scopes = ["group"]
+tags = ["weather", "data"]
+
+[endpoint]
+url = "https://weather.example.com/nova"
+trust_level = "high"
+
+[schemas.input]
+type = "object"
+required = ["city"]
+properties = { city = { type = "string" } }
+"#;
+
+#[tokio::test]
+async fn registers_toml_and_json_manifests() {
+    let server = TestServer::start().await.unwrap();
+    let client = server.client(context(PluginContextType::User, "5"));
+    let post = |path: &str, content_type: Option<&str>, body: String| {
+        let mut request = client.request(Method::POST, path).body(body);
+        if let Some(content_type) = content_type {
+            request = request.header("content-type", content_type);
+        }
+        request.send()
+    };
+
+    let response = post(
+        "/plugins/register-manifest",
+        Some("application/toml"),
+        MANIFEST.to_string(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let metadata: PluginMetadata = response.json().await.unwrap();
+    assert_eq!(metadata.fq_name, "user_5_weather_v2");
+    assert_eq!(metadata.endpoint_url, "https://weather.example.com/nova");
+    assert_eq!(metadata.allowed_contexts, vec![PluginContextType::Group]);
+    assert_eq!(metadata.tags, vec!["weather", "data"]);
+    assert_eq!(metadata.input_schema["required"], json!(["city"]));
+
+    // JSON is recognised without a content type, on the /tools alias too
+    let manifest = json!({
+        "name": "forecast",
+        "description": "Tomorrow's weather",
+        "endpoint": { "url": "https://weather.example.com/forecast" },
+        "schemas": { "input": { "type": "object" } }
+    });
+    let response = post("/tools/register-manifest", None, manifest.to_string())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let metadata: PluginMetadata = response.json().await.unwrap();
+    assert_eq!(metadata.fq_name, "user_5_forecast_v1");
+    assert!(metadata.allowed_contexts.is_empty());
+
+    for (body, message) in [
+        (
+            MANIFEST.replace("[endpoint]", "endpoint_typo = 1\n[endpoint]"),
+            "unknown field `endpoint_typo`",
+        ),
+        (MANIFEST.replace("url = ", "uri = "), "unknown field `uri`"),
+        (
+            MANIFEST.replace(r#""data""#, r#""Big Data""#),
+            "Invalid tag 'Big Data'",
+        ),
+    ] {
+        let response = post("/plugins/register-manifest", Some("application/toml"), body)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let error: Value = response.json().await.unwrap();
+        let error = error["error"].as_str().unwrap();
+        assert!(error.contains(message), "{}", error);
+    }
+}
+
+#[test]
+fn scopes_limit_where_a_plugin_can_be_enabled() {
+    let manager = PluginManager::in_memory().unwrap();
+    let owner = context(PluginContextType::User, "5");
+    let manifest = PluginManifest::parse(MANIFEST, ManifestFormat::Toml).unwrap();
+    let plugin = manager
+        .register_plugin(&owner, manifest.into_registration())
+        .unwrap();
+    let enable = |context_type, context_id: &str| {
+        manager.set_enablement(PluginEnableRequest {
+            context_type,
+            context_id: context_id.to_string(),
+            plugin_id: plugin.plugin_id,
+            enable: true,
+            added_by: Some("7".to_string()),
+        })
+    };
+
+    let err = enable(PluginContextType::User, "7").unwrap_err();
+    assert!(err.to_string().contains("cannot be enabled for a user"));
+    enable(PluginContextType::Group, "-100").unwrap();
+    assert!(manager
+        .is_enabled(plugin.plugin_id, PluginContextType::Group, "-100")
+        .unwrap());
+
+    // Narrowing the scopes hides existing enablements outside them
+    manager
+        .update_plugin(
+            &owner,
+            plugin.plugin_id,
+            PluginUpdateRequest {
+                allowed_contexts: Some(vec![PluginContextType::User]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert!(!manager
+        .is_enabled(plugin.plugin_id, PluginContextType::Group, "-100")
+        .unwrap());
+    enable(PluginContextType::User, "7").unwrap();
+}
+
+#[test]
+fn env_placeholders_are_substituted() {
+    let lookup = |name: &str| (name == "TOKEN").then(|| "s3cret".to_string());
+    assert_eq!(
+        substitute_env("token = \"${TOKEN}\" # ${TOKEN}", lookup).unwrap(),
+        "token = \"s3cret\" # s3cret"
+    );
+    assert_eq!(
+        substitute_env("cost = \"$5 {x}\"", lookup).unwrap(),
+        "cost = \"$5 {x}\""
+    );
+    let err = substitute_env("token = \"${MISSING}\"", lookup).unwrap_err();
+    assert!(err.to_string().contains("MISSING"));
+
+    assert_eq!(
+        ManifestFormat::detect(Some("text/plain"), "  {\"name\": 1}"),
+        ManifestFormat::Json
+    );
+    assert_eq!(
+        ManifestFormat::detect(None, "name = \"x\""),
+        ManifestFormat::Toml
+    );
+}
+
+fn context(context_type: PluginContextType, id: &str) -> RequestContext {
+    RequestContext {
+        context_type,
+        context_id: id.to_string(),
+        actor_id: None,
+    }
+}
@@ -0,0 +1,171 @@
+use nova_mcp::config::NovaConfig;
+use nova_mcp::plugins::{
+    ErrorResponse, PluginContextType, PluginEnablementStatus, PluginRegistrationRequest,
+    RequestContext,
+};
+use nova_mcp::test_util::{StubPlugin, TestServer};
+use nova_mcp::tools::upstream_health::UpstreamState;
+use reqwest::{Method, StatusCode};
+use serde_json::json;
+
+#[tokio::test]
+async fn single_plugin_has_usage_and_health() {
+    let stub = StubPlugin::start().await.unwrap();
+    let server = TestServer::start().await.unwrap();
+    let owner = server.client(context(PluginContextType::User, "5"));
+    let member = server.client(context(PluginContextType::User, "7"));
+    let stranger = server.client(context(PluginContextType::User, "9"));
+    let plugin = owner
+        .register(&registration(&stub.url("/invoke")))
+        .await
+        .unwrap();
+
+    let details = owner.get_plugin(plugin.plugin_id).await.unwrap();
+    assert_eq!(details.metadata.fq_name, plugin.fq_name);
+    assert_eq!(details.metadata.installs, 0);
+    assert_eq!(details.health.state, UpstreamState::Unknown);
+    assert_eq!(details.health.name, format!("plugin:{}", plugin.fq_name));
+
+    member.enable(plugin.plugin_id, true).await.unwrap();
+    let result = member
+        .tools_call(&plugin.fq_name, json!({ "city": "Porto" }))
+        .await
+        .unwrap();
+    assert_eq!(result["isError"], false);
+
+    let details = member.get_plugin(plugin.plugin_id).await.unwrap();
+    assert_eq!(details.metadata.installs, 1);
+    assert!(details.metadata.last_used_at.is_some());
+    assert_eq!(details.health.state, UpstreamState::Healthy);
+    assert_eq!(details.health.total_calls, 1);
+
+    // Plugins the caller neither owns nor enabled look like missing ones
+    let hidden = stranger
+        .request(Method::GET, &format!("/v1/plugins/{}", plugin.plugin_id))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(hidden.status(), StatusCode::NOT_FOUND);
+    let error: ErrorResponse = hidden.json().await.unwrap();
+    assert_eq!(error.details.unwrap()["code"], "plugin_not_found");
+}
+
+#[tokio::test]
+async fn enablement_for_own_or_named_context() {
+    let mut config = NovaConfig::default();
+    config.admin.tokens = vec!["ops-token".into()];
+    let server = TestServer::with_config(config).await.unwrap();
+    let owner = server.client(context(PluginContextType::User, "5"));
+    let group = server.client(RequestContext {
+        actor_id: Some("11".to_string()),
+        ..context(PluginContextType::Group, "-100")
+    });
+    let plugin = owner
+        .register(&registration("https://weather.example.com/nova"))
+        .await
+        .unwrap();
+
+    // Registering enables the plugin for its owner
+    let status = owner.enablement(plugin.plugin_id, None).await.unwrap();
+    assert!(status.enabled);
+    assert_eq!(status.context_id, "5");
+    let other = context(PluginContextType::User, "6");
+    let path = format!("/v1/plugins/{}/enablement", plugin.plugin_id);
+    let named = |target: &RequestContext, admin: bool| {
+        let request = owner
+            .request(Method::GET, &path)
+            .query(&[("context", target.key())]);
+        if admin {
+            request.header("x-admin-token", "ops-token")
+        } else {
+            request
+        }
+    };
+    // Another context's record is for operators only
+    let refused = named(&other, false).send().await.unwrap();
+    assert_eq!(refused.status(), StatusCode::FORBIDDEN);
+    let status: PluginEnablementStatus = named(&other, true)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(!status.enabled);
+    assert_eq!(status.consent_ts, 0);
+    assert_eq!(status.context_id, "6");
+
+    // Nor can a caller enable a plugin for a context it did not authenticate as
+    let foreign = owner
+        .request(Method::POST, "/v1/plugins/enable")
+        .json(&json!({
+            "context_type": "user",
+            "context_id": "6",
+            "plugin_id": plugin.plugin_id,
+            "enable": true
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(foreign.status(), StatusCode::FORBIDDEN);
+
+    group.enable(plugin.plugin_id, true).await.unwrap();
+    let group_context = group.context().clone();
+    let status: PluginEnablementStatus = named(&group_context, true)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(status.enabled);
+    assert_eq!(status.context_type, PluginContextType::Group);
+    assert_eq!(status.added_by.as_deref(), Some("11"));
+    assert!(status.consent_ts > 0);
+
+    group.enable(plugin.plugin_id, false).await.unwrap();
+    let status = group.enablement(plugin.plugin_id, None).await.unwrap();
+    assert!(!status.enabled);
+    assert_eq!(status.added_by.as_deref(), Some("11"));
+
+    for (path, code) in [
+        ("/v1/plugins/999/enablement", StatusCode::NOT_FOUND),
+        (
+            &*format!("/v1/plugins/{}/enablement?context=user", plugin.plugin_id),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            &*format!(
+                "/v1/plugins/{}/enablement?context=user:abc",
+                plugin.plugin_id
+            ),
+            StatusCode::BAD_REQUEST,
+        ),
+    ] {
+        let response = owner
+            .request(Method::GET, path)
+            .header("x-admin-token", "ops-token")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), code, "{}", path);
+    }
+}
+
+fn registration(endpoint_url: &str) -> PluginRegistrationRequest {
+    serde_json::from_value(json!({
+        "name": "weather",
+        "description": "Current weather",
+        "input_schema": { "type": "object" },
+        "endpoint_url": endpoint_url
+    }))
+    .unwrap()
+}
+
+fn context(context_type: PluginContextType, id: &str) -> RequestContext {
+    RequestContext {
+        context_type,
+        context_id: id.to_string(),
+        actor_id: None,
+    }
+}
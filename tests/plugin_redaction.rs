@@ -0,0 +1,141 @@
+use axum::{http::StatusCode, routing::post, Json, Router};
+use nova_mcp::config::PluginsConfig;
+use nova_mcp::plugins::{
+    EgressPolicy, PluginContextType, PluginManager, PluginRegistrationRequest, RedactionRules,
+    RequestContext,
+};
+use serde_json::json;
+
+#[test]
+fn paths_and_field_patterns_redact_matching_values() {
+    let rules = RedactionRules::parse(&[
+        "$.account.iban",
+        "$.holders[*].email",
+        "$..seed",
+        "*_token",
+        "Password",
+    ])
+    .unwrap();
+    let mut value = json!({
+        "account": { "iban": "DE89", "owner": "Ada" },
+        "holders": [{ "email": "a@x", "name": "A" }, { "email": "b@x" }],
+        "wallet": { "keys": [{ "seed": "abandon" }] },
+        "session": { "refresh_token": "r1", "PASSWORD": "hunter2" },
+        "price": 1.5
+    });
+    assert_eq!(rules.apply(&mut value), 6);
+    assert_eq!(
+        value,
+        json!({
+            "account": { "iban": "[REDACTED]", "owner": "Ada" },
+            "holders": [{ "email": "[REDACTED]", "name": "A" }, { "email": "[REDACTED]" }],
+            "wallet": { "keys": [{ "seed": "[REDACTED]" }] },
+            "session": { "refresh_token": "[REDACTED]", "PASSWORD": "[REDACTED]" },
+            "price": 1.5
+        })
+    );
+
+    for invalid in ["$", "$.", "$.items[", "$[abc]", ""] {
+        assert!(RedactionRules::parse(&[invalid]).is_err(), "{}", invalid);
+    }
+}
+
+#[tokio::test]
+async fn responses_and_error_bodies_are_redacted() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let app = Router::new()
+        .route(
+            "/hook",
+            post(|| async {
+                Json(json!({ "user": { "phone": "+4912345", "api_key": "k1" }, "ok": true }))
+            }),
+        )
+        .route(
+            "/broken",
+            post(|| async {
+                (
+                    StatusCode::BAD_GATEWAY,
+                    Json(json!({ "error": "upstream", "api_key": "k1" })),
+                )
+            }),
+        );
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let config = PluginsConfig {
+        allowed_schemes: vec!["http".into()],
+        allow_private_networks: true,
+        redact: vec!["api_key".to_string()],
+        ..PluginsConfig::default()
+    };
+    let manager = manager()
+        .with_egress_policy(EgressPolicy::new(&config))
+        .with_redaction(RedactionRules::parse(&config.redact).unwrap());
+
+    let mut request = registration(&format!("http://127.0.0.1:{}/hook", port));
+    request.redact = vec!["$.user.phone".to_string()];
+    let metadata = manager.register_plugin(&owner(), request).unwrap();
+    assert_eq!(metadata.redact, vec!["$.user.phone"]);
+    let result = manager
+        .invoke_plugin(&metadata, &owner(), json!({}))
+        .await
+        .unwrap();
+    assert_eq!(
+        result,
+        json!({ "user": { "phone": "[REDACTED]", "api_key": "[REDACTED]" }, "ok": true })
+    );
+
+    let mut broken = registration(&format!("http://127.0.0.1:{}/broken", port));
+    broken.name = "broken".to_string();
+    let metadata = manager.register_plugin(&owner(), broken).unwrap();
+    let err = manager
+        .invoke_plugin(&metadata, &owner(), json!({}))
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("502"));
+    assert!(!err.contains("k1"));
+
+    let mut invalid = registration("https://example.com/hook");
+    invalid.name = "invalid".to_string();
+    invalid.redact = vec!["$.user[".to_string()];
+    assert!(manager.register_plugin(&owner(), invalid).is_err());
+}
+
+fn owner() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
+
+fn registration(endpoint: &str) -> PluginRegistrationRequest {
+    PluginRegistrationRequest {
+        name: "hook".to_string(),
+        description: "test".to_string(),
+        owner_id: None,
+        input_schema: json!({ "type": "object" }),
+        output_schema: None,
+        endpoint_url: endpoint.to_string(),
+        script: None,
+        version: 1,
+        trust_level: Default::default(),
+        client_certificate: None,
+        credentials: None,
+        redact: Vec::new(),
+        request_template: None,
+        listing: None,
+        allowed_contexts: Vec::new(),
+        tags: Vec::new(),
+        coerce_arguments: false,
+    }
+}
+
+fn manager() -> PluginManager {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager")
+}
@@ -0,0 +1,86 @@
+use nova_mcp::plugins::{
+    PluginContextType, PluginManager, PluginRegistrationRequest, RequestContext,
+};
+use serde_json::json;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn concurrent_registrations_keep_names_unique() {
+    let manager = Arc::new(test_manager());
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let manager = Arc::clone(&manager);
+            thread::spawn(move || {
+                manager
+                    .register_plugin(&user_context("42"), registration("Echo"))
+                    .is_ok()
+            })
+        })
+        .collect();
+    let successes = handles
+        .into_iter()
+        .map(|h| h.join().unwrap())
+        .filter(|ok| *ok)
+        .count();
+    assert_eq!(successes, 1);
+    assert_eq!(manager.list_plugins().unwrap().len(), 1);
+}
+
+#[test]
+fn name_is_reusable_after_unregister() {
+    let manager = test_manager();
+    let context = user_context("7");
+    let metadata = manager
+        .register_plugin(&context, registration("echo"))
+        .unwrap();
+    assert!(manager
+        .register_plugin(&context, registration("ECHO"))
+        .is_err());
+
+    manager
+        .unregister_plugin(&context, metadata.plugin_id)
+        .unwrap();
+    assert!(manager.get_plugin_by_fq_name(&metadata.fq_name).is_err());
+    assert!(manager
+        .register_plugin(&context, registration("echo"))
+        .is_ok());
+}
+
+fn registration(name: &str) -> PluginRegistrationRequest {
+    PluginRegistrationRequest {
+        name: name.to_string(),
+        description: "test".to_string(),
+        owner_id: None,
+        input_schema: json!({ "type": "object" }),
+        output_schema: None,
+        endpoint_url: "https://example.com/hook".to_string(),
+        script: None,
+        version: 1,
+        trust_level: Default::default(),
+        client_certificate: None,
+        credentials: None,
+        redact: Vec::new(),
+        request_template: None,
+        listing: None,
+        allowed_contexts: Vec::new(),
+        tags: Vec::new(),
+        coerce_arguments: false,
+    }
+}
+
+fn user_context(id: &str) -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: id.to_string(),
+        actor_id: None,
+    }
+}
+
+fn test_manager() -> PluginManager {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager")
+}
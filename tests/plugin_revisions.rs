@@ -0,0 +1,132 @@
+use nova_mcp::plugins::{
+    ErrorResponse, PluginContextType, PluginManager, PluginRegistrationRequest,
+    PluginUpdateRequest, RequestContext,
+};
+use nova_mcp::test_util::TestServer;
+use nova_mcp::NovaError;
+use reqwest::{Method, StatusCode};
+use serde_json::{json, Value};
+
+#[test]
+fn every_change_bumps_the_revision() {
+    let manager = PluginManager::in_memory().unwrap();
+    let plugin = manager.register_plugin(&owner(), registration()).unwrap();
+    assert_eq!(plugin.revision, 1);
+
+    let updated = manager
+        .update_plugin(&owner(), plugin.plugin_id, describe("v2", Some(1)))
+        .unwrap();
+    assert_eq!(updated.revision, 2);
+    let err = manager
+        .update_plugin(&owner(), plugin.plugin_id, describe("lost", Some(1)))
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        NovaError::RevisionConflict {
+            expected: 1,
+            current: 2,
+            ..
+        }
+    ));
+    assert_eq!(err.code(), "revision_conflict");
+
+    // Without a revision the change applies to whatever is current
+    let blind = manager
+        .update_plugin(&owner(), plugin.plugin_id, describe("v3", None))
+        .unwrap();
+    assert_eq!(blind.revision, 3);
+    assert_eq!(manager.get_plugin(plugin.plugin_id).unwrap().revision, 3);
+}
+
+#[tokio::test]
+async fn stale_if_match_gets_409_with_the_current_plugin() {
+    let server = TestServer::start().await.unwrap();
+    let client = server.client(owner());
+    let plugin = client.register(&registration()).await.unwrap();
+    let path = format!("/v1/plugins/{}", plugin.plugin_id);
+
+    let read = client.request(Method::GET, &path).send().await.unwrap();
+    assert_eq!(read.headers()["etag"], "\"1\"");
+
+    let first = client
+        .request(Method::PATCH, &path)
+        .header("if-match", "\"1\"")
+        .json(&json!({ "description": "Mine" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(first.status(), StatusCode::OK);
+    assert_eq!(first.headers()["etag"], "\"2\"");
+    let first: Value = first.json().await.unwrap();
+    assert_eq!(first["revision"], 2);
+
+    // A second editor still holding revision 1
+    let second = client
+        .request(Method::PATCH, &path)
+        .header("if-match", "\"1\"")
+        .json(&json!({ "description": "Theirs" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(second.status(), StatusCode::CONFLICT);
+    let error: ErrorResponse = second.json().await.unwrap();
+    let details = error.details.unwrap();
+    assert_eq!(details["code"], "revision_conflict");
+    assert_eq!(details["category"], "conflict");
+    assert_eq!(details["details"]["current"], 2);
+    assert_eq!(details["details"]["plugin"]["description"], "Mine");
+    assert_eq!(details["details"]["plugin"]["revision"], 2);
+
+    // PUT takes the same headers; `*` matches any revision
+    let put = client
+        .request(Method::PUT, &path)
+        .header("if-match", "*")
+        .json(&json!({ "description": "Anyone" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(put.status(), StatusCode::OK);
+
+    for (if_match, body) in [
+        ("\"4\"", json!({ "description": "x", "revision": 3 })),
+        ("latest", json!({ "description": "x" })),
+    ] {
+        let response = client
+            .request(Method::PATCH, &path)
+            .header("if-match", if_match)
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", if_match);
+    }
+    let current = client.get_plugin(plugin.plugin_id).await.unwrap();
+    assert_eq!(current.metadata.revision, 3);
+    assert_eq!(current.metadata.description, "Anyone");
+}
+
+fn describe(description: &str, revision: Option<u64>) -> PluginUpdateRequest {
+    PluginUpdateRequest {
+        description: Some(description.to_string()),
+        revision,
+        ..PluginUpdateRequest::default()
+    }
+}
+
+fn registration() -> PluginRegistrationRequest {
+    serde_json::from_value(json!({
+        "name": "weather",
+        "description": "Current weather",
+        "input_schema": { "type": "object" },
+        "endpoint_url": "https://weather.example.com/nova"
+    }))
+    .unwrap()
+}
+
+fn owner() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
@@ -0,0 +1,218 @@
+use axum::body::{Body, Bytes};
+use axum::http::header;
+use axum::routing::post;
+use axum::Router;
+use nova_mcp::plugins::{PluginContextType, PluginRegistrationRequest, RequestContext};
+use nova_mcp::test_util::TestServer;
+use serde_json::{json, Value};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_stream::StreamExt;
+
+type Feed = Arc<Mutex<Option<mpsc::UnboundedReceiver<Bytes>>>>;
+
+#[tokio::test]
+async fn streamed_answers_end_with_their_last_chunk() {
+    let (addr, _) = stream_plugin().await;
+    let server = TestServer::start().await.unwrap();
+    let client = server.client(owner());
+
+    for (name, path) in [("lines", "/lines"), ("events", "/events")] {
+        let plugin = client
+            .register(&registration(name, &format!("http://{}{}", addr, path)))
+            .await
+            .unwrap();
+        let result = client.tools_call(&plugin.fq_name, json!({})).await.unwrap();
+        assert_eq!(result["isError"], false, "{}", result);
+        let text = result["content"][0]["text"].as_str().unwrap();
+        let answer: Value = serde_json::from_str(text).unwrap();
+        assert_eq!(
+            answer,
+            json!({ "done": true, "temperature": 21 }),
+            "{}",
+            name
+        );
+    }
+}
+
+#[tokio::test]
+async fn chunks_reach_the_client_as_progress_while_the_plugin_runs() {
+    let (addr, feed) = stream_plugin().await;
+    let server = TestServer::start().await.unwrap();
+    let client = server.client(owner());
+    let mut request = registration("forecast", &format!("http://{}/live", addr));
+    request.redact = vec!["token".to_string()];
+    let plugin = client.register(&request).await.unwrap();
+
+    let http = reqwest::Client::new();
+    let url = server.url("/mcp");
+    let init = http
+        .post(&url)
+        .header("x-nova-context-type", "user")
+        .header("x-nova-context-id", "5")
+        .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize" }))
+        .send()
+        .await
+        .unwrap();
+    let session = init.headers()["mcp-session-id"]
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    *feed.lock().unwrap() = Some(receiver);
+    sender
+        .send(Bytes::from("{\"step\":1,\"token\":\"secret\"}\n{\"st"))
+        .unwrap();
+
+    let mut response = http
+        .post(&url)
+        .header("accept", "text/event-stream")
+        .header("mcp-session-id", &session)
+        .json(&json!({
+            "jsonrpc": "2.0", "id": 2, "method": "tools/call",
+            "params": {
+                "name": plugin.fq_name,
+                "arguments": {},
+                "_meta": { "progressToken": "job-1" }
+            }
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let mut buffer = String::new();
+
+    // The first chunk arrives before the plugin has finished
+    let first = next_message(&mut response, &mut buffer).await;
+    assert_eq!(first["method"], "notifications/progress");
+    assert_eq!(first["params"]["progressToken"], "job-1");
+    assert_eq!(first["params"]["progress"], 1);
+    let message = first["params"]["message"].as_str().unwrap();
+    assert!(message.contains("\"step\":1"), "{}", message);
+    assert!(!message.contains("secret"), "{}", message);
+
+    sender
+        .send(Bytes::from("ep\":2}\n{\"done\":true,\"temperature\":21}\n"))
+        .unwrap();
+    drop(sender);
+
+    let second = next_message(&mut response, &mut buffer).await;
+    assert_eq!(second["params"]["progress"], 2);
+    assert_eq!(second["params"]["message"], "{\"step\":2}");
+    let third = next_message(&mut response, &mut buffer).await;
+    assert_eq!(third["params"]["progress"], 3);
+    let reply = next_message(&mut response, &mut buffer).await;
+    assert_eq!(reply["id"], 2);
+    let text = reply["result"]["content"][0]["text"].as_str().unwrap();
+    assert!(text.contains("temperature"), "{}", text);
+}
+
+/// The next JSON-RPC message on an SSE response; `buffer` keeps what was
+/// read past it.
+async fn next_message(response: &mut reqwest::Response, buffer: &mut String) -> Value {
+    loop {
+        if let Some(end) = buffer.find("\n\n") {
+            let event: String = buffer.drain(..end + 2).collect();
+            let data: String = event
+                .lines()
+                .filter_map(|line| line.strip_prefix("data:"))
+                .map(str::trim)
+                .collect();
+            if !data.is_empty() {
+                return serde_json::from_str(&data).unwrap();
+            }
+            continue;
+        }
+        let chunk = tokio::time::timeout(Duration::from_secs(5), response.chunk())
+            .await
+            .expect("no event within 5s")
+            .unwrap()
+            .expect("stream ended");
+        buffer.push_str(&String::from_utf8_lossy(&chunk));
+    }
+}
+
+/// An endpoint answering `/lines` with NDJSON, `/events` with SSE and
+/// `/live` with whatever is sent to the receiver put in the returned feed.
+async fn stream_plugin() -> (SocketAddr, Feed) {
+    let feed: Feed = Arc::default();
+    let live = feed.clone();
+    let app = Router::new()
+        .route(
+            "/lines",
+            post(|| async {
+                streamed(
+                    "application/x-ndjson",
+                    [
+                        "{\"step\":1}\n{\"st",
+                        "ep\":2}\n",
+                        "{\"done\":true,\"temperature\":21}",
+                    ],
+                )
+            }),
+        )
+        .route(
+            "/events",
+            post(|| async {
+                streamed(
+                    "text/event-stream; charset=utf-8",
+                    [
+                        "event: progress\r\ndata: {\"step\":1}\r\n\r\n: keep-alive\n",
+                        "data: half\ndata: way\n\nid: 3\ndata: {\"done\":true,",
+                        "\"temperature\":21}\n\n",
+                    ],
+                )
+            }),
+        )
+        .route(
+            "/live",
+            post(move || {
+                let receiver = live.lock().unwrap().take().expect("no feed");
+                async move {
+                    let body = UnboundedReceiverStream::new(receiver).map(Ok::<_, Infallible>);
+                    (
+                        [(header::CONTENT_TYPE, "application/x-ndjson")],
+                        Body::from_stream(body),
+                    )
+                }
+            }),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    (addr, feed)
+}
+
+fn streamed(
+    content_type: &'static str,
+    parts: [&'static str; 3],
+) -> ([(header::HeaderName, &'static str); 1], Body) {
+    let body = tokio_stream::iter(parts).map(|part| Ok::<_, Infallible>(Bytes::from(part)));
+    (
+        [(header::CONTENT_TYPE, content_type)],
+        Body::from_stream(body),
+    )
+}
+
+fn registration(name: &str, endpoint_url: &str) -> PluginRegistrationRequest {
+    serde_json::from_value(json!({
+        "name": name,
+        "description": "Streams its answer",
+        "input_schema": { "type": "object" },
+        "endpoint_url": endpoint_url
+    }))
+    .unwrap()
+}
+
+fn owner() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
@@ -0,0 +1,139 @@
+use axum::{routing::post, Json, Router};
+use nova_mcp::config::PluginsConfig;
+use nova_mcp::plugins::{
+    EgressPolicy, PluginContextType, PluginManager, PluginRegistrationRequest, PluginUpdateRequest,
+    RequestContext, RequestTemplate,
+};
+use serde_json::{json, Value};
+
+#[test]
+fn placeholders_keep_types_or_interpolate_text() {
+    let template = RequestTemplate::parse(&json!({
+        "addr": "{{ arguments.address }}",
+        "limit": "{{arguments.limit}}",
+        "first": "{{ arguments.pairs.0 }}",
+        "label": "{{ context_type }}:{{ context_id }} wants {{ arguments.limit }}",
+        "missing": "{{ arguments.nope }}",
+        "nested": [{ "id": "{{ arguments.address }}" }, 7, true],
+        "static": "plain"
+    }))
+    .unwrap();
+    let scope = json!({
+        "context_type": "user",
+        "context_id": "5",
+        "arguments": { "address": "0x1", "limit": 10, "pairs": ["APT", "USDC"] }
+    });
+    assert_eq!(
+        template.render(&scope),
+        json!({
+            "addr": "0x1",
+            "limit": 10,
+            "first": "APT",
+            "label": "user:5 wants 10",
+            "missing": null,
+            "nested": [{ "id": "0x1" }, 7, true],
+            "static": "plain"
+        })
+    );
+
+    for invalid in [
+        json!("{{ arguments.x"),
+        json!({ "a": "{{ env.HOME }}" }),
+        json!(["{{ arguments..x }}"]),
+        json!("{{}}"),
+    ] {
+        assert!(RequestTemplate::parse(&invalid).is_err(), "{}", invalid);
+    }
+}
+
+#[tokio::test]
+async fn endpoints_receive_the_rendered_body() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let app = Router::new().route(
+        "/echo",
+        post(|Json(body): Json<Value>| async { Json(body) }),
+    );
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let manager = manager().with_egress_policy(EgressPolicy::new(&PluginsConfig {
+        allowed_schemes: vec!["http".into()],
+        allow_private_networks: true,
+        ..PluginsConfig::default()
+    }));
+    let mut request = registration(&format!("http://127.0.0.1:{}/echo", port));
+    request.request_template = Some(json!({
+        "query": { "owner": "{{ arguments.address }}" },
+        "page_size": "{{ arguments.limit }}"
+    }));
+    let metadata = manager.register_plugin(&owner(), request).unwrap();
+    let echoed = manager
+        .invoke_plugin(&metadata, &owner(), json!({ "address": "0x1", "limit": 3 }))
+        .await
+        .unwrap();
+    assert_eq!(
+        echoed,
+        json!({ "query": { "owner": "0x1" }, "page_size": 3 })
+    );
+
+    let invalid = PluginUpdateRequest {
+        request_template: Some(Some(json!("{{ secrets.key }}"))),
+        ..PluginUpdateRequest::default()
+    };
+    assert!(manager
+        .update_plugin(&owner(), metadata.plugin_id, invalid)
+        .is_err());
+
+    let removed = PluginUpdateRequest {
+        request_template: Some(None),
+        ..PluginUpdateRequest::default()
+    };
+    let metadata = manager
+        .update_plugin(&owner(), metadata.plugin_id, removed)
+        .unwrap();
+    assert!(metadata.request_template.is_none());
+    let echoed = manager
+        .invoke_plugin(&metadata, &owner(), json!({ "address": "0x1" }))
+        .await
+        .unwrap();
+    assert_eq!(echoed["arguments"], json!({ "address": "0x1" }));
+    assert_eq!(echoed["context_id"], "5");
+}
+
+fn owner() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
+
+fn registration(endpoint: &str) -> PluginRegistrationRequest {
+    PluginRegistrationRequest {
+        name: "echo".to_string(),
+        description: "test".to_string(),
+        owner_id: None,
+        input_schema: json!({ "type": "object" }),
+        output_schema: None,
+        endpoint_url: endpoint.to_string(),
+        script: None,
+        version: 1,
+        trust_level: Default::default(),
+        client_certificate: None,
+        credentials: None,
+        redact: Vec::new(),
+        request_template: None,
+        listing: None,
+        allowed_contexts: Vec::new(),
+        tags: Vec::new(),
+        coerce_arguments: false,
+    }
+}
+
+fn manager() -> PluginManager {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager")
+}
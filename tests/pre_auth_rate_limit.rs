@@ -0,0 +1,124 @@
+use nova_mcp::clock::{ManualClock, SharedClock};
+use nova_mcp::{NovaConfig, NovaServer, PluginManager};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+// 45 seconds into a minute
+const NOW: i64 = 1_700_000_025;
+
+fn start(mut config: NovaConfig, clock: &ManualClock) -> String {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    config.server.port = port;
+    let plugin_manager = PluginManager::in_memory()
+        .unwrap()
+        .with_clock(SharedClock::new(clock.clone()));
+    let server = NovaServer::new(config.clone(), Arc::new(plugin_manager));
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+    format!("http://127.0.0.1:{}", port)
+}
+
+async fn wait_for(client: &reqwest::Client, base: &str) {
+    for _ in 0..50 {
+        if client.get(format!("{}/healthz", base)).send().await.is_ok() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("server did not start");
+}
+
+fn tools_list() -> Value {
+    json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" })
+}
+
+#[tokio::test]
+async fn junk_requests_exhaust_the_ip_budget() {
+    let mut config = NovaConfig::default();
+    config.auth.enabled = true;
+    config.auth.allowed_keys = vec!["right-key-123".into()];
+    config.auth.lockout_threshold = 0;
+    config.apis.pre_auth_rate_limit_per_minute = 3;
+    let clock = ManualClock::at(NOW);
+    let base = start(config, &clock);
+    let client = reqwest::Client::new();
+    wait_for(&client, &base).await;
+
+    // Health checks carry no key and are not counted
+    for _ in 0..5 {
+        let health = client
+            .get(format!("{}/healthz", base))
+            .send()
+            .await
+            .unwrap();
+        assert!(health.status().is_success());
+    }
+
+    let unauthenticated = client
+        .post(format!("{}/rpc", base))
+        .json(&tools_list())
+        .send()
+        .await
+        .unwrap();
+    let body: Value = unauthenticated.json().await.unwrap();
+    assert_eq!(body["error"]["message"], "Unauthorized");
+    for _ in 0..2 {
+        let malformed = client
+            .post(format!("{}/rpc", base))
+            .header("x-api-key", "right-key-123")
+            .header("content-type", "application/json")
+            .body("{not json")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(malformed.status(), 400);
+    }
+
+    let authenticated = || {
+        client
+            .post(format!("{}/rpc", base))
+            .header("x-api-key", "right-key-123")
+            .header("x-nova-context-type", "user")
+            .header("x-nova-context-id", "7")
+            .json(&tools_list())
+            .send()
+    };
+    let refused = authenticated().await.unwrap();
+    assert_eq!(refused.status(), 429);
+    // Until the minute is over
+    assert_eq!(refused.headers()["retry-after"], "15");
+    let body: Value = refused.json().await.unwrap();
+    assert_eq!(body["error"], "Too many failed or malformed requests");
+
+    clock.advance(Duration::from_secs(15));
+    let accepted = authenticated().await.unwrap();
+    assert!(accepted.status().is_success());
+}
+
+#[tokio::test]
+async fn well_formed_requests_do_not_count() {
+    let mut config = NovaConfig::default();
+    config.apis.pre_auth_rate_limit_per_minute = 1;
+    let base = start(config, &ManualClock::at(NOW));
+    let client = reqwest::Client::new();
+    wait_for(&client, &base).await;
+
+    for _ in 0..3 {
+        let ok: Value = client
+            .post(format!("{}/rpc", base))
+            .header("x-nova-context-type", "user")
+            .header("x-nova-context-id", "7")
+            .json(&tools_list())
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert!(ok["result"]["tools"].is_array());
+    }
+}
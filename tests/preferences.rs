@@ -0,0 +1,208 @@
+use chrono::DateTime;
+use nova_mcp::mcp::{dto::McpRequest, handler};
+use nova_mcp::plugins::{PluginContextType, PluginManager, RequestContext};
+use nova_mcp::preferences::{
+    ContextPreferences, Localizer, NumberFormat, PreferenceStore, ResultFormat,
+};
+use nova_mcp::{NovaConfig, NovaServer};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn preferences(currency: &str, locale: &str, timezone: &str) -> ContextPreferences {
+    ContextPreferences {
+        currency: currency.to_string(),
+        locale: locale.to_string(),
+        timezone: timezone.to_string(),
+        number_format: NumberFormat::Standard,
+        result_format: ResultFormat::Full,
+    }
+}
+
+#[test]
+fn localizes_usd_values_and_timestamps() {
+    let rates = HashMap::from([("EUR".to_string(), 0.5)]);
+    let localizer = Localizer::new(&preferences("EUR", "de-DE", "Europe/Berlin"), &rates);
+    let output = localizer.localize(&json!({
+        "attributes": {
+            "name": "WETH / USDC",
+            "reserve_in_usd": "2469135.8",
+            "volume_usd": { "h24": 1000 },
+            "base_token_price_usd": "0.000123456",
+            "pool_created_at": "2024-01-15T12:00:00Z",
+            "fdv_usd": null
+        }
+    }));
+    let attributes = &output["attributes"];
+    assert_eq!(attributes["name"], "WETH / USDC");
+    assert_eq!(attributes["reserve_in_usd"], "1.234.567,90 €");
+    assert_eq!(attributes["volume_usd"]["h24"], "500,00 €");
+    assert_eq!(attributes["base_token_price_usd"], "0,00006173 €");
+    assert_eq!(attributes["pool_created_at"], "2024-01-15 13:00:00 CET");
+    assert_eq!(attributes["fdv_usd"], Value::Null);
+}
+
+#[test]
+fn number_formats() {
+    let mut prefs = preferences("USD", "en-US", "UTC");
+    let rates = HashMap::new();
+    assert_eq!(Localizer::new(&prefs, &rates).money(-1234.5), "-$1,234.50");
+    prefs.number_format = NumberFormat::Compact;
+    assert_eq!(Localizer::new(&prefs, &rates).money(2_500_000.0), "$2.50M");
+    prefs.number_format = NumberFormat::Plain;
+    assert_eq!(Localizer::new(&prefs, &rates).money(1234.5), "$1234.50");
+    let at = DateTime::parse_from_rfc3339("2024-07-01T00:30:00+02:00").unwrap();
+    assert_eq!(
+        Localizer::new(&prefs, &rates).timestamp(&at),
+        "2024-06-30 22:30:00 UTC"
+    );
+}
+
+#[test]
+fn validation_rejects_unknown_values() {
+    let rates = HashMap::from([("EUR".to_string(), 0.9)]);
+    let mut ok = preferences("eur", "fr_FR", "America/New_York");
+    ok.validate(&rates).unwrap();
+    assert_eq!(ok.currency, "EUR");
+
+    let err = preferences("JPY", "not a locale", "Mars/Base")
+        .validate(&rates)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("currency"), "{}", err);
+    assert!(err.contains("locale"), "{}", err);
+    assert!(err.contains("timezone"), "{}", err);
+}
+
+#[test]
+fn sled_store_round_trips() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let store = PreferenceStore::persistent(db.open_tree("context_preferences").unwrap());
+    let context = RequestContext {
+        context_type: PluginContextType::Group,
+        context_id: "-100".to_string(),
+        actor_id: None,
+    };
+    assert_eq!(store.get(&context).unwrap(), None);
+    let prefs = preferences("USD", "en-GB", "Europe/London");
+    store.put(&context, &prefs).unwrap();
+    assert_eq!(store.get(&context).unwrap(), Some(prefs));
+    assert!(store.remove(&context).unwrap());
+    assert_eq!(
+        store.get_or_default(&context).unwrap(),
+        ContextPreferences::default()
+    );
+}
+
+#[tokio::test]
+async fn set_my_preferences_merges_into_the_callers_context() {
+    let server = test_server(NovaConfig::default());
+    let call = |arguments: Value| McpRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: "tools/call".to_string(),
+        params: Some(json!({ "name": "set_my_preferences", "arguments": arguments })),
+        context_type: Some("user".to_string()),
+        context_id: Some("9".to_string()),
+        actor_id: None,
+    };
+
+    let resp =
+        handler::handle_request(&server, call(json!({ "timezone": "Asia/Tokyo" })), None).await;
+    assert!(resp.error.is_none(), "{:?}", resp.error);
+    let resp =
+        handler::handle_request(&server, call(json!({ "number_format": "compact" })), None).await;
+    assert!(resp.error.is_none(), "{:?}", resp.error);
+
+    let context = RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "9".to_string(),
+        actor_id: None,
+    };
+    let stored = server.preferences().get(&context).unwrap().unwrap();
+    assert_eq!(stored.timezone, "Asia/Tokyo");
+    assert_eq!(stored.number_format, NumberFormat::Compact);
+
+    // EUR has no configured rate
+    let resp = handler::handle_request(&server, call(json!({ "currency": "EUR" })), None).await;
+    let err = resp.error.unwrap();
+    assert_eq!(err.code, -32602);
+    assert_eq!(err.data.unwrap()["code"], "validation_failed");
+}
+
+#[tokio::test]
+async fn http_crud() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig::default();
+    config.server.port = port;
+    config.preferences.usd_rates.insert("GBP".to_string(), 0.8);
+    let server = test_server(config.clone());
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let client = reqwest::Client::new();
+    let url = format!("http://127.0.0.1:{}/preferences", port);
+    let request = |builder: reqwest::RequestBuilder| {
+        builder
+            .header("x-nova-context-type", "user")
+            .header("x-nova-context-id", "42")
+    };
+    let mut initial = None;
+    for _ in 0..50 {
+        match request(client.get(&url)).send().await {
+            Ok(resp) => {
+                initial = Some(resp);
+                break;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(50)).await,
+        }
+    }
+    let initial: Value = initial.expect("server did not start").json().await.unwrap();
+    assert_eq!(initial["currency"], "USD");
+
+    let saved = request(client.put(&url))
+        .json(&json!({ "currency": "gbp", "locale": "en-GB" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(saved.status(), 200);
+    let saved: Value = saved.json().await.unwrap();
+    assert_eq!(saved["currency"], "GBP");
+    assert_eq!(saved["timezone"], "UTC");
+
+    let rejected = request(client.put(&url))
+        .json(&json!({ "timezone": "Nowhere/Special" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(rejected.status(), 400);
+
+    let fetched: Value = request(client.get(&url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(fetched["locale"], "en-GB");
+
+    let deleted = request(client.delete(&url)).send().await.unwrap();
+    assert_eq!(deleted.status(), 204);
+    let deleted = request(client.delete(&url)).send().await.unwrap();
+    assert_eq!(deleted.status(), 404);
+}
+
+fn test_server(config: NovaConfig) -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager"),
+    );
+    NovaServer::new(config, plugin_manager)
+}
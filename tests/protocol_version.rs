@@ -0,0 +1,87 @@
+use nova_mcp::mcp::dto::McpRequest;
+use nova_mcp::mcp::handler::handle_session_request;
+use nova_mcp::mcp::protocol::ProtocolVersion;
+use nova_mcp::mcp::session::McpSession;
+use nova_mcp::{NovaConfig, NovaServer, PluginManager};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+fn request(method: &str, params: Option<Value>) -> McpRequest {
+    McpRequest {
+        jsonrpc: "2.0".into(),
+        id: Some(json!(1)),
+        method: method.into(),
+        params,
+        context_type: Some("user".into()),
+        context_id: Some("1".into()),
+        actor_id: None,
+    }
+}
+
+#[tokio::test]
+async fn initialize_echoes_supported_version_and_pins_session() {
+    let server = test_server();
+    let mut session = McpSession::new();
+    let resp = handle_session_request(
+        &server,
+        &mut session,
+        request(
+            "initialize",
+            Some(json!({ "protocolVersion": "2025-03-26" })),
+        ),
+        None,
+    )
+    .await;
+    assert_eq!(resp.result.unwrap()["protocolVersion"], "2025-03-26");
+    assert_eq!(session.protocol_version(), ProtocolVersion::V2025_03_26);
+
+    let resp =
+        handle_session_request(&server, &mut session, request("tools/list", None), None).await;
+    let tools = resp.result.unwrap()["tools"].clone();
+    assert_eq!(tools[0]["annotations"]["readOnlyHint"], true);
+}
+
+#[tokio::test]
+async fn unsupported_version_is_rejected_with_supported_list() {
+    let server = test_server();
+    let mut session = McpSession::new();
+    let resp = handle_session_request(
+        &server,
+        &mut session,
+        request(
+            "initialize",
+            Some(json!({ "protocolVersion": "1999-01-01" })),
+        ),
+        None,
+    )
+    .await;
+    let error = resp.error.unwrap();
+    assert_eq!(error.code, -32602);
+    assert_eq!(error.data.unwrap()["supported"][0], "2024-11-05");
+    assert!(!session.is_initialized());
+}
+
+#[tokio::test]
+async fn legacy_sessions_omit_newer_tool_fields() {
+    let server = test_server();
+    let mut session = McpSession::new();
+    let resp =
+        handle_session_request(&server, &mut session, request("initialize", None), None).await;
+    assert_eq!(resp.result.unwrap()["protocolVersion"], "2024-11-05");
+
+    let resp =
+        handle_session_request(&server, &mut session, request("tools/list", None), None).await;
+    let tools = resp.result.unwrap()["tools"].clone();
+    assert!(tools[0].get("annotations").is_none());
+}
+
+fn test_server() -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager"),
+    );
+    NovaServer::new(NovaConfig::default(), plugin_manager)
+}
@@ -12,10 +12,14 @@ async fn get_gecko_networks_live() {
     let call = ToolCall {
         name: "get_gecko_networks".into(),
         arguments: json!({}),
+        select: None,
+        format: None,
+        priority: None,
     };
     let context = RequestContext {
         context_type: PluginContextType::User,
         context_id: "0".to_string(),
+        actor_id: None,
     };
     let res = server.handle_tool_call(call, &context).await.unwrap();
     assert!(res.content.contains("networks"));
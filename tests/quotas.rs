@@ -0,0 +1,270 @@
+use nova_mcp::clock::{ManualClock, SharedClock};
+use nova_mcp::config::QuotaLimits;
+use nova_mcp::mcp::{dto::McpRequest, handler};
+use nova_mcp::plugins::{PluginContextType, RequestContext};
+use nova_mcp::quotas::{QuotaOverride, QuotaStore};
+use nova_mcp::{NovaConfig, NovaError, NovaServer};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+
+fn context(context_id: &str) -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: context_id.to_string(),
+        actor_id: None,
+    }
+}
+
+#[test]
+fn caps_refuse_calls_without_counting_them() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let store = QuotaStore::persistent(db.open_tree("quotas").unwrap());
+    let mut config = NovaConfig::default().quotas;
+    config.daily_calls = 5;
+    config.monthly_calls = 2;
+    config.plugins = HashMap::from([(
+        "user_1_echo_v1".to_string(),
+        QuotaLimits {
+            daily_calls: 1,
+            monthly_calls: 0,
+        },
+    )]);
+    let user = context("1");
+
+    store.consume(&user, None, &config).unwrap();
+    store.consume(&user, None, &config).unwrap();
+    let err = store.consume(&user, None, &config).unwrap_err();
+    assert_eq!(err.code(), "quota_exceeded");
+    match err {
+        NovaError::QuotaExceeded { period, limit, .. } => {
+            assert_eq!((period.as_str(), limit), ("monthly", 2));
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+    // The refused call did not use up a daily slot either
+    let usage = store.usage(&user, &config).unwrap();
+    assert_eq!(usage.daily.used, 2);
+    assert_eq!(usage.daily.remaining, Some(3));
+    assert_eq!(usage.monthly.remaining, Some(0));
+    assert!(usage.monthly.resets_at.ends_with("T00:00:00Z"));
+
+    store
+        .consume(&user, Some("user_1_echo_v1"), &config)
+        .unwrap();
+    assert!(store
+        .consume(&user, Some("user_1_echo_v1"), &config)
+        .is_err());
+    let usage = store.usage(&user, &config).unwrap();
+    let echo = &usage.plugins["user_1_echo_v1"];
+    assert_eq!((echo.daily.used, echo.daily.limit), (1, Some(1)));
+    assert_eq!(echo.monthly.limit, None);
+
+    // Other contexts have their own counters
+    store.consume(&context("2"), None, &config).unwrap();
+
+    let previous = store
+        .set_override(
+            &user,
+            None,
+            QuotaOverride {
+                daily_calls: None,
+                monthly_calls: Some(0),
+            },
+        )
+        .unwrap();
+    assert_eq!(previous, None);
+    store.consume(&user, None, &config).unwrap();
+    let usage = store.usage(&user, &config).unwrap();
+    assert_eq!(usage.monthly.limit, None);
+    assert_eq!(usage.daily.limit, Some(5));
+
+    // Nothing is from a past period yet
+    assert_eq!(store.sweep().unwrap(), 0);
+    assert_eq!(store.remove(&user).unwrap(), 5);
+    assert_eq!(store.usage(&user, &config).unwrap().daily.used, 0);
+}
+
+#[test]
+fn counters_roll_over_at_utc_day_and_month_boundaries() {
+    // 2026-01-30T23:59:00Z
+    let clock = ManualClock::at(1_769_817_540);
+    let store = QuotaStore::in_memory().with_clock(SharedClock::new(clock.clone()));
+    let mut config = NovaConfig::default().quotas;
+    config.daily_calls = 2;
+    config.monthly_calls = 3;
+    let user = context("1");
+    let refused = |store: &QuotaStore| match store.consume(&user, None, &config) {
+        Err(NovaError::QuotaExceeded {
+            period, resets_at, ..
+        }) => (period, resets_at),
+        other => panic!("expected quota_exceeded, got {:?}", other),
+    };
+
+    store.consume(&user, None, &config).unwrap();
+    store.consume(&user, None, &config).unwrap();
+    assert_eq!(
+        refused(&store),
+        ("daily".to_string(), "2026-01-31T00:00:00Z".to_string())
+    );
+
+    // A new day frees the daily cap but not the month's
+    clock.advance(Duration::from_secs(60));
+    store.consume(&user, None, &config).unwrap();
+    assert_eq!(
+        refused(&store),
+        ("monthly".to_string(), "2026-02-01T00:00:00Z".to_string())
+    );
+    let usage = store.usage(&user, &config).unwrap();
+    assert_eq!((usage.daily.used, usage.monthly.used), (1, 3));
+
+    clock.advance(Duration::from_secs(86_400));
+    store.consume(&user, None, &config).unwrap();
+    let usage = store.usage(&user, &config).unwrap();
+    assert_eq!((usage.daily.used, usage.monthly.used), (1, 1));
+    assert_eq!(usage.daily.resets_at, "2026-02-02T00:00:00Z");
+    assert_eq!(usage.monthly.resets_at, "2026-03-01T00:00:00Z");
+
+    // January's two days and the month itself are past periods now
+    assert_eq!(store.sweep().unwrap(), 3);
+    assert_eq!(store.usage(&user, &config).unwrap().monthly.used, 1);
+}
+
+#[tokio::test]
+async fn tool_calls_spend_the_context_quota() {
+    let mut config = NovaConfig::default();
+    config.quotas.daily_calls = 2;
+    let server = NovaServer::in_memory(config).unwrap();
+    let call = |name: &str, arguments: Value| McpRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: "tools/call".to_string(),
+        params: Some(json!({ "name": name, "arguments": arguments })),
+        context_type: Some("user".to_string()),
+        context_id: Some("9".to_string()),
+        actor_id: None,
+    };
+
+    for _ in 0..2 {
+        let set = call("set_my_preferences", json!({ "currency": "USD" }));
+        let response = handler::handle_request(&server, set, None).await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+    }
+    let set = call("set_my_preferences", json!({ "currency": "USD" }));
+    let err = handler::handle_request(&server, set, None)
+        .await
+        .error
+        .expect("expected the quota to run out");
+    let data = err.data.unwrap();
+    assert_eq!(data["code"], "quota_exceeded");
+    assert_eq!(data["category"], "rate_limited");
+    assert_eq!(data["details"]["period"], "daily");
+
+    // Checking usage is free
+    let usage = handler::handle_request(&server, call("get_my_usage", json!({})), None).await;
+    let text = usage.result.unwrap()["content"][0]["text"].clone();
+    let usage: Value = serde_json::from_str(text.as_str().unwrap()).unwrap();
+    assert_eq!(usage["context"], "user:9");
+    assert_eq!(usage["daily"]["used"], 2);
+    assert_eq!(usage["daily"]["remaining"], 0);
+    assert_eq!(usage["monthly"]["limit"], Value::Null);
+
+    let mut invalid = NovaConfig::default();
+    invalid.quotas.daily_calls = 10;
+    invalid.quotas.monthly_calls = 5;
+    let err = invalid.validate().unwrap_err().to_string();
+    assert!(err.contains("quotas.daily_calls"), "{}", err);
+}
+
+#[tokio::test]
+async fn admins_grant_overrides() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig::default();
+    config.server.port = port;
+    config.admin.tokens = vec!["ops-token".into()];
+    config.quotas.daily_calls = 100;
+    let server = NovaServer::in_memory(config.clone()).unwrap();
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let client = reqwest::Client::new();
+    let base = format!("http://127.0.0.1:{}", port);
+    for _ in 0..50 {
+        if client.get(format!("{}/healthz", base)).send().await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let url = format!("{}/admin/quotas/group/-100", base);
+
+    let unauthorized = client.get(&url).send().await.unwrap();
+    assert_eq!(unauthorized.status(), 401);
+
+    let usage: Value = client
+        .put(&url)
+        .header("x-admin-token", "ops-token")
+        .json(&json!({ "daily_calls": 1000 }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(usage["daily"]["limit"], 1000);
+
+    let usage: Value = client
+        .put(&url)
+        .header("x-admin-token", "ops-token")
+        .json(&json!({ "plugin": "group_-100_echo_v1", "monthly_calls": 50 }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        usage["plugins"]["group_-100_echo_v1"]["monthly"]["limit"],
+        50
+    );
+
+    // Clearing the override falls back to `[quotas]`
+    let usage: Value = client
+        .put(&url)
+        .header("x-admin-token", "ops-token")
+        .json(&json!({}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(usage["daily"]["limit"], 100);
+
+    let audit: Value = client
+        .get(format!("{}/admin/audit", base))
+        .header("x-admin-token", "ops-token")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let actions: Vec<&str> = audit["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|entry| entry["action"].as_str())
+        .collect();
+    assert_eq!(actions, ["admin.quotas.update"; 3]);
+
+    let invalid = client
+        .get(format!("{}/admin/quotas/team/1", base))
+        .header("x-admin-token", "ops-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(invalid.status(), 400);
+}
@@ -0,0 +1,116 @@
+use nova_mcp::clock::{ManualClock, SharedClock};
+use nova_mcp::rate_limits::RateLimitStore;
+use nova_mcp::{NovaConfig, NovaServer, PluginManager};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+// The start of a minute
+const NOW: i64 = 1_700_000_040;
+
+#[test]
+fn counters_survive_reopening_the_tree() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let clock = ManualClock::at(NOW);
+    let open = || {
+        RateLimitStore::persistent(db.open_tree("rate_limits").unwrap())
+            .with_clock(SharedClock::new(clock.clone()))
+    };
+    let store = open();
+    assert!(store.is_persistent());
+    assert!(store.try_acquire("ctx:user:1", 2).unwrap());
+    assert!(store.try_acquire("ctx:user:1", 2).unwrap());
+    store.record("ip:10.0.0.9").unwrap();
+    drop(store);
+
+    let reopened = open();
+    assert_eq!(reopened.current("ctx:user:1").unwrap(), 2);
+    assert!(!reopened.try_acquire("ctx:user:1", 2).unwrap());
+    // A refused request is not counted
+    assert_eq!(reopened.current("ctx:user:1").unwrap(), 2);
+    assert!(reopened.try_acquire("ctx:user:2", 2).unwrap());
+    assert_eq!(reopened.active("ctx:"), 2);
+    assert_eq!(reopened.active("ip:"), 1);
+    // Nothing is from an earlier minute yet
+    assert_eq!(reopened.sweep().unwrap(), 0);
+
+    clock.advance(Duration::from_secs(59));
+    assert_eq!(reopened.current("ctx:user:1").unwrap(), 2);
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(reopened.current("ctx:user:1").unwrap(), 0);
+    assert_eq!(reopened.active("ctx:"), 0);
+    assert_eq!(reopened.sweep().unwrap(), 3);
+    assert!(reopened.try_acquire("ctx:user:1", 2).unwrap());
+}
+
+#[test]
+fn memory_store_counts_per_key() {
+    let clock = ManualClock::at(NOW);
+    let store = RateLimitStore::in_memory().with_clock(SharedClock::new(clock.clone()));
+    assert!(!store.is_persistent());
+    assert!(store.try_acquire("ctx:a", 1).unwrap());
+    assert!(!store.try_acquire("ctx:a", 1).unwrap());
+    assert!(store.try_acquire("ctx:b", 1).unwrap());
+    assert_eq!(store.current("ctx:missing").unwrap(), 0);
+    assert_eq!(store.active("ctx:"), 2);
+
+    // A new minute brings a new budget
+    clock.advance(Duration::from_secs(60));
+    assert!(store.try_acquire("ctx:a", 1).unwrap());
+    assert_eq!(store.active("ctx:"), 1);
+    // The minute's first request already swept `ctx:b`
+    assert_eq!(store.sweep().unwrap(), 0);
+}
+
+#[tokio::test]
+async fn a_restarted_server_keeps_the_spent_budget() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let client = reqwest::Client::new();
+    let call = |base: String| {
+        client
+            .post(format!("{}/rpc", base))
+            .header("x-nova-context-type", "user")
+            .header("x-nova-context-id", "42")
+            .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }))
+            .send()
+    };
+
+    let clock = ManualClock::at(NOW);
+    let first = start(&db, &clock).await;
+    for _ in 0..2 {
+        let body: Value = call(first.clone()).await.unwrap().json().await.unwrap();
+        assert!(body["error"].is_null(), "{}", body);
+    }
+
+    // A second instance over the same database stands in for a restart
+    let second = start(&db, &clock).await;
+    let body: Value = call(second).await.unwrap().json().await.unwrap();
+    assert_eq!(body["error"]["message"], "Rate limit exceeded");
+}
+
+async fn start(db: &sled::Db, clock: &ManualClock) -> String {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig::default();
+    config.server.port = port;
+    config.apis.rate_limit_per_minute = 2;
+    let clock = SharedClock::new(clock.clone());
+    let store =
+        RateLimitStore::persistent(db.open_tree("rate_limits").unwrap()).with_clock(clock.clone());
+    let plugin_manager = PluginManager::in_memory().unwrap().with_clock(clock);
+    let server = NovaServer::new(config.clone(), Arc::new(plugin_manager)).with_rate_limits(store);
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let base = format!("http://127.0.0.1:{}", port);
+    let client = reqwest::Client::new();
+    for _ in 0..50 {
+        if client.get(format!("{}/healthz", base)).send().await.is_ok() {
+            return base;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("server did not start");
+}
@@ -0,0 +1,113 @@
+use nova_mcp::plugins::{
+    PluginContextType, PluginEnableRequest, PluginManager, PluginMetadata,
+    PluginRegistrationRequest, RequestContext,
+};
+use nova_mcp::test_util::{StubPlugin, TestServer};
+use nova_mcp::{NovaConfig, NovaError};
+use reqwest::{Method, StatusCode};
+use serde_json::{json, Value};
+
+#[test]
+fn replicas_read_what_the_writer_stored() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let open = || {
+        PluginManager::new(
+            db.open_tree("plugin_metadata").unwrap(),
+            db.open_tree("user_plugins").unwrap(),
+            db.open_tree("group_plugins").unwrap(),
+        )
+        .unwrap()
+    };
+    let owner = context("5");
+    let plugin = open()
+        .register_plugin(&owner, registration("https://weather.example.com/nova"))
+        .unwrap();
+
+    let replica = open().with_read_only(true);
+    assert!(replica.is_read_only());
+    assert_eq!(replica.list_plugins_for_context(&owner).unwrap().len(), 1);
+    assert!(replica
+        .is_enabled(plugin.plugin_id, PluginContextType::User, "5")
+        .unwrap());
+
+    let err = replica
+        .register_plugin(&context("6"), registration("https://a.example.com"))
+        .unwrap_err();
+    assert!(matches!(err, NovaError::ReadOnly));
+    assert_eq!(err.code(), "read_only");
+    let err = replica
+        .set_enablement(PluginEnableRequest {
+            context_type: PluginContextType::User,
+            context_id: "6".to_string(),
+            plugin_id: plugin.plugin_id,
+            enable: true,
+            added_by: None,
+        })
+        .unwrap_err();
+    assert!(matches!(err, NovaError::ReadOnly));
+    assert!(replica.unregister_plugin(&owner, plugin.plugin_id).is_err());
+    assert!(replica.purge_context(&owner).is_err());
+    assert_eq!(replica.list_plugins().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn http_writes_get_403_while_calls_are_served() {
+    let stub = StubPlugin::start().await.unwrap();
+    let mut config = NovaConfig::default();
+    config.server.read_only = true;
+    let server = TestServer::with_config(config).await.unwrap();
+    let client = server.client(context("5"));
+
+    let register = client
+        .request(Method::POST, "/plugins/register")
+        .json(&registration(&stub.url("/invoke")))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(register.status(), StatusCode::FORBIDDEN);
+    let error: Value = register.json().await.unwrap();
+    assert_eq!(error["details"]["code"], "read_only");
+    assert_eq!(error["details"]["category"], "permission_denied");
+
+    for (method, path, body) in [
+        (Method::PUT, "/plugins/1", json!({ "description": "x" })),
+        (Method::DELETE, "/plugins/1", json!({})),
+        (
+            Method::POST,
+            "/plugins/enable",
+            json!({ "context_type": "user", "context_id": "5", "plugin_id": 1, "enable": true }),
+        ),
+    ] {
+        let response = client
+            .request(method, path)
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN, "{}", path);
+    }
+
+    // Reads and MCP calls keep working
+    let plugins: Vec<PluginMetadata> = client.list_plugins().await.unwrap();
+    assert!(plugins.is_empty());
+    assert!(!client.tools_list().await.unwrap().is_empty());
+    client.tools_call("get_my_usage", json!({})).await.unwrap();
+}
+
+fn registration(endpoint_url: &str) -> PluginRegistrationRequest {
+    serde_json::from_value(json!({
+        "name": "weather",
+        "description": "Current weather",
+        "input_schema": { "type": "object" },
+        "endpoint_url": endpoint_url
+    }))
+    .unwrap()
+}
+
+fn context(id: &str) -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: id.to_string(),
+        actor_id: None,
+    }
+}
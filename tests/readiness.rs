@@ -0,0 +1,126 @@
+use axum::{http::StatusCode, routing::get, Json, Router};
+use nova_mcp::config::{NovaConfig, ReadinessConfig};
+use nova_mcp::mcp::{dto::McpRequest, handler};
+use nova_mcp::plugins::PluginManager;
+use nova_mcp::readiness::ComponentState;
+use nova_mcp::NovaServer;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn storage_and_registry_are_checked() {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let server = test_server(&db, NovaConfig::default())
+        .with_readiness_probe(db.open_tree("readiness").unwrap());
+    let report = server.readiness().await;
+    assert!(report.ready, "{:?}", report);
+    assert_eq!(report.status, "ready");
+    assert_eq!(report.components["storage"].status, ComponentState::Ok);
+    assert_eq!(
+        report.components["plugin_registry"].detail.as_deref(),
+        Some("0 plugins loaded")
+    );
+    assert_eq!(
+        report.components["upstream_canary"].status,
+        ComponentState::Skipped
+    );
+
+    // Without a probe tree storage is not checked
+    let report = test_server(&db, NovaConfig::default()).readiness().await;
+    assert_eq!(report.components["storage"].status, ComponentState::Skipped);
+}
+
+#[tokio::test]
+async fn canary_requires_a_recent_upstream_success() {
+    let healthy = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&healthy);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let upstream = format!("http://{}", listener.local_addr().unwrap());
+    let app = Router::new().route(
+        "/networks",
+        get(move || {
+            let healthy = flag.load(Ordering::SeqCst);
+            async move {
+                if healthy {
+                    (StatusCode::OK, Json(json!({ "data": [] })))
+                } else {
+                    (StatusCode::BAD_GATEWAY, Json(json!({ "error": "down" })))
+                }
+            }
+        }),
+    );
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig {
+        readiness: ReadinessConfig {
+            upstream_canary: true,
+            ..ReadinessConfig::default()
+        },
+        ..NovaConfig::default()
+    };
+    config.server.port = port;
+    config.apis.gecko_terminal_url = Some(upstream);
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let server = test_server(&db, config.clone());
+
+    let report = server.readiness().await;
+    assert!(!report.ready);
+    let canary = &report.components["upstream_canary"];
+    assert_eq!(canary.status, ComponentState::Failed);
+    assert!(
+        canary.detail.as_ref().unwrap().contains("502"),
+        "{:?}",
+        canary
+    );
+    // The next check does not probe again right away
+    let report = server.readiness().await;
+    let detail = report.components["upstream_canary"].detail.clone().unwrap();
+    assert!(detail.contains("no successful"), "{}", detail);
+
+    // Any successful GeckoTerminal call counts
+    healthy.store(true, Ordering::SeqCst);
+    let call = McpRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: "tools/call".to_string(),
+        params: Some(json!({ "name": "get_gecko_networks", "arguments": {} })),
+        context_type: Some("user".to_string()),
+        context_id: Some("1".to_string()),
+        actor_id: None,
+    };
+    let response = handler::handle_request(&server, call, None).await;
+    assert!(response.error.is_none(), "{:?}", response.error);
+
+    healthy.store(false, Ordering::SeqCst);
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+    let base = format!("http://127.0.0.1:{}", port);
+    let mut ready = None;
+    for _ in 0..50 {
+        if let Ok(response) = reqwest::get(format!("{}/readyz", base)).await {
+            ready = Some(response);
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let ready = ready.expect("server did not start");
+    assert_eq!(ready.status(), 200);
+    let body: Value = ready.json().await.unwrap();
+    assert_eq!(body["components"]["upstream_canary"]["status"], "ok");
+    assert_eq!(body["upstreams"]["geckoterminal"], "degraded");
+}
+
+fn test_server(db: &sled::Db, config: NovaConfig) -> NovaServer {
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager"),
+    );
+    NovaServer::new(config, plugin_manager)
+}
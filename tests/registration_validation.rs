@@ -0,0 +1,146 @@
+use nova_mcp::plugins::{
+    ErrorResponse, ManifestFormat, PluginContextType, PluginManager, PluginManifest, RequestContext,
+};
+use nova_mcp::test_util::TestServer;
+use reqwest::{Method, StatusCode};
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn registration_reports_every_field_at_once() {
+    let server = TestServer::start().await.unwrap();
+    let client = server.client(context());
+
+    let response = client
+        .request(Method::POST, "/v1/plugins/register")
+        .json(&json!({
+            "name": "  ",
+            "description": "Current weather",
+            "input_schema": { "type": "not-a-type" },
+            "endpoint_url": "not a url",
+            "trust_level": "extreme",
+            "allowed_contexts": ["user", "galaxy"],
+            "tags": ["Weather"]
+        }))
+        .send()
+        .await
+        .unwrap();
+    let fields = invalid_fields(response).await;
+    assert_eq!(
+        fields.as_object().unwrap().keys().collect::<Vec<_>>(),
+        [
+            "allowed_contexts[1]",
+            "endpoint_url",
+            "input_schema",
+            "name",
+            "tags",
+            "trust_level"
+        ]
+    );
+    assert_eq!(fields["name"], "Plugin name cannot be empty");
+    assert_eq!(
+        fields["trust_level"],
+        "unknown value 'extreme'; expected standard or high"
+    );
+    assert!(fields["allowed_contexts[1]"]
+        .as_str()
+        .unwrap()
+        .contains("'galaxy'"));
+    assert!(fields["endpoint_url"]
+        .as_str()
+        .unwrap()
+        .starts_with("Invalid plugin endpoint"));
+
+    // Missing fields are reported alongside problems in the ones present
+    let response = client
+        .request(Method::POST, "/v1/plugins/register")
+        .json(&json!({
+            "name": "weather",
+            "input_schema": { "type": "object" },
+            "version": 0
+        }))
+        .send()
+        .await
+        .unwrap();
+    let fields = invalid_fields(response).await;
+    assert_eq!(fields["description"], "is required");
+    assert_eq!(fields["endpoint_url"], "is required");
+    assert_eq!(
+        fields["version"],
+        "Version must be greater than or equal to 1"
+    );
+
+    // A value of the wrong type stops at that field
+    let response = client
+        .request(Method::POST, "/v1/plugins/register")
+        .json(&json!({
+            "name": "weather",
+            "description": "Current weather",
+            "input_schema": { "type": "object" },
+            "endpoint_url": "https://weather.example.com/nova",
+            "listing": { "categories": "weather" }
+        }))
+        .send()
+        .await
+        .unwrap();
+    let fields = invalid_fields(response).await;
+    assert!(fields["listing.categories"]
+        .as_str()
+        .unwrap()
+        .contains("invalid type"));
+    assert!(server.plugin_manager().list_plugins().unwrap().is_empty());
+}
+
+#[test]
+fn manifests_and_updates_collect_field_problems() {
+    let manager = PluginManager::in_memory().unwrap();
+    let manifest = PluginManifest::parse(
+        r#"
+name = ""
+description = "Tomorrow's weather"
+tags = ["Big Data"]
+
+[endpoint]
+url = "https://weather.example.com/forecast"
+
+[schemas]
+input = { type = "object" }
+
+[listing]
+icon_url = "http://weather.example.com/icon.png"
+"#,
+        ManifestFormat::Toml,
+    )
+    .unwrap();
+    let err = manager
+        .register_plugin(&context(), manifest.into_registration())
+        .unwrap_err();
+    assert_eq!(err.code(), "validation_failed");
+    let details = err.details().unwrap();
+    let fields = details["fields"].as_object().unwrap();
+    assert_eq!(
+        fields.keys().collect::<Vec<_>>(),
+        ["listing.icon_url", "name", "tags"]
+    );
+    assert!(
+        err.to_string().contains("tags: Invalid tag 'Big Data'"),
+        "{}",
+        err
+    );
+}
+
+async fn invalid_fields(response: reqwest::Response) -> Value {
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let error: ErrorResponse = response.json().await.unwrap();
+    let details = error.details.unwrap();
+    assert_eq!(details["code"], "validation_failed");
+    assert_eq!(details["category"], "validation");
+    details["details"]["fields"].clone()
+}
+
+fn context() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
@@ -0,0 +1,227 @@
+use nova_mcp::mcp::{dto::McpRequest, handler};
+use nova_mcp::plugins::{PluginContextType, PluginManager, RequestContext};
+use nova_mcp::preferences::{ContextPreferences, Localizer, Markup, ResultFormat};
+use nova_mcp::tools::gecko_terminal::summary::{summarize, summarize_as};
+use nova_mcp::{NovaConfig, NovaServer};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn pool(name: &str, price: &str, volume: &str, change: &str) -> Value {
+    json!({
+        "id": name,
+        "attributes": {
+            "name": name,
+            "base_token_price_usd": price,
+            "volume_usd": { "h24": volume },
+            "price_change_percentage": { "h24": change },
+            "reserve_in_usd": "1250000",
+            "pool_created_at": "2024-05-01T12:00:00Z"
+        }
+    })
+}
+
+fn localizer() -> Localizer {
+    Localizer::new(&ContextPreferences::default(), &HashMap::new())
+}
+
+#[test]
+fn pool_lists_keep_the_top_five() {
+    let pools: Vec<Value> = (1..=7)
+        .map(|i| pool(&format!("P{} / USDC", i), "8.41", "1000", "-2.5"))
+        .collect();
+    let output = json!({ "pools": { "data": pools } });
+
+    let text = summarize("get_trending_pools", &output, &localizer()).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 6, "{}", text);
+    assert_eq!(lines[0], "Trending pools (top 5 of 7):");
+    assert!(lines[1].starts_with("1. P1 / USDC — "), "{}", lines[1]);
+    assert!(lines[1].contains("vol 24h"), "{}", lines[1]);
+    assert!(lines[1].contains("24h -2.50%"), "{}", lines[1]);
+    assert!(!text.contains("P6"));
+
+    let text = summarize("get_new_pools", &output, &localizer()).unwrap();
+    assert!(text.lines().nth(1).unwrap().contains("created"), "{}", text);
+
+    let empty = json!({ "pools": { "data": [] } });
+    assert_eq!(
+        summarize("search_pools", &empty, &localizer()).unwrap(),
+        "Matching pools: none found"
+    );
+}
+
+#[test]
+fn single_results_and_unknown_tools() {
+    let output = json!({ "pool": { "data": pool("APT / USDC", "8.41", "1000", "3") } });
+    let text = summarize("get_gecko_pool", &output, &localizer()).unwrap();
+    assert!(text.starts_with("APT / USDC\nPrice: "), "{}", text);
+    assert!(text.contains("Liquidity: "), "{}", text);
+    assert!(text.contains("Change 24h: +3.00%"), "{}", text);
+
+    let output = json!({ "token": { "data": { "attributes": {
+        "name": "Aptos", "symbol": "APT", "price_usd": "8.41", "fdv_usd": null
+    } } } });
+    let text = summarize("get_gecko_token", &output, &localizer()).unwrap();
+    assert!(text.starts_with("Aptos (APT)\nPrice: "), "{}", text);
+    assert!(!text.contains("FDV"), "{}", text);
+
+    let networks: Vec<Value> = (0..25)
+        .map(|i| json!({ "id": format!("n{}", i) }))
+        .collect();
+    let output = json!({ "networks": { "data": networks } });
+    let text = summarize("get_gecko_networks", &output, &localizer()).unwrap();
+    assert!(text.starts_with("25 networks: n0, n1"), "{}", text);
+    assert!(text.ends_with("n19 and 5 more"), "{}", text);
+
+    assert!(summarize("user_5_pool", &json!({}), &localizer()).is_none());
+}
+
+#[test]
+fn telegram_markup_escapes_values_and_links_pages() {
+    let mut data = pool("PEPE_2.0 / WETH", "0.5", "1000", "-2.5");
+    data["id"] = json!("eth_0xabc");
+    data["attributes"]["address"] = json!("0xabc");
+    let output = json!({ "pool": { "data": data } });
+
+    let text = summarize_as(
+        "get_gecko_pool",
+        &output,
+        &localizer(),
+        Markup::TelegramMarkdown,
+    )
+    .unwrap();
+    assert!(
+        text.starts_with("*PEPE\\_2\\.0 / WETH*\nPrice: $0\\.50"),
+        "{}",
+        text
+    );
+    assert!(text.contains("Change 24h: \\-2\\.50%"), "{}", text);
+    assert!(text.contains("Address: `0xabc`"), "{}", text);
+    assert!(
+        text.ends_with("[View on GeckoTerminal](https://www.geckoterminal.com/eth/pools/0xabc)"),
+        "{}",
+        text
+    );
+
+    let output = json!({ "token": { "data": {
+        "id": "solana_So11111111111111111111111111111111111111112",
+        "attributes": { "name": "Wrapped <SOL> & co", "symbol": "SOL", "price_usd": "150" }
+    } } });
+    let text = summarize_as(
+        "get_gecko_token",
+        &output,
+        &localizer(),
+        Markup::TelegramHtml,
+    )
+    .unwrap();
+    assert!(
+        text.starts_with("<b>Wrapped &lt;SOL&gt; &amp; co</b> (SOL)\n"),
+        "{}",
+        text
+    );
+    assert!(text.ends_with(
+        "<a href=\"https://www.geckoterminal.com/solana/tokens/So11111111111111111111111111111111111111112\">View on GeckoTerminal</a>"
+    ), "{}", text);
+
+    let pools = json!({ "pools": { "data": [data_with_id("eth_0xdef")] } });
+    let text = summarize_as(
+        "get_trending_pools",
+        &pools,
+        &localizer(),
+        Markup::TelegramMarkdown,
+    )
+    .unwrap();
+    assert!(
+        text.starts_with("Trending pools:\n1\\. *A / B* — "),
+        "{}",
+        text
+    );
+    assert!(
+        text.ends_with("[GeckoTerminal](https://www.geckoterminal.com/eth/pools/0xdef)"),
+        "{}",
+        text
+    );
+
+    // Plain summaries are unchanged
+    let text = summarize("get_trending_pools", &pools, &localizer()).unwrap();
+    assert!(!text.contains("GeckoTerminal"), "{}", text);
+}
+
+#[tokio::test]
+async fn telegram_formats_wrap_other_results_in_code_blocks() {
+    let server = test_server();
+    let call = |format: &str| McpRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: "tools/call".to_string(),
+        params: Some(json!({
+            "name": "set_my_preferences",
+            "arguments": { "currency": "USD" },
+            "format": format
+        })),
+        context_type: Some("user".to_string()),
+        context_id: Some("9".to_string()),
+        actor_id: None,
+    };
+    let text = |resp: nova_mcp::mcp::dto::McpResponse| {
+        let result = resp.result.unwrap();
+        result["content"][0]["text"].as_str().unwrap().to_string()
+    };
+
+    let markdown = text(handler::handle_request(&server, call("telegram_markdown"), None).await);
+    assert!(markdown.starts_with("```json\n{"), "{}", markdown);
+    assert!(markdown.ends_with("}\n```"), "{}", markdown);
+
+    let html = text(handler::handle_request(&server, call("telegram_html"), None).await);
+    assert!(
+        html.starts_with("<pre><code class=\"language-json\">{"),
+        "{}",
+        html
+    );
+    assert!(html.ends_with("}</code></pre>"), "{}", html);
+}
+
+fn data_with_id(id: &str) -> Value {
+    let mut data = pool("A / B", "1", "10", "1");
+    data["id"] = json!(id);
+    data
+}
+
+#[tokio::test]
+async fn result_format_is_a_context_preference() {
+    let server = test_server();
+    let request = McpRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: "tools/call".to_string(),
+        params: Some(json!({
+            "name": "set_my_preferences",
+            "arguments": { "result_format": "summary" }
+        })),
+        context_type: Some("user".to_string()),
+        context_id: Some("9".to_string()),
+        actor_id: None,
+    };
+    let resp = handler::handle_request(&server, request, None).await;
+    assert!(resp.error.is_none(), "{:?}", resp.error);
+
+    let context = RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "9".to_string(),
+        actor_id: None,
+    };
+    let stored = server.preferences().get(&context).unwrap().unwrap();
+    assert_eq!(stored.result_format, ResultFormat::Summary);
+}
+
+fn test_server() -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager"),
+    );
+    NovaServer::new(NovaConfig::default(), plugin_manager)
+}
@@ -0,0 +1,246 @@
+use nova_mcp::plugins::{
+    ErrorResponse, PluginContextType, PluginManager, PluginRegistrationRequest,
+    PluginUpdateRequest, RequestContext,
+};
+use nova_mcp::schema::{breaking_changes, SchemaChangeKind};
+use nova_mcp::test_util::TestServer;
+use reqwest::{Method, StatusCode};
+use serde_json::{json, Value};
+
+#[test]
+fn breaking_changes_are_found_at_any_depth() {
+    let old = json!({
+        "type": "object",
+        "required": ["city"],
+        "properties": {
+            "city": { "type": "string" },
+            "days": { "type": "integer" },
+            "units": { "type": "string" },
+            "filters": {
+                "type": "object",
+                "properties": { "min": { "type": "number" } }
+            },
+            "ids": { "type": "array", "items": { "type": "string" } }
+        }
+    });
+    let new = json!({
+        "type": "object",
+        "required": ["city", "country"],
+        "properties": {
+            "city": { "type": "string" },
+            "country": { "type": "string" },
+            "days": { "type": "number" },
+            "filters": {
+                "type": "object",
+                "properties": { "min": { "type": "string" } }
+            },
+            "ids": { "type": "array", "items": { "type": "integer" } }
+        }
+    });
+    let changes: Vec<(String, SchemaChangeKind)> = breaking_changes(&old, &new)
+        .into_iter()
+        .map(|change| (change.field, change.kind))
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            ("country".to_string(), SchemaChangeKind::RequiredAdded),
+            ("filters.min".to_string(), SchemaChangeKind::TypeChanged),
+            ("ids[]".to_string(), SchemaChangeKind::TypeChanged),
+            ("units".to_string(), SchemaChangeKind::PropertyRemoved),
+        ]
+    );
+
+    // Added optional properties, dropped requirements and wider types are fine
+    let widened = json!({
+        "type": "object",
+        "properties": {
+            "city": { "type": ["string", "null"] },
+            "days": { "type": "integer" },
+            "units": {},
+            "filters": { "type": "object", "properties": { "min": { "type": "number" } } },
+            "ids": { "type": "array", "items": { "type": "string" } },
+            "lang": { "type": "string" }
+        }
+    });
+    assert!(breaking_changes(&old, &widened).is_empty());
+    assert_eq!(
+        breaking_changes(&json!({}), &json!({ "type": "object" }))[0].field,
+        "arguments"
+    );
+}
+
+#[test]
+fn breaking_updates_need_an_acknowledgment() {
+    let manager = PluginManager::in_memory().unwrap();
+    let plugin = manager.register_plugin(&owner(), registration()).unwrap();
+    assert!(plugin.schema_changes.is_empty());
+
+    let stricter = json!({
+        "type": "object",
+        "required": ["city", "country"],
+        "properties": { "city": { "type": "string" }, "country": { "type": "string" } }
+    });
+    let update = PluginUpdateRequest {
+        input_schema: Some(stricter.clone()),
+        ..Default::default()
+    };
+    let err = manager
+        .update_plugin(&owner(), plugin.plugin_id, update.clone())
+        .unwrap_err();
+    assert_eq!(err.code(), "breaking_schema_change");
+    assert_eq!(
+        err.details().unwrap()["changes"][0],
+        json!({ "field": "country", "kind": "required_added", "message": "is now required" })
+    );
+    // Nothing was published
+    assert_eq!(manager.get_plugin(plugin.plugin_id).unwrap().version, 1);
+
+    let updated = manager
+        .update_plugin(
+            &owner(),
+            plugin.plugin_id,
+            PluginUpdateRequest {
+                breaking: true,
+                ..update
+            },
+        )
+        .unwrap();
+    assert_eq!(updated.version, 2);
+    assert_eq!(updated.input_schema, stricter);
+    assert_eq!(updated.schema_changes.len(), 1);
+    assert_eq!(updated.schema_changes[0].field, "country");
+    // The old version stays callable under its own name
+    let previous = manager.get_plugin_by_fq_name(&plugin.fq_name).unwrap();
+    assert_eq!(previous.version, 1);
+
+    // Compatible changes need no flag, and carry no changes forward
+    let looser = PluginUpdateRequest {
+        input_schema: Some(json!({
+            "type": "object",
+            "required": ["city", "country"],
+            "properties": {
+                "city": { "type": "string" },
+                "country": { "type": "string" },
+                "lang": { "type": "string" }
+            }
+        })),
+        ..Default::default()
+    };
+    let updated = manager
+        .update_plugin(&owner(), plugin.plugin_id, looser)
+        .unwrap();
+    assert_eq!(updated.version, 3);
+    assert!(updated.schema_changes.is_empty());
+}
+
+#[tokio::test]
+async fn breaking_updates_over_http_are_conflicts() {
+    let server = TestServer::start().await.unwrap();
+    let client = server.client(owner());
+    let plugin = client.register(&registration()).await.unwrap();
+
+    let path = format!("/v1/plugins/{}", plugin.plugin_id);
+    let schema = json!({ "type": "object", "required": ["country"] });
+    let response = client
+        .request(Method::PATCH, &path)
+        .json(&json!({ "input_schema": schema }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let error: ErrorResponse = response.json().await.unwrap();
+    assert!(error.error.contains("breaking=true"), "{}", error.error);
+    let details = error.details.unwrap();
+    assert_eq!(details["code"], "breaking_schema_change");
+    assert_eq!(details["category"], "conflict");
+    // `city` was dropped and `country` became required
+    assert_eq!(details["details"]["changes"][0]["kind"], "property_removed");
+    assert_eq!(details["details"]["changes"][1]["field"], "country");
+
+    let response = client
+        .request(Method::PATCH, &path)
+        .json(&json!({ "input_schema": schema, "breaking": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let updated: Value = response.json().await.unwrap();
+    assert_eq!(updated["version"], 2);
+    assert_eq!(updated["schema_changes"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn rejected_updates_leave_the_plugin_unchanged() {
+    let server = TestServer::start().await.unwrap();
+    let client = server.client(owner());
+    let plugin = client.register(&registration()).await.unwrap();
+    let path = format!("/v1/plugins/{}", plugin.plugin_id);
+    let before: Value = client
+        .request(Method::GET, &path)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let rejected = [
+        (
+            json!({ "input_schema": { "type": "object", "required": ["country"] } }),
+            StatusCode::CONFLICT,
+        ),
+        (
+            json!({ "endpoint_url": "ftp://weather.example.com/nova" }),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            json!({ "script": { "source": "args" } }),
+            StatusCode::BAD_REQUEST,
+        ),
+    ];
+    for (mut update, status) in rejected {
+        update["description"] = json!("rewritten");
+        update["owner_id"] = json!("mallory");
+        let response = client
+            .request(Method::PATCH, &path)
+            .json(&update)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), status, "{}", update);
+    }
+
+    let after: Value = client
+        .request(Method::GET, &path)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(after, before);
+    assert_eq!(after["description"], "Current weather");
+}
+
+fn registration() -> PluginRegistrationRequest {
+    serde_json::from_value(json!({
+        "name": "weather",
+        "description": "Current weather",
+        "input_schema": {
+            "type": "object",
+            "required": ["city"],
+            "properties": { "city": { "type": "string" } }
+        },
+        "endpoint_url": "https://weather.example.com/nova"
+    }))
+    .unwrap()
+}
+
+fn owner() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
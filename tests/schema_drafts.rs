@@ -0,0 +1,169 @@
+use nova_mcp::plugins::{
+    ErrorResponse, PluginContextType, PluginRegistrationRequest, RequestContext,
+};
+use nova_mcp::schema::{validate_arguments, CompiledSchemas, SchemaDraft};
+use nova_mcp::test_util::TestServer;
+use reqwest::{Method, StatusCode};
+use serde_json::{json, Value};
+
+#[test]
+fn detects_the_declared_draft() {
+    for (declared, draft) in [
+        (
+            "http://json-schema.org/draft-04/schema#",
+            SchemaDraft::Draft4,
+        ),
+        (
+            "http://json-schema.org/draft-06/schema",
+            SchemaDraft::Draft6,
+        ),
+        (
+            "https://json-schema.org/draft-07/schema#",
+            SchemaDraft::Draft7,
+        ),
+        (
+            "https://json-schema.org/draft/2019-09/schema",
+            SchemaDraft::Draft201909,
+        ),
+        (
+            "https://json-schema.org/draft/2020-12/schema",
+            SchemaDraft::Draft202012,
+        ),
+    ] {
+        let schema = json!({ "$schema": declared, "type": "object" });
+        assert_eq!(SchemaDraft::detect(&schema).unwrap(), draft, "{}", declared);
+    }
+    assert_eq!(
+        SchemaDraft::detect(&json!({ "type": "object" })).unwrap(),
+        SchemaDraft::Draft7
+    );
+
+    let err = SchemaDraft::detect(&json!({ "$schema": "https://example.com/mine" })).unwrap_err();
+    assert!(err.contains("2020-12"), "{}", err);
+    assert!(SchemaDraft::detect(&json!({ "$schema": 7 })).is_err());
+}
+
+#[test]
+fn validates_with_the_declared_draft() {
+    // A boolean `exclusiveMaximum` is draft-04 only
+    let bounded = |declared: Option<&str>| {
+        let mut schema = json!({
+            "type": "object",
+            "properties": {
+                "days": { "type": "integer", "maximum": 10, "exclusiveMaximum": true }
+            }
+        });
+        if let Some(declared) = declared {
+            schema["$schema"] = json!(declared);
+        }
+        schema
+    };
+    let draft4 = bounded(Some("http://json-schema.org/draft-04/schema#"));
+    validate_arguments("forecast", &draft4, &json!({ "days": 9 })).unwrap();
+    let err = validate_arguments("forecast", &draft4, &json!({ "days": 10 })).unwrap_err();
+    assert_eq!(err.code(), "invalid_arguments");
+    let err = validate_arguments("forecast", &bounded(None), &json!({ "days": 9 })).unwrap_err();
+    assert!(err.to_string().contains("JSON Schema draft-07"), "{}", err);
+
+    // Compile errors say which draft the schema was read as
+    let broken = json!({
+        "$schema": "https://json-schema.org/draft/2019-09/schema",
+        "type": "object",
+        "minProperties": "two"
+    });
+    let err = validate_arguments("geo", &broken, &json!({})).unwrap_err();
+    assert!(err.to_string().contains("JSON Schema 2019-09"), "{}", err);
+}
+
+#[test]
+fn compiled_schemas_follow_a_changed_schema() {
+    let schemas = CompiledSchemas::default();
+    let strict = json!({
+        "type": "object",
+        "properties": { "days": { "type": "integer", "maximum": 5 } }
+    });
+    schemas.prepare("forecast", &strict).unwrap();
+    schemas
+        .validate("forecast", &strict, &json!({ "days": 5 }))
+        .unwrap();
+    let err = schemas
+        .validate("forecast", &strict, &json!({ "days": 9 }))
+        .unwrap_err();
+    assert_eq!(err.code(), "invalid_arguments");
+
+    // A new schema under the same name is compiled again, not served stale
+    let loose = json!({ "type": "object" });
+    schemas
+        .validate("forecast", &loose, &json!({ "days": 9 }))
+        .unwrap();
+
+    let broken = json!({ "type": "object", "minProperties": "two" });
+    assert!(schemas.prepare("geo", &broken).is_err());
+    assert!(schemas.validate("geo", &broken, &json!({})).is_err());
+}
+
+#[tokio::test]
+async fn metadata_carries_the_detected_draft() {
+    let server = TestServer::start().await.unwrap();
+    let client = server.client(owner());
+
+    let modern = client
+        .register(&registration(
+            "modern",
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "type": "object",
+                "$defs": { "city": { "type": "string" } },
+                "properties": { "city": { "$ref": "#/$defs/city" } }
+            }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(modern.schema_draft, SchemaDraft::Draft202012);
+    let listed = client.get_plugin(modern.plugin_id).await.unwrap();
+    assert_eq!(
+        serde_json::to_value(&listed.metadata).unwrap()["schema_draft"],
+        "2020-12"
+    );
+
+    let plain = client
+        .register(&registration("plain", json!({ "type": "object" })))
+        .await
+        .unwrap();
+    assert_eq!(plain.schema_draft, SchemaDraft::Draft7);
+
+    let response = client
+        .request(Method::POST, "/v1/plugins/register")
+        .json(&json!({
+            "name": "odd",
+            "description": "Unknown draft",
+            "input_schema": { "$schema": "https://example.com/schema", "type": "object" },
+            "endpoint_url": "https://odd.example.com/nova"
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let error: ErrorResponse = response.json().await.unwrap();
+    let fields = &error.details.unwrap()["details"]["fields"];
+    let message = fields["input_schema"].as_str().unwrap();
+    assert!(message.contains("unsupported $schema"), "{}", message);
+}
+
+fn registration(name: &str, input_schema: Value) -> PluginRegistrationRequest {
+    serde_json::from_value(json!({
+        "name": name,
+        "description": "Draft test",
+        "input_schema": input_schema,
+        "endpoint_url": format!("https://{}.example.com/nova", name)
+    }))
+    .unwrap()
+}
+
+fn owner() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
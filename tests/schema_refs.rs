@@ -0,0 +1,163 @@
+use axum::{routing::get, Json, Router};
+use nova_mcp::plugins::{
+    ErrorResponse, PluginContextType, PluginManager, PluginRegistrationRequest, RequestContext,
+};
+use nova_mcp::schema::{check_refs, validate_arguments};
+use nova_mcp::test_util::TestServer;
+use nova_mcp::NovaConfig;
+use reqwest::{Method, StatusCode};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[tokio::test]
+async fn remote_refs_are_bundled_at_registration() {
+    let (addr, fetches) = schema_host().await;
+    let mut config = NovaConfig::default();
+    config.plugins.schema_ref_hosts = vec!["127.0.0.1".to_string()];
+    let server = TestServer::with_config(config).await.unwrap();
+    let client = server.client(owner());
+
+    let schema = json!({
+        "type": "object",
+        "required": ["location"],
+        "properties": {
+            "location": { "$ref": format!("http://{}/geo.json#/definitions/point", addr) }
+        }
+    });
+    let plugin = client
+        .register(&registration("weather", schema.clone()))
+        .await
+        .unwrap();
+    // Both documents were fetched, `units.json` through geo.json's relative ref
+    assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    let stored = &plugin.input_schema;
+    check_refs(stored).unwrap();
+    let definitions = stored["definitions"].as_object().unwrap();
+    assert_eq!(definitions.len(), 2);
+    let geo = format!("http_127.0.0.1_{}_geo.json", addr.port());
+    assert_eq!(
+        stored["properties"]["location"]["$ref"],
+        format!("#/definitions/{}/definitions/point", geo)
+    );
+    assert!(definitions[&geo].get("$id").is_none());
+
+    // Validation follows the inlined documents without the network
+    let valid = json!({ "location": { "lat": 41.1, "lon": -8.6, "units": "metric" } });
+    validate_arguments(&plugin.fq_name, stored, &valid).unwrap();
+    let invalid = json!({ "location": { "lat": "north", "lon": -8.6, "units": "kelvin" } });
+    let err = validate_arguments(&plugin.fq_name, stored, &invalid).unwrap_err();
+    assert_eq!(err.code(), "invalid_arguments");
+
+    // Fetched documents are cached
+    client
+        .register(&registration("forecast", schema))
+        .await
+        .unwrap();
+    assert_eq!(fetches.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn remote_refs_need_an_allowlisted_host() {
+    let (addr, fetches) = schema_host().await;
+    let server = TestServer::start().await.unwrap();
+    let client = server.client(owner());
+
+    for (schema, message) in [
+        (
+            json!({ "$ref": format!("http://{}/geo.json", addr) }),
+            "plugins.schema_ref_hosts",
+        ),
+        (
+            json!({ "$ref": "geo.json#/definitions/point" }),
+            "absolute $id",
+        ),
+        (
+            json!({ "properties": { "a": { "$ref": "#/definitions/missing" } } }),
+            "points at nothing",
+        ),
+    ] {
+        let response = client
+            .request(Method::POST, "/v1/plugins/register")
+            .json(&json!({
+                "name": "weather",
+                "description": "Current weather",
+                "input_schema": schema,
+                "endpoint_url": "https://weather.example.com/nova"
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let error: ErrorResponse = response.json().await.unwrap();
+        let field = &error.details.unwrap()["details"]["fields"]["input_schema"];
+        assert!(field.as_str().unwrap().contains(message), "{}", field);
+    }
+    assert_eq!(fetches.load(Ordering::SeqCst), 0);
+
+    // Without bundling, a remote ref is refused rather than fetched later
+    let manager = PluginManager::in_memory().unwrap();
+    let schema = json!({ "$ref": format!("http://{}/geo.json", addr) });
+    let err = manager
+        .register_plugin(&owner(), registration("weather", schema))
+        .unwrap_err();
+    assert!(err.to_string().contains("not bundled"), "{}", err);
+    assert_eq!(fetches.load(Ordering::SeqCst), 0);
+}
+
+/// Serves `/geo.json`, which refers to `/units.json` by a relative ref.
+async fn schema_host() -> (SocketAddr, Arc<AtomicUsize>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let geo = json!({
+        "$id": format!("http://{}/geo.json", addr),
+        "definitions": {
+            "coordinate": { "type": "number" },
+            "point": {
+                "type": "object",
+                "required": ["lat", "lon"],
+                "properties": {
+                    "lat": { "$ref": "#/definitions/coordinate" },
+                    "lon": { "$ref": "#/definitions/coordinate" },
+                    "units": { "$ref": "units.json" }
+                }
+            }
+        }
+    });
+    let units = json!({ "enum": ["metric", "imperial"] });
+    let app = Router::new()
+        .route("/geo.json", get(serve(geo, fetches.clone())))
+        .route("/units.json", get(serve(units, fetches.clone())));
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (addr, fetches)
+}
+
+fn serve(
+    document: Value,
+    fetches: Arc<AtomicUsize>,
+) -> impl Fn() -> std::future::Ready<Json<Value>> + Clone {
+    move || {
+        fetches.fetch_add(1, Ordering::SeqCst);
+        std::future::ready(Json(document.clone()))
+    }
+}
+
+fn registration(name: &str, input_schema: Value) -> PluginRegistrationRequest {
+    serde_json::from_value(json!({
+        "name": name,
+        "description": "Current weather",
+        "input_schema": input_schema,
+        "endpoint_url": "https://weather.example.com/nova"
+    }))
+    .unwrap()
+}
+
+fn owner() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
@@ -0,0 +1,193 @@
+use axum::{routing::get, Json, Router};
+use nova_mcp::config::PluginsConfig;
+use nova_mcp::plugins::{
+    EgressPolicy, PluginContextType, PluginManager, PluginRegistrationRequest, PluginScript,
+    PluginUpdateRequest, RequestContext, ScriptLanguage, ScriptLimits,
+};
+use serde_json::{json, Value};
+
+const SUMMARY: &str = r#"
+let total = 0.0;
+for amount in args.amounts { total += amount; }
+#{ total: total, count: args.amounts.len(), caller: context.context_id }
+"#;
+
+#[tokio::test]
+async fn scripts_run_in_place_of_an_endpoint_and_version_like_plugins() {
+    let manager = PluginManager::in_memory().unwrap();
+    let metadata = manager
+        .register_plugin(&owner(), registration(SUMMARY))
+        .unwrap();
+    assert_eq!(metadata.fq_name, "user_5_summarize_v1");
+    assert_eq!(metadata.endpoint_url, "");
+
+    let result = manager
+        .invoke_plugin(&metadata, &owner(), json!({ "amounts": [1.5, 2.5] }))
+        .await
+        .unwrap();
+    assert_eq!(result, json!({ "total": 4.0, "count": 2, "caller": "5" }));
+
+    // Arguments are validated against the input schema first
+    assert!(manager
+        .invoke_plugin(&metadata, &owner(), json!({}))
+        .await
+        .is_err());
+
+    let update = PluginUpdateRequest {
+        script: Some(script("#{ count: args.amounts.len() }")),
+        ..PluginUpdateRequest::default()
+    };
+    let updated = manager
+        .update_plugin(&owner(), metadata.plugin_id, update)
+        .unwrap();
+    assert_eq!(updated.version, 2);
+    let result = manager
+        .invoke_plugin(&updated, &owner(), json!({ "amounts": [1.0] }))
+        .await
+        .unwrap();
+    assert_eq!(result, json!({ "count": 1 }));
+
+    // Scripted plugins keep to scripts
+    let endpoint = PluginUpdateRequest {
+        endpoint_url: Some("https://example.com/nova".to_string()),
+        ..PluginUpdateRequest::default()
+    };
+    assert!(manager
+        .update_plugin(&owner(), metadata.plugin_id, endpoint)
+        .is_err());
+}
+
+#[tokio::test]
+async fn scripts_are_checked_at_registration_and_bounded_at_runtime() {
+    let manager = PluginManager::in_memory()
+        .unwrap()
+        .with_script_limits(ScriptLimits {
+            max_operations: 10_000,
+            ..ScriptLimits::default()
+        });
+
+    let err = manager
+        .register_plugin(&owner(), registration("let x = ;"))
+        .unwrap_err();
+    assert!(err.to_string().contains("script"), "{}", err);
+
+    let mut both = registration("1");
+    both.endpoint_url = "https://example.com/nova".to_string();
+    assert!(manager.register_plugin(&owner(), both).is_err());
+
+    let mut evaluator = registration(r#"eval("1 + 1")"#);
+    evaluator.name = "evaluator".to_string();
+    assert!(manager.register_plugin(&owner(), evaluator).is_err());
+
+    // Out of operations, and no modules to import
+    for (name, source) in [("spin", "loop { }"), ("reader", r#"import "fs" as fs; 1"#)] {
+        let mut request = registration(source);
+        request.name = name.to_string();
+        let metadata = manager.register_plugin(&owner(), request).unwrap();
+        let err = manager
+            .invoke_plugin(&metadata, &owner(), json!({ "amounts": [] }))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Plugin script failed"), "{}", err);
+    }
+
+    let mut thrower = registration(r#"throw "no amounts""#);
+    thrower.name = "thrower".to_string();
+    let metadata = manager.register_plugin(&owner(), thrower).unwrap();
+    let err = manager
+        .invoke_plugin(&metadata, &owner(), json!({ "amounts": [] }))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("no amounts"), "{}", err);
+}
+
+#[tokio::test]
+async fn script_http_calls_follow_the_egress_policy() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let app = Router::new().route("/price", get(|| async { Json(json!({ "usd": 2.5 })) }));
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let source = format!(
+        r#"
+        let reply = http_get("http://127.0.0.1:{}/price");
+        #{{ status: reply.status, value: reply.body.usd * args.amounts[0] }}
+        "#,
+        port
+    );
+
+    // Default policy: https only, no private networks
+    let strict = PluginManager::in_memory().unwrap();
+    let metadata = strict
+        .register_plugin(&owner(), registration(&source))
+        .unwrap();
+    assert!(strict
+        .invoke_plugin(&metadata, &owner(), json!({ "amounts": [2.0] }))
+        .await
+        .is_err());
+
+    let relaxed = PluginManager::in_memory()
+        .unwrap()
+        .with_egress_policy(EgressPolicy::new(&PluginsConfig {
+            allowed_schemes: vec!["http".into()],
+            allow_private_networks: true,
+            ..PluginsConfig::default()
+        }));
+    let metadata = relaxed
+        .register_plugin(&owner(), registration(&source))
+        .unwrap();
+    let result: Value = relaxed
+        .invoke_plugin(&metadata, &owner(), json!({ "amounts": [2.0] }))
+        .await
+        .unwrap();
+    assert_eq!(result, json!({ "status": 200, "value": 5.0 }));
+
+    let limited = PluginManager::in_memory()
+        .unwrap()
+        .with_egress_policy(EgressPolicy::new(&PluginsConfig {
+            allowed_schemes: vec!["http".into()],
+            allow_private_networks: true,
+            ..PluginsConfig::default()
+        }))
+        .with_script_limits(ScriptLimits {
+            max_http_calls: 0,
+            ..ScriptLimits::default()
+        });
+    let metadata = limited
+        .register_plugin(&owner(), registration(&source))
+        .unwrap();
+    let err = limited
+        .invoke_plugin(&metadata, &owner(), json!({ "amounts": [2.0] }))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("HTTP calls"), "{}", err);
+}
+
+fn script(source: &str) -> PluginScript {
+    PluginScript {
+        language: ScriptLanguage::Rhai,
+        source: source.to_string(),
+    }
+}
+
+fn registration(source: &str) -> PluginRegistrationRequest {
+    serde_json::from_value(json!({
+        "name": "summarize",
+        "description": "Sums amounts",
+        "input_schema": {
+            "type": "object",
+            "properties": { "amounts": { "type": "array", "items": { "type": "number" } } },
+            "required": ["amounts"]
+        },
+        "script": { "source": source }
+    }))
+    .unwrap()
+}
+
+fn owner() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
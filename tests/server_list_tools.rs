@@ -8,9 +8,10 @@ fn list_tools_contains_expected() {
     let context = RequestContext {
         context_type: PluginContextType::User,
         context_id: "0".to_string(),
+        actor_id: None,
     };
     let tools = server.get_tools(&context).unwrap();
-    assert_eq!(tools.len(), 6);
+    assert_eq!(tools.len(), 15);
     let names: Vec<_> = tools.iter().map(|t| t.name.as_str()).collect();
     assert!(names.contains(&"get_gecko_networks"));
     assert!(names.contains(&"get_gecko_token"));
@@ -18,6 +19,15 @@ fn list_tools_contains_expected() {
     assert!(names.contains(&"get_trending_pools"));
     assert!(names.contains(&"search_pools"));
     assert!(names.contains(&"get_new_pools"));
+    assert!(names.contains(&"find_token_across_networks"));
+    assert!(names.contains(&"find_arbitrage_spreads"));
+    assert!(names.contains(&"get_solana_token_holders"));
+    assert!(names.contains(&"watch_whales"));
+    assert!(names.contains(&"unwatch_whales"));
+    assert!(names.contains(&"get_token_unlocks"));
+    assert!(names.contains(&"set_my_preferences"));
+    assert!(names.contains(&"get_my_usage"));
+    assert!(names.contains(&"get_job_status"));
 }
 
 fn test_server() -> NovaServer {
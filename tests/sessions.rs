@@ -0,0 +1,224 @@
+use nova_mcp::mcp::dto::McpRequest;
+use nova_mcp::mcp::handler::handle_session_request;
+use nova_mcp::mcp::session::{McpSession, SessionStore};
+use nova_mcp::{NovaConfig, NovaServer, PluginManager};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn store_binds_sessions_to_their_credential() {
+    let store = SessionStore::new(Duration::from_secs(60));
+    let session = McpSession::new();
+    let id = session.id().to_string();
+    store.insert(session, Some("key-a"));
+
+    assert!(store.get(&id, Some("key-a")).is_some());
+    assert!(store.get(&id, Some("key-b")).is_none());
+    assert!(!store.remove(&id, Some("key-b")));
+    assert!(store.remove(&id, Some("key-a")));
+    assert!(store.get(&id, Some("key-a")).is_none());
+}
+
+#[test]
+fn idle_sessions_expire() {
+    let store = SessionStore::new(Duration::ZERO);
+    let session = McpSession::new();
+    let id = session.id().to_string();
+    store.insert(session, None);
+    std::thread::sleep(Duration::from_millis(5));
+    assert!(store.get(&id, None).is_none());
+}
+
+#[tokio::test]
+async fn context_from_initialize_is_reused() {
+    let server = test_server(NovaConfig::default());
+    let mut session = McpSession::new();
+    let init = McpRequest {
+        jsonrpc: "2.0".into(),
+        id: Some(json!(1)),
+        method: "initialize".into(),
+        params: Some(json!({ "capabilities": { "roots": {} } })),
+        context_type: Some("group".into()),
+        context_id: Some("-100".into()),
+        actor_id: None,
+    };
+    handle_session_request(&server, &mut session, init, None).await;
+    assert_eq!(session.context().unwrap().context_id, "-100");
+    assert!(session
+        .client_capabilities()
+        .unwrap()
+        .get("roots")
+        .is_some());
+
+    let list = McpRequest {
+        jsonrpc: "2.0".into(),
+        id: Some(json!(2)),
+        method: "tools/list".into(),
+        params: None,
+        context_type: None,
+        context_id: None,
+        actor_id: None,
+    };
+    let resp = handle_session_request(&server, &mut session, list, None).await;
+    assert!(resp.error.is_none());
+}
+
+#[tokio::test]
+async fn http_session_header_replaces_context_headers() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig::default();
+    config.server.port = port;
+    let server = test_server(config.clone());
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let client = reqwest::Client::new();
+    let url = format!("http://127.0.0.1:{}/rpc", port);
+    let init = json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} });
+    let mut response = None;
+    for _ in 0..50 {
+        match client
+            .post(&url)
+            .header("x-nova-context-type", "user")
+            .header("x-nova-context-id", "42")
+            .json(&init)
+            .send()
+            .await
+        {
+            Ok(resp) => {
+                response = Some(resp);
+                break;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(50)).await,
+        }
+    }
+    let response = response.expect("server did not start");
+    let session_id = response.headers()["mcp-session-id"]
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    let list = json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" });
+    let body: Value = client
+        .post(&url)
+        .header("mcp-session-id", &session_id)
+        .json(&list)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(body["result"]["tools"].is_array());
+
+    let ended = client
+        .delete(&url)
+        .header("mcp-session-id", &session_id)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(ended.status(), 204);
+    let gone = client
+        .post(&url)
+        .header("mcp-session-id", &session_id)
+        .json(&list)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(gone.status(), 404);
+}
+
+fn test_server(config: NovaConfig) -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager"),
+    );
+    NovaServer::new(config, plugin_manager)
+}
+
+#[test]
+fn expired_sessions_are_reported_to_the_eviction_hook() {
+    let evicted = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = Arc::clone(&evicted);
+    let store = SessionStore::new(Duration::from_millis(20))
+        .with_eviction_hook(move |id| seen.lock().unwrap().push(id.to_string()));
+    let stale = McpSession::new();
+    let stale_id = stale.id().to_string();
+    let idle = McpSession::new();
+    let idle_id = idle.id().to_string();
+    store.insert(stale, None);
+    store.insert(idle, None);
+    std::thread::sleep(Duration::from_millis(30));
+
+    // Looking up an expired session evicts it
+    assert!(store.get(&idle_id, None).is_none());
+    // Inserting prunes whatever else has idled out
+    let fresh = McpSession::new();
+    let fresh_id = fresh.id().to_string();
+    store.insert(fresh, None);
+    assert_eq!(*evicted.lock().unwrap(), vec![idle_id, stale_id]);
+
+    // Explicit removal is the caller's own business
+    assert!(store.remove(&fresh_id, None));
+    assert_eq!(evicted.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn concurrent_requests_keep_each_others_changes() {
+    let server = test_server(NovaConfig::default());
+    let store = SessionStore::new(Duration::from_secs(60));
+    let session = McpSession::new();
+    let id = session.id().to_string();
+    store.insert(session, Some("key-a"));
+
+    // Two requests read the session before either writes it back
+    let mut first = store.get(&id, Some("key-a")).unwrap();
+    let mut second = store.get(&id, Some("key-a")).unwrap();
+    let init = McpRequest {
+        jsonrpc: "2.0".into(),
+        id: Some(json!(1)),
+        method: "initialize".into(),
+        params: Some(json!({})),
+        context_type: Some("user".into()),
+        context_id: Some("7".into()),
+        actor_id: None,
+    };
+    handle_session_request(&server, &mut first, init, None).await;
+    let set_level = McpRequest {
+        jsonrpc: "2.0".into(),
+        id: Some(json!(2)),
+        method: "logging/setLevel".into(),
+        params: Some(json!({ "level": "debug" })),
+        context_type: Some("user".into()),
+        context_id: Some("7".into()),
+        actor_id: None,
+    };
+    handle_session_request(&server, &mut second, set_level, None).await;
+    store.update(first);
+    store.update(second);
+
+    let stored = store.get(&id, Some("key-a")).unwrap();
+    assert!(stored.is_initialized());
+    assert_eq!(stored.context().unwrap().context_id, "7");
+    assert!(stored.log_level().is_some());
+}
+
+#[test]
+fn store_refuses_sessions_past_its_cap() {
+    let store = SessionStore::new(Duration::from_secs(60)).with_max_sessions(1);
+    let first = McpSession::new();
+    let first_id = first.id().to_string();
+    assert!(store.insert(first, Some("key-a")));
+    assert!(!store.insert(McpSession::new(), Some("key-a")));
+    assert_eq!(store.len(), 1);
+
+    assert!(store.remove(&first_id, Some("key-a")));
+    assert!(store.insert(McpSession::new(), Some("key-a")));
+}
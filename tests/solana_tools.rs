@@ -0,0 +1,178 @@
+// The Solana tools against a mocked JSON-RPC endpoint, and how the
+// GeckoTerminal tools treat base58 addresses.
+use nova_mcp::tools::rate_limit::UpstreamRateLimiter;
+use nova_mcp::tools::{
+    GetSolanaTokenHoldersInput, SearchPoolsInput, SearchPoolsTools, SolanaTools,
+};
+use nova_mcp::NovaError;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use wiremock::matchers::{body_partial_json, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+const WHALE: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+const ACCOUNT_A: &str = "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1";
+const ACCOUNT_B: &str = "HN7cABqLq46Es1jh92dQQisAq662SmxELLLsHHe4YWrH";
+
+fn limiter() -> Arc<UpstreamRateLimiter> {
+    Arc::new(UpstreamRateLimiter::new("solana_rpc", 600, Duration::ZERO))
+}
+
+fn solana(upstream: &MockServer) -> SolanaTools {
+    SolanaTools::with_rate_limiter(limiter()).with_rpc_url(upstream.uri())
+}
+
+/// Answers JSON-RPC `method` with `result`, or with `error` when it is one.
+async fn rpc(upstream: &MockServer, rpc_method: &str, reply: Value) {
+    let body = if reply.get("code").is_some() {
+        json!({ "jsonrpc": "2.0", "id": 1, "error": reply })
+    } else {
+        json!({ "jsonrpc": "2.0", "id": 1, "result": reply })
+    };
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "method": rpc_method })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(body))
+        .mount(upstream)
+        .await;
+}
+
+fn amount(raw: &str, ui: &str) -> Value {
+    json!({ "amount": raw, "decimals": 5, "uiAmountString": ui })
+}
+
+#[tokio::test]
+async fn token_holders_come_with_owners_and_shares() {
+    let upstream = MockServer::start().await;
+    rpc(
+        &upstream,
+        "getTokenSupply",
+        json!({ "value": amount("100000000000", "1000000") }),
+    )
+    .await;
+    let mut first = amount("50000000000", "500000");
+    first["address"] = json!(ACCOUNT_A);
+    let mut second = amount("12345000000", "123450");
+    second["address"] = json!(ACCOUNT_B);
+    rpc(
+        &upstream,
+        "getTokenLargestAccounts",
+        json!({ "value": [first, second] }),
+    )
+    .await;
+    rpc(
+        &upstream,
+        "getMultipleAccounts",
+        json!({ "value": [
+            { "data": { "parsed": { "info": { "owner": WHALE } } } },
+            null
+        ] }),
+    )
+    .await;
+
+    let output = solana(&upstream)
+        .get_token_holders(GetSolanaTokenHoldersInput {
+            address: format!(" {} ", BONK),
+            limit: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(output.mint, BONK);
+    assert_eq!(output.decimals, 5);
+    assert_eq!(output.supply, "1000000");
+    assert_eq!(output.top_holders_percent, 62.345);
+
+    let whale = &output.holders[0];
+    assert_eq!(whale.rank, 1);
+    assert_eq!(whale.owner.as_deref(), Some(WHALE));
+    assert_eq!(whale.token_account, ACCOUNT_A);
+    assert_eq!(whale.amount, "500000");
+    assert_eq!(whale.percent_of_supply, 50.0);
+    assert_eq!(
+        whale.explorer.as_deref(),
+        Some(format!("https://solscan.io/account/{}", WHALE).as_str())
+    );
+    // Unreadable account: no owner, linked by its token account
+    let second = &output.holders[1];
+    assert_eq!(second.owner, None);
+    assert_eq!(second.percent_of_supply, 12.345);
+    assert_eq!(
+        second.explorer.as_deref(),
+        Some(format!("https://solscan.io/account/{}", ACCOUNT_B).as_str())
+    );
+}
+
+#[tokio::test]
+async fn token_holders_reject_bad_mints() {
+    let upstream = MockServer::start().await;
+    rpc(
+        &upstream,
+        "getTokenSupply",
+        json!({ "code": -32602, "message": "Invalid param: not a Token mint" }),
+    )
+    .await;
+    rpc(&upstream, "getTokenLargestAccounts", json!({ "value": [] })).await;
+    let tools = solana(&upstream);
+
+    let err = tools
+        .get_token_holders(GetSolanaTokenHoldersInput {
+            address: WHALE.into(),
+            limit: Some(5),
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, NovaError::TokenNotFound { .. }), "{:?}", err);
+    let calls = upstream.received_requests().await.unwrap().len();
+
+    // Checked locally, before any RPC call
+    for (address, limit) in [
+        ("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", None),
+        ("DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjn", None),
+        (BONK, Some(21)),
+    ] {
+        assert!(tools
+            .get_token_holders(GetSolanaTokenHoldersInput {
+                address: address.into(),
+                limit,
+            })
+            .await
+            .is_err());
+    }
+    assert_eq!(upstream.received_requests().await.unwrap().len(), calls);
+}
+
+#[tokio::test]
+async fn searching_solana_by_address_checks_base58() {
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/search/pools"))
+        .and(query_param("query", BONK))
+        .and(query_param("network", "solana"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "data": [] })))
+        .expect(1)
+        .mount(&upstream)
+        .await;
+    let tools = SearchPoolsTools::with_rate_limiter(limiter()).with_base_url(upstream.uri());
+    let search = |query: &str| SearchPoolsInput {
+        query: query.into(),
+        network: Some("solana".into()),
+        page: None,
+    };
+
+    tools
+        .search_pools(search(&format!("{}\n", BONK)))
+        .await
+        .unwrap();
+    // Base58, but too short for a 32-byte key
+    let err = tools
+        .search_pools(search("DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjn"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, NovaError::InvalidAddress { .. }), "{:?}", err);
+    // Names are searched as given
+    assert!(tools
+        .search_pools(search("bonk"))
+        .await
+        .is_err_and(|err| { !matches!(err, NovaError::InvalidAddress { .. }) }));
+}
@@ -0,0 +1,148 @@
+use nova_mcp::stdio::{serve, FrameReader, Framing, Incoming};
+use nova_mcp::{NovaConfig, NovaServer, PluginManager};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+const PING: &str = r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#;
+
+fn frame(body: &str) -> String {
+    format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+}
+
+#[tokio::test]
+async fn auto_detects_newline_framing() {
+    let input = format!("\n{}\n{}\n", PING, PING);
+    let mut reader = FrameReader::new(input.as_bytes(), Framing::Auto);
+    assert_eq!(
+        reader.next().await.unwrap(),
+        Some(Incoming::Message(PING.to_string()))
+    );
+    assert_eq!(reader.framing(), Framing::Newline);
+    assert!(reader.next().await.unwrap().is_some());
+    assert_eq!(reader.next().await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn auto_detects_content_length_framing() {
+    let input = format!(
+        "{}content-length: {}\r\nContent-Type: application/json\r\n\r\n{}",
+        frame(PING),
+        PING.len(),
+        PING
+    );
+    let mut reader = FrameReader::new(input.as_bytes(), Framing::Auto);
+    assert_eq!(
+        reader.next().await.unwrap(),
+        Some(Incoming::Message(PING.to_string()))
+    );
+    assert_eq!(reader.framing(), Framing::ContentLength);
+    assert_eq!(
+        reader.next().await.unwrap(),
+        Some(Incoming::Message(PING.to_string()))
+    );
+    assert_eq!(reader.next().await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn malformed_frames_are_reported_and_skipped() {
+    let input = format!(
+        "Content-Length: abc\r\n\r\nX-Other: 1\r\n\r\n{}Content-Length: 500\r\n\r\n{{}}",
+        frame(PING)
+    );
+    let mut reader = FrameReader::new(input.as_bytes(), Framing::ContentLength);
+    assert!(matches!(
+        reader.next().await.unwrap(),
+        Some(Incoming::Malformed(reason)) if reason.contains("invalid Content-Length")
+    ));
+    assert!(matches!(
+        reader.next().await.unwrap(),
+        Some(Incoming::Malformed(reason)) if reason.contains("missing Content-Length")
+    ));
+    assert_eq!(
+        reader.next().await.unwrap(),
+        Some(Incoming::Message(PING.to_string()))
+    );
+    assert!(matches!(
+        reader.next().await.unwrap(),
+        Some(Incoming::Malformed(reason)) if reason.contains("frame body")
+    ));
+}
+
+#[tokio::test]
+async fn oversized_lines_are_skipped_through_their_newline() {
+    let huge = "x".repeat(9 * 1024 * 1024);
+    let input = format!("{}\n{}\n", huge, PING);
+    let mut reader = FrameReader::new(input.as_bytes(), Framing::Newline);
+    assert!(matches!(
+        reader.next().await.unwrap(),
+        Some(Incoming::Malformed(reason)) if reason.contains("exceeds limit")
+    ));
+    assert_eq!(
+        reader.next().await.unwrap(),
+        Some(Incoming::Message(PING.to_string()))
+    );
+    assert_eq!(reader.next().await.unwrap(), None);
+
+    // A runaway header line is dropped with the rest of its frame
+    let input = format!(
+        "Content-Length: 2\r\nX-Padding: {}\r\n\r\n{{}}{}",
+        huge,
+        frame(PING)
+    );
+    let mut reader = FrameReader::new(input.as_bytes(), Framing::ContentLength);
+    assert!(matches!(
+        reader.next().await.unwrap(),
+        Some(Incoming::Malformed(reason)) if reason.contains("frame header exceeds limit")
+    ));
+    assert_eq!(
+        reader.next().await.unwrap(),
+        Some(Incoming::Message(PING.to_string()))
+    );
+
+    // An unterminated one ends the input
+    let mut reader = FrameReader::new(huge.as_bytes(), Framing::Newline);
+    assert!(matches!(
+        reader.next().await.unwrap(),
+        Some(Incoming::Malformed(_))
+    ));
+    assert_eq!(reader.next().await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn responses_use_the_detected_framing() {
+    let server = test_server();
+    let input = frame(PING);
+    let mut output = Vec::new();
+    serve(&server, input.as_bytes(), &mut output, Framing::Auto)
+        .await
+        .unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    let (header, body) = output.split_once("\r\n\r\n").unwrap();
+    assert_eq!(header, format!("Content-Length: {}", body.len()));
+    let response: Value = serde_json::from_str(body).unwrap();
+    assert_eq!(response["result"], json!({}));
+
+    let mut output = Vec::new();
+    serve(
+        &server,
+        "not json\n".as_bytes(),
+        &mut output,
+        Framing::Newline,
+    )
+    .await
+    .unwrap();
+    let response: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(response["error"]["code"], -32700);
+}
+
+fn test_server() -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager"),
+    );
+    NovaServer::new(NovaConfig::default(), plugin_manager)
+}
@@ -0,0 +1,75 @@
+// Runs the server binary over stdio and checks that stdout carries nothing
+// but JSON-RPC messages, even with debug logging on.
+use serde_json::{json, Value};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+const SESSION: &str = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-06-18","capabilities":{},"clientInfo":{"name":"test","version":"1"}},"context_type":"user","context_id":"42"}
+{"jsonrpc":"2.0","method":"notifications/initialized"}
+{"jsonrpc":"2.0","id":2,"method":"logging/setLevel","params":{"level":"debug"}}
+{"jsonrpc":"2.0","id":3,"method":"tools/list"}
+{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"get_my_usage","arguments":{}}}
+{"jsonrpc":"2.0","id":5,"method":"tools/call","params":{"name":"no_such_tool","arguments":{}}}
+this is not json
+{"jsonrpc":"2.0","id":6,"method":"ping"}
+"#;
+
+#[tokio::test]
+async fn stdout_only_carries_json_rpc() {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_nova-mcp-stdio"));
+    command
+        .args(["--transport", "stdio", "--ephemeral"])
+        .env("RUST_LOG", "debug")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    for name in [
+        "NOVA_MCP_CONFIG",
+        "NOVA_MCP_TRANSPORT",
+        "NOVA_MCP_STDIO_FRAMING",
+    ] {
+        command.env_remove(name);
+    }
+    let mut child = command.spawn().unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(SESSION.as_bytes()).await.unwrap();
+    drop(stdin);
+    let output = tokio::time::timeout(std::time::Duration::from_secs(60), child.wait_with_output())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let messages: Vec<Value> = stdout
+        .lines()
+        .map(|line| {
+            serde_json::from_str(line).unwrap_or_else(|_| panic!("not JSON on stdout: {}", line))
+        })
+        .collect();
+    assert!(messages.iter().all(|message| message["jsonrpc"] == "2.0"));
+    let ids: Vec<Value> = messages
+        .iter()
+        .filter(|message| message.get("method").is_none())
+        .map(|message| message["id"].clone())
+        .collect();
+    // The malformed line is answered with a null id
+    assert_eq!(
+        ids,
+        [
+            json!(1),
+            json!(2),
+            json!(3),
+            json!(4),
+            json!(5),
+            Value::Null,
+            json!(6)
+        ]
+    );
+
+    // Logs, including those from before the transport was chosen, are on stderr
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Starting Nova MCP Server"), "{}", stderr);
+    assert!(stderr.contains("Received:"), "{}", stderr);
+}
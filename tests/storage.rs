@@ -0,0 +1,140 @@
+use nova_mcp::config::{CliArgs, NovaConfig, StorageConfig};
+use nova_mcp::plugins::{
+    PluginContextType, PluginManager, PluginRegistrationRequest, RequestContext,
+};
+use nova_mcp::storage::{open_db, open_temporary, KvStore, MemoryKv};
+use nova_mcp::NovaServer;
+use serde_json::json;
+use std::ops::Bound;
+
+#[test]
+fn memory_path_opens_temporary_database() {
+    let cfg = StorageConfig {
+        path: StorageConfig::MEMORY_PATH.to_string(),
+        ..StorageConfig::default()
+    };
+    let db = open_db(&cfg).unwrap();
+    db.insert("k", "v").unwrap();
+    assert_eq!(db.get("k").unwrap().as_deref(), Some(&b"v"[..]));
+}
+
+#[test]
+fn memory_and_sled_stores_behave_alike() {
+    let tree = open_temporary().unwrap().open_tree("kv").unwrap();
+    let stores: [Box<dyn KvStore>; 2] = [Box::new(MemoryKv::default()), Box::new(tree)];
+    for store in &stores {
+        assert_eq!(store.insert(b"a|1", b"one".to_vec()).unwrap(), None);
+        assert_eq!(
+            store.insert(b"a|1", b"uno".to_vec()).unwrap().as_deref(),
+            Some(&b"one"[..])
+        );
+        store.insert(b"a|2", b"two".to_vec()).unwrap();
+        store.insert(b"b|1", b"three".to_vec()).unwrap();
+
+        let keys = |pairs: Vec<(Vec<u8>, Vec<u8>)>| -> Vec<Vec<u8>> {
+            pairs.into_iter().map(|(key, _)| key).collect()
+        };
+        assert_eq!(
+            keys(store.scan_prefix(b"a|").unwrap()),
+            [b"a|1".to_vec(), b"a|2".to_vec()]
+        );
+        assert_eq!(
+            keys(
+                store
+                    .range(Bound::Excluded(&b"a|1"[..]), Bound::Included(&b"b|1"[..]))
+                    .unwrap()
+            ),
+            [b"a|2".to_vec(), b"b|1".to_vec()]
+        );
+        assert_eq!(store.last().unwrap().unwrap().0, b"b|1");
+
+        let old = store
+            .fetch_and_update(b"a|2", &mut |old| old.map(|value| [value, b"!"].concat()))
+            .unwrap();
+        assert_eq!(old.as_deref(), Some(&b"two"[..]));
+        assert_eq!(store.get(b"a|2").unwrap().as_deref(), Some(&b"two!"[..]));
+        store.fetch_and_update(b"a|2", &mut |_| None).unwrap();
+        assert_eq!(store.get(b"a|2").unwrap(), None);
+
+        // Only removed while it still holds the expected value
+        assert!(!store.remove_if(b"b|1", b"stale").unwrap());
+        assert!(store.remove_if(b"b|1", b"three").unwrap());
+        assert_eq!(store.remove(b"a|1").unwrap().as_deref(), Some(&b"uno"[..]));
+        assert!(store.scan_prefix(b"").unwrap().is_empty());
+        store.flush().unwrap();
+    }
+    assert!(!stores[0].is_persistent());
+    assert!(stores[1].is_persistent());
+}
+
+#[test]
+fn tuned_database_persists_at_configured_path() {
+    let dir = std::env::temp_dir().join(format!("nova-storage-{}", std::process::id()));
+    let cfg = StorageConfig {
+        path: dir.display().to_string(),
+        cache_capacity_bytes: 8 * 1024 * 1024,
+        flush_every_ms: 0,
+        compression: true,
+    };
+    {
+        let db = open_db(&cfg).unwrap();
+        db.insert("plugin", "echo").unwrap();
+        db.flush().unwrap();
+    }
+    let db = open_db(&cfg).unwrap();
+    assert_eq!(db.get("plugin").unwrap().as_deref(), Some(&b"echo"[..]));
+    drop(db);
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn ephemeral_flag_selects_a_temporary_database() {
+    let cli = CliArgs::parse(["--ephemeral".to_string()]).unwrap();
+    assert!(cli.ephemeral);
+    let config = NovaConfig::load_with(&cli).unwrap();
+    assert!(config.storage.is_temporary());
+    assert!(CliArgs::parse(["--ephemeral=yes".to_string()]).is_err());
+
+    let db = open_temporary().unwrap();
+    db.insert("k", "v").unwrap();
+    assert!(open_temporary().unwrap().get("k").unwrap().is_none());
+}
+
+#[test]
+fn in_memory_registries_are_isolated() {
+    let owner = RequestContext {
+        context_type: PluginContextType::Channel,
+        context_id: "-100123".to_string(),
+        actor_id: None,
+    };
+    let first = PluginManager::in_memory().unwrap();
+    let plugin = first
+        .register_plugin(
+            &owner,
+            PluginRegistrationRequest {
+                name: "echo".to_string(),
+                description: "test".to_string(),
+                owner_id: None,
+                input_schema: json!({ "type": "object" }),
+                output_schema: None,
+                endpoint_url: "https://example.com/hook".to_string(),
+                script: None,
+                version: 1,
+                trust_level: Default::default(),
+                client_certificate: None,
+                credentials: None,
+                redact: Vec::new(),
+                request_template: None,
+                listing: None,
+                allowed_contexts: Vec::new(),
+                tags: Vec::new(),
+                coerce_arguments: false,
+            },
+        )
+        .unwrap();
+    assert!(first.get_plugin(plugin.plugin_id).is_ok());
+    assert_eq!(first.check_registry().unwrap(), 1);
+
+    let server = NovaServer::in_memory(NovaConfig::default()).unwrap();
+    assert_eq!(server.plugin_manager().check_registry().unwrap(), 0);
+}
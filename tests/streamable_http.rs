@@ -0,0 +1,269 @@
+use nova_mcp::{NovaConfig, NovaServer, PluginManager};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn streamable_http_session_lifecycle() {
+    let url = start_server().await;
+    let client = reqwest::Client::new();
+
+    let init = client
+        .post(&url)
+        .header("accept", "application/json, text/event-stream")
+        .header("x-nova-context-type", "user")
+        .header("x-nova-context-id", "7")
+        .json(&json!({
+            "jsonrpc": "2.0", "id": 1, "method": "initialize",
+            "params": { "protocolVersion": "2025-03-26" }
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(init.status(), 200);
+    let session = init.headers()["mcp-session-id"]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let body: Value = init.json().await.unwrap();
+    assert_eq!(body["result"]["protocolVersion"], "2025-03-26");
+
+    let notified = client
+        .post(&url)
+        .header("mcp-session-id", &session)
+        .json(&json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(notified.status(), 202);
+
+    let streamed = client
+        .post(&url)
+        .header("accept", "text/event-stream")
+        .header("mcp-session-id", &session)
+        .json(&json!({ "jsonrpc": "2.0", "id": 2, "method": "ping" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        streamed.headers()["content-type"].to_str().unwrap(),
+        "text/event-stream"
+    );
+    let text = streamed.text().await.unwrap();
+    assert!(text.contains("id: 1"));
+    assert!(text.contains(r#""result":{}"#));
+
+    // Resume from before the first event: the ping response is replayed.
+    let mut resumed = client
+        .get(&url)
+        .header("accept", "text/event-stream")
+        .header("mcp-session-id", &session)
+        .header("last-event-id", "0")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resumed.status(), 200);
+    let chunk = tokio::time::timeout(Duration::from_secs(5), resumed.chunk())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&chunk).contains(r#""result":{}"#));
+    drop(resumed);
+
+    let missing = client
+        .post(&url)
+        .json(&json!({ "jsonrpc": "2.0", "id": 3, "method": "ping" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(missing.status(), 400);
+
+    let closed = client
+        .delete(&url)
+        .header("mcp-session-id", &session)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(closed.status(), 204);
+    let gone = client
+        .post(&url)
+        .header("mcp-session-id", &session)
+        .json(&json!({ "jsonrpc": "2.0", "id": 4, "method": "ping" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(gone.status(), 404);
+}
+
+#[tokio::test]
+async fn batches_return_one_response_per_request() {
+    let url = start_server().await;
+    let client = reqwest::Client::new();
+    let init = client
+        .post(&url)
+        .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize" }))
+        .send()
+        .await
+        .unwrap();
+    let session = init.headers()["mcp-session-id"]
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    let body: Value = client
+        .post(&url)
+        .header("mcp-session-id", &session)
+        .json(&json!([
+            { "jsonrpc": "2.0", "id": 10, "method": "ping" },
+            { "jsonrpc": "2.0", "method": "notifications/cancelled" },
+            { "jsonrpc": "2.0", "id": 11, "method": "ping" }
+        ]))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let ids: Vec<_> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["id"].clone())
+        .collect();
+    assert_eq!(ids, vec![json!(10), json!(11)]);
+}
+
+#[tokio::test]
+async fn browser_origins_must_be_allowed() {
+    let mut config = NovaConfig::default();
+    config.server.allowed_origins = vec!["https://app.example.com".to_string()];
+    let url = start_server_with(config).await;
+    let client = reqwest::Client::new();
+    let initialize = json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize" });
+
+    let allowed = client
+        .post(&url)
+        .header("origin", "https://app.example.com")
+        .json(&initialize)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(allowed.status(), 200);
+    let session = allowed.headers()["mcp-session-id"]
+        .to_str()
+        .unwrap()
+        .to_string();
+    // The server's own origin needs no listing
+    let same_origin = client
+        .post(&url)
+        .header("origin", url.trim_end_matches("/mcp"))
+        .header("mcp-session-id", &session)
+        .json(&json!({ "jsonrpc": "2.0", "id": 2, "method": "ping" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(same_origin.status(), 200);
+
+    for origin in ["https://evil.example", "null", "http://app.example.com"] {
+        let rejected = client
+            .post(&url)
+            .header("origin", origin)
+            .header("mcp-session-id", &session)
+            .json(&json!({ "jsonrpc": "2.0", "id": 3, "method": "ping" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(rejected.status(), 403, "{}", origin);
+        let stream = client
+            .get(&url)
+            .header("origin", origin)
+            .header("accept", "text/event-stream")
+            .header("mcp-session-id", &session)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(stream.status(), 403, "{}", origin);
+        let closed = client
+            .delete(&url)
+            .header("origin", origin)
+            .header("mcp-session-id", &session)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(closed.status(), 403, "{}", origin);
+    }
+
+    let closed = client
+        .delete(&url)
+        .header("origin", "https://app.example.com")
+        .header("mcp-session-id", &session)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(closed.status(), 204);
+}
+
+#[tokio::test]
+async fn cross_origin_requests_are_refused_by_default() {
+    let url = start_server().await;
+    let refused = reqwest::Client::new()
+        .post(&url)
+        .header("origin", "https://app.example.com")
+        .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(refused.status(), 403);
+
+    // A page on a name rebound to 127.0.0.1 sends a matching Host
+    let port = reqwest::Url::parse(&url).unwrap().port().unwrap();
+    let rebound = reqwest::Client::new()
+        .post(&url)
+        .header("origin", format!("http://evil.example:{}", port))
+        .header("host", format!("evil.example:{}", port))
+        .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(rebound.status(), 403);
+
+    let mut config = NovaConfig::default();
+    config.server.allowed_origins = vec!["https://app.example.com/tools".to_string()];
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("server.allowed_origins"), "{}", err);
+}
+
+async fn start_server() -> String {
+    start_server_with(NovaConfig::default()).await
+}
+
+async fn start_server_with(mut config: NovaConfig) -> String {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    config.server.port = port;
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager"),
+    );
+    let server = NovaServer::new(config.clone(), plugin_manager);
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let url = format!("http://127.0.0.1:{}/mcp", port);
+    for _ in 0..50 {
+        if reqwest::get(format!("http://127.0.0.1:{}/healthz", port))
+            .await
+            .is_ok()
+        {
+            return url;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("server did not start");
+}
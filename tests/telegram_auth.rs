@@ -0,0 +1,179 @@
+use hmac::{Hmac, Mac};
+use nova_mcp::auth::TelegramAuth;
+use nova_mcp::clock::{ManualClock, SharedClock};
+use nova_mcp::config::AuthConfig;
+use nova_mcp::plugins::PluginContextType;
+use nova_mcp::{NovaConfig, NovaServer, PluginManager};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::Duration;
+
+const BOT_TOKEN: &str = "123456:test-bot-token";
+const NOW: i64 = 1_700_000_000;
+
+#[test]
+fn init_data_from_a_group_acts_for_the_group() {
+    let telegram = telegram();
+    let raw = sign_init_data(&[
+        ("auth_date", NOW.to_string()),
+        ("user", json!({ "id": 42, "first_name": "A b" }).to_string()),
+        (
+            "chat",
+            json!({ "id": -100123, "type": "supergroup" }).to_string(),
+        ),
+    ]);
+    let context = telegram.verify_init_data(&raw, NOW + 60).unwrap();
+    assert_eq!(context.context_type, PluginContextType::Group);
+    assert_eq!(context.context_id, "-100123");
+    assert_eq!(context.actor_id.as_deref(), Some("42"));
+
+    let private = sign_init_data(&[
+        ("auth_date", NOW.to_string()),
+        ("user", json!({ "id": 42 }).to_string()),
+    ]);
+    let context = telegram.verify_init_data(&private, NOW).unwrap();
+    assert_eq!(context.context_type, PluginContextType::User);
+    assert_eq!(context.context_id, "42");
+    assert_eq!(context.actor_id, None);
+}
+
+#[test]
+fn tampered_or_stale_data_is_rejected() {
+    let telegram = telegram();
+    let raw = sign_init_data(&[
+        ("auth_date", NOW.to_string()),
+        ("user", json!({ "id": 42 }).to_string()),
+    ]);
+    let forged = raw.replace("42", "43");
+    assert!(telegram.verify_init_data(&forged, NOW).is_err());
+    assert!(telegram.verify_init_data(&raw, NOW + 86_401).is_err());
+    // Login-widget signatures use a different key
+    assert!(telegram.verify_login(&raw, NOW).is_err());
+}
+
+#[test]
+fn login_widget_names_a_user() {
+    let telegram = telegram();
+    let fields = [("auth_date", NOW.to_string()), ("id", "7".to_string())];
+    let secret = Sha256::digest(BOT_TOKEN.as_bytes());
+    let raw = sign(&fields, &secret);
+    let context = telegram.verify_login(&raw, NOW).unwrap();
+    assert_eq!(context.context_type, PluginContextType::User);
+    assert_eq!(context.context_id, "7");
+}
+
+#[tokio::test]
+async fn telegram_mode_ignores_context_headers() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig::default();
+    config.server.port = port;
+    config.auth.mode = "telegram".into();
+    config.auth.telegram_bot_token = Some(BOT_TOKEN.into());
+    let clock = ManualClock::at(NOW);
+    let server = test_server(config.clone(), &clock);
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let client = reqwest::Client::new();
+    let url = format!("http://127.0.0.1:{}/rpc", port);
+    let list = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" });
+    let mut spoofed = None;
+    for _ in 0..50 {
+        match client
+            .post(&url)
+            .header("x-nova-context-type", "user")
+            .header("x-nova-context-id", "1")
+            .json(&list)
+            .send()
+            .await
+        {
+            Ok(resp) => {
+                spoofed = Some(resp.json::<Value>().await.unwrap());
+                break;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(50)).await,
+        }
+    }
+    assert!(spoofed.expect("server did not start")["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("Invalid Telegram auth data"));
+
+    let init_data = sign_init_data(&[
+        ("auth_date", NOW.to_string()),
+        ("user", json!({ "id": 42 }).to_string()),
+    ]);
+    let send = || {
+        client
+            .post(&url)
+            .header("x-telegram-init-data", init_data.clone())
+            .json(&list)
+            .send()
+    };
+    let verified: Value = send().await.unwrap().json().await.unwrap();
+    assert!(verified["result"].is_object());
+
+    // The same init data a day and a second later is too old
+    clock.advance(Duration::from_secs(86_401));
+    let expired: Value = send().await.unwrap().json().await.unwrap();
+    assert!(expired["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("Invalid Telegram auth data"));
+}
+
+fn telegram() -> TelegramAuth {
+    let config = AuthConfig {
+        mode: "telegram".into(),
+        telegram_bot_token: Some(BOT_TOKEN.into()),
+        ..AuthConfig::default()
+    };
+    TelegramAuth::new(&config).unwrap()
+}
+
+fn sign_init_data(fields: &[(&str, String)]) -> String {
+    sign(fields, &hmac(b"WebAppData", BOT_TOKEN.as_bytes()))
+}
+
+fn sign(fields: &[(&str, String)], secret: &[u8]) -> String {
+    let mut sorted = fields.to_vec();
+    sorted.sort();
+    let data_check = sorted
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let hash: String = hmac(secret, data_check.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    fields
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, urlencoding::encode(value)))
+        .chain(std::iter::once(format!("hash={}", hash)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn hmac(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn test_server(config: NovaConfig, clock: &ManualClock) -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree)
+            .expect("init plugin manager")
+            .with_clock(SharedClock::new(clock.clone())),
+    );
+    NovaServer::new(config, plugin_manager)
+}
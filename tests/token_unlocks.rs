@@ -0,0 +1,153 @@
+// get_token_unlocks against a mocked DefiLlama emissions endpoint.
+use nova_mcp::clock::{ManualClock, SharedClock};
+use nova_mcp::tools::rate_limit::UpstreamRateLimiter;
+use nova_mcp::tools::{GetTokenUnlocksInput, TokenUnlockTools};
+use nova_mcp::{NovaConfig, NovaError};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+// 2026-03-01T00:00:00Z
+const NOW: i64 = 1_772_323_200;
+const DAY: i64 = 86_400;
+
+fn tools(upstream: &MockServer) -> TokenUnlockTools {
+    let limiter = Arc::new(UpstreamRateLimiter::new(
+        "token_unlocks",
+        600,
+        Duration::ZERO,
+    ));
+    TokenUnlockTools::with_rate_limiter(limiter)
+        .with_base_url(upstream.uri())
+        .with_clock(SharedClock::new(ManualClock::at(NOW)))
+}
+
+fn input(token: &str) -> GetTokenUnlocksInput {
+    GetTokenUnlocksInput {
+        token: token.to_string(),
+        days: None,
+        limit: None,
+    }
+}
+
+fn event(at: i64, tokens: &[f64], category: &str, kind: &str) -> Value {
+    json!({
+        "timestamp": at,
+        "noOfTokens": tokens,
+        "category": category,
+        "unlockType": kind,
+        "description": format!("{} unlock", category),
+    })
+}
+
+fn emission() -> Value {
+    json!({
+        "name": "Arbitrum",
+        "supplyMetrics": { "maxSupply": 10_000_000_000.0, "adjustedSupply": 9_000_000_000.0 },
+        "metadata": {
+            "token": "coingecko:arbitrum",
+            "events": [
+                event(NOW - 30 * DAY, &[92_650_000.0], "insiders", "cliff"),
+                event(NOW + 45 * DAY, &[50_000_000.0], "privateSale", "cliff"),
+                event(NOW + 15 * DAY, &[56_130_000.0, 36_520_000.0], "insiders", "cliff"),
+                event(NOW + 15 * DAY, &[10_000_000.0], "airdrop", "linear"),
+                event(NOW + 120 * DAY, &[92_650_000.0], "insiders", "cliff"),
+            ]
+        }
+    })
+}
+
+#[tokio::test]
+async fn lists_upcoming_unlocks_with_their_share_of_supply() {
+    let upstream = MockServer::start().await;
+    // DefiLlama wraps the document in a string
+    Mock::given(method("GET"))
+        .and(path("/emission/arbitrum"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "body": emission().to_string() })),
+        )
+        .mount(&upstream)
+        .await;
+
+    let output = tools(&upstream)
+        .get_unlocks(input(" Arbitrum "))
+        .await
+        .unwrap();
+    assert_eq!(output.token, "arbitrum");
+    assert_eq!(output.name.as_deref(), Some("Arbitrum"));
+    assert_eq!(output.max_supply, Some(10_000_000_000.0));
+    assert_eq!(output.days, 90);
+    // The past and beyond-90-days unlocks are left out
+    let listed: Vec<_> = output
+        .unlocks
+        .iter()
+        .map(|u| (u.timestamp - NOW, u.category.as_deref().unwrap()))
+        .collect();
+    assert_eq!(
+        listed,
+        [
+            (15 * DAY, "airdrop"),
+            (15 * DAY, "insiders"),
+            (45 * DAY, "privateSale")
+        ]
+    );
+    assert_eq!(output.unlocks[1].amount, 92_650_000.0);
+    assert_eq!(output.unlocks[1].percent_of_supply, Some(0.9265));
+    assert_eq!(output.unlocks[0].kind.as_deref(), Some("linear"));
+    assert_eq!(output.unlocks[0].date, "2026-03-16T00:00:00Z");
+
+    let next = output.next_unlock.unwrap();
+    assert_eq!(next.timestamp, NOW + 15 * DAY);
+    assert_eq!(next.amount, 102_650_000.0);
+    assert_eq!(next.percent_of_supply, Some(1.0265));
+    assert_eq!(next.categories, ["airdrop", "insiders"]);
+    assert_eq!(output.upcoming_percent_of_supply, Some(1.5265));
+
+    let mut short = input("arbitrum");
+    short.days = Some(10);
+    let output = tools(&upstream).get_unlocks(short).await.unwrap();
+    assert!(output.unlocks.is_empty());
+    assert!(output.next_unlock.is_none());
+    assert_eq!(output.upcoming_percent_of_supply, Some(0.0));
+
+    let mut one = input("arbitrum");
+    one.limit = Some(1);
+    let output = tools(&upstream).get_unlocks(one).await.unwrap();
+    assert_eq!(output.unlocks.len(), 1);
+    assert_eq!(output.upcoming_percent_of_supply, Some(1.5265));
+}
+
+#[tokio::test]
+async fn unknown_tokens_and_bad_input_are_reported() {
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/emission/nope"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&upstream)
+        .await;
+    let tools = tools(&upstream);
+
+    let err = tools.get_unlocks(input("nope")).await.unwrap_err();
+    assert!(matches!(err, NovaError::TokenNotFound { .. }), "{:?}", err);
+
+    let mut days = input("arbitrum");
+    days.days = Some(0);
+    let mut limit = input("arbitrum");
+    limit.limit = Some(51);
+    for bad in [input("../admin"), input(""), days, limit] {
+        let err = tools.get_unlocks(bad).await.unwrap_err();
+        assert!(
+            matches!(err, NovaError::ValidationError { .. }),
+            "{:?}",
+            err
+        );
+    }
+
+    let mut config = NovaConfig::default();
+    config.apis.token_unlocks_url = Some("ftp://llama".to_string());
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("apis.token_unlocks_url"), "{}", err);
+}
@@ -0,0 +1,143 @@
+use axum::{routing::get, Json, Router};
+use nova_mcp::mcp::{dto::McpRequest, handler};
+use nova_mcp::tools::{CallPriority, ToolConcurrency};
+use nova_mcp::{NovaConfig, NovaServer};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[tokio::test]
+async fn slots_are_released_on_drop() {
+    let caps = HashMap::from([("search_pools".to_string(), 1)]);
+    let concurrency = ToolConcurrency::new(&caps);
+    assert!(concurrency
+        .acquire("get_gecko_token", CallPriority::Interactive)
+        .await
+        .is_none());
+
+    let held = concurrency
+        .acquire("search_pools", CallPriority::Interactive)
+        .await
+        .unwrap();
+    assert_eq!(concurrency.snapshot()["search_pools"].in_use, 1);
+    let waiting = tokio::time::timeout(
+        Duration::from_millis(50),
+        concurrency.acquire("search_pools", CallPriority::Interactive),
+    )
+    .await;
+    assert!(waiting.is_err());
+
+    drop(held);
+    // The waiter that timed out does not keep the slot
+    let again = concurrency
+        .acquire("search_pools", CallPriority::Background)
+        .await;
+    assert!(again.is_some());
+    assert_eq!(concurrency.snapshot()["search_pools"].in_use, 1);
+    drop(again);
+    assert_eq!(concurrency.snapshot()["search_pools"].in_use, 0);
+}
+
+#[tokio::test]
+async fn interactive_calls_take_freed_slots_first() {
+    let caps = HashMap::from([("get_new_pools".to_string(), 1)]);
+    let concurrency = Arc::new(ToolConcurrency::new(&caps));
+    let held = concurrency
+        .acquire("get_new_pools", CallPriority::Interactive)
+        .await
+        .unwrap();
+
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let mut waiters = Vec::new();
+    for (label, priority) in [
+        ("refresh-1", CallPriority::Background),
+        ("refresh-2", CallPriority::Background),
+        ("user-1", CallPriority::Interactive),
+        ("user-2", CallPriority::Interactive),
+    ] {
+        let (shared, log) = (Arc::clone(&concurrency), Arc::clone(&order));
+        waiters.push(tokio::spawn(async move {
+            let _slot = shared.acquire("get_new_pools", priority).await;
+            log.lock().unwrap().push(label);
+        }));
+        // Queue them in a known order
+        while concurrency.snapshot()["get_new_pools"].waiting_interactive
+            + concurrency.snapshot()["get_new_pools"].waiting_background
+            < waiters.len()
+        {
+            tokio::task::yield_now().await;
+        }
+    }
+    let slots = &concurrency.snapshot()["get_new_pools"];
+    assert_eq!(
+        (slots.waiting_interactive, slots.waiting_background),
+        (2, 2)
+    );
+
+    drop(held);
+    for waiter in waiters {
+        waiter.await.unwrap();
+    }
+    assert_eq!(
+        *order.lock().unwrap(),
+        ["user-1", "user-2", "refresh-1", "refresh-2"]
+    );
+}
+
+#[tokio::test]
+async fn capped_tool_calls_queue_behind_each_other() {
+    let active = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let (current, highest) = (Arc::clone(&active), Arc::clone(&peak));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let upstream = format!("http://{}", listener.local_addr().unwrap());
+    let app = Router::new().route(
+        "/search/pools",
+        get(move || {
+            let (current, highest) = (Arc::clone(&current), Arc::clone(&highest));
+            async move {
+                let now = current.fetch_add(1, Ordering::SeqCst) + 1;
+                highest.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(150)).await;
+                current.fetch_sub(1, Ordering::SeqCst);
+                Json(json!({ "data": [] }))
+            }
+        }),
+    );
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let mut config = NovaConfig::default();
+    config.apis.gecko_terminal_url = Some(upstream);
+    config.limits.tool_concurrency = HashMap::from([("search_pools".to_string(), 2)]);
+    let server = Arc::new(NovaServer::in_memory(config).unwrap());
+
+    let calls = (0..5).map(|i| {
+        let server = Arc::clone(&server);
+        tokio::spawn(async move {
+            let call = McpRequest {
+                jsonrpc: "2.0".to_string(),
+                id: Some(json!(i)),
+                method: "tools/call".to_string(),
+                params: Some(json!({
+                    "name": "search_pools",
+                    "arguments": { "query": format!("pepe-{}", i) }
+                })),
+                context_type: Some("user".to_string()),
+                context_id: Some("1".to_string()),
+                actor_id: None,
+            };
+            handler::handle_request(&server, call, None).await
+        })
+    });
+    for call in calls.collect::<Vec<_>>() {
+        let response = call.await.unwrap();
+        assert!(response.error.is_none(), "{:?}", response.error);
+    }
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+
+    let mut invalid = NovaConfig::default();
+    invalid.limits.tool_concurrency = HashMap::from([("search_pools".to_string(), 0)]);
+    let err = invalid.validate().unwrap_err().to_string();
+    assert!(err.contains("limits.tool_concurrency"), "{}", err);
+}
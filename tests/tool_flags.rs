@@ -0,0 +1,70 @@
+use nova_mcp::plugins::{PluginContextType, RequestContext};
+use nova_mcp::server::ToolCall;
+use nova_mcp::{NovaConfig, NovaError, NovaServer, PluginManager};
+use serde_json::json;
+use std::sync::Arc;
+
+#[test]
+fn allowlist_limits_listed_builtin_tools() {
+    let mut config = NovaConfig::default();
+    config.tools.enabled = Some(vec!["get_gecko_networks".into(), "search_pools".into()]);
+    config.tools.disabled = vec!["search_pools".into()];
+    let server = test_server(config);
+
+    let names: Vec<_> = server
+        .get_tools(&context())
+        .unwrap()
+        .into_iter()
+        .map(|tool| tool.name)
+        .collect();
+    assert_eq!(names, vec!["get_gecko_networks"]);
+}
+
+#[tokio::test]
+async fn calling_a_disabled_tool_reports_disabled() {
+    let mut config = NovaConfig::default();
+    config.tools.enabled = Some(vec!["get_gecko_networks".into()]);
+    let server = test_server(config);
+
+    let err = server
+        .handle_tool_call(
+            ToolCall {
+                name: "get_trending_pools".into(),
+                arguments: json!({ "network": "eth" }),
+                select: None,
+                format: None,
+                priority: None,
+            },
+            &context(),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, NovaError::ToolDisabled { ref name } if name == "get_trending_pools"));
+}
+
+#[test]
+fn unknown_tool_names_fail_validation() {
+    let mut config = NovaConfig::default();
+    config.tools.disabled = vec!["get_gecko_pools".into()];
+    let err = config.validate().unwrap_err();
+    assert!(err.to_string().contains("get_gecko_pools"));
+}
+
+fn context() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "1".into(),
+        actor_id: None,
+    }
+}
+
+fn test_server(config: NovaConfig) -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree).expect("init plugin manager"),
+    );
+    NovaServer::new(config, plugin_manager)
+}
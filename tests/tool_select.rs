@@ -0,0 +1,140 @@
+use axum::{routing::post, Json, Router};
+use nova_mcp::config::PluginsConfig;
+use nova_mcp::mcp::select::Selector;
+use nova_mcp::mcp::{dto::McpRequest, handler};
+use nova_mcp::plugins::{
+    EgressPolicy, PluginContextType, PluginManager, PluginRegistrationRequest, RequestContext,
+};
+use nova_mcp::{NovaConfig, NovaServer};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+fn pool() -> Value {
+    json!({
+        "data": {
+            "id": "aptos_0x1",
+            "attributes": { "name": "APT / USDC", "base_token_price_usd": "8.41" },
+            "relationships": { "dex": { "data": { "id": "thala" } } }
+        },
+        "included": [
+            { "id": "apt", "attributes": { "symbol": "APT" } },
+            { "id": "usdc", "attributes": { "symbol": "USDC" } }
+        ]
+    })
+}
+
+#[test]
+fn paths_pick_values_and_wildcards_collect_them() {
+    let select = |expression: &str| Selector::parse(expression).unwrap().apply(&pool());
+    assert_eq!(
+        select("data.attributes.base_token_price_usd"),
+        json!("8.41")
+    );
+    assert_eq!(
+        select("$.data.attributes.base_token_price_usd"),
+        json!("8.41")
+    );
+    assert_eq!(select("included[1].id"), json!("usdc"));
+    assert_eq!(select("data['id']"), json!("aptos_0x1"));
+    assert_eq!(select("data.attributes.missing"), Value::Null);
+    assert_eq!(
+        select("included[*].attributes.symbol"),
+        json!(["APT", "USDC"])
+    );
+    assert_eq!(select("$..symbol"), json!(["APT", "USDC"]));
+    assert_eq!(select("$"), pool());
+
+    for invalid in ["data[", "data.", "$[abc]"] {
+        assert!(Selector::parse(invalid).is_err(), "{}", invalid);
+    }
+}
+
+#[tokio::test]
+async fn tools_call_applies_select_before_serializing() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let app = Router::new().route("/pool", post(|| async { Json(pool()) }));
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let server = test_server();
+    let metadata = server
+        .plugin_manager()
+        .register_plugin(
+            &owner(),
+            registration(&format!("http://127.0.0.1:{}/pool", port)),
+        )
+        .unwrap();
+
+    let call = |select: Value| {
+        let mut params = json!({ "name": metadata.fq_name, "arguments": {} });
+        params["select"] = select;
+        McpRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(1)),
+            method: "tools/call".to_string(),
+            params: Some(params),
+            context_type: Some("user".to_string()),
+            context_id: Some("5".to_string()),
+            actor_id: None,
+        }
+    };
+
+    let response =
+        handler::handle_request(&server, call(json!("data.attributes.name")), None).await;
+    let result = response.result.expect("selected result");
+    assert_eq!(result["content"][0]["text"], "\"APT / USDC\"");
+
+    let response = handler::handle_request(&server, call(Value::Null), None).await;
+    let text = response.result.unwrap()["content"][0]["text"].clone();
+    assert!(text.as_str().unwrap().contains("base_token_price_usd"));
+
+    let response = handler::handle_request(&server, call(json!("data[")), None).await;
+    let error = response.error.expect("invalid select");
+    assert_eq!(error.code, -32602);
+    assert!(error.message.contains("select"));
+}
+
+fn owner() -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    }
+}
+
+fn registration(endpoint: &str) -> PluginRegistrationRequest {
+    PluginRegistrationRequest {
+        name: "pool".to_string(),
+        description: "test".to_string(),
+        owner_id: None,
+        input_schema: json!({ "type": "object" }),
+        output_schema: None,
+        endpoint_url: endpoint.to_string(),
+        script: None,
+        version: 1,
+        trust_level: Default::default(),
+        client_certificate: None,
+        credentials: None,
+        redact: Vec::new(),
+        request_template: None,
+        listing: None,
+        allowed_contexts: Vec::new(),
+        tags: Vec::new(),
+        coerce_arguments: false,
+    }
+}
+
+fn test_server() -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = PluginManager::new(metadata_tree, user_tree, group_tree)
+        .expect("init plugin manager")
+        .with_egress_policy(EgressPolicy::new(&PluginsConfig {
+            allowed_schemes: vec!["http".into()],
+            allow_private_networks: true,
+            ..PluginsConfig::default()
+        }));
+    NovaServer::new(NovaConfig::default(), Arc::new(plugin_manager))
+}
@@ -0,0 +1,106 @@
+use nova_mcp::config::{PluginsConfig, TimeoutConfig};
+use nova_mcp::mcp::{dto::McpRequest, handler};
+use nova_mcp::plugins::{
+    EgressPolicy, PluginContextType, PluginManager, PluginRegistrationRequest, RequestContext,
+};
+use nova_mcp::{NovaConfig, NovaServer};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn per_tool_overrides_win() {
+    let cfg = TimeoutConfig {
+        request_timeout_secs: 30,
+        tool_timeout_secs: 15,
+        tool_overrides: HashMap::from([("search_pools".to_string(), 3)]),
+    };
+    assert_eq!(cfg.for_tool("search_pools"), Duration::from_secs(3));
+    assert_eq!(cfg.for_tool("get_new_pools"), Duration::from_secs(15));
+}
+
+#[tokio::test]
+async fn hanging_tool_returns_timeout_error() {
+    // Accepts connections but never answers, so the plugin call hangs.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let mut held = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            held.push(socket);
+        }
+    });
+
+    let mut config = NovaConfig::default();
+    config.timeouts.tool_timeout_secs = 1;
+    let server = test_server(config);
+    let owner = RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "5".to_string(),
+        actor_id: None,
+    };
+    let metadata = server
+        .plugin_manager()
+        .register_plugin(
+            &owner,
+            PluginRegistrationRequest {
+                name: "slow".to_string(),
+                description: "never answers".to_string(),
+                owner_id: None,
+                input_schema: json!({ "type": "object" }),
+                output_schema: None,
+                endpoint_url: format!("https://127.0.0.1:{}/hook", port),
+                script: None,
+                version: 1,
+                trust_level: Default::default(),
+                client_certificate: None,
+                credentials: None,
+                redact: Vec::new(),
+                request_template: None,
+                listing: None,
+                allowed_contexts: Vec::new(),
+                tags: Vec::new(),
+                coerce_arguments: false,
+            },
+        )
+        .unwrap();
+
+    let req = McpRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: "tools/call".to_string(),
+        params: Some(json!({ "name": metadata.fq_name, "arguments": {} })),
+        context_type: Some("user".to_string()),
+        context_id: Some("5".to_string()),
+        actor_id: None,
+    };
+    let resp = handler::handle_request(&server, req, None).await;
+    let err = resp.error.expect("expected timeout error");
+    assert_eq!(err.code, -32000);
+    assert_eq!(err.message, "Tool call timed out after 1s");
+    let data = err.data.unwrap();
+    assert_eq!(data["timeoutSeconds"], 1);
+    assert_eq!(data["tool"], metadata.fq_name);
+    assert_eq!(data["code"], "tool_timeout");
+    assert_eq!(data["retryable"], true);
+    assert_eq!(data["details"]["timeout_secs"], 1);
+}
+
+fn test_server(config: NovaConfig) -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    // The hanging endpoint listens on loopback
+    let egress = EgressPolicy::new(&PluginsConfig {
+        allow_private_networks: true,
+        ..PluginsConfig::default()
+    });
+    let plugin_manager = Arc::new(
+        PluginManager::new(metadata_tree, user_tree, group_tree)
+            .expect("init plugin manager")
+            .with_egress_policy(egress),
+    );
+    NovaServer::new(config, plugin_manager)
+}
@@ -0,0 +1,176 @@
+use nova_mcp::plugins::{
+    PluginContextType, PluginEnableRequest, PluginManager, PluginRegistrationRequest,
+    RegistryChange, RequestContext,
+};
+use nova_mcp::stdio::{serve, Framing};
+use nova_mcp::test_util::TestServer;
+use nova_mcp::{NovaConfig, NovaServer};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader, Lines};
+
+const LIST_CHANGED: &str = "notifications/tools/list_changed";
+
+#[test]
+fn registry_writes_are_announced() {
+    let manager = PluginManager::in_memory().unwrap();
+    let mut changes = manager.subscribe();
+    let owner = context(PluginContextType::User, "5");
+    let plugin = manager.register_plugin(&owner, registration()).unwrap();
+    assert_eq!(
+        changes.try_recv().unwrap(),
+        RegistryChange::Plugin(plugin.plugin_id)
+    );
+
+    manager
+        .set_enablement(enable(plugin.plugin_id, "9"))
+        .unwrap();
+    let change = changes.try_recv().unwrap();
+    assert!(change.affects(Some(&context(PluginContextType::User, "9"))));
+    assert!(!change.affects(Some(&owner)));
+    assert!(!change.affects(None));
+
+    manager.unregister_plugin(&owner, plugin.plugin_id).unwrap();
+    assert_eq!(
+        changes.try_recv().unwrap(),
+        RegistryChange::Plugin(plugin.plugin_id)
+    );
+    assert!(changes.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn stdio_clients_hear_about_their_context_only() {
+    let server = Arc::new(NovaServer::in_memory(NovaConfig::default()).unwrap());
+    let (client, transport) = tokio::io::duplex(64 * 1024);
+    let (transport_in, transport_out) = tokio::io::split(transport);
+    let serving = Arc::clone(&server);
+    tokio::spawn(
+        async move { serve(&serving, transport_in, transport_out, Framing::Newline).await },
+    );
+    let (client_in, mut client_out) = tokio::io::split(client);
+    let mut lines = BufReader::new(client_in).lines();
+
+    // Nothing is pushed before `initialize`
+    let owner = context(PluginContextType::User, "5");
+    let plugin = server
+        .plugin_manager()
+        .register_plugin(&owner, registration())
+        .unwrap();
+    send(
+        &mut client_out,
+        json!({
+            "jsonrpc": "2.0", "id": 1, "method": "initialize",
+            "params": { "protocolVersion": "2025-06-18", "capabilities": {} },
+            "context_type": "user", "context_id": "7"
+        }),
+    )
+    .await;
+    let initialized = next(&mut lines).await;
+    assert_eq!(initialized["id"], 1);
+    assert_eq!(
+        initialized["result"]["capabilities"]["tools"]["listChanged"],
+        true
+    );
+
+    // Enabling for another context is not this client's concern
+    server
+        .plugin_manager()
+        .set_enablement(enable(plugin.plugin_id, "8"))
+        .unwrap();
+    server
+        .plugin_manager()
+        .set_enablement(enable(plugin.plugin_id, "7"))
+        .unwrap();
+    assert_eq!(next(&mut lines).await["method"], LIST_CHANGED);
+    send(
+        &mut client_out,
+        json!({ "jsonrpc": "2.0", "id": 2, "method": "ping" }),
+    )
+    .await;
+    assert_eq!(next(&mut lines).await["id"], 2);
+}
+
+#[tokio::test]
+async fn http_sessions_get_the_notification_on_their_stream() {
+    let server = TestServer::start().await.unwrap();
+    let http = reqwest::Client::new();
+    let url = server.url("/mcp");
+    let init = http
+        .post(&url)
+        .header("accept", "application/json")
+        .header("x-nova-context-type", "user")
+        .header("x-nova-context-id", "7")
+        .json(&json!({
+            "jsonrpc": "2.0", "id": 1, "method": "initialize",
+            "params": { "protocolVersion": "2025-06-18" }
+        }))
+        .send()
+        .await
+        .unwrap();
+    let session = init.headers()["mcp-session-id"]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let mut stream = http
+        .get(&url)
+        .header("accept", "text/event-stream")
+        .header("mcp-session-id", &session)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(stream.status(), 200);
+
+    server
+        .plugin_manager()
+        .register_plugin(&context(PluginContextType::Group, "-100"), registration())
+        .unwrap();
+    let chunk = tokio::time::timeout(Duration::from_secs(5), stream.chunk())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&chunk).contains(LIST_CHANGED));
+}
+
+async fn send(out: &mut (impl AsyncWrite + Unpin), message: Value) {
+    let line = format!("{}\n", message);
+    out.write_all(line.as_bytes()).await.unwrap();
+}
+
+async fn next(lines: &mut Lines<impl AsyncBufRead + Unpin>) -> Value {
+    let line = tokio::time::timeout(Duration::from_secs(5), lines.next_line())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    serde_json::from_str(&line).unwrap()
+}
+
+fn registration() -> PluginRegistrationRequest {
+    serde_json::from_value(json!({
+        "name": "weather",
+        "description": "Current weather",
+        "input_schema": { "type": "object" },
+        "endpoint_url": "https://weather.example.com/nova"
+    }))
+    .unwrap()
+}
+
+fn enable(plugin_id: u64, user_id: &str) -> PluginEnableRequest {
+    PluginEnableRequest {
+        context_type: PluginContextType::User,
+        context_id: user_id.to_string(),
+        plugin_id,
+        enable: true,
+        added_by: None,
+    }
+}
+
+fn context(context_type: PluginContextType, id: &str) -> RequestContext {
+    RequestContext {
+        context_type,
+        context_id: id.to_string(),
+        actor_id: None,
+    }
+}
@@ -0,0 +1,65 @@
+#![cfg(unix)]
+
+use nova_mcp::{NovaConfig, NovaServer};
+use std::path::Path;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+
+#[tokio::test]
+async fn serves_http_on_a_unix_socket() {
+    let dir = std::env::temp_dir().join(format!("nova-uds-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("nova.sock");
+    // A socket file left by an earlier run is replaced
+    drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+
+    let mut config = NovaConfig::default();
+    config.server.transport = "http".to_string();
+    config.server.unix_socket = Some(path.to_string_lossy().into_owned());
+    config.validate().unwrap();
+    let server = NovaServer::in_memory(config.clone()).unwrap();
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+
+    let health = request(&path, "GET /healthz", "").await;
+    assert!(health.starts_with("HTTP/1.1 200"), "{}", health);
+    assert!(health.contains("\r\n\r\n2\r\nok\r\n"), "{}", health);
+
+    let body = r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#;
+    let listed = request(&path, "POST /rpc", body).await;
+    assert!(listed.starts_with("HTTP/1.1 200"), "{}", listed);
+    assert!(listed.contains("get_gecko_networks"), "{}", listed);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn socket_path_must_not_be_empty() {
+    let mut config = NovaConfig::default();
+    config.server.unix_socket = Some(String::new());
+    let err = config.validate().unwrap_err();
+    assert!(err.to_string().contains("server.unix_socket"), "{}", err);
+}
+
+/// Sends one HTTP/1.1 request with `Connection: close` and returns the
+/// whole response.
+async fn request(path: &Path, request_line: &str, json_body: &str) -> String {
+    let message = format!(
+        "{} HTTP/1.1\r\nHost: nova\r\nContent-Type: application/json\r\n\
+         x-nova-context-type: user\r\nx-nova-context-id: 5\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        request_line,
+        json_body.len(),
+        json_body
+    );
+    for _ in 0..100 {
+        let Ok(mut stream) = UnixStream::connect(path).await else {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            continue;
+        };
+        stream.write_all(message.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        return response;
+    }
+    panic!("server did not listen on {}", path.display());
+}
@@ -0,0 +1,365 @@
+// Contract tests for the GeckoTerminal tools: the exact requests each tool
+// sends, and how upstream replies map to `NovaError` variants.
+use nova_mcp::tools::gecko_terminal::links;
+use nova_mcp::tools::rate_limit::UpstreamRateLimiter;
+use nova_mcp::tools::{
+    GeckoTerminalTools, GetGeckoNetworksInput, GetGeckoPoolInput, GetGeckoTokenInput,
+    GetNewPoolsInput, GetTrendingPoolsInput, NewPoolsTools, SearchPoolsInput, SearchPoolsTools,
+    TrendingPoolsTools,
+};
+use nova_mcp::NovaError;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const POOL: &str = "0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640";
+const TOKEN: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+const USER_AGENT: &str = "Nova-MCP/0.1.0";
+
+fn fixture(name: &str) -> Value {
+    let text = match name {
+        "networks" => include_str!("fixtures/geckoterminal/networks.json"),
+        "pool" => include_str!("fixtures/geckoterminal/pool.json"),
+        "token" => include_str!("fixtures/geckoterminal/token.json"),
+        "pools" => include_str!("fixtures/geckoterminal/pools.json"),
+        other => panic!("no fixture {}", other),
+    };
+    serde_json::from_str(text).unwrap()
+}
+
+/// A fixture as the tools return it, with `links` added.
+fn linked(name: &str) -> Value {
+    let mut document = fixture(name);
+    links::enrich(&mut document);
+    document
+}
+
+// A private limiter so the process-wide 30/min budget never slows the suite
+fn limiter() -> Arc<UpstreamRateLimiter> {
+    Arc::new(UpstreamRateLimiter::new(
+        "geckoterminal",
+        600,
+        Duration::ZERO,
+    ))
+}
+
+fn gecko(upstream: &MockServer) -> GeckoTerminalTools {
+    GeckoTerminalTools::with_rate_limiter(limiter()).with_base_url(upstream.uri())
+}
+
+/// A GET on `route` sent with the tools' user agent.
+fn get(route: &str) -> wiremock::MockBuilder {
+    Mock::given(method("GET"))
+        .and(path(route))
+        .and(header("user-agent", USER_AGENT))
+}
+
+async fn assert_requests(upstream: &MockServer, expected: &[&str]) {
+    let requests = upstream.received_requests().await.unwrap();
+    let urls: Vec<String> = requests
+        .iter()
+        .map(|request| {
+            let url = &request.url;
+            match url.query() {
+                Some(query) => format!("{}?{}", url.path(), query),
+                None => url.path().to_string(),
+            }
+        })
+        .collect();
+    assert_eq!(urls, expected);
+    // Public API: nothing identifying beyond the user agent
+    for request in &requests {
+        assert!(!request.headers.contains_key("authorization"));
+        assert!(!request.headers.contains_key("x-api-key"));
+    }
+}
+
+#[tokio::test]
+async fn networks_pool_and_token_requests() {
+    let upstream = MockServer::start().await;
+    for (route, body) in [
+        ("/networks".to_string(), fixture("networks")),
+        (format!("/networks/eth/pools/{}", POOL), fixture("pool")),
+        (format!("/networks/eth/tokens/{}", TOKEN), fixture("token")),
+    ] {
+        get(&route)
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .expect(1)
+            .mount(&upstream)
+            .await;
+    }
+    let tools = gecko(&upstream);
+
+    let networks = tools.get_networks(GetGeckoNetworksInput {}).await.unwrap();
+    assert_eq!(networks.networks, fixture("networks"));
+    let pool = tools
+        .get_pool(GetGeckoPoolInput {
+            network: "eth".into(),
+            address: POOL.into(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(pool.pool, linked("pool"));
+    assert_eq!(
+        pool.pool["data"]["links"],
+        json!({
+            "geckoterminal": format!("https://www.geckoterminal.com/eth/pools/{}", POOL),
+            "dexscreener": format!("https://dexscreener.com/ethereum/{}", POOL),
+            "explorer": format!("https://etherscan.io/address/{}", POOL),
+        })
+    );
+    let token = tools
+        .get_token(GetGeckoTokenInput {
+            network: "eth".into(),
+            address: TOKEN.into(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(token.token, linked("token"));
+    assert_eq!(
+        token.token["data"]["links"]["explorer"],
+        format!("https://etherscan.io/token/{}", TOKEN)
+    );
+
+    assert_requests(
+        &upstream,
+        &[
+            "/networks",
+            &format!("/networks/eth/pools/{}", POOL),
+            &format!("/networks/eth/tokens/{}", TOKEN),
+        ],
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn pool_list_requests_carry_paging_and_includes() {
+    let upstream = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/networks/eth/trending_pools"))
+        .and(query_param("page", "2"))
+        .and(query_param("duration", "1h"))
+        .and(query_param("limit", "5"))
+        .and(query_param("include", "base_token,quote_token,dex"))
+        .and(header("user-agent", USER_AGENT))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("pools")))
+        .expect(1)
+        .mount(&upstream)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/networks/base/new_pools"))
+        .and(query_param("page", "1"))
+        .and(query_param("include", "base_token,quote_token,dex"))
+        .and(header("user-agent", USER_AGENT))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("pools")))
+        .expect(1)
+        .mount(&upstream)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/search/pools"))
+        .and(query_param("query", "weth usdc"))
+        .and(query_param("page", "3"))
+        .and(query_param("network", "eth"))
+        .and(query_param("include", "base_token,quote_token,dex"))
+        .and(header("user-agent", USER_AGENT))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture("pools")))
+        .expect(1)
+        .mount(&upstream)
+        .await;
+
+    let trending = TrendingPoolsTools::with_rate_limiter(limiter())
+        .with_base_url(upstream.uri())
+        .get_trending_pools(GetTrendingPoolsInput {
+            network: "eth".into(),
+            limit: Some(5),
+            page: Some(2),
+            duration: Some("1h".into()),
+        })
+        .await
+        .unwrap();
+    assert_eq!(trending.pools, linked("pools"));
+    assert_eq!(
+        trending.pools["data"][0]["links"]["geckoterminal"],
+        format!("https://www.geckoterminal.com/eth/pools/{}", POOL)
+    );
+    // Only pools and tokens get links, not the included dex
+    assert!(trending.pools["included"][0].get("links").is_none());
+    let new_pools = NewPoolsTools::with_rate_limiter(limiter())
+        .with_base_url(upstream.uri())
+        .get_new_pools(GetNewPoolsInput {
+            network: "base".into(),
+            page: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(new_pools.pools, linked("pools"));
+    let search = SearchPoolsTools::with_rate_limiter(limiter())
+        .with_base_url(upstream.uri())
+        .search_pools(SearchPoolsInput {
+            query: "weth usdc".into(),
+            network: Some("eth".into()),
+            page: Some(3),
+        })
+        .await
+        .unwrap();
+    assert_eq!(search.pools, linked("pools"));
+
+    assert_requests(
+        &upstream,
+        &[
+            "/networks/eth/trending_pools?page=2&duration=1h&limit=5&include=base_token,quote_token,dex",
+            "/networks/base/new_pools?page=1&include=base_token,quote_token,dex",
+            "/search/pools?query=weth%20usdc&page=3&network=eth&include=base_token,quote_token,dex",
+        ],
+    )
+    .await;
+}
+
+#[test]
+fn links_follow_the_network() {
+    let solana = "So11111111111111111111111111111111111111112";
+    assert_eq!(
+        links::links("solana", links::ResourceKind::Token, solana),
+        json!({
+            "geckoterminal": format!("https://www.geckoterminal.com/solana/tokens/{}", solana),
+            "dexscreener": format!("https://dexscreener.com/solana/{}", solana),
+            "explorer": format!("https://solscan.io/token/{}", solana),
+        })
+    );
+    assert_eq!(
+        links::tx_url("base", "0xabc").as_deref(),
+        Some("https://basescan.org/tx/0xabc")
+    );
+
+    // Networks without metadata still get their GeckoTerminal page
+    assert_eq!(
+        links::links("some-chain", links::ResourceKind::Pool, "abc"),
+        json!({ "geckoterminal": "https://www.geckoterminal.com/some-chain/pools/abc" })
+    );
+    assert_eq!(links::tx_url("some-chain", "abc"), None);
+}
+
+#[tokio::test]
+async fn invalid_arguments_never_reach_upstream() {
+    let upstream = MockServer::start().await;
+    let tools = gecko(&upstream);
+    let err = tools
+        .get_pool(GetGeckoPoolInput {
+            network: "eth".into(),
+            address: "0x1234".into(),
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, NovaError::InvalidAddress { .. }), "{:?}", err);
+    let err = TrendingPoolsTools::with_rate_limiter(limiter())
+        .with_base_url(upstream.uri())
+        .get_trending_pools(GetTrendingPoolsInput {
+            network: "eth".into(),
+            limit: Some(50),
+            page: None,
+            duration: None,
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(err, NovaError::ApiError(_)), "{:?}", err);
+    assert_requests(&upstream, &[]).await;
+}
+
+#[tokio::test]
+async fn upstream_failures_map_to_error_variants() {
+    let upstream = MockServer::start().await;
+    let not_found = json!({ "errors": [{ "status": "404", "title": "Not Found" }] });
+    let bad_network = json!({ "errors": [{ "status": "404", "title": "Network not found" }] });
+    let bad_address = json!({ "errors": [{ "status": "422", "detail": "Invalid address" }] });
+    let token_route = format!("/networks/eth/tokens/{}", TOKEN);
+    // Cached as missing, so the second lookup does not reach upstream
+    get(&token_route)
+        .respond_with(ResponseTemplate::new(404).set_body_json(not_found))
+        .expect(1)
+        .mount(&upstream)
+        .await;
+    get(&format!("/networks/eth/pools/{}", POOL))
+        .respond_with(ResponseTemplate::new(404).set_body_json(bad_network))
+        .mount(&upstream)
+        .await;
+    get(&format!("/networks/base/pools/{}", POOL))
+        .respond_with(ResponseTemplate::new(422).set_body_json(bad_address))
+        .mount(&upstream)
+        .await;
+    get(&format!("/networks/base/tokens/{}", TOKEN))
+        .respond_with(ResponseTemplate::new(200).set_body_string("<html>maintenance</html>"))
+        .mount(&upstream)
+        .await;
+    get("/networks")
+        .respond_with(ResponseTemplate::new(503).set_body_string("upstream down"))
+        .mount(&upstream)
+        .await;
+    let tools = gecko(&upstream);
+    let token = |network: &str| GetGeckoTokenInput {
+        network: network.into(),
+        address: TOKEN.into(),
+    };
+    let pool = |network: &str| GetGeckoPoolInput {
+        network: network.into(),
+        address: POOL.into(),
+    };
+
+    for _ in 0..2 {
+        let err = tools.get_token(token("eth")).await.unwrap_err();
+        assert!(matches!(err, NovaError::TokenNotFound { .. }), "{:?}", err);
+    }
+    // A 404 about the network is not a missing pool
+    let err = tools.get_pool(pool("eth")).await.unwrap_err();
+    assert!(
+        matches!(&err, NovaError::ApiError(message) if message.contains("404")),
+        "{:?}",
+        err
+    );
+    let err = tools.get_pool(pool("base")).await.unwrap_err();
+    assert!(matches!(err, NovaError::InvalidAddress { .. }), "{:?}", err);
+    let err = tools.get_token(token("base")).await.unwrap_err();
+    assert!(matches!(err, NovaError::NetworkError(_)), "{:?}", err);
+    let err = tools
+        .get_networks(GetGeckoNetworksInput {})
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, NovaError::ApiError(message) if message.contains("503")),
+        "{:?}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn upstream_429_becomes_rate_limit_exceeded() {
+    let upstream = MockServer::start().await;
+    get("/networks/eth/new_pools")
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "7"))
+        .expect(1)
+        .mount(&upstream)
+        .await;
+    let tools = NewPoolsTools::with_rate_limiter(limiter()).with_base_url(upstream.uri());
+    let input = || GetNewPoolsInput {
+        network: "eth".into(),
+        page: None,
+    };
+
+    let err = tools.get_new_pools(input()).await.unwrap_err();
+    match err {
+        NovaError::RateLimitExceeded {
+            api,
+            retry_after_secs,
+        } => {
+            assert_eq!(api, "geckoterminal");
+            assert_eq!(retry_after_secs, Some(7));
+        }
+        other => panic!("expected rate limit error, got {:?}", other),
+    }
+    // The limiter holds further calls back instead of hitting upstream again
+    assert!(matches!(
+        tools.get_new_pools(input()).await,
+        Err(NovaError::RateLimitExceeded { .. })
+    ));
+}
@@ -0,0 +1,170 @@
+use axum::{http::StatusCode, routing::post, Router};
+use nova_mcp::config::{AdminConfig, NovaConfig, PluginsConfig};
+use nova_mcp::plugins::{
+    EgressPolicy, PluginContextType, PluginManager, PluginRegistrationRequest, RequestContext,
+};
+use nova_mcp::tools::upstream_health::{UpstreamHealth, UpstreamState};
+use nova_mcp::NovaServer;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn state_follows_the_recent_window() {
+    let health = UpstreamHealth::new(4);
+    health.register("geckoterminal");
+    assert_eq!(health.snapshot()[0].state, UpstreamState::Unknown);
+
+    for ms in [10, 20, 30, 40] {
+        health.record("geckoterminal", Duration::from_millis(ms), None);
+    }
+    let status = &health.snapshot()[0];
+    assert_eq!(status.state, UpstreamState::Healthy);
+    let latency = status.latency.as_ref().unwrap();
+    assert_eq!(
+        (latency.avg_ms, latency.p50_ms, latency.max_ms),
+        (25, 20, 40)
+    );
+
+    health.record(
+        "geckoterminal",
+        Duration::from_millis(50),
+        Some("HTTP 502".into()),
+    );
+    let status = &health.snapshot()[0];
+    assert_eq!(status.state, UpstreamState::Degraded);
+    // The ring buffer keeps the last 4 calls; totals keep counting
+    assert_eq!((status.samples, status.errors), (4, 1));
+    assert_eq!((status.total_calls, status.total_errors), (5, 1));
+    assert_eq!(status.last_error.as_deref(), Some("HTTP 502"));
+
+    for _ in 0..2 {
+        health.record(
+            "geckoterminal",
+            Duration::from_millis(50),
+            Some("timeout".into()),
+        );
+    }
+    assert_eq!(health.snapshot()[0].state, UpstreamState::Down);
+    health.record("geckoterminal", Duration::from_millis(5), None);
+    assert_eq!(health.snapshot()[0].state, UpstreamState::Degraded);
+}
+
+#[tokio::test]
+async fn plugin_failures_show_up_in_admin_upstreams_and_readyz() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}/flaky", listener.local_addr().unwrap());
+    let app = Router::new().route(
+        "/flaky",
+        post(|| async { (StatusCode::SERVICE_UNAVAILABLE, "maintenance") }),
+    );
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut config = NovaConfig {
+        admin: AdminConfig {
+            tokens: vec!["ops-token".into()],
+            ..AdminConfig::default()
+        },
+        ..NovaConfig::default()
+    };
+    config.server.port = port;
+    let server = test_server(config.clone());
+    let owner = RequestContext {
+        context_type: PluginContextType::User,
+        context_id: "4".to_string(),
+        actor_id: None,
+    };
+    let metadata = server
+        .plugin_manager()
+        .register_plugin(&owner, registration(&endpoint))
+        .unwrap();
+    for _ in 0..3 {
+        let result = server
+            .plugin_manager()
+            .invoke_plugin(&metadata, &owner, json!({}))
+            .await;
+        assert!(result.is_err());
+    }
+
+    tokio::spawn(nova_mcp::http::run_http_server(server, config));
+    let base = format!("http://127.0.0.1:{}", port);
+    let client = reqwest::Client::new();
+    let mut ready = None;
+    for _ in 0..50 {
+        if let Ok(response) = client.get(format!("{}/readyz", base)).send().await {
+            ready = Some(response);
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    let ready: Value = ready.expect("server did not start").json().await.unwrap();
+    let plugin = format!("plugin:{}", metadata.fq_name);
+    assert_eq!(ready["status"], "ready");
+    assert_eq!(ready["upstreams"]["geckoterminal"], "unknown");
+    assert_eq!(ready["upstreams"][&plugin], "down");
+
+    let unauthorized = client
+        .get(format!("{}/admin/upstreams", base))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(unauthorized.status(), 401);
+    let upstreams: Vec<Value> = client
+        .get(format!("{}/admin/upstreams", base))
+        .header("x-admin-token", "ops-token")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let flaky = upstreams
+        .iter()
+        .find(|upstream| upstream["name"] == plugin.as_str())
+        .unwrap();
+    assert_eq!(flaky["errors"], 3);
+    assert_eq!(flaky["error_rate"], 1.0);
+    assert_eq!(flaky["last_error"], "HTTP 503");
+}
+
+fn registration(endpoint: &str) -> PluginRegistrationRequest {
+    PluginRegistrationRequest {
+        name: "flaky".to_string(),
+        description: "test".to_string(),
+        owner_id: None,
+        input_schema: json!({ "type": "object" }),
+        output_schema: None,
+        endpoint_url: endpoint.to_string(),
+        script: None,
+        version: 1,
+        trust_level: Default::default(),
+        client_certificate: None,
+        credentials: None,
+        redact: Vec::new(),
+        request_template: None,
+        listing: None,
+        allowed_contexts: Vec::new(),
+        tags: Vec::new(),
+        coerce_arguments: false,
+    }
+}
+
+fn test_server(config: NovaConfig) -> NovaServer {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let metadata_tree = db.open_tree("plugin_metadata").unwrap();
+    let user_tree = db.open_tree("user_plugins").unwrap();
+    let group_tree = db.open_tree("group_plugins").unwrap();
+    let plugin_manager = PluginManager::new(metadata_tree, user_tree, group_tree)
+        .expect("init plugin manager")
+        .with_egress_policy(EgressPolicy::new(&PluginsConfig {
+            allowed_schemes: vec!["http".into()],
+            allow_private_networks: true,
+            ..PluginsConfig::default()
+        }));
+    NovaServer::new(config, Arc::new(plugin_manager))
+}
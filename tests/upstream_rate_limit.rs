@@ -0,0 +1,39 @@
+use nova_mcp::tools::rate_limit::{parse_retry_after, UpstreamRateLimiter};
+use nova_mcp::NovaError;
+use std::time::Duration;
+
+#[tokio::test]
+async fn exhausted_bucket_fails_fast_with_hint() {
+    let limiter = UpstreamRateLimiter::new("geckoterminal", 2, Duration::ZERO);
+    assert!(limiter.acquire().await.is_ok());
+    assert!(limiter.acquire().await.is_ok());
+    match limiter.acquire().await {
+        Err(NovaError::RateLimitExceeded {
+            api,
+            retry_after_secs,
+        }) => {
+            assert_eq!(api, "geckoterminal");
+            assert!(retry_after_secs.unwrap() >= 1);
+        }
+        other => panic!("expected rate limit error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn upstream_429_blocks_bucket_for_retry_after() {
+    let limiter = UpstreamRateLimiter::new("geckoterminal", 30, Duration::ZERO);
+    let err = limiter.defer(Some(Duration::from_secs(12))).await;
+    assert_eq!(err.retry_after_secs(), Some(12));
+    let err = limiter.acquire().await.unwrap_err();
+    assert!(err.retry_after_secs().unwrap() > 10);
+}
+
+#[test]
+fn parses_retry_after_seconds_and_dates() {
+    assert_eq!(parse_retry_after("7"), Some(Duration::from_secs(7)));
+    assert_eq!(
+        parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+        Some(Duration::ZERO)
+    );
+    assert_eq!(parse_retry_after("soon"), None);
+}
@@ -0,0 +1,227 @@
+// watch_whales / unwatch_whales and the poller over a mocked GeckoTerminal
+// trades endpoint.
+use nova_mcp::clock::{ManualClock, SharedClock};
+use nova_mcp::config::WhalesConfig;
+use nova_mcp::events::{EventBus, EventKind};
+use nova_mcp::plugins::{PluginContextType, RequestContext};
+use nova_mcp::tools::rate_limit::UpstreamRateLimiter;
+use nova_mcp::tools::{UnwatchWhalesInput, WatchWhalesInput, WhaleTools};
+use nova_mcp::NovaError;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const POOL: &str = "0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640";
+const TRADES: &str = "/networks/eth/pools/0x88e6a0c2ddd26feeb64f039a2c41296fcb3f5640/trades";
+// 2026-01-01T00:00:00Z
+const START: i64 = 1_767_225_600;
+
+fn trade(id: &str, volume: &str, at: &str, kind: &str) -> Value {
+    json!({
+        "id": format!("eth_{}", id),
+        "type": "trade",
+        "attributes": {
+            "tx_hash": format!("0x{}", id),
+            "tx_from_address": "0x1111111111111111111111111111111111111111",
+            "kind": kind,
+            "volume_in_usd": volume,
+            "block_timestamp": at
+        }
+    })
+}
+
+async fn trades(upstream: &MockServer, min_volume: &str, data: Vec<Value>) {
+    upstream.reset().await;
+    Mock::given(method("GET"))
+        .and(path(TRADES))
+        .and(query_param("trade_volume_in_usd_greater_than", min_volume))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "data": data })))
+        .mount(upstream)
+        .await;
+}
+
+fn context(id: &str) -> RequestContext {
+    RequestContext {
+        context_type: PluginContextType::User,
+        context_id: id.to_string(),
+        actor_id: None,
+    }
+}
+
+fn watch(min_volume_usd: f64) -> WatchWhalesInput {
+    WatchWhalesInput {
+        network: "eth".to_string(),
+        pool: POOL.to_uppercase().replacen("0X", "0x", 1),
+        min_volume_usd,
+    }
+}
+
+fn tools(upstream: &MockServer, events: &Arc<EventBus>, clock: &ManualClock) -> WhaleTools {
+    let limiter = Arc::new(UpstreamRateLimiter::new(
+        "geckoterminal",
+        600,
+        Duration::ZERO,
+    ));
+    WhaleTools::with_rate_limiter(limiter)
+        .with_base_url(upstream.uri())
+        .with_events(events.clone())
+        .with_clock(SharedClock::new(clock.clone()))
+        .with_config(&WhalesConfig {
+            max_watches_per_context: 1,
+            ..WhalesConfig::default()
+        })
+}
+
+#[tokio::test]
+async fn watching_lists_recent_trades_and_enforces_limits() {
+    let upstream = MockServer::start().await;
+    let events = Arc::new(EventBus::new());
+    let tools = tools(&upstream, &events, &ManualClock::at(START));
+    trades(
+        &upstream,
+        "50000",
+        vec![
+            trade("a1", "80000", "2025-12-31T22:00:00Z", "buy"),
+            trade("a2", "120000", "2025-12-31T23:00:00Z", "sell"),
+            // Below the threshold despite the filter
+            trade("a3", "20000", "2025-12-31T23:30:00Z", "buy"),
+        ],
+    )
+    .await;
+
+    let output = tools.watch(&context("1"), watch(50_000.0)).await.unwrap();
+    assert!(output.created);
+    assert_eq!(output.watch.id, format!("eth_{}", POOL));
+    assert_eq!(output.watch.context, "user:1");
+    assert_eq!(output.watch.last_trade_at, START - 3600);
+    let hashes: Vec<_> = output
+        .recent_trades
+        .iter()
+        .map(|t| t.tx_hash.as_str())
+        .collect();
+    assert_eq!(hashes, ["0xa2", "0xa1"]);
+    assert_eq!(output.recent_trades[0].kind.as_deref(), Some("sell"));
+    assert_eq!(
+        output.recent_trades[0].tx_url.as_deref(),
+        Some("https://etherscan.io/tx/0xa2")
+    );
+    assert!(output.sightings.is_empty());
+
+    trades(&upstream, "100000", vec![]).await;
+    let updated = tools.watch(&context("1"), watch(100_000.0)).await.unwrap();
+    assert!(!updated.created);
+    assert_eq!(updated.watch.min_volume_usd, 100_000.0);
+    assert_eq!(updated.watch.created_at, output.watch.created_at);
+
+    let too_small = tools.watch(&context("2"), watch(5.0)).await.unwrap_err();
+    assert!(matches!(too_small, NovaError::ValidationError { .. }));
+    let mut other = watch(100_000.0);
+    other.pool = "0x11b815efb8f581194ae79006d24e0d814b7697f6".to_string();
+    let too_many = tools.watch(&context("1"), other).await.unwrap_err();
+    assert!(too_many.to_string().contains("at most 1"), "{}", too_many);
+
+    let removed = tools
+        .unwatch(
+            &context("1"),
+            UnwatchWhalesInput {
+                network: "eth".to_string(),
+                pool: POOL.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(removed.watch.unwrap().id, format!("eth_{}", POOL));
+    assert!(tools.watches().list().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn polling_records_and_announces_each_new_trade_once() {
+    let upstream = MockServer::start().await;
+    let events = Arc::new(EventBus::new());
+    let mut stream = events.subscribe();
+    let tools = tools(&upstream, &events, &ManualClock::at(START));
+    trades(&upstream, "50000", vec![]).await;
+    tools.watch(&context("1"), watch(50_000.0)).await.unwrap();
+    trades(&upstream, "200000", vec![]).await;
+    tools.watch(&context("2"), watch(200_000.0)).await.unwrap();
+
+    // One call per pool, at the smallest threshold watching it
+    let new_trades = vec![
+        trade("b2", "250000", "2026-01-01T00:05:00Z", "buy"),
+        trade("b1", "60000", "2026-01-01T00:02:00Z", "sell"),
+        trade("old", "900000", "2025-12-31T12:00:00Z", "buy"),
+    ];
+    trades(&upstream, "50000", new_trades.clone()).await;
+    assert_eq!(tools.poll().await.unwrap(), 3);
+
+    let mut announced = Vec::new();
+    while let Ok(event) = stream.try_recv() {
+        if let EventKind::WhaleTrade {
+            context,
+            tx_hash,
+            volume_usd,
+            tx_url,
+            ..
+        } = event.kind
+        {
+            assert!(tx_url.is_some());
+            announced.push((context, tx_hash, volume_usd));
+        }
+    }
+    assert_eq!(
+        announced,
+        [
+            ("user:1".to_string(), "0xb1".to_string(), 60_000.0),
+            ("user:1".to_string(), "0xb2".to_string(), 250_000.0),
+            ("user:2".to_string(), "0xb2".to_string(), 250_000.0),
+        ]
+    );
+
+    // The same trades again are neither recorded nor announced twice
+    assert_eq!(tools.poll().await.unwrap(), 0);
+    let again = tools.watch(&context("1"), watch(50_000.0)).await.unwrap();
+    let sightings: Vec<_> = again.sightings.iter().map(|t| t.tx_hash.as_str()).collect();
+    assert_eq!(sightings, ["0xb2", "0xb1"]);
+    assert_eq!(again.watch.last_trade_at, START + 300);
+
+    let removed = tools.watches().remove_context(&context("1")).unwrap();
+    assert_eq!(removed, 1);
+    assert_eq!(tools.watches().list().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn unknown_pools_are_refused_and_failing_polls_are_skipped() {
+    let upstream = MockServer::start().await;
+    let events = Arc::new(EventBus::new());
+    let clock = ManualClock::at(START);
+    let tools = tools(&upstream, &events, &clock);
+    Mock::given(method("GET"))
+        .and(path(TRADES))
+        .respond_with(
+            ResponseTemplate::new(404)
+                .set_body_json(json!({ "errors": [{ "status": "404", "title": "Not Found" }] })),
+        )
+        .mount(&upstream)
+        .await;
+    let err = tools
+        .watch(&context("1"), watch(50_000.0))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, NovaError::PoolNotFound { .. }), "{:?}", err);
+    assert!(tools.watches().list().unwrap().is_empty());
+
+    trades(&upstream, "50000", vec![]).await;
+    tools.watch(&context("1"), watch(50_000.0)).await.unwrap();
+    upstream.reset().await;
+    Mock::given(method("GET"))
+        .and(path(TRADES))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&upstream)
+        .await;
+    clock.advance(Duration::from_secs(60));
+    assert_eq!(tools.poll().await.unwrap(), 0);
+    let watch = &tools.watches().list().unwrap()[0];
+    assert_eq!(watch.last_checked_at, Some(START + 60));
+    assert_eq!(watch.last_trade_at, START);
+}